@@ -0,0 +1,305 @@
+//! Chain configuration: chain id and the set of rules (hardfork and EIPs)
+//! the circuit inputs are generated for.
+
+use eth_types::evm_types::{
+    GasCost, OpcodeId, MAX_REFUND_QUOTIENT_OF_GAS_USED, MAX_REFUND_QUOTIENT_OF_GAS_USED_PRE_EIP3529,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+
+/// Mainnet hardforks supported by the circuit input builder, in activation
+/// order.
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize,
+)]
+pub enum Hardfork {
+    /// Istanbul (and Muir Glacier), before the access lists
+    Istanbul,
+    /// Berlin
+    Berlin,
+    /// London
+    London,
+    /// Merge (Paris)
+    Merge,
+    /// Shanghai
+    #[default]
+    Shanghai,
+    /// Cancun
+    Cancun,
+}
+
+/// EIPs changing the behaviour of the EVM that the circuit input builder is
+/// aware of.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum Eip {
+    /// Gas cost increases for state access opcodes, with warm and cold
+    /// accesses (Berlin)
+    Eip2929,
+    /// BASEFEE opcode (London)
+    Eip3198,
+    /// Reduction in refunds (London)
+    Eip3529,
+    /// Reject new contract code starting with the 0xEF byte (London)
+    Eip3541,
+    /// DIFFICULTY opcode returns PREVRANDAO (Merge)
+    Eip4399,
+    /// Warm COINBASE (Shanghai)
+    Eip3651,
+    /// PUSH0 opcode (Shanghai)
+    Eip3855,
+    /// Limit and meter initcode (Shanghai)
+    Eip3860,
+    /// Transient storage opcodes (Cancun)
+    Eip1153,
+    /// MCOPY opcode (Cancun)
+    Eip5656,
+}
+
+impl Eip {
+    /// Returns the EIP introducing `opcode`, or `None` for the opcodes of the
+    /// Istanbul EVM.  The opcodes unknown to the [`OpcodeId`] enum are
+    /// matched by their byte.
+    pub fn introducing(opcode: OpcodeId) -> Option<Self> {
+        match opcode {
+            OpcodeId::BASEFEE => Some(Self::Eip3198),
+            // PUSH0
+            OpcodeId::INVALID(0x5f) => Some(Self::Eip3855),
+            // TLOAD and TSTORE
+            OpcodeId::INVALID(0x5c | 0x5d) => Some(Self::Eip1153),
+            // MCOPY
+            OpcodeId::INVALID(0x5e) => Some(Self::Eip5656),
+            _ => None,
+        }
+    }
+}
+
+impl Hardfork {
+    /// EIPs activated by this hardfork only.
+    fn activated_eips(&self) -> &'static [Eip] {
+        match self {
+            Self::Istanbul => &[],
+            Self::Berlin => &[Eip::Eip2929],
+            Self::London => &[Eip::Eip3198, Eip::Eip3529, Eip::Eip3541],
+            Self::Merge => &[Eip::Eip4399],
+            Self::Shanghai => &[Eip::Eip3651, Eip::Eip3855, Eip::Eip3860],
+            Self::Cancun => &[Eip::Eip1153, Eip::Eip5656],
+        }
+    }
+
+    /// All the EIPs enabled at this hardfork, including the ones activated by
+    /// the previous hardforks.
+    pub fn eips(&self) -> BTreeSet<Eip> {
+        [
+            Self::Istanbul,
+            Self::Berlin,
+            Self::London,
+            Self::Merge,
+            Self::Shanghai,
+            Self::Cancun,
+        ]
+        .into_iter()
+        .filter(|hardfork| hardfork <= self)
+        .flat_map(|hardfork| hardfork.activated_eips().iter().copied())
+        .collect()
+    }
+}
+
+/// Configuration of the chain the blocks belong to.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChainConfig {
+    /// Chain id
+    pub chain_id: u64,
+    /// Hardfork the rules are taken from
+    pub hardfork: Hardfork,
+    /// Enabled EIPs.  Defaults to the EIPs of `hardfork`, but can be
+    /// customized to follow the rules of a chain that doesn't track mainnet.
+    pub eips: BTreeSet<Eip>,
+}
+
+impl Default for ChainConfig {
+    fn default() -> Self {
+        Self::new(1, Hardfork::default())
+    }
+}
+
+impl ChainConfig {
+    /// Create a new ChainConfig with the EIPs enabled at `hardfork`.
+    pub fn new(chain_id: u64, hardfork: Hardfork) -> Self {
+        Self {
+            chain_id,
+            hardfork,
+            eips: hardfork.eips(),
+        }
+    }
+
+    /// Enable `eip` on top of the ones of the hardfork.
+    pub fn with_eip(mut self, eip: Eip) -> Self {
+        self.eips.insert(eip);
+        self
+    }
+
+    /// Disable `eip`.
+    pub fn without_eip(mut self, eip: Eip) -> Self {
+        self.eips.remove(&eip);
+        self
+    }
+
+    /// Return whether `eip` is enabled.
+    pub fn is_enabled(&self, eip: Eip) -> bool {
+        self.eips.contains(&eip)
+    }
+
+    /// Return whether `opcode` is enabled, that is the EIP introducing it (if
+    /// any) is enabled.  Note that the opcodes unknown to the [`OpcodeId`]
+    /// enum (like PUSH0) are not supported by the circuits even when enabled.
+    pub fn is_opcode_enabled(&self, opcode: OpcodeId) -> bool {
+        Eip::introducing(opcode).map_or(true, |eip| self.is_enabled(eip))
+    }
+
+    /// Return whether the gas schedule of the state accesses distinguishes
+    /// warm and cold accesses (EIP-2929).
+    pub fn is_access_list_gas(&self) -> bool {
+        self.is_enabled(Eip::Eip2929)
+    }
+
+    /// Gas cost of an SLOAD of a slot which is warm or not.
+    pub fn sload_gas(&self, is_warm: bool) -> u64 {
+        match (self.is_access_list_gas(), is_warm) {
+            (false, _) => GasCost::ISTANBUL_SLOAD,
+            (true, true) => GasCost::WARM_ACCESS,
+            (true, false) => GasCost::COLD_SLOAD,
+        }
+    }
+
+    /// Gas cost of the access to an account which is warm or not, by BALANCE,
+    /// EXTCODESIZE, EXTCODECOPY, EXTCODEHASH and the CALL family.
+    pub fn account_access_gas(&self, is_warm: bool) -> u64 {
+        match (self.is_access_list_gas(), is_warm) {
+            (false, _) => GasCost::ISTANBUL_ACCOUNT_ACCESS,
+            (true, true) => GasCost::WARM_ACCESS,
+            (true, false) => GasCost::COLD_ACCOUNT_ACCESS,
+        }
+    }
+
+    /// Gas cost of an SSTORE changing the original value of a non-zero slot,
+    /// excluding the surcharge of a cold slot.
+    pub fn sstore_reset_gas(&self) -> u64 {
+        if self.is_access_list_gas() {
+            GasCost::SSTORE_RESET
+        } else {
+            GasCost::ISTANBUL_SSTORE_RESET
+        }
+    }
+
+    /// Refund of an SSTORE clearing a non-zero slot (EIP-3529).
+    pub fn sstore_clears_schedule(&self) -> u64 {
+        if self.is_enabled(Eip::Eip3529) {
+            GasCost::SSTORE_CLEARS_SCHEDULE
+        } else {
+            GasCost::SSTORE_CLEARS_SCHEDULE_PRE_EIP3529
+        }
+    }
+
+    /// Maximum refund quotient of gas used (EIP-3529).
+    pub fn max_refund_quotient_of_gas_used(&self) -> u64 {
+        if self.is_enabled(Eip::Eip3529) {
+            MAX_REFUND_QUOTIENT_OF_GAS_USED as u64
+        } else {
+            MAX_REFUND_QUOTIENT_OF_GAS_USED_PRE_EIP3529 as u64
+        }
+    }
+
+    /// Return whether the coinbase is warm at the beginning of a transaction
+    /// (EIP-3651).
+    pub fn is_coinbase_warm(&self) -> bool {
+        self.is_enabled(Eip::Eip3651)
+    }
+
+    /// Return whether the DIFFICULTY opcode returns the PREVRANDAO of the
+    /// block instead of its difficulty (EIP-4399).
+    pub fn is_difficulty_prevrandao(&self) -> bool {
+        self.is_enabled(Eip::Eip4399)
+    }
+
+    /// Return whether the init code of a creation transaction is metered
+    /// (EIP-3860).
+    pub fn is_init_code_metered(&self) -> bool {
+        self.is_enabled(Eip::Eip3860)
+    }
+}
+
+#[cfg(test)]
+mod chain_config_tests {
+    use super::*;
+
+    #[test]
+    fn hardfork_eips_are_cumulative() {
+        assert!(Hardfork::Istanbul.eips().is_empty());
+        assert_eq!(Hardfork::Berlin.eips(), BTreeSet::from_iter([Eip::Eip2929]));
+        let london = ChainConfig::new(1, Hardfork::London);
+        assert!(london.is_enabled(Eip::Eip3529));
+        assert!(!london.is_coinbase_warm());
+        assert!(!london.is_init_code_metered());
+        assert!(!london.is_difficulty_prevrandao());
+        assert!(ChainConfig::new(1, Hardfork::Merge).is_difficulty_prevrandao());
+
+        let shanghai = ChainConfig::new(1, Hardfork::Shanghai);
+        assert!(shanghai.is_enabled(Eip::Eip3198));
+        assert!(shanghai.is_coinbase_warm());
+        assert!(shanghai.is_init_code_metered());
+        assert!(shanghai.is_difficulty_prevrandao());
+        assert!(!shanghai.is_enabled(Eip::Eip1153));
+        assert!(Hardfork::Cancun.eips().is_superset(&shanghai.eips));
+    }
+
+    #[test]
+    fn gas_schedule() {
+        let istanbul = ChainConfig::new(1, Hardfork::Istanbul);
+        assert!(!istanbul.is_access_list_gas());
+        assert_eq!(istanbul.sload_gas(true), 800);
+        assert_eq!(istanbul.sload_gas(false), 800);
+        assert_eq!(istanbul.account_access_gas(false), 700);
+        assert_eq!(istanbul.sstore_reset_gas(), 5000);
+        assert_eq!(istanbul.sstore_clears_schedule(), 15000);
+
+        let berlin = ChainConfig::new(1, Hardfork::Berlin);
+        assert!(berlin.is_access_list_gas());
+        assert_eq!(berlin.sload_gas(true), 100);
+        assert_eq!(berlin.sload_gas(false), 2100);
+        assert_eq!(berlin.account_access_gas(true), 100);
+        assert_eq!(berlin.account_access_gas(false), 2600);
+        assert_eq!(berlin.sstore_reset_gas(), 2900);
+        assert_eq!(berlin.sstore_clears_schedule(), 15000);
+        assert_eq!(ChainConfig::default().sstore_clears_schedule(), 4800);
+        assert!(ChainConfig::default().is_access_list_gas());
+    }
+
+    #[test]
+    fn custom_eips() {
+        let config = ChainConfig::new(1, Hardfork::Berlin).with_eip(Eip::Eip3198);
+        assert!(config.is_opcode_enabled(OpcodeId::BASEFEE));
+        assert!(!ChainConfig::new(1, Hardfork::Berlin).is_opcode_enabled(OpcodeId::BASEFEE));
+        assert_eq!(config.max_refund_quotient_of_gas_used(), 2);
+
+        let config = ChainConfig::default().without_eip(Eip::Eip3529);
+        assert_eq!(config.max_refund_quotient_of_gas_used(), 2);
+        assert_eq!(
+            ChainConfig::default().max_refund_quotient_of_gas_used(),
+            MAX_REFUND_QUOTIENT_OF_GAS_USED as u64
+        );
+    }
+
+    #[test]
+    fn opcodes_of_eips() {
+        let shanghai = ChainConfig::new(1, Hardfork::Shanghai);
+        assert!(shanghai.is_opcode_enabled(OpcodeId::BASEFEE));
+        assert!(shanghai.is_opcode_enabled(OpcodeId::INVALID(0x5f)));
+        assert!(!shanghai.is_opcode_enabled(OpcodeId::INVALID(0x5c)));
+        assert!(!shanghai.is_opcode_enabled(OpcodeId::INVALID(0x5e)));
+        let cancun = ChainConfig::new(1, Hardfork::Cancun);
+        for byte in 0x5c..=0x5f {
+            assert!(cancun.is_opcode_enabled(OpcodeId::INVALID(byte)));
+        }
+        assert!(ChainConfig::new(1, Hardfork::Istanbul).is_opcode_enabled(OpcodeId::INVALID(0xfe)));
+    }
+}
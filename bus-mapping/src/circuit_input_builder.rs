@@ -4,41 +4,55 @@
 mod access;
 mod block;
 mod call;
+mod chunk;
 mod execution;
+mod fee;
 mod input_state_ref;
 #[cfg(test)]
 mod tracer_tests;
 mod transaction;
 
-use self::access::gen_state_access_trace;
+use self::{
+    access::gen_state_access_trace,
+    chunk::chunk_tx_ranges,
+    transaction::{calls_is_success, missing_out_of_gas_step},
+};
+#[cfg(feature = "rpc")]
+use crate::rpc::GethClient;
 use crate::{
     error::Error,
     evm::opcodes::{gen_associated_ops, gen_associated_steps},
     operation::{CallContextField, Operation, RWCounter, StartOp, RW},
-    rpc::GethClient,
     state_db::{self, CodeDB, StateDB},
 };
 pub use access::{Access, AccessSet, AccessValue, CodeSource};
 pub use block::{Block, BlockContext};
 pub use call::{Call, CallContext, CallKind};
+pub use chunk::{Chunk, ChunkBoundary};
 use core::fmt::Debug;
+#[cfg(feature = "rpc")]
+use eth_types::ToWord;
 use eth_types::{
     self, geth_types,
     sign_types::{pk_bytes_le, pk_bytes_swap_endianness, SignData},
-    Address, GethExecStep, GethExecTrace, ToWord, Word,
+    Address, GethExecStep, GethExecTrace, Word,
 };
+#[cfg(feature = "rpc")]
 use ethers_providers::JsonRpcClient;
 pub use execution::{
     CopyDataType, CopyEvent, CopyStep, ExecState, ExecStep, ExpEvent, ExpStep, NumberOrHash,
 };
+pub(crate) use fee::{fee_policy_begin_tx, fee_policy_end_tx};
+pub use fee::{EthereumFeePolicy, FeePolicy, L1DataFeePolicy, L1_FEE_PRECISION};
 pub use input_state_ref::CircuitInputStateRef;
 use itertools::Itertools;
 use log::warn;
-use std::{collections::HashMap, ops::Deref};
+use serde::{Deserialize, Serialize};
+use std::{borrow::Cow, collections::HashMap, ops::Deref};
 pub use transaction::{Transaction, TransactionContext};
 
 /// Circuit Setup Parameters
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub struct FixedCParams {
     /// Maximum number of rw operations in the state circuit (RwTable length /
     /// nummber of rows). This must be at least the number of rw operations
@@ -68,6 +82,22 @@ pub struct FixedCParams {
     /// calculated, so the same circuit will not be able to prove different
     /// witnesses.
     pub max_keccak_rows: usize,
+    /// When set, the first tx of the block must be the anchor tx of an L2
+    /// which requires one at the start of every block.
+    #[serde(default)]
+    pub anchor_tx: Option<AnchorTxParams>,
+}
+
+/// Expected fields of the anchor tx, the system tx that some L2s require as
+/// the first tx of every block.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AnchorTxParams {
+    /// Sender of the anchor tx
+    pub caller: Address,
+    /// Contract called by the anchor tx
+    pub callee: Address,
+    /// First bytes of the calldata: the selector of the called function
+    pub calldata_prefix: [u8; 4],
 }
 
 /// Unset Circuits Parameters, computed dynamically together with circuit witness generation.
@@ -94,6 +124,7 @@ impl Default for FixedCParams {
             max_bytecode: 512,
             max_evm_rows: 0,
             max_keccak_rows: 0,
+            anchor_tx: None,
         }
     }
 }
@@ -224,8 +255,32 @@ impl<'a, C: CircuitsParams> CircuitInputBuilder<C> {
         is_last_tx: bool,
         tx_index: u64,
     ) -> Result<(), Error> {
+        let _span = tracing::debug_span!("tx", tx_index, hash = ?eth_tx.hash).entered();
         let mut tx = self.new_tx(tx_index, eth_tx, !geth_trace.failed)?;
         let mut tx_ctx = TransactionContext::new(eth_tx, geth_trace, is_last_tx)?;
+        let memory_enabled = geth_trace
+            .struct_logs
+            .iter()
+            .any(|geth_step| !geth_step.memory.is_empty());
+        // Complete the trace with the step at which the root call runs out of
+        // gas, if the tracer didn't log it.
+        let struct_logs = match missing_out_of_gas_step(
+            geth_trace.failed,
+            geth_trace.struct_logs.last(),
+            self.code_db
+                .get(&tx.calls()[0].code_hash)
+                .unwrap_or_default(),
+        )? {
+            Some(geth_step) => Cow::Owned(
+                geth_trace
+                    .struct_logs
+                    .iter()
+                    .cloned()
+                    .chain(std::iter::once(geth_step))
+                    .collect(),
+            ),
+            None => Cow::Borrowed(geth_trace.struct_logs.as_slice()),
+        };
 
         // Generate BeginTx step
         let begin_tx_step = gen_associated_steps(
@@ -234,13 +289,14 @@ impl<'a, C: CircuitsParams> CircuitInputBuilder<C> {
         )?;
         tx.steps_mut().push(begin_tx_step);
 
-        for (index, geth_step) in geth_trace.struct_logs.iter().enumerate() {
+        for (index, geth_step) in struct_logs.iter().enumerate() {
             let mut state_ref = self.state_ref(&mut tx, &mut tx_ctx);
             log::trace!("handle {}th opcode {:?} ", index, geth_step.op);
             let exec_steps = gen_associated_ops(
                 &geth_step.op,
                 &mut state_ref,
-                &geth_trace.struct_logs[index..],
+                &struct_logs[index..],
+                memory_enabled,
             )?;
             tx.steps_mut().extend(exec_steps);
         }
@@ -249,12 +305,149 @@ impl<'a, C: CircuitsParams> CircuitInputBuilder<C> {
         let end_tx_step =
             gen_associated_steps(&mut self.state_ref(&mut tx, &mut tx_ctx), ExecState::EndTx)?;
         tx.steps_mut().push(end_tx_step);
+        tracing::debug!(
+            steps = tx.steps().len(),
+            rwc = self.block_ctx.rwc.0,
+            "tx handled"
+        );
 
         self.sdb.commit_tx();
         self.block.txs.push(tx);
 
         Ok(())
     }
+
+    /// Handle a transaction whose struct logs are provided by `struct_logs`
+    /// instead of being held in memory as a whole, which is required for
+    /// transactions with millions of steps.  `struct_logs` is called twice:
+    /// a first pass collects the result of each call, and a second pass
+    /// generates the associated operations.  In both passes at most two steps
+    /// are kept in memory, and each step (with its memory snapshot) is dropped
+    /// as soon as its operations have been generated.  The first pass also
+    /// finds out whether the trace has memory snapshots, so that the
+    /// operations are generated with the same checks as in `handle_tx`.
+    pub fn handle_tx_streaming<F, I>(
+        &mut self,
+        eth_tx: &eth_types::Transaction,
+        failed: bool,
+        struct_logs: F,
+        is_last_tx: bool,
+        tx_index: u64,
+    ) -> Result<(), Error>
+    where
+        F: Fn() -> I,
+        I: Iterator<Item = Result<GethExecStep, Error>>,
+    {
+        let mut struct_logs_err = Ok(());
+        let mut memory_enabled = false;
+        let call_is_success = calls_is_success(
+            failed,
+            struct_logs()
+                .map_while(|geth_step| geth_step.map_err(|err| struct_logs_err = Err(err)).ok())
+                .inspect(|geth_step| memory_enabled |= !geth_step.memory.is_empty()),
+        )?;
+        struct_logs_err?;
+        let _span = tracing::debug_span!("tx", tx_index, hash = ?eth_tx.hash).entered();
+        let mut tx = self.new_tx(tx_index, eth_tx, !failed)?;
+        let mut tx_ctx =
+            TransactionContext::new_with_calls_is_success(eth_tx, call_is_success, is_last_tx)?;
+
+        // Generate BeginTx step
+        let begin_tx_step = gen_associated_steps(
+            &mut self.state_ref(&mut tx, &mut tx_ctx),
+            ExecState::BeginTx,
+        )?;
+        tx.steps_mut().push(begin_tx_step);
+
+        // Opcode handlers look at most one step ahead.
+        let root_code = self
+            .code_db
+            .get(&tx.calls()[0].code_hash)
+            .unwrap_or_default()
+            .to_vec();
+        let mut struct_logs = struct_logs();
+        let mut window = Vec::with_capacity(2);
+        if let Some(geth_step) = struct_logs.next() {
+            window.push(geth_step?);
+        }
+        let mut index = 0;
+        while !window.is_empty() {
+            if let Some(geth_step) = struct_logs.next() {
+                window.push(geth_step?);
+            } else if let Some(geth_step) =
+                missing_out_of_gas_step(failed, window.last(), &root_code)?
+            {
+                // Complete the trace like in `handle_tx`.
+                window.push(geth_step);
+            }
+            let mut state_ref = self.state_ref(&mut tx, &mut tx_ctx);
+            log::trace!("handle {}th opcode {:?} ", index, window[0].op);
+            let exec_steps =
+                gen_associated_ops(&window[0].op, &mut state_ref, &window, memory_enabled)?;
+            tx.steps_mut().extend(exec_steps);
+            window.remove(0);
+            index += 1;
+        }
+
+        // Generate EndTx step
+        let end_tx_step =
+            gen_associated_steps(&mut self.state_ref(&mut tx, &mut tx_ctx), ExecState::EndTx)?;
+        tx.steps_mut().push(end_tx_step);
+        tracing::debug!(
+            steps = tx.steps().len(),
+            rwc = self.block_ctx.rwc.0,
+            "tx handled"
+        );
+
+        self.sdb.commit_tx();
+        self.block.txs.push(tx);
+
+        Ok(())
+    }
+
+    /// Handle the transaction at position `idx` of `eth_block`.
+    fn handle_block_tx(
+        &mut self,
+        eth_block: &EthBlock,
+        geth_traces: &[eth_types::GethExecTrace],
+        idx: usize,
+    ) -> Result<(), Error> {
+        let tx = &eth_block.transactions[idx];
+        let geth_trace = &geth_traces[idx];
+        // Transaction index starts from 1
+        let tx_id = idx + 1;
+        self.handle_tx(
+            tx,
+            geth_trace,
+            tx_id == eth_block.transactions.len(),
+            tx_id as u64,
+        )
+    }
+
+    /// Return the state of the builder at the boundary between the last
+    /// handled transaction and the next one.
+    pub fn chunk_boundary(&self) -> ChunkBoundary {
+        ChunkBoundary {
+            block_number: self.block.number,
+            tx_count: self.block.txs.len(),
+            rwc: self.block_ctx.rwc,
+            cumulative_gas_used: self.block_ctx.cumulative_gas_used,
+            copy_event_count: self.block.copy_events.len(),
+            exp_event_count: self.block.exp_events.len(),
+        }
+    }
+
+    /// Create a new CircuitInputBuilder for the block that follows the one
+    /// handled by `self`.  The StateDB and CodeDB are carried over, so that
+    /// the new block starts from the state left by the previous one.
+    pub fn new_next_block(&self, block: Block) -> Self {
+        Self::new(
+            self.sdb.clone(),
+            self.code_db.clone(),
+            block,
+            self.circuits_params,
+        )
+    }
 }
 
 impl CircuitInputBuilder<FixedCParams> {
@@ -271,6 +464,34 @@ impl CircuitInputBuilder<FixedCParams> {
         Ok(self)
     }
 
+    /// Handle a block splitting its transactions in chunks of at most
+    /// `max_txs_per_chunk` transactions.  See
+    /// [`CircuitInputBuilder::begin_handle_block_in_chunks`].
+    pub fn handle_block_in_chunks(
+        &mut self,
+        eth_block: &EthBlock,
+        geth_traces: &[eth_types::GethExecTrace],
+        max_txs_per_chunk: usize,
+    ) -> Result<&CircuitInputBuilder<FixedCParams>, Error> {
+        self.begin_handle_block_in_chunks(eth_block, geth_traces, max_txs_per_chunk)?;
+        self.set_end_block(self.circuits_params.max_rws);
+        Ok(self)
+    }
+
+    /// Handle the block that follows the one handled by `self`, starting from
+    /// the StateDB and CodeDB left by it.  This allows ingesting several
+    /// consecutive blocks while keeping a continuous state.
+    pub fn handle_next_block(
+        &self,
+        block: Block,
+        eth_block: &EthBlock,
+        geth_traces: &[eth_types::GethExecTrace],
+    ) -> Result<CircuitInputBuilder<FixedCParams>, Error> {
+        let mut builder = self.new_next_block(block);
+        builder.handle_block(eth_block, geth_traces)?;
+        Ok(builder)
+    }
+
     fn set_end_block(&mut self, max_rws: usize) {
         let mut end_block_not_last = self.block.block_steps.end_block_not_last.clone();
         let mut end_block_last = self.block.block_steps.end_block_last.clone();
@@ -335,23 +556,56 @@ impl<C: CircuitsParams> CircuitInputBuilder<C> {
         eth_block: &EthBlock,
         geth_traces: &[eth_types::GethExecTrace],
     ) -> Result<(), Error> {
+        let _span = tracing::info_span!("block", number = ?eth_block.number).entered();
         // accumulates gas across all txs in the block
-        for (idx, tx) in eth_block.transactions.iter().enumerate() {
-            let geth_trace = &geth_traces[idx];
-            // Transaction index starts from 1
-            let tx_id = idx + 1;
-            self.handle_tx(
-                tx,
-                geth_trace,
-                tx_id == eth_block.transactions.len(),
-                tx_id as u64,
-            )?;
+        for idx in 0..eth_block.transactions.len() {
+            self.handle_block_tx(eth_block, geth_traces, idx)?;
         }
         // set eth_block
         self.block.eth_block = eth_block.clone();
         self.set_value_ops_call_context_rwc_eor();
+        self.trace_block_handled();
         Ok(())
     }
+
+    /// Same as `begin_handle_block`, but the transactions are split at
+    /// transaction boundaries in chunks of at most `max_txs_per_chunk`
+    /// transactions.  The boundary state of each chunk is recorded in
+    /// `self.block.chunks`.
+    pub fn begin_handle_block_in_chunks(
+        &mut self,
+        eth_block: &EthBlock,
+        geth_traces: &[eth_types::GethExecTrace],
+        max_txs_per_chunk: usize,
+    ) -> Result<(), Error> {
+        let _span = tracing::info_span!("block", number = ?eth_block.number).entered();
+        let tx_ranges = chunk_tx_ranges(eth_block.transactions.len(), max_txs_per_chunk)?;
+        for (index, tx_range) in tx_ranges.into_iter().enumerate() {
+            let begin = self.chunk_boundary();
+            for idx in tx_range {
+                self.handle_block_tx(eth_block, geth_traces, idx)?;
+            }
+            let end = self.chunk_boundary();
+            self.block.chunks.push(Chunk { index, begin, end });
+        }
+        // set eth_block
+        self.block.eth_block = eth_block.clone();
+        self.set_value_ops_call_context_rwc_eor();
+        self.trace_block_handled();
+        Ok(())
+    }
+
+    /// Reports the size of the handled block, which drives the number of rows
+    /// of the circuits.
+    fn trace_block_handled(&self) {
+        tracing::info!(
+            txs = self.block.txs.len(),
+            rwc = self.block_ctx.rwc.0,
+            copy_events = self.block.copy_events.len(),
+            exp_events = self.block.exp_events.len(),
+            "block handled"
+        );
+    }
 }
 impl CircuitInputBuilder<DynamicCParams> {
     /// Handle a block by handling each transaction to generate all the
@@ -414,6 +668,7 @@ impl CircuitInputBuilder<DynamicCParams> {
                 max_bytecode,
                 max_evm_rows,
                 max_keccak_rows,
+                anchor_tx: None,
             }
         };
         let mut cib = CircuitInputBuilder::<FixedCParams> {
@@ -485,8 +740,9 @@ pub fn keccak_inputs_tx_circuit(
     // Keccak inputs from SignVerify Chip
     let sign_verify_inputs = keccak_inputs_sign_verify(&sign_datas);
     inputs.extend_from_slice(&sign_verify_inputs);
-    // NOTE: We don't verify the Tx Hash in the circuit yet, so we don't have more
-    // hash inputs.
+    // Keccak inputs from the Tx Hash of each tx.  Padding txs hash the empty input,
+    // which is always present in the keccak table.
+    inputs.extend(txs.iter().map(|tx| tx.rlp_signed(chain_id).to_vec()));
     Ok(inputs)
 }
 
@@ -524,6 +780,7 @@ type EthBlock = eth_types::Block<eth_types::Transaction>;
 /// Struct that wraps a GethClient and contains methods to perform all the steps
 /// necessary to generate the circuit inputs for a block by querying geth for
 /// the necessary information and using the CircuitInputBuilder.
+#[cfg(feature = "rpc")]
 pub struct BuilderClient<P: JsonRpcClient> {
     cli: GethClient<P>,
     chain_id: Word,
@@ -582,6 +839,7 @@ pub fn build_state_code_db(
     (sdb, code_db)
 }
 
+#[cfg(feature = "rpc")]
 impl<P: JsonRpcClient> BuilderClient<P> {
     /// Create a new BuilderClient
     pub async fn new(client: GethClient<P>, circuits_params: FixedCParams) -> Result<Self, Error> {
@@ -1,12 +1,19 @@
 //! Block-related utility module
 
-use super::{execution::ExecState, transaction::Transaction, CopyEvent, ExecStep, ExpEvent};
+use super::{
+    chunk::Chunk,
+    execution::ExecState,
+    fee::{EthereumFeePolicy, FeePolicy},
+    transaction::Transaction,
+    CopyEvent, ExecStep, ExpEvent,
+};
 use crate::{
-    operation::{OperationContainer, RWCounter},
+    chain_config::{ChainConfig, Hardfork},
+    operation::{MemoryOpMode, OperationContainer, RWCounter},
     Error,
 };
-use eth_types::{evm_unimplemented, Address, Word};
-use std::collections::HashMap;
+use eth_types::{evm_unimplemented, Address, ToWord, Word};
+use std::{collections::HashMap, sync::Arc};
 
 /// Context of a [`Block`] which can mutate in a [`Transaction`].
 #[derive(Debug, Clone)]
@@ -55,6 +62,9 @@ pub struct BlockSteps {
 pub struct Block {
     /// chain id
     pub chain_id: Word,
+    /// Rules (hardfork and EIPs) of the chain.  Defaults to the latest
+    /// supported hardfork with chain id `chain_id`.
+    pub chain_config: ChainConfig,
     /// history hashes contains most recent 256 block hashes in history, where
     /// the lastest one is at history_hashes[history_hashes.len() - 1].
     pub history_hashes: Vec<Word>,
@@ -68,6 +78,9 @@ pub struct Block {
     pub timestamp: Word,
     /// gas limit
     pub difficulty: Word,
+    /// PREVRANDAO of the block (its mix hash), returned by the DIFFICULTY
+    /// opcode after the merge
+    pub prev_randao: Word,
     /// base fee
     pub base_fee: Word,
     /// State root of the previous block
@@ -84,6 +97,15 @@ pub struct Block {
     pub sha3_inputs: Vec<Vec<u8>>,
     /// Exponentiation events in the block.
     pub exp_events: Vec<ExpEvent>,
+    /// Chunks in which the transactions of the block have been handled.
+    /// Empty when the block has not been split.
+    pub chunks: Vec<Chunk>,
+    /// Policy charging fees on top of the EVM gas at the beginning and end of
+    /// each transaction.  Defaults to [`EthereumFeePolicy`].
+    pub fee_policy: Arc<dyn FeePolicy>,
+    /// Granularity of the memory operations generated by `MLOAD`, `MSTORE`
+    /// and `MSTORE8`.  Defaults to [`MemoryOpMode::Byte`].
+    pub memory_op_mode: MemoryOpMode,
     /// Original block from geth
     pub eth_block: eth_types::Block<eth_types::Transaction>,
 }
@@ -105,6 +127,7 @@ impl Block {
 
         Ok(Self {
             chain_id,
+            chain_config: ChainConfig::new(chain_id.as_u64(), Hardfork::default()),
             history_hashes,
             coinbase: eth_block
                 .author
@@ -117,6 +140,7 @@ impl Block {
                 .into(),
             timestamp: eth_block.timestamp,
             difficulty: eth_block.difficulty,
+            prev_randao: eth_block.mix_hash.unwrap_or_default().to_word(),
             base_fee: eth_block.base_fee_per_gas.unwrap_or_default(),
             prev_state_root,
             container: OperationContainer::new(),
@@ -134,10 +158,29 @@ impl Block {
             copy_events: Vec::new(),
             exp_events: Vec::new(),
             sha3_inputs: Vec::new(),
+            chunks: Vec::new(),
+            fee_policy: Arc::new(EthereumFeePolicy),
+            memory_op_mode: MemoryOpMode::default(),
             eth_block: eth_block.clone(),
         })
     }
 
+    /// Set the rules of the chain, keeping `chain_id` in sync.
+    pub fn set_chain_config(&mut self, chain_config: ChainConfig) {
+        self.chain_id = chain_config.chain_id.into();
+        self.chain_config = chain_config;
+    }
+
+    /// Return the value of the DIFFICULTY opcode in this block: the
+    /// PREVRANDAO after the merge, the difficulty before it.
+    pub fn difficulty_opcode_value(&self) -> Word {
+        if self.chain_config.is_difficulty_prevrandao() {
+            self.prev_randao
+        } else {
+            self.difficulty
+        }
+    }
+
     /// Return the list of transactions of this block.
     pub fn txs(&self) -> &[Transaction] {
         &self.txs
@@ -0,0 +1,263 @@
+//! Chunk-related utility module
+
+use crate::{operation::RWCounter, Error};
+use eth_types::Word;
+use std::ops::Range;
+
+/// Snapshot of the [`CircuitInputBuilder`](super::CircuitInputBuilder) state
+/// at the boundary between two chunks.  The end boundary of a chunk is the
+/// begin boundary of the following one, so that chunks can be proven
+/// independently and linked afterwards.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ChunkBoundary {
+    /// Number of the block the boundary belongs to.
+    pub block_number: Word,
+    /// Number of transactions of the block that have been handled before the
+    /// boundary.  This is also the index (in `Block.txs`) of the first
+    /// transaction after the boundary.
+    pub tx_count: usize,
+    /// Next available value of the global rw counter.
+    pub rwc: RWCounter,
+    /// Total gas used by the transactions of the block handled before the
+    /// boundary.
+    pub cumulative_gas_used: u64,
+    /// Number of copy events generated before the boundary.
+    pub copy_event_count: usize,
+    /// Number of exponentiation events generated before the boundary.
+    pub exp_event_count: usize,
+}
+
+/// Contiguous range of transactions of a block, split at transaction
+/// boundaries, that can be proven independently.
+///
+/// Every transaction starts with a new root call, whose stack and memory are
+/// empty, and all its calls end with the transaction.  So no stack or memory
+/// is live at a boundary, and [`ChunkBoundary`] describes the whole state
+/// carried from a chunk to the next one: there's no stack or memory state to
+/// commit to at a boundary.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Chunk {
+    /// Index of the chunk within the block.
+    pub index: usize,
+    /// State at the beginning of the chunk.
+    pub begin: ChunkBoundary,
+    /// State at the end of the chunk.
+    pub end: ChunkBoundary,
+}
+
+impl Chunk {
+    /// Range of transaction indices (in `Block.txs`) contained in the chunk.
+    pub fn tx_range(&self) -> Range<usize> {
+        self.begin.tx_count..self.end.tx_count
+    }
+
+    /// Range of rw counters used by the operations of the chunk.
+    pub fn rwc_range(&self) -> Range<usize> {
+        self.begin.rwc.0..self.end.rwc.0
+    }
+
+    /// Return whether the chunk contains no transactions.
+    pub fn is_empty(&self) -> bool {
+        self.begin.tx_count == self.end.tx_count
+    }
+}
+
+/// Split `num_txs` transactions into ranges of at most `max_txs_per_chunk`
+/// transactions.  A block without transactions results in a single empty
+/// chunk, so that every block has at least one boundary pair.
+pub(crate) fn chunk_tx_ranges(
+    num_txs: usize,
+    max_txs_per_chunk: usize,
+) -> Result<Vec<Range<usize>>, Error> {
+    if max_txs_per_chunk == 0 {
+        return Err(Error::InternalError("max_txs_per_chunk must be positive"));
+    }
+    if num_txs == 0 {
+        return Ok(vec![0..0]);
+    }
+    Ok((0..num_txs)
+        .step_by(max_txs_per_chunk)
+        .map(|start| start..std::cmp::min(start + max_txs_per_chunk, num_txs))
+        .collect())
+}
+
+#[cfg(test)]
+mod chunk_tests {
+    use super::*;
+    use crate::{circuit_input_builder::Block, mock::BlockData};
+    use eth_types::{bytecode, geth_types::GethData};
+    use mock::{
+        test_ctx::{helpers::*, TestContext},
+        MOCK_ACCOUNTS,
+    };
+    use std::collections::HashSet;
+
+    #[test]
+    fn chunk_tx_ranges_split() {
+        assert_eq!(chunk_tx_ranges(0, 2).unwrap(), vec![0..0]);
+        assert_eq!(chunk_tx_ranges(1, 2).unwrap(), vec![0..1]);
+        assert_eq!(chunk_tx_ranges(4, 2).unwrap(), vec![0..2, 2..4]);
+        assert_eq!(chunk_tx_ranges(5, 2).unwrap(), vec![0..2, 2..4, 4..5]);
+        assert_eq!(chunk_tx_ranges(3, 10).unwrap(), vec![0..3]);
+        assert!(chunk_tx_ranges(3, 0).is_err());
+    }
+
+    #[test]
+    fn handle_block_in_chunks_of_zero_txs_per_chunk() {
+        let block: GethData = TestContext::<2, 1>::simple_ctx_with_bytecode(bytecode! { STOP })
+            .unwrap()
+            .into();
+        let mut builder =
+            BlockData::new_from_geth_data_with_params(block.clone(), Default::default())
+                .new_circuit_input_builder();
+        assert!(matches!(
+            builder.handle_block_in_chunks(&block.eth_block, &block.geth_traces, 0),
+            Err(Error::InternalError(_))
+        ));
+        assert!(builder.block.txs.is_empty());
+    }
+
+    #[test]
+    fn handle_block_in_chunks_without_txs() {
+        let block: GethData = TestContext::<0, 0>::new(None, |_| {}, |_, _| {}, |b, _| b)
+            .unwrap()
+            .into();
+        let mut builder =
+            BlockData::new_from_geth_data_with_params(block.clone(), Default::default())
+                .new_circuit_input_builder();
+        builder
+            .handle_block_in_chunks(&block.eth_block, &block.geth_traces, 2)
+            .unwrap();
+
+        let chunks = &builder.block.chunks;
+        assert_eq!(chunks.len(), 1);
+        assert!(chunks[0].is_empty());
+        assert_eq!(chunks[0].tx_range(), 0..0);
+        assert_eq!(chunks[0].begin, chunks[0].end);
+        assert_eq!(chunks[0].begin.rwc.0, 1);
+    }
+
+    #[test]
+    fn handle_block_in_chunks() {
+        let code = bytecode! {
+            PUSH1(0x01)
+            PUSH1(0x02)
+            ADD
+            STOP
+        };
+        let block: GethData = TestContext::<2, 3>::new(
+            None,
+            account_0_code_account_1_no_code(code),
+            |mut txs, accs| {
+                for tx in txs.iter_mut() {
+                    tx.from(accs[1].address).to(accs[0].address);
+                }
+            },
+            |block, _tx| block.number(0xcafeu64),
+        )
+        .unwrap()
+        .into();
+
+        let mut builder =
+            BlockData::new_from_geth_data_with_params(block.clone(), Default::default())
+                .new_circuit_input_builder();
+        builder
+            .handle_block_in_chunks(&block.eth_block, &block.geth_traces, 2)
+            .unwrap();
+
+        let chunks = &builder.block.chunks;
+        assert_eq!(chunks.len(), 2);
+        assert_eq!(chunks[0].tx_range(), 0..2);
+        assert_eq!(chunks[1].tx_range(), 2..3);
+        // Boundaries are contiguous and cover all the rw operations of the txs.
+        assert_eq!(chunks[0].end, chunks[1].begin);
+        assert_eq!(chunks[0].begin.rwc.0, 1);
+        assert_eq!(chunks[1].end.rwc, builder.block_ctx.rwc);
+        assert!(chunks[0].end.cumulative_gas_used < chunks[1].end.cumulative_gas_used);
+
+        // The next block starts from the state left by this one.
+        let next_block = Block::new(
+            builder.block.chain_id,
+            Vec::new(),
+            Word::zero(),
+            &block.eth_block,
+        )
+        .unwrap();
+        let next_builder = builder.new_next_block(next_block);
+        assert_eq!(
+            next_builder.sdb.get_nonce(&MOCK_ACCOUNTS[1]),
+            builder.sdb.get_nonce(&MOCK_ACCOUNTS[1])
+        );
+        assert_eq!(next_builder.sdb.get_nonce(&MOCK_ACCOUNTS[1]), 3);
+    }
+
+    #[test]
+    fn chunks_share_no_stack_or_memory() {
+        let code = bytecode! {
+            PUSH1(0x2a)
+            PUSH1(0x00)
+            MSTORE
+            PUSH1(0x00)
+            MLOAD
+            STOP
+        };
+        let block: GethData = TestContext::<2, 3>::new(
+            None,
+            account_0_code_account_1_no_code(code),
+            |mut txs, accs| {
+                for tx in txs.iter_mut() {
+                    tx.from(accs[1].address).to(accs[0].address);
+                }
+            },
+            |block, _tx| block.number(0xcafeu64),
+        )
+        .unwrap()
+        .into();
+
+        let mut builder =
+            BlockData::new_from_geth_data_with_params(block.clone(), Default::default())
+                .new_circuit_input_builder();
+        builder
+            .handle_block_in_chunks(&block.eth_block, &block.geth_traces, 1)
+            .unwrap();
+
+        // Ids of the calls whose stack or memory is accessed in each chunk
+        let container = &builder.block.container;
+        let call_ids: Vec<HashSet<usize>> = builder
+            .block
+            .chunks
+            .iter()
+            .map(|chunk| {
+                let in_chunk = |rwc: RWCounter| chunk.rwc_range().contains(&rwc.0);
+                container
+                    .stack
+                    .iter()
+                    .filter(|op| in_chunk(op.rwc()))
+                    .map(|op| op.op().call_id)
+                    .chain(
+                        container
+                            .memory
+                            .iter()
+                            .filter(|op| in_chunk(op.rwc()))
+                            .map(|op| op.op().call_id),
+                    )
+                    .chain(
+                        container
+                            .memory_word
+                            .iter()
+                            .filter(|op| in_chunk(op.rwc()))
+                            .map(|op| op.op().call_id),
+                    )
+                    .collect()
+            })
+            .collect();
+
+        assert_eq!(call_ids.len(), 3);
+        for (index, ids) in call_ids.iter().enumerate() {
+            assert!(!ids.is_empty());
+            for other in &call_ids[index + 1..] {
+                assert!(ids.is_disjoint(other));
+            }
+        }
+    }
+}
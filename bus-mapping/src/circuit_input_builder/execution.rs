@@ -7,10 +7,11 @@ use crate::{
 use eth_types::{evm_types::OpcodeId, GethExecStep, Word, H256};
 use gadgets::impl_expr;
 use halo2_proofs::plonk::Expression;
+use serde::{Deserialize, Serialize};
 use strum_macros::EnumIter;
 
 /// An execution step of the EVM.
-#[derive(Clone, Debug, Default)]
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
 pub struct ExecStep {
     /// Execution state
     pub exec_state: ExecState,
@@ -120,10 +121,10 @@ impl ExecStep {
 }
 
 /// Execution state
-#[derive(Clone, Debug, Eq, PartialEq)]
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
 pub enum ExecState {
     /// EVM Opcode ID
-    Op(OpcodeId),
+    Op(#[serde(with = "opcode_byte")] OpcodeId),
     /// Virtual step Begin Tx
     BeginTx,
     /// Virtual step End Tx
@@ -132,6 +133,26 @@ pub enum ExecState {
     EndBlock,
 }
 
+/// (De)serialization of an [`OpcodeId`] as its byte, so that the invalid
+/// opcodes round-trip.
+mod opcode_byte {
+    use eth_types::evm_types::OpcodeId;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub(super) fn serialize<S: Serializer>(
+        op: &OpcodeId,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u8(op.as_u8())
+    }
+
+    pub(super) fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<OpcodeId, D::Error> {
+        u8::deserialize(deserializer).map(OpcodeId::from)
+    }
+}
+
 impl Default for ExecState {
     fn default() -> Self {
         ExecState::Op(OpcodeId::STOP)
@@ -177,7 +198,7 @@ impl ExecState {
 }
 
 /// Defines the various source/destination types for a copy event.
-#[derive(Clone, Copy, Debug, PartialEq, Eq, EnumIter)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, EnumIter, Serialize, Deserialize)]
 pub enum CopyDataType {
     /// When we need to pad the Copy rows of the circuit up to a certain maximum
     /// with rows that are not "useful".
@@ -222,7 +243,7 @@ pub struct CopyStep {
 }
 
 /// Defines an enum type that can hold either a number or a hash value.
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub enum NumberOrHash {
     /// Variant to indicate a number value.
     Number(usize),
@@ -233,7 +254,7 @@ pub enum NumberOrHash {
 /// Defines a copy event associated with EVM opcodes such as CALLDATACOPY,
 /// CODECOPY, CREATE, etc. More information:
 /// <https://github.com/privacy-scaling-explorations/zkevm-specs/blob/master/specs/copy-proof.md>.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct CopyEvent {
     /// Represents the start address at the source of the copy event.
     pub src_addr: u64,
@@ -296,7 +317,7 @@ impl CopyEvent {
 }
 
 /// Intermediary multiplication step, representing `a * b == d (mod 2^256)`
-#[derive(Clone, Debug, Eq, PartialEq)]
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
 pub struct ExpStep {
     /// First multiplicand.
     pub a: Word,
@@ -317,7 +338,7 @@ impl From<(Word, Word, Word)> for ExpStep {
 }
 
 /// Event representating an exponentiation `a ^ b == d (mod 2^256)`.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct ExpEvent {
     /// Identifier for the exponentiation trace.
     pub identifier: usize,
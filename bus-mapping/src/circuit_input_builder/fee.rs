@@ -0,0 +1,308 @@
+//! Fee policies applied by the input builder at the beginning and end of a
+//! transaction.
+
+use super::{CircuitInputStateRef, ExecStep, Transaction};
+use crate::{
+    exec_trace::OperationRef,
+    operation::{AccountField, Target, RW},
+    Error,
+};
+use eth_types::{Address, Word};
+use serde::{Deserialize, Serialize};
+use std::fmt::Debug;
+
+/// Precision of the [`L1DataFeePolicy::scalar`].
+pub const L1_FEE_PRECISION: u64 = 1_000_000_000;
+
+/// Policy used to charge fees on top of the ones defined by the EVM.  The
+/// hooks are called by the `BeginTx` and `EndTx` steps after the regular gas
+/// payments, and every balance change they make must be pushed as an rw
+/// operation (via [`CircuitInputStateRef::account_write`]) so that the
+/// circuits can constrain it.  The circuits only know the layout of the
+/// [`L1DataFeePolicy`] hooks, so the input builder returns an error when a
+/// policy generates other rw operations than the balance write of the sender
+/// at `BeginTx` and of the fee vault at `EndTx` of its
+/// [`FeePolicy::l1_data_fee`] parameters, or any rw operation without them.
+pub trait FeePolicy: Debug + Send + Sync {
+    /// Called at `BeginTx` right after the gas prepayment of the sender.
+    fn begin_tx(
+        &self,
+        _state: &mut CircuitInputStateRef,
+        _exec_step: &mut ExecStep,
+    ) -> Result<(), Error> {
+        Ok(())
+    }
+
+    /// Called at `EndTx` right after the coinbase reward.
+    fn end_tx(
+        &self,
+        _state: &mut CircuitInputStateRef,
+        _exec_step: &mut ExecStep,
+    ) -> Result<(), Error> {
+        Ok(())
+    }
+
+    /// Parameters of the L1 data fee charged by the policy, which the
+    /// circuits are configured with to constrain the balance changes of the
+    /// hooks.  Policies returning `None` must not generate any rw operation.
+    fn l1_data_fee(&self) -> Option<&L1DataFeePolicy> {
+        None
+    }
+}
+
+/// Ethereum fee policy: no fee other than the gas paid to the coinbase, so
+/// no extra rw operation is generated.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct EthereumFeePolicy;
+
+impl FeePolicy for EthereumFeePolicy {}
+
+/// Rollup fee policy charging the sender for the cost of posting the
+/// transaction data on L1.  The fee is deducted from the sender at `BeginTx`
+/// and credited to the `fee_vault` at `EndTx`, regardless of the execution
+/// result.  Both balance writes are generated even when the fee is zero, so
+/// that the steps have the same layout for every transaction.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct L1DataFeePolicy {
+    /// Account receiving the L1 data fees.
+    pub fee_vault: Address,
+    /// Base fee of the L1 block the rollup batch is posted in.
+    pub l1_base_fee: Word,
+    /// Fixed L1 gas overhead charged per transaction.
+    pub overhead: u64,
+    /// Fee multiplier, in units of [`L1_FEE_PRECISION`].
+    pub scalar: u64,
+}
+
+impl L1DataFeePolicy {
+    /// L1 data fee of a transaction: `(data_gas + overhead) * l1_base_fee *
+    /// scalar / L1_FEE_PRECISION`, where `data_gas` is the calldata cost of
+    /// the transaction.
+    pub fn l1_fee(&self, tx: &Transaction) -> Word {
+        let l1_gas = Word::from(tx.call_data_gas_cost() + self.overhead);
+        l1_gas * self.scaled_l1_base_fee() / Word::from(L1_FEE_PRECISION)
+    }
+
+    /// `l1_base_fee * scalar`, the L1 data fee per L1 gas in units of
+    /// [`L1_FEE_PRECISION`].
+    pub fn scaled_l1_base_fee(&self) -> Word {
+        self.l1_base_fee * Word::from(self.scalar)
+    }
+}
+
+impl FeePolicy for L1DataFeePolicy {
+    fn begin_tx(
+        &self,
+        state: &mut CircuitInputStateRef,
+        exec_step: &mut ExecStep,
+    ) -> Result<(), Error> {
+        let l1_fee = self.l1_fee(state.tx);
+        let sender = state.tx.from;
+        let (found, sender_account) = state.sdb.get_account(&sender);
+        if !found {
+            return Err(Error::AccountNotFound(sender));
+        }
+        let sender_balance_prev = sender_account.balance;
+        let sender_balance = sender_balance_prev
+            .checked_sub(l1_fee)
+            .ok_or(Error::InternalError("insufficient balance for L1 data fee"))?;
+        state.account_write(
+            exec_step,
+            sender,
+            AccountField::Balance,
+            sender_balance,
+            sender_balance_prev,
+        )
+    }
+
+    fn end_tx(
+        &self,
+        state: &mut CircuitInputStateRef,
+        exec_step: &mut ExecStep,
+    ) -> Result<(), Error> {
+        let l1_fee = self.l1_fee(state.tx);
+        let (found, vault_account) = state.sdb.get_account(&self.fee_vault);
+        if !found {
+            return Err(Error::AccountNotFound(self.fee_vault));
+        }
+        let vault_balance_prev = vault_account.balance;
+        state.account_write(
+            exec_step,
+            self.fee_vault,
+            AccountField::Balance,
+            vault_balance_prev + l1_fee,
+            vault_balance_prev,
+        )
+    }
+
+    fn l1_data_fee(&self) -> Option<&L1DataFeePolicy> {
+        Some(self)
+    }
+}
+
+/// Calls [`FeePolicy::begin_tx`] of the block fee policy and checks the rw
+/// operations it generates.
+pub(crate) fn fee_policy_begin_tx(
+    state: &mut CircuitInputStateRef,
+    exec_step: &mut ExecStep,
+) -> Result<(), Error> {
+    let fee_policy = state.block.fee_policy.clone();
+    let rws_before = exec_step.bus_mapping_instance.len();
+    fee_policy.begin_tx(state, exec_step)?;
+    let payer = fee_policy.l1_data_fee().map(|_| state.tx.from);
+    check_rws(state, exec_step, rws_before, payer)
+}
+
+/// Calls [`FeePolicy::end_tx`] of the block fee policy and checks the rw
+/// operations it generates.
+pub(crate) fn fee_policy_end_tx(
+    state: &mut CircuitInputStateRef,
+    exec_step: &mut ExecStep,
+) -> Result<(), Error> {
+    let fee_policy = state.block.fee_policy.clone();
+    let rws_before = exec_step.bus_mapping_instance.len();
+    fee_policy.end_tx(state, exec_step)?;
+    let fee_vault = fee_policy.l1_data_fee().map(|policy| policy.fee_vault);
+    check_rws(state, exec_step, rws_before, fee_vault)
+}
+
+/// Checks that the rw operations of `exec_step` from `rws_before` are a single
+/// balance write of `account`, or nothing if there is no account.
+fn check_rws(
+    state: &CircuitInputStateRef,
+    exec_step: &ExecStep,
+    rws_before: usize,
+    account: Option<Address>,
+) -> Result<(), Error> {
+    let is_balance_write = |op_ref: &OperationRef| {
+        if op_ref.target() != Target::Account {
+            return false;
+        }
+        let operation = &state.block.container.account[op_ref.as_usize()];
+        operation.rw() == RW::WRITE
+            && operation.op().field == AccountField::Balance
+            && Some(operation.op().address) == account
+    };
+    let rws = &exec_step.bus_mapping_instance[rws_before..];
+    if rws.len() != usize::from(account.is_some()) || !rws.iter().all(is_balance_write) {
+        return Err(Error::InternalError(
+            "fee policy rw operations differ from the L1 data fee ones",
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod fee_tests {
+    use super::*;
+    use crate::mock::BlockData;
+    use eth_types::{bytecode, geth_types::GethData};
+    use mock::{test_ctx::TestContext, MOCK_ACCOUNTS};
+    use std::sync::Arc;
+
+    fn block_with_calldata() -> GethData {
+        let code = bytecode! {
+            STOP
+        };
+        TestContext::<3, 1>::new(
+            None,
+            |accs| {
+                accs[0].address(MOCK_ACCOUNTS[0]).code(code);
+                accs[1]
+                    .address(MOCK_ACCOUNTS[1])
+                    .balance(Word::from(10u64.pow(18)));
+                accs[2].address(MOCK_ACCOUNTS[2]);
+            },
+            |mut txs, accs| {
+                txs[0]
+                    .from(accs[1].address)
+                    .to(accs[0].address)
+                    .input(vec![0, 1, 2, 3].into());
+            },
+            |block, _tx| block.number(0xcafeu64),
+        )
+        .unwrap()
+        .into()
+    }
+
+    #[test]
+    fn l1_data_fee_is_moved_to_vault() {
+        let block = block_with_calldata();
+        let policy = L1DataFeePolicy {
+            fee_vault: MOCK_ACCOUNTS[2],
+            l1_base_fee: Word::from(1_000_000_000u64),
+            overhead: 2100,
+            scalar: L1_FEE_PRECISION,
+        };
+        let mut builder = BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+        builder.block.fee_policy = Arc::new(policy.clone());
+        builder
+            .handle_block(&block.eth_block, &block.geth_traces)
+            .unwrap();
+
+        // 3 non-zero bytes and 1 zero byte of calldata on top of the overhead.
+        let l1_fee = policy.l1_fee(&builder.block.txs()[0]);
+        assert_eq!(l1_fee, Word::from((3 * 16 + 4 + 2100) * 1_000_000_000u64));
+
+        let balance_ops = builder
+            .block
+            .container
+            .account
+            .iter()
+            .map(|op| op.op())
+            .filter(|op| op.field == AccountField::Balance)
+            .collect::<Vec<_>>();
+        assert!(balance_ops.iter().any(|op| op.address == policy.fee_vault
+            && op.value.checked_sub(op.value_prev) == Some(l1_fee)));
+        assert!(balance_ops.iter().any(|op| op.address == MOCK_ACCOUNTS[1]
+            && op.value_prev.checked_sub(op.value) == Some(l1_fee)));
+    }
+
+    #[test]
+    fn l1_data_fee_needs_the_vault_account() {
+        let block = block_with_calldata();
+        let fee_vault = MOCK_ACCOUNTS[3];
+        let mut builder = BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+        builder.block.fee_policy = Arc::new(L1DataFeePolicy {
+            fee_vault,
+            ..Default::default()
+        });
+        assert!(matches!(
+            builder.handle_block(&block.eth_block, &block.geth_traces),
+            Err(Error::AccountNotFound(address)) if address == fee_vault
+        ));
+    }
+
+    /// Policy paying the coinbase without L1 data fee parameters.
+    #[derive(Debug)]
+    struct CoinbaseTipPolicy;
+
+    impl FeePolicy for CoinbaseTipPolicy {
+        fn end_tx(
+            &self,
+            state: &mut CircuitInputStateRef,
+            exec_step: &mut ExecStep,
+        ) -> Result<(), Error> {
+            let coinbase = state.block.coinbase;
+            let balance_prev = state.sdb.get_account(&coinbase).1.balance;
+            state.account_write(
+                exec_step,
+                coinbase,
+                AccountField::Balance,
+                balance_prev + Word::one(),
+                balance_prev,
+            )
+        }
+    }
+
+    #[test]
+    fn unconstrained_fee_rws_are_rejected() {
+        let block = block_with_calldata();
+        let mut builder = BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+        builder.block.fee_policy = Arc::new(CoinbaseTipPolicy);
+        assert!(matches!(
+            builder.handle_block(&block.eth_block, &block.geth_traces),
+            Err(Error::InternalError(_))
+        ));
+    }
+}
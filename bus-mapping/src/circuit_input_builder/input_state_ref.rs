@@ -2,16 +2,16 @@
 
 use super::{
     get_call_memory_offset_length, get_create_init_code, Block, BlockContext, Call, CallContext,
-    CallKind, CodeSource, CopyEvent, ExecState, ExecStep, ExpEvent, Transaction,
+    CallKind, CodeSource, CopyDataType, CopyEvent, ExecState, ExecStep, ExpEvent, Transaction,
     TransactionContext,
 };
 use crate::{
     error::{DepthError, ExecError, InsufficientBalanceError, NonceUintOverflowError},
     exec_trace::OperationRef,
     operation::{
-        AccountField, AccountOp, CallContextField, CallContextOp, MemoryOp, Op, OpEnum, Operation,
-        StackOp, Target, TxAccessListAccountOp, TxLogField, TxLogOp, TxReceiptField, TxReceiptOp,
-        RW,
+        AccountField, AccountOp, CallContextField, CallContextOp, MemoryOp, MemoryOpMode,
+        MemoryWordOp, Op, OpEnum, Operation, StackOp, Target, TxAccessListAccountOp, TxLogField,
+        TxLogOp, TxReceiptField, TxReceiptOp, RW,
     },
     state_db::{CodeDB, StateDB},
     Error,
@@ -218,6 +218,7 @@ impl<'a> CircuitInputStateRef<'a> {
         address: MemoryAddress,
         value: u8,
     ) -> Result<(), Error> {
+        self.check_byte_memory_access(step)?;
         let call_id = self.call()?.call_id;
         self.push_op(step, RW::READ, MemoryOp::new(call_id, address, value));
         Ok(())
@@ -235,11 +236,103 @@ impl<'a> CircuitInputStateRef<'a> {
         address: MemoryAddress,
         value: u8,
     ) -> Result<(), Error> {
+        self.check_byte_memory_access(step)?;
         let call_id = self.call()?.call_id;
         self.push_op(step, RW::WRITE, MemoryOp::new(call_id, address, value));
         Ok(())
     }
 
+    /// Push the read type [`MemoryWordOp`]s covering `length` (at most 32)
+    /// bytes of the current call memory at `offset`: one operation for the
+    /// aligned word containing `offset`, and another one for the following
+    /// word if the access crosses a word boundary.  The values are taken
+    /// from the memory before the access, reading zeros past its end.
+    pub fn memory_read_words(
+        &mut self,
+        step: &mut ExecStep,
+        offset: MemoryAddress,
+        length: usize,
+    ) -> Result<(), Error> {
+        let (address, shift, chunk) = self.memory_word_chunk(offset, length)?;
+        self.push_memory_word_ops(step, RW::READ, address, shift, length, &chunk, &chunk)
+    }
+
+    /// Push the write type [`MemoryWordOp`]s of writing `bytes` (at most 32)
+    /// into the current call memory at `offset`, with the values of the
+    /// words before and after the write.  See [`Self::memory_read_words`].
+    /// The call memory itself is not updated.
+    pub fn memory_write_words(
+        &mut self,
+        step: &mut ExecStep,
+        offset: MemoryAddress,
+        bytes: &[u8],
+    ) -> Result<(), Error> {
+        let (address, shift, chunk_prev) = self.memory_word_chunk(offset, bytes.len())?;
+        let mut chunk = chunk_prev.clone();
+        chunk[shift..shift + bytes.len()].copy_from_slice(bytes);
+        self.push_memory_word_ops(
+            step,
+            RW::WRITE,
+            address,
+            shift,
+            bytes.len(),
+            &chunk,
+            &chunk_prev,
+        )
+    }
+
+    /// Return the aligned address of the first word accessed by an access of
+    /// `length` bytes at `offset`, the shift of `offset` within that word and
+    /// the content of the two words starting at the aligned address.
+    fn memory_word_chunk(
+        &self,
+        offset: MemoryAddress,
+        length: usize,
+    ) -> Result<(MemoryAddress, usize, Vec<u8>), Error> {
+        debug_assert!(length <= 32, "memory word access is at most 32 bytes");
+        let address = offset.map(|addr| addr - addr % 32);
+        let shift = offset.0 % 32;
+        let chunk = self
+            .call_ctx()?
+            .memory
+            .read_chunk(address, MemoryAddress::from(64));
+        Ok((address, shift, chunk))
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn push_memory_word_ops(
+        &mut self,
+        step: &mut ExecStep,
+        rw: RW,
+        address: MemoryAddress,
+        shift: usize,
+        length: usize,
+        chunk: &[u8],
+        chunk_prev: &[u8],
+    ) -> Result<(), Error> {
+        let call_id = self.call()?.call_id;
+        let num_words = if shift + length > 32 { 2 } else { 1 };
+        for (i, (word, word_prev)) in chunk
+            .chunks(32)
+            .zip(chunk_prev.chunks(32))
+            .take(num_words)
+            .enumerate()
+        {
+            self.push_op(
+                step,
+                rw,
+                MemoryWordOp::new(
+                    call_id,
+                    address.map(|addr| addr + 32 * i),
+                    Word::from_big_endian(word),
+                    Word::from_big_endian(word_prev),
+                    shift as u8,
+                ),
+            );
+        }
+        Ok(())
+    }
+
     /// Push a write type [`StackOp`] into the
     /// [`OperationContainer`](crate::operation::OperationContainer) with the
     /// next [`RWCounter`](crate::operation::RWCounter)  and `call_id`, and then
@@ -1195,10 +1288,26 @@ impl<'a> CircuitInputStateRef<'a> {
         Ok(())
     }
 
-    /// Push a copy event to the state.
-    pub fn push_copy(&mut self, step: &mut ExecStep, event: CopyEvent) {
+    /// Push a copy event to the state.  Copy events access the memory byte by
+    /// byte, see [`Self::check_byte_memory_access`].
+    pub fn push_copy(&mut self, step: &mut ExecStep, event: CopyEvent) -> Result<(), Error> {
+        if event.src_type == CopyDataType::Memory || event.dst_type == CopyDataType::Memory {
+            self.check_byte_memory_access(step)?;
+        }
         step.copy_rw_counter_delta = event.rw_counter_delta();
         self.block.add_copy_event(event);
+        Ok(())
+    }
+
+    /// Returns an error if the memory is accessed in [`MemoryOpMode::Word`]
+    /// by `step` byte by byte, like [`Self::memory_read`], the copy events and
+    /// the CALLDATALOAD of internal calls do: only the accesses of MLOAD,
+    /// MSTORE and MSTORE8 are made on words.
+    pub(crate) fn check_byte_memory_access(&self, step: &ExecStep) -> Result<(), Error> {
+        match self.block.memory_op_mode {
+            MemoryOpMode::Byte => Ok(()),
+            MemoryOpMode::Word => Err(Error::ByteMemoryAccessInWordMode(step.exec_state.clone())),
+        }
     }
 
     /// Push a exponentiation event to the state.
@@ -1211,7 +1320,7 @@ impl<'a> CircuitInputStateRef<'a> {
         step: &GethExecStep,
         next_step: Option<&GethExecStep>,
     ) -> Result<Option<ExecError>, Error> {
-        if let Ok(error) = ExecError::try_from(step) {
+        if let Some(error) = ExecError::from_geth_step(step)? {
             return Ok(Some(error));
         }
 
@@ -1349,14 +1458,14 @@ impl<'a> CircuitInputStateRef<'a> {
             && next_pc != 0
         {
             if step.depth == 1025 {
-                return Ok(Some(ExecError::Depth(match step.op {
-                    OpcodeId::CALL | OpcodeId::CALLCODE => DepthError::Call,
-                    OpcodeId::CREATE => DepthError::Create,
-                    OpcodeId::CREATE2 => DepthError::Create2,
-                    op => {
-                        unreachable!("Depth error unexpected for opcode: {:?}", op)
-                    }
-                })));
+                return Ok(Some(ExecError::Depth(
+                    DepthError::try_from(&step.op).map_err(|_| {
+                        Error::InvalidGethExecStep(
+                            "depth error unexpected for opcode",
+                            Box::new(step.clone()),
+                        )
+                    })?,
+                )));
             }
 
             let sender = self.call()?.address;
@@ -1365,26 +1474,30 @@ impl<'a> CircuitInputStateRef<'a> {
                 return Err(Error::AccountNotFound(sender));
             }
             if account.balance < value {
-                return Ok(Some(ExecError::InsufficientBalance(match step.op {
-                    OpcodeId::CALL | OpcodeId::CALLCODE => InsufficientBalanceError::Call,
-                    OpcodeId::CREATE => InsufficientBalanceError::Create,
-                    OpcodeId::CREATE2 => InsufficientBalanceError::Create2,
-                    op => {
-                        unreachable!("insufficient balance error unexpected for opcode: {:?}", op)
-                    }
-                })));
+                return Ok(Some(ExecError::InsufficientBalance(
+                    InsufficientBalanceError::try_from(&step.op).map_err(|_| {
+                        Error::InvalidGethExecStep(
+                            "insufficient balance error unexpected for opcode",
+                            Box::new(step.clone()),
+                        )
+                    })?,
+                )));
             }
 
             // Nonce Uint overflow
             // If user's nonce is equal u64::MAX, nonce will be overflow in this call
             // Nonce is u64 so it's impossible to larger than u64::MAX, that's why we're using `==`
             // here.
-            if account.nonce == u64::MAX {
-                return Ok(Some(ExecError::NonceUintOverflow(match step.op {
-                    OpcodeId::CREATE => NonceUintOverflowError::Create,
-                    OpcodeId::CREATE2 => NonceUintOverflowError::Create2,
-                    op => unreachable!("Nonce Uint overflow error unexpected for opcode: {:?}", op),
-                })));
+            if account.nonce == u64::MAX && matches!(step.op, OpcodeId::CREATE | OpcodeId::CREATE2)
+            {
+                return Ok(Some(ExecError::NonceUintOverflow(
+                    NonceUintOverflowError::try_from(&step.op).map_err(|_| {
+                        Error::InvalidGethExecStep(
+                            "nonce uint overflow error unexpected for opcode",
+                            Box::new(step.clone()),
+                        )
+                    })?,
+                )));
             }
 
             // Address collision
@@ -2178,3 +2178,164 @@ fn test_gen_access_trace_create_push_call_stack() {
         }
     )
 }
+
+#[test]
+fn handle_tx_streaming_matches_handle_tx() {
+    let code_a = bytecode! {
+        PUSH1(0x0) // retLength
+        PUSH1(0x0) // retOffset
+        PUSH1(0x0) // argsLength
+        PUSH1(0x0) // argsOffset
+        PUSH1(0x0) // value
+        PUSH32(*WORD_ADDR_B) // addr
+        PUSH32(0x1_0000) // gas
+        CALL
+        PUSH2(0xaa)
+    };
+    let code_b = bytecode! {
+        PUSH1(0x01) // value
+        PUSH1(0x02) // key
+        SSTORE
+        STOP
+    };
+
+    let block: GethData = TestContext::<3, 1>::new(
+        None,
+        |accs| {
+            accs[0].address(*ADDR_A).code(code_a);
+            accs[1].address(*ADDR_B).code(code_b);
+            accs[2]
+                .address(address!("0x000000000000000000000000000000000cafe002"))
+                .balance(Word::from(1u64 << 30));
+        },
+        |mut txs, accs| {
+            txs[0].to(accs[0].address).from(accs[2].address);
+        },
+        |block, _tx| block.number(0xcafeu64),
+    )
+    .unwrap()
+    .into();
+
+    check_handle_tx_streaming(block);
+}
+
+#[test]
+fn handle_tx_streaming_matches_handle_tx_with_memory() {
+    let code = bytecode! {
+        PUSH32(word!("0x0102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f20"))
+        PUSH1(0x10)
+        MSTORE
+        PUSH1(0x20)
+        PUSH1(0x08)
+        MLOAD
+        PUSH1(0x40)
+        MSTORE8
+        PUSH1(0x40)
+        PUSH1(0x00)
+        RETURN
+    };
+
+    // The memory snapshots of the trace are checked against the memory of the
+    // call context in both paths.
+    let block: GethData = TestContext::<2, 1>::new_with_logger_config(
+        None,
+        account_0_code_account_1_no_code(code),
+        tx_from_1_to_0,
+        |block, _tx| block.number(0xcafeu64),
+        LoggerConfig::enable_memory(),
+    )
+    .unwrap()
+    .into();
+    assert!(block.geth_traces[0]
+        .struct_logs
+        .iter()
+        .any(|step| !step.memory.is_empty()));
+
+    check_handle_tx_streaming(block);
+}
+
+/// Handle the single transaction of `block` with `handle_tx` and with
+/// `handle_tx_streaming`, and check that both generate the same operations.
+fn check_handle_tx_streaming(block: GethData) {
+    let mut builder =
+        crate::mock::BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+    builder
+        .begin_handle_block(&block.eth_block, &block.geth_traces)
+        .unwrap();
+
+    let mut streaming_builder =
+        crate::mock::BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+    let geth_trace = &block.geth_traces[0];
+    streaming_builder
+        .handle_tx_streaming(
+            &block.eth_block.transactions[0],
+            geth_trace.failed,
+            || geth_trace.struct_logs.iter().cloned().map(Ok),
+            true,
+            1,
+        )
+        .unwrap();
+    streaming_builder.set_value_ops_call_context_rwc_eor();
+
+    assert_eq!(
+        streaming_builder.block.txs()[0].calls().len(),
+        builder.block.txs()[0].calls().len()
+    );
+    assert_eq!(
+        streaming_builder.block.txs()[0].steps().len(),
+        builder.block.txs()[0].steps().len()
+    );
+    assert_eq!(streaming_builder.block_ctx.rwc, builder.block_ctx.rwc);
+    assert_eq!(streaming_builder.block.container, builder.block.container);
+}
+
+#[test]
+fn handle_tx_missing_out_of_gas_step() {
+    // The transaction runs out of gas at its last opcode, which some tracers
+    // don't log.
+    let code = bytecode! {
+        PUSH1(1)
+        PUSH1(2)
+        ADD
+    };
+    let block: GethData = TestContext::<2, 1>::new(
+        None,
+        account_0_code_account_1_no_code(code),
+        |mut txs, accs| {
+            txs[0]
+                .from(accs[1].address)
+                .to(accs[0].address)
+                .gas(Word::from(
+                    21_000 + 2 * OpcodeId::PUSH1.constant_gas_cost() + 2,
+                ));
+        },
+        |block, _tx| block,
+    )
+    .unwrap()
+    .into();
+    let last_step = block.geth_traces[0].struct_logs.last().unwrap();
+    assert_eq!(last_step.op, OpcodeId::ADD);
+    assert_eq!(last_step.error.as_deref(), Some(GETH_ERR_OUT_OF_GAS));
+
+    let mut truncated_block = block.clone();
+    truncated_block.geth_traces[0].struct_logs.pop();
+
+    let mut builder =
+        crate::mock::BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+    builder
+        .begin_handle_block(&block.eth_block, &block.geth_traces)
+        .unwrap();
+    let mut truncated_builder = crate::mock::BlockData::new_from_geth_data(truncated_block.clone())
+        .new_circuit_input_builder();
+    truncated_builder
+        .begin_handle_block(&truncated_block.eth_block, &truncated_block.geth_traces)
+        .unwrap();
+
+    assert_eq!(
+        format!("{:?}", truncated_builder.block.txs()[0].steps()),
+        format!("{:?}", builder.block.txs()[0].steps())
+    );
+    assert_eq!(truncated_builder.block.container, builder.block.container);
+
+    check_handle_tx_streaming(truncated_block);
+}
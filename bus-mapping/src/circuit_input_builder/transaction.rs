@@ -1,17 +1,135 @@
 //! Transaction & TransactionContext utility module.
 
-use std::collections::BTreeMap;
+use std::{borrow::Borrow, collections::BTreeMap};
 
-use eth_types::{evm_types::Memory, geth_types, GethExecTrace};
+use eth_types::{
+    evm_types::{Memory, OpcodeId},
+    geth_types, GethExecStep, GethExecTrace, Word,
+};
 use ethers_core::utils::get_contract_address;
+use serde::{Deserialize, Serialize};
 
 use crate::{
+    geth_errors::GETH_ERR_OUT_OF_GAS,
     state_db::{CodeDB, StateDB},
     Error,
 };
 
 use super::{call::ReversionGroup, Call, CallContext, CallKind, CodeSource, ExecStep};
 
+/// Iterate over the steps of a geth trace to inspect and collect each call's
+/// is_success, which is at the top of stack at the step after a call.  Only
+/// two consecutive steps are inspected at a time, so `struct_logs` can be
+/// streamed.  The first element of the result belongs to the root call.
+pub(crate) fn calls_is_success<S: Borrow<GethExecStep>>(
+    failed: bool,
+    struct_logs: impl IntoIterator<Item = S>,
+) -> Result<Vec<bool>, Error> {
+    let mut call_is_success_map = BTreeMap::new();
+    let mut call_indices = Vec::new();
+    let mut struct_logs = struct_logs.into_iter();
+    let mut geth_step = struct_logs.next();
+    let mut index = 0;
+    while let Some(step) = geth_step {
+        let geth_next_step = struct_logs.next();
+        if let Some(next_step) = geth_next_step.as_ref() {
+            let (step, next_step) = (step.borrow(), next_step.borrow());
+            // Dive into call
+            if step.depth + 1 == next_step.depth {
+                call_indices.push(index);
+            // Emerge from call
+            } else if step.depth - 1 == next_step.depth {
+                let is_success = !next_step.stack.last()?.is_zero();
+                call_is_success_map.insert(call_indices.pop().unwrap(), is_success);
+            // Callee with empty code
+            } else if CallKind::try_from(step.op).is_ok() {
+                let is_success = !next_step.stack.last()?.is_zero();
+                call_is_success_map.insert(index, is_success);
+            }
+        }
+        geth_step = geth_next_step;
+        index += 1;
+    }
+
+    Ok(std::iter::once(!failed)
+        .chain(call_is_success_map.into_values())
+        .collect())
+}
+
+/// Return the step missing at the end of the trace of a failed transaction
+/// when the tracer doesn't log the opcode at which the root call runs out of
+/// gas, so that the last logged step (`last_step`) is a successful step which
+/// doesn't halt.  The missing step is derived from the last logged one, which
+/// must only move values on the stack (PUSH, DUP, SWAP, POP or JUMPDEST), and
+/// the opcode following it in the root call `code` must run out of its
+/// constant gas.
+pub(crate) fn missing_out_of_gas_step(
+    failed: bool,
+    last_step: Option<&GethExecStep>,
+    code: &[u8],
+) -> Result<Option<GethExecStep>, Error> {
+    let last_step = match last_step {
+        Some(step) if failed && step.depth == 1 && step.error.is_none() => step,
+        _ => return Ok(None),
+    };
+    let op = last_step.op;
+    if !(op.is_push()
+        || op.is_dup()
+        || op.is_swap()
+        || matches!(op, OpcodeId::POP | OpcodeId::JUMPDEST))
+    {
+        return Ok(None);
+    }
+
+    let mut stack = last_step.stack.clone();
+    let pc = last_step.pc as usize;
+    if op.is_push() {
+        // The bytes of the immediate beyond the code are zero.
+        let mut value = [0u8; 32];
+        for (i, byte) in value[32 - op.data_len()..].iter_mut().enumerate() {
+            *byte = code.get(pc + 1 + i).copied().unwrap_or_default();
+        }
+        stack.0.push(Word::from_big_endian(&value));
+    } else if op.is_dup() {
+        stack.0.push(
+            last_step
+                .stack
+                .nth_last(op.postfix().unwrap() as usize - 1)?,
+        );
+    } else if op.is_swap() {
+        let nth = op.postfix().unwrap() as usize;
+        // Check the stack depth before swapping the values.
+        stack.nth_last(nth)?;
+        let len = stack.0.len();
+        stack.0.swap(len - 1, len - 1 - nth);
+    } else if op == OpcodeId::POP {
+        stack.0.pop();
+    }
+
+    let next_pc = pc + 1 + op.data_len();
+    let next_op = OpcodeId::from(code.get(next_pc).copied().unwrap_or_default());
+    let gas = last_step.gas - last_step.gas_cost;
+    if gas >= next_op.constant_gas_cost() {
+        return Err(Error::InvalidGethExecStep(
+            "failed transaction ending without running out of gas",
+            Box::new(last_step.clone()),
+        ));
+    }
+
+    Ok(Some(GethExecStep {
+        pc: next_pc as u64,
+        op: next_op,
+        gas,
+        gas_cost: next_op.constant_gas_cost(),
+        refund: last_step.refund,
+        depth: last_step.depth,
+        error: Some(GETH_ERR_OUT_OF_GAS.to_string()),
+        stack,
+        memory: last_step.memory.clone(),
+        storage: last_step.storage.clone(),
+    }))
+}
+
 #[derive(Debug, Default)]
 /// Context of a [`Transaction`] which can mutate in an [`ExecStep`].
 pub struct TransactionContext {
@@ -40,33 +158,17 @@ impl TransactionContext {
         geth_trace: &GethExecTrace,
         is_last_tx: bool,
     ) -> Result<Self, Error> {
-        // Iterate over geth_trace to inspect and collect each call's is_success, which
-        // is at the top of stack at the step after a call.
-        let call_is_success = {
-            let mut call_is_success_map = BTreeMap::new();
-            let mut call_indices = Vec::new();
-            for (index, geth_step) in geth_trace.struct_logs.iter().enumerate() {
-                if let Some(geth_next_step) = geth_trace.struct_logs.get(index + 1) {
-                    // Dive into call
-                    if geth_step.depth + 1 == geth_next_step.depth {
-                        call_indices.push(index);
-                    // Emerge from call
-                    } else if geth_step.depth - 1 == geth_next_step.depth {
-                        let is_success = !geth_next_step.stack.last()?.is_zero();
-                        call_is_success_map.insert(call_indices.pop().unwrap(), is_success);
-                    // Callee with empty code
-                    } else if CallKind::try_from(geth_step.op).is_ok() {
-                        let is_success = !geth_next_step.stack.last()?.is_zero();
-                        call_is_success_map.insert(index, is_success);
-                    }
-                }
-            }
-
-            std::iter::once(!geth_trace.failed)
-                .chain(call_is_success_map.into_values())
-                .collect()
-        };
+        let call_is_success = calls_is_success(geth_trace.failed, geth_trace.struct_logs.iter())?;
+        Self::new_with_calls_is_success(eth_tx, call_is_success, is_last_tx)
+    }
 
+    /// Create a new Self from the `is_success` of each call of the
+    /// transaction, as returned by [`calls_is_success`].
+    pub(crate) fn new_with_calls_is_success(
+        eth_tx: &eth_types::Transaction,
+        call_is_success: Vec<bool>,
+        is_last_tx: bool,
+    ) -> Result<Self, Error> {
         let mut tx_ctx = Self {
             id: eth_tx
                 .transaction_index
@@ -176,7 +278,7 @@ impl TransactionContext {
     }
 }
 
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 /// Result of the parsing of an Ethereum Transaction.
 pub struct Transaction {
     /// The transaction id
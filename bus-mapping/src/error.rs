@@ -2,12 +2,21 @@
 
 use core::fmt::{Display, Formatter, Result as FmtResult};
 use eth_types::{evm_types::OpcodeId, Address, GethExecStep, Word, H256};
+#[cfg(feature = "rpc")]
 use ethers_providers::ProviderError;
+use serde::{Deserialize, Serialize};
 use std::error::Error as StdError;
 
-use crate::geth_errors::{
-    GETH_ERR_GAS_UINT_OVERFLOW, GETH_ERR_OUT_OF_GAS, GETH_ERR_STACK_OVERFLOW,
-    GETH_ERR_STACK_UNDERFLOW,
+use crate::{
+    circuit_input_builder::ExecState,
+    geth_errors::{
+        GETH_ERR_CODE_STORE_OUT_OF_GAS, GETH_ERR_CONTRACT_ADDRESS_COLLISION, GETH_ERR_DEPTH,
+        GETH_ERR_EXECUTION_REVERTED, GETH_ERR_GAS_UINT_OVERFLOW, GETH_ERR_INSUFFICIENT_BALANCE,
+        GETH_ERR_INVALID_CODE, GETH_ERR_INVALID_JUMP, GETH_ERR_INVALID_OPCODE,
+        GETH_ERR_MAX_CODE_SIZE_EXCEEDED, GETH_ERR_NONCE_UINT_OVERFLOW, GETH_ERR_OUT_OF_GAS,
+        GETH_ERR_RETURN_DATA_OUT_OF_BOUNDS, GETH_ERR_STACK_OVERFLOW, GETH_ERR_STACK_UNDERFLOW,
+        GETH_ERR_WRITE_PROTECTION,
+    },
 };
 
 /// Error type for any BusMapping related failure.
@@ -16,6 +25,7 @@ pub enum Error {
     /// Serde de/serialization error.
     SerdeError(serde_json::error::Error),
     /// JSON-RPC related error.
+    #[cfg(feature = "rpc")]
     JSONRpcError(ProviderError),
     /// OpcodeId is not a call type.
     OpcodeIdNotCallType,
@@ -40,6 +50,10 @@ pub enum Error {
     ExecutionError(ExecError),
     /// Internal Code error
     InternalError(&'static str),
+    /// The memory is accessed byte by byte, for instance by a copy event, in
+    /// [`MemoryOpMode::Word`](crate::operation::MemoryOpMode::Word), which
+    /// only supports the word accesses of MLOAD, MSTORE and MSTORE8.
+    ByteMemoryAccessInWordMode(ExecState),
 }
 
 impl From<eth_types::Error> for Error {
@@ -48,6 +62,7 @@ impl From<eth_types::Error> for Error {
     }
 }
 
+#[cfg(feature = "rpc")]
 impl From<ProviderError> for Error {
     fn from(err: ProviderError) -> Self {
         Error::JSONRpcError(err)
@@ -63,7 +78,7 @@ impl Display for Error {
 impl StdError for Error {}
 
 /// Out of Gas errors by opcode
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub enum OogError {
     /// Out of Gas for opcodes which have non-zero constant gas cost
     Constant,
@@ -132,7 +147,7 @@ impl From<&OpcodeId> for OogError {
 }
 
 /// Insufficient balance errors by opcode/state.
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub enum InsufficientBalanceError {
     /// Insufficient balance during CALL/CALLCODE opcode.
     Call,
@@ -142,8 +157,22 @@ pub enum InsufficientBalanceError {
     Create2,
 }
 
+// Given OpCodeId, returns correponding InsufficientBalanceError.
+impl TryFrom<&OpcodeId> for InsufficientBalanceError {
+    type Error = ();
+
+    fn try_from(op: &OpcodeId) -> Result<Self, Self::Error> {
+        match op {
+            OpcodeId::CALL | OpcodeId::CALLCODE => Ok(InsufficientBalanceError::Call),
+            OpcodeId::CREATE => Ok(InsufficientBalanceError::Create),
+            OpcodeId::CREATE2 => Ok(InsufficientBalanceError::Create2),
+            _ => Err(()),
+        }
+    }
+}
+
 /// Nonce uint overflow errors by opcode/state.
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub enum NonceUintOverflowError {
     /// Nonce uint overflow during CREATE opcode.
     Create,
@@ -151,10 +180,23 @@ pub enum NonceUintOverflowError {
     Create2,
 }
 
+// Given OpCodeId, returns correponding NonceUintOverflowError.
+impl TryFrom<&OpcodeId> for NonceUintOverflowError {
+    type Error = ();
+
+    fn try_from(op: &OpcodeId) -> Result<Self, Self::Error> {
+        match op {
+            OpcodeId::CREATE => Ok(NonceUintOverflowError::Create),
+            OpcodeId::CREATE2 => Ok(NonceUintOverflowError::Create2),
+            _ => Err(()),
+        }
+    }
+}
+
 /// Call depth errors by opcode/state.
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub enum DepthError {
-    /// Call depth errors in CALL/CALLCODE opcode.
+    /// Call depth errors in CALL/CALLCODE/DELEGATECALL/STATICCALL opcode.
     Call,
     /// Call depth errors in CREATE opcode.
     Create,
@@ -162,8 +204,24 @@ pub enum DepthError {
     Create2,
 }
 
+// Given OpCodeId, returns correponding DepthError.
+impl TryFrom<&OpcodeId> for DepthError {
+    type Error = ();
+
+    fn try_from(op: &OpcodeId) -> Result<Self, Self::Error> {
+        match op {
+            OpcodeId::CALL | OpcodeId::CALLCODE | OpcodeId::DELEGATECALL | OpcodeId::STATICCALL => {
+                Ok(DepthError::Call)
+            }
+            OpcodeId::CREATE => Ok(DepthError::Create),
+            OpcodeId::CREATE2 => Ok(DepthError::Create2),
+            _ => Err(()),
+        }
+    }
+}
+
 /// EVM Execution Error
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub enum ExecError {
     /// Invalid Opcode
     InvalidOpcode,
@@ -197,26 +255,193 @@ pub enum ExecError {
     NonceUintOverflow(NonceUintOverflowError),
 }
 
-// Returns a GethExecStep's error if present, else return the empty error.
-impl TryFrom<&GethExecStep> for ExecError {
-    type Error = ();
-
-    fn try_from(step: &GethExecStep) -> Result<Self, Self::Error> {
-        Ok(match step.error.as_ref().ok_or(())?.as_str() {
+impl ExecError {
+    /// Returns the error of a [`GethExecStep`] if present, else `None`.
+    /// Errors which are not an exceptional halt of the step (like a REVERT)
+    /// also return `None`, while an unknown error, or one which doesn't apply
+    /// to the step's opcode, is an invalid step.
+    pub fn from_geth_step(step: &GethExecStep) -> Result<Option<Self>, Error> {
+        let invalid = |msg| Error::InvalidGethExecStep(msg, Box::new(step.clone()));
+        let error = match step.error.as_ref() {
+            Some(error) => error.as_str(),
+            None => return Ok(None),
+        };
+        Ok(Some(match error {
             GETH_ERR_OUT_OF_GAS | GETH_ERR_GAS_UINT_OVERFLOW => {
                 // NOTE: We report a GasUintOverflow error as an OutOfGas error
                 let oog_err = OogError::from(&step.op);
                 ExecError::OutOfGas(oog_err)
             }
+            GETH_ERR_CODE_STORE_OUT_OF_GAS => ExecError::CodeStoreOutOfGas,
+            GETH_ERR_INVALID_JUMP => ExecError::InvalidJump,
+            GETH_ERR_WRITE_PROTECTION => ExecError::WriteProtection,
+            GETH_ERR_DEPTH => ExecError::Depth(
+                DepthError::try_from(&step.op)
+                    .map_err(|_| invalid("depth error unexpected for opcode"))?,
+            ),
+            GETH_ERR_INSUFFICIENT_BALANCE => ExecError::InsufficientBalance(
+                InsufficientBalanceError::try_from(&step.op)
+                    .map_err(|_| invalid("insufficient balance error unexpected for opcode"))?,
+            ),
+            GETH_ERR_NONCE_UINT_OVERFLOW => ExecError::NonceUintOverflow(
+                NonceUintOverflowError::try_from(&step.op)
+                    .map_err(|_| invalid("nonce uint overflow error unexpected for opcode"))?,
+            ),
+            GETH_ERR_CONTRACT_ADDRESS_COLLISION => ExecError::ContractAddressCollision,
+            GETH_ERR_MAX_CODE_SIZE_EXCEEDED => ExecError::MaxCodeSizeExceeded,
+            GETH_ERR_INVALID_CODE => ExecError::InvalidCreationCode,
+            GETH_ERR_RETURN_DATA_OUT_OF_BOUNDS => ExecError::ReturnDataOutOfBounds,
+            GETH_ERR_EXECUTION_REVERTED => return Ok(None),
             error => {
                 if error.starts_with(GETH_ERR_STACK_OVERFLOW) {
                     ExecError::StackOverflow
                 } else if error.starts_with(GETH_ERR_STACK_UNDERFLOW) {
                     ExecError::StackUnderflow
+                } else if error.starts_with(GETH_ERR_INVALID_OPCODE) {
+                    ExecError::InvalidOpcode
                 } else {
-                    panic!("Unknown GethExecStep.error: {}", error);
+                    return Err(invalid("unknown GethExecStep.error"));
                 }
             }
-        })
+        }))
+    }
+}
+
+#[cfg(test)]
+mod geth_errors_tests {
+    use super::*;
+    use eth_types::evm_types::{Memory, Stack, Storage};
+
+    fn step_with_error(op: OpcodeId, error: &str) -> GethExecStep {
+        GethExecStep {
+            pc: 0,
+            op,
+            gas: 0,
+            gas_cost: 0,
+            refund: 0,
+            depth: 1,
+            error: Some(error.to_string()),
+            stack: Stack::new(),
+            memory: Memory::default(),
+            storage: Storage::empty(),
+        }
+    }
+
+    #[test]
+    fn geth_error_to_exec_error() {
+        for (op, error, expected) in [
+            (
+                OpcodeId::SLOAD,
+                "out of gas",
+                Some(ExecError::OutOfGas(OogError::SloadSstore)),
+            ),
+            (
+                OpcodeId::MSTORE,
+                "gas uint64 overflow",
+                Some(ExecError::OutOfGas(OogError::StaticMemoryExpansion)),
+            ),
+            (
+                OpcodeId::PUSH1,
+                "stack limit reached 1024 (1023)",
+                Some(ExecError::StackOverflow),
+            ),
+            (
+                OpcodeId::ADD,
+                "stack underflow (0 <=> 2)",
+                Some(ExecError::StackUnderflow),
+            ),
+            (
+                OpcodeId::INVALID(0xfe),
+                "invalid opcode: INVALID",
+                Some(ExecError::InvalidOpcode),
+            ),
+            (
+                OpcodeId::JUMP,
+                "invalid jump destination",
+                Some(ExecError::InvalidJump),
+            ),
+            (
+                OpcodeId::SSTORE,
+                "write protection",
+                Some(ExecError::WriteProtection),
+            ),
+            (
+                OpcodeId::CREATE2,
+                "max call depth exceeded",
+                Some(ExecError::Depth(DepthError::Create2)),
+            ),
+            (
+                OpcodeId::CALL,
+                "insufficient balance for transfer",
+                Some(ExecError::InsufficientBalance(
+                    InsufficientBalanceError::Call,
+                )),
+            ),
+            (
+                OpcodeId::CREATE,
+                "nonce uint64 overflow",
+                Some(ExecError::NonceUintOverflow(NonceUintOverflowError::Create)),
+            ),
+            (
+                OpcodeId::RETURN,
+                "max code size exceeded",
+                Some(ExecError::MaxCodeSizeExceeded),
+            ),
+            (
+                OpcodeId::RETURNDATACOPY,
+                "return data out of bounds",
+                Some(ExecError::ReturnDataOutOfBounds),
+            ),
+            (OpcodeId::REVERT, "execution reverted", None),
+            (
+                OpcodeId::DELEGATECALL,
+                "max call depth exceeded",
+                Some(ExecError::Depth(DepthError::Call)),
+            ),
+            (
+                OpcodeId::STATICCALL,
+                "max call depth exceeded",
+                Some(ExecError::Depth(DepthError::Call)),
+            ),
+        ] {
+            assert_eq!(
+                ExecError::from_geth_step(&step_with_error(op, error)).unwrap(),
+                expected,
+                "{:?}: {}",
+                op,
+                error
+            );
+        }
+    }
+
+    #[test]
+    fn invalid_geth_error() {
+        for (op, error) in [
+            (OpcodeId::ADD, "max call depth exceeded"),
+            (OpcodeId::STATICCALL, "insufficient balance for transfer"),
+            (OpcodeId::CALL, "nonce uint64 overflow"),
+            (OpcodeId::ADD, "unknown error"),
+        ] {
+            assert!(
+                matches!(
+                    ExecError::from_geth_step(&step_with_error(op, error)),
+                    Err(Error::InvalidGethExecStep(..))
+                ),
+                "{:?}: {}",
+                op,
+                error
+            );
+        }
+    }
+
+    #[test]
+    fn oog_constant_iff_no_dynamic_gas_cost() {
+        for op in OpcodeId::valid_opcodes() {
+            assert_eq!(
+                OogError::from(&op) == OogError::Constant,
+                !op.has_dynamic_gas_cost(),
+                "{op:?}"
+            );
+        }
     }
 }
@@ -327,13 +327,16 @@ fn fn_gen_error_state_associated_ops(error: &ExecError) -> Option<FnGenAssociate
 }
 #[allow(clippy::collapsible_else_if)]
 /// Generate the associated operations according to the particular
-/// [`OpcodeId`].
+/// [`OpcodeId`].  `memory_enabled` tells whether the trace of the transaction
+/// has memory snapshots, in which case the memory of the call context is
+/// checked against the one of the step.
 pub fn gen_associated_ops(
     opcode_id: &OpcodeId,
     state: &mut CircuitInputStateRef,
     geth_steps: &[GethExecStep],
+    memory_enabled: bool,
 ) -> Result<Vec<ExecStep>, Error> {
-    let memory_enabled = !geth_steps.iter().all(|s| s.memory.is_empty());
+    let _span = tracing::trace_span!("opcode", op = ?opcode_id, pc = geth_steps[0].pc).entered();
     if memory_enabled {
         assert_eq!(
             &state.call_ctx()?.memory,
@@ -343,6 +346,22 @@ pub fn gen_associated_ops(
         );
     }
 
+    if geth_steps[0].error.is_none() {
+        if !state.block.chain_config.is_opcode_enabled(*opcode_id) {
+            return Err(Error::InvalidGethExecStep(
+                "opcode not enabled by the chain config",
+                Box::new(geth_steps[0].clone()),
+            ));
+        }
+        // Opcodes enabled by the chain config that the circuits don't support yet, like PUSH0.
+        if matches!(opcode_id, OpcodeId::INVALID(_)) {
+            return Err(Error::InvalidGethExecStep(
+                "opcode enabled by the chain config is not supported",
+                Box::new(geth_steps[0].clone()),
+            ));
+        }
+    }
+
     // check if have error
     let geth_step = &geth_steps[0];
     let mut exec_step = state.new_step(geth_step)?;
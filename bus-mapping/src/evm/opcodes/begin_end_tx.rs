@@ -1,14 +1,13 @@
 use super::TxExecSteps;
 use crate::{
-    circuit_input_builder::{CircuitInputStateRef, ExecState, ExecStep},
+    circuit_input_builder::{
+        fee_policy_begin_tx, fee_policy_end_tx, CircuitInputStateRef, ExecState, ExecStep,
+    },
     operation::{AccountField, AccountOp, CallContextField, TxReceiptField, TxRefundOp, RW},
     state_db::CodeDB,
     Error,
 };
-use eth_types::{
-    evm_types::{GasCost, MAX_REFUND_QUOTIENT_OF_GAS_USED},
-    evm_unimplemented, ToWord, Word,
-};
+use eth_types::{evm_types::GasCost, evm_unimplemented, ToWord, Word};
 use ethers_core::utils::get_contract_address;
 
 #[derive(Clone, Copy, Debug)]
@@ -60,7 +59,11 @@ fn gen_begin_tx_steps(state: &mut CircuitInputStateRef) -> Result<ExecStep, Erro
     )?;
 
     // Add caller, callee and coinbase (for EIP-3651) to access list.
-    for address in [call.caller_address, call.address, state.block.coinbase] {
+    let mut warm_addresses = vec![call.caller_address, call.address];
+    if state.block.chain_config.is_coinbase_warm() {
+        warm_addresses.push(state.block.coinbase);
+    }
+    for address in warm_addresses {
         let is_warm_prev = !state.sdb.add_account_to_access_list(address);
         state.tx_accesslist_account_write(
             &mut exec_step,
@@ -71,12 +74,13 @@ fn gen_begin_tx_steps(state: &mut CircuitInputStateRef) -> Result<ExecStep, Erro
         )?;
     }
 
-    let init_code_gas_cost = if state.tx.is_create() {
-        // Calculate gas cost of init code for EIP-3860.
-        (state.tx.call_data.len() as u64 + 31) / 32 * eth_types::evm_types::INIT_CODE_WORD_GAS
-    } else {
-        0
-    };
+    let init_code_gas_cost =
+        if state.tx.is_create() && state.block.chain_config.is_init_code_metered() {
+            // Calculate gas cost of init code for EIP-3860.
+            (state.tx.call_data.len() as u64 + 31) / 32 * eth_types::evm_types::INIT_CODE_WORD_GAS
+        } else {
+            0
+        };
 
     let intrinsic_gas_cost = if state.tx.is_create() {
         GasCost::CREATION_TX
@@ -117,6 +121,8 @@ fn gen_begin_tx_steps(state: &mut CircuitInputStateRef) -> Result<ExecStep, Erro
         Some(state.tx.gas_price * state.tx.gas()),
     )?;
 
+    fee_policy_begin_tx(state, &mut exec_step)?;
+
     // In case of contract creation we wish to verify the correctness of the
     // contract's address (callee). This address is defined as:
     //
@@ -255,8 +261,10 @@ fn gen_end_tx_steps(state: &mut CircuitInputStateRef) -> Result<ExecStep, Error>
         },
     );
 
-    let effective_refund =
-        refund.min((state.tx.gas() - exec_step.gas_left) / MAX_REFUND_QUOTIENT_OF_GAS_USED as u64);
+    let effective_refund = refund.min(
+        (state.tx.gas() - exec_step.gas_left)
+            / state.block.chain_config.max_refund_quotient_of_gas_used(),
+    );
     let (found, caller_account) = state.sdb.get_account(&call.caller_address);
     if !found {
         return Err(Error::AccountNotFound(call.caller_address));
@@ -288,6 +296,8 @@ fn gen_end_tx_steps(state: &mut CircuitInputStateRef) -> Result<ExecStep, Error>
         coinbase_balance_prev,
     )?;
 
+    fee_policy_end_tx(state, &mut exec_step)?;
+
     // handle tx receipt tag
     state.tx_receipt_write(
         &mut exec_step,
@@ -29,7 +29,7 @@ impl Opcode for Calldatacopy {
         memory.copy_from(memory_offset, data_offset, length, &call_ctx.call_data);
 
         let copy_event = gen_copy_event(state, geth_step, &mut exec_steps[0])?;
-        state.push_copy(&mut exec_steps[0], copy_event);
+        state.push_copy(&mut exec_steps[0], copy_event)?;
         Ok(exec_steps)
     }
 }
@@ -70,6 +70,9 @@ impl Opcode for Calldataload {
                 state.call_ctx()?.call_data.to_vec(),
             );
 
+            if !is_root && src_addr < src_addr_end {
+                state.check_byte_memory_access(&exec_step)?;
+            }
             let calldata: Vec<_> = (0..32)
                 .map(|idx| {
                     let addr = src_addr.checked_add(idx).unwrap_or(src_addr_end);
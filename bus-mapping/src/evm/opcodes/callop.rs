@@ -202,20 +202,18 @@ impl<const N_ARGS: usize> Opcode for CallOpcode<N_ARGS> {
         let has_value = !call.value.is_zero() && !call.is_delegatecall();
         let memory_expansion_gas_cost =
             memory_expansion_gas_cost(curr_memory_word_size, next_memory_word_size);
-        let gas_cost = if is_warm {
-            GasCost::WARM_ACCESS
-        } else {
-            GasCost::COLD_ACCOUNT_ACCESS
-        } + if has_value {
-            GasCost::CALL_WITH_VALUE
-                + if call.kind == CallKind::Call && !callee_exists {
-                    GasCost::NEW_ACCOUNT
-                } else {
-                    0
-                }
-        } else {
-            0
-        } + memory_expansion_gas_cost;
+        let gas_cost = state.block.chain_config.account_access_gas(is_warm)
+            + if has_value {
+                GasCost::CALL_WITH_VALUE
+                    + if call.kind == CallKind::Call && !callee_exists {
+                        GasCost::NEW_ACCOUNT
+                    } else {
+                        0
+                    }
+            } else {
+                0
+            }
+            + memory_expansion_gas_cost;
         let gas_specified = geth_step.stack.last()?;
         let callee_gas_left = eip150_gas(geth_step.gas - gas_cost, gas_specified);
 
@@ -319,7 +319,7 @@ fn handle_copy(
             log_id: None,
             bytes,
         },
-    );
+    )?;
 
     Ok((initialization_bytes, code_hash))
 }
@@ -35,7 +35,7 @@ impl Opcode for Extcodecopy {
         memory.copy_from(dst_offset, code_offset, length, &code);
 
         let copy_event = gen_copy_event(state, geth_step, &mut exec_steps[0])?;
-        state.push_copy(&mut exec_steps[0], copy_event);
+        state.push_copy(&mut exec_steps[0], copy_event)?;
         Ok(exec_steps)
     }
 }
@@ -20,7 +20,7 @@ impl Opcode for Log {
         let mut exec_step = gen_log_step(state, geth_step)?;
         if state.call()?.is_persistent {
             let copy_event = gen_copy_event(state, geth_step, &mut exec_step)?;
-            state.push_copy(&mut exec_step, copy_event);
+            state.push_copy(&mut exec_step, copy_event)?;
             state.tx_ctx.log_id += 1;
         }
 
@@ -126,6 +126,25 @@ fn gen_log_step(
         }
     }
 
+    if state.call()?.is_persistent {
+        state.tx_log_write(
+            &mut exec_step,
+            state.tx_ctx.id(),
+            state.tx_ctx.log_id + 1,
+            TxLogField::TopicLength,
+            0,
+            topic_count.into(),
+        )?;
+        state.tx_log_write(
+            &mut exec_step,
+            state.tx_ctx.id(),
+            state.tx_ctx.log_id + 1,
+            TxLogField::DataLength,
+            0,
+            msize,
+        )?;
+    }
+
     Ok(exec_step)
 }
 
@@ -394,6 +413,36 @@ mod log_tests {
             { log_topic_ops },
         );
 
+        // topic and data length writes
+        assert_eq!(
+            (1 + topic_count..3 + topic_count)
+                .map(|idx| &builder.block.container.tx_log[idx])
+                .map(|op| (op.rw(), op.op().clone()))
+                .collect::<Vec<(RW, TxLogOp)>>(),
+            vec![
+                (
+                    RW::WRITE,
+                    TxLogOp::new(
+                        1,
+                        step.log_id + 1,
+                        TxLogField::TopicLength,
+                        0,
+                        Word::from(topic_count)
+                    )
+                ),
+                (
+                    RW::WRITE,
+                    TxLogOp::new(
+                        1,
+                        step.log_id + 1,
+                        TxLogField::DataLength,
+                        0,
+                        Word::from(msize)
+                    )
+                ),
+            ],
+        );
+
         // memory reads.
         let mut log_data_ops = Vec::with_capacity(msize);
         assert_eq!(
@@ -426,7 +475,7 @@ mod log_tests {
             },
         );
         assert_eq!(
-            ((1 + topic_count)..msize + 1 + topic_count)
+            ((3 + topic_count)..msize + 3 + topic_count)
                 .map(|idx| &builder.block.container.tx_log[idx])
                 .map(|op| (op.rw(), op.op().clone()))
                 .collect::<Vec<(RW, TxLogOp)>>(),
@@ -1,6 +1,7 @@
 use super::Opcode;
 use crate::{
     circuit_input_builder::{CircuitInputStateRef, ExecStep},
+    operation::MemoryOpMode,
     Error,
 };
 use eth_types::{evm_types::MemoryAddress, GethExecStep, ToBigEndian};
@@ -38,13 +39,22 @@ impl Opcode for Mload {
         //
         state.stack_write(&mut exec_step, stack_position, mem_read_value)?;
 
-        // First mem read -> 32 MemoryOp generated.
-        //
-        for byte in mem_read_value.to_be_bytes() {
-            state.memory_read(&mut exec_step, mem_read_addr, byte)?;
-
-            // Update mem_read_addr to next byte's one
-            mem_read_addr += MemoryAddress::from(1);
+        match state.block.memory_op_mode {
+            // First mem read -> 32 MemoryOp generated.
+            //
+            MemoryOpMode::Byte => {
+                for byte in mem_read_value.to_be_bytes() {
+                    state.memory_read(&mut exec_step, mem_read_addr, byte)?;
+
+                    // Update mem_read_addr to next byte's one
+                    mem_read_addr += MemoryAddress::from(1);
+                }
+            }
+            // First mem read -> 1 or 2 MemoryWordOp generated.
+            //
+            MemoryOpMode::Word => {
+                state.memory_read_words(&mut exec_step, mem_read_addr, 32)?;
+            }
         }
 
         // reconstruction
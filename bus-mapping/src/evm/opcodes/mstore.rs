@@ -1,6 +1,7 @@
 use super::Opcode;
 use crate::{
     circuit_input_builder::{CircuitInputStateRef, ExecStep},
+    operation::MemoryOpMode,
     Error,
 };
 use eth_types::{evm_types::MemoryAddress, GethExecStep, ToBigEndian, ToLittleEndian};
@@ -31,8 +32,8 @@ impl<const IS_MSTORE8: bool> Opcode for Mstore<IS_MSTORE8> {
         // First mem write -> 32 MemoryOp generated.
         let offset_addr: MemoryAddress = offset.try_into()?;
 
-        match IS_MSTORE8 {
-            true => {
+        match (IS_MSTORE8, state.block.memory_op_mode) {
+            (true, MemoryOpMode::Byte) => {
                 // stack write operation for mstore8
                 state.memory_write(
                     &mut exec_step,
@@ -40,13 +41,20 @@ impl<const IS_MSTORE8: bool> Opcode for Mstore<IS_MSTORE8> {
                     *value.to_le_bytes().first().unwrap(),
                 )?;
             }
-            false => {
+            (false, MemoryOpMode::Byte) => {
                 // stack write each byte for mstore
                 let bytes = value.to_be_bytes();
                 for (i, byte) in bytes.iter().enumerate() {
                     state.memory_write(&mut exec_step, offset_addr.map(|a| a + i), *byte)?;
                 }
             }
+            // write the word(s) containing the stored byte(s)
+            (true, MemoryOpMode::Word) => {
+                state.memory_write_words(&mut exec_step, offset_addr, &value.to_le_bytes()[..1])?;
+            }
+            (false, MemoryOpMode::Word) => {
+                state.memory_write_words(&mut exec_step, offset_addr, &value.to_be_bytes())?;
+            }
         }
 
         // reconstruction
@@ -82,7 +90,7 @@ mod mstore_tests {
     use crate::{
         circuit_input_builder::ExecState,
         mock::BlockData,
-        operation::{MemoryOp, StackOp, RW},
+        operation::{MemoryOp, MemoryWordOp, StackOp, RW},
     };
     use eth_types::{
         bytecode,
@@ -213,4 +221,82 @@ mod mstore_tests {
             (RW::WRITE, &MemoryOp::new(1, MemoryAddress(0x100), 0x34))
         )
     }
+
+    #[test]
+    fn mstore_word_mode_unaligned() {
+        let code = bytecode! {
+            PUSH2(0x1234)
+            PUSH1(0x30)
+            MSTORE
+            PUSH2(0x5678)
+            PUSH1(0x45)
+            MSTORE8
+            STOP
+        };
+
+        let block: GethData = TestContext::<2, 1>::new(
+            None,
+            account_0_code_account_1_no_code(code),
+            tx_from_1_to_0,
+            |block, _tx| block.number(0xcafeu64),
+        )
+        .unwrap()
+        .into();
+
+        let mut builder = BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+        builder.block.memory_op_mode = MemoryOpMode::Word;
+        let builder = builder
+            .handle_block(&block.eth_block, &block.geth_traces)
+            .unwrap();
+        assert!(builder.block.container.memory.is_empty());
+
+        let word_ops = |opcode| {
+            let step = builder.block.txs()[0]
+                .steps()
+                .iter()
+                .find(|step| step.exec_state == ExecState::Op(opcode))
+                .unwrap();
+            step.bus_mapping_instance[2..]
+                .iter()
+                .map(|op_ref| &builder.block.container.memory_word[op_ref.as_usize()])
+                .map(|operation| (operation.rw(), operation.op().clone()))
+                .collect_vec()
+        };
+
+        // The 32 bytes at 0x30 span the words at 0x20 and 0x40, with the
+        // non-zero bytes of the value landing in the second one.
+        assert_eq!(
+            word_ops(OpcodeId::MSTORE),
+            vec![
+                (
+                    RW::WRITE,
+                    MemoryWordOp::new(1, MemoryAddress(0x20), Word::zero(), Word::zero(), 0x10)
+                ),
+                (
+                    RW::WRITE,
+                    MemoryWordOp::new(
+                        1,
+                        MemoryAddress(0x40),
+                        Word::from(0x1234u64) << 128,
+                        Word::zero(),
+                        0x10
+                    )
+                ),
+            ]
+        );
+        // A single byte never crosses a word boundary.
+        assert_eq!(
+            word_ops(OpcodeId::MSTORE8),
+            vec![(
+                RW::WRITE,
+                MemoryWordOp::new(
+                    1,
+                    MemoryAddress(0x40),
+                    (Word::from(0x1234u64) << 128) | (Word::from(0x78u64) << 208),
+                    Word::from(0x1234u64) << 128,
+                    0x05
+                )
+            )]
+        );
+    }
 }
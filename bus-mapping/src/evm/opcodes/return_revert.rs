@@ -196,7 +196,7 @@ fn handle_copy(
             log_id: None,
             bytes,
         },
-    );
+    )?;
 
     Ok(())
 }
@@ -234,18 +234,21 @@ fn handle_create(
             log_id: None,
             bytes,
         },
-    );
+    )?;
 
     Ok(code_hash)
 }
 
 #[cfg(test)]
 mod return_tests {
-    use crate::mock::BlockData;
-    use eth_types::{bytecode, geth_types::GethData, word};
+    use crate::{
+        circuit_input_builder::ExecState, mock::BlockData, operation::MemoryOpMode, Error,
+    };
+    use eth_types::{bytecode, evm_types::OpcodeId, geth_types::GethData, word, Word};
     use mock::{
+        generate_mock_call_bytecode,
         test_ctx::helpers::{account_0_code_account_1_no_code, tx_from_1_to_0},
-        TestContext, MOCK_DEPLOYED_CONTRACT_BYTECODE,
+        MockCallBytecodeParams, TestContext, MOCK_ACCOUNTS, MOCK_DEPLOYED_CONTRACT_BYTECODE,
     };
 
     #[test]
@@ -343,4 +346,51 @@ mod return_tests {
             .handle_block(&block.eth_block, &block.geth_traces)
             .unwrap();
     }
+
+    #[test]
+    fn test_word_mode_return_to_caller() {
+        let (addr_a, addr_b) = (MOCK_ACCOUNTS[0], MOCK_ACCOUNTS[1]);
+
+        // The word written by MSTORE is returned to the memory of the caller
+        // with a copy event, which accesses the memory byte by byte.
+        let code_b = bytecode! {
+            PUSH32(0x1234)
+            PUSH1(0)
+            MSTORE
+            PUSH1(0x20)
+            PUSH1(0)
+            RETURN
+        };
+        let code_a = generate_mock_call_bytecode(MockCallBytecodeParams {
+            address: addr_b,
+            return_data_size: 0x20,
+            ..MockCallBytecodeParams::default()
+        });
+
+        let block: GethData = TestContext::<3, 1>::new(
+            None,
+            |accs| {
+                accs[0].address(addr_b).code(code_b);
+                accs[1].address(addr_a).code(code_a);
+                accs[2]
+                    .address(MOCK_ACCOUNTS[2])
+                    .balance(Word::from(1u64 << 30));
+            },
+            |mut txs, accs| {
+                txs[0].to(accs[1].address).from(accs[2].address);
+            },
+            |block, _tx| block,
+        )
+        .unwrap()
+        .into();
+
+        let mut builder = BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+        builder.block.memory_op_mode = MemoryOpMode::Word;
+        assert!(matches!(
+            builder.handle_block(&block.eth_block, &block.geth_traces),
+            Err(Error::ByteMemoryAccessInWordMode(ExecState::Op(
+                OpcodeId::RETURN
+            )))
+        ));
+    }
 }
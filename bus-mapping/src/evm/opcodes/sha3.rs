@@ -80,7 +80,7 @@ impl Opcode for Sha3 {
                 rw_counter_start,
                 bytes: steps,
             },
-        );
+        )?;
 
         Ok(vec![exec_step])
     }
@@ -72,7 +72,7 @@ mod stackonlyop_tests {
     use itertools::Itertools;
     use mock::{
         test_ctx::{helpers::*, TestContext},
-        MOCK_BASEFEE, MOCK_DIFFICULTY, MOCK_GASLIMIT,
+        MOCK_BASEFEE, MOCK_GASLIMIT,
     };
     use pretty_assertions::assert_eq;
     use std::ops::{BitOr, BitXor};
@@ -388,7 +388,9 @@ mod stackonlyop_tests {
                 STOP
             },
             vec![],
-            vec![StackOp::new(1, StackAddress(1023), *MOCK_DIFFICULTY)],
+            // After the merge DIFFICULTY returns the mix hash of the block, which
+            // is zero in the mock block
+            vec![StackOp::new(1, StackAddress(1023), Word::zero())],
         );
     }
 
@@ -1,9 +1,10 @@
 //! This module contains the logic for parsing and interacting with EVM
 //! execution traces.
 use crate::operation::Target;
+use serde::{Deserialize, Serialize};
 use std::fmt;
 
-#[derive(Clone, Copy, PartialEq, Eq)]
+#[derive(Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 /// The target and index of an `Operation` in the context of an
 /// `ExecutionTrace`.
 pub struct OperationRef(pub Target, pub usize);
@@ -24,6 +25,7 @@ impl fmt::Debug for OperationRef {
                 Target::CallContext => "CallContext",
                 Target::TxReceipt => "TxReceipt",
                 Target::TxLog => "TxLog",
+                Target::MemoryWord => "MemoryWord",
             },
             self.1
         ))
@@ -225,6 +225,7 @@
 extern crate alloc;
 extern crate core;
 
+pub mod chain_config;
 pub mod circuit_input_builder;
 pub mod error;
 pub mod evm;
@@ -233,6 +234,7 @@ pub(crate) mod geth_errors;
 pub mod mock;
 pub mod operation;
 pub mod precompile;
+#[cfg(feature = "rpc")]
 pub mod rpc;
 pub mod state_db;
 pub use error::Error;
@@ -8,6 +8,7 @@ pub use container::OperationContainer;
 pub use eth_types::evm_types::{MemoryAddress, StackAddress};
 use gadgets::impl_expr;
 use halo2_proofs::plonk::Expression;
+use serde::{Deserialize, Serialize};
 use strum_macros::EnumIter;
 
 use core::{cmp::Ordering, fmt, fmt::Debug};
@@ -38,7 +39,7 @@ impl RW {
 /// Wrapper type over `usize` which represents the global counter. The purpose
 /// of the `RWCounter` is to enforce that each Opcode/Instruction and Operation
 /// is unique and just executed once.
-#[derive(Clone, Copy, Eq, PartialEq, PartialOrd, Ord)]
+#[derive(Clone, Copy, Eq, PartialEq, PartialOrd, Ord, Serialize, Deserialize)]
 pub struct RWCounter(pub usize);
 
 impl fmt::Debug for RWCounter {
@@ -91,7 +92,7 @@ impl RWCounter {
 }
 
 /// Enum used to differenciate between EVM Stack, Memory and Storage operations.
-#[derive(Debug, Clone, PartialEq, Eq, Copy, EnumIter, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, Copy, EnumIter, Hash, Serialize, Deserialize)]
 pub enum Target {
     /// Start is a padding operation.
     Start = 1,
@@ -115,6 +116,9 @@ pub enum Target {
     TxReceipt,
     /// Means the target of the operation is the TxLog.
     TxLog,
+    /// Means the target of the operation is a 32-byte aligned word of the
+    /// Memory (see [`MemoryOpMode::Word`]).
+    MemoryWord,
 }
 
 impl_expr!(Target);
@@ -226,6 +230,119 @@ impl Ord for MemoryOp {
     }
 }
 
+/// Granularity at which memory accesses are recorded as rw operations.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum MemoryOpMode {
+    /// One [`MemoryOp`] per accessed byte.
+    #[default]
+    Byte,
+    /// One [`MemoryWordOp`] per accessed 32-byte aligned word for the
+    /// `MLOAD`, `MSTORE` and `MSTORE8` opcodes.  Memory accesses generated by
+    /// copy events are still recorded per byte.
+    Word,
+}
+
+/// Represents a [`READ`](RW::READ)/[`WRITE`](RW::WRITE) of a 32-byte aligned
+/// word of the memory, used in [`MemoryOpMode::Word`].  An unaligned access
+/// spans two consecutive words and generates one operation for each of them.
+#[derive(Clone, PartialEq, Eq)]
+pub struct MemoryWordOp {
+    /// Call ID
+    pub call_id: usize,
+    /// Memory Address, aligned to 32 bytes
+    pub address: MemoryAddress,
+    /// Value of the whole word after the access
+    pub value: Word,
+    /// Value of the whole word before the access, equal to `value` for reads
+    pub value_prev: Word,
+    /// Offset of the accessed bytes within the word, in the range `0..32`
+    pub shift: u8,
+}
+
+impl fmt::Debug for MemoryWordOp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("MemoryWordOp { ")?;
+        f.write_fmt(format_args!(
+            "call_id: {:?}, addr: {:?}, value: 0x{:x}, value_prev: 0x{:x}, shift: {}",
+            self.call_id, self.address, self.value, self.value_prev, self.shift
+        ))?;
+        f.write_str(" }")
+    }
+}
+
+impl MemoryWordOp {
+    /// Create a new instance of a `MemoryWordOp` from it's components.
+    pub fn new(
+        call_id: usize,
+        address: MemoryAddress,
+        value: Word,
+        value_prev: Word,
+        shift: u8,
+    ) -> MemoryWordOp {
+        debug_assert_eq!(address.0 % 32, 0, "memory word address must be aligned");
+        debug_assert!(shift < 32, "memory word shift must be less than 32");
+        MemoryWordOp {
+            call_id,
+            address,
+            value,
+            value_prev,
+            shift,
+        }
+    }
+
+    /// Returns the [`Target`] (operation type) of this operation.
+    pub const fn target(&self) -> Target {
+        Target::MemoryWord
+    }
+
+    /// Returns the call id associated to this Operation.
+    pub const fn call_id(&self) -> usize {
+        self.call_id
+    }
+
+    /// Returns the [`MemoryAddress`] associated to this Operation.
+    pub const fn address(&self) -> &MemoryAddress {
+        &self.address
+    }
+
+    /// Returns the word read or written by this operation.
+    pub const fn value(&self) -> &Word {
+        &self.value
+    }
+
+    /// Returns the word before this operation.
+    pub const fn value_prev(&self) -> &Word {
+        &self.value_prev
+    }
+
+    /// Returns the offset of the accessed bytes within the word.
+    pub const fn shift(&self) -> u8 {
+        self.shift
+    }
+}
+
+impl Op for MemoryWordOp {
+    fn into_enum(self) -> OpEnum {
+        OpEnum::MemoryWord(self)
+    }
+
+    fn reverse(&self) -> Self {
+        unreachable!("MemoryWordOp can't be reverted")
+    }
+}
+
+impl PartialOrd for MemoryWordOp {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for MemoryWordOp {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (&self.call_id, &self.address).cmp(&(&other.call_id, &other.address))
+    }
+}
+
 /// Represents a [`READ`](RW::READ)/[`WRITE`](RW::WRITE) into the stack implied
 /// by an specific [`OpcodeId`](eth_types::evm_types::opcode_ids::OpcodeId) of
 /// the [`ExecStep`](crate::circuit_input_builder::ExecStep).
@@ -769,8 +886,11 @@ pub enum TxLogField {
     Topic,
     /// data of log entry
     Data,
-    // TODO: Add `TopicLength` and `DataLength`, which will be used for the RLP encoding of the
-    // Tx Receipt
+    /// number of topics of log entry, used for the RLP encoding of the Tx Receipt
+    TopicLength,
+    /// length in bytes of the data of log entry, used for the RLP encoding of the Tx
+    /// Receipt
+    DataLength,
 }
 
 /// Represents TxLog read/write operation.
@@ -955,6 +1075,8 @@ pub enum OpEnum {
     TxLog(TxLogOp),
     /// Start
     Start(StartOp),
+    /// MemoryWord
+    MemoryWord(MemoryWordOp),
 }
 
 /// Operation is a Wrapper over a type that implements Op with a RWCounter.
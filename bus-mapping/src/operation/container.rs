@@ -1,7 +1,7 @@
 use super::{
-    AccountOp, CallContextOp, MemoryOp, Op, OpEnum, Operation, RWCounter, StackOp, StartOp,
-    StorageOp, Target, TxAccessListAccountOp, TxAccessListAccountStorageOp, TxLogOp, TxReceiptOp,
-    TxRefundOp, RW,
+    AccountOp, CallContextOp, MemoryOp, MemoryWordOp, Op, OpEnum, Operation, RWCounter, StackOp,
+    StartOp, StorageOp, Target, TxAccessListAccountOp, TxAccessListAccountStorageOp, TxLogOp,
+    TxReceiptOp, TxRefundOp, RW,
 };
 use crate::exec_trace::OperationRef;
 use itertools::Itertools;
@@ -44,6 +44,8 @@ pub struct OperationContainer {
     pub tx_log: Vec<Operation<TxLogOp>>,
     /// Operations of Start
     pub start: Vec<Operation<StartOp>>,
+    /// Operations of MemoryWordOp
+    pub memory_word: Vec<Operation<MemoryWordOp>>,
 }
 
 impl Default for OperationContainer {
@@ -68,6 +70,7 @@ impl OperationContainer {
             tx_receipt: Vec::new(),
             tx_log: Vec::new(),
             start: Vec::new(),
+            memory_word: Vec::new(),
         }
     }
 
@@ -164,6 +167,10 @@ impl OperationContainer {
                 self.start.push(Operation::new(rwc, rw, op));
                 OperationRef::from((Target::Start, self.start.len() - 1))
             }
+            OpEnum::MemoryWord(op) => {
+                self.memory_word.push(Operation::new(rwc, rw, op));
+                OperationRef::from((Target::MemoryWord, self.memory_word.len() - 1))
+            }
         }
     }
 
@@ -173,6 +180,12 @@ impl OperationContainer {
         self.memory.iter().sorted().cloned().collect()
     }
 
+    /// Returns a sorted vector of all of the [`MemoryWordOp`]s contained inside
+    /// of the container.
+    pub fn sorted_memory_word(&self) -> Vec<Operation<MemoryWordOp>> {
+        self.memory_word.iter().sorted().cloned().collect()
+    }
+
     /// Returns a sorted vector of all of the [`StackOp`]s contained inside of
     /// the container.
     pub fn sorted_stack(&self) -> Vec<Operation<StackOp>> {
@@ -1,16 +1,17 @@
 //! Implementation of an in-memory key-value database to represent the
 //! Ethereum State Trie.
 
-use eth_types::{geth_types, Address, BigEndianHash, Bytecode, Hash, Word, H256, U256};
+use eth_types::{geth_types, Address, BigEndianHash, Bytecode, Bytes, Hash, Word, H256, U256};
 use ethers_core::utils::keccak256;
 use itertools::Itertools;
 use lazy_static::lazy_static;
-use std::collections::{HashMap, HashSet};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::collections::{BTreeMap, HashMap, HashSet};
 
 lazy_static! {
     static ref ACCOUNT_ZERO: Account = Account::zero();
-    /// Hash value for empty code hash.
-    static ref EMPTY_CODE_HASH: Hash = CodeDB::hash(&[]);
+    /// Hash value for empty code hash, with the default [`HashScheme`].
+    static ref EMPTY_CODE_HASH: Hash = HashScheme::default().hash_code(&[]);
     /// bytes of empty code hash, in little endian order.
     pub static ref EMPTY_CODE_HASH_LE: [u8; 32] = {
         let mut bytes = EMPTY_CODE_HASH.to_fixed_bytes();
@@ -21,52 +22,188 @@ lazy_static! {
 
 const VALUE_ZERO: Word = Word::zero();
 
-/// Memory storage for contract code by code hash.
+/// Number of bytes of code packed in each field element hashed by
+/// [`poseidon_code_hash`].
+#[cfg(feature = "poseidon-codehash")]
+pub const POSEIDON_CODE_HASH_BYTES_PER_ELEMENT: usize = 31;
+
+/// Poseidon code hash, used by rollups to commit to bytecodes more cheaply
+/// than with keccak.  The code is hashed as its length followed by its bytes
+/// packed by chunks of [`POSEIDON_CODE_HASH_BYTES_PER_ELEMENT`] big-endian
+/// bytes, and the resulting field element is returned in big-endian.
+#[cfg(feature = "poseidon-codehash")]
+pub fn poseidon_code_hash(code: &[u8]) -> Hash {
+    use halo2_proofs::halo2curves::{bn256::Fr, group::ff::PrimeField};
+    use poseidon::Poseidon;
+
+    let mut elements = vec![Fr::from(code.len() as u64)];
+    elements.extend(
+        code.chunks(POSEIDON_CODE_HASH_BYTES_PER_ELEMENT)
+            .map(|chunk| {
+                let mut repr = [0; 32];
+                for (repr_byte, byte) in repr.iter_mut().zip(chunk.iter().rev()) {
+                    *repr_byte = *byte;
+                }
+                Fr::from_repr(repr).unwrap()
+            }),
+    );
+    let mut hasher = Poseidon::<Fr, 3, 2>::new(8, 57);
+    hasher.update(&elements);
+    let mut bytes = hasher.squeeze().to_repr();
+    bytes.reverse();
+    H256(bytes)
+}
+
+/// Hash function of the code hashes.  The default one is keccak, unless the
+/// `poseidon-codehash` feature is enabled, in which case the circuits look up
+/// the code hashes in the Poseidon table.  The [`CodeDB`], the code hashes of
+/// the accounts and the empty code hash all use the default scheme, so that
+/// the code of an account is always found under its code hash.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HashScheme {
+    /// Keccak256, as defined by Ethereum
+    Keccak,
+    /// Poseidon, see [`poseidon_code_hash`]
+    #[cfg(feature = "poseidon-codehash")]
+    Poseidon,
+}
+
+impl Default for HashScheme {
+    #[cfg(not(feature = "poseidon-codehash"))]
+    fn default() -> Self {
+        Self::Keccak
+    }
+
+    #[cfg(feature = "poseidon-codehash")]
+    fn default() -> Self {
+        Self::Poseidon
+    }
+}
+
+impl HashScheme {
+    /// Compute the hash of given code with the scheme.
+    pub fn hash_code(&self, code: &[u8]) -> Hash {
+        match self {
+            Self::Keccak => CodeDB::hash(code),
+            #[cfg(feature = "poseidon-codehash")]
+            Self::Poseidon => poseidon_code_hash(code),
+        }
+    }
+}
+
+/// Memory storage for contract code by code hash.  Bytecodes are
+/// deduplicated, and iteration follows the order of their hashes so that the
+/// bytecode circuit assignment is deterministic.
+///
+/// Code hashes are computed with the default [`HashScheme`], like the code
+/// hashes of the accounts and [`CodeDB::empty_code_hash`].  Rollups committing
+/// to bytecodes with Poseidon pick it with the `poseidon-codehash` feature.
+/// The scheme is fixed at compile time rather than per CodeDB: the account
+/// code hashes are computed without a CodeDB, so a CodeDB with its own hasher
+/// would index the codes under hashes that no account refers to.
 #[derive(Debug, Clone, Default)]
-pub struct CodeDB(HashMap<Hash, Vec<u8>>);
+pub struct CodeDB {
+    codes: BTreeMap<Hash, Vec<u8>>,
+}
 
 impl CodeDB {
-    /// Insert code indexed by code hash, and return the code hash.
+    /// Insert code indexed by code hash, and return the code hash.  Inserting
+    /// the same code twice doesn't duplicate it.
     pub fn insert(&mut self, code: Vec<u8>) -> Hash {
-        let hash = Self::hash(&code);
-        self.0.insert(hash, code);
+        let hash = self.hash_code(&code);
+        self.codes.entry(hash).or_insert(code);
         hash
     }
 
-    /// Compute hash of given code.
+    /// Compute keccak hash of given code.
     pub fn hash(code: &[u8]) -> Hash {
         H256(keccak256(code))
     }
 
-    /// Code hash of empty code.
+    /// Compute hash of given code with the default [`HashScheme`].
+    pub fn hash_code(&self, code: &[u8]) -> Hash {
+        HashScheme::default().hash_code(code)
+    }
+
+    /// Code hash of empty code, with the default [`HashScheme`].
     pub fn empty_code_hash() -> Hash {
         *EMPTY_CODE_HASH
     }
 
     /// Compute number of rows required for bytecode table.
     pub fn num_rows_required_for_bytecode_table(&self) -> usize {
-        self.0.values().map(|bytecode| bytecode.len() + 1).sum()
+        self.codes.values().map(|bytecode| bytecode.len() + 1).sum()
+    }
+
+    /// Return the code indexed by `codehash`.
+    pub fn get(&self, codehash: &H256) -> Option<&[u8]> {
+        self.codes.get(codehash).map(Vec::as_slice)
     }
 
     /// Query Bytecode by H256
     pub fn get_from_h256(&self, codehash: &H256) -> Option<Bytecode> {
-        self.0.get(codehash).cloned().map(|code| code.into())
+        self.codes.get(codehash).cloned().map(|code| code.into())
     }
 
     /// Query Bytecode by U256
     pub fn get_from_u256(&self, codehash: &Word) -> Option<Bytecode> {
         self.get_from_h256(&H256::from_uint(codehash))
     }
+
+    /// Return whether the code indexed by `codehash` is stored.
+    pub fn contains(&self, codehash: &H256) -> bool {
+        self.codes.contains_key(codehash)
+    }
+
+    /// Number of distinct bytecodes stored.
+    pub fn len(&self) -> usize {
+        self.codes.len()
+    }
+
+    /// Return whether no bytecode is stored.
+    pub fn is_empty(&self) -> bool {
+        self.codes.is_empty()
+    }
+
+    /// Iterate over the code hashes and bytecodes, ordered by code hash.
+    pub fn iter(&self) -> impl Iterator<Item = (&Hash, &[u8])> {
+        self.codes
+            .iter()
+            .map(|(hash, code)| (hash, code.as_slice()))
+    }
 }
 
 impl From<Vec<Vec<u8>>> for CodeDB {
     fn from(bytecodes: Vec<Vec<u8>>) -> Self {
-        Self(HashMap::from_iter(
-            bytecodes
+        let mut code_db = Self::default();
+        for bytecode in bytecodes {
+            code_db.insert(bytecode);
+        }
+        code_db
+    }
+}
+
+/// The bytecodes are serialized with their hashes, so that a deserialized
+/// CodeDB indexes them as the original one.
+impl Serialize for CodeDB {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_map(
+            self.codes
                 .iter()
-                .cloned()
-                .map(|bytecode| (Self::hash(&bytecode), bytecode)),
-        ))
+                .map(|(hash, code)| (hash, Bytes::from(code.clone()))),
+        )
+    }
+}
+
+impl<'de> Deserialize<'de> for CodeDB {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let codes = BTreeMap::<Hash, Bytes>::deserialize(deserializer)?;
+        Ok(Self {
+            codes: codes
+                .into_iter()
+                .map(|(hash, code)| (hash, code.to_vec()))
+                .collect(),
+        })
     }
 }
 
@@ -74,10 +211,10 @@ impl IntoIterator for CodeDB {
     type Item = Bytecode;
     type IntoIter = std::vec::IntoIter<Self::Item>;
 
+    /// Bytecodes ordered by code hash.
     fn into_iter(self) -> Self::IntoIter {
-        self.0
-            .values()
-            .cloned()
+        self.codes
+            .into_values()
             .map(Bytecode::from)
             .collect_vec()
             .into_iter()
@@ -104,7 +241,7 @@ impl From<geth_types::Account> for Account {
             nonce: account.nonce.as_u64(),
             balance: account.balance,
             storage: account.storage.clone(),
-            code_hash: CodeDB::hash(&account.code),
+            code_hash: HashScheme::default().hash_code(&account.code),
         }
     }
 }
@@ -157,6 +294,11 @@ impl StateDB {
         self.state.insert(*addr, acc);
     }
 
+    /// Iterate over the [`Account`]s in the StateDB.
+    pub fn iter(&self) -> impl Iterator<Item = (&Address, &Account)> {
+        self.state.iter()
+    }
+
     /// Get a reference to the [`Account`] at `addr`.  Returns false and a zero
     /// [`Account`] when the [`Account`] wasn't found in the state.
     pub fn get_account(&self, addr: &Address) -> (bool, &Account) {
@@ -320,6 +462,55 @@ mod statedb_tests {
     use super::*;
     use eth_types::address;
 
+    #[test]
+    fn codedb_dedup_and_order() {
+        let codes = vec![vec![0x60, 0x01], vec![0x00], vec![0x60, 0x01], vec![]];
+        let code_db = CodeDB::from(codes.clone());
+        assert_eq!(code_db.len(), 3);
+        assert!(code_db.contains(&CodeDB::empty_code_hash()));
+        assert_eq!(code_db.get(&CodeDB::hash(&codes[0])), Some(&codes[0][..]));
+
+        let hashes = code_db.iter().map(|(hash, _)| *hash).collect_vec();
+        let mut sorted_hashes = hashes.clone();
+        sorted_hashes.sort();
+        assert_eq!(hashes, sorted_hashes);
+        assert_eq!(
+            code_db
+                .into_iter()
+                .map(|bytecode| bytecode.hash_h256())
+                .collect_vec(),
+            sorted_hashes
+        );
+    }
+
+    #[test]
+    fn codedb_hashes_match_accounts() {
+        let code = vec![0x60, 0x01, 0x00];
+        let mut code_db = CodeDB::default();
+        let account = Account::from(geth_types::Account {
+            code: code.clone().into(),
+            ..Default::default()
+        });
+        assert_eq!(code_db.insert(code.clone()), account.code_hash);
+        assert_eq!(code_db.get(&account.code_hash), Some(&code[..]));
+        assert_eq!(code_db.insert(vec![]), CodeDB::empty_code_hash());
+        assert_eq!(Account::zero().code_hash, CodeDB::empty_code_hash());
+        assert_eq!(HashScheme::Keccak.hash_code(&code), CodeDB::hash(&code));
+    }
+
+    #[cfg(feature = "poseidon-codehash")]
+    #[test]
+    fn codedb_with_poseidon_scheme() {
+        let code = vec![0x60; 100];
+        let mut code_db = CodeDB::default();
+        let hash = code_db.insert(code.clone());
+        assert_eq!(hash, poseidon_code_hash(&code));
+        assert_ne!(hash, CodeDB::hash(&code));
+        assert_eq!(code_db.get(&hash), Some(&code[..]));
+        // The length is hashed, so that trailing zeros change the hash
+        assert_ne!(poseidon_code_hash(&[1]), poseidon_code_hash(&[1, 0]));
+    }
+
     #[test]
     fn statedb() {
         let addr_a = address!("0x0000000000000000000000000000000000000001");
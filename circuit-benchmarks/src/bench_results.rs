@@ -0,0 +1,43 @@
+//! Results of the circuit benchmarks, emitted as JSON for trend tracking
+
+use serde::Serialize;
+use std::{env::var, fs::OpenOptions, io::Write};
+
+/// Durations in milliseconds of the phases of a circuit benchmark
+#[derive(Debug, Serialize)]
+pub struct BenchResult {
+    pub circuit: &'static str,
+    pub degree: u32,
+    pub setup_ms: u128,
+    pub keygen_ms: u128,
+    pub proving_ms: u128,
+    pub verification_ms: u128,
+}
+
+impl BenchResult {
+    pub fn new(circuit: &'static str, degree: u32) -> Self {
+        Self {
+            circuit,
+            degree,
+            setup_ms: 0,
+            keygen_ms: 0,
+            proving_ms: 0,
+            verification_ms: 0,
+        }
+    }
+
+    /// Print the result as a JSON line, also appended to the file at the
+    /// `BENCH_RESULTS` env var when it's set.
+    pub fn emit(&self) {
+        let json = serde_json::to_string(self).expect("Cannot serialize bench result");
+        println!("{}", json);
+        if let Ok(path) = var("BENCH_RESULTS") {
+            let mut file = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&path)
+                .expect("Cannot open BENCH_RESULTS file");
+            writeln!(file, "{}", json).expect("Cannot write BENCH_RESULTS file");
+        }
+    }
+}
@@ -3,3 +3,4 @@
 pub const SETUP_PREFIX: &str = "[Setup generation]";
 pub const PROOFGEN_PREFIX: &str = "[Proof generation]";
 pub const PROOFVER_PREFIX: &str = "[Proof verification]";
+pub const KEYGEN_PREFIX: &str = "[Key generation]";
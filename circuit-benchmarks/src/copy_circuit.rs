@@ -2,6 +2,7 @@
 
 #[cfg(test)]
 mod tests {
+    use crate::bench_results::BenchResult;
     use ark_std::{end_timer, start_timer};
     use bus_mapping::{circuit_input_builder::FixedCParams, mock::BlockData};
     use eth_types::{bytecode, geth_types::GethData, Word};
@@ -23,7 +24,7 @@ mod tests {
     use mock::test_ctx::{helpers::*, TestContext};
     use rand::SeedableRng;
     use rand_xorshift::XorShiftRng;
-    use std::env::var;
+    use std::{env::var, time::Instant};
     use zkevm_circuits::{
         copy_circuit::TestCopyCircuit,
         evm_circuit::witness::{block_convert, Block},
@@ -35,6 +36,7 @@ mod tests {
     fn bench_copy_circuit_prover() {
         let setup_prfx = crate::constants::SETUP_PREFIX;
         let proof_gen_prfx = crate::constants::PROOFGEN_PREFIX;
+        let keygen_prfx = crate::constants::KEYGEN_PREFIX;
         let proof_ver_prfx = crate::constants::PROOFVER_PREFIX;
         let degree: u32 = var("DEGREE")
             .unwrap_or_else(|_| "14".to_string())
@@ -56,14 +58,22 @@ mod tests {
 
         // Bench setup generation
         let setup_message = format!("{} {} with degree = {}", BENCHMARK_ID, setup_prfx, degree);
+        let mut result = BenchResult::new(BENCHMARK_ID, degree);
+        let start = Instant::now();
         let start1 = start_timer!(|| setup_message);
         let general_params = ParamsKZG::<Bn256>::setup(degree, &mut rng);
         let verifier_params: ParamsVerifierKZG<Bn256> = general_params.verifier_params().clone();
         end_timer!(start1);
+        result.setup_ms = start.elapsed().as_millis();
 
-        // Initialize the proving key
+        // Bench key generation
+        let start = Instant::now();
+        let start_keygen = start_timer!(|| format!("{} {}", BENCHMARK_ID, keygen_prfx));
         let vk = keygen_vk(&general_params, &circuit).expect("keygen_vk should not fail");
         let pk = keygen_pk(&general_params, vk, &circuit).expect("keygen_pk should not fail");
+        end_timer!(start_keygen);
+        result.keygen_ms = start.elapsed().as_millis();
+
         // Create a proof
         let mut transcript = Blake2bWrite::<_, G1Affine, Challenge255<_>>::init(vec![]);
 
@@ -72,6 +82,7 @@ mod tests {
             "{} {} with degree = {}",
             BENCHMARK_ID, proof_gen_prfx, degree
         );
+        let start = Instant::now();
         let start2 = start_timer!(|| proof_message);
         create_proof::<
             KZGCommitmentScheme<Bn256>,
@@ -84,8 +95,10 @@ mod tests {
         .expect("proof generation should not fail");
         let proof = transcript.finalize();
         end_timer!(start2);
+        result.proving_ms = start.elapsed().as_millis();
 
         // Bench verification time
+        let start = Instant::now();
         let start3 = start_timer!(|| format!("{} {}", BENCHMARK_ID, proof_ver_prfx));
         let mut verifier_transcript = Blake2bRead::<_, G1Affine, Challenge255<_>>::init(&proof[..]);
         let strategy = SingleStrategy::new(&general_params);
@@ -105,6 +118,8 @@ mod tests {
         )
         .expect("failed to verify bench circuit");
         end_timer!(start3);
+        result.verification_ms = start.elapsed().as_millis();
+        result.emit();
     }
 
     /// generate enough copy events to fillup copy circuit
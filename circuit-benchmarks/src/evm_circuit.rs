@@ -2,10 +2,12 @@
 
 #[cfg(test)]
 mod evm_circ_benches {
+    use crate::bench_results::BenchResult;
     use ark_std::{end_timer, start_timer};
     use bus_mapping::{circuit_input_builder::FixedCParams, mock::BlockData};
-    use eth_types::geth_types::GethData;
+    use eth_types::{bytecode, geth_types::GethData};
     use halo2_proofs::{
+        dev::MockProver,
         halo2curves::bn256::{Bn256, Fr, G1Affine},
         plonk::{create_proof, keygen_pk, keygen_vk, verify_proof},
         poly::{
@@ -23,7 +25,8 @@ mod evm_circ_benches {
     use mock::TestContext;
     use rand::SeedableRng;
     use rand_xorshift::XorShiftRng;
-    use std::env::var;
+    use rayon::ThreadPoolBuilder;
+    use std::{env::var, time::Instant};
     use zkevm_circuits::evm_circuit::{witness::block_convert, TestEvmCircuit};
 
     #[cfg_attr(not(feature = "benches"), ignore)]
@@ -31,6 +34,7 @@ mod evm_circ_benches {
     fn bench_evm_circuit_prover() {
         let setup_prfx = crate::constants::SETUP_PREFIX;
         let proof_gen_prfx = crate::constants::PROOFGEN_PREFIX;
+        let keygen_prfx = crate::constants::KEYGEN_PREFIX;
         let proof_ver_prfx = crate::constants::PROOFVER_PREFIX;
         // Unique string used by bench results module for parsing the result
         const BENCHMARK_ID: &str = "EVM Circuit";
@@ -62,14 +66,22 @@ mod evm_circ_benches {
 
         // Bench setup generation
         let setup_message = format!("{} {} with degree = {}", BENCHMARK_ID, setup_prfx, degree);
+        let mut result = BenchResult::new(BENCHMARK_ID, degree);
+        let start = Instant::now();
         let start1 = start_timer!(|| setup_message);
         let general_params = ParamsKZG::<Bn256>::setup(degree, &mut rng);
         let verifier_params: ParamsVerifierKZG<Bn256> = general_params.verifier_params().clone();
         end_timer!(start1);
+        result.setup_ms = start.elapsed().as_millis();
 
-        // Initialize the proving key
+        // Bench key generation
+        let start = Instant::now();
+        let start_keygen = start_timer!(|| format!("{} {}", BENCHMARK_ID, keygen_prfx));
         let vk = keygen_vk(&general_params, &circuit).expect("keygen_vk should not fail");
         let pk = keygen_pk(&general_params, vk, &circuit).expect("keygen_pk should not fail");
+        end_timer!(start_keygen);
+        result.keygen_ms = start.elapsed().as_millis();
+
         // Create a proof
         let mut transcript = Blake2bWrite::<_, G1Affine, Challenge255<_>>::init(vec![]);
 
@@ -78,6 +90,7 @@ mod evm_circ_benches {
             "{} {} with degree = {}",
             BENCHMARK_ID, proof_gen_prfx, degree
         );
+        let start = Instant::now();
         let start2 = start_timer!(|| proof_message);
         create_proof::<
             KZGCommitmentScheme<Bn256>,
@@ -97,8 +110,10 @@ mod evm_circ_benches {
         .expect("proof generation should not fail");
         let proof = transcript.finalize();
         end_timer!(start2);
+        result.proving_ms = start.elapsed().as_millis();
 
         // Bench verification time
+        let start = Instant::now();
         let start3 = start_timer!(|| format!("{} {}", BENCHMARK_ID, proof_ver_prfx));
         let mut verifier_transcript = Blake2bRead::<_, G1Affine, Challenge255<_>>::init(&proof[..]);
         let strategy = SingleStrategy::new(&general_params);
@@ -118,5 +133,65 @@ mod evm_circ_benches {
         )
         .expect("failed to verify bench circuit");
         end_timer!(start3);
+        result.verification_ms = start.elapsed().as_millis();
+        result.emit();
+    }
+
+    /// Compares the synthesis of a block of many steps with the step witnesses
+    /// computed on a single thread and on all of them.
+    #[cfg_attr(not(feature = "benches"), ignore)]
+    #[test]
+    fn bench_evm_circuit_step_witness() {
+        // Unique string used by bench results module for parsing the result
+        const BENCHMARK_ID: &str = "EVM Circuit step witness";
+
+        let degree: u32 = var("DEGREE")
+            .expect("No DEGREE env var was provided")
+            .parse()
+            .expect("Cannot parse DEGREE env var as u32");
+
+        // 6 steps per iteration
+        let iterations = 2000u64;
+        let code = bytecode! {
+            PUSH2(iterations)
+            JUMPDEST                // PC offset 0x03
+            PUSH1(0x01)
+            SWAP1
+            SUB
+            DUP1
+            PUSH1(0x03)
+            JUMPI                   // goto JUMPDEST(0x03) if (--iterations > 0)
+            STOP
+        };
+        let block: GethData = TestContext::<2, 1>::simple_ctx_with_bytecode(code)
+            .unwrap()
+            .into();
+        let mut builder = BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+        let builder = builder
+            .handle_block(&block.eth_block, &block.geth_traces)
+            .unwrap();
+        let block = block_convert::<Fr>(&builder).unwrap();
+        let circuit = TestEvmCircuit::<Fr>::new(block);
+
+        let single_thread = ThreadPoolBuilder::new().num_threads(1).build().unwrap();
+        let start = Instant::now();
+        let start1 = start_timer!(|| format!("{} on 1 thread", BENCHMARK_ID));
+        single_thread
+            .install(|| MockProver::<Fr>::run(degree, &circuit, vec![]))
+            .expect("synthesis should not fail");
+        end_timer!(start1);
+        let single_thread_ms = start.elapsed().as_millis();
+
+        let threads = rayon::current_num_threads();
+        let start = Instant::now();
+        let start2 = start_timer!(|| format!("{} on {} threads", BENCHMARK_ID, threads));
+        MockProver::<Fr>::run(degree, &circuit, vec![]).expect("synthesis should not fail");
+        end_timer!(start2);
+        let all_threads_ms = start.elapsed().as_millis();
+
+        println!(
+            "{} with degree = {}: {} ms on 1 thread, {} ms on {} threads",
+            BENCHMARK_ID, degree, single_thread_ms, all_threads_ms, threads
+        );
     }
 }
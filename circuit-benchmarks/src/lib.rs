@@ -36,6 +36,10 @@ pub mod exp_circuit;
 #[cfg(feature = "benches")]
 pub mod constants;
 
+#[cfg(test)]
+#[cfg(feature = "benches")]
+pub mod bench_results;
+
 #[cfg(test)]
 #[cfg(feature = "benches")]
 pub mod mpt_circuit;
@@ -1,6 +1,7 @@
 //! Error module for the eth-types crate
 
 use core::fmt::{Display, Formatter, Result as FmtResult};
+use ethers_core::utils::rlp::DecoderError;
 use std::error::Error as StdError;
 
 /// Error type for any BusMapping related failure.
@@ -33,6 +34,14 @@ pub enum Error {
     WordToMemAddr,
     /// Signature parsing error.
     Signature(libsecp256k1::Error),
+    /// Error while decoding an RLP encoded value.
+    RlpDecoding(DecoderError),
+}
+
+impl From<DecoderError> for Error {
+    fn from(err: DecoderError) -> Self {
+        Error::RlpDecoding(err)
+    }
 }
 
 impl From<libsecp256k1::Error> for Error {
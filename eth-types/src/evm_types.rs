@@ -20,6 +20,8 @@ pub const INVALID_INIT_CODE_FIRST_BYTE: u8 = 0xef;
 pub const INIT_CODE_WORD_GAS: u64 = 2;
 /// Quotient for max refund of gas used
 pub const MAX_REFUND_QUOTIENT_OF_GAS_USED: usize = 5;
+/// Quotient for max refund of gas used before EIP-3529 (London)
+pub const MAX_REFUND_QUOTIENT_OF_GAS_USED_PRE_EIP3529: usize = 2;
 /// Gas stipend when CALL or CALLCODE is attached with value.
 pub const GAS_STIPEND_CALL_WITH_VALUE: u64 = 2300;
 
@@ -65,15 +67,24 @@ impl GasCost {
     pub const COLD_SLOAD: u64 = 2100;
     /// Constant cost for a cold account access
     pub const COLD_ACCOUNT_ACCESS: u64 = 2600;
+    /// Constant cost for an SLOAD before Berlin (EIP-1884)
+    pub const ISTANBUL_SLOAD: u64 = 800;
+    /// Constant cost for accessing an account (BALANCE, EXTCODE* and the
+    /// CALL family) before Berlin (EIP-1884)
+    pub const ISTANBUL_ACCOUNT_ACCESS: u64 = 700;
     /// SSTORE reentrancy sentry
     pub const SSTORE_SENTRY: u64 = 2300;
     /// Constant cost for a storage set
     pub const SSTORE_SET: u64 = 20000;
     /// Constant cost for a storage reset
     pub const SSTORE_RESET: u64 = 2900;
+    /// Constant cost for a storage reset before Berlin (EIP-2200)
+    pub const ISTANBUL_SSTORE_RESET: u64 = 5000;
     /// Constant cost for a storage clear. EIP-3529 changed it to 4800 from
     /// 15000.
     pub const SSTORE_CLEARS_SCHEDULE: u64 = 4800;
+    /// Constant cost for a storage clear before EIP-3529
+    pub const SSTORE_CLEARS_SCHEDULE_PRE_EIP3529: u64 = 15000;
     /// Constant cost for a non-creation transaction
     pub const TX: u64 = 21000;
     /// Constant cost for a creation transaction
@@ -824,6 +824,43 @@ impl OpcodeId {
             .collect()
     }
 
+    /// Returns `true` if the `OpcodeId` costs more gas than its
+    /// [`constant_gas_cost`](Self::constant_gas_cost) depending on its
+    /// operands or on the state, e.g. memory expansion or cold accesses.
+    pub const fn has_dynamic_gas_cost(&self) -> bool {
+        matches!(
+            self,
+            OpcodeId::EXP
+                | OpcodeId::SHA3
+                | OpcodeId::BALANCE
+                | OpcodeId::CALLDATACOPY
+                | OpcodeId::CODECOPY
+                | OpcodeId::EXTCODESIZE
+                | OpcodeId::EXTCODECOPY
+                | OpcodeId::RETURNDATACOPY
+                | OpcodeId::EXTCODEHASH
+                | OpcodeId::MLOAD
+                | OpcodeId::MSTORE
+                | OpcodeId::MSTORE8
+                | OpcodeId::SLOAD
+                | OpcodeId::SSTORE
+                | OpcodeId::LOG0
+                | OpcodeId::LOG1
+                | OpcodeId::LOG2
+                | OpcodeId::LOG3
+                | OpcodeId::LOG4
+                | OpcodeId::CREATE
+                | OpcodeId::CALL
+                | OpcodeId::CALLCODE
+                | OpcodeId::RETURN
+                | OpcodeId::DELEGATECALL
+                | OpcodeId::CREATE2
+                | OpcodeId::STATICCALL
+                | OpcodeId::REVERT
+                | OpcodeId::SELFDESTRUCT
+        )
+    }
+
     /// Returns `true` if the `OpcodeId` has memory access
     pub const fn has_memory_access(&self) -> bool {
         matches!(
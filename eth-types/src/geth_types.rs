@@ -2,19 +2,26 @@
 
 use crate::{
     keccak256,
-    sign_types::{biguint_to_32bytes_le, ct_option_ok_or, recover_pk, SignData, SECP256K1_Q},
+    sign_types::{
+        biguint_to_32bytes_le, ct_option_ok_or, pk_bytes_le, pk_bytes_swap_endianness, recover_pk,
+        SignData, SECP256K1_Q,
+    },
     AccessList, Address, Block, Bytecode, Bytes, Error, GethExecTrace, Hash, ToBigEndian,
-    ToLittleEndian, ToWord, Word, U64,
+    ToLittleEndian, ToWord, Word, H256, U64,
 };
 use ethers_core::{
+    k256::ecdsa::SigningKey,
     types::{transaction::response, NameOrAddress, TransactionRequest},
-    utils::get_contract_address,
+    utils::{
+        get_contract_address,
+        rlp::{DecoderError, Rlp, RlpStream},
+    },
 };
 use ethers_signers::{LocalWallet, Signer};
 use halo2_proofs::halo2curves::{group::ff::PrimeField, secp256k1};
 use num::Integer;
 use num_bigint::BigUint;
-use serde::{Serialize, Serializer};
+use serde::{Deserialize, Serialize, Serializer};
 use serde_with::serde_as;
 use std::collections::HashMap;
 
@@ -96,6 +103,9 @@ pub struct BlockConstants {
     pub gas_limit: Word,
     /// base fee
     pub base_fee: Word,
+    /// mix hash, returned by the DIFFICULTY opcode (PREVRANDAO) after the
+    /// merge
+    pub mix_hash: H256,
 }
 
 impl<TX> TryFrom<&Block<TX>> for BlockConstants {
@@ -109,6 +119,7 @@ impl<TX> TryFrom<&Block<TX>> for BlockConstants {
             difficulty: block.difficulty,
             gas_limit: block.gas_limit,
             base_fee: block.base_fee_per_gas.ok_or(Error::IncompleteBlock)?,
+            mix_hash: block.mix_hash.unwrap_or_default(),
         })
     }
 }
@@ -122,6 +133,7 @@ impl BlockConstants {
         difficulty: Word,
         gas_limit: Word,
         base_fee: Word,
+        mix_hash: H256,
     ) -> BlockConstants {
         BlockConstants {
             coinbase,
@@ -130,13 +142,69 @@ impl BlockConstants {
             difficulty,
             gas_limit,
             base_fee,
+            mix_hash,
         }
     }
 }
 
+/// Transaction envelope type (EIP-2718).
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum TxType {
+    /// Legacy transaction, replay protected (EIP-155) unless signed with a
+    /// `v` of 27 or 28
+    #[default]
+    Legacy = 0,
+    /// Transaction with access list (EIP-2930)
+    Eip2930 = 1,
+    /// Transaction with priority fee (EIP-1559)
+    Eip1559 = 2,
+    /// Transaction carrying blobs (EIP-4844)
+    Eip4844 = 3,
+}
+
+impl TxType {
+    /// Return the type corresponding to the envelope type byte `ty`.
+    pub fn from_byte(ty: u8) -> Option<Self> {
+        match ty {
+            0 => Some(Self::Legacy),
+            1 => Some(Self::Eip2930),
+            2 => Some(Self::Eip1559),
+            3 => Some(Self::Eip4844),
+            _ => None,
+        }
+    }
+
+    /// Return whether the transaction is wrapped in a typed envelope.
+    pub fn is_typed(&self) -> bool {
+        *self != Self::Legacy
+    }
+
+    /// Number of RLP fields of the signed transaction.
+    fn num_signed_fields(&self) -> usize {
+        match self {
+            Self::Legacy => 9,
+            Self::Eip2930 => 11,
+            Self::Eip1559 => 12,
+            Self::Eip4844 => 14,
+        }
+    }
+}
+
+impl From<Option<U64>> for TxType {
+    /// Unknown and missing transaction types are considered legacy.
+    fn from(ty: Option<U64>) -> Self {
+        ty.and_then(|ty| u8::try_from(ty.as_u64()).ok())
+            .and_then(Self::from_byte)
+            .unwrap_or_default()
+    }
+}
+
 /// Definition of all of the constants related to an Ethereum transaction.
-#[derive(Debug, Default, Clone, Serialize)]
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
 pub struct Transaction {
+    /// Envelope type
+    #[serde(default)]
+    pub tx_type: TxType,
     /// Sender address
     pub from: Address,
     /// Recipient address (None for contract creation)
@@ -162,6 +230,12 @@ pub struct Transaction {
     pub call_data: Bytes,
     /// Access list
     pub access_list: Option<AccessList>,
+    /// Max fee per blob gas (EIP-4844)
+    #[serde(default)]
+    pub max_fee_per_blob_gas: Word,
+    /// Versioned hashes of the blobs (EIP-4844)
+    #[serde(default)]
+    pub blob_versioned_hashes: Vec<H256>,
 
     /// "v" value of the transaction signature
     pub v: u64,
@@ -187,6 +261,7 @@ impl From<&Transaction> for crate::Transaction {
             v: tx.v.into(),
             r: tx.r,
             s: tx.s,
+            transaction_type: tx.tx_type.is_typed().then(|| U64::from(tx.tx_type as u64)),
             ..Default::default()
         }
     }
@@ -195,6 +270,7 @@ impl From<&Transaction> for crate::Transaction {
 impl From<&crate::Transaction> for Transaction {
     fn from(tx: &crate::Transaction) -> Transaction {
         Transaction {
+            tx_type: tx.transaction_type.into(),
             from: tx.from,
             to: tx.to,
             nonce: tx.nonce.as_u64().into(),
@@ -205,6 +281,8 @@ impl From<&crate::Transaction> for Transaction {
             gas_fee_cap: tx.max_fee_per_gas.unwrap_or_default(),
             call_data: tx.input.clone(),
             access_list: tx.access_list.clone(),
+            max_fee_per_blob_gas: Word::zero(),
+            blob_versioned_hashes: Vec::new(),
             v: tx.v.as_u64(),
             r: tx.r,
             s: tx.s,
@@ -227,7 +305,45 @@ impl From<&Transaction> for TransactionRequest {
     }
 }
 
+/// Transaction signed with [`Transaction::sign`].
+#[derive(Debug, Clone)]
+pub struct SignedTransaction {
+    /// Transaction with its sender and signature set
+    pub tx: Transaction,
+    /// Network encoding of the signed transaction
+    pub raw: Bytes,
+    /// Transaction hash
+    pub hash: H256,
+}
+
 impl Transaction {
+    /// Sign the transaction for `chain_id` with `secret_key`, which also sets
+    /// its sender.  Legacy transactions are signed with replay protection
+    /// (EIP-155).
+    pub fn sign(&self, secret_key: &[u8], chain_id: u64) -> Result<SignedTransaction, Error> {
+        let wallet: LocalWallet = SigningKey::from_slice(secret_key)
+            .map_err(|_| Error::Signature(libsecp256k1::Error::InvalidSecretKey))?
+            .into();
+        let mut tx = Transaction {
+            from: wallet.address(),
+            v: 0,
+            ..self.clone()
+        };
+        let sig = wallet
+            .sign_hash(tx.signing_hash(chain_id))
+            .map_err(|_| Error::Signature(libsecp256k1::Error::InvalidSignature))?;
+        let parity = sig.v - 27;
+        tx.v = match tx.tx_type {
+            TxType::Legacy => parity + 35 + 2 * chain_id,
+            _ => parity,
+        };
+        tx.r = sig.r;
+        tx.s = sig.s;
+        let raw = tx.rlp_signed(chain_id);
+        let hash = H256(keccak256(&raw));
+        Ok(SignedTransaction { tx, raw, hash })
+    }
+
     /// Return the SignData associated with this Transaction.
     pub fn sign_data(&self, chain_id: u64) -> Result<SignData, Error> {
         let sig_r_le = self.r.to_le_bytes();
@@ -240,14 +356,8 @@ impl Transaction {
             secp256k1::Fq::from_repr(sig_s_le),
             Error::Signature(libsecp256k1::Error::InvalidSignature),
         )?;
-        // msg = rlp([nonce, gasPrice, gas, to, value, data, sig_v, r, s])
-        let req: TransactionRequest = self.into();
-        let msg = req.chain_id(chain_id).rlp();
-        let msg_hash: [u8; 32] = keccak256(&msg);
-        let v = self
-            .v
-            .checked_sub(35 + chain_id * 2)
-            .ok_or(Error::Signature(libsecp256k1::Error::InvalidSignature))? as u8;
+        let msg_hash = self.signing_hash(chain_id).to_fixed_bytes();
+        let v = self.recovery_id(chain_id)?;
         let pk = recover_pk(v, &self.r, &self.s, &msg_hash)?;
         // msg_hash = msg_hash % q
         let msg_hash = BigUint::from_bytes_be(msg_hash.as_slice());
@@ -264,6 +374,193 @@ impl Transaction {
         })
     }
 
+    /// Append the fields of the transaction that are covered by the
+    /// signature, excluding the EIP-155 chain id of legacy transactions.
+    fn rlp_append_fields(&self, stream: &mut RlpStream, chain_id: u64) {
+        if self.tx_type.is_typed() {
+            stream.append(&chain_id);
+        }
+        stream.append(&self.nonce.as_u64());
+        match self.tx_type {
+            TxType::Legacy | TxType::Eip2930 => {
+                stream.append(&self.gas_price);
+            }
+            TxType::Eip1559 | TxType::Eip4844 => {
+                stream.append(&self.gas_tip_cap);
+                stream.append(&self.gas_fee_cap);
+            }
+        }
+        stream.append(&self.gas_limit.as_u64());
+        match self.to {
+            Some(to) => stream.append(&to),
+            None => stream.append_empty_data(),
+        };
+        stream.append(&self.value);
+        stream.append(&self.call_data.to_vec());
+        if self.tx_type.is_typed() {
+            stream.append(&self.access_list.clone().unwrap_or_default());
+        }
+        if self.tx_type == TxType::Eip4844 {
+            stream.append(&self.max_fee_per_blob_gas);
+            stream.append_list(&self.blob_versioned_hashes);
+        }
+    }
+
+    /// Prefix the RLP `payload` with the envelope type of typed transactions.
+    fn envelope(&self, payload: &[u8]) -> Bytes {
+        let mut bytes = Vec::with_capacity(payload.len() + 1);
+        if self.tx_type.is_typed() {
+            bytes.push(self.tx_type as u8);
+        }
+        bytes.extend_from_slice(payload);
+        bytes.into()
+    }
+
+    /// Return whether this is a legacy transaction signed without replay
+    /// protection (pre EIP-155).
+    fn is_pre_eip155(&self) -> bool {
+        self.tx_type == TxType::Legacy && (self.v == 27 || self.v == 28)
+    }
+
+    /// Encoding of the transaction whose hash is signed by the sender:
+    /// - Legacy: `rlp([nonce, gasPrice, gas, to, value, data, chainId, 0, 0])`
+    ///   (EIP-155), or without the last 3 fields if `v` is 27 or 28.
+    /// - Typed: `type || rlp([chainId, ..fields, accessList, ..])`.
+    pub fn rlp_unsigned(&self, chain_id: u64) -> Bytes {
+        let mut stream = RlpStream::new();
+        stream.begin_unbounded_list();
+        self.rlp_append_fields(&mut stream, chain_id);
+        if self.tx_type == TxType::Legacy && !self.is_pre_eip155() {
+            stream.append(&chain_id);
+            stream.append(&0u8);
+            stream.append(&0u8);
+        }
+        stream.finalize_unbounded_list();
+        self.envelope(&stream.out())
+    }
+
+    /// Network encoding of the signed transaction, whose hash is the
+    /// transaction hash.
+    pub fn rlp_signed(&self, chain_id: u64) -> Bytes {
+        let mut stream = RlpStream::new();
+        stream.begin_unbounded_list();
+        self.rlp_append_fields(&mut stream, chain_id);
+        stream.append(&self.v);
+        stream.append(&self.r);
+        stream.append(&self.s);
+        stream.finalize_unbounded_list();
+        self.envelope(&stream.out())
+    }
+
+    /// Hash signed by the sender of the transaction.
+    pub fn signing_hash(&self, chain_id: u64) -> H256 {
+        H256(keccak256(&self.rlp_unsigned(chain_id)))
+    }
+
+    /// Hash of the signed transaction.
+    pub fn hash(&self, chain_id: u64) -> H256 {
+        H256(keccak256(&self.rlp_signed(chain_id)))
+    }
+
+    /// Recovery id (y parity) of the signature: the `v` of typed transactions
+    /// and `v - 27` or `v - 35 - 2 * chain_id` for legacy ones.
+    fn recovery_id(&self, chain_id: u64) -> Result<u8, Error> {
+        let v = match self.tx_type {
+            TxType::Legacy if self.is_pre_eip155() => self.v - 27,
+            TxType::Legacy => self
+                .v
+                .checked_sub(35 + chain_id * 2)
+                .ok_or(Error::Signature(libsecp256k1::Error::InvalidSignature))?,
+            _ => self.v,
+        };
+        u8::try_from(v).map_err(|_| Error::Signature(libsecp256k1::Error::InvalidSignature))
+    }
+
+    /// Recover the sender of the transaction from its signature.
+    pub fn recover_from(&self, chain_id: u64) -> Result<Address, Error> {
+        let msg_hash = self.signing_hash(chain_id).to_fixed_bytes();
+        let pk = recover_pk(self.recovery_id(chain_id)?, &self.r, &self.s, &msg_hash)?;
+        let pk_hash = keccak256(&pk_bytes_swap_endianness(&pk_bytes_le(&pk)));
+        Ok(Address::from_slice(&pk_hash[12..]))
+    }
+
+    /// Decode a signed transaction from its network encoding (see
+    /// [`Self::rlp_signed`]), recovering its sender.  Return the transaction
+    /// and the chain id it was signed for, which is `None` for legacy
+    /// transactions without replay protection.
+    pub fn decode_signed(bytes: &[u8]) -> Result<(Self, Option<u64>), Error> {
+        let (tx_type, payload) = match bytes.first() {
+            None => return Err(DecoderError::RlpIsTooShort.into()),
+            Some(byte) if *byte >= 0xc0 => (TxType::Legacy, bytes),
+            Some(byte) => (
+                TxType::from_byte(*byte)
+                    .filter(TxType::is_typed)
+                    .ok_or(DecoderError::Custom("unknown transaction type"))?,
+                &bytes[1..],
+            ),
+        };
+        let rlp = Rlp::new(payload);
+        if rlp.item_count()? != tx_type.num_signed_fields() {
+            return Err(DecoderError::RlpIncorrectListLen.into());
+        }
+
+        let mut tx = Transaction {
+            tx_type,
+            ..Default::default()
+        };
+        let mut index = 0;
+        let mut next = || {
+            index += 1;
+            index - 1
+        };
+        let chain_id = if tx_type.is_typed() {
+            Some(rlp.val_at::<u64>(next())?)
+        } else {
+            None
+        };
+        tx.nonce = rlp.val_at::<u64>(next())?.into();
+        match tx_type {
+            TxType::Legacy | TxType::Eip2930 => {
+                tx.gas_price = rlp.val_at(next())?;
+            }
+            TxType::Eip1559 | TxType::Eip4844 => {
+                tx.gas_tip_cap = rlp.val_at(next())?;
+                tx.gas_fee_cap = rlp.val_at(next())?;
+            }
+        }
+        tx.gas_limit = rlp.val_at::<u64>(next())?.into();
+        let to = rlp.at(next())?;
+        tx.to = if to.is_empty() {
+            None
+        } else {
+            Some(to.as_val()?)
+        };
+        tx.value = rlp.val_at(next())?;
+        tx.call_data = rlp.val_at::<Vec<u8>>(next())?.into();
+        if tx_type.is_typed() {
+            tx.access_list = Some(rlp.val_at(next())?);
+        }
+        if tx_type == TxType::Eip4844 {
+            tx.max_fee_per_blob_gas = rlp.val_at(next())?;
+            tx.blob_versioned_hashes = rlp.list_at(next())?;
+        }
+        tx.v = rlp.val_at(next())?;
+        tx.r = rlp.val_at(next())?;
+        tx.s = rlp.val_at(next())?;
+
+        let chain_id = match chain_id {
+            Some(chain_id) => Some(chain_id),
+            None if tx.is_pre_eip155() => None,
+            None => Some(
+                tx.v.checked_sub(35)
+                    .ok_or(Error::Signature(libsecp256k1::Error::InvalidSignature))?
+                    / 2,
+            ),
+        };
+        tx.from = tx.recover_from(chain_id.unwrap_or_default())?;
+        Ok((tx, chain_id))
+    }
+
     /// Compute call data gas cost from call data
     pub fn call_data_gas_cost(&self) -> u64 {
         self.call_data
@@ -349,3 +646,74 @@ impl GethData {
         }
     }
 }
+
+#[cfg(test)]
+mod geth_types_tests {
+    use super::*;
+    use ethers_core::types::transaction::eip2930::AccessListItem;
+
+    const CHAIN_ID: u64 = 1337;
+
+    fn check_round_trip(tx_type: TxType) {
+        let secret_key = [0x4cu8; 32];
+        let wallet: LocalWallet = SigningKey::from_slice(&secret_key).unwrap().into();
+        let mut tx = Transaction {
+            tx_type,
+            to: Some(Address::repeat_byte(0x12)),
+            nonce: 3.into(),
+            gas_limit: 100_000.into(),
+            value: Word::from(1_000_000_000u64),
+            gas_price: Word::from(7u64),
+            gas_fee_cap: Word::from(9u64),
+            gas_tip_cap: Word::from(2u64),
+            call_data: vec![0xde, 0xad, 0xbe, 0xef].into(),
+            access_list: tx_type.is_typed().then(|| {
+                AccessList(vec![AccessListItem {
+                    address: Address::repeat_byte(0x34),
+                    storage_keys: vec![H256::repeat_byte(0x56)],
+                }])
+            }),
+            max_fee_per_blob_gas: Word::from(11u64),
+            blob_versioned_hashes: vec![H256::repeat_byte(0x01)],
+            ..Default::default()
+        };
+        if tx_type != TxType::Eip4844 {
+            tx.max_fee_per_blob_gas = Word::zero();
+            tx.blob_versioned_hashes.clear();
+        }
+        let signed = tx.sign(&secret_key, CHAIN_ID).unwrap();
+        assert_eq!(signed.tx.from, wallet.address());
+
+        let (decoded, chain_id) = Transaction::decode_signed(&signed.raw).unwrap();
+        assert_eq!(chain_id, Some(CHAIN_ID));
+        assert_eq!(decoded.from, wallet.address());
+        assert_eq!(decoded.rlp_signed(CHAIN_ID), signed.raw);
+        assert_eq!(decoded.hash(CHAIN_ID), signed.hash);
+        assert!(decoded.sign_data(CHAIN_ID).is_ok());
+    }
+
+    #[test]
+    fn legacy_signing_hash_is_eip155() {
+        let tx = Transaction {
+            to: Some(Address::repeat_byte(0x12)),
+            call_data: vec![0x01, 0x02].into(),
+            ..Default::default()
+        };
+        let req: TransactionRequest = (&tx).into();
+        assert_eq!(tx.rlp_unsigned(CHAIN_ID), req.chain_id(CHAIN_ID).rlp());
+    }
+
+    #[test]
+    fn rlp_round_trip() {
+        check_round_trip(TxType::Legacy);
+        check_round_trip(TxType::Eip2930);
+        check_round_trip(TxType::Eip1559);
+        check_round_trip(TxType::Eip4844);
+    }
+
+    #[test]
+    fn decode_unknown_type() {
+        assert!(Transaction::decode_signed(&[0x7f, 0xc0]).is_err());
+        assert!(Transaction::decode_signed(&[]).is_err());
+    }
+}
@@ -2,10 +2,11 @@
 
 use eth_types::{
     geth_types::{Account, BlockConstants, Transaction},
-    Address, Error, GethExecTrace, Word,
+    keccak256, Address, Bytes, Error, GethExecTrace, Word, H256, U64,
 };
-use serde::Serialize;
-use std::collections::HashMap;
+use ethers_providers::{Http, JsonRpcClient};
+use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, path::Path};
 
 /// Configuration structure for `geth_utlis::trace`
 #[derive(Debug, Default, Clone, Serialize)]
@@ -23,6 +24,15 @@ pub struct TraceConfig {
     pub transactions: Vec<Transaction>,
     /// logger
     pub logger_config: LoggerConfig,
+    /// name of the hardfork whose rules are applied by the tracer (one of
+    /// "Istanbul", "Berlin", "London", "Merge" or "Shanghai"), the latest one
+    /// if `None`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hardfork: Option<String>,
+    /// dump the state of the accessed accounts after each transaction into
+    /// [`TxTrace::post_state`]
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub dump_post_state: bool,
 }
 
 /// Configuration structure for `logger.Config`
@@ -59,15 +69,239 @@ impl LoggerConfig {
     }
 }
 
+/// Result of tracing one of the [`TraceConfig::transactions`].
+#[derive(Debug, Clone, Deserialize)]
+pub struct TxTrace {
+    /// Execution trace of the transaction
+    #[serde(flatten)]
+    pub trace: GethExecTrace,
+    /// Reason of the failure when the execution of the transaction failed
+    /// (reverted or aborted with an error).
+    #[serde(default)]
+    pub error: Option<String>,
+    /// State of the accounts accessed so far after the transaction, when
+    /// [`TraceConfig::dump_post_state`] is set.
+    #[serde(default)]
+    pub post_state: Option<HashMap<Address, AccountState>>,
+}
+
+/// State of an account as dumped by the tracer, with only the storage slots
+/// that were accessed.
+#[derive(Debug, Clone, Deserialize)]
+pub struct AccountState {
+    /// nonce
+    pub nonce: U64,
+    /// balance
+    pub balance: Word,
+    /// code
+    pub code: Bytes,
+    /// accessed storage slots
+    pub storage: HashMap<Word, Word>,
+}
+
+impl TxTrace {
+    /// Gas used by the transaction
+    pub fn gas_used(&self) -> u64 {
+        self.trace.gas
+    }
+
+    /// Reason of the failure, or `None` if the transaction succeeded.
+    pub fn failure(&self) -> Option<&str> {
+        if self.trace.failed {
+            Some(self.error.as_deref().unwrap_or_default())
+        } else {
+            None
+        }
+    }
+}
+
+impl TraceConfig {
+    /// Hash identifying the config, independent of the iteration order of
+    /// its maps.
+    pub fn cache_key(&self) -> H256 {
+        // json objects of a `serde_json::Value` are sorted by key
+        let value = serde_json::to_value(self).unwrap();
+        H256(keccak256(value.to_string().as_bytes()))
+    }
+}
+
+fn geth_trace_string(config: &TraceConfig) -> Result<String, Error> {
+    geth_utils::trace(&serde_json::to_string(&config).unwrap()).map_err(|error| match error {
+        geth_utils::Error::TracingError(error) => Error::TracingError(error),
+    })
+}
+
+/// Creates one trace per transaction of the specified config, with the
+/// failure reason and gas used of each of them.
+pub fn trace_txs(config: &TraceConfig) -> Result<Vec<TxTrace>, Error> {
+    // Get the trace
+    let trace_string = geth_trace_string(config)?;
+
+    let traces = serde_json::from_str(&trace_string).map_err(Error::SerdeError)?;
+    Ok(traces)
+}
+
+/// Same as [`trace_txs`], but reusing the traces stored in `cache_dir` by a
+/// previous call with the same config (see [`TraceConfig::cache_key`]).
+/// Failed tracing attempts are not cached.
+pub fn trace_txs_cached(config: &TraceConfig, cache_dir: &Path) -> Result<Vec<TxTrace>, Error> {
+    let path = cache_dir.join(format!("{:x}.json", config.cache_key()));
+    if let Ok(trace_string) = std::fs::read_to_string(&path) {
+        // an unreadable entry (e.g. partially written) is traced again
+        if let Ok(traces) = serde_json::from_str(&trace_string) {
+            return Ok(traces);
+        }
+    }
+
+    let trace_string = geth_trace_string(config)?;
+    let traces = serde_json::from_str(&trace_string).map_err(Error::SerdeError)?;
+    std::fs::write(&path, trace_string).map_err(|err| {
+        Error::TracingError(format!("unable to write {}: {}", path.display(), err))
+    })?;
+    Ok(traces)
+}
+
 /// Creates a trace for the specified config
 pub fn trace(config: &TraceConfig) -> Result<Vec<GethExecTrace>, Error> {
-    // Get the trace
-    let trace_string = geth_utils::trace(&serde_json::to_string(&config).unwrap()).map_err(
-        |error| match error {
-            geth_utils::Error::TracingError(error) => Error::TracingError(error),
-        },
-    )?;
-
-    let trace = serde_json::from_str(&trace_string).map_err(Error::SerdeError)?;
-    Ok(trace)
+    Ok(trace_txs(config)?
+        .into_iter()
+        .map(|tx_trace| tx_trace.trace)
+        .collect())
+}
+
+/// Source of the execution traces of the transactions of a [`TraceConfig`].
+#[derive(Debug, Clone, Default)]
+pub enum TracerBackend {
+    /// Embedded go-ethereum library, executing the transactions on top of the
+    /// accounts and block constants of the config.
+    #[default]
+    Geth,
+    /// Live node tracing the already included transactions with
+    /// `debug_traceTransaction` and the struct logger configured by
+    /// [`TraceConfig::logger_config`].  The transactions must be signed, as
+    /// they are looked up by hash; the rest of the config is ignored.
+    Rpc(Http),
+}
+
+impl TracerBackend {
+    /// Creates one trace per transaction of `config` with this backend.
+    pub async fn trace_txs(&self, config: &TraceConfig) -> Result<Vec<TxTrace>, Error> {
+        match self {
+            Self::Geth => trace_txs(config),
+            Self::Rpc(client) => {
+                let mut traces = Vec::with_capacity(config.transactions.len());
+                for tx in config.transactions.iter() {
+                    let tx_hash = tx.hash(config.chain_id.as_u64());
+                    let trace = client
+                        .request("debug_traceTransaction", (tx_hash, &config.logger_config))
+                        .await
+                        .map_err(|err| Error::TracingError(err.to_string()))?;
+                    traces.push(trace);
+                }
+                Ok(traces)
+            }
+        }
+    }
+
+    /// Creates a trace for the specified config with this backend.
+    pub async fn trace(&self, config: &TraceConfig) -> Result<Vec<GethExecTrace>, Error> {
+        Ok(self
+            .trace_txs(config)
+            .await?
+            .into_iter()
+            .map(|tx_trace| tx_trace.trace)
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use eth_types::{address, word, H256};
+    use std::{
+        io::{BufRead, BufReader, Read, Write},
+        net::TcpListener,
+        str::FromStr,
+        thread,
+    };
+
+    /// Signed transaction of the example of EIP-155, with chain id 1.
+    fn eip155_tx() -> Transaction {
+        Transaction {
+            from: address!("0x9d8a62f656a8d1615c1294fd71e9cfb3e4855a4f"),
+            to: Some(address!("0x3535353535353535353535353535353535353535")),
+            nonce: 9.into(),
+            gas_limit: 21000.into(),
+            value: Word::exp10(18),
+            gas_price: Word::from(20_000_000_000u64),
+            v: 37,
+            r: word!("0x28ef61340bd939bc2195fe537567866003e1a15d3c71ff63e1590620aa636276"),
+            s: word!("0x67cbe9d8997f761aecb703304b3800ccf555c9f3dc64214b297fb1966a3b6d83"),
+            ..Default::default()
+        }
+    }
+
+    /// Answers one JSON-RPC request over HTTP with an empty trace, and returns
+    /// the request.
+    fn serve_one_trace(listener: TcpListener) -> serde_json::Value {
+        let (stream, _) = listener.accept().unwrap();
+        let mut reader = BufReader::new(stream);
+        let mut content_length = 0;
+        loop {
+            let mut line = String::new();
+            reader.read_line(&mut line).unwrap();
+            let line = line.trim_end();
+            if line.is_empty() {
+                break;
+            }
+            if let Some((name, value)) = line.split_once(':') {
+                if name.eq_ignore_ascii_case("content-length") {
+                    content_length = value.trim().parse().unwrap();
+                }
+            }
+        }
+        let mut body = vec![0; content_length];
+        reader.read_exact(&mut body).unwrap();
+        let request: serde_json::Value = serde_json::from_slice(&body).unwrap();
+
+        let response = serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": request["id"],
+            "result": {"gas": 21000, "failed": false, "returnValue": "", "structLogs": []},
+        })
+        .to_string();
+        write!(
+            reader.get_mut(),
+            "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\r\n{}",
+            response.len(),
+            response
+        )
+        .unwrap();
+        request
+    }
+
+    #[tokio::test]
+    async fn rpc_traces_the_signed_transaction_hash() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let server = thread::spawn(move || serve_one_trace(listener));
+
+        let config = TraceConfig {
+            chain_id: Word::one(),
+            transactions: vec![eip155_tx()],
+            ..Default::default()
+        };
+        let backend = TracerBackend::Rpc(Http::from_str(&url).unwrap());
+        let traces = backend.trace_txs(&config).await.unwrap();
+        assert_eq!(traces.len(), 1);
+
+        let request = server.join().unwrap();
+        assert_eq!(request["method"], "debug_traceTransaction");
+        let tx_hash: H256 = serde_json::from_value(request["params"][0].clone()).unwrap();
+        assert_eq!(
+            tx_hash,
+            H256::from_str("0x33469b22e9f636356c4160a87eb19df52b7412e8eac32a4a55ffe88ea8350788")
+                .unwrap()
+        );
+    }
 }
@@ -134,6 +134,16 @@ where
         selector: Column<Fixed>,
         value: Option<Column<Advice>>,
     ) -> BinaryNumberConfig<T, N> {
+        // Two variants with the same bits would mean that N is too small for T.
+        let valid_values: BTreeSet<usize> = T::iter().map(|t| from_bits(&t.as_bits())).collect();
+        assert_eq!(
+            valid_values.len(),
+            T::iter().count(),
+            "{} bits are not enough to encode {} values",
+            N,
+            T::iter().count()
+        );
+
         let bits = [0; N].map(|_| meta.advice_column());
         bits.map(|bit| {
             meta.create_gate("bit column is 0 or 1", |meta| {
@@ -160,7 +170,6 @@ where
         }
 
         // Disallow bit patterns (if any) that don't correspond to a variant of T.
-        let valid_values: BTreeSet<usize> = T::iter().map(|t| from_bits(&t.as_bits())).collect();
         let mut invalid_values = (0..1 << N).filter(|i| !valid_values.contains(i)).peekable();
         if invalid_values.peek().is_some() {
             meta.create_gate("binary number value in range", |meta| {
@@ -202,3 +211,164 @@ pub fn from_bits(bits: &[bool]) -> usize {
     bits.iter()
         .fold(0, |result, &bit| bit as usize + 2 * result)
 }
+
+/// Returns the number of bits needed to encode `num_values` distinct values,
+/// i.e. ceil(log2(num_values)).
+pub const fn num_bits(num_values: usize) -> usize {
+    let mut bits = 0;
+    while (1 << bits) < num_values {
+        bits += 1;
+    }
+    bits
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use halo2_proofs::{
+        circuit::{Layouter, SimpleFloorPlanner},
+        dev::MockProver,
+        halo2curves::bn256::Fr as Fp,
+        plonk::Circuit,
+    };
+
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    enum Tag {
+        A,
+        B,
+        C,
+        D,
+        E,
+    }
+
+    impl From<Tag> for usize {
+        fn from(tag: Tag) -> Self {
+            tag as usize
+        }
+    }
+
+    impl IntoEnumIterator for Tag {
+        type Iterator = std::array::IntoIter<Tag, 5>;
+
+        fn iter() -> Self::Iterator {
+            [Tag::A, Tag::B, Tag::C, Tag::D, Tag::E].into_iter()
+        }
+    }
+
+    const N_BITS: usize = num_bits(5);
+
+    #[derive(Clone, Debug)]
+    struct TestCircuitConfig {
+        selector: Column<Fixed>,
+        value: Column<Advice>,
+        is_c: Column<Advice>,
+        tag: BinaryNumberConfig<Tag, N_BITS>,
+    }
+
+    #[derive(Default)]
+    struct TestCircuit<F> {
+        /// The tags as raw bit patterns, to also assign invalid ones.
+        values: Vec<usize>,
+        _marker: PhantomData<F>,
+    }
+
+    impl<F: Field> Circuit<F> for TestCircuit<F> {
+        type Config = TestCircuitConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+        type Params = ();
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+            let selector = meta.fixed_column();
+            let value = meta.advice_column();
+            let is_c = meta.advice_column();
+            let tag = BinaryNumberChip::<F, Tag, N_BITS>::configure(meta, selector, Some(value));
+
+            meta.create_gate("is_c", |meta| {
+                let selector = meta.query_fixed(selector, Rotation::cur());
+                let is_c = meta.query_advice(is_c, Rotation::cur());
+                vec![selector * (tag.value_equals(Tag::C, Rotation::cur())(meta) - is_c)]
+            });
+
+            TestCircuitConfig {
+                selector,
+                value,
+                is_c,
+                tag,
+            }
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            layouter.assign_region(
+                || "witness",
+                |mut region| {
+                    for (offset, &value) in self.values.iter().enumerate() {
+                        region.assign_fixed(
+                            || "selector",
+                            config.selector,
+                            offset,
+                            || Value::known(F::ONE),
+                        )?;
+                        region.assign_advice(
+                            || "value",
+                            config.value,
+                            offset,
+                            || Value::known(F::from(value as u64)),
+                        )?;
+                        region.assign_advice(
+                            || "is_c",
+                            config.is_c,
+                            offset,
+                            || Value::known(F::from((value == Tag::C as usize) as u64)),
+                        )?;
+                        for (&bit, &column) in AsBits::<N_BITS>::as_bits(&value)
+                            .iter()
+                            .zip(&config.tag.bits)
+                        {
+                            region.assign_advice(
+                                || "bit",
+                                column,
+                                offset,
+                                || Value::known(F::from(bit as u64)),
+                            )?;
+                        }
+                    }
+                    Ok(())
+                },
+            )
+        }
+    }
+
+    fn verify(values: Vec<usize>) -> bool {
+        let circuit = TestCircuit::<Fp> {
+            values,
+            _marker: PhantomData,
+        };
+        let prover = MockProver::<Fp>::run(6, &circuit, vec![]).unwrap();
+        prover.verify().is_ok()
+    }
+
+    #[test]
+    fn bits_for_values() {
+        assert_eq!(num_bits(1), 0);
+        assert_eq!(num_bits(2), 1);
+        assert_eq!(num_bits(5), 3);
+        assert_eq!(num_bits(8), 3);
+        assert_eq!(num_bits(9), 4);
+    }
+
+    #[test]
+    fn tag_values() {
+        assert!(verify(Tag::iter().map(usize::from).collect()));
+        // bit patterns that are not a variant of Tag
+        assert!(!verify(vec![5]));
+        assert!(!verify(vec![7]));
+    }
+}
@@ -9,6 +9,7 @@ use halo2_proofs::{
 
 use super::{
     bool_check,
+    range_check_chip::{RangeCheckChip, RangeCheckConfig},
     util::{expr_from_bytes, pow_of_two},
 };
 
@@ -23,7 +24,8 @@ pub trait LtInstruction<F: Field> {
         rhs: Value<F>,
     ) -> Result<(), Error>;
 
-    /// Load the u8 lookup table.
+    /// Load the u8 lookup table.  Not needed when the table is shared, see
+    /// [`LtChip::configure_with_u8_table`].
     fn load(&self, layouter: &mut impl Layouter<F>) -> Result<(), Error>;
 }
 
@@ -60,11 +62,24 @@ impl<F: Field, const N_BYTES: usize> LtChip<F, N_BYTES> {
         q_enable: impl FnOnce(&mut VirtualCells<'_, F>) -> Expression<F>,
         lhs: impl FnOnce(&mut VirtualCells<F>) -> Expression<F>,
         rhs: impl FnOnce(&mut VirtualCells<F>) -> Expression<F>,
+    ) -> LtConfig<F, N_BYTES> {
+        let u8_table = RangeCheckChip::configure(meta);
+        Self::configure_with_u8_table(meta, q_enable, lhs, rhs, u8_table)
+    }
+
+    /// Configures the Lt chip with a shared u8 table, which is loaded by its
+    /// owner.
+    pub fn configure_with_u8_table(
+        meta: &mut ConstraintSystem<F>,
+        q_enable: impl FnOnce(&mut VirtualCells<'_, F>) -> Expression<F>,
+        lhs: impl FnOnce(&mut VirtualCells<F>) -> Expression<F>,
+        rhs: impl FnOnce(&mut VirtualCells<F>) -> Expression<F>,
+        u8_table: RangeCheckConfig<8>,
     ) -> LtConfig<F, N_BYTES> {
         let lt = meta.advice_column();
         let diff = [(); N_BYTES].map(|_| meta.advice_column());
         let range = pow_of_two(N_BYTES * 8);
-        let u8 = meta.fixed_column();
+        let u8 = u8_table.table;
 
         meta.create_gate("lt gate", |meta| {
             let q_enable = q_enable(meta);
@@ -85,8 +100,6 @@ impl<F: Field, const N_BYTES: usize> LtChip<F, N_BYTES> {
                 .map(move |poly| q_enable.clone() * poly)
         });
 
-        meta.annotate_lookup_any_column(u8, || "LOOKUP_u8");
-
         diff[0..N_BYTES].iter().for_each(|column| {
             meta.lookup_any("range check for u8", |meta| {
                 let u8_cell = meta.query_advice(*column, Rotation::cur());
@@ -152,22 +165,10 @@ impl<F: Field, const N_BYTES: usize> LtInstruction<F> for LtChip<F, N_BYTES> {
     }
 
     fn load(&self, layouter: &mut impl Layouter<F>) -> Result<(), Error> {
-        const RANGE: usize = 256;
-
-        layouter.assign_region(
-            || "load u8 range check table",
-            |mut region| {
-                for i in 0..RANGE {
-                    region.assign_fixed(
-                        || "assign cell in fixed column",
-                        self.config.u8,
-                        i,
-                        || Value::known(F::from(i as u64)),
-                    )?;
-                }
-                Ok(())
-            },
-        )
+        RangeCheckChip::<8>::construct(RangeCheckConfig {
+            table: self.config.u8,
+        })
+        .load(layouter)
     }
 }
 
@@ -0,0 +1,177 @@
+//! RangeCheck chip can be used to check that expressions fit in N_BITS bits,
+//! with lookups into a single fixed table holding 0..2^N_BITS.
+//!
+//! The table is meant to be shared: the circuits and the other chips that
+//! need a u8 or u16 range check register their lookups into the same
+//! `RangeCheckConfig` instead of allocating (and loading) their own fixed
+//! column.
+
+use eth_types::Field;
+use halo2_proofs::{
+    circuit::{Layouter, Value},
+    plonk::{Column, ConstraintSystem, Error, Expression, Fixed, VirtualCells},
+    poly::Rotation,
+};
+
+/// Config for the RangeCheck chip.
+#[derive(Clone, Copy, Debug)]
+pub struct RangeCheckConfig<const N_BITS: usize> {
+    /// The fixed column holding 0..2^N_BITS.
+    pub table: Column<Fixed>,
+}
+
+impl<const N_BITS: usize> RangeCheckConfig<N_BITS> {
+    /// Registers one lookup per expression returned by `values`, checking
+    /// that it fits in N_BITS bits when `q_enable` is 1.
+    pub fn range_check<F: Field, const M: usize>(
+        &self,
+        meta: &mut ConstraintSystem<F>,
+        name: &'static str,
+        q_enable: impl Fn(&mut VirtualCells<'_, F>) -> Expression<F>,
+        values: impl Fn(&mut VirtualCells<'_, F>) -> [Expression<F>; M],
+    ) {
+        for i in 0..M {
+            meta.lookup_any(name, |meta| {
+                let q_enable = q_enable(meta);
+                let value = values(meta)[i].clone();
+                // 0 is in the table, so disabled rows pass
+                vec![(
+                    q_enable * value,
+                    meta.query_fixed(self.table, Rotation::cur()),
+                )]
+            });
+        }
+    }
+}
+
+/// Chip that checks that expressions fit in N_BITS bits.
+#[derive(Clone, Debug)]
+pub struct RangeCheckChip<const N_BITS: usize> {
+    config: RangeCheckConfig<N_BITS>,
+}
+
+impl<const N_BITS: usize> RangeCheckChip<N_BITS> {
+    /// Configures the RangeCheck chip, allocating its table.
+    pub fn configure<F: Field>(meta: &mut ConstraintSystem<F>) -> RangeCheckConfig<N_BITS> {
+        let table = meta.fixed_column();
+        meta.annotate_lookup_any_column(table, || format!("LOOKUP_u{}", N_BITS));
+        RangeCheckConfig { table }
+    }
+
+    /// Constructs a RangeCheck chip given a config.
+    pub fn construct(config: RangeCheckConfig<N_BITS>) -> Self {
+        Self { config }
+    }
+
+    /// Loads the table.  It must be done once per config, by the owner of the
+    /// table.
+    pub fn load<F: Field>(&self, layouter: &mut impl Layouter<F>) -> Result<(), Error> {
+        layouter.assign_region(
+            || format!("load u{} range check table", N_BITS),
+            |mut region| {
+                for i in 0..1 << N_BITS {
+                    region.assign_fixed(
+                        || "assign cell in fixed column",
+                        self.config.table,
+                        i,
+                        || Value::known(F::from(i as u64)),
+                    )?;
+                }
+                Ok(())
+            },
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use halo2_proofs::{
+        circuit::SimpleFloorPlanner,
+        dev::MockProver,
+        halo2curves::bn256::Fr as Fp,
+        plonk::{Advice, Circuit, Selector},
+    };
+    use std::marker::PhantomData;
+
+    #[derive(Clone, Debug)]
+    struct TestCircuitConfig {
+        q_enable: Selector,
+        values: [Column<Advice>; 3],
+        u8: RangeCheckConfig<8>,
+    }
+
+    #[derive(Default)]
+    struct TestCircuit<F> {
+        rows: Vec<[u64; 3]>,
+        _marker: PhantomData<F>,
+    }
+
+    impl<F: Field> Circuit<F> for TestCircuit<F> {
+        type Config = TestCircuitConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+        type Params = ();
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+            let q_enable = meta.complex_selector();
+            let values = [(); 3].map(|_| meta.advice_column());
+            let u8 = RangeCheckChip::configure(meta);
+            u8.range_check(
+                meta,
+                "values fit in u8",
+                |meta| meta.query_selector(q_enable),
+                |meta| values.map(|value| meta.query_advice(value, Rotation::cur())),
+            );
+            TestCircuitConfig {
+                q_enable,
+                values,
+                u8,
+            }
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            RangeCheckChip::construct(config.u8).load(&mut layouter)?;
+            layouter.assign_region(
+                || "witness",
+                |mut region| {
+                    for (offset, row) in self.rows.iter().enumerate() {
+                        config.q_enable.enable(&mut region, offset)?;
+                        for (column, value) in config.values.iter().zip(row) {
+                            region.assign_advice(
+                                || "value",
+                                *column,
+                                offset,
+                                || Value::known(F::from(*value)),
+                            )?;
+                        }
+                    }
+                    Ok(())
+                },
+            )
+        }
+    }
+
+    fn verify(rows: Vec<[u64; 3]>) -> bool {
+        let circuit = TestCircuit::<Fp> {
+            rows,
+            _marker: PhantomData,
+        };
+        let prover = MockProver::<Fp>::run(9, &circuit, vec![]).unwrap();
+        prover.verify().is_ok()
+    }
+
+    #[test]
+    fn values_in_range() {
+        assert!(verify(vec![[0, 1, 2], [255, 128, 0]]));
+        assert!(!verify(vec![[0, 256, 2]]));
+        assert!(!verify(vec![[0, 1, 2], [1, 2, 1000]]));
+    }
+}
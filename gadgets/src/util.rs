@@ -16,9 +16,9 @@ pub mod sum {
     }
 
     /// Returns the sum of the given list of values within the field.
-    pub fn value<F: Field>(values: &[u8]) -> F {
+    pub fn value<'a, F: Field>(values: impl IntoIterator<Item = &'a u8>) -> F {
         values
-            .iter()
+            .into_iter()
             .fold(F::ZERO, |acc, value| acc + F::from(*value as u64))
     }
 }
@@ -117,6 +117,23 @@ pub mod select {
         selector.clone() * when_true + (1.expr() - selector) * when_false
     }
 
+    /// Returns the `when_true` limbs when the selector is true, else returns
+    /// the `when_false` limbs.
+    pub fn word<F: Field, const N: usize>(
+        selector: Expression<F>,
+        when_true: [Expression<F>; N],
+        when_false: [Expression<F>; N],
+    ) -> [Expression<F>; N] {
+        let mut when_false = when_false.into_iter();
+        when_true.map(|when_true| {
+            expr(
+                selector.clone(),
+                when_true,
+                when_false.next().expect("same number of limbs"),
+            )
+        })
+    }
+
     /// Returns the `when_true` value when the selector is true, else returns
     /// the `when_false` value.
     pub fn value<F: Field>(selector: F, when_true: F, when_false: F) -> F {
@@ -138,6 +155,36 @@ pub mod select {
     }
 }
 
+/// Checks the degree of the constraints against the target degree of the
+/// circuit, so that a gate that is too high fails at configure time instead of
+/// silently increasing the degree of the whole circuit.
+pub mod degree {
+    use eth_types::Field;
+    use halo2_proofs::plonk::Expression;
+
+    /// Returns an upper bound of the degree of the expression, i.e. the
+    /// highest number of queried cells multiplied together.
+    pub fn expr<F: Field>(expr: &Expression<F>) -> usize {
+        expr.degree()
+    }
+
+    /// Returns the highest degree of the given expressions.
+    pub fn max<'a, F: Field>(exprs: impl IntoIterator<Item = &'a Expression<F>>) -> usize {
+        exprs.into_iter().map(expr).max().unwrap_or(0)
+    }
+
+    /// Panics when `degree` exceeds `max_degree`, naming the expression.
+    pub fn validate(name: &str, degree: usize, max_degree: usize) {
+        assert!(
+            degree <= max_degree,
+            "Expression {} degree too high: {} > {}",
+            name,
+            degree,
+            max_degree,
+        );
+    }
+}
+
 /// Returns the power of a number using straightforward multiplications
 pub mod pow {
     use crate::util::Expr;
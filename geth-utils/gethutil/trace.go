@@ -20,10 +20,15 @@ import (
 // while replaying a transaction in debug mode as well as transaction
 // execution status, the amount of gas used and the return value
 type ExecutionResult struct {
-	Gas         uint64         `json:"gas"`
-	Failed      bool           `json:"failed"`
-	ReturnValue string         `json:"returnValue"`
-	StructLogs  []StructLogRes `json:"structLogs"`
+	Gas         uint64                     `json:"gas"`
+	Failed      bool                       `json:"failed"`
+	ReturnValue string                     `json:"returnValue"`
+	StructLogs  []StructLogRes             `json:"structLogs"`
+	// Not part of ethapi.ExecutionResult: reason of the failure, if any.
+	Error       string                     `json:"error,omitempty"`
+	// Not part of ethapi.ExecutionResult: state of the accounts touched so
+	// far after the transaction, if TraceConfig.DumpPostState is set.
+	PostState   map[common.Address]Account `json:"post_state,omitempty"`
 }
 
 // StructLogRes stores a structured log emitted by the EVM while replaying a
@@ -88,6 +93,7 @@ type Block struct {
 	Difficulty *hexutil.Big   `json:"difficulty"`
 	GasLimit   *hexutil.Big   `json:"gas_limit"`
 	BaseFee    *hexutil.Big   `json:"base_fee"`
+	MixHash    common.Hash    `json:"mix_hash"`
 }
 
 type Account struct {
@@ -122,6 +128,62 @@ type TraceConfig struct {
 	Accounts      map[common.Address]Account `json:"accounts"`
 	Transactions  []Transaction              `json:"transactions"`
 	LoggerConfig  *logger.Config             `json:"logger_config"`
+	// Hardfork whose rules are applied, the latest supported one if empty.
+	Hardfork      string                     `json:"hardfork"`
+	// Dump the state of the touched accounts after each transaction.
+	DumpPostState bool                       `json:"dump_post_state"`
+}
+
+// accessTracer is a StructLogger that also records the accounts and storage
+// slots accessed by the transactions, to dump their post state.
+type accessTracer struct {
+	*logger.StructLogger
+	accessed map[common.Address]map[common.Hash]struct{}
+}
+
+func (t *accessTracer) access(address common.Address) map[common.Hash]struct{} {
+	if _, ok := t.accessed[address]; !ok {
+		t.accessed[address] = make(map[common.Hash]struct{})
+	}
+	return t.accessed[address]
+}
+
+func (t *accessTracer) CaptureStart(env *vm.EVM, from common.Address, to common.Address, create bool, input []byte, gas uint64, value *big.Int) {
+	t.access(from)
+	t.access(to)
+	t.StructLogger.CaptureStart(env, from, to, create, input, gas, value)
+}
+
+func (t *accessTracer) CaptureEnter(typ vm.OpCode, from common.Address, to common.Address, input []byte, gas uint64, value *big.Int) {
+	t.access(from)
+	t.access(to)
+	t.StructLogger.CaptureEnter(typ, from, to, input, gas, value)
+}
+
+func (t *accessTracer) CaptureState(pc uint64, op vm.OpCode, gas, cost uint64, scope *vm.ScopeContext, rData []byte, depth int, err error) {
+	if (op == vm.SLOAD || op == vm.SSTORE) && len(scope.Stack.Data()) > 0 {
+		key := common.Hash(scope.Stack.Back(0).Bytes32())
+		t.access(scope.Contract.Address())[key] = struct{}{}
+	}
+	t.StructLogger.CaptureState(pc, op, gas, cost, scope, rData, depth, err)
+}
+
+// dump returns the state of the accessed accounts and storage slots.
+func (t *accessTracer) dump(stateDB *state.StateDB) map[common.Address]Account {
+	accounts := make(map[common.Address]Account, len(t.accessed))
+	for address, keys := range t.accessed {
+		storage := make(map[common.Hash]common.Hash, len(keys))
+		for key := range keys {
+			storage[key] = stateDB.GetState(address, key)
+		}
+		accounts[address] = Account{
+			Nonce:   hexutil.Uint64(stateDB.GetNonce(address)),
+			Balance: (*hexutil.Big)(stateDB.GetBalance(address)),
+			Code:    stateDB.GetCode(address),
+			Storage: storage,
+		}
+	}
+	return accounts
 }
 
 func newUint64(val uint64) *uint64 { return &val }
@@ -154,6 +216,28 @@ func Trace(config TraceConfig) ([]*ExecutionResult, error) {
 		TerminalTotalDifficulty:       big.NewInt(0),
 		TerminalTotalDifficultyPassed: true,
 	}
+	switch config.Hardfork {
+	case "", "Shanghai":
+	case "Merge":
+		chainConfig.ShanghaiTime = nil
+	case "London":
+		chainConfig.ShanghaiTime = nil
+		chainConfig.TerminalTotalDifficulty = nil
+		chainConfig.TerminalTotalDifficultyPassed = false
+	case "Berlin":
+		chainConfig.ShanghaiTime = nil
+		chainConfig.TerminalTotalDifficulty = nil
+		chainConfig.TerminalTotalDifficultyPassed = false
+		chainConfig.LondonBlock = nil
+	case "Istanbul":
+		chainConfig.ShanghaiTime = nil
+		chainConfig.TerminalTotalDifficulty = nil
+		chainConfig.TerminalTotalDifficultyPassed = false
+		chainConfig.LondonBlock = nil
+		chainConfig.BerlinBlock = nil
+	default:
+		return nil, fmt.Errorf("unsupported hardfork %q", config.Hardfork)
+	}
 
 	var txsGasLimit uint64
 	blockGasLimit := toBigInt(config.Block.GasLimit).Uint64()
@@ -190,8 +274,12 @@ func Trace(config TraceConfig) ([]*ExecutionResult, error) {
 		return nil, fmt.Errorf("txs total gas: %d Exceeds block gas limit: %d", txsGasLimit, blockGasLimit)
 	}
 
-	// For opcode PREVRANDAO
-	randao := common.BigToHash(toBigInt(config.Block.Difficulty)) // TODO: fix
+	// For opcode PREVRANDAO, which is only defined after the merge: a non-nil
+	// Random makes the EVM apply the merge rules.
+	var randao *common.Hash
+	if chainConfig.TerminalTotalDifficultyPassed {
+		randao = &config.Block.MixHash
+	}
 
 	blockCtx := vm.BlockContext{
 		CanTransfer: core.CanTransfer,
@@ -208,7 +296,7 @@ func Trace(config TraceConfig) ([]*ExecutionResult, error) {
 		BlockNumber: toBigInt(config.Block.Number),
 		Time:        toBigInt(config.Block.Timestamp).Uint64(),
 		Difficulty:  toBigInt(config.Block.Difficulty),
-		Random:      &randao,
+		Random:      randao,
 		BaseFee:     toBigInt(config.Block.BaseFee),
 		GasLimit:    blockGasLimit,
 	}
@@ -227,10 +315,20 @@ func Trace(config TraceConfig) ([]*ExecutionResult, error) {
 	}
 	stateDB.Finalise(true)
 
+	// The accounts and slots of the config are dumped even if not accessed.
+	accessed := make(map[common.Address]map[common.Hash]struct{})
+	accessed[config.Block.Coinbase] = make(map[common.Hash]struct{})
+	for address, account := range config.Accounts {
+		accessed[address] = make(map[common.Hash]struct{})
+		for key := range account.Storage {
+			accessed[address][key] = struct{}{}
+		}
+	}
+
 	// Run the transactions with tracing enabled.
 	executionResults := make([]*ExecutionResult, len(config.Transactions))
 	for i, message := range messages {
-		tracer := logger.NewStructLogger(config.LoggerConfig)
+		tracer := &accessTracer{logger.NewStructLogger(config.LoggerConfig), accessed}
 		evm := vm.NewEVM(blockCtx, core.NewEVMTxContext(&message), stateDB, &chainConfig, vm.Config{Debug: true, Tracer: tracer, NoBaseFee: true})
 
 		result, err := core.ApplyMessage(evm, &message, new(core.GasPool).AddGas(message.GasLimit))
@@ -245,6 +343,12 @@ func Trace(config TraceConfig) ([]*ExecutionResult, error) {
 			ReturnValue: fmt.Sprintf("%x", result.ReturnData),
 			StructLogs:  FormatLogs(tracer.StructLogs()),
 		}
+		if result.Err != nil {
+			executionResults[i].Error = result.Err.Error()
+		}
+		if config.DumpPostState {
+			executionResults[i].PostState = tracer.dump(stateDB)
+		}
 	}
 
 	return executionResults, nil
@@ -74,6 +74,7 @@ const CIRCUITS_PARAMS: FixedCParams = FixedCParams {
     max_evm_rows: MAX_EVM_ROWS,
     max_exp_steps: MAX_EXP_STEPS,
     max_keccak_rows: MAX_KECCAK_ROWS,
+    anchor_tx: None,
 };
 
 const EVM_CIRCUIT_DEGREE: u32 = 18;
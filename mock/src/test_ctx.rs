@@ -110,6 +110,54 @@ impl<const NACC: usize, const NTX: usize> TestContext<NACC, NTX> {
         func_block: Fb,
         logger_config: LoggerConfig,
     ) -> Result<Self, Error>
+    where
+        FTx: FnOnce(Vec<&mut MockTransaction>, [MockAccount; NACC]),
+        Fb: FnOnce(&mut MockBlock, Vec<MockTransaction>) -> &mut MockBlock,
+        FAcc: FnOnce([&mut MockAccount; NACC]),
+    {
+        Self::new_with_tracer_config(
+            history_hashes,
+            acc_fns,
+            func_tx,
+            func_block,
+            logger_config,
+            None,
+        )
+    }
+
+    /// Create a new TestContext like [`TestContext::new`], whose execution
+    /// traces are generated with the rules of `hardfork` (one of the names
+    /// supported by the tracer, like "Berlin") instead of the latest ones.
+    pub fn new_with_hardfork<FAcc, FTx, Fb>(
+        history_hashes: Option<Vec<Word>>,
+        acc_fns: FAcc,
+        func_tx: FTx,
+        func_block: Fb,
+        hardfork: &str,
+    ) -> Result<Self, Error>
+    where
+        FTx: FnOnce(Vec<&mut MockTransaction>, [MockAccount; NACC]),
+        Fb: FnOnce(&mut MockBlock, Vec<MockTransaction>) -> &mut MockBlock,
+        FAcc: FnOnce([&mut MockAccount; NACC]),
+    {
+        Self::new_with_tracer_config(
+            history_hashes,
+            acc_fns,
+            func_tx,
+            func_block,
+            LoggerConfig::default(),
+            Some(hardfork.to_string()),
+        )
+    }
+
+    fn new_with_tracer_config<FAcc, FTx, Fb>(
+        history_hashes: Option<Vec<Word>>,
+        acc_fns: FAcc,
+        func_tx: FTx,
+        func_block: Fb,
+        logger_config: LoggerConfig,
+        hardfork: Option<String>,
+    ) -> Result<Self, Error>
     where
         FTx: FnOnce(Vec<&mut MockTransaction>, [MockAccount; NACC]),
         Fb: FnOnce(&mut MockBlock, Vec<MockTransaction>) -> &mut MockBlock,
@@ -169,12 +217,13 @@ impl<const NACC: usize, const NTX: usize> TestContext<NACC, NTX> {
             .try_into()
             .expect("Mismatched acc len");
 
-        let geth_traces = gen_geth_traces(
+        let geth_traces = gen_geth_traces_with_hardfork(
             chain_id,
             block.clone(),
             accounts.to_vec(),
             history_hashes.clone(),
             logger_config,
+            hardfork,
         )?;
 
         Ok(Self {
@@ -235,6 +284,24 @@ pub fn gen_geth_traces(
     accounts: Vec<Account>,
     history_hashes: Option<Vec<Word>>,
     logger_config: LoggerConfig,
+) -> Result<Vec<GethExecTrace>, Error> {
+    gen_geth_traces_with_hardfork(
+        chain_id,
+        block,
+        accounts,
+        history_hashes,
+        logger_config,
+        None,
+    )
+}
+
+fn gen_geth_traces_with_hardfork(
+    chain_id: Word,
+    block: Block<Transaction>,
+    accounts: Vec<Account>,
+    history_hashes: Option<Vec<Word>>,
+    logger_config: LoggerConfig,
+    hardfork: Option<String>,
 ) -> Result<Vec<GethExecTrace>, Error> {
     let trace_config = TraceConfig {
         chain_id,
@@ -250,6 +317,8 @@ pub fn gen_geth_traces(
             .map(eth_types::geth_types::Transaction::from)
             .collect(),
         logger_config,
+        hardfork,
+        dump_post_state: false,
     };
     let traces = trace(&trace_config)?;
     Ok(traces)
@@ -0,0 +1,107 @@
+//! Backend running the arithmetic of the proving: the MSMs and FFTs of halo2.
+//!
+//! halo2 parallelizes them on the rayon thread pool of the calling thread, so
+//! a CPU backend runs the proving inside a pool with its number of threads.
+//! The halo2 version used by the circuits has no GPU support, so the CPU is
+//! the only backend for now.
+
+use crate::Error;
+use rayon::ThreadPoolBuilder;
+use serde::{Deserialize, Serialize};
+use std::{fmt, num::NonZeroUsize, str::FromStr, thread};
+
+/// Backend of the proving arithmetic.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum Backend {
+    /// CPU with a pool of `threads` threads
+    Cpu {
+        /// Number of threads, at least 1
+        threads: usize,
+    },
+}
+
+impl Backend {
+    /// Returns the best backend of the machine: the CPU with a thread per
+    /// available core.
+    pub fn detect() -> Self {
+        Backend::Cpu {
+            threads: thread::available_parallelism()
+                .map(NonZeroUsize::get)
+                .unwrap_or(1),
+        }
+    }
+
+    /// Runs `op`, which does the MSMs and FFTs of a proving, on the backend.
+    pub fn install<T: Send>(&self, op: impl FnOnce() -> T + Send) -> Result<T, Error> {
+        match self {
+            Backend::Cpu { threads } => {
+                let pool = ThreadPoolBuilder::new()
+                    .num_threads(*threads)
+                    .thread_name(|index| format!("prover-cpu-{}", index))
+                    .build()
+                    .map_err(|err| Error::Backend(err.to_string()))?;
+                Ok(pool.install(op))
+            }
+        }
+    }
+}
+
+impl Default for Backend {
+    fn default() -> Self {
+        Self::detect()
+    }
+}
+
+/// Parses `auto` as the detected backend, and `cpu` or `cpu:THREADS` as the
+/// CPU with a thread per core or `THREADS` threads.
+impl FromStr for Backend {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.split_once(':') {
+            None if s == "auto" || s == "cpu" => Ok(Self::detect()),
+            Some(("cpu", threads)) => match threads.parse() {
+                Ok(threads) if threads > 0 => Ok(Backend::Cpu { threads }),
+                _ => Err(Error::Backend(format!(
+                    "invalid number of threads {}",
+                    threads
+                ))),
+            },
+            _ => Err(Error::Backend(format!("unknown backend {}", s))),
+        }
+    }
+}
+
+impl fmt::Display for Backend {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Backend::Cpu { threads } => write!(f, "cpu:{}", threads),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_backend() {
+        assert_eq!(
+            "cpu:3".parse::<Backend>().unwrap(),
+            Backend::Cpu { threads: 3 }
+        );
+        assert_eq!("auto".parse::<Backend>().unwrap(), Backend::detect());
+        assert_eq!("cpu".parse::<Backend>().unwrap(), Backend::detect());
+        assert!("cpu:0".parse::<Backend>().is_err());
+        assert!("gpu".parse::<Backend>().is_err());
+        let backend = Backend::Cpu { threads: 2 };
+        assert_eq!(backend.to_string().parse::<Backend>().unwrap(), backend);
+    }
+
+    #[test]
+    fn install_cpu_threads() {
+        let backend = Backend::Cpu { threads: 2 };
+        assert_eq!(backend.install(rayon::current_num_threads).unwrap(), 2);
+    }
+}
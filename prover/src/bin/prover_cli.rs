@@ -0,0 +1,211 @@
+//! Generates and caches the KZG params and the keys of the circuits, so that
+//! the provers load them instead of redoing the setup and the key generation.
+//! Also compares the proving time of the circuits on the backends.
+use clap::{Parser, Subcommand};
+use halo2_proofs::{
+    halo2curves::bn256::{Bn256, Fr},
+    plonk::Circuit,
+    poly::kzg::commitment::ParamsKZG,
+};
+use prover::{
+    backend::Backend,
+    circuit::{empty_block, CircuitId, CircuitVisitor},
+    keys::{KeyHeader, KeyStore},
+    params::{params_digest, ParamsCache},
+    proof::{prove, verify, Proof},
+    Error,
+};
+use std::path::PathBuf;
+use strum::IntoEnumIterator;
+use zkevm_circuits::{util::SubCircuit, witness::Block};
+
+/// KZG params and keys management
+#[derive(Parser, Debug)]
+#[clap(author, version, about, long_about = None)]
+struct Args {
+    /// Directory of the KZG params
+    #[clap(long, default_value = "params")]
+    params_dir: PathBuf,
+
+    /// Directory of the proving and verifying keys
+    #[clap(long, default_value = "keys")]
+    keys_dir: PathBuf,
+
+    #[clap(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Generates (or downsizes) the KZG params of a degree
+    Params {
+        /// Degree of the params
+        #[clap(long)]
+        degree: u32,
+    },
+    /// Generates the keys of the circuits, replacing the existing ones
+    Keygen {
+        /// Circuits to generate the keys of, all of them by default
+        #[clap(long, value_delimiter = ',')]
+        circuits: Vec<CircuitId>,
+    },
+    /// Loads the keys of the circuits, checking that they match the params
+    Check {
+        /// Circuits to check the keys of, all of them by default
+        #[clap(long, value_delimiter = ',')]
+        circuits: Vec<CircuitId>,
+    },
+    /// Verifies a proof in the binary encoding with the keys of its circuit
+    Verify {
+        /// Path of the proof
+        proof: PathBuf,
+    },
+    /// Proves the empty block with the circuits on each backend, comparing
+    /// the proving times
+    Bench {
+        /// Circuits to prove, all of them by default
+        #[clap(long, value_delimiter = ',')]
+        circuits: Vec<CircuitId>,
+        /// Backends to compare: `auto`, `cpu` or `cpu:THREADS`
+        #[clap(long, value_delimiter = ',', default_value = "cpu:1,auto")]
+        backends: Vec<Backend>,
+    },
+}
+
+/// Generates, or only loads when `generate` is false, the keys of a circuit.
+struct Keys<'a> {
+    params: &'a ParamsKZG<Bn256>,
+    store: &'a KeyStore,
+    block: &'a Block<Fr>,
+    generate: bool,
+}
+
+impl CircuitVisitor for Keys<'_> {
+    type Output = Result<(), Error>;
+
+    fn visit<C: SubCircuit<Fr> + Circuit<Fr>>(self, id: CircuitId) -> Self::Output {
+        let header = KeyHeader::new(id, id.degree(), params_digest(self.params)?);
+        let circuit = C::new_from_block(self.block);
+        if self.generate {
+            self.store.generate(&header, self.params, &circuit)?;
+        } else {
+            self.store.load_pk::<C>(&header, circuit.params())?;
+            self.store.load_vk::<C>(&header, circuit.params())?;
+        }
+        log::info!("{} keys: {}", id, self.store.pk_path(&header).display());
+        Ok(())
+    }
+}
+
+/// Verifies a proof with the verifying key of its circuit.
+struct Verify<'a> {
+    params: &'a ParamsKZG<Bn256>,
+    store: &'a KeyStore,
+    block: &'a Block<Fr>,
+    proof: &'a Proof,
+}
+
+impl CircuitVisitor for Verify<'_> {
+    type Output = Result<(), Error>;
+
+    fn visit<C: SubCircuit<Fr> + Circuit<Fr>>(self, id: CircuitId) -> Self::Output {
+        let header = KeyHeader::new(id, id.degree(), params_digest(self.params)?);
+        let circuit = C::new_from_block(self.block);
+        let vk = self.store.load_vk::<C>(&header, circuit.params())?;
+        verify(self.proof, &vk, self.params)
+    }
+}
+
+/// Proves a circuit on each backend, logging the proving times.
+struct Bench<'a> {
+    params: &'a ParamsKZG<Bn256>,
+    store: &'a KeyStore,
+    block: &'a Block<Fr>,
+    backends: &'a [Backend],
+}
+
+impl CircuitVisitor for Bench<'_> {
+    type Output = Result<(), Error>;
+
+    fn visit<C: SubCircuit<Fr> + Circuit<Fr>>(self, id: CircuitId) -> Self::Output {
+        let header = KeyHeader::new(id, id.degree(), params_digest(self.params)?);
+        let circuit = C::new_from_block(self.block);
+        let pk = self
+            .store
+            .load_or_generate_pk(&header, self.params, &circuit)?;
+        let instances = circuit.instance();
+        for backend in self.backends {
+            let proof = backend.install(|| {
+                prove(
+                    id,
+                    self.params,
+                    &pk,
+                    C::new_from_block(self.block),
+                    instances.clone(),
+                )
+            })??;
+            log::info!(
+                "{} proof on {}: {} ms",
+                id,
+                backend,
+                proof.finished_at - proof.started_at
+            );
+        }
+        Ok(())
+    }
+}
+
+fn main() -> Result<(), Error> {
+    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info")).init();
+    let args = Args::parse();
+    let params_cache = ParamsCache::new(&args.params_dir)?;
+
+    let store = KeyStore::new(&args.keys_dir)?;
+    let block = empty_block();
+    let (circuits, generate, backends) = match args.command {
+        Command::Params { degree } => {
+            params_cache.load_or_setup(degree)?;
+            log::info!("KZG params: {}", params_cache.path(degree).display());
+            return Ok(());
+        }
+        Command::Keygen { circuits } => (circuits, true, None),
+        Command::Check { circuits } => (circuits, false, None),
+        Command::Bench { circuits, backends } => (circuits, false, Some(backends)),
+        Command::Verify { proof } => {
+            let proof = Proof::from_bytes(&std::fs::read(proof)?)?;
+            let params = params_cache.load_or_setup(proof.circuit.degree())?;
+            proof.circuit.visit(Verify {
+                params: &params,
+                store: &store,
+                block: &block,
+                proof: &proof,
+            })?;
+            log::info!("valid {} proof", proof.circuit);
+            return Ok(());
+        }
+    };
+    let circuits = if circuits.is_empty() {
+        CircuitId::iter().collect()
+    } else {
+        circuits
+    };
+
+    for circuit in circuits {
+        let params = params_cache.load_or_setup(circuit.degree())?;
+        match &backends {
+            Some(backends) => circuit.visit(Bench {
+                params: &params,
+                store: &store,
+                block: &block,
+                backends,
+            })?,
+            None => circuit.visit(Keys {
+                params: &params,
+                store: &store,
+                block: &block,
+                generate,
+            })?,
+        }
+    }
+    Ok(())
+}
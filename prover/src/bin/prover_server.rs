@@ -0,0 +1,135 @@
+//! HTTP server queueing the proofs of blocks with the super circuit.
+//!
+//! Endpoints:
+//! - `POST /prove_block` with a body `{"block_number": N}` queues the proof of
+//!   the block `N` of the geth node and answers `{"job_id": ID}`.
+//! - `GET /proof_status/ID` answers the status of the job `ID`, with the hex
+//!   encoded proof once it's done.
+use clap::Parser;
+use hyper::{
+    body,
+    service::{make_service_fn, service_fn},
+    Body, Method, Request, Response, Server, StatusCode,
+};
+use prover::{
+    backend::Backend,
+    keys::KeyStore,
+    params::ParamsCache,
+    service::{JobId, ProveRequest, ProverService},
+    Error,
+};
+use serde::{Deserialize, Serialize};
+use std::{convert::Infallible, net::SocketAddr, path::PathBuf, sync::Arc};
+use url::Url;
+
+/// Proving service for the blocks of a geth node
+#[derive(Parser, Debug)]
+#[clap(author, version, about, long_about = None)]
+struct Args {
+    /// Address the server listens on
+    #[clap(long, default_value = "127.0.0.1:8080")]
+    listen: SocketAddr,
+
+    /// URL of the geth node the blocks are fetched from
+    #[clap(long, default_value = "http://localhost:8545")]
+    geth_url: Url,
+
+    /// Directory of the KZG params
+    #[clap(long, default_value = "params")]
+    params_dir: PathBuf,
+
+    /// Directory of the proving and verifying keys
+    #[clap(long, default_value = "keys")]
+    keys_dir: PathBuf,
+
+    /// Proving backend: `auto`, `cpu` or `cpu:THREADS`
+    #[clap(long, default_value = "auto")]
+    backend: Backend,
+}
+
+#[derive(Deserialize)]
+struct ProveBlockRequest {
+    block_number: u64,
+}
+
+#[derive(Serialize)]
+struct ProveBlockResponse {
+    job_id: JobId,
+}
+
+#[derive(Serialize)]
+struct ErrorResponse {
+    error: String,
+}
+
+fn json_response<T: Serialize>(status: StatusCode, value: &T) -> Response<Body> {
+    Response::builder()
+        .status(status)
+        .header("content-type", "application/json")
+        .body(Body::from(serde_json::to_vec(value).unwrap()))
+        .unwrap()
+}
+
+fn error_response(status: StatusCode, error: impl ToString) -> Response<Body> {
+    json_response(
+        status,
+        &ErrorResponse {
+            error: error.to_string(),
+        },
+    )
+}
+
+async fn handle(
+    service: Arc<ProverService>,
+    request: Request<Body>,
+) -> Result<Response<Body>, Infallible> {
+    let method = request.method().clone();
+    let path = request.uri().path().to_string();
+    let response = match (method, path.as_str()) {
+        (Method::POST, "/prove_block") => match body::to_bytes(request.into_body()).await {
+            Ok(bytes) => match serde_json::from_slice::<ProveBlockRequest>(&bytes) {
+                Ok(prove_block) => {
+                    let job_id =
+                        service.prove_block(ProveRequest::BlockNumber(prove_block.block_number));
+                    json_response(StatusCode::OK, &ProveBlockResponse { job_id })
+                }
+                Err(err) => error_response(StatusCode::BAD_REQUEST, err),
+            },
+            Err(err) => error_response(StatusCode::BAD_REQUEST, err),
+        },
+        (Method::GET, path) if path.starts_with("/proof_status/") => {
+            match path["/proof_status/".len()..].parse::<JobId>() {
+                Ok(id) => match service.proof_status(id) {
+                    Some(status) => json_response(StatusCode::OK, &status),
+                    None => error_response(StatusCode::NOT_FOUND, format!("unknown job {}", id)),
+                },
+                Err(err) => error_response(StatusCode::BAD_REQUEST, err),
+            }
+        }
+        _ => error_response(StatusCode::NOT_FOUND, "unknown endpoint"),
+    };
+    Ok(response)
+}
+
+#[tokio::main]
+async fn main() -> Result<(), Error> {
+    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info")).init();
+    let args = Args::parse();
+    let service = Arc::new(ProverService::start(
+        args.geth_url,
+        ParamsCache::new(&args.params_dir)?,
+        KeyStore::new(&args.keys_dir)?,
+        args.backend,
+    ));
+    log::info!("proving on {}", args.backend);
+
+    let make_service = make_service_fn(move |_| {
+        let service = service.clone();
+        async move { Ok::<_, Infallible>(service_fn(move |request| handle(service.clone(), request))) }
+    });
+    log::info!("listening on {}", args.listen);
+    if let Err(err) = Server::bind(&args.listen).serve(make_service).await {
+        log::error!("server error: {}", err);
+    }
+    Ok(())
+}
@@ -0,0 +1,120 @@
+//! The circuits that can be proved, and the sizes they are configured with.
+
+use bus_mapping::{circuit_input_builder::FixedCParams, mock::BlockData};
+use eth_types::geth_types::GethData;
+use halo2_proofs::{halo2curves::bn256::Fr, plonk::Circuit};
+use mock::TestContext;
+use serde::{Deserialize, Serialize};
+use strum_macros::{Display, EnumIter, EnumString};
+use zkevm_circuits::{
+    bytecode_circuit::TestBytecodeCircuit,
+    copy_circuit::TestCopyCircuit,
+    evm_circuit::TestEvmCircuit,
+    exp_circuit::TestExpCircuit,
+    keccak_circuit::TestKeccakCircuit,
+    pi_circuit::TestPiCircuit,
+    state_circuit::TestStateCircuit,
+    super_circuit::SuperCircuit,
+    tx_circuit::TestTxCircuit,
+    util::SubCircuit,
+    witness::{block_convert, Block},
+};
+
+/// Sizes of the circuits.  The keys only fit the blocks that are padded to
+/// these sizes, so they are fixed for all the proofs.
+pub const CIRCUITS_PARAMS: FixedCParams = FixedCParams {
+    max_rws: 5888,
+    max_txs: 4,
+    max_calldata: 512,
+    max_copy_rows: 5888,
+    max_exp_steps: 1000,
+    max_bytecode: 5000,
+    max_evm_rows: 10000,
+    max_keccak_rows: 38000,
+    anchor_tx: None,
+};
+
+/// Identifier of a circuit, used in the file names and headers of its keys.
+#[derive(
+    Clone, Copy, Debug, PartialEq, Eq, Display, EnumIter, EnumString, Serialize, Deserialize,
+)]
+#[strum(serialize_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum CircuitId {
+    /// EVM circuit
+    Evm,
+    /// State circuit
+    State,
+    /// Tx circuit
+    Tx,
+    /// Bytecode circuit
+    Bytecode,
+    /// Copy circuit
+    Copy,
+    /// Exp circuit
+    Exp,
+    /// Keccak circuit
+    Keccak,
+    /// PI circuit
+    Pi,
+    /// Super circuit, with all the other circuits but the PI one
+    Super,
+}
+
+impl CircuitId {
+    /// Returns the degree that fits the circuit configured with
+    /// [`CIRCUITS_PARAMS`].
+    pub fn degree(&self) -> u32 {
+        match self {
+            CircuitId::Evm => 18,
+            CircuitId::State => 17,
+            CircuitId::Tx => 20,
+            CircuitId::Bytecode => 16,
+            CircuitId::Copy => 16,
+            CircuitId::Exp => 16,
+            CircuitId::Keccak => 16,
+            CircuitId::Pi => 17,
+            CircuitId::Super => 20,
+        }
+    }
+
+    /// Calls the visitor with the concrete type of the circuit.
+    pub fn visit<V: CircuitVisitor>(self, visitor: V) -> V::Output {
+        match self {
+            CircuitId::Evm => visitor.visit::<TestEvmCircuit<Fr>>(self),
+            CircuitId::State => visitor.visit::<TestStateCircuit<Fr>>(self),
+            CircuitId::Tx => visitor.visit::<TestTxCircuit<Fr>>(self),
+            CircuitId::Bytecode => visitor.visit::<TestBytecodeCircuit<Fr>>(self),
+            CircuitId::Copy => visitor.visit::<TestCopyCircuit<Fr>>(self),
+            CircuitId::Exp => visitor.visit::<TestExpCircuit<Fr>>(self),
+            CircuitId::Keccak => visitor.visit::<TestKeccakCircuit<Fr>>(self),
+            CircuitId::Pi => visitor.visit::<TestPiCircuit<Fr>>(self),
+            CircuitId::Super => visitor.visit::<SuperCircuit<Fr>>(self),
+        }
+    }
+}
+
+/// Operation that is generic over the type of the circuit, to run it from a
+/// [`CircuitId`] known only at runtime.
+pub trait CircuitVisitor {
+    /// Result of the operation
+    type Output;
+
+    /// Runs the operation for the circuit `C`, identified by `id`.
+    fn visit<C: SubCircuit<Fr> + Circuit<Fr>>(self, id: CircuitId) -> Self::Output;
+}
+
+/// Returns a block without transactions, padded to [`CIRCUITS_PARAMS`].  The
+/// circuits built from it have the same fixed columns as for any other block,
+/// so it is used for the key generation.
+pub fn empty_block() -> Block<Fr> {
+    let block: GethData = TestContext::<0, 0>::new(None, |_| {}, |_, _| {}, |b, _| b)
+        .expect("the empty context is valid")
+        .into();
+    let mut builder = BlockData::new_from_geth_data_with_params(block.clone(), CIRCUITS_PARAMS)
+        .new_circuit_input_builder();
+    builder
+        .handle_block(&block.eth_block, &block.geth_traces)
+        .expect("the empty block is valid");
+    block_convert(&builder).expect("the empty block is valid")
+}
@@ -0,0 +1,58 @@
+//! Error module for the prover crate
+
+use core::fmt::{Display, Formatter, Result as FmtResult};
+use halo2_proofs::plonk;
+use std::{error::Error as StdError, io};
+
+/// Error type for any prover related failure.
+#[derive(Debug)]
+pub enum Error {
+    /// Reading or writing params, keys or proofs failed.
+    Io(io::Error),
+    /// Key generation or proving failed.
+    Plonk(plonk::Error),
+    /// Fetching a block or building its witness failed.
+    Witness(bus_mapping::Error),
+    /// The header of a serialized key doesn't match the expected one: it was
+    /// written by another version, for another circuit or degree, or with
+    /// other params.
+    KeyMismatch(String),
+    /// A proof can't be decoded, or doesn't match the verifying key or params
+    /// it is verified with.
+    InvalidProof(String),
+    /// The proving backend is unknown or can't be started.
+    Backend(String),
+}
+
+impl From<io::Error> for Error {
+    fn from(err: io::Error) -> Self {
+        Error::Io(err)
+    }
+}
+
+impl From<plonk::Error> for Error {
+    fn from(err: plonk::Error) -> Self {
+        Error::Plonk(err)
+    }
+}
+
+impl From<bus_mapping::Error> for Error {
+    fn from(err: bus_mapping::Error) -> Self {
+        Error::Witness(err)
+    }
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match self {
+            Error::Io(err) => write!(f, "io error: {}", err),
+            Error::Plonk(err) => write!(f, "plonk error: {:?}", err),
+            Error::Witness(err) => write!(f, "witness error: {}", err),
+            Error::KeyMismatch(msg) => write!(f, "key mismatch: {}", msg),
+            Error::InvalidProof(msg) => write!(f, "invalid proof: {}", msg),
+            Error::Backend(msg) => write!(f, "backend error: {}", msg),
+        }
+    }
+}
+
+impl StdError for Error {}
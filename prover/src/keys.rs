@@ -0,0 +1,251 @@
+//! Proving and verifying keys serialized on disk.
+//!
+//! Each key file starts with a [`KeyHeader`], so that a key generated by
+//! another version of the circuits or with other params is rejected when
+//! loading it instead of producing invalid proofs.
+
+use crate::{circuit::CircuitId, Error};
+use halo2_proofs::{
+    halo2curves::bn256::{Bn256, Fr, G1Affine},
+    plonk::{keygen_pk, keygen_vk, Circuit, ProvingKey, VerifyingKey},
+    poly::kzg::commitment::ParamsKZG,
+    SerdeFormat,
+};
+use std::{
+    fs::{self, File},
+    io::{BufReader, BufWriter, Read, Write},
+    path::{Path, PathBuf},
+};
+
+/// Version of the key files.  It must be bumped whenever the circuits change
+/// in a way that changes their keys.
+pub const KEY_FORMAT_VERSION: u32 = 1;
+
+const KEY_MAGIC: &[u8; 8] = b"zkevmkey";
+
+/// Header of a serialized key.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct KeyHeader {
+    /// Version of the key format and circuits
+    pub version: u32,
+    /// Circuit of the key
+    pub circuit: CircuitId,
+    /// Degree of the circuit
+    pub degree: u32,
+    /// Digest of the params the key was generated with
+    pub params_digest: [u8; 32],
+}
+
+impl KeyHeader {
+    /// Returns the header of the keys of the current version.
+    pub fn new(circuit: CircuitId, degree: u32, params_digest: [u8; 32]) -> Self {
+        Self {
+            version: KEY_FORMAT_VERSION,
+            circuit,
+            degree,
+            params_digest,
+        }
+    }
+
+    /// Writes the header.
+    pub fn write<W: Write>(&self, writer: &mut W) -> Result<(), Error> {
+        let circuit = self.circuit.to_string();
+        writer.write_all(KEY_MAGIC)?;
+        writer.write_all(&self.version.to_le_bytes())?;
+        writer.write_all(&[circuit.len() as u8])?;
+        writer.write_all(circuit.as_bytes())?;
+        writer.write_all(&self.degree.to_le_bytes())?;
+        writer.write_all(&self.params_digest)?;
+        Ok(())
+    }
+
+    /// Reads a header.
+    pub fn read<R: Read>(reader: &mut R) -> Result<Self, Error> {
+        let mut magic = [0; 8];
+        reader.read_exact(&mut magic)?;
+        if &magic != KEY_MAGIC {
+            return Err(Error::KeyMismatch("not a key file".to_string()));
+        }
+        let mut u32_bytes = [0; 4];
+        reader.read_exact(&mut u32_bytes)?;
+        let version = u32::from_le_bytes(u32_bytes);
+        let mut len = [0; 1];
+        reader.read_exact(&mut len)?;
+        let mut circuit = vec![0; len[0] as usize];
+        reader.read_exact(&mut circuit)?;
+        let circuit = String::from_utf8_lossy(&circuit)
+            .parse()
+            .map_err(|_| Error::KeyMismatch("unknown circuit".to_string()))?;
+        reader.read_exact(&mut u32_bytes)?;
+        let degree = u32::from_le_bytes(u32_bytes);
+        let mut params_digest = [0; 32];
+        reader.read_exact(&mut params_digest)?;
+        Ok(Self {
+            version,
+            circuit,
+            degree,
+            params_digest,
+        })
+    }
+
+    /// Returns an error if the header is not the `expected` one.
+    pub fn check(&self, expected: &KeyHeader) -> Result<(), Error> {
+        if self.version != expected.version {
+            return Err(Error::KeyMismatch(format!(
+                "version {} instead of {}",
+                self.version, expected.version
+            )));
+        }
+        if self.circuit != expected.circuit || self.degree != expected.degree {
+            return Err(Error::KeyMismatch(format!(
+                "{} circuit of degree {} instead of {} circuit of degree {}",
+                self.circuit, self.degree, expected.circuit, expected.degree
+            )));
+        }
+        if self.params_digest != expected.params_digest {
+            return Err(Error::KeyMismatch(
+                "generated with other params".to_string(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Directory holding the keys, in files named `{circuit}_{degree}.pk` and
+/// `{circuit}_{degree}.vk`.
+#[derive(Clone, Debug)]
+pub struct KeyStore {
+    dir: PathBuf,
+}
+
+impl KeyStore {
+    /// Returns the store in `dir`, which is created if needed.
+    pub fn new(dir: impl AsRef<Path>) -> Result<Self, Error> {
+        fs::create_dir_all(dir.as_ref())?;
+        Ok(Self {
+            dir: dir.as_ref().to_path_buf(),
+        })
+    }
+
+    /// Returns the path of the proving key of `header`.
+    pub fn pk_path(&self, header: &KeyHeader) -> PathBuf {
+        self.dir
+            .join(format!("{}_{}.pk", header.circuit, header.degree))
+    }
+
+    /// Returns the path of the verifying key of `header`.
+    pub fn vk_path(&self, header: &KeyHeader) -> PathBuf {
+        self.dir
+            .join(format!("{}_{}.vk", header.circuit, header.degree))
+    }
+
+    /// Generates the keys of `circuit` and writes them with `header`.
+    pub fn generate<C: Circuit<Fr>>(
+        &self,
+        header: &KeyHeader,
+        params: &ParamsKZG<Bn256>,
+        circuit: &C,
+    ) -> Result<ProvingKey<G1Affine>, Error> {
+        log::info!(
+            "generating the keys of the {} circuit of degree {}",
+            header.circuit,
+            header.degree
+        );
+        let vk = keygen_vk(params, circuit)?;
+        let pk = keygen_pk(params, vk, circuit)?;
+
+        let mut writer = BufWriter::new(File::create(self.pk_path(header))?);
+        header.write(&mut writer)?;
+        pk.write(&mut writer, SerdeFormat::RawBytes)?;
+
+        let mut writer = BufWriter::new(File::create(self.vk_path(header))?);
+        header.write(&mut writer)?;
+        pk.get_vk().write(&mut writer, SerdeFormat::RawBytes)?;
+
+        Ok(pk)
+    }
+
+    /// Reads the proving key of `header`, for the circuit `C` configured
+    /// with `circuit_params`.
+    pub fn load_pk<C: Circuit<Fr>>(
+        &self,
+        header: &KeyHeader,
+        circuit_params: C::Params,
+    ) -> Result<ProvingKey<G1Affine>, Error> {
+        let mut reader = BufReader::new(File::open(self.pk_path(header))?);
+        KeyHeader::read(&mut reader)?.check(header)?;
+        Ok(ProvingKey::read::<_, C>(
+            &mut reader,
+            SerdeFormat::RawBytes,
+            circuit_params,
+        )?)
+    }
+
+    /// Reads the verifying key of `header`, for the circuit `C` configured
+    /// with `circuit_params`.
+    pub fn load_vk<C: Circuit<Fr>>(
+        &self,
+        header: &KeyHeader,
+        circuit_params: C::Params,
+    ) -> Result<VerifyingKey<G1Affine>, Error> {
+        let mut reader = BufReader::new(File::open(self.vk_path(header))?);
+        KeyHeader::read(&mut reader)?.check(header)?;
+        Ok(VerifyingKey::read::<_, C>(
+            &mut reader,
+            SerdeFormat::RawBytes,
+            circuit_params,
+        )?)
+    }
+
+    /// Reads the proving key of `header`, or generates it when it's missing
+    /// or outdated.
+    pub fn load_or_generate_pk<C: Circuit<Fr>>(
+        &self,
+        header: &KeyHeader,
+        params: &ParamsKZG<Bn256>,
+        circuit: &C,
+    ) -> Result<ProvingKey<G1Affine>, Error> {
+        match self.load_pk::<C>(header, circuit.params()) {
+            Ok(pk) => Ok(pk),
+            Err(Error::Io(err)) if err.kind() == std::io::ErrorKind::NotFound => {
+                self.generate(header, params, circuit)
+            }
+            Err(Error::KeyMismatch(msg)) => {
+                log::warn!("regenerating the {} keys: {}", header.circuit, msg);
+                self.generate(header, params, circuit)
+            }
+            Err(err) => Err(err),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn header_roundtrip() {
+        let header = KeyHeader::new(CircuitId::Evm, 18, [7; 32]);
+        let mut bytes = Vec::new();
+        header.write(&mut bytes).unwrap();
+        let read = KeyHeader::read(&mut &bytes[..]).unwrap();
+        assert_eq!(read, header);
+        read.check(&header).unwrap();
+
+        let other_params = KeyHeader::new(CircuitId::Evm, 18, [8; 32]);
+        assert!(matches!(
+            read.check(&other_params),
+            Err(Error::KeyMismatch(_))
+        ));
+        let other_circuit = KeyHeader::new(CircuitId::State, 18, [7; 32]);
+        assert!(matches!(
+            read.check(&other_circuit),
+            Err(Error::KeyMismatch(_))
+        ));
+        let outdated = KeyHeader {
+            version: KEY_FORMAT_VERSION - 1,
+            ..header
+        };
+        assert!(matches!(read.check(&outdated), Err(Error::KeyMismatch(_))));
+    }
+}
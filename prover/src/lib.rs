@@ -0,0 +1,25 @@
+//! # Prover
+//!
+//! Management of the artifacts needed to prove the zkEVM circuits: the KZG
+//! params, cached on disk per degree, and the proving/verifying keys of each
+//! circuit, serialized with a header recording the format version and the
+//! digest of the params they were generated with.  The proofs carry the same
+//! digests, so that a verifier can check them with the matching artifacts.
+//!
+//! The proving runs on a [`backend::Backend`], the CPU with a configurable
+//! number of threads.  The [`service`] queues the proofs of blocks requested to the
+//! `prover-server`, which exposes it over HTTP.
+
+#![deny(rustdoc::broken_intra_doc_links)]
+#![deny(missing_docs)]
+#![deny(unsafe_code)]
+
+pub mod backend;
+pub mod circuit;
+pub mod error;
+pub mod keys;
+pub mod params;
+pub mod proof;
+pub mod service;
+
+pub use error::Error;
@@ -0,0 +1,83 @@
+//! KZG params cached on disk, one file per degree.
+
+use crate::Error;
+use halo2_proofs::{
+    halo2curves::bn256::Bn256,
+    poly::{commitment::Params, kzg::commitment::ParamsKZG},
+};
+use rand::SeedableRng;
+use rand_chacha::ChaCha20Rng;
+use sha3::{Digest, Keccak256};
+use std::{
+    fs::{self, File},
+    io::{BufReader, BufWriter},
+    path::{Path, PathBuf},
+};
+
+/// Directory holding the KZG params, in files named `kzg_bn254_{degree}.srs`.
+///
+/// The params of a trusted setup ceremony can be copied there.  The missing
+/// ones are derived from params of a higher degree when there are some, and
+/// generated otherwise, which is insecure and only fit for development.
+#[derive(Clone, Debug)]
+pub struct ParamsCache {
+    dir: PathBuf,
+}
+
+impl ParamsCache {
+    /// Returns the cache stored in `dir`, which is created if needed.
+    pub fn new(dir: impl AsRef<Path>) -> Result<Self, Error> {
+        fs::create_dir_all(dir.as_ref())?;
+        Ok(Self {
+            dir: dir.as_ref().to_path_buf(),
+        })
+    }
+
+    /// Returns the path of the params of `degree`.
+    pub fn path(&self, degree: u32) -> PathBuf {
+        self.dir.join(format!("kzg_bn254_{}.srs", degree))
+    }
+
+    /// Returns the params of `degree`, reading them from the cache, or
+    /// downsizing or generating them and storing them in the cache.
+    pub fn load_or_setup(&self, degree: u32) -> Result<ParamsKZG<Bn256>, Error> {
+        let path = self.path(degree);
+        if path.exists() {
+            log::debug!("reading KZG params from {}", path.display());
+            return Ok(ParamsKZG::read(&mut BufReader::new(File::open(path)?))?);
+        }
+
+        let params = match self.higher_degree(degree) {
+            Some(higher) => {
+                log::info!("downsizing KZG params from degree {} to {}", higher, degree);
+                let mut params =
+                    ParamsKZG::<Bn256>::read(&mut BufReader::new(File::open(self.path(higher))?))?;
+                params.downsize(degree);
+                params
+            }
+            None => {
+                log::warn!(
+                    "generating insecure KZG params of degree {}, only fit for development",
+                    degree
+                );
+                ParamsKZG::<Bn256>::setup(degree, ChaCha20Rng::from_entropy())
+            }
+        };
+        params.write(&mut BufWriter::new(File::create(path)?))?;
+        Ok(params)
+    }
+
+    /// Returns the lowest degree above `degree` with params in the cache.
+    fn higher_degree(&self, degree: u32) -> Option<u32> {
+        // the params of degree above 28 don't fit in memory anyway
+        (degree + 1..=28).find(|higher| self.path(*higher).exists())
+    }
+}
+
+/// Returns the keccak hash of the serialized params, recorded in the keys and
+/// the proofs to check that they are used with the same params.
+pub fn params_digest(params: &ParamsKZG<Bn256>) -> Result<[u8; 32], Error> {
+    let mut bytes = Vec::new();
+    params.write(&mut bytes)?;
+    Ok(Keccak256::digest(&bytes).into())
+}
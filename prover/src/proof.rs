@@ -0,0 +1,322 @@
+//! Proofs with the metadata needed to verify them elsewhere: the circuit, the
+//! public inputs and the digests of the params and verifying key.
+//!
+//! Besides serde, a [`Proof`] has a binary encoding ([`Proof::to_bytes`])
+//! which is stable across versions of the crate: it starts with a magic and a
+//! version, and all the integers are little-endian.
+
+use crate::{circuit::CircuitId, params::params_digest, Error};
+use halo2_proofs::{
+    halo2curves::{
+        bn256::{Bn256, Fr, G1Affine},
+        ff::PrimeField,
+    },
+    plonk::{create_proof, verify_proof, Circuit, ProvingKey, VerifyingKey},
+    poly::{
+        commitment::ParamsProver,
+        kzg::{
+            commitment::{KZGCommitmentScheme, ParamsKZG},
+            multiopen::{ProverSHPLONK, VerifierSHPLONK},
+            strategy::SingleStrategy,
+        },
+    },
+    transcript::{
+        Blake2bRead, Blake2bWrite, Challenge255, TranscriptReadBuffer, TranscriptWriterBuffer,
+    },
+};
+use rand::rngs::OsRng;
+use serde::{Deserialize, Serialize};
+use std::{
+    io::Read,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// Version of the binary encoding of the proofs.
+pub const PROOF_FORMAT_VERSION: u32 = 1;
+
+const PROOF_MAGIC: &[u8; 8] = b"zkevmprf";
+
+/// A proof of a circuit, with its metadata.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Proof {
+    /// Circuit proved
+    pub circuit: CircuitId,
+    /// Digest of the params of the proof
+    pub params_digest: [u8; 32],
+    /// Digest of the verifying key of the circuit
+    pub vk_digest: [u8; 32],
+    /// Public inputs, per instance column, as little-endian field elements
+    pub instances: Vec<Vec<[u8; 32]>>,
+    /// The proof itself
+    pub bytes: Vec<u8>,
+    /// Start of the proving, in milliseconds since the UNIX epoch
+    pub started_at: u64,
+    /// End of the proving, in milliseconds since the UNIX epoch
+    pub finished_at: u64,
+}
+
+/// Returns the digest of a verifying key, recorded in the proofs.
+pub fn vk_digest(vk: &VerifyingKey<G1Affine>) -> [u8; 32] {
+    vk.transcript_repr().to_repr()
+}
+
+fn now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_millis() as u64)
+        .unwrap_or_default()
+}
+
+impl Proof {
+    /// Returns the public inputs as field elements.
+    pub fn instances(&self) -> Result<Vec<Vec<Fr>>, Error> {
+        self.instances
+            .iter()
+            .map(|column| {
+                column
+                    .iter()
+                    .map(|repr| {
+                        Option::from(Fr::from_repr(*repr)).ok_or_else(|| {
+                            Error::InvalidProof("instance is not a field element".to_string())
+                        })
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// Returns the binary encoding of the proof.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let circuit = self.circuit.to_string();
+        let mut bytes = Vec::with_capacity(self.bytes.len() + 256);
+        bytes.extend_from_slice(PROOF_MAGIC);
+        bytes.extend_from_slice(&PROOF_FORMAT_VERSION.to_le_bytes());
+        bytes.push(circuit.len() as u8);
+        bytes.extend_from_slice(circuit.as_bytes());
+        bytes.extend_from_slice(&self.params_digest);
+        bytes.extend_from_slice(&self.vk_digest);
+        bytes.extend_from_slice(&self.started_at.to_le_bytes());
+        bytes.extend_from_slice(&self.finished_at.to_le_bytes());
+        bytes.extend_from_slice(&(self.instances.len() as u32).to_le_bytes());
+        for column in &self.instances {
+            bytes.extend_from_slice(&(column.len() as u32).to_le_bytes());
+            for value in column {
+                bytes.extend_from_slice(value);
+            }
+        }
+        bytes.extend_from_slice(&(self.bytes.len() as u32).to_le_bytes());
+        bytes.extend_from_slice(&self.bytes);
+        bytes
+    }
+
+    /// Decodes a proof from its binary encoding.
+    pub fn from_bytes(mut bytes: &[u8]) -> Result<Self, Error> {
+        let reader = &mut bytes;
+        let mut magic = [0; 8];
+        reader.read_exact(&mut magic)?;
+        if &magic != PROOF_MAGIC {
+            return Err(Error::InvalidProof("not a proof".to_string()));
+        }
+        let version = read_u32(reader)?;
+        if version != PROOF_FORMAT_VERSION {
+            return Err(Error::InvalidProof(format!(
+                "unsupported version {}",
+                version
+            )));
+        }
+        let mut len = [0; 1];
+        reader.read_exact(&mut len)?;
+        let mut circuit = vec![0; len[0] as usize];
+        reader.read_exact(&mut circuit)?;
+        let circuit = String::from_utf8_lossy(&circuit)
+            .parse()
+            .map_err(|_| Error::InvalidProof("unknown circuit".to_string()))?;
+        let mut params_digest = [0; 32];
+        reader.read_exact(&mut params_digest)?;
+        let mut vk_digest = [0; 32];
+        reader.read_exact(&mut vk_digest)?;
+        let started_at = read_u64(reader)?;
+        let finished_at = read_u64(reader)?;
+        let instances = (0..read_len(reader, 4)?)
+            .map(|_| {
+                (0..read_len(reader, 32)?)
+                    .map(|_| {
+                        let mut value = [0; 32];
+                        reader.read_exact(&mut value)?;
+                        Ok(value)
+                    })
+                    .collect::<Result<Vec<_>, Error>>()
+            })
+            .collect::<Result<Vec<_>, Error>>()?;
+        let mut proof = vec![0; read_len(reader, 1)?];
+        reader.read_exact(&mut proof)?;
+        if !reader.is_empty() {
+            return Err(Error::InvalidProof("trailing bytes".to_string()));
+        }
+        Ok(Self {
+            circuit,
+            params_digest,
+            vk_digest,
+            instances,
+            bytes: proof,
+            started_at,
+            finished_at,
+        })
+    }
+}
+
+fn read_u32(reader: &mut &[u8]) -> Result<u32, Error> {
+    let mut bytes = [0; 4];
+    reader.read_exact(&mut bytes)?;
+    Ok(u32::from_le_bytes(bytes))
+}
+
+/// Reads the length of a sequence of items of at least `item_size` bytes, and
+/// checks that the bytes left can hold them, before anything is allocated.
+fn read_len(reader: &mut &[u8], item_size: usize) -> Result<usize, Error> {
+    let len = read_u32(reader)? as usize;
+    if len.saturating_mul(item_size) > reader.len() {
+        return Err(Error::InvalidProof(format!(
+            "length {} exceeds the {} bytes left",
+            len,
+            reader.len()
+        )));
+    }
+    Ok(len)
+}
+
+fn read_u64(reader: &mut &[u8]) -> Result<u64, Error> {
+    let mut bytes = [0; 8];
+    reader.read_exact(&mut bytes)?;
+    Ok(u64::from_le_bytes(bytes))
+}
+
+/// Proves `circuit` with the public inputs `instances`.
+pub fn prove<C: Circuit<Fr>>(
+    id: CircuitId,
+    params: &ParamsKZG<Bn256>,
+    pk: &ProvingKey<G1Affine>,
+    circuit: C,
+    instances: Vec<Vec<Fr>>,
+) -> Result<Proof, Error> {
+    let started_at = now_millis();
+    let instance_slices: Vec<&[Fr]> = instances.iter().map(|column| column.as_slice()).collect();
+    let mut transcript = Blake2bWrite::<_, G1Affine, Challenge255<_>>::init(vec![]);
+    create_proof::<KZGCommitmentScheme<Bn256>, ProverSHPLONK<'_, Bn256>, _, _, _, _>(
+        params,
+        pk,
+        &[circuit],
+        &[&instance_slices],
+        OsRng,
+        &mut transcript,
+    )?;
+    Ok(Proof {
+        circuit: id,
+        params_digest: params_digest(params)?,
+        vk_digest: vk_digest(pk.get_vk()),
+        instances: instances
+            .iter()
+            .map(|column| column.iter().map(|value| value.to_repr()).collect())
+            .collect(),
+        bytes: transcript.finalize(),
+        started_at,
+        finished_at: now_millis(),
+    })
+}
+
+/// Verifies `proof` with the verifying key and params of its circuit.
+pub fn verify(
+    proof: &Proof,
+    vk: &VerifyingKey<G1Affine>,
+    params: &ParamsKZG<Bn256>,
+) -> Result<(), Error> {
+    if proof.vk_digest != vk_digest(vk) {
+        return Err(Error::InvalidProof(format!(
+            "proof of another {} verifying key",
+            proof.circuit
+        )));
+    }
+    if proof.params_digest != params_digest(params)? {
+        return Err(Error::InvalidProof("proof with other params".to_string()));
+    }
+    let instances = proof.instances()?;
+    let instance_slices: Vec<&[Fr]> = instances.iter().map(|column| column.as_slice()).collect();
+    let mut transcript = Blake2bRead::<_, G1Affine, Challenge255<_>>::init(&proof.bytes[..]);
+    verify_proof::<KZGCommitmentScheme<Bn256>, VerifierSHPLONK<'_, Bn256>, _, _, _>(
+        params.verifier_params(),
+        vk,
+        SingleStrategy::new(params),
+        &[&instance_slices],
+        &mut transcript,
+    )?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn proof() -> Proof {
+        Proof {
+            circuit: CircuitId::State,
+            params_digest: [1; 32],
+            vk_digest: [2; 32],
+            instances: vec![vec![Fr::from(3).to_repr(), Fr::from(4).to_repr()], vec![]],
+            bytes: vec![5; 100],
+            started_at: 1_700_000_000_000,
+            finished_at: 1_700_000_060_000,
+        }
+    }
+
+    #[test]
+    fn bytes_roundtrip() {
+        let proof = proof();
+        let bytes = proof.to_bytes();
+        assert_eq!(Proof::from_bytes(&bytes).unwrap(), proof);
+        assert_eq!(
+            proof.instances().unwrap(),
+            vec![vec![Fr::from(3), Fr::from(4)], vec![]]
+        );
+
+        assert!(Proof::from_bytes(&bytes[..bytes.len() - 1]).is_err());
+        let mut trailing = bytes.clone();
+        trailing.push(0);
+        assert!(Proof::from_bytes(&trailing).is_err());
+        let mut other_version = bytes;
+        other_version[8] += 1;
+        assert!(matches!(
+            Proof::from_bytes(&other_version),
+            Err(Error::InvalidProof(_))
+        ));
+    }
+
+    #[test]
+    fn bytes_with_too_long_lengths() {
+        let bytes = proof().to_bytes();
+        // The proof length is the last u32 before the 100 bytes of the proof.
+        let proof_len = bytes.len() - 100 - 4;
+        let mut too_long = bytes.clone();
+        too_long[proof_len..proof_len + 4].copy_from_slice(&u32::MAX.to_le_bytes());
+        assert!(matches!(
+            Proof::from_bytes(&too_long),
+            Err(Error::InvalidProof(_))
+        ));
+
+        // The number of instance columns follows the magic, the version, the
+        // circuit, the digests and the timestamps.
+        let columns = 8 + 4 + 1 + CircuitId::State.to_string().len() + 32 + 32 + 8 + 8;
+        let mut too_many = bytes;
+        too_many[columns..columns + 4].copy_from_slice(&u32::MAX.to_le_bytes());
+        assert!(matches!(
+            Proof::from_bytes(&too_many),
+            Err(Error::InvalidProof(_))
+        ));
+    }
+
+    #[test]
+    fn json_roundtrip() {
+        let proof = proof();
+        let json = serde_json::to_string(&proof).unwrap();
+        assert_eq!(serde_json::from_str::<Proof>(&json).unwrap(), proof);
+    }
+}
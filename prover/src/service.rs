@@ -0,0 +1,281 @@
+//! Queue of proving jobs, run one after the other by a worker thread.
+//!
+//! A job proves a block with the super circuit: the block is fetched from a
+//! geth node with the [`BuilderClient`] and converted to the circuits witness,
+//! unless the witness is given directly.  The keys of the super circuit are
+//! loaded (or generated) once by the worker and kept for the next jobs.
+
+use crate::{
+    backend::Backend,
+    circuit::{CircuitId, CIRCUITS_PARAMS},
+    keys::{KeyHeader, KeyStore},
+    params::{params_digest, ParamsCache},
+    proof::{prove, Proof},
+    Error,
+};
+use bus_mapping::{circuit_input_builder::BuilderClient, rpc::GethClient};
+use ethers_providers::Http;
+use halo2_proofs::{
+    halo2curves::bn256::{Bn256, Fr, G1Affine},
+    plonk::ProvingKey,
+    poly::kzg::commitment::ParamsKZG,
+};
+use serde::Serialize;
+use std::{
+    any::Any,
+    collections::HashMap,
+    panic::{self, AssertUnwindSafe},
+    sync::{
+        mpsc::{channel, Receiver, Sender},
+        Arc, Mutex,
+    },
+    thread,
+};
+use url::Url;
+use zkevm_circuits::{
+    super_circuit::SuperCircuit,
+    util::SubCircuit,
+    witness::{block_convert, Block},
+};
+
+/// Identifier of a proving job.
+pub type JobId = u64;
+
+/// What a proving job proves.
+#[derive(Debug)]
+pub enum ProveRequest {
+    /// Block fetched from the geth node
+    BlockNumber(u64),
+    /// Witness of a block built by the caller
+    Witness(Box<Block<Fr>>),
+}
+
+/// Status of a proving job.
+#[derive(Clone, Debug, Serialize)]
+#[serde(tag = "status", rename_all = "lowercase")]
+pub enum JobStatus {
+    /// The job waits for the previous ones to finish
+    Queued,
+    /// The witness of the job is being built or proved
+    Proving,
+    /// The job succeeded
+    Done {
+        /// Proof in its binary encoding, hex encoded
+        proof: String,
+    },
+    /// The job failed
+    Failed {
+        /// Description of the failure
+        error: String,
+    },
+}
+
+/// Handle to the job queue, shared by the endpoints of the server.
+#[derive(Debug)]
+pub struct ProverService {
+    jobs: Arc<Mutex<HashMap<JobId, JobStatus>>>,
+    sender: Mutex<Sender<(JobId, ProveRequest)>>,
+    next_id: Mutex<JobId>,
+}
+
+impl ProverService {
+    /// Starts the worker proving the queued jobs on `backend`, fetching the
+    /// blocks from the geth node at `geth_url`.
+    pub fn start(
+        geth_url: Url,
+        params_cache: ParamsCache,
+        key_store: KeyStore,
+        backend: Backend,
+    ) -> Self {
+        let jobs = Arc::new(Mutex::new(HashMap::new()));
+        let (sender, receiver) = channel();
+        let worker = Worker {
+            geth_url,
+            params_cache,
+            key_store,
+            backend,
+            keys: None,
+            jobs: jobs.clone(),
+        };
+        thread::spawn(move || worker.run(receiver));
+        Self {
+            jobs,
+            sender: Mutex::new(sender),
+            next_id: Mutex::new(0),
+        }
+    }
+
+    /// Queues the proof of a block and returns the identifier of the job.
+    pub fn prove_block(&self, request: ProveRequest) -> JobId {
+        let id = {
+            let mut next_id = self.next_id.lock().unwrap();
+            *next_id += 1;
+            *next_id
+        };
+        self.jobs.lock().unwrap().insert(id, JobStatus::Queued);
+        if self.sender.lock().unwrap().send((id, request)).is_err() {
+            self.set_status(id, failed("the prover worker stopped"));
+        }
+        id
+    }
+
+    /// Returns the status of a job, or `None` if it doesn't exist.
+    pub fn proof_status(&self, id: JobId) -> Option<JobStatus> {
+        self.jobs.lock().unwrap().get(&id).cloned()
+    }
+
+    fn set_status(&self, id: JobId, status: JobStatus) {
+        self.jobs.lock().unwrap().insert(id, status);
+    }
+}
+
+fn failed(error: impl ToString) -> JobStatus {
+    JobStatus::Failed {
+        error: error.to_string(),
+    }
+}
+
+struct Worker {
+    geth_url: Url,
+    params_cache: ParamsCache,
+    key_store: KeyStore,
+    backend: Backend,
+    keys: Option<(ParamsKZG<Bn256>, ProvingKey<G1Affine>)>,
+    jobs: Arc<Mutex<HashMap<JobId, JobStatus>>>,
+}
+
+impl Worker {
+    fn run(mut self, receiver: Receiver<(JobId, ProveRequest)>) {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .expect("cannot start the runtime of the prover worker");
+        let jobs = self.jobs.clone();
+        run_jobs(&jobs, receiver, |request| {
+            runtime
+                .block_on(self.witness(request))
+                .and_then(|block| self.prove(&block))
+        });
+    }
+
+    async fn witness(&self, request: ProveRequest) -> Result<Block<Fr>, Error> {
+        match request {
+            ProveRequest::BlockNumber(block_number) => {
+                let geth_client = GethClient::new(Http::new(self.geth_url.clone()));
+                let cli = BuilderClient::new(geth_client, CIRCUITS_PARAMS).await?;
+                let (builder, _) = cli.gen_inputs(block_number).await?;
+                Ok(block_convert(&builder)?)
+            }
+            ProveRequest::Witness(block) => Ok(*block),
+        }
+    }
+
+    fn prove(&mut self, block: &Block<Fr>) -> Result<Proof, Error> {
+        let circuit = SuperCircuit::<Fr>::new_from_block(block);
+        let instances = circuit.instance();
+        if self.keys.is_none() {
+            let degree = CircuitId::Super.degree();
+            let params = self.params_cache.load_or_setup(degree)?;
+            let header = KeyHeader::new(CircuitId::Super, degree, params_digest(&params)?);
+            let pk = self
+                .key_store
+                .load_or_generate_pk(&header, &params, &circuit)?;
+            self.keys = Some((params, pk));
+        }
+        let (params, pk) = self.keys.as_ref().unwrap();
+        self.backend
+            .install(|| prove(CircuitId::Super, params, pk, circuit, instances))?
+    }
+}
+
+/// Runs the jobs received on `receiver` one after the other with `run_job`,
+/// and records their status in `jobs`.  A job that panics is marked as failed
+/// and the next jobs still run.
+fn run_jobs(
+    jobs: &Mutex<HashMap<JobId, JobStatus>>,
+    receiver: Receiver<(JobId, ProveRequest)>,
+    mut run_job: impl FnMut(ProveRequest) -> Result<Proof, Error>,
+) {
+    for (id, request) in receiver {
+        jobs.lock().unwrap().insert(id, JobStatus::Proving);
+        log::info!("proving job {}: {}", id, request_summary(&request));
+        let status = match panic::catch_unwind(AssertUnwindSafe(|| run_job(request))) {
+            Ok(Ok(proof)) => JobStatus::Done {
+                proof: hex::encode(proof.to_bytes()),
+            },
+            Ok(Err(err)) => {
+                log::error!("job {} failed: {}", id, err);
+                failed(err)
+            }
+            Err(payload) => {
+                let message = panic_message(payload.as_ref());
+                log::error!("job {} panicked: {}", id, message);
+                failed(format!("panicked: {}", message))
+            }
+        };
+        jobs.lock().unwrap().insert(id, status);
+    }
+}
+
+fn panic_message(payload: &(dyn Any + Send)) -> &str {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        message
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message
+    } else {
+        "unknown panic"
+    }
+}
+
+fn request_summary(request: &ProveRequest) -> String {
+    match request {
+        ProveRequest::BlockNumber(block_number) => format!("block {}", block_number),
+        ProveRequest::Witness(_) => "given witness".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn job_status_json() {
+        assert_eq!(
+            serde_json::to_string(&JobStatus::Queued).unwrap(),
+            r#"{"status":"queued"}"#
+        );
+        assert_eq!(
+            serde_json::to_string(&JobStatus::Done {
+                proof: "00ff".to_string()
+            })
+            .unwrap(),
+            r#"{"status":"done","proof":"00ff"}"#
+        );
+        assert_eq!(
+            serde_json::to_string(&failed("no block")).unwrap(),
+            r#"{"status":"failed","error":"no block"}"#
+        );
+    }
+
+    #[test]
+    fn panicking_job_fails_and_worker_keeps_running() {
+        let jobs = Mutex::new(HashMap::new());
+        let (sender, receiver) = channel();
+        for id in 1..=2 {
+            sender.send((id, ProveRequest::BlockNumber(id))).unwrap();
+        }
+        drop(sender);
+
+        run_jobs(&jobs, receiver, |request| match request {
+            ProveRequest::BlockNumber(1) => panic!("witness of block 1"),
+            _ => Err(Error::Backend("no backend".to_string())),
+        });
+
+        let jobs = jobs.into_inner().unwrap();
+        assert!(matches!(
+            &jobs[&1],
+            JobStatus::Failed { error } if error == "panicked: witness of block 1"
+        ));
+        assert!(matches!(&jobs[&2], JobStatus::Failed { .. }));
+    }
+}
@@ -0,0 +1,391 @@
+//! Runs the test suites through the circuits, with one subcommand per task.
+//!
+//! The exit code can be used to gate CI: 0 if all the tests passed (or were
+//! ignored), 1 if any of them failed or panicked and 2 if they could not be
+//! run at all (bad arguments, config or suite).
+use anyhow::{bail, Result};
+use clap::{Args, Parser, Subcommand};
+use std::{path::PathBuf, process::ExitCode};
+use testool::{
+    config::{Config, TestSuite},
+    live_block::run_block,
+    runner::{
+        filter_tests, list_tests, load_state_tests, parse_circuits, run_report, run_suite,
+        select_forks,
+    },
+    statetest::{fuzz, load_blockchain_tests_suite, CircuitsConfig, ResultLevel, SuiteTest},
+    utils::MainnetFork,
+};
+
+/// All the tests passed or were ignored
+const EXIT_SUCCESS: u8 = 0;
+/// Some test failed or panicked
+const EXIT_FAILURE: u8 = 1;
+/// The tests could not be run
+const EXIT_ERROR: u8 = 2;
+
+/// State tests runner
+#[derive(Parser, Debug)]
+#[clap(author, version, about, long_about = None)]
+struct Cli {
+    #[clap(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Runs the tests, exiting with 1 if any of them fails
+    Run {
+        #[clap(flatten)]
+        selection: Selection,
+        #[clap(flatten)]
+        run: RunArgs,
+        /// Reuses and updates the results of this csv file
+        #[clap(long)]
+        cache: Option<String>,
+        /// Writes the results as json in this file
+        #[clap(long)]
+        json: Option<String>,
+    },
+    /// Lists the ids of the tests, without running them
+    List {
+        #[clap(flatten)]
+        selection: Selection,
+    },
+    /// Runs the tests and writes the csv, json and html reports, compared
+    /// with the previous run of the suite
+    Report {
+        #[clap(flatten)]
+        selection: Selection,
+        #[clap(flatten)]
+        run: RunArgs,
+        /// Reuses the results of this csv file, except the ignored ones
+        #[clap(long)]
+        cache: Option<String>,
+        /// Only reruns the cached tests with these levels
+        #[clap(short, long, value_parser, value_delimiter = ',')]
+        levels: Vec<ResultLevel>,
+        /// Writes the results as json in this file
+        #[clap(long)]
+        json: Option<String>,
+    },
+    /// Generates and verifies real proofs of the tests instead of using the
+    /// MockProver
+    Prove {
+        #[clap(flatten)]
+        selection: Selection,
+        #[clap(flatten)]
+        run: RunArgs,
+        /// Writes the results as json in this file
+        #[clap(long)]
+        json: Option<String>,
+    },
+    /// Builds the circuit inputs of a block of a live chain from a node, and
+    /// runs the circuits for it
+    RunBlock {
+        /// Url of the node
+        #[clap(long)]
+        rpc: String,
+        /// Number of the block
+        #[clap(long)]
+        block: u64,
+        /// Fork whose rules are applied (by default the latest one)
+        #[clap(long, value_parser)]
+        fork: Option<MainnetFork>,
+        /// Circuits to execute, e.g. "evm,tx,copy" (by default evm,state)
+        #[clap(long, value_delimiter = ',')]
+        circuits: Vec<String>,
+        /// Generate and verify real proofs instead of using the MockProver
+        #[clap(long)]
+        prove: bool,
+    },
+    /// Runs random transactions and bytecode through the circuits, shrinking
+    /// the failing cases
+    Fuzz {
+        #[clap(flatten)]
+        run: RunArgs,
+        /// Seed of the generated cases
+        #[clap(long, default_value_t = 0)]
+        seed: u64,
+        /// Number of generated cases
+        #[clap(long, default_value_t = 100)]
+        iterations: usize,
+        /// Maximum number of instructions of the generated programs
+        #[clap(long, default_value_t = 32)]
+        max_instructions: usize,
+    },
+}
+
+/// Tests to load
+#[derive(Args, Debug)]
+struct Selection {
+    /// Suite of the config file
+    #[clap(long, default_value = "default")]
+    suite: String,
+
+    /// Forks to run the tests against, e.g. "Berlin,London,Shanghai" (by
+    /// default Merge)
+    #[clap(long, value_parser, value_delimiter = ',')]
+    fork: Vec<MainnetFork>,
+
+    /// The suite contains BlockchainTests, whose blocks are run one after the
+    /// other
+    #[clap(long)]
+    blockchain: bool,
+
+    /// Only the tests whose id matches this regex
+    #[clap(long)]
+    test: Option<String>,
+
+    /// Only the tests whose code uses this opcode (e.g. "SAR")
+    #[clap(long)]
+    uses_opcode: Option<String>,
+}
+
+/// How the tests are run
+#[derive(Args, Debug)]
+struct RunArgs {
+    /// Circuits to execute, e.g. "evm,tx,copy" (by default evm,state)
+    #[clap(long, value_delimiter = ',')]
+    circuits: Vec<String>,
+
+    /// Diffs geth's post state against the state of the circuit input
+    /// builder
+    #[clap(long)]
+    diff_post_state: bool,
+
+    /// Re-executes the transactions with an independent interpreter and
+    /// compares its storage accesses against the ones of the circuit input
+    /// builder
+    #[clap(long)]
+    reexecute: bool,
+
+    /// Checks the post state root expected by the filled tests
+    #[clap(long)]
+    check_state_root: bool,
+
+    /// Caches the geth traces in this folder
+    #[clap(long)]
+    cache_dir: Option<String>,
+
+    /// Skip the tests that run for longer than this, in seconds
+    #[clap(long)]
+    timeout: Option<u64>,
+
+    /// Skip the tests that run while the process uses more than this memory,
+    /// in MB
+    #[clap(long)]
+    max_memory: Option<u64>,
+
+    /// Number of tests executed in parallel (by default one per CPU)
+    #[clap(short, long)]
+    jobs: Option<usize>,
+}
+
+impl RunArgs {
+    fn circuits_config(&self, prove: bool) -> Result<CircuitsConfig> {
+        let mut circuits_config = CircuitsConfig::default();
+        if !self.circuits.is_empty() {
+            circuits_config.circuits = parse_circuits(&self.circuits)?;
+        }
+        circuits_config.prove = prove;
+        circuits_config.diff_post_state = self.diff_post_state;
+        circuits_config.reexecute = self.reexecute;
+        circuits_config.check_state_root = self.check_state_root;
+        if let Some(cache_dir) = &self.cache_dir {
+            std::fs::create_dir_all(cache_dir)?;
+            circuits_config.trace_cache_dir = Some(PathBuf::from(cache_dir));
+        }
+        Ok(circuits_config)
+    }
+}
+
+/// What is done with the selected tests
+enum Action<'a> {
+    List,
+    Run {
+        run: &'a RunArgs,
+        prove: bool,
+        cache: Option<&'a str>,
+        json: Option<&'a str>,
+    },
+    Report {
+        run: &'a RunArgs,
+        cache: Option<&'a str>,
+        levels: &'a [ResultLevel],
+        json: Option<&'a str>,
+    },
+}
+
+/// Loads the selected tests and applies `action` to them.  Returns false if
+/// any of them failed.
+fn load_and_apply(selection: &Selection, action: Action) -> Result<bool> {
+    let config = Config::load()?;
+    let mut suite = config.suite(&selection.suite)?.clone();
+    if let Action::Run { run, .. } | Action::Report { run, .. } = &action {
+        if run.timeout.is_some() {
+            suite.timeout = run.timeout;
+        }
+        if run.max_memory.is_some() {
+            suite.max_memory = run.max_memory;
+        }
+    }
+    let forks = select_forks(&selection.fork)?;
+
+    if selection.blockchain {
+        if selection.uses_opcode.is_some() {
+            bail!("--uses-opcode is not supported with --blockchain");
+        }
+        let tests = load_blockchain_tests_suite(&suite.path, config, &forks)?;
+        apply(selection, &suite, action, tests)
+    } else {
+        let tests = load_state_tests(&suite, config, &forks, selection.uses_opcode.as_deref())?;
+        apply(selection, &suite, action, tests)
+    }
+}
+
+fn apply<T: SuiteTest>(
+    selection: &Selection,
+    suite: &TestSuite,
+    action: Action,
+    mut tests: Vec<T>,
+) -> Result<bool> {
+    filter_tests(&mut tests, selection.test.as_deref())?;
+
+    match action {
+        Action::List => {
+            for test in list_tests(&tests) {
+                println!("{}", test);
+            }
+            Ok(true)
+        }
+        Action::Run {
+            run,
+            prove,
+            cache,
+            json,
+        } => run_suite(
+            tests,
+            &run.circuits_config(prove)?,
+            suite,
+            cache,
+            json,
+            run.jobs,
+        ),
+        Action::Report {
+            run,
+            cache,
+            levels,
+            json,
+        } => run_report(
+            &selection.suite,
+            tests,
+            &run.circuits_config(false)?,
+            suite,
+            cache,
+            levels,
+            json,
+            run.jobs,
+        ),
+    }
+}
+
+fn execute(cli: Cli) -> Result<bool> {
+    match &cli.command {
+        Command::Run {
+            selection,
+            run,
+            cache,
+            json,
+        } => load_and_apply(
+            selection,
+            Action::Run {
+                run,
+                prove: false,
+                cache: cache.as_deref(),
+                json: json.as_deref(),
+            },
+        ),
+        Command::List { selection } => load_and_apply(selection, Action::List),
+        Command::Report {
+            selection,
+            run,
+            cache,
+            levels,
+            json,
+        } => load_and_apply(
+            selection,
+            Action::Report {
+                run,
+                cache: cache.as_deref(),
+                levels,
+                json: json.as_deref(),
+            },
+        ),
+        Command::Prove {
+            selection,
+            run,
+            json,
+        } => load_and_apply(
+            selection,
+            Action::Run {
+                run,
+                prove: true,
+                cache: None,
+                json: json.as_deref(),
+            },
+        ),
+        Command::RunBlock {
+            rpc,
+            block,
+            fork,
+            circuits,
+            prove,
+        } => {
+            let mut circuits_config = CircuitsConfig::default();
+            if !circuits.is_empty() {
+                circuits_config.circuits = parse_circuits(circuits)?;
+            }
+            circuits_config.prove = *prove;
+            let block_run = tokio::runtime::Runtime::new()?.block_on(run_block(
+                rpc,
+                *block,
+                *fork,
+                &circuits_config,
+            ))?;
+            block_run.print_tty();
+            Ok(block_run.success())
+        }
+        Command::Fuzz {
+            run,
+            seed,
+            iterations,
+            max_instructions,
+        } => {
+            let failures = fuzz(
+                *seed,
+                *iterations,
+                *max_instructions,
+                &run.circuits_config(false)?,
+            );
+            for failure in &failures {
+                println!("{}\n  {}", failure.error, failure.case);
+            }
+            Ok(failures.is_empty())
+        }
+    }
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info")).init();
+
+    match execute(cli) {
+        Ok(true) => ExitCode::from(EXIT_SUCCESS),
+        Ok(false) => ExitCode::from(EXIT_FAILURE),
+        Err(err) => {
+            log::error!("{:#}", err);
+            ExitCode::from(EXIT_ERROR)
+        }
+    }
+}
@@ -1,5 +1,6 @@
 use anyhow::{anyhow, bail, ensure, Context, Result};
 use serde::Deserialize;
+use std::collections::HashMap;
 
 const CONFIG_FILE: &str = "Config.toml";
 
@@ -9,6 +10,8 @@ pub struct Config {
     pub set: Vec<TestsSet>,
     pub skip_paths: Vec<SkipPaths>,
     pub skip_tests: Vec<SkipTests>,
+    #[serde(default)]
+    pub known_failures: Vec<KnownFailures>,
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -17,9 +20,17 @@ pub struct TestSuite {
     pub path: String,
     pub max_gas: u64,
     pub max_steps: u64,
+    /// wall-clock time limit of a test, in seconds
+    pub timeout: Option<u64>,
+    /// approximate memory limit of the process while running a test, in MB
+    pub max_memory: Option<u64>,
 
     ignore_tests: Option<Vec<String>>,
     allow_tests: Option<Vec<String>>,
+
+    /// tests expected to fail, filled from `Config::known_failures`
+    #[serde(skip)]
+    known_failures: HashMap<String, FailureReason>,
 }
 
 impl Default for TestSuite {
@@ -29,8 +40,11 @@ impl Default for TestSuite {
             path: String::default(),
             max_gas: u64::MAX,
             max_steps: u64::MAX,
+            timeout: None,
+            max_memory: None,
             ignore_tests: Some(Vec::new()),
             allow_tests: None,
+            known_failures: HashMap::new(),
         }
     }
 }
@@ -49,6 +63,11 @@ impl TestSuite {
             unreachable!()
         }
     }
+
+    /// returns the reason why the test is expected to fail, if any
+    pub fn known_failure(&self, test_id: &str) -> Option<FailureReason> {
+        self.known_failures.get(test_id).copied()
+    }
 }
 
 impl Config {
@@ -84,6 +103,11 @@ impl Config {
                 } else {
                     suite.ignore_tests = Some(all);
                 }
+                suite.known_failures = config
+                    .known_failures
+                    .iter()
+                    .flat_map(|kf| kf.tests.iter().map(|test| (test.clone(), kf.reason)))
+                    .collect();
                 Ok(suite)
             })
             .collect::<Result<_>>()?;
@@ -115,3 +139,52 @@ pub struct SkipTests {
     pub desc: Option<String>,
     pub tests: Vec<String>,
 }
+
+/// Why a test is expected to fail
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+pub enum FailureReason {
+    /// the test uses a feature not supported by the circuits yet
+    Unimplemented,
+    /// the test needs more rows than the circuits are configured with
+    Oversized,
+    /// the test hits a bug tracked elsewhere
+    KnownBug,
+}
+
+impl std::fmt::Display for FailureReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct KnownFailures {
+    pub reason: FailureReason,
+    pub desc: Option<String>,
+    pub tests: Vec<String>,
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn known_failures() -> Result<()> {
+        let config: Config = toml::from_str(
+            r#"
+            suite = []
+            set = []
+            skip_paths = []
+            skip_tests = []
+
+            [[known_failures]]
+            reason = "Oversized"
+            desc = "more rows than the default suite allows"
+            tests = ["add_d0_g0_v0"]
+            "#,
+        )?;
+        assert_eq!(config.known_failures[0].reason, FailureReason::Oversized);
+        assert_eq!(config.known_failures[0].tests, vec!["add_d0_g0_v0"]);
+        Ok(())
+    }
+}
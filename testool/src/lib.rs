@@ -0,0 +1,14 @@
+//! Runs the ethereum/tests test vectors through the tracer, bus-mapping and
+//! the circuits
+mod abi;
+pub mod compiler;
+pub mod config;
+pub mod live_block;
+pub mod runner;
+pub mod statetest;
+pub mod utils;
+
+use compiler::Compiler;
+
+#[macro_use]
+extern crate prettytable;
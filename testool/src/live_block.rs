@@ -0,0 +1,119 @@
+//! Runs a block of a live chain through the circuits, building its circuit
+//! inputs from a node with the RPC `BuilderClient`
+
+use crate::{
+    statetest::{block_witness, check_test_circuit, CircuitsConfig, TestCircuit},
+    utils::MainnetFork,
+};
+use anyhow::{anyhow, Result};
+use bus_mapping::{
+    circuit_input_builder::{
+        Block, BuilderClient, CircuitInputBuilder, DynamicCParams, FixedCParams,
+    },
+    rpc::GethClient,
+};
+use ethers_providers::Http;
+use std::panic::AssertUnwindSafe;
+use url::Url;
+use zkevm_circuits::witness::RowEstimate;
+
+/// Result of running a block through the selected circuits
+#[derive(Debug, Clone)]
+pub struct BlockRun {
+    pub block_num: u64,
+    pub num_txs: usize,
+    pub gas_used: u64,
+    /// rows needed by each sub-circuit
+    pub rows: RowEstimate,
+    /// each circuit with the reason why it failed, if it did
+    pub circuits: Vec<(TestCircuit, Option<String>)>,
+}
+
+impl BlockRun {
+    /// returns true if all the circuits passed
+    pub fn success(&self) -> bool {
+        self.circuits.iter().all(|(_, failure)| failure.is_none())
+    }
+
+    pub fn print_tty(&self) {
+        println!(
+            "block {}: {} txs, {} gas",
+            self.block_num, self.num_txs, self.gas_used
+        );
+        for (name, rows) in self.rows.entries() {
+            println!("  {:<10} {:>10} rows", name, rows);
+        }
+        for (circuit, failure) in &self.circuits {
+            match failure {
+                None => println!("  {:<10} ok", circuit.to_string()),
+                Some(failure) => println!("  {:<10} FAIL {}", circuit.to_string(), failure),
+            }
+        }
+    }
+}
+
+/// Builds the circuit inputs of block `block_num` from the node at `rpc`,
+/// with the circuit parameters derived from the block, and checks each of
+/// the selected circuits.  The rules of `fork` are applied if set, else the
+/// ones of the latest hardfork supported by the circuit input builder.
+pub async fn run_block(
+    rpc: &str,
+    block_num: u64,
+    fork: Option<MainnetFork>,
+    circuits_config: &CircuitsConfig,
+) -> Result<BlockRun> {
+    let client = GethClient::new(Http::new(Url::parse(rpc)?));
+    let chain_id = client.get_chain_id().await?;
+    // the circuit parameters of the client are not used, they are derived
+    // from the block below
+    let cli = BuilderClient::new(client, FixedCParams::default()).await?;
+
+    let (eth_block, geth_traces, history_hashes, prev_state_root) =
+        cli.get_block(block_num).await?;
+    let access_set = BuilderClient::<Http>::get_state_accesses(&eth_block, &geth_traces)?;
+    let (proofs, codes) = cli.get_state(block_num, access_set).await?;
+    let (sdb, code_db) = BuilderClient::<Http>::build_state_code_db(proofs, codes);
+
+    let mut block = Block::new(chain_id.into(), history_hashes, prev_state_root, &eth_block)?;
+    if let Some(fork) = fork {
+        block.set_chain_config(
+            fork.chain_config(chain_id)
+                .ok_or_else(|| anyhow!("fork '{}' is not supported by the circuits", fork))?,
+        );
+    }
+    let builder = CircuitInputBuilder::new(sdb, code_db, block, DynamicCParams {})
+        .handle_block(&eth_block, &geth_traces)?;
+
+    let block = block_witness(&builder)?;
+    let rows = block.estimate_rows();
+
+    // panics are reported as failures of the circuit
+    let default_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(|_info| {}));
+    let circuits = circuits_config
+        .circuits
+        .iter()
+        .map(|&circuit| {
+            log::info!("block {}: running the {} circuit", block_num, circuit);
+            let result = std::panic::catch_unwind(AssertUnwindSafe(|| {
+                check_test_circuit(circuit, &block, circuits_config.prove)
+            }));
+            let failure = result.err().map(|err| {
+                err.downcast_ref::<String>()
+                    .cloned()
+                    .or_else(|| err.downcast_ref::<&str>().map(|s| s.to_string()))
+                    .unwrap_or_else(|| "unable to get panic info".into())
+            });
+            (circuit, failure)
+        })
+        .collect();
+    std::panic::set_hook(default_hook);
+
+    Ok(BlockRun {
+        block_num,
+        num_txs: eth_block.transactions.len(),
+        gas_used: eth_block.gas_used.as_u64(),
+        rows,
+        circuits,
+    })
+}
@@ -1,35 +1,17 @@
 /// Execute the bytecode from an empty state and run the EVM and State circuits
-mod abi;
-mod compiler;
-mod config;
-mod statetest;
-mod utils;
-
-use crate::{config::TestSuite, statetest::ResultLevel};
 use anyhow::{bail, Result};
 use clap::Parser;
-use compiler::Compiler;
-use config::Config;
 use log::info;
-use statetest::{
-    geth_trace, load_statetests_suite, run_statetests_suite, run_test, CircuitsConfig, Results,
-    StateTest,
+use std::path::PathBuf;
+use testool::{
+    config::{Config, TestSuite},
+    runner::{
+        filter_tests, list_tests, load_state_tests, parse_circuits, run_report, run_single_test,
+        run_suite, select_forks,
+    },
+    statetest::{load_blockchain_tests_suite, CircuitsConfig, ResultLevel, StateTest, SuiteTest},
+    utils::MainnetFork,
 };
-use std::{collections::HashSet, path::PathBuf, time::SystemTime};
-use strum::EnumString;
-
-const REPORT_FOLDER: &str = "report";
-const CODEHASH_FILE: &str = "./codehash.txt";
-
-#[macro_use]
-extern crate prettytable;
-
-#[allow(non_camel_case_types)]
-#[derive(PartialEq, Parser, EnumString, Debug)]
-enum Circuits {
-    basic,
-    sc,
-}
 
 /// EVM test vectors utility
 #[derive(Parser, Debug)]
@@ -47,6 +29,19 @@ struct Args {
     #[clap(long)]
     ls: bool,
 
+    /// Only run the tests whose id matches this regex
+    #[clap(long)]
+    test: Option<String>,
+
+    /// The suite contains BlockchainTests, whose blocks are run one after the
+    /// other
+    #[clap(long)]
+    blockchain: bool,
+
+    /// Only run the tests whose code uses this opcode (e.g. "SAR")
+    #[clap(long)]
+    uses_opcode: Option<String>,
+
     /// Cache execution results
     #[clap(long)]
     cache: Option<String>,
@@ -59,30 +54,71 @@ struct Args {
     #[clap(long)]
     report: bool,
 
+    /// Writes the results as json in this file
+    #[clap(long)]
+    json: Option<String>,
+
     /// Run statetest in oneliner spec
     #[clap(long)]
     oneliner: Option<String>,
 
-    /// Circuits to execute, can be basic (evm only) or sc (supercircuit)
+    /// Circuits to execute, e.g. "evm,tx,copy".  Any of evm, state, tx,
+    /// bytecode, copy, exp, keccak and super; basic is an alias for
+    /// evm,state (the default) and sc for super
+    #[clap(long, value_delimiter = ',')]
+    circuits: Vec<String>,
+
+    /// Generate and verify real proofs instead of using the MockProver.  The
+    /// KZG params are generated once per degree
     #[clap(long)]
-    circuits: Option<Circuits>,
+    prove: bool,
+
+    /// Ask geth for its own post state and diff it, field by field, against
+    /// the state of the circuit input builder
+    #[clap(long)]
+    diff_post_state: bool,
+
+    /// Re-execute the transactions with an independent interpreter and
+    /// compare its storage accesses against the ones of the circuit input
+    /// builder
+    #[clap(long)]
+    reexecute: bool,
+
+    /// Recompute the root of the post state of the circuit input builder and
+    /// check it against the one expected by the filled tests
+    #[clap(long)]
+    check_state_root: bool,
+
+    /// Skip the tests that run for longer than this, in seconds (overrides
+    /// the `timeout` of the suite)
+    #[clap(long)]
+    timeout: Option<u64>,
+
+    /// Skip the tests that run while the process uses more than this memory,
+    /// in MB (overrides the `max_memory` of the suite)
+    #[clap(long)]
+    max_memory: Option<u64>,
+
+    /// Forks to run the tests against, e.g. "Berlin,London,Shanghai" (by
+    /// default Merge).  With more than one fork, test ids are suffixed with
+    /// the fork name.
+    #[clap(long, value_parser, value_delimiter = ',')]
+    fork: Vec<MainnetFork>,
+
+    /// Caches the geth traces in this folder, so that they are reused by
+    /// the following runs
+    #[clap(long)]
+    cache_dir: Option<String>,
+
+    /// Number of tests executed in parallel (by default one per CPU)
+    #[clap(short, long)]
+    jobs: Option<usize>,
 
     /// Verbose
     #[clap(short, long)]
     v: bool,
 }
 
-fn run_single_test(test: StateTest, circuits_config: CircuitsConfig) -> Result<()> {
-    println!("{}", &test);
-    let trace = geth_trace(test.clone())?;
-    crate::utils::print_trace(trace)?;
-    println!(
-        "result={:?}",
-        run_test(test, TestSuite::default(), circuits_config)
-    );
-    Ok(())
-}
-
 fn go() -> Result<()> {
     //  RAYON_NUM_THREADS=1 RUST_BACKTRACE=1 cargo run -- --path
     // "tests/src/GeneralStateTestsFiller/**/" --skip-state-circuit
@@ -90,8 +126,16 @@ fn go() -> Result<()> {
     let args = Args::parse();
 
     let mut circuits_config = CircuitsConfig::default();
-    if args.circuits == Some(Circuits::sc) {
-        circuits_config.super_circuit = true;
+    if !args.circuits.is_empty() {
+        circuits_config.circuits = parse_circuits(&args.circuits)?;
+    }
+    circuits_config.prove = args.prove;
+    circuits_config.diff_post_state = args.diff_post_state;
+    circuits_config.reexecute = args.reexecute;
+    circuits_config.check_state_root = args.check_state_root;
+    if let Some(cache_dir) = &args.cache_dir {
+        std::fs::create_dir_all(cache_dir)?;
+        circuits_config.trace_cache_dir = Some(PathBuf::from(cache_dir));
     }
 
     if let Some(oneliner) = &args.oneliner {
@@ -106,29 +150,39 @@ fn go() -> Result<()> {
 
     log::info!("Using suite '{}'", args.suite);
     log::info!("Parsing and compliling tests...");
-    let compiler = Compiler::new(true, Some(PathBuf::from(CODEHASH_FILE)))?;
-    let suite = config.suite(&args.suite)?.clone();
-    let state_tests = load_statetests_suite(&suite.path, config, compiler)?;
-    log::info!("{} tests collected in {}", state_tests.len(), suite.path);
-
-    if args.ls {
-        let mut list: Vec<_> = state_tests.into_iter().map(|t| t.id).collect();
-        list.sort();
-        for test in list {
-            info!("{}", test);
+    let mut suite = config.suite(&args.suite)?.clone();
+    if args.timeout.is_some() {
+        suite.timeout = args.timeout;
+    }
+    if args.max_memory.is_some() {
+        suite.max_memory = args.max_memory;
+    }
+    let forks = select_forks(&args.fork)?;
+    if args.blockchain {
+        if args.uses_opcode.is_some() || args.inspect.is_some() {
+            bail!("--uses-opcode and --inspect are not supported with --blockchain");
         }
-        return Ok(());
+        let blockchain_tests = load_blockchain_tests_suite(&suite.path, config, &forks)?;
+        log::info!(
+            "{} blockchain tests collected in {}",
+            blockchain_tests.len(),
+            suite.path
+        );
+        return run_tests(args, &suite, &circuits_config, blockchain_tests);
     }
-    if let Some(test_id) = args.inspect {
+
+    let state_tests = load_state_tests(&suite, config, &forks, args.uses_opcode.as_deref())?;
+
+    if let Some(test_id) = &args.inspect {
         // Test only one and return
         let mut state_tests_filtered: Vec<_> =
-            state_tests.iter().filter(|t| t.id == test_id).collect();
+            state_tests.iter().filter(|t| &t.id == test_id).collect();
         if state_tests_filtered.is_empty() {
             info!(
                 "Test '{}' not found but found some that partially matches:",
                 test_id
             );
-            for test in state_tests.iter().filter(|t| t.id.contains(&test_id)) {
+            for test in state_tests.iter().filter(|t| t.id.contains(test_id)) {
                 info!("{}", test.id);
             }
             bail!("test '{}' not found", test_id);
@@ -137,90 +191,45 @@ fn go() -> Result<()> {
         return Ok(());
     };
 
-    if args.report {
-        let git_hash = utils::current_git_commit()?;
-        let git_submodule_tests_hash = utils::current_submodule_git_commit()?;
-        let timestamp = SystemTime::now()
-            .duration_since(SystemTime::UNIX_EPOCH)
-            .unwrap()
-            .as_secs();
-
-        std::fs::create_dir_all(REPORT_FOLDER)?;
-        let csv_filename = format!(
-            "{}/{}.{}.{}.csv",
-            REPORT_FOLDER, args.suite, timestamp, git_hash
-        );
-        let html_filename = format!(
-            "{}/{}.{}.{}.html",
-            REPORT_FOLDER, args.suite, timestamp, git_hash
-        );
-
-        // when running a report, the tests result of the containing cache file
-        // are used, but by default removing all Ignored tests
-        // Another way is to skip the test which level not in whitelist_levels
-        let mut previous_results = if let Some(cache_filename) = args.cache {
-            let whitelist_levels = HashSet::<ResultLevel>::from_iter(args.levels);
-
-            let mut previous_results = Results::from_file(PathBuf::from(cache_filename))?;
-            if !whitelist_levels.is_empty() {
-                // if whitelist is provided, test not in whitelist will be skip
-                previous_results
-                    .tests
-                    .retain(|_, test| !whitelist_levels.contains(&test.level));
-            } else {
-                // by default only skip ignore
-                previous_results
-                    .tests
-                    .retain(|_, test| test.level != ResultLevel::Ignored);
-            }
-
-            previous_results
-        } else {
-            Results::default()
-        };
-        previous_results.set_cache(PathBuf::from(csv_filename));
-        run_statetests_suite(state_tests, &circuits_config, &suite, &mut previous_results)?;
-
-        // filter non-csv files and files from the same commit
-        let mut files: Vec<_> = std::fs::read_dir(REPORT_FOLDER)
-            .unwrap()
-            .filter_map(|f| {
-                let filename = f.unwrap().file_name().to_str().unwrap().to_string();
-                (filename.starts_with(&format!("{}.", args.suite))
-                    && filename.ends_with(".csv")
-                    && !filename.contains(&format!(".{}.", git_hash)))
-                .then_some(filename)
-            })
-            .collect();
-
-        files.sort_by(|f, s| s.cmp(f));
-        let previous = if !files.is_empty() {
-            let file = files.remove(0);
-            let path = format!("{}/{}", REPORT_FOLDER, file);
-            info!("Comparing with previous results in {}", path);
-            Some((file, Results::from_file(PathBuf::from(path))?))
-        } else {
-            None
-        };
-        let report = previous_results.report(previous);
-        std::fs::write(&html_filename, report.gen_html(git_submodule_tests_hash)?)?;
-
-        report.print_tty()?;
-        info!("{}", html_filename);
-    } else {
-        let mut results = if let Some(cache_filename) = args.cache {
-            Results::with_cache(PathBuf::from(cache_filename))?
-        } else {
-            Results::default()
-        };
+    run_tests(args, &suite, &circuits_config, state_tests)
+}
 
-        log::info!("Executing...");
-        run_statetests_suite(state_tests, &circuits_config, &suite, &mut results)?;
-        let success = results.success();
+/// Lists or runs the tests, writing the results and the report
+fn run_tests<T: SuiteTest>(
+    args: Args,
+    suite: &TestSuite,
+    circuits_config: &CircuitsConfig,
+    mut tests: Vec<T>,
+) -> Result<()> {
+    filter_tests(&mut tests, args.test.as_deref())?;
 
-        log::info!("Generating report...");
-        results.report(None).print_tty()?;
+    if args.ls {
+        for test in list_tests(&tests) {
+            info!("{}", test);
+        }
+        return Ok(());
+    }
 
+    if args.report {
+        run_report(
+            &args.suite,
+            tests,
+            circuits_config,
+            suite,
+            args.cache.as_deref(),
+            &args.levels,
+            args.json.as_deref(),
+            args.jobs,
+        )?;
+    } else {
+        let success = run_suite(
+            tests,
+            circuits_config,
+            suite,
+            args.cache.as_deref(),
+            args.json.as_deref(),
+            args.jobs,
+        )?;
         if !success {
             std::process::exit(1);
         }
@@ -0,0 +1,234 @@
+//! Loading, running and reporting of the test suites, shared by the `testool`
+//! and `statetest` binaries
+
+use crate::{
+    compiler::Compiler,
+    config::{Config, TestSuite},
+    statetest::{
+        geth_trace, load_statetests_suite, run_statetests_suite, run_test, CircuitsConfig,
+        ResultLevel, Results, StateTest, SuiteTest, TestCircuit,
+    },
+    utils::{self, MainnetFork, TEST_FORK},
+};
+use anyhow::{bail, Result};
+use eth_types::evm_types::OpcodeId;
+use log::info;
+use regex::Regex;
+use std::{collections::HashSet, path::PathBuf, str::FromStr, time::SystemTime};
+
+pub const REPORT_FOLDER: &str = "report";
+pub const CODEHASH_FILE: &str = "./codehash.txt";
+
+/// Parses a list of circuit names, where "basic" is an alias for the EVM and
+/// State circuits and "sc" for the SuperCircuit
+pub fn parse_circuits(names: &[String]) -> Result<Vec<TestCircuit>> {
+    let mut circuits = Vec::new();
+    for name in names {
+        let selected = match name.to_lowercase().as_str() {
+            "basic" => vec![TestCircuit::Evm, TestCircuit::State],
+            "sc" => vec![TestCircuit::Super],
+            "mpt" => {
+                bail!("the mpt circuit is not supported, state tests do not generate its witness")
+            }
+            name => vec![TestCircuit::from_str(name)
+                .map_err(|_| anyhow::anyhow!("unknown circuit {}", name))?],
+        };
+        for circuit in selected {
+            if !circuits.contains(&circuit) {
+                circuits.push(circuit);
+            }
+        }
+    }
+    Ok(circuits)
+}
+
+/// Returns the forks to run the tests against, `TEST_FORK` if none is
+/// selected, failing if any of them is not supported by the circuits
+pub fn select_forks(forks: &[MainnetFork]) -> Result<Vec<MainnetFork>> {
+    let forks = if forks.is_empty() {
+        vec![TEST_FORK]
+    } else {
+        forks.to_vec()
+    };
+    for fork in &forks {
+        if fork.chain_config(1).is_none() {
+            bail!("fork '{}' is not supported by the circuits", fork);
+        }
+    }
+    Ok(forks)
+}
+
+/// Loads the state tests of the suite, keeping only the ones whose code uses
+/// `opcode` if set
+pub fn load_state_tests(
+    suite: &TestSuite,
+    config: Config,
+    forks: &[MainnetFork],
+    opcode: Option<&str>,
+) -> Result<Vec<StateTest>> {
+    let compiler = Compiler::new(true, Some(PathBuf::from(CODEHASH_FILE)))?;
+    let mut state_tests = load_statetests_suite(&suite.path, config, compiler, forks)?;
+    log::info!("{} tests collected in {}", state_tests.len(), suite.path);
+
+    if let Some(opcode) = opcode {
+        let opcode = OpcodeId::from_str(opcode)
+            .map_err(|_| anyhow::anyhow!("unknown opcode '{}'", opcode))?;
+        state_tests.retain(|t| t.uses_opcode(opcode));
+        log::info!("{} tests use {:?}", state_tests.len(), opcode);
+    }
+    Ok(state_tests)
+}
+
+/// Keeps only the tests whose id matches the `test` regex, if set
+pub fn filter_tests<T: SuiteTest>(tests: &mut Vec<T>, test: Option<&str>) -> Result<()> {
+    if let Some(test) = test {
+        let regex = Regex::new(test)?;
+        tests.retain(|t| regex.is_match(t.id()));
+        log::info!("{} tests selected", tests.len());
+    }
+    Ok(())
+}
+
+/// Returns the ids of the tests, sorted
+pub fn list_tests<T: SuiteTest>(tests: &[T]) -> Vec<String> {
+    let mut list: Vec<_> = tests.iter().map(|t| t.id().to_string()).collect();
+    list.sort();
+    list
+}
+
+/// Runs a single test, dumping the test and its geth trace
+pub fn run_single_test(test: StateTest, circuits_config: CircuitsConfig) -> Result<()> {
+    println!("{}", &test);
+    let trace = geth_trace(test.clone())?;
+    utils::print_trace(trace)?;
+    println!(
+        "result={:?}",
+        run_test(test, TestSuite::default(), circuits_config)
+    );
+    Ok(())
+}
+
+/// Runs the tests, reusing and updating the results of the `cache` csv file
+/// if set, and prints the results.  Returns true if none of them failed.
+pub fn run_suite<T: SuiteTest>(
+    tests: Vec<T>,
+    circuits_config: &CircuitsConfig,
+    suite: &TestSuite,
+    cache: Option<&str>,
+    json: Option<&str>,
+    jobs: Option<usize>,
+) -> Result<bool> {
+    let mut results = if let Some(cache_filename) = cache {
+        Results::with_cache(PathBuf::from(cache_filename))?
+    } else {
+        Results::default()
+    };
+
+    log::info!("Executing...");
+    run_statetests_suite(tests, circuits_config, suite, &mut results, jobs)?;
+    let success = results.success();
+    if let Some(json_filename) = json {
+        results.write_json(PathBuf::from(json_filename))?;
+    }
+
+    log::info!("Generating report...");
+    results.report(None).print_tty()?;
+
+    Ok(success)
+}
+
+/// Runs the tests and writes the csv, json and html reports of the run into
+/// `REPORT_FOLDER`, compared with the previous run of the suite.  Returns
+/// true if none of the tests failed.
+#[allow(clippy::too_many_arguments)]
+pub fn run_report<T: SuiteTest>(
+    suite_name: &str,
+    tests: Vec<T>,
+    circuits_config: &CircuitsConfig,
+    suite: &TestSuite,
+    cache: Option<&str>,
+    levels: &[ResultLevel],
+    json: Option<&str>,
+    jobs: Option<usize>,
+) -> Result<bool> {
+    let git_hash = utils::current_git_commit()?;
+    let git_submodule_tests_hash = utils::current_submodule_git_commit()?;
+    let timestamp = SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .unwrap()
+        .as_secs();
+
+    std::fs::create_dir_all(REPORT_FOLDER)?;
+    let csv_filename = format!(
+        "{}/{}.{}.{}.csv",
+        REPORT_FOLDER, suite_name, timestamp, git_hash
+    );
+    let html_filename = format!(
+        "{}/{}.{}.{}.html",
+        REPORT_FOLDER, suite_name, timestamp, git_hash
+    );
+    let json_filename = json.map(str::to_string).unwrap_or_else(|| {
+        format!(
+            "{}/{}.{}.{}.json",
+            REPORT_FOLDER, suite_name, timestamp, git_hash
+        )
+    });
+
+    // when running a report, the tests result of the containing cache file
+    // are used, but by default removing all Ignored tests
+    // Another way is to skip the test which level not in whitelist_levels
+    let mut previous_results = if let Some(cache_filename) = cache {
+        let whitelist_levels = HashSet::<ResultLevel>::from_iter(levels.iter().copied());
+
+        let mut previous_results = Results::from_file(PathBuf::from(cache_filename))?;
+        if !whitelist_levels.is_empty() {
+            // if whitelist is provided, test not in whitelist will be skip
+            previous_results
+                .tests
+                .retain(|_, test| !whitelist_levels.contains(&test.level));
+        } else {
+            // by default only skip ignore
+            previous_results
+                .tests
+                .retain(|_, test| test.level != ResultLevel::Ignored);
+        }
+
+        previous_results
+    } else {
+        Results::default()
+    };
+    previous_results.set_cache(PathBuf::from(csv_filename));
+    run_statetests_suite(tests, circuits_config, suite, &mut previous_results, jobs)?;
+    let success = previous_results.success();
+
+    // filter non-csv files and files from the same commit
+    let mut files: Vec<_> = std::fs::read_dir(REPORT_FOLDER)
+        .unwrap()
+        .filter_map(|f| {
+            let filename = f.unwrap().file_name().to_str().unwrap().to_string();
+            (filename.starts_with(&format!("{}.", suite_name))
+                && filename.ends_with(".csv")
+                && !filename.contains(&format!(".{}.", git_hash)))
+            .then_some(filename)
+        })
+        .collect();
+
+    files.sort_by(|f, s| s.cmp(f));
+    let previous = if !files.is_empty() {
+        let file = files.remove(0);
+        let path = format!("{}/{}", REPORT_FOLDER, file);
+        info!("Comparing with previous results in {}", path);
+        Some((file, Results::from_file(PathBuf::from(path))?))
+    } else {
+        None
+    };
+    previous_results.write_json(PathBuf::from(&json_filename))?;
+    let report = previous_results.report(previous);
+    std::fs::write(&html_filename, report.gen_html(git_submodule_tests_hash)?)?;
+
+    report.print_tty()?;
+    info!("{}", html_filename);
+    info!("{}", json_filename);
+
+    Ok(success)
+}
@@ -0,0 +1,348 @@
+use super::{
+    general::{AccountPre, GeneralStateTestBuilder},
+    parse,
+    spec::{AccountMatch, Env, StateTestResult},
+};
+use crate::utils::{MainnetFork, TEST_FORK};
+use anyhow::Result;
+use eth_types::{
+    geth_types::{Account, Transaction},
+    Address, Bytes, H256, U256,
+};
+use ethers_core::utils::rlp::Rlp;
+use serde::Deserialize;
+use std::{collections::HashMap, str::FromStr};
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct JsonBlockHeader {
+    coinbase: String,
+    difficulty: String,
+    gas_limit: String,
+    gas_used: String,
+    hash: String,
+    number: String,
+    parent_hash: String,
+    state_root: String,
+    timestamp: String,
+    base_fee_per_gas: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct JsonBlock {
+    block_header: Option<JsonBlockHeader>,
+    expect_exception: Option<String>,
+    rlp: String,
+    #[serde(default)]
+    withdrawals: Vec<serde_json::Value>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct JsonBlockchainTest {
+    genesis_block_header: JsonBlockHeader,
+    blocks: Vec<JsonBlock>,
+    network: String,
+    pre: HashMap<String, AccountPre>,
+    post_state: Option<HashMap<String, AccountPre>>,
+}
+
+/// A block of a `BlockchainTest`
+#[derive(Debug, Clone)]
+pub struct BlockchainTestBlock {
+    pub env: Env,
+    pub hash: H256,
+    pub base_fee: U256,
+    /// gas used by the transactions of the block, from its header
+    pub gas_used: u64,
+    /// signed transactions, with the sender recovered from the signature
+    pub transactions: Vec<Transaction>,
+}
+
+/// A test of the ethereum/tests `BlockchainTests` suite: a sequence of blocks
+/// applied to the `pre` state, with the expected state after the last one.
+#[derive(Debug, Clone)]
+pub struct BlockchainTest {
+    pub path: String,
+    pub id: String,
+    pub fork: MainnetFork,
+    pub genesis_hash: H256,
+    pub pre: HashMap<Address, Account>,
+    pub blocks: Vec<BlockchainTestBlock>,
+    pub post: StateTestResult,
+    /// state root after the last block, only set from the Merge on
+    pub state_root: Option<H256>,
+}
+
+/// Loader of the filled tests of the ethereum/tests `BlockchainTests` suite.
+///
+/// Tests containing invalid blocks or withdrawals are not loaded, as the
+/// circuit input builder can neither reject a block nor apply withdrawals.
+pub struct BlockchainTestBuilder {
+    fork: MainnetFork,
+}
+
+impl Default for BlockchainTestBuilder {
+    fn default() -> Self {
+        Self { fork: TEST_FORK }
+    }
+}
+
+impl BlockchainTestBuilder {
+    /// selects the fork whose tests are loaded
+    pub fn with_fork(mut self, fork: MainnetFork) -> Self {
+        self.fork = fork;
+        self
+    }
+
+    /// returns true if `source` looks like a filled `BlockchainTests` file
+    pub fn is_blockchain_test(source: &str) -> bool {
+        serde_json::from_str::<HashMap<String, serde_json::Value>>(source)
+            .map(|tests| {
+                tests.values().all(|test| {
+                    test.get("blocks").is_some() && test.get("genesisBlockHeader").is_some()
+                })
+            })
+            .unwrap_or(false)
+    }
+
+    /// generates the `BlockchainTest`s of a filled `BlockchainTests` file
+    /// for the tested fork
+    pub fn load_json(&self, path: &str, source: &str) -> Result<Vec<BlockchainTest>> {
+        let mut blockchain_tests = Vec::new();
+        let tests: HashMap<String, JsonBlockchainTest> = serde_json::from_str(source)?;
+
+        for (test_name, test) in tests {
+            if MainnetFork::from_str(&test.network).map_or(true, |fork| fork != self.fork) {
+                continue;
+            }
+            if test
+                .blocks
+                .iter()
+                .any(|block| block.expect_exception.is_some() || !block.withdrawals.is_empty())
+            {
+                log::debug!(
+                    "{}: skipping test with invalid blocks or withdrawals",
+                    test_name
+                );
+                continue;
+            }
+
+            let mut blocks = Vec::new();
+            for block in &test.blocks {
+                let header = match &block.block_header {
+                    Some(header) => header,
+                    None => anyhow::bail!("{}: block without header", test_name),
+                };
+                blocks.push(BlockchainTestBlock {
+                    env: Self::parse_env(header)?,
+                    hash: parse::parse_hash(&header.hash)?,
+                    base_fee: header
+                        .base_fee_per_gas
+                        .as_ref()
+                        .map(|base_fee| parse::parse_u256(base_fee))
+                        .transpose()?
+                        .unwrap_or_default(),
+                    gas_used: parse::parse_u64(&header.gas_used)?,
+                    transactions: Self::parse_transactions(&parse::parse_bytes(&block.rlp)?)?,
+                });
+            }
+
+            let mut post = HashMap::new();
+            if let Some(post_state) = &test.post_state {
+                for (address, account) in GeneralStateTestBuilder::parse_accounts_pre(post_state)? {
+                    post.insert(
+                        address,
+                        AccountMatch {
+                            address,
+                            balance: Some(account.balance),
+                            code: Some(account.code),
+                            nonce: Some(account.nonce.as_u64()),
+                            storage: account.storage,
+                        },
+                    );
+                }
+            }
+            let last_header = test
+                .blocks
+                .last()
+                .and_then(|block| block.block_header.as_ref())
+                .unwrap_or(&test.genesis_block_header);
+            let mut state_root = Some(parse::parse_hash(&last_header.state_root)?);
+            // the block rewards are not applied by the circuit input builder,
+            // so neither the coinbase balance nor the state root match
+            if self.fork < MainnetFork::Merge {
+                for block in &blocks {
+                    if let Some(coinbase) = post.get_mut(&block.env.current_coinbase) {
+                        coinbase.balance = None;
+                    }
+                }
+                state_root = None;
+            }
+
+            blockchain_tests.push(BlockchainTest {
+                path: path.to_string(),
+                id: test_name,
+                fork: self.fork,
+                genesis_hash: parse::parse_hash(&test.genesis_block_header.hash)?,
+                pre: GeneralStateTestBuilder::parse_accounts_pre(&test.pre)?,
+                blocks,
+                post,
+                state_root,
+            });
+        }
+
+        Ok(blockchain_tests)
+    }
+
+    /// parse the env of a block from its header
+    fn parse_env(header: &JsonBlockHeader) -> Result<Env> {
+        Ok(Env {
+            current_coinbase: parse::parse_address(&header.coinbase)?,
+            current_difficulty: parse::parse_u256(&header.difficulty)?,
+            current_gas_limit: parse::parse_u64(&header.gas_limit)?,
+            current_number: parse::parse_u64(&header.number)?,
+            current_timestamp: parse::parse_u64(&header.timestamp)?,
+            previous_hash: parse::parse_hash(&header.parent_hash)?,
+        })
+    }
+
+    /// decode the transactions of a RLP encoded block, where legacy
+    /// transactions are lists and typed ones are byte strings
+    fn parse_transactions(block: &Bytes) -> Result<Vec<Transaction>> {
+        Rlp::new(block)
+            .at(1)?
+            .iter()
+            .map(|tx| {
+                let encoded = if tx.is_list() {
+                    tx.as_raw()
+                } else {
+                    tx.data()?
+                };
+                Ok(Transaction::decode_signed(encoded)?.0)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use eth_types::U64;
+    use ethers_core::utils::rlp::RlpStream;
+
+    fn blockchain_test_json(block_rlp: &Bytes) -> String {
+        r#"
+{
+    "callValue_d0g0v0_Merge" : {
+        "_info" : {
+            "comment" : "multi-block test"
+        },
+        "blocks" : [
+            {
+                "blockHeader" : {
+                    "baseFeePerGas" : "0x0a",
+                    "coinbase" : "0x2adc25665018aa1fe0e6bc666dac8fc2697ff9ba",
+                    "difficulty" : "0x00",
+                    "gasLimit" : "0x0f4240",
+                    "gasUsed" : "0x5208",
+                    "hash" : "0x1111111111111111111111111111111111111111111111111111111111111111",
+                    "number" : "0x01",
+                    "parentHash" : "0x2222222222222222222222222222222222222222222222222222222222222222",
+                    "stateRoot" : "0x3333333333333333333333333333333333333333333333333333333333333333",
+                    "timestamp" : "0x03e8"
+                },
+                "rlp" : "BLOCK_RLP",
+                "transactions" : [],
+                "uncleHeaders" : []
+            }
+        ],
+        "genesisBlockHeader" : {
+            "coinbase" : "0x2adc25665018aa1fe0e6bc666dac8fc2697ff9ba",
+            "difficulty" : "0x00",
+            "gasLimit" : "0x0f4240",
+            "gasUsed" : "0x00",
+            "hash" : "0x2222222222222222222222222222222222222222222222222222222222222222",
+            "number" : "0x00",
+            "parentHash" : "0x0000000000000000000000000000000000000000000000000000000000000000",
+            "stateRoot" : "0x4444444444444444444444444444444444444444444444444444444444444444",
+            "timestamp" : "0x00"
+        },
+        "network" : "Merge",
+        "postState" : {
+            "0x095e7baea6a6c7c4c2dfeb977efac326af552d87" : {
+                "balance" : "0x01",
+                "code" : "0x",
+                "nonce" : "0x00",
+                "storage" : {}
+            }
+        },
+        "pre" : {
+            "0xa94f5374fce5edbc8e2a8697c15331677e6ebf0b" : {
+                "balance" : "0x0de0b6b3a7640000",
+                "code" : "0x",
+                "nonce" : "0x00",
+                "storage" : {}
+            }
+        },
+        "sealEngine" : "NoProof"
+    }
+}
+"#
+        .replace("BLOCK_RLP", &format!("0x{}", hex::encode(block_rlp)))
+    }
+
+    #[test]
+    fn test_blockchain_test_parse() -> Result<()> {
+        let secret_key =
+            hex::decode("45a915e4d060149eb4365960e6a7a45f334393093061116b197e3240065ff2d8")?;
+        let sender = Address::from_str("0xa94f5374fce5edbc8e2a8697c15331677e6ebf0b")?;
+        let to = Address::from_str("0x095e7baea6a6c7c4c2dfeb977efac326af552d87")?;
+        let tx = Transaction {
+            from: sender,
+            to: Some(to),
+            gas_limit: U64::from(21000),
+            gas_price: U256::from(10),
+            value: U256::one(),
+            ..Default::default()
+        }
+        .sign(&secret_key, 1)?;
+
+        // [header, transactions, uncles], the header is not decoded
+        let mut stream = RlpStream::new_list(3);
+        stream.begin_list(0);
+        stream.begin_list(1).append_raw(&tx.raw, 1);
+        stream.begin_list(0);
+        let block_rlp = Bytes::from(stream.out().to_vec());
+
+        let json = blockchain_test_json(&block_rlp);
+        assert!(BlockchainTestBuilder::is_blockchain_test(&json));
+        assert!(BlockchainTestBuilder::default()
+            .with_fork(MainnetFork::Berlin)
+            .load_json("test_path", &json)?
+            .is_empty());
+
+        let mut tests = BlockchainTestBuilder::default().load_json("test_path", &json)?;
+        assert_eq!(tests.len(), 1);
+        let test = tests.remove(0);
+        assert_eq!(test.id, "callValue_d0g0v0_Merge");
+        assert_eq!(test.genesis_hash, H256::repeat_byte(0x22));
+        assert_eq!(test.pre[&sender].balance, U256::from(10).pow(18.into()));
+        assert_eq!(test.post[&to].balance, Some(U256::one()));
+        assert_eq!(test.state_root, Some(H256::repeat_byte(0x33)));
+
+        assert_eq!(test.blocks.len(), 1);
+        let block = &test.blocks[0];
+        assert_eq!(block.env.current_number, 1);
+        assert_eq!(block.env.previous_hash, test.genesis_hash);
+        assert_eq!(block.base_fee, U256::from(10));
+        assert_eq!(block.gas_used, 21000);
+        assert_eq!(block.transactions.len(), 1);
+        assert_eq!(block.transactions[0].from, sender);
+        assert_eq!(block.transactions[0].to, Some(to));
+        assert_eq!(block.transactions[0].value, U256::one());
+
+        Ok(())
+    }
+}
@@ -1,20 +1,41 @@
-use super::{AccountMatch, StateTest, StateTestResult};
-use crate::config::TestSuite;
+use super::{
+    prover,
+    reexec::{reexecute, StorageAccess, TxRwSet, Unsupported},
+    trie::{state_root, TrieAccount},
+    AccountMatch, BlockchainTest, StateTest, StateTestResult,
+};
+use crate::{config::TestSuite, utils::MainnetFork};
 use bus_mapping::{
+    chain_config::ChainConfig,
     circuit_input_builder::{CircuitInputBuilder, FixedCParams},
     mock::BlockData,
+    operation::{AccountField, Target},
+    state_db::Account,
+};
+use eth_types::{
+    geth_types, keccak256, Address, Bytes, GethExecTrace, ToBigEndian, H256, U256, U64,
 };
-use eth_types::{geth_types, Address, Bytes, GethExecTrace, U256, U64};
-use ethers_core::{
-    k256::ecdsa::SigningKey,
-    types::{transaction::eip2718::TypedTransaction, TransactionRequest},
+use external_tracer::{AccountState, TraceConfig, TxTrace};
+use halo2_proofs::{dev::MockProver, halo2curves::bn256::Fr, plonk::Circuit};
+use std::{
+    collections::{BTreeMap, HashMap},
+    path::PathBuf,
+    time::{Duration, Instant},
 };
-use ethers_signers::{LocalWallet, Signer};
-use external_tracer::TraceConfig;
-use halo2_proofs::{dev::MockProver, halo2curves::bn256::Fr};
-use std::{collections::HashMap, str::FromStr};
+use strum_macros::{Display, EnumString};
 use thiserror::Error;
-use zkevm_circuits::{super_circuit::SuperCircuit, test_util::CircuitTestBuilder, witness::Block};
+use zkevm_circuits::{
+    bytecode_circuit::circuit::BytecodeCircuit,
+    copy_circuit::CopyCircuit,
+    evm_circuit::{witness::block_convert, EvmCircuit},
+    exp_circuit::ExpCircuit,
+    keccak_circuit::KeccakCircuit,
+    state_circuit::StateCircuit,
+    super_circuit::SuperCircuit,
+    tx_circuit::TxCircuit,
+    util::{log2_ceil, SubCircuit},
+    witness::{Block, RowEstimate},
+};
 
 #[derive(PartialEq, Eq, Error, Debug)]
 pub enum StateTestError {
@@ -38,20 +59,111 @@ pub enum StateTestError {
     SkipTestMaxSteps(usize),
     #[error("Exception(expected:{expected:?}, found:{found:?})")]
     Exception { expected: bool, found: String },
+    #[error("GasUsedMismatch(block:{block}, expected:{expected}, found:{found})")]
+    GasUsedMismatch {
+        block: u64,
+        expected: u64,
+        found: u64,
+    },
+    #[error("SkipTestTimeout({0}s)")]
+    SkipTestTimeout(u64),
+    #[error("SkipTestOutOfMemory({0}MB)")]
+    SkipTestOutOfMemory(u64),
+    #[error("GethPostStateMismatch({0})")]
+    GethPostStateMismatch(String),
+    #[error("StateRootMismatch(expected:{expected:?}, found:{found:?})")]
+    StateRootMismatch { expected: H256, found: H256 },
+    #[error("RwMismatch({0})")]
+    RwMismatch(String),
+    #[error("SkipTestReexecution({0})")]
+    SkipTestReexecution(String),
 }
 
 impl StateTestError {
     pub fn is_skip(&self) -> bool {
         matches!(
             self,
-            StateTestError::SkipTestMaxSteps(_) | StateTestError::SkipTestMaxGasLimit(_)
+            StateTestError::SkipTestMaxSteps(_)
+                | StateTestError::SkipTestMaxGasLimit(_)
+                | StateTestError::SkipTestTimeout(_)
+                | StateTestError::SkipTestOutOfMemory(_)
+                | StateTestError::SkipTestReexecution(_)
         )
     }
 }
 
-#[derive(Default, Debug, Clone)]
+/// Circuits that can be exercised by a test
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug, EnumString, Display)]
+#[strum(ascii_case_insensitive, serialize_all = "lowercase")]
+pub enum TestCircuit {
+    Evm,
+    State,
+    Tx,
+    Bytecode,
+    Copy,
+    Exp,
+    Keccak,
+    Super,
+}
+
+#[derive(Debug, Clone)]
 pub struct CircuitsConfig {
-    pub super_circuit: bool,
+    /// circuits checked with the MockProver, by default the EVM and State
+    /// circuits
+    pub circuits: Vec<TestCircuit>,
+    /// generate and verify real proofs instead of using the MockProver
+    pub prove: bool,
+    /// folder where the geth traces are cached, keyed by the hash of their
+    /// `TraceConfig`
+    pub trace_cache_dir: Option<PathBuf>,
+    /// ask the tracer for its own post state and diff it against the state
+    /// of the circuit input builder
+    pub diff_post_state: bool,
+    /// re-execute the transactions with an independent interpreter and
+    /// compare its storage accesses against the storage operations of the
+    /// circuit input builder
+    pub reexecute: bool,
+    /// recompute the root of the post state of the circuit input builder and
+    /// check it against the one the filled tests expect
+    pub check_state_root: bool,
+}
+
+impl Default for CircuitsConfig {
+    fn default() -> Self {
+        Self {
+            circuits: vec![TestCircuit::Evm, TestCircuit::State],
+            prove: false,
+            trace_cache_dir: None,
+            diff_post_state: false,
+            reexecute: false,
+            check_state_root: false,
+        }
+    }
+}
+
+/// Minimum degree of the standalone sub-circuits, enough to fit the 16 bit
+/// range tables some of them load
+const MIN_K: u32 = 18;
+
+/// Checks `C` for `block` with a degree of at least `min_k`, with the
+/// MockProver or with a real proof if `prove` is set
+fn check_circuit<C: SubCircuit<Fr> + Circuit<Fr>>(
+    circuit: TestCircuit,
+    block: &Block<Fr>,
+    min_k: u32,
+    prove: bool,
+) {
+    let (_, rows_needed) = C::min_num_rows_block(block);
+    let k = log2_ceil(C::unusable_rows() + rows_needed).max(min_k);
+    let sub_circuit = C::new_from_block(block);
+    let instance = sub_circuit.instance();
+
+    if prove {
+        prover::prove_and_verify(&circuit.to_string(), k, sub_circuit, instance);
+    } else {
+        let prover = MockProver::run(k, &sub_circuit, instance).unwrap();
+        prover.assert_satisfied_par();
+    }
 }
 
 fn check_post(
@@ -77,15 +189,7 @@ fn check_post(
         }
 
         if let Some(expected_code) = &expected.code {
-            let actual_code = (!actual.code_hash.is_zero())
-                .then(|| {
-                    builder
-                        .code_db
-                        .get_from_h256(&actual.code_hash)
-                        .map(|bytecode| bytecode.code())
-                        .expect("code exists")
-                })
-                .unwrap_or_default();
+            let actual_code = account_code(builder, actual);
             if actual_code != expected_code.0 {
                 return Err(StateTestError::CodeMismatch {
                     expected: expected_code.clone(),
@@ -107,29 +211,310 @@ fn check_post(
     Ok(())
 }
 
+/// Code of an account of the state of `builder`
+fn account_code(builder: &CircuitInputBuilder<FixedCParams>, account: &Account) -> Vec<u8> {
+    (!account.code_hash.is_zero())
+        .then(|| {
+            builder
+                .code_db
+                .get_from_h256(&account.code_hash)
+                .map(|bytecode| bytecode.code())
+                .expect("code exists")
+        })
+        .unwrap_or_default()
+}
+
+/// Checks the root of the state trie committing to the post state of
+/// `builder`, without its empty accounts, against the `expected` one.  The
+/// code hashes are recomputed with keccak, whatever the hash scheme of the
+/// code db.
+fn check_state_root(
+    builder: &CircuitInputBuilder<FixedCParams>,
+    expected: H256,
+) -> Result<(), StateTestError> {
+    let accounts = builder.sdb.iter().filter_map(|(address, account)| {
+        let code = account_code(builder, account);
+        let empty = account.nonce == 0 && account.balance.is_zero() && code.is_empty();
+        (!empty).then(|| {
+            (
+                *address,
+                TrieAccount {
+                    nonce: account.nonce,
+                    balance: account.balance,
+                    code_hash: H256(keccak256(&code)),
+                    storage: account.storage.clone(),
+                },
+            )
+        })
+    });
+    let found = state_root(accounts);
+    if found != expected {
+        return Err(StateTestError::StateRootMismatch { expected, found });
+    }
+    Ok(())
+}
+
+/// Operations of each transaction of `builder`, in the shape of the ones of
+/// the re-execution.  The storage operations are sorted by rw counter, with
+/// the index of their step: the reversions are attached to the step of the
+/// reverted write, and the reads of the steps failing with an error are left
+/// out, as the re-execution does not perform them.
+fn builder_rw_sets(builder: &CircuitInputBuilder<FixedCParams>) -> Vec<TxRwSet> {
+    let container = &builder.block.container;
+    builder
+        .block
+        .txs
+        .iter()
+        .map(|tx| {
+            let op_refs = |target: Target| {
+                let mut op_refs: Vec<_> = tx
+                    .steps()
+                    .iter()
+                    .enumerate()
+                    .flat_map(|(index, step)| {
+                        step.bus_mapping_instance
+                            .iter()
+                            .filter(move |op_ref| op_ref.target() == target)
+                            .map(move |op_ref| (index, step, op_ref.as_usize()))
+                    })
+                    .collect();
+                op_refs.sort_by_key(|(_, _, i)| match target {
+                    Target::Storage => container.storage[*i].rwc(),
+                    Target::Account => container.account[*i].rwc(),
+                    Target::TxAccessListAccount => container.tx_access_list_account[*i].rwc(),
+                    _ => container.tx_access_list_account_storage[*i].rwc(),
+                });
+                op_refs
+            };
+
+            let mut rws = TxRwSet::default();
+            for (step, _, i) in op_refs(Target::Storage)
+                .into_iter()
+                .filter(|(_, step, _)| step.error.is_none())
+            {
+                let op = &container.storage[i];
+                rws.storage.push(StorageAccess {
+                    step,
+                    is_write: op.rw().is_write(),
+                    address: op.op().address,
+                    key: op.op().key,
+                    value: op.op().value,
+                    value_prev: op.op().value_prev,
+                    committed_value: op.op().committed_value,
+                });
+            }
+            // value before the first and after the last operation
+            for (_, _, i) in op_refs(Target::Account) {
+                let op = container.account[i].op();
+                let changes = match op.field {
+                    AccountField::Nonce => &mut rws.nonces,
+                    AccountField::Balance => &mut rws.balances,
+                    AccountField::CodeHash => continue,
+                };
+                changes
+                    .entry(op.address)
+                    .or_insert((op.value_prev, op.value))
+                    .1 = op.value;
+            }
+            rws.nonces.retain(|_, (prev, value)| prev != value);
+            rws.balances.retain(|_, (prev, value)| prev != value);
+            for (_, _, i) in op_refs(Target::TxAccessListAccount) {
+                let op = container.tx_access_list_account[i].op();
+                rws.warm_accounts
+                    .entry(op.address)
+                    .or_insert(op.is_warm_prev);
+            }
+            for (_, _, i) in op_refs(Target::TxAccessListAccountStorage) {
+                let op = container.tx_access_list_account_storage[i].op();
+                rws.warm_slots
+                    .entry((op.address, op.key))
+                    .or_insert(op.is_warm_prev);
+            }
+            rws
+        })
+        .collect()
+}
+
+/// Returns the first entry of `expected` or `found` differing from the other
+fn first_map_mismatch<K: Ord + Copy, V: PartialEq + Copy>(
+    expected: &BTreeMap<K, V>,
+    found: &BTreeMap<K, V>,
+) -> Option<(K, Option<V>, Option<V>)> {
+    expected
+        .keys()
+        .chain(found.keys())
+        .map(|key| (*key, expected.get(key).copied(), found.get(key).copied()))
+        .find(|(_, expected, found)| expected != found)
+}
+
+/// Compares the operations of `builder` against the ones of the
+/// re-execution, reporting the first mismatch of each transaction and kind
+/// of operation, with the step numbers of both sides for the storage.
+fn check_rw_set(
+    builder: &CircuitInputBuilder<FixedCParams>,
+    expected_txs: &[TxRwSet],
+) -> Result<(), StateTestError> {
+    fn show<T: std::fmt::Debug>(value: Option<T>) -> String {
+        value.map_or_else(|| "none".to_string(), |value| format!("{:?}", value))
+    }
+
+    let mut mismatches = Vec::new();
+    for (index, (expected, found)) in expected_txs
+        .iter()
+        .zip(builder_rw_sets(builder))
+        .enumerate()
+    {
+        let tx = index + 1;
+        let (expected_storage, found_storage) = (&expected.storage, &found.storage);
+        let position = (0..expected_storage.len().max(found_storage.len()))
+            .find(|&i| expected_storage.get(i) != found_storage.get(i));
+        if let Some(i) = position {
+            let show = |access: Option<&StorageAccess>| {
+                access.map_or_else(|| "none".to_string(), |access| access.to_string())
+            };
+            mismatches.push(format!(
+                "tx {} op {}: reexecution {}, bus-mapping {}",
+                tx,
+                i,
+                show(expected_storage.get(i)),
+                show(found_storage.get(i))
+            ));
+        }
+        for (kind, expected, found) in [
+            ("nonce", &expected.nonces, &found.nonces),
+            ("balance", &expected.balances, &found.balances),
+        ] {
+            if let Some((address, expected, found)) = first_map_mismatch(expected, found) {
+                mismatches.push(format!(
+                    "tx {} {} of {:?}: reexecution {}, bus-mapping {}",
+                    tx,
+                    kind,
+                    address,
+                    show(expected),
+                    show(found)
+                ));
+            }
+        }
+        if let Some((address, expected, found)) =
+            first_map_mismatch(&expected.warm_accounts, &found.warm_accounts)
+        {
+            mismatches.push(format!(
+                "tx {} is_warm_prev of {:?}: reexecution {}, bus-mapping {}",
+                tx,
+                address,
+                show(expected),
+                show(found)
+            ));
+        }
+        if let Some(((address, key), expected, found)) =
+            first_map_mismatch(&expected.warm_slots, &found.warm_slots)
+        {
+            mismatches.push(format!(
+                "tx {} is_warm_prev of {:?}[{:#x}]: reexecution {}, bus-mapping {}",
+                tx,
+                address,
+                key,
+                show(expected),
+                show(found)
+            ));
+        }
+    }
+
+    if mismatches.is_empty() {
+        Ok(())
+    } else {
+        Err(StateTestError::RwMismatch(mismatches.join(", ")))
+    }
+}
+
+/// Re-executes the transactions of `trace_config` if it is enabled
+fn run_reexecution(
+    trace_config: &TraceConfig,
+    fork: MainnetFork,
+    circuits_config: &CircuitsConfig,
+) -> Option<Result<Vec<TxRwSet>, Unsupported>> {
+    circuits_config
+        .reexecute
+        .then(|| reexecute(trace_config, fork))
+}
+
+/// Diffs the post state dumped by geth against the state of `builder`, field
+/// by field for every account and storage slot accessed by geth, so that
+/// divergences are caught even for the accounts not listed in the expected
+/// result of the test.
+fn check_geth_post_state(
+    builder: &CircuitInputBuilder<FixedCParams>,
+    geth_post: &HashMap<Address, AccountState>,
+) -> Result<(), StateTestError> {
+    let mut divergences = Vec::new();
+    let mut addresses: Vec<_> = geth_post.keys().collect();
+    addresses.sort();
+    for address in addresses {
+        let geth = &geth_post[address];
+        let (_, actual) = builder.sdb.get_account(address);
+
+        if geth.nonce.as_u64() != actual.nonce {
+            divergences.push(format!(
+                "{:?} nonce geth:{} found:{}",
+                address, geth.nonce, actual.nonce
+            ));
+        }
+        if geth.balance != actual.balance {
+            divergences.push(format!(
+                "{:?} balance geth:{} found:{}",
+                address, geth.balance, actual.balance
+            ));
+        }
+        let actual_code = account_code(builder, actual);
+        if actual_code != geth.code.0 {
+            divergences.push(format!(
+                "{:?} code geth:{} found:{}",
+                address,
+                geth.code,
+                Bytes::from(actual_code)
+            ));
+        }
+        let mut slots: Vec<_> = geth.storage.iter().collect();
+        slots.sort();
+        for (slot, geth_value) in slots {
+            let actual_value = actual.storage.get(slot).cloned().unwrap_or_else(U256::zero);
+            if *geth_value != actual_value {
+                divergences.push(format!(
+                    "{:?} slot {:#x} geth:{:#x} found:{:#x}",
+                    address, slot, geth_value, actual_value
+                ));
+            }
+        }
+    }
+
+    if divergences.is_empty() {
+        Ok(())
+    } else {
+        Err(StateTestError::GethPostStateMismatch(
+            divergences.join(", "),
+        ))
+    }
+}
+
 fn into_traceconfig(st: StateTest) -> (String, TraceConfig, StateTestResult) {
     let chain_id = 1;
-    let wallet = LocalWallet::from_str(&hex::encode(st.secret_key.0)).unwrap();
-    let mut tx = TransactionRequest::new()
-        .chain_id(chain_id)
-        .from(st.from)
-        .nonce(st.nonce)
-        .value(st.value)
-        .data(st.data.clone())
-        .gas(st.gas_limit)
-        .gas_price(st.gas_price);
-
-    if let Some(to) = st.to {
-        tx = tx.to(to);
+    let tx = geth_types::Transaction {
+        from: st.from,
+        to: st.to,
+        nonce: U64::from(st.nonce),
+        value: st.value,
+        gas_limit: U64::from(st.gas_limit),
+        gas_price: st.gas_price,
+        call_data: st.data,
+        ..Default::default()
     }
-    let tx: TypedTransaction = tx.into();
-
-    let sig = wallet.sign_transaction_sync(&tx).unwrap();
+    .sign(&st.secret_key, chain_id)
+    .expect("statetest secret key should be valid");
 
     (
         st.id,
         TraceConfig {
-            chain_id: U256::one(),
+            chain_id: U256::from(chain_id),
             history_hashes: vec![U256::from_big_endian(st.env.previous_hash.as_bytes())],
             block_constants: geth_types::BlockConstants {
                 coinbase: st.env.current_coinbase,
@@ -138,24 +523,14 @@ fn into_traceconfig(st: StateTest) -> (String, TraceConfig, StateTestResult) {
                 difficulty: st.env.current_difficulty,
                 gas_limit: U256::from(st.env.current_gas_limit),
                 base_fee: U256::one(),
+                // The env of the statetests doesn't carry the PREVRANDAO, so the
+                // difficulty is used in its place.
+                mix_hash: H256::from(st.env.current_difficulty.to_be_bytes()),
             },
 
-            transactions: vec![geth_types::Transaction {
-                from: st.from,
-                to: st.to,
-                nonce: U64::from(st.nonce),
-                value: st.value,
-                gas_limit: U64::from(st.gas_limit),
-                gas_price: st.gas_price,
-                gas_fee_cap: U256::zero(),
-                gas_tip_cap: U256::zero(),
-                call_data: st.data,
-                access_list: None,
-                v: sig.v,
-                r: sig.r,
-                s: sig.s,
-            }],
+            transactions: vec![tx.tx],
             accounts: st.pre,
+            hardfork: Some(st.fork.tracer_fork().to_string()),
             ..Default::default()
         },
         st.result,
@@ -171,43 +546,121 @@ pub fn geth_trace(st: StateTest) -> Result<GethExecTrace, StateTestError> {
     Ok(geth_traces.remove(0))
 }
 
-pub fn run_test(
-    st: StateTest,
-    suite: TestSuite,
-    circuits_config: CircuitsConfig,
-) -> Result<(), StateTestError> {
-    // get the geth traces
+/// Resources used by a test, to size the circuits from real transactions
+#[derive(Default, Debug, Clone, PartialEq, Eq)]
+pub struct TestStats {
+    /// gas used by the transactions
+    pub gas_used: u64,
+    /// rows needed by each sub-circuit
+    pub rows: RowEstimate,
+}
 
-    let (_, trace_config, post) = into_traceconfig(st.clone());
+/// Wall-clock and memory limits of a test, checked between its phases
+/// (tracing, building the circuit input, checking a circuit): a breach aborts
+/// the rest of the test.  The suite runner also stops waiting for a test at
+/// its deadline, and these checks end the test it left behind.
+struct TestGuard {
+    start: Instant,
+    timeout: Option<Duration>,
+    max_memory_mb: Option<u64>,
+}
 
-    let geth_traces = external_tracer::trace(&trace_config);
+impl TestGuard {
+    fn new(suite: &TestSuite) -> Self {
+        Self {
+            start: Instant::now(),
+            timeout: suite.timeout.map(Duration::from_secs),
+            max_memory_mb: suite.max_memory,
+        }
+    }
 
-    let geth_traces = match (geth_traces, st.exception) {
-        (Ok(res), false) => res,
-        (Ok(_), true) => {
-            return Err(StateTestError::Exception {
-                expected: true,
-                found: "no error".into(),
-            })
+    /// Returns a skip error if a limit is exceeded
+    fn check(&self) -> Result<(), StateTestError> {
+        if let Some(timeout) = self.timeout {
+            if self.start.elapsed() > timeout {
+                return Err(StateTestError::SkipTestTimeout(timeout.as_secs()));
+            }
         }
-        (Err(_), true) => return Ok(()),
-        (Err(err), false) => {
-            return Err(StateTestError::Exception {
-                expected: false,
-                found: err.to_string(),
-            })
+        if let Some(max_memory_mb) = self.max_memory_mb {
+            if let Some(memory_mb) = resident_memory_mb() {
+                if memory_mb > max_memory_mb {
+                    return Err(StateTestError::SkipTestOutOfMemory(memory_mb));
+                }
+            }
         }
-    };
+        Ok(())
+    }
+}
+
+/// Resident memory of the process in MB, shared by the tests running in
+/// parallel.  Only available on linux.
+fn resident_memory_mb() -> Option<u64> {
+    const PAGE_SIZE: u64 = 4096;
+    let statm = std::fs::read_to_string("/proc/self/statm").ok()?;
+    let resident_pages: u64 = statm.split_whitespace().nth(1)?.parse().ok()?;
+    Some(resident_pages * PAGE_SIZE / (1024 * 1024))
+}
+
+/// Traces the transactions of `trace_config`, reusing the cached traces if
+/// a cache folder is configured
+fn trace_txs(
+    trace_config: &TraceConfig,
+    circuits_config: &CircuitsConfig,
+) -> Result<Vec<TxTrace>, eth_types::Error> {
+    match &circuits_config.trace_cache_dir {
+        Some(cache_dir) => external_tracer::trace_txs_cached(trace_config, cache_dir),
+        None => external_tracer::trace_txs(trace_config),
+    }
+}
+
+/// Checks the traces against the limits of the suite, returning the gas used
+/// by the transactions
+fn check_limits(id: &str, tx_traces: &[TxTrace], suite: &TestSuite) -> Result<u64, StateTestError> {
+    let steps: usize = tx_traces
+        .iter()
+        .map(|tx_trace| tx_trace.trace.struct_logs.len())
+        .sum();
+    if steps as u64 > suite.max_steps {
+        return Err(StateTestError::SkipTestMaxSteps(steps));
+    }
 
-    if geth_traces[0].struct_logs.len() as u64 > suite.max_steps {
-        return Err(StateTestError::SkipTestMaxSteps(
-            geth_traces[0].struct_logs.len(),
-        ));
+    let gas_used: u64 = tx_traces.iter().map(|tx_trace| tx_trace.gas_used()).sum();
+    if suite.max_gas > 0 && gas_used > suite.max_gas {
+        return Err(StateTestError::SkipTestMaxGasLimit(gas_used));
     }
 
-    if suite.max_gas > 0 && geth_traces[0].gas > suite.max_gas {
-        return Err(StateTestError::SkipTestMaxGasLimit(geth_traces[0].gas));
+    for (index, tx_trace) in tx_traces.iter().enumerate() {
+        if let Some(failure) = tx_trace.failure() {
+            log::debug!("{}: tx {} failed: {}", id, index, failure);
+        }
     }
+    Ok(gas_used)
+}
+
+/// The post state dumped by the tracer after the last transaction, if it was
+/// requested
+fn geth_post_state(tx_traces: &[TxTrace]) -> Option<HashMap<Address, AccountState>> {
+    tx_traces
+        .last()
+        .and_then(|tx_trace| tx_trace.post_state.clone())
+}
+
+/// Builds the circuit input of the block of signed transactions traced with
+/// `trace_config`, and checks it with the selected circuits.  Returns the
+/// builder, holding the post state, and the rows needed by each sub-circuit.
+fn check_block(
+    id: &str,
+    trace_config: TraceConfig,
+    tx_traces: Vec<TxTrace>,
+    chain_config: ChainConfig,
+    circuits_config: &CircuitsConfig,
+    guard: &TestGuard,
+) -> Result<(CircuitInputBuilder<FixedCParams>, RowEstimate), StateTestError> {
+    let num_txs = tx_traces.len();
+    let geth_traces: Vec<GethExecTrace> = tx_traces
+        .into_iter()
+        .map(|tx_trace| tx_trace.trace)
+        .collect();
 
     let transactions = trace_config
         .transactions
@@ -229,72 +682,278 @@ pub fn run_test(
         difficulty: trace_config.block_constants.difficulty,
         gas_limit: trace_config.block_constants.gas_limit,
         base_fee_per_gas: Some(trace_config.block_constants.base_fee),
+        mix_hash: Some(trace_config.block_constants.mix_hash),
         transactions,
         ..eth_types::Block::default()
     };
 
-    let wallet: LocalWallet = SigningKey::from_slice(&st.secret_key).unwrap().into();
-    let mut wallets = HashMap::new();
-    wallets.insert(
-        wallet.address(),
-        wallet.with_chain_id(trace_config.chain_id.as_u64()),
-    );
-
-    // process the transaction
-    let mut geth_data = eth_types::geth_types::GethData {
+    // process the transactions, which are already signed
+    let geth_data = eth_types::geth_types::GethData {
         chain_id: trace_config.chain_id,
-        history_hashes: trace_config.history_hashes.clone(),
-        geth_traces: geth_traces.clone(),
-        accounts: trace_config.accounts.values().cloned().collect(),
-        eth_block: eth_block.clone(),
+        history_hashes: trace_config.history_hashes,
+        geth_traces,
+        accounts: trace_config.accounts.into_values().collect(),
+        eth_block,
     };
 
-    let mut builder;
-
-    if !circuits_config.super_circuit {
-        let circuits_params = FixedCParams {
-            max_txs: 1,
-            max_rws: 55000,
-            max_calldata: 5000,
-            max_bytecode: 5000,
-            max_copy_rows: 55000,
-            max_evm_rows: 0,
-            max_exp_steps: 5000,
-            max_keccak_rows: 0,
-        };
-        let block_data = BlockData::new_from_geth_data_with_params(geth_data, circuits_params);
+    let circuits_params = FixedCParams {
+        max_txs: num_txs,
+        max_rws: 55000,
+        max_calldata: 5000,
+        max_bytecode: 5000,
+        max_copy_rows: 55000,
+        max_evm_rows: 0,
+        max_exp_steps: 5000,
+        max_keccak_rows: 0,
+        anchor_tx: None,
+    };
+    let block_data = BlockData::new_from_geth_data_with_params(geth_data.clone(), circuits_params);
 
-        builder = block_data.new_circuit_input_builder();
-        builder
-            .handle_block(&eth_block, &geth_traces)
-            .map_err(|err| StateTestError::CircuitInput(err.to_string()))?;
+    let mut builder = block_data.new_circuit_input_builder();
+    builder.block.set_chain_config(chain_config);
+    builder
+        .handle_block(&geth_data.eth_block, &geth_data.geth_traces)
+        .map_err(|err| StateTestError::CircuitInput(err.to_string()))?;
 
-        let block: Block<Fr> =
-            zkevm_circuits::evm_circuit::witness::block_convert(&builder).unwrap();
+    let block = block_witness(&builder)?;
+    let rows = block.estimate_rows();
 
-        CircuitTestBuilder::<1, 1>::new_from_block(block).run();
-    } else {
-        geth_data.sign(&wallets);
-
-        let circuits_params = FixedCParams {
-            max_txs: 1,
-            max_calldata: 32,
-            max_rws: 256,
-            max_copy_rows: 256,
-            max_exp_steps: 256,
-            max_bytecode: 512,
-            max_evm_rows: 0,
-            max_keccak_rows: 0,
-        };
-        let (k, circuit, instance, _builder) =
-            SuperCircuit::<Fr>::build(geth_data, circuits_params, Fr::from(0x100)).unwrap();
-        builder = _builder;
+    for &circuit in &circuits_config.circuits {
+        guard.check()?;
+        log::debug!("{}: running the {} circuit", id, circuit);
+        check_test_circuit(circuit, &block, circuits_config.prove);
+    }
 
-        let prover = MockProver::run(k, &circuit, instance).unwrap();
-        prover.assert_satisfied_par();
+    Ok((builder, rows))
+}
+
+/// Converts the circuit input of `builder` into the witness of the circuits
+pub fn block_witness(
+    builder: &CircuitInputBuilder<FixedCParams>,
+) -> Result<Block<Fr>, StateTestError> {
+    let mut block: Block<Fr> =
+        block_convert(builder).map_err(|err| StateTestError::CircuitInput(err.to_string()))?;
+    block.randomness = Fr::from(0x100);
+    Ok(block)
+}
+
+/// Checks `circuit` for the witness `block`, panicking if it is not satisfied
+pub fn check_test_circuit(circuit: TestCircuit, block: &Block<Fr>, prove: bool) {
+    match circuit {
+        TestCircuit::Evm => {
+            check_circuit::<EvmCircuit<Fr>>(circuit, block, block.get_test_degree(), prove)
+        }
+        TestCircuit::State => check_circuit::<StateCircuit<Fr>>(circuit, block, MIN_K, prove),
+        TestCircuit::Tx => check_circuit::<TxCircuit<Fr>>(circuit, block, MIN_K, prove),
+        TestCircuit::Bytecode => check_circuit::<BytecodeCircuit<Fr>>(circuit, block, MIN_K, prove),
+        TestCircuit::Copy => check_circuit::<CopyCircuit<Fr>>(circuit, block, MIN_K, prove),
+        TestCircuit::Exp => check_circuit::<ExpCircuit<Fr>>(circuit, block, MIN_K, prove),
+        TestCircuit::Keccak => check_circuit::<KeccakCircuit<Fr>>(circuit, block, MIN_K, prove),
+        TestCircuit::Super => check_circuit::<SuperCircuit<Fr>>(circuit, block, 0, prove),
+    }
+}
+
+/// Runs the test, returning the gas it used and the rows needed by the
+/// sub-circuits (all zero if the transaction is expected to fail before
+/// reaching the circuits).
+pub fn run_test(
+    st: StateTest,
+    suite: TestSuite,
+    circuits_config: CircuitsConfig,
+) -> Result<TestStats, StateTestError> {
+    let guard = TestGuard::new(&suite);
+
+    // get the geth traces
+    let chain_config = st
+        .fork
+        .chain_config(1)
+        .ok_or_else(|| StateTestError::CircuitInput(format!("unsupported fork {}", st.fork)))?;
+    let (_, mut trace_config, post) = into_traceconfig(st.clone());
+    trace_config.dump_post_state = circuits_config.diff_post_state;
+
+    let tx_traces = match (trace_txs(&trace_config, &circuits_config), st.exception) {
+        (Ok(res), false) => res,
+        (Ok(_), true) => {
+            return Err(StateTestError::Exception {
+                expected: true,
+                found: "no error".into(),
+            })
+        }
+        (Err(_), true) => return Ok(TestStats::default()),
+        (Err(err), false) => {
+            return Err(StateTestError::Exception {
+                expected: false,
+                found: err.to_string(),
+            })
+        }
     };
 
+    let gas_used = check_limits(&st.id, &tx_traces, &suite)?;
+    guard.check()?;
+    let geth_post = geth_post_state(&tx_traces);
+    let reexecution = run_reexecution(&trace_config, st.fork, &circuits_config);
+    let (builder, rows) = check_block(
+        &st.id,
+        trace_config,
+        tx_traces,
+        chain_config,
+        &circuits_config,
+        &guard,
+    )?;
+
+    if let Some(geth_post) = geth_post {
+        check_geth_post_state(&builder, &geth_post)?;
+    }
+    let unsupported = match reexecution {
+        Some(Ok(expected_txs)) => {
+            check_rw_set(&builder, &expected_txs)?;
+            None
+        }
+        Some(Err(unsupported)) => Some(unsupported),
+        None => None,
+    };
     check_post(&builder, &post)?;
+    if circuits_config.check_state_root {
+        if let Some(expected) = st.state_root {
+            check_state_root(&builder, expected)?;
+        }
+    }
+    // the other checks passed, but the test is not fully checked
+    if let Some(Unsupported(reason)) = unsupported {
+        return Err(StateTestError::SkipTestReexecution(reason));
+    }
 
-    Ok(())
+    Ok(TestStats { gas_used, rows })
+}
+
+/// Runs the blocks of the test one after the other, each one from the state
+/// left by the previous one, and checks the state after the last block.
+/// Returns the total gas used and the rows needed by the largest block.
+pub fn run_blockchain_test(
+    bt: BlockchainTest,
+    suite: TestSuite,
+    circuits_config: CircuitsConfig,
+) -> Result<TestStats, StateTestError> {
+    let chain_id = 1;
+    let chain_config = bt
+        .fork
+        .chain_config(chain_id)
+        .ok_or_else(|| StateTestError::CircuitInput(format!("unsupported fork {}", bt.fork)))?;
+
+    let guard = TestGuard::new(&suite);
+    let mut accounts = bt.pre;
+    let mut history_hashes = vec![U256::from_big_endian(bt.genesis_hash.as_bytes())];
+    let mut stats = TestStats::default();
+    let mut post_builder = None;
+    let mut unsupported = None;
+
+    for test_block in bt.blocks {
+        let trace_config = TraceConfig {
+            chain_id: U256::from(chain_id),
+            history_hashes: history_hashes.clone(),
+            block_constants: geth_types::BlockConstants {
+                coinbase: test_block.env.current_coinbase,
+                timestamp: U256::from(test_block.env.current_timestamp),
+                number: U64::from(test_block.env.current_number),
+                difficulty: test_block.env.current_difficulty,
+                gas_limit: U256::from(test_block.env.current_gas_limit),
+                base_fee: test_block.base_fee,
+                mix_hash: H256::from(test_block.env.current_difficulty.to_be_bytes()),
+            },
+            transactions: test_block.transactions,
+            accounts: accounts.clone(),
+            hardfork: Some(bt.fork.tracer_fork().to_string()),
+            dump_post_state: circuits_config.diff_post_state,
+            ..Default::default()
+        };
+
+        let tx_traces = trace_txs(&trace_config, &circuits_config).map_err(|err| {
+            StateTestError::Exception {
+                expected: false,
+                found: err.to_string(),
+            }
+        })?;
+        let gas_used = check_limits(&bt.id, &tx_traces, &suite)?;
+        guard.check()?;
+        if gas_used != test_block.gas_used {
+            return Err(StateTestError::GasUsedMismatch {
+                block: test_block.env.current_number,
+                expected: test_block.gas_used,
+                found: gas_used,
+            });
+        }
+
+        let geth_post = geth_post_state(&tx_traces);
+        let reexecution = run_reexecution(&trace_config, bt.fork, &circuits_config);
+        let (builder, rows) = check_block(
+            &bt.id,
+            trace_config,
+            tx_traces,
+            chain_config.clone(),
+            &circuits_config,
+            &guard,
+        )?;
+        if let Some(geth_post) = geth_post {
+            check_geth_post_state(&builder, &geth_post)?;
+        }
+        match reexecution {
+            Some(Ok(expected_txs)) => check_rw_set(&builder, &expected_txs)?,
+            Some(Err(err)) => {
+                unsupported.get_or_insert(err);
+            }
+            None => {}
+        }
+
+        stats.gas_used += gas_used;
+        if rows.max_rows() > stats.rows.max_rows() {
+            stats.rows = rows;
+        }
+
+        // the state after the block is the pre state of the next one
+        accounts = builder
+            .sdb
+            .iter()
+            .filter(|(_, account)| !account.is_empty())
+            .map(|(address, account)| {
+                let code = builder
+                    .code_db
+                    .get(&account.code_hash)
+                    .map(|code| code.to_vec())
+                    .unwrap_or_default();
+                let storage = account
+                    .storage
+                    .iter()
+                    .filter(|(_, value)| !value.is_zero())
+                    .map(|(key, value)| (*key, *value))
+                    .collect();
+                let account = geth_types::Account {
+                    address: *address,
+                    nonce: account.nonce.into(),
+                    balance: account.balance,
+                    code: code.into(),
+                    storage,
+                };
+                (*address, account)
+            })
+            .collect();
+        history_hashes.push(U256::from_big_endian(test_block.hash.as_bytes()));
+        if history_hashes.len() > 256 {
+            history_hashes.remove(0);
+        }
+        post_builder = Some(builder);
+    }
+
+    if let Some(builder) = post_builder {
+        check_post(&builder, &bt.post)?;
+        if circuits_config.check_state_root {
+            if let Some(expected) = bt.state_root {
+                check_state_root(&builder, expected)?;
+            }
+        }
+    }
+    if let Some(Unsupported(reason)) = unsupported {
+        return Err(StateTestError::SkipTestReexecution(reason));
+    }
+
+    Ok(stats)
 }
@@ -0,0 +1,391 @@
+use super::{executor::run_test, CircuitsConfig, StateTest};
+use crate::config::TestSuite;
+use eth_types::{bytecode::Bytecode, evm_types::OpcodeId, Address, Word};
+use rand::{seq::SliceRandom, Rng, SeedableRng};
+use rand_chacha::ChaCha20Rng;
+use std::{fmt, panic::AssertUnwindSafe};
+
+/// Kind of the value pushed for a stack input of an instruction, to keep the
+/// memory expansion, the storage slots and the accounts in a small range
+#[derive(Debug, Clone, Copy)]
+enum Input {
+    /// any value, biased towards the edge cases
+    Any,
+    /// memory offset
+    Offset,
+    /// size of a memory area
+    Size,
+    /// storage slot, usually one of the layout
+    Slot,
+    /// address, usually one of the accounts of the test
+    Address,
+}
+
+/// Opcodes generated by the fuzzer, with their weight, inputs and number of
+/// outputs.  Jumps, calls and creations are not generated, so that the
+/// instructions are independent of each other.
+const OPCODES: &[(OpcodeId, u32, &[Input], usize)] = {
+    use Input::*;
+    use OpcodeId::*;
+    &[
+        (ADD, 4, &[Any, Any], 1),
+        (MUL, 4, &[Any, Any], 1),
+        (SUB, 4, &[Any, Any], 1),
+        (DIV, 3, &[Any, Any], 1),
+        (SDIV, 3, &[Any, Any], 1),
+        (MOD, 3, &[Any, Any], 1),
+        (SMOD, 3, &[Any, Any], 1),
+        (ADDMOD, 3, &[Any, Any, Any], 1),
+        (MULMOD, 3, &[Any, Any, Any], 1),
+        (EXP, 3, &[Any, Any], 1),
+        (SIGNEXTEND, 3, &[Any, Any], 1),
+        (LT, 2, &[Any, Any], 1),
+        (GT, 2, &[Any, Any], 1),
+        (SLT, 2, &[Any, Any], 1),
+        (SGT, 2, &[Any, Any], 1),
+        (EQ, 2, &[Any, Any], 1),
+        (ISZERO, 2, &[Any], 1),
+        (AND, 2, &[Any, Any], 1),
+        (OR, 2, &[Any, Any], 1),
+        (XOR, 2, &[Any, Any], 1),
+        (NOT, 2, &[Any], 1),
+        (BYTE, 3, &[Any, Any], 1),
+        (SHL, 3, &[Any, Any], 1),
+        (SHR, 3, &[Any, Any], 1),
+        (SAR, 3, &[Any, Any], 1),
+        (SHA3, 3, &[Offset, Size], 1),
+        (ADDRESS, 1, &[], 1),
+        (BALANCE, 2, &[Address], 1),
+        (ORIGIN, 1, &[], 1),
+        (CALLER, 1, &[], 1),
+        (CALLVALUE, 1, &[], 1),
+        (CALLDATALOAD, 2, &[Offset], 1),
+        (CALLDATASIZE, 1, &[], 1),
+        (CALLDATACOPY, 2, &[Offset, Offset, Size], 0),
+        (CODESIZE, 1, &[], 1),
+        (CODECOPY, 2, &[Offset, Offset, Size], 0),
+        (GASPRICE, 1, &[], 1),
+        (EXTCODESIZE, 2, &[Address], 1),
+        (EXTCODECOPY, 2, &[Address, Offset, Offset, Size], 0),
+        (RETURNDATASIZE, 1, &[], 1),
+        (EXTCODEHASH, 2, &[Address], 1),
+        (BLOCKHASH, 1, &[Any], 1),
+        (COINBASE, 1, &[], 1),
+        (TIMESTAMP, 1, &[], 1),
+        (NUMBER, 1, &[], 1),
+        (DIFFICULTY, 1, &[], 1),
+        (GASLIMIT, 1, &[], 1),
+        (CHAINID, 1, &[], 1),
+        (SELFBALANCE, 1, &[], 1),
+        (BASEFEE, 1, &[], 1),
+        (MLOAD, 3, &[Offset], 1),
+        (MSTORE, 3, &[Offset, Any], 0),
+        (MSTORE8, 2, &[Offset, Any], 0),
+        (MSIZE, 1, &[], 1),
+        (SLOAD, 4, &[Slot], 1),
+        (SSTORE, 4, &[Slot, Any], 0),
+        (PC, 1, &[], 1),
+        (GAS, 1, &[], 1),
+        (LOG0, 1, &[Offset, Size], 0),
+        (LOG1, 1, &[Offset, Size, Any], 0),
+        (LOG2, 1, &[Offset, Size, Any, Any], 0),
+    ]
+};
+
+/// Address of the fuzzed contract
+const CONTRACT: Address = Address::repeat_byte(0xcc);
+
+/// Gas limit of the fuzzed transactions
+const GAS_LIMIT: u64 = 1_000_000;
+
+/// An opcode with the values pushed for its inputs, whose outputs are popped,
+/// so that removing an instruction keeps the program valid
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Instruction {
+    pub opcode: OpcodeId,
+    pub inputs: Vec<Word>,
+    pub outputs: usize,
+}
+
+/// A random call to a contract with a random program and storage layout
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FuzzCase {
+    pub instructions: Vec<Instruction>,
+    pub storage: Vec<(Word, Word)>,
+    pub calldata: Vec<u8>,
+    pub value: Word,
+}
+
+impl FuzzCase {
+    /// Generates a case of up to `max_instructions` instructions
+    pub fn random(rng: &mut impl Rng, max_instructions: usize) -> Self {
+        let accounts = [CONTRACT, StateTest::builder().build().from];
+        let storage: Vec<(Word, Word)> = (0..rng.gen_range(0..8))
+            .map(|_| (Word::from(rng.gen_range(0..16u64)), random_word(rng)))
+            .collect();
+        let instructions = (0..rng.gen_range(1..=max_instructions))
+            .map(|_| {
+                let (opcode, _, inputs, outputs) = *OPCODES
+                    .choose_weighted(rng, |(_, weight, _, _)| *weight)
+                    .expect("opcodes have weights");
+                Instruction {
+                    opcode,
+                    inputs: inputs
+                        .iter()
+                        .map(|input| random_input(rng, *input, &storage, &accounts))
+                        .collect(),
+                    outputs,
+                }
+            })
+            .collect();
+        let calldata = (0..rng.gen_range(0..64)).map(|_| rng.gen()).collect();
+        let value = Word::from(rng.gen_range(0..3u64));
+
+        Self {
+            instructions,
+            storage,
+            calldata,
+            value,
+        }
+    }
+
+    /// Code of the contract
+    pub fn code(&self) -> Vec<u8> {
+        let mut code = Bytecode::default();
+        for instruction in &self.instructions {
+            // the first input is the top of the stack
+            for input in instruction.inputs.iter().rev() {
+                code.push(32, *input);
+            }
+            code.write_op(instruction.opcode);
+            for _ in 0..instruction.outputs {
+                code.write_op(OpcodeId::POP);
+            }
+        }
+        code.write_op(OpcodeId::STOP);
+        code.code()
+    }
+
+    /// Test calling the contract, without expected post state: the circuits
+    /// are the ones checked
+    pub fn state_test(&self, id: &str) -> StateTest {
+        let mut builder = StateTest::builder()
+            .id(id)
+            .code(CONTRACT, self.code())
+            .call(CONTRACT)
+            .data(self.calldata.clone())
+            .value(self.value)
+            .gas_limit(GAS_LIMIT);
+        for (slot, value) in &self.storage {
+            builder = builder.storage(CONTRACT, *slot, *value);
+        }
+        builder.build()
+    }
+
+    /// Smaller cases, from the most to the least reduced
+    fn reductions(&self) -> Vec<FuzzCase> {
+        let mut reductions = Vec::new();
+
+        // remove chunks of instructions, halving their size
+        let len = self.instructions.len();
+        let mut chunk = len / 2;
+        while chunk > 0 {
+            for start in (0..len).step_by(chunk) {
+                let mut case = self.clone();
+                case.instructions.drain(start..(start + chunk).min(len));
+                reductions.push(case);
+            }
+            chunk /= 2;
+        }
+        for index in 0..self.storage.len() {
+            let mut case = self.clone();
+            case.storage.remove(index);
+            reductions.push(case);
+        }
+        if !self.calldata.is_empty() {
+            reductions.push(Self {
+                calldata: Vec::new(),
+                ..self.clone()
+            });
+        }
+        if !self.value.is_zero() {
+            reductions.push(Self {
+                value: Word::zero(),
+                ..self.clone()
+            });
+        }
+        reductions
+    }
+
+    /// Reduces the case while it still `fails`
+    pub fn shrink(self, fails: impl Fn(&FuzzCase) -> bool) -> FuzzCase {
+        let mut case = self;
+        'reduce: loop {
+            for reduction in case.reductions() {
+                if fails(&reduction) {
+                    case = reduction;
+                    continue 'reduce;
+                }
+            }
+            return case;
+        }
+    }
+}
+
+/// The case as a oneliner spec, to reproduce it with `testool --oneliner`
+impl fmt::Display for FuzzCase {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "call;{};{:#x};{} {};{};0",
+            hex::encode(&self.calldata),
+            self.value,
+            GAS_LIMIT,
+            hex::encode(CONTRACT),
+            hex::encode(self.code()),
+        )?;
+        for (slot, value) in &self.storage {
+            write!(f, ";{:#x}:{:#x}", slot, value)?;
+        }
+        Ok(())
+    }
+}
+
+fn random_word(rng: &mut impl Rng) -> Word {
+    match rng.gen_range(0..8) {
+        0 => Word::zero(),
+        1 => Word::one(),
+        2 => Word::MAX,
+        3 => Word::one() << 255,
+        4 => Word::from(rng.gen_range(0..256u64)),
+        5 => Word::from(rng.gen::<u64>()),
+        _ => Word::from_big_endian(&rng.gen::<[u8; 32]>()),
+    }
+}
+
+fn random_input(
+    rng: &mut impl Rng,
+    input: Input,
+    storage: &[(Word, Word)],
+    accounts: &[Address],
+) -> Word {
+    match input {
+        Input::Any => random_word(rng),
+        Input::Offset => Word::from(rng.gen_range(0..256u64)),
+        Input::Size => Word::from(rng.gen_range(0..64u64)),
+        Input::Slot => match storage.choose(rng) {
+            Some((slot, _)) if rng.gen_bool(0.75) => *slot,
+            _ => Word::from(rng.gen_range(0..16u64)),
+        },
+        Input::Address => {
+            let address = match accounts.choose(rng) {
+                Some(address) if rng.gen_bool(0.75) => *address,
+                _ => Address::from_low_u64_be(rng.gen_range(1..16)),
+            };
+            Word::from_big_endian(address.as_bytes())
+        }
+    }
+}
+
+/// A fuzzed case rejected by the circuits, after shrinking
+#[derive(Debug, Clone)]
+pub struct FuzzFailure {
+    pub case: FuzzCase,
+    pub error: String,
+}
+
+/// Runs the case through the tracer, bus-mapping and the circuits, returning
+/// the error if it fails
+fn run_case(id: &str, case: &FuzzCase, circuits_config: &CircuitsConfig) -> Option<String> {
+    let result = std::panic::catch_unwind(AssertUnwindSafe(|| {
+        run_test(
+            case.state_test(id),
+            TestSuite::default(),
+            circuits_config.clone(),
+        )
+    }));
+    match result {
+        Ok(Ok(_)) => None,
+        Ok(Err(err)) if err.is_skip() => None,
+        Ok(Err(err)) => Some(err.to_string()),
+        Err(err) => Some(
+            err.downcast_ref::<String>()
+                .cloned()
+                .or_else(|| err.downcast_ref::<&str>().map(|s| s.to_string()))
+                .unwrap_or_else(|| "unable to get panic info".into()),
+        ),
+    }
+}
+
+/// Runs `iterations` random cases generated from `seed`, returning the
+/// failing ones shrunk to a minimal case.
+pub fn fuzz(
+    seed: u64,
+    iterations: usize,
+    max_instructions: usize,
+    circuits_config: &CircuitsConfig,
+) -> Vec<FuzzFailure> {
+    let mut rng = ChaCha20Rng::seed_from_u64(seed);
+    let mut failures = Vec::new();
+
+    // panics are reported as failures
+    let default_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(|_info| {}));
+
+    for iteration in 0..iterations {
+        let id = format!("fuzz_{}_{}", seed, iteration);
+        let case = FuzzCase::random(&mut rng, max_instructions);
+        if let Some(error) = run_case(&id, &case, circuits_config) {
+            log::info!("{} failed, shrinking: {}", id, error);
+            let case = case.shrink(|case| run_case(&id, case, circuits_config).is_some());
+            let error = run_case(&id, &case, circuits_config).unwrap_or(error);
+            log::info!("{}: {}\n  {}", id, error, case);
+            failures.push(FuzzFailure { case, error });
+        } else {
+            log::debug!("{} passed", id);
+        }
+    }
+
+    std::panic::set_hook(default_hook);
+    failures
+}
+
+#[cfg(test)]
+mod fuzz_tests {
+    use super::*;
+
+    #[test]
+    fn random_cases_are_deterministic() {
+        let case = FuzzCase::random(&mut ChaCha20Rng::seed_from_u64(7), 32);
+        assert_eq!(
+            case,
+            FuzzCase::random(&mut ChaCha20Rng::seed_from_u64(7), 32)
+        );
+        assert!(!case.instructions.is_empty() && case.instructions.len() <= 32);
+
+        // the program ends with STOP and is a valid oneliner
+        assert_eq!(case.code().last(), Some(&OpcodeId::STOP.as_u8()));
+        let test = StateTest::parse_oneline_spec(&case.to_string()).unwrap();
+        assert_eq!(test.pre[&CONTRACT].code.to_vec(), case.code());
+    }
+
+    #[test]
+    fn shrink_to_minimal_case() {
+        let mut rng = ChaCha20Rng::seed_from_u64(1);
+        let mut case = FuzzCase::random(&mut rng, 64);
+        let sstore = Instruction {
+            opcode: OpcodeId::SSTORE,
+            inputs: vec![Word::one(), Word::from(2)],
+            outputs: 0,
+        };
+        case.instructions
+            .insert(case.instructions.len() / 2, sstore.clone());
+        case.value = Word::one();
+
+        let shrunk = case.shrink(|case| case.instructions.contains(&sstore));
+        assert_eq!(shrunk.instructions, vec![sstore]);
+        assert!(shrunk.storage.is_empty());
+        assert!(shrunk.calldata.is_empty());
+        assert!(shrunk.value.is_zero());
+    }
+}
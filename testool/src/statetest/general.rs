@@ -0,0 +1,327 @@
+use super::{
+    parse,
+    spec::{Env, StateTest},
+};
+use crate::utils::{MainnetFork, TEST_FORK};
+use anyhow::{bail, Result};
+use eth_types::{geth_types::Account, Address};
+use ethers_core::{k256::ecdsa::SigningKey, utils::secret_key_to_address};
+use serde::Deserialize;
+use std::{collections::HashMap, str::FromStr};
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TestEnv {
+    current_coinbase: String,
+    current_difficulty: String,
+    current_gas_limit: String,
+    current_number: String,
+    current_timestamp: String,
+    previous_hash: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct Indexes {
+    data: usize,
+    gas: usize,
+    value: usize,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Post {
+    indexes: Indexes,
+    hash: String,
+    expect_exception: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub(super) struct AccountPre {
+    balance: String,
+    code: String,
+    nonce: String,
+    storage: HashMap<String, String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Transaction {
+    data: Vec<String>,
+    gas_limit: Vec<String>,
+    gas_price: Option<String>,
+    nonce: String,
+    secret_key: String,
+    to: String,
+    value: Vec<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct GeneralStateTest {
+    env: TestEnv,
+    transaction: Transaction,
+    pre: HashMap<String, AccountPre>,
+    post: HashMap<String, Vec<Post>>,
+}
+
+/// Loader of the filled tests of the ethereum/tests `GeneralStateTests`
+/// suite, where each entry of the `post` section of a fork refers to one
+/// combination of the indexed transaction data, gas and value.
+///
+/// The filled tests only commit to the post state root, so the expanded
+/// `StateTest`s only check whether the transaction is expected to fail, and
+/// the state root with `--check-state-root`.
+pub struct GeneralStateTestBuilder {
+    fork: MainnetFork,
+}
+
+impl Default for GeneralStateTestBuilder {
+    fn default() -> Self {
+        Self { fork: TEST_FORK }
+    }
+}
+
+impl GeneralStateTestBuilder {
+    /// selects the fork whose `post` section is loaded
+    pub fn with_fork(mut self, fork: MainnetFork) -> Self {
+        self.fork = fork;
+        self
+    }
+
+    /// returns true if `source` looks like a filled `GeneralStateTests` file
+    pub fn is_general_state_test(source: &str) -> bool {
+        serde_json::from_str::<HashMap<String, serde_json::Value>>(source)
+            .map(|tests| tests.values().all(|test| test.get("post").is_some()))
+            .unwrap_or(false)
+    }
+
+    /// generates `StateTest` vectors from a filled `GeneralStateTests` file,
+    /// for the `post` section of the tested fork.
+    pub fn load_json(&self, path: &str, source: &str) -> Result<Vec<StateTest>> {
+        let mut state_tests = Vec::new();
+        let tests: HashMap<String, GeneralStateTest> = serde_json::from_str(source)?;
+
+        for (test_name, test) in tests {
+            let posts: Vec<_> = test
+                .post
+                .iter()
+                .filter(|(fork, _)| {
+                    MainnetFork::from_str(fork).map_or(false, |fork| fork == self.fork)
+                })
+                .flat_map(|(_, posts)| posts)
+                .collect();
+            if posts.is_empty() {
+                continue;
+            }
+
+            let env = Self::parse_env(&test.env)?;
+            let pre = Self::parse_accounts_pre(&test.pre)?;
+
+            let to = parse::parse_to_address(&test.transaction.to)?;
+            let secret_key = parse::parse_bytes(&test.transaction.secret_key)?;
+            let from = secret_key_to_address(&SigningKey::from_slice(&secret_key)?);
+            let nonce = parse::parse_u64(&test.transaction.nonce)?;
+            let gas_price = match &test.transaction.gas_price {
+                Some(gas_price) => parse::parse_u256(gas_price)?,
+                None => bail!("{}: only legacy transactions are supported", test_name),
+            };
+
+            for post in posts {
+                let Indexes { data, gas, value } = post.indexes;
+                let (data_s, gas_limit_s, value_s) = match (
+                    test.transaction.data.get(data),
+                    test.transaction.gas_limit.get(gas),
+                    test.transaction.value.get(value),
+                ) {
+                    (Some(data_s), Some(gas_limit_s), Some(value_s)) => {
+                        (data_s, gas_limit_s, value_s)
+                    }
+                    _ => bail!(
+                        "{}: post indexes out of range {:?}",
+                        test_name,
+                        post.indexes
+                    ),
+                };
+
+                state_tests.push(StateTest {
+                    path: path.to_string(),
+                    id: format!("{}_d{}_g{}_v{}", test_name, data, gas, value),
+                    env: env.clone(),
+                    pre: pre.clone(),
+                    result: HashMap::new(),
+                    from,
+                    to,
+                    secret_key: secret_key.clone(),
+                    nonce,
+                    gas_price,
+                    gas_limit: parse::parse_u64(gas_limit_s)?,
+                    value: parse::parse_u256(value_s)?,
+                    data: parse::parse_bytes(data_s)?,
+                    exception: post.expect_exception.is_some(),
+                    fork: self.fork,
+                    state_root: Some(parse::parse_hash(&post.hash)?),
+                });
+            }
+        }
+
+        Ok(state_tests)
+    }
+
+    /// parse env section
+    fn parse_env(env: &TestEnv) -> Result<Env> {
+        Ok(Env {
+            current_coinbase: parse::parse_address(&env.current_coinbase)?,
+            current_difficulty: parse::parse_u256(&env.current_difficulty)?,
+            current_gas_limit: parse::parse_u64(&env.current_gas_limit)?,
+            current_number: parse::parse_u64(&env.current_number)?,
+            current_timestamp: parse::parse_u64(&env.current_timestamp)?,
+            previous_hash: env
+                .previous_hash
+                .as_ref()
+                .map(|hash| parse::parse_hash(hash))
+                .transpose()?
+                .unwrap_or_default(),
+        })
+    }
+
+    /// parse a vector of address=>(storage,balance,code,nonce) entry
+    pub(super) fn parse_accounts_pre(
+        accounts_pre: &HashMap<String, AccountPre>,
+    ) -> Result<HashMap<Address, Account>> {
+        let mut accounts = HashMap::new();
+        for (address, acc) in accounts_pre {
+            let address = parse::parse_address(address)?;
+            let mut storage = HashMap::new();
+            for (k, v) in &acc.storage {
+                storage.insert(parse::parse_u256(k)?, parse::parse_u256(v)?);
+            }
+            let account = Account {
+                address,
+                balance: parse::parse_u256(&acc.balance)?,
+                nonce: parse::parse_u64(&acc.nonce)?.into(),
+                code: parse::parse_bytes(&acc.code)?,
+                storage,
+            };
+            accounts.insert(address, account);
+        }
+        Ok(accounts)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use eth_types::{Bytes, H256, U256};
+
+    const JSON: &str = r#"
+{
+    "add11" : {
+        "_info" : {
+            "comment" : "A test for (add 1 1) opcode result",
+            "filling-rpc-server" : "evm version 1.11.4-unstable"
+        },
+        "env" : {
+            "currentBaseFee" : "0x0a",
+            "currentCoinbase" : "0x2adc25665018aa1fe0e6bc666dac8fc2697ff9ba",
+            "currentDifficulty" : "0x020000",
+            "currentGasLimit" : "0xff112233445566",
+            "currentNumber" : "0x01",
+            "currentTimestamp" : "0x03e8",
+            "previousHash" : "0x5e20a0453cecd065ea59c37ac63e079ee08998b6045136a8ce6635c7912ec0b6"
+        },
+        "post" : {
+            "Berlin" : [
+                {
+                    "hash" : "0x8a2b9b0b1b5ea3d5a7e0e0f0c4e9f0f5b2d1a8c6e4d2b0a9f8e7d6c5b4a39281",
+                    "indexes" : { "data" : 0, "gas" : 0, "value" : 0 },
+                    "logs" : "0x1dcc4de8dec75d7aab85b567b6ccd41ad312451b948a7413f0a142fd40d49347",
+                    "txbytes" : "0x"
+                }
+            ],
+            "Merge" : [
+                {
+                    "hash" : "0x8a2b9b0b1b5ea3d5a7e0e0f0c4e9f0f5b2d1a8c6e4d2b0a9f8e7d6c5b4a39281",
+                    "indexes" : { "data" : 1, "gas" : 0, "value" : 0 },
+                    "logs" : "0x1dcc4de8dec75d7aab85b567b6ccd41ad312451b948a7413f0a142fd40d49347",
+                    "txbytes" : "0x"
+                },
+                {
+                    "expectException" : "TR_IntrinsicGas",
+                    "hash" : "0x8a2b9b0b1b5ea3d5a7e0e0f0c4e9f0f5b2d1a8c6e4d2b0a9f8e7d6c5b4a39281",
+                    "indexes" : { "data" : 0, "gas" : 1, "value" : 0 },
+                    "logs" : "0x1dcc4de8dec75d7aab85b567b6ccd41ad312451b948a7413f0a142fd40d49347",
+                    "txbytes" : "0x"
+                }
+            ]
+        },
+        "pre" : {
+            "0x095e7baea6a6c7c4c2dfeb977efac326af552d87" : {
+                "balance" : "0x0de0b6b3a7640000",
+                "code" : "0x600160010160005500",
+                "nonce" : "0x00",
+                "storage" : {
+                    "0x00" : "0x01"
+                }
+            }
+        },
+        "transaction" : {
+            "data" : [ "0x6001", "0x6002" ],
+            "gasLimit" : [ "0x061a80", "0x5208" ],
+            "gasPrice" : "0x0a",
+            "nonce" : "0x00",
+            "secretKey" : "0x45a915e4d060149eb4365960e6a7a45f334393093061116b197e3240065ff2d8",
+            "sender" : "0xa94f5374fce5edbc8e2a8697c15331677e6ebf0b",
+            "to" : "0x095e7baea6a6c7c4c2dfeb977efac326af552d87",
+            "value" : [ "0x0186a0" ]
+        }
+    }
+}
+"#;
+
+    #[test]
+    fn test_general_state_test_parse() -> Result<()> {
+        assert!(GeneralStateTestBuilder::is_general_state_test(JSON));
+
+        let mut tests = GeneralStateTestBuilder::default().load_json("test_path", JSON)?;
+        assert_eq!(
+            GeneralStateTestBuilder::default()
+                .with_fork(MainnetFork::Berlin)
+                .load_json("test_path", JSON)?
+                .len(),
+            1
+        );
+        tests.sort_by(|a, b| a.id.cmp(&b.id));
+        assert_eq!(tests.len(), 2);
+
+        let acc095e = Address::from_str("0x095e7baea6a6c7c4c2dfeb977efac326af552d87")?;
+        let test = &tests[0];
+        assert_eq!(test.id, "add11_d0_g1_v0");
+        assert_eq!(test.gas_limit, 21000);
+        assert!(test.exception);
+
+        let test = &tests[1];
+        assert_eq!(test.id, "add11_d1_g0_v0");
+        assert_eq!(
+            test.from,
+            Address::from_str("0xa94f5374fce5edbc8e2a8697c15331677e6ebf0b")?
+        );
+        assert_eq!(test.to, Some(acc095e));
+        assert_eq!(test.gas_limit, 400000);
+        assert_eq!(test.gas_price, U256::from(10u64));
+        assert_eq!(test.value, U256::from(100000u64));
+        assert_eq!(test.data, Bytes::from(hex::decode("6002")?));
+        assert_eq!(
+            test.pre[&acc095e].storage,
+            HashMap::from([(U256::zero(), U256::one())])
+        );
+        assert!(test.result.is_empty());
+        assert!(!test.exception);
+        assert_eq!(
+            test.state_root,
+            Some(H256::from_str(
+                "0x8a2b9b0b1b5ea3d5a7e0e0f0c4e9f0f5b2d1a8c6e4d2b0a9f8e7d6c5b4a39281"
+            )?)
+        );
+
+        Ok(())
+    }
+}
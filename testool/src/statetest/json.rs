@@ -2,7 +2,10 @@ use super::{
     parse,
     spec::{AccountMatch, Env, StateTest},
 };
-use crate::{compiler::Compiler, utils::MainnetFork};
+use crate::{
+    compiler::Compiler,
+    utils::{MainnetFork, TEST_FORK},
+};
 use anyhow::{bail, Result};
 use eth_types::{geth_types::Account, Address, U256};
 use ethers_core::{k256::ecdsa::SigningKey, utils::secret_key_to_address};
@@ -89,11 +92,21 @@ impl Refs {
 
 pub struct JsonStateTestBuilder<'a> {
     compiler: &'a mut Compiler,
+    fork: MainnetFork,
 }
 
 impl<'a> JsonStateTestBuilder<'a> {
     pub fn new(compiler: &'a mut Compiler) -> Self {
-        Self { compiler }
+        Self {
+            compiler,
+            fork: TEST_FORK,
+        }
+    }
+
+    /// selects the fork whose expectations are loaded
+    pub fn with_fork(mut self, fork: MainnetFork) -> Self {
+        self.fork = fork;
+        self
     }
 
     /// generates `StateTest` vectors from a ethereum josn test specification
@@ -139,7 +152,7 @@ impl<'a> JsonStateTestBuilder<'a> {
                 let value_refs = Self::parse_refs(&expect.indexes.value)?;
                 let result = self.parse_accounts_post(&expect.result)?;
 
-                if MainnetFork::in_network_range(&expect.network)? {
+                if MainnetFork::in_network_range(&expect.network, self.fork)? {
                     expects.push((data_refs, gas_refs, value_refs, result));
                 }
             }
@@ -178,6 +191,8 @@ impl<'a> JsonStateTestBuilder<'a> {
                                 value: *value,
                                 data: data.0.clone(),
                                 exception: false,
+                                fork: self.fork,
+                                state_root: None,
                             });
                         }
                     }
@@ -411,6 +426,8 @@ mod test {
                 },
             )]),
             exception: false,
+            fork: TEST_FORK,
+            state_root: None,
         };
 
         assert_eq!(expected, test);
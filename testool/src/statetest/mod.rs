@@ -1,14 +1,28 @@
+mod blockchain;
 mod executor;
+mod fuzz;
+mod general;
 mod json;
 mod parse;
+mod prover;
+mod reexec;
 mod results;
 pub mod spec;
 mod suite;
+mod trie;
 mod yaml;
 
-pub use executor::{geth_trace, run_test, CircuitsConfig, StateTestError};
+pub use blockchain::{BlockchainTest, BlockchainTestBlock, BlockchainTestBuilder};
+pub use executor::{
+    block_witness, check_test_circuit, geth_trace, run_blockchain_test, run_test, CircuitsConfig,
+    StateTestError, TestCircuit, TestStats,
+};
+pub use fuzz::{fuzz, FuzzCase, FuzzFailure};
+pub use general::GeneralStateTestBuilder;
 pub use json::JsonStateTestBuilder;
 pub use results::{ResultLevel, Results};
 pub use spec::{AccountMatch, Env, StateTest, StateTestResult};
-pub use suite::{load_statetests_suite, run_statetests_suite};
+pub use suite::{
+    load_blockchain_tests_suite, load_statetests_suite, run_statetests_suite, SuiteTest,
+};
 pub use yaml::YamlStateTestBuilder;
@@ -0,0 +1,96 @@
+use halo2_proofs::{
+    halo2curves::bn256::{Bn256, Fr, G1Affine},
+    plonk::{create_proof, keygen_pk, keygen_vk, verify_proof, Circuit},
+    poly::{
+        commitment::ParamsProver,
+        kzg::{
+            commitment::{KZGCommitmentScheme, ParamsKZG, ParamsVerifierKZG},
+            multiopen::{ProverSHPLONK, VerifierSHPLONK},
+            strategy::SingleStrategy,
+        },
+    },
+    transcript::{
+        Blake2bRead, Blake2bWrite, Challenge255, TranscriptReadBuffer, TranscriptWriterBuffer,
+    },
+};
+use once_cell::sync::Lazy;
+use rand::SeedableRng;
+use rand_chacha::ChaCha20Rng;
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::Instant,
+};
+
+/// The KZG setup is the most expensive step for the small circuits of the
+/// tests, so the params are generated once per degree and shared by all the
+/// tests.
+static PARAMS: Lazy<Mutex<HashMap<u32, Arc<ParamsKZG<Bn256>>>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Returns the (insecure, deterministic) KZG params of degree `k`
+fn params(k: u32) -> Arc<ParamsKZG<Bn256>> {
+    // the lock is held during the setup, so that tests running in parallel do
+    // not generate the same params twice
+    let mut params = PARAMS.lock().unwrap();
+    params
+        .entry(k)
+        .or_insert_with(|| {
+            log::info!("generating KZG params of degree {}", k);
+            Arc::new(ParamsKZG::<Bn256>::setup(
+                k,
+                ChaCha20Rng::seed_from_u64(k as u64),
+            ))
+        })
+        .clone()
+}
+
+/// Generates the keys for `circuit`, creates a proof and verifies it.
+/// Panics if any of the steps fails, as the MockProver does when the
+/// constraints are not satisfied.
+pub fn prove_and_verify<C: Circuit<Fr>>(name: &str, k: u32, circuit: C, instance: Vec<Vec<Fr>>) {
+    let general_params = params(k);
+    let verifier_params: ParamsVerifierKZG<Bn256> = general_params.verifier_params().clone();
+    let instance: Vec<&[Fr]> = instance.iter().map(|v| v.as_slice()).collect();
+
+    let start = Instant::now();
+    let vk = keygen_vk(general_params.as_ref(), &circuit).expect("keygen_vk should not fail");
+    let pk = keygen_pk(general_params.as_ref(), vk, &circuit).expect("keygen_pk should not fail");
+    let keygen_time = start.elapsed();
+
+    let start = Instant::now();
+    let mut transcript = Blake2bWrite::<_, G1Affine, Challenge255<_>>::init(vec![]);
+    create_proof::<KZGCommitmentScheme<Bn256>, ProverSHPLONK<'_, Bn256>, _, _, _, _>(
+        general_params.as_ref(),
+        &pk,
+        &[circuit],
+        &[&instance],
+        ChaCha20Rng::seed_from_u64(0),
+        &mut transcript,
+    )
+    .expect("proof generation should not fail");
+    let proof = transcript.finalize();
+    let proof_time = start.elapsed();
+
+    let start = Instant::now();
+    let mut verifier_transcript = Blake2bRead::<_, G1Affine, Challenge255<_>>::init(&proof[..]);
+    verify_proof::<KZGCommitmentScheme<Bn256>, VerifierSHPLONK<'_, Bn256>, _, _, _>(
+        &verifier_params,
+        pk.get_vk(),
+        SingleStrategy::new(general_params.as_ref()),
+        &[&instance],
+        &mut verifier_transcript,
+    )
+    .expect("failed to verify circuit");
+    let verify_time = start.elapsed();
+
+    log::info!(
+        "{} circuit (k={}): keygen {:?}, proof {:?} ({} bytes), verify {:?}",
+        name,
+        k,
+        keygen_time,
+        proof_time,
+        proof.len(),
+        verify_time
+    );
+}
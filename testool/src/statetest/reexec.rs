@@ -0,0 +1,1466 @@
+//! Lightweight EVM interpreter re-executing the transactions of a test from
+//! its pre state, independently of the geth traces followed by the circuit
+//! input builder, to cross-check the operations of the builder.
+//!
+//! The storage reads and writes, the nonce and balance changes and the
+//! accesses to the access list are recorded.  Contract creations and calls
+//! to precompiles are not supported: the re-execution stops and the
+//! cross-check is skipped.
+
+use crate::utils::MainnetFork;
+use bus_mapping::precompile::is_precompiled;
+use eth_types::{
+    evm_types::{
+        gas_utils::{eip150_gas, memory_expansion_gas_cost},
+        GasCost, OpcodeId, GAS_STIPEND_CALL_WITH_VALUE, MAX_EXPANDED_MEMORY_ADDRESS,
+    },
+    geth_types::{Transaction, TxType},
+    keccak256, Address, ToAddress, ToBigEndian, ToWord, Word, U512,
+};
+use external_tracer::TraceConfig;
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+const MAX_STACK_SIZE: usize = 1024;
+const MAX_CALL_DEPTH: usize = 1024;
+
+/// A storage read or write of the re-execution, with the fields of the
+/// `StorageOp`s of the circuit input builder
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StorageAccess {
+    /// index of the step in the transaction, where 0 is the `BeginTx` step
+    /// and the steps of the callees follow the call step
+    pub step: usize,
+    pub is_write: bool,
+    pub address: Address,
+    pub key: Word,
+    pub value: Word,
+    pub value_prev: Word,
+    /// value of the slot before the transaction
+    pub committed_value: Word,
+}
+
+impl std::fmt::Display for StorageAccess {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "step {} {} {:?}[{:#x}] {:#x}->{:#x} (committed {:#x})",
+            self.step,
+            if self.is_write { "write" } else { "read" },
+            self.address,
+            self.key,
+            self.value_prev,
+            self.value,
+            self.committed_value
+        )
+    }
+}
+
+/// The operations of a transaction of the re-execution.  The code hashes are
+/// not recorded, as only the unsupported creations change them, and neither
+/// is the transient storage, which no [`MainnetFork`] enables.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TxRwSet {
+    /// storage reads and writes in the order they happen, the reversions of
+    /// the failing calls included
+    pub storage: Vec<StorageAccess>,
+    /// nonce before and after the transaction of the accounts it changes
+    pub nonces: BTreeMap<Address, (Word, Word)>,
+    /// balance before and after the transaction of the accounts it changes
+    pub balances: BTreeMap<Address, (Word, Word)>,
+    /// whether the accessed accounts were warm at their first access
+    pub warm_accounts: BTreeMap<Address, bool>,
+    /// whether the accessed slots were warm at their first access
+    pub warm_slots: BTreeMap<(Address, Word), bool>,
+}
+
+/// The re-execution used a feature the interpreter does not implement
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Unsupported(pub String);
+
+/// Re-executes the transactions of `trace_config` one after the other under
+/// the rules of `fork`, returning the operations of each of them.
+pub fn reexecute(
+    trace_config: &TraceConfig,
+    fork: MainnetFork,
+) -> Result<Vec<TxRwSet>, Unsupported> {
+    if fork < MainnetFork::Berlin {
+        return Err(Unsupported(format!("fork {}", fork)));
+    }
+    let mut evm = Evm::new(trace_config, fork);
+    trace_config
+        .transactions
+        .iter()
+        .map(|tx| evm.transact(tx))
+        .collect()
+}
+
+#[derive(Debug, Clone, Default)]
+struct Account {
+    nonce: u64,
+    balance: Word,
+    code: Vec<u8>,
+    storage: HashMap<Word, Word>,
+}
+
+impl Account {
+    fn is_empty(&self) -> bool {
+        self.nonce == 0 && self.balance.is_zero() && self.code.is_empty()
+    }
+}
+
+/// State changes undone when a call fails
+enum Journal {
+    Storage {
+        address: Address,
+        key: Word,
+        prev: Word,
+        step: usize,
+    },
+    Balance {
+        address: Address,
+        prev: Word,
+    },
+    Created(Address),
+    WarmAccount(Address),
+    WarmSlot(Address, Word),
+    Refund(i64),
+    Destructed(Address),
+}
+
+/// Why a frame stopped before its end
+enum Interrupt {
+    /// exceptional halt, consuming all the gas of the frame
+    Error,
+    Unsupported(String),
+}
+
+enum Halt {
+    Stop,
+    Return(Vec<u8>),
+    Revert(Vec<u8>),
+}
+
+struct Outcome {
+    success: bool,
+    gas_left: u64,
+    output: Vec<u8>,
+}
+
+/// A call frame and its machine state
+struct Frame {
+    /// address whose storage and balance are used
+    address: Address,
+    caller: Address,
+    value: Word,
+    code: Vec<u8>,
+    jumpdests: HashSet<usize>,
+    calldata: Vec<u8>,
+    is_static: bool,
+    depth: usize,
+    pc: usize,
+    stack: Vec<Word>,
+    memory: Vec<u8>,
+    gas: u64,
+    return_data: Vec<u8>,
+}
+
+impl Frame {
+    fn pop(&mut self) -> Word {
+        self.stack
+            .pop()
+            .expect("stack size is checked before the step")
+    }
+
+    fn push(&mut self, value: Word) {
+        self.stack.push(value);
+    }
+
+    fn charge(&mut self, gas: u64) -> Result<(), Interrupt> {
+        if gas > self.gas {
+            return Err(Interrupt::Error);
+        }
+        self.gas -= gas;
+        Ok(())
+    }
+
+    /// Cost of the expansion of the memory to cover `size` bytes at `offset`,
+    /// an error if it can not be paid for
+    fn memory_cost(&self, offset: Word, size: Word) -> Result<u64, Interrupt> {
+        if size.is_zero() {
+            return Ok(0);
+        }
+        let end = offset.saturating_add(size);
+        if end > Word::from(MAX_EXPANDED_MEMORY_ADDRESS) {
+            return Err(Interrupt::Error);
+        }
+        let words = (end.as_u64() + 31) / 32;
+        let current = self.memory.len() as u64 / 32;
+        Ok(if words > current {
+            memory_expansion_gas_cost(current, words)
+        } else {
+            0
+        })
+    }
+
+    /// Resizes the memory to cover `size` bytes at `offset`, whose expansion
+    /// has already been paid for, and returns the offset
+    fn expand_memory(&mut self, offset: Word, size: Word) -> usize {
+        if size.is_zero() {
+            return 0;
+        }
+        let end = (offset + size).as_usize();
+        let len = (end + 31) / 32 * 32;
+        if len > self.memory.len() {
+            self.memory.resize(len, 0);
+        }
+        offset.as_usize()
+    }
+
+    fn read_memory(&self, offset: usize, size: usize) -> Vec<u8> {
+        if size == 0 {
+            return Vec::new();
+        }
+        self.memory[offset..offset + size].to_vec()
+    }
+}
+
+/// `size` bytes of `data` from `offset`, padded with zeros
+fn padded_slice(data: &[u8], offset: Word, size: usize) -> Vec<u8> {
+    let mut slice = vec![0; size];
+    if offset < Word::from(data.len()) {
+        let offset = offset.as_usize();
+        let len = size.min(data.len() - offset);
+        slice[..len].copy_from_slice(&data[offset..offset + len]);
+    }
+    slice
+}
+
+fn jumpdests(code: &[u8]) -> HashSet<usize> {
+    let mut jumpdests = HashSet::new();
+    let mut pc = 0;
+    while pc < code.len() {
+        let op = OpcodeId::from(code[pc]);
+        if op == OpcodeId::JUMPDEST {
+            jumpdests.insert(pc);
+        }
+        pc += 1 + op.data_len();
+    }
+    jumpdests
+}
+
+/// Number of the stack items popped and pushed by `op`
+fn stack_io(op: OpcodeId) -> (usize, usize) {
+    use OpcodeId::*;
+    match op {
+        STOP | JUMPDEST | INVALID(_) => (0, 0),
+        ADD | MUL | SUB | DIV | SDIV | MOD | SMOD | EXP | SIGNEXTEND | LT | GT | SLT | SGT | EQ
+        | AND | OR | XOR | BYTE | SHL | SHR | SAR | SHA3 => (2, 1),
+        ADDMOD | MULMOD => (3, 1),
+        ISZERO | NOT | BALANCE | CALLDATALOAD | EXTCODESIZE | EXTCODEHASH | BLOCKHASH | MLOAD
+        | SLOAD => (1, 1),
+        ADDRESS | ORIGIN | CALLER | CALLVALUE | CALLDATASIZE | CODESIZE | GASPRICE
+        | RETURNDATASIZE | COINBASE | TIMESTAMP | NUMBER | DIFFICULTY | GASLIMIT | CHAINID
+        | SELFBALANCE | BASEFEE | PC | MSIZE | GAS => (0, 1),
+        CALLDATACOPY | CODECOPY | RETURNDATACOPY => (3, 0),
+        EXTCODECOPY => (4, 0),
+        POP | JUMP | SELFDESTRUCT => (1, 0),
+        MSTORE | MSTORE8 | SSTORE | JUMPI | RETURN | REVERT => (2, 0),
+        CREATE => (3, 1),
+        CREATE2 => (4, 1),
+        CALL | CALLCODE => (7, 1),
+        DELEGATECALL | STATICCALL => (6, 1),
+        LOG0 | LOG1 | LOG2 | LOG3 | LOG4 => ((op.as_u8() - LOG0.as_u8()) as usize + 2, 0),
+        op if op.is_push() => (0, 1),
+        op if op.as_u8() >= DUP1.as_u8() && op.as_u8() <= DUP16.as_u8() => {
+            let n = (op.as_u8() - DUP1.as_u8()) as usize + 1;
+            (n, n + 1)
+        }
+        op => {
+            let n = (op.as_u8() - SWAP1.as_u8()) as usize + 1;
+            (n + 1, n + 1)
+        }
+    }
+}
+
+fn is_negative(value: Word) -> bool {
+    value.bit(255)
+}
+
+fn negate(value: Word) -> Word {
+    (!value).overflowing_add(Word::one()).0
+}
+
+fn abs(value: Word) -> Word {
+    if is_negative(value) {
+        negate(value)
+    } else {
+        value
+    }
+}
+
+fn bool_word(value: bool) -> Word {
+    if value {
+        Word::one()
+    } else {
+        Word::zero()
+    }
+}
+
+/// Reduces `value` modulo `modulus`, which is not zero
+fn reduce(value: U512, modulus: Word) -> Word {
+    Word::try_from(value % U512::from(modulus)).expect("reduced value fits in a word")
+}
+
+struct Evm<'a> {
+    config: &'a TraceConfig,
+    fork: MainnetFork,
+    accounts: HashMap<Address, Account>,
+    // transaction context
+    origin: Address,
+    gas_price: Word,
+    committed: HashMap<(Address, Word), Word>,
+    warm_accounts: HashSet<Address>,
+    warm_slots: HashSet<(Address, Word)>,
+    refund: i64,
+    destructed: HashSet<Address>,
+    journal: Vec<Journal>,
+    rws: TxRwSet,
+    step: usize,
+}
+
+impl<'a> Evm<'a> {
+    fn new(config: &'a TraceConfig, fork: MainnetFork) -> Self {
+        let accounts = config
+            .accounts
+            .iter()
+            .map(|(address, account)| {
+                (
+                    *address,
+                    Account {
+                        nonce: account.nonce.as_u64(),
+                        balance: account.balance,
+                        code: account.code.to_vec(),
+                        storage: account.storage.clone(),
+                    },
+                )
+            })
+            .collect();
+        Self {
+            config,
+            fork,
+            accounts,
+            origin: Address::zero(),
+            gas_price: Word::zero(),
+            committed: HashMap::new(),
+            warm_accounts: HashSet::new(),
+            warm_slots: HashSet::new(),
+            refund: 0,
+            destructed: HashSet::new(),
+            journal: Vec::new(),
+            rws: TxRwSet::default(),
+            step: 0,
+        }
+    }
+
+    fn account(&self, address: &Address) -> Option<&Account> {
+        self.accounts.get(address)
+    }
+
+    fn is_empty(&self, address: &Address) -> bool {
+        self.account(address).map_or(true, Account::is_empty)
+    }
+
+    fn balance(&self, address: &Address) -> Word {
+        self.account(address)
+            .map_or_else(Word::zero, |account| account.balance)
+    }
+
+    fn code(&self, address: &Address) -> Vec<u8> {
+        self.account(address)
+            .map(|account| account.code.clone())
+            .unwrap_or_default()
+    }
+
+    fn storage(&self, address: &Address, key: &Word) -> Word {
+        self.account(address)
+            .and_then(|account| account.storage.get(key).copied())
+            .unwrap_or_default()
+    }
+
+    fn committed_storage(&self, address: &Address, key: &Word) -> Word {
+        self.committed
+            .get(&(*address, *key))
+            .copied()
+            .unwrap_or_else(|| self.storage(address, key))
+    }
+
+    fn account_mut(&mut self, address: Address) -> &mut Account {
+        if !self.accounts.contains_key(&address) {
+            self.journal.push(Journal::Created(address));
+        }
+        self.accounts.entry(address).or_default()
+    }
+
+    fn set_balance(&mut self, address: Address, balance: Word) {
+        let account = self.account_mut(address);
+        let prev = std::mem::replace(&mut account.balance, balance);
+        self.journal.push(Journal::Balance { address, prev });
+    }
+
+    fn transfer(&mut self, from: Address, to: Address, value: Word) {
+        self.set_balance(from, self.balance(&from) - value);
+        self.set_balance(to, self.balance(&to) + value);
+    }
+
+    fn set_storage(&mut self, address: Address, key: Word, value: Word) {
+        let committed = self.committed_storage(&address, &key);
+        self.committed.entry((address, key)).or_insert(committed);
+        let prev = self
+            .account_mut(address)
+            .storage
+            .insert(key, value)
+            .unwrap_or_default();
+        self.journal.push(Journal::Storage {
+            address,
+            key,
+            prev,
+            step: self.step,
+        });
+        self.rws.storage.push(StorageAccess {
+            step: self.step,
+            is_write: true,
+            address,
+            key,
+            value,
+            value_prev: prev,
+            committed_value: committed,
+        });
+    }
+
+    /// Marks the account as warm, returning true if it was cold
+    fn warm_account(&mut self, address: Address) -> bool {
+        let cold = self.warm_accounts.insert(address);
+        if cold {
+            self.journal.push(Journal::WarmAccount(address));
+        }
+        self.rws.warm_accounts.entry(address).or_insert(!cold);
+        cold
+    }
+
+    /// Marks the slot as warm, returning true if it was cold
+    fn warm_slot(&mut self, address: Address, key: Word) -> bool {
+        let cold = self.warm_slots.insert((address, key));
+        if cold {
+            self.journal.push(Journal::WarmSlot(address, key));
+        }
+        self.rws.warm_slots.entry((address, key)).or_insert(!cold);
+        cold
+    }
+
+    fn add_refund(&mut self, refund: i64) {
+        self.journal.push(Journal::Refund(self.refund));
+        self.refund += refund;
+    }
+
+    /// Extra cost of the access to an account, on top of the warm access
+    fn account_access_cost(&mut self, address: Address) -> u64 {
+        if self.warm_account(address) {
+            GasCost::COLD_ACCOUNT_ACCESS - GasCost::WARM_ACCESS
+        } else {
+            0
+        }
+    }
+
+    /// Undoes the changes done since `checkpoint`, recording the storage
+    /// writes restoring the reverted slots
+    fn revert(&mut self, checkpoint: usize) {
+        while self.journal.len() > checkpoint {
+            match self
+                .journal
+                .pop()
+                .expect("journal is longer than checkpoint")
+            {
+                Journal::Storage {
+                    address,
+                    key,
+                    prev,
+                    step,
+                } => {
+                    let committed = self.committed_storage(&address, &key);
+                    let account = self.accounts.get_mut(&address).expect("written account");
+                    let value = account.storage.insert(key, prev).unwrap_or_default();
+                    self.rws.storage.push(StorageAccess {
+                        step,
+                        is_write: true,
+                        address,
+                        key,
+                        value: prev,
+                        value_prev: value,
+                        committed_value: committed,
+                    });
+                }
+                Journal::Balance { address, prev } => {
+                    self.accounts
+                        .get_mut(&address)
+                        .expect("account with balance")
+                        .balance = prev;
+                }
+                Journal::Created(address) => {
+                    self.accounts.remove(&address);
+                }
+                Journal::WarmAccount(address) => {
+                    self.warm_accounts.remove(&address);
+                }
+                Journal::WarmSlot(address, key) => {
+                    self.warm_slots.remove(&(address, key));
+                }
+                Journal::Refund(prev) => self.refund = prev,
+                Journal::Destructed(address) => {
+                    self.destructed.remove(&address);
+                }
+            }
+        }
+    }
+
+    fn intrinsic_gas(tx: &Transaction) -> u64 {
+        let data_gas: u64 = tx
+            .call_data
+            .iter()
+            .map(|byte| if *byte == 0 { 4 } else { 16 })
+            .sum();
+        let access_list_gas: u64 = tx.access_list.as_ref().map_or(0, |access_list| {
+            access_list
+                .0
+                .iter()
+                .map(|item| 2400 + 1900 * item.storage_keys.len() as u64)
+                .sum()
+        });
+        GasCost::TX + data_gas + access_list_gas
+    }
+
+    /// Executes `tx` and returns its operations
+    fn transact(&mut self, tx: &Transaction) -> Result<TxRwSet, Unsupported> {
+        let to = match tx.to {
+            Some(to) => to,
+            None => return Err(Unsupported("contract creation".into())),
+        };
+        if is_precompiled(&to) {
+            return Err(Unsupported(format!("call to precompile {:?}", to)));
+        }
+        let base_fee = self.config.block_constants.base_fee;
+        let gas_price = match tx.tx_type {
+            TxType::Eip1559 => tx.gas_fee_cap.min(base_fee + tx.gas_tip_cap),
+            _ => tx.gas_price,
+        };
+        let gas_limit = tx.gas_limit.as_u64();
+        let intrinsic_gas = Self::intrinsic_gas(tx);
+        let max_fee = Word::from(gas_limit) * gas_price;
+        if gas_limit < intrinsic_gas || self.balance(&tx.from) < max_fee + tx.value {
+            return Err(Unsupported("invalid transaction".into()));
+        }
+
+        self.origin = tx.from;
+        self.gas_price = gas_price;
+        self.step = 0;
+        self.rws = TxRwSet::default();
+        let accounts_prev: HashMap<_, _> = self
+            .accounts
+            .iter()
+            .map(|(address, account)| (*address, (account.nonce, account.balance)))
+            .collect();
+
+        let sender = self.account_mut(tx.from);
+        sender.nonce += 1;
+        sender.balance -= max_fee;
+
+        // the sender, the callee and the coinbase are accessed by the
+        // transaction, the precompiles and the access list are only warm
+        let mut warm_addresses = vec![tx.from, to];
+        if self.fork >= MainnetFork::Shanghai {
+            warm_addresses.push(self.config.block_constants.coinbase);
+        }
+        for address in warm_addresses {
+            self.warm_account(address);
+        }
+        self.warm_accounts
+            .extend((1..=9u64).map(Address::from_low_u64_be));
+        if let Some(access_list) = &tx.access_list {
+            for item in &access_list.0 {
+                self.warm_accounts.insert(item.address);
+                for key in &item.storage_keys {
+                    self.warm_slots
+                        .insert((item.address, Word::from(key.as_bytes())));
+                }
+            }
+        }
+
+        let outcome = self.call(
+            tx.from,
+            to,
+            to,
+            true,
+            tx.value,
+            tx.call_data.to_vec(),
+            gas_limit - intrinsic_gas,
+            false,
+            0,
+        )?;
+
+        let gas_used = gas_limit - outcome.gas_left;
+        let max_refund = gas_used
+            / if self.fork >= MainnetFork::London {
+                5
+            } else {
+                2
+            };
+        let refund = (self.refund.max(0) as u64).min(max_refund);
+        let sender_balance = self.balance(&tx.from);
+        self.account_mut(tx.from).balance =
+            sender_balance + Word::from(outcome.gas_left + refund) * gas_price;
+        let coinbase = self.config.block_constants.coinbase;
+        let coinbase_price = if self.fork >= MainnetFork::London {
+            gas_price - base_fee
+        } else {
+            gas_price
+        };
+        let coinbase_balance = self.balance(&coinbase);
+        self.account_mut(coinbase).balance =
+            coinbase_balance + Word::from(gas_used - refund) * coinbase_price;
+
+        for address in std::mem::take(&mut self.destructed) {
+            self.accounts.remove(&address);
+        }
+        self.committed.clear();
+        self.warm_accounts.clear();
+        self.warm_slots.clear();
+        self.refund = 0;
+        self.journal.clear();
+
+        let addresses: HashSet<_> = accounts_prev
+            .keys()
+            .chain(self.accounts.keys())
+            .copied()
+            .collect();
+        for address in addresses {
+            let (nonce_prev, balance_prev) =
+                accounts_prev.get(&address).copied().unwrap_or_default();
+            let (nonce, balance) = self
+                .account(&address)
+                .map_or_else(Default::default, |account| (account.nonce, account.balance));
+            if nonce != nonce_prev {
+                self.rws
+                    .nonces
+                    .insert(address, (nonce_prev.into(), nonce.into()));
+            }
+            if balance != balance_prev {
+                self.rws.balances.insert(address, (balance_prev, balance));
+            }
+        }
+        Ok(std::mem::take(&mut self.rws))
+    }
+
+    /// Runs the code of `code_address` in the context of `address`,
+    /// transferring `value` from the caller to `address` if `transfer` is
+    /// set, and reverts the state changes if it fails
+    #[allow(clippy::too_many_arguments)]
+    fn call(
+        &mut self,
+        caller: Address,
+        address: Address,
+        code_address: Address,
+        transfer: bool,
+        value: Word,
+        calldata: Vec<u8>,
+        gas: u64,
+        is_static: bool,
+        depth: usize,
+    ) -> Result<Outcome, Unsupported> {
+        let checkpoint = self.journal.len();
+        if transfer && !value.is_zero() {
+            self.transfer(caller, address, value);
+        }
+
+        let code = self.code(&code_address);
+        let mut frame = Frame {
+            address,
+            caller,
+            value,
+            jumpdests: jumpdests(&code),
+            code,
+            calldata,
+            is_static,
+            depth,
+            pc: 0,
+            stack: Vec::new(),
+            memory: Vec::new(),
+            gas,
+            return_data: Vec::new(),
+        };
+
+        let outcome = loop {
+            if frame.pc >= frame.code.len() {
+                break Outcome {
+                    success: true,
+                    gas_left: frame.gas,
+                    output: Vec::new(),
+                };
+            }
+            self.step += 1;
+            match self.execute_instruction(&mut frame) {
+                Ok(None) => {}
+                Ok(Some(Halt::Stop)) => {
+                    break Outcome {
+                        success: true,
+                        gas_left: frame.gas,
+                        output: Vec::new(),
+                    }
+                }
+                Ok(Some(Halt::Return(output))) => {
+                    break Outcome {
+                        success: true,
+                        gas_left: frame.gas,
+                        output,
+                    }
+                }
+                Ok(Some(Halt::Revert(output))) => {
+                    break Outcome {
+                        success: false,
+                        gas_left: frame.gas,
+                        output,
+                    }
+                }
+                Err(Interrupt::Error) => {
+                    break Outcome {
+                        success: false,
+                        gas_left: 0,
+                        output: Vec::new(),
+                    }
+                }
+                Err(Interrupt::Unsupported(reason)) => return Err(Unsupported(reason)),
+            }
+        };
+
+        if !outcome.success {
+            self.revert(checkpoint);
+        }
+        Ok(outcome)
+    }
+
+    /// Executes the instruction at the pc of `frame`, returning how the frame
+    /// ends if it does
+    fn execute_instruction(&mut self, frame: &mut Frame) -> Result<Option<Halt>, Interrupt> {
+        use OpcodeId::*;
+
+        let byte = frame.code[frame.pc];
+        let op = OpcodeId::from(byte);
+        let available = match op {
+            BASEFEE => self.fork >= MainnetFork::London,
+            INVALID(0x5f) if self.fork >= MainnetFork::Shanghai => {
+                return Err(Interrupt::Unsupported("PUSH0".into()))
+            }
+            INVALID(_) => false,
+            _ => true,
+        };
+        if !available {
+            return Err(Interrupt::Error);
+        }
+        let (pops, pushes) = stack_io(op);
+        if frame.stack.len() < pops || frame.stack.len() - pops + pushes > MAX_STACK_SIZE {
+            return Err(Interrupt::Error);
+        }
+        let writes_state = matches!(
+            op,
+            SSTORE | LOG0 | LOG1 | LOG2 | LOG3 | LOG4 | CREATE | CREATE2 | SELFDESTRUCT
+        ) || (op == CALL && frame.stack[frame.stack.len() - 3] != Word::zero());
+        if frame.is_static && writes_state {
+            return Err(Interrupt::Error);
+        }
+        frame.charge(op.constant_gas_cost())?;
+        frame.pc += 1;
+
+        match op {
+            STOP => return Ok(Some(Halt::Stop)),
+            ADD => {
+                let (a, b) = (frame.pop(), frame.pop());
+                frame.push(a.overflowing_add(b).0);
+            }
+            MUL => {
+                let (a, b) = (frame.pop(), frame.pop());
+                frame.push(a.overflowing_mul(b).0);
+            }
+            SUB => {
+                let (a, b) = (frame.pop(), frame.pop());
+                frame.push(a.overflowing_sub(b).0);
+            }
+            DIV => {
+                let (a, b) = (frame.pop(), frame.pop());
+                frame.push(if b.is_zero() { b } else { a / b });
+            }
+            SDIV => {
+                let (a, b) = (frame.pop(), frame.pop());
+                let quotient = if b.is_zero() { b } else { abs(a) / abs(b) };
+                frame.push(if is_negative(a) != is_negative(b) {
+                    negate(quotient)
+                } else {
+                    quotient
+                });
+            }
+            MOD => {
+                let (a, b) = (frame.pop(), frame.pop());
+                frame.push(if b.is_zero() { b } else { a % b });
+            }
+            SMOD => {
+                let (a, b) = (frame.pop(), frame.pop());
+                let remainder = if b.is_zero() { b } else { abs(a) % abs(b) };
+                frame.push(if is_negative(a) {
+                    negate(remainder)
+                } else {
+                    remainder
+                });
+            }
+            ADDMOD => {
+                let (a, b, n) = (frame.pop(), frame.pop(), frame.pop());
+                frame.push(if n.is_zero() {
+                    n
+                } else {
+                    reduce(U512::from(a) + U512::from(b), n)
+                });
+            }
+            MULMOD => {
+                let (a, b, n) = (frame.pop(), frame.pop(), frame.pop());
+                frame.push(if n.is_zero() {
+                    n
+                } else {
+                    reduce(a.full_mul(b), n)
+                });
+            }
+            EXP => {
+                let (base, exponent) = (frame.pop(), frame.pop());
+                let exponent_bytes = (exponent.bits() as u64 + 7) / 8;
+                frame.charge(GasCost::EXP_BYTE_TIMES * exponent_bytes)?;
+                frame.push(base.overflowing_pow(exponent).0);
+            }
+            SIGNEXTEND => {
+                let (size, value) = (frame.pop(), frame.pop());
+                frame.push(if size < Word::from(31) {
+                    let bit = size.as_usize() * 8 + 7;
+                    let mask = (Word::one() << (bit + 1)) - Word::one();
+                    if value.bit(bit) {
+                        value | !mask
+                    } else {
+                        value & mask
+                    }
+                } else {
+                    value
+                });
+            }
+            LT | GT | SLT | SGT | EQ => {
+                let (a, b) = (frame.pop(), frame.pop());
+                let sign = Word::one() << 255;
+                frame.push(bool_word(match op {
+                    LT => a < b,
+                    GT => a > b,
+                    SLT => (a ^ sign) < (b ^ sign),
+                    SGT => (a ^ sign) > (b ^ sign),
+                    _ => a == b,
+                }));
+            }
+            ISZERO => {
+                let a = frame.pop();
+                frame.push(bool_word(a.is_zero()));
+            }
+            AND => {
+                let (a, b) = (frame.pop(), frame.pop());
+                frame.push(a & b);
+            }
+            OR => {
+                let (a, b) = (frame.pop(), frame.pop());
+                frame.push(a | b);
+            }
+            XOR => {
+                let (a, b) = (frame.pop(), frame.pop());
+                frame.push(a ^ b);
+            }
+            NOT => {
+                let a = frame.pop();
+                frame.push(!a);
+            }
+            BYTE => {
+                let (index, value) = (frame.pop(), frame.pop());
+                frame.push(if index < Word::from(32) {
+                    Word::from(value.to_be_bytes()[index.as_usize()])
+                } else {
+                    Word::zero()
+                });
+            }
+            SHL | SHR | SAR => {
+                let (shift, value) = (frame.pop(), frame.pop());
+                let negative = op == SAR && is_negative(value);
+                frame.push(if shift >= Word::from(256) {
+                    if negative {
+                        Word::MAX
+                    } else {
+                        Word::zero()
+                    }
+                } else {
+                    let shift = shift.as_usize();
+                    match op {
+                        SHL => value << shift,
+                        _ if negative => !(!value >> shift),
+                        _ => value >> shift,
+                    }
+                });
+            }
+            SHA3 => {
+                let (offset, size) = (frame.pop(), frame.pop());
+                let words = (size.saturating_add(Word::from(31)) / 32).min(Word::from(u32::MAX));
+                frame.charge(GasCost::COPY_SHA3 * words.as_u64())?;
+                frame.charge(frame.memory_cost(offset, size)?)?;
+                let offset = frame.expand_memory(offset, size);
+                let data = frame.read_memory(offset, size.low_u64() as usize);
+                frame.push(Word::from(keccak256(&data)));
+            }
+            ADDRESS => frame.push(frame.address.to_word()),
+            BALANCE => {
+                let address = frame.pop().to_address();
+                frame.charge(self.account_access_cost(address))?;
+                frame.push(self.balance(&address));
+            }
+            ORIGIN => frame.push(self.origin.to_word()),
+            CALLER => frame.push(frame.caller.to_word()),
+            CALLVALUE => frame.push(frame.value),
+            CALLDATALOAD => {
+                let offset = frame.pop();
+                frame.push(Word::from(
+                    padded_slice(&frame.calldata, offset, 32).as_slice(),
+                ));
+            }
+            CALLDATASIZE => frame.push(Word::from(frame.calldata.len())),
+            CALLDATACOPY | CODECOPY | RETURNDATACOPY => {
+                let (memory_offset, data_offset, size) = (frame.pop(), frame.pop(), frame.pop());
+                if op == RETURNDATACOPY
+                    && data_offset.saturating_add(size) > Word::from(frame.return_data.len())
+                {
+                    return Err(Interrupt::Error);
+                }
+                self.copy_to_memory(frame, op, memory_offset, data_offset, size, None)?;
+            }
+            CODESIZE => frame.push(Word::from(frame.code.len())),
+            GASPRICE => frame.push(self.gas_price),
+            EXTCODESIZE => {
+                let address = frame.pop().to_address();
+                frame.charge(self.account_access_cost(address))?;
+                frame.push(Word::from(self.code(&address).len()));
+            }
+            EXTCODECOPY => {
+                let address = frame.pop().to_address();
+                let (memory_offset, data_offset, size) = (frame.pop(), frame.pop(), frame.pop());
+                frame.charge(self.account_access_cost(address))?;
+                let code = self.code(&address);
+                self.copy_to_memory(frame, op, memory_offset, data_offset, size, Some(code))?;
+            }
+            RETURNDATASIZE => frame.push(Word::from(frame.return_data.len())),
+            EXTCODEHASH => {
+                let address = frame.pop().to_address();
+                frame.charge(self.account_access_cost(address))?;
+                frame.push(if self.is_empty(&address) {
+                    Word::zero()
+                } else {
+                    Word::from(keccak256(&self.code(&address)))
+                });
+            }
+            BLOCKHASH => {
+                let number = frame.pop();
+                let current = Word::from(self.config.block_constants.number.as_u64());
+                let history = &self.config.history_hashes;
+                frame.push(
+                    if number < current && current - number <= Word::from(history.len()) {
+                        history[history.len() - (current - number).as_usize()]
+                    } else {
+                        Word::zero()
+                    },
+                );
+            }
+            COINBASE => frame.push(self.config.block_constants.coinbase.to_word()),
+            TIMESTAMP => frame.push(self.config.block_constants.timestamp),
+            NUMBER => frame.push(Word::from(self.config.block_constants.number.as_u64())),
+            DIFFICULTY => frame.push(self.config.block_constants.difficulty),
+            GASLIMIT => frame.push(self.config.block_constants.gas_limit),
+            CHAINID => frame.push(self.config.chain_id),
+            SELFBALANCE => frame.push(self.balance(&frame.address)),
+            BASEFEE => frame.push(self.config.block_constants.base_fee),
+            POP => {
+                frame.pop();
+            }
+            MLOAD => {
+                let offset = frame.pop();
+                frame.charge(frame.memory_cost(offset, Word::from(32))?)?;
+                let offset = frame.expand_memory(offset, Word::from(32));
+                frame.push(Word::from(&frame.memory[offset..offset + 32]));
+            }
+            MSTORE | MSTORE8 => {
+                let (offset, value) = (frame.pop(), frame.pop());
+                let size = if op == MSTORE { 32 } else { 1 };
+                frame.charge(frame.memory_cost(offset, Word::from(size))?)?;
+                let offset = frame.expand_memory(offset, Word::from(size));
+                frame.memory[offset..offset + size]
+                    .copy_from_slice(&value.to_be_bytes()[32 - size..]);
+            }
+            SLOAD => {
+                let key = frame.pop();
+                if self.warm_slot(frame.address, key) {
+                    frame.charge(GasCost::COLD_SLOAD)?;
+                } else {
+                    frame.charge(GasCost::WARM_ACCESS)?;
+                }
+                let value = self.storage(&frame.address, &key);
+                self.rws.storage.push(StorageAccess {
+                    step: self.step,
+                    is_write: false,
+                    address: frame.address,
+                    key,
+                    value,
+                    value_prev: value,
+                    committed_value: self.committed_storage(&frame.address, &key),
+                });
+                frame.push(value);
+            }
+            SSTORE => {
+                let (key, value) = (frame.pop(), frame.pop());
+                self.sstore(frame, key, value)?;
+            }
+            JUMP | JUMPI => {
+                let destination = frame.pop();
+                let condition = if op == JUMPI {
+                    frame.pop()
+                } else {
+                    Word::one()
+                };
+                if !condition.is_zero() {
+                    if destination > Word::from(frame.code.len())
+                        || !frame.jumpdests.contains(&destination.as_usize())
+                    {
+                        return Err(Interrupt::Error);
+                    }
+                    frame.pc = destination.as_usize();
+                }
+            }
+            PC => frame.push(Word::from(frame.pc - 1)),
+            MSIZE => frame.push(Word::from(frame.memory.len())),
+            GAS => frame.push(Word::from(frame.gas)),
+            JUMPDEST => {}
+            LOG0 | LOG1 | LOG2 | LOG3 | LOG4 => {
+                let (offset, size) = (frame.pop(), frame.pop());
+                let topics = (op.as_u8() - LOG0.as_u8()) as u64;
+                for _ in 0..topics {
+                    frame.pop();
+                }
+                if size > Word::from(u32::MAX) {
+                    return Err(Interrupt::Error);
+                }
+                frame.charge(GasCost::LOG * (1 + topics) + 8 * size.as_u64())?;
+                frame.charge(frame.memory_cost(offset, size)?)?;
+                frame.expand_memory(offset, size);
+            }
+            CALL | CALLCODE | DELEGATECALL | STATICCALL => {
+                self.call_op(frame, op)?;
+            }
+            RETURN | REVERT => {
+                let (offset, size) = (frame.pop(), frame.pop());
+                frame.charge(frame.memory_cost(offset, size)?)?;
+                let offset = frame.expand_memory(offset, size);
+                let output = frame.read_memory(offset, size.low_u64() as usize);
+                return Ok(Some(if op == RETURN {
+                    Halt::Return(output)
+                } else {
+                    Halt::Revert(output)
+                }));
+            }
+            SELFDESTRUCT => {
+                let beneficiary = frame.pop().to_address();
+                let balance = self.balance(&frame.address);
+                let mut cost = 0;
+                if self.warm_account(beneficiary) {
+                    cost += GasCost::COLD_ACCOUNT_ACCESS;
+                }
+                if !balance.is_zero() && self.is_empty(&beneficiary) {
+                    cost += GasCost::NEW_ACCOUNT;
+                }
+                frame.charge(cost)?;
+                if self.fork < MainnetFork::London && !self.destructed.contains(&frame.address) {
+                    self.add_refund(24000);
+                }
+                self.transfer(frame.address, beneficiary, balance);
+                self.set_balance(frame.address, Word::zero());
+                if self.destructed.insert(frame.address) {
+                    self.journal.push(Journal::Destructed(frame.address));
+                }
+                return Ok(Some(Halt::Stop));
+            }
+            CREATE | CREATE2 => return Err(Interrupt::Unsupported(format!("{:?}", op))),
+            op if op.is_push() => {
+                let size = op.data_len();
+                let mut bytes = [0u8; 32];
+                let end = (frame.pc + size).min(frame.code.len());
+                bytes[32 - size..32 - size + end - frame.pc]
+                    .copy_from_slice(&frame.code[frame.pc..end]);
+                frame.pc += size;
+                frame.push(Word::from(bytes));
+            }
+            op if op.as_u8() >= DUP1.as_u8() && op.as_u8() <= DUP16.as_u8() => {
+                let n = (op.as_u8() - DUP1.as_u8()) as usize + 1;
+                frame.push(frame.stack[frame.stack.len() - n]);
+            }
+            op => {
+                let n = (op.as_u8() - SWAP1.as_u8()) as usize + 1;
+                let top = frame.stack.len() - 1;
+                frame.stack.swap(top, top - n);
+            }
+        }
+        Ok(None)
+    }
+
+    /// Copies `size` bytes from `data_offset` of the calldata, the code or
+    /// the return data of the frame, or of `data` if set, into the memory
+    fn copy_to_memory(
+        &mut self,
+        frame: &mut Frame,
+        op: OpcodeId,
+        memory_offset: Word,
+        data_offset: Word,
+        size: Word,
+        data: Option<Vec<u8>>,
+    ) -> Result<(), Interrupt> {
+        if size > Word::from(u32::MAX) {
+            return Err(Interrupt::Error);
+        }
+        let size = size.as_usize();
+        frame.charge(GasCost::COPY * ((size as u64 + 31) / 32))?;
+        frame.charge(frame.memory_cost(memory_offset, Word::from(size))?)?;
+        let memory_offset = frame.expand_memory(memory_offset, Word::from(size));
+        let data = match (op, data) {
+            (_, Some(data)) => data,
+            (OpcodeId::CALLDATACOPY, None) => frame.calldata.clone(),
+            (OpcodeId::CODECOPY, None) => frame.code.clone(),
+            (_, None) => frame.return_data.clone(),
+        };
+        frame.memory[memory_offset..memory_offset + size].copy_from_slice(&padded_slice(
+            &data,
+            data_offset,
+            size,
+        ));
+        Ok(())
+    }
+
+    /// SSTORE under EIP-2200 and EIP-2929, with the refunds of EIP-3529 from
+    /// London on
+    fn sstore(&mut self, frame: &mut Frame, key: Word, value: Word) -> Result<(), Interrupt> {
+        if frame.gas <= GasCost::SSTORE_SENTRY {
+            return Err(Interrupt::Error);
+        }
+        let current = self.storage(&frame.address, &key);
+        let original = self.committed_storage(&frame.address, &key);
+        let mut cost = if self.warm_slot(frame.address, key) {
+            GasCost::COLD_SLOAD
+        } else {
+            0
+        };
+        cost += if current == value {
+            GasCost::WARM_ACCESS
+        } else if original == current {
+            if original.is_zero() {
+                GasCost::SSTORE_SET
+            } else {
+                GasCost::SSTORE_RESET
+            }
+        } else {
+            GasCost::WARM_ACCESS
+        };
+        frame.charge(cost)?;
+
+        let clears_schedule = if self.fork >= MainnetFork::London {
+            GasCost::SSTORE_CLEARS_SCHEDULE
+        } else {
+            15000
+        } as i64;
+        if current != value {
+            if original == current {
+                if !original.is_zero() && value.is_zero() {
+                    self.add_refund(clears_schedule);
+                }
+            } else {
+                if !original.is_zero() {
+                    if current.is_zero() {
+                        self.add_refund(-clears_schedule);
+                    } else if value.is_zero() {
+                        self.add_refund(clears_schedule);
+                    }
+                }
+                if original == value {
+                    if original.is_zero() {
+                        self.add_refund((GasCost::SSTORE_SET - GasCost::WARM_ACCESS) as i64);
+                    } else {
+                        self.add_refund((GasCost::SSTORE_RESET - GasCost::WARM_ACCESS) as i64);
+                    }
+                }
+            }
+        }
+        self.set_storage(frame.address, key, value);
+        Ok(())
+    }
+
+    fn call_op(&mut self, frame: &mut Frame, op: OpcodeId) -> Result<(), Interrupt> {
+        let gas = frame.pop();
+        let address = frame.pop().to_address();
+        let value = if matches!(op, OpcodeId::CALL | OpcodeId::CALLCODE) {
+            frame.pop()
+        } else {
+            Word::zero()
+        };
+        let (input_offset, input_size) = (frame.pop(), frame.pop());
+        let (output_offset, output_size) = (frame.pop(), frame.pop());
+
+        // the callee is warmed before the memory expansion is checked
+        let mut cost = self.account_access_cost(address);
+        cost += frame
+            .memory_cost(input_offset, input_size)?
+            .max(frame.memory_cost(output_offset, output_size)?);
+        if !value.is_zero() {
+            cost += GasCost::CALL_WITH_VALUE;
+            if op == OpcodeId::CALL && self.is_empty(&address) {
+                cost += GasCost::NEW_ACCOUNT;
+            }
+        }
+        frame.charge(cost)?;
+        let mut callee_gas = eip150_gas(frame.gas, gas);
+        frame.charge(callee_gas)?;
+        if !value.is_zero() {
+            callee_gas += GAS_STIPEND_CALL_WITH_VALUE;
+        }
+        let input_offset = frame.expand_memory(input_offset, input_size);
+        let output_offset = frame.expand_memory(output_offset, output_size);
+
+        if is_precompiled(&address) {
+            return Err(Interrupt::Unsupported(format!(
+                "call to precompile {:?}",
+                address
+            )));
+        }
+        frame.return_data.clear();
+        if frame.depth + 1 > MAX_CALL_DEPTH
+            || (!value.is_zero() && self.balance(&frame.address) < value)
+        {
+            frame.gas += callee_gas;
+            frame.push(Word::zero());
+            return Ok(());
+        }
+
+        let input = frame.read_memory(input_offset, input_size.low_u64() as usize);
+        let (caller, context, transfer, call_value, is_static) = match op {
+            OpcodeId::CALL => (frame.address, address, true, value, frame.is_static),
+            OpcodeId::CALLCODE => (frame.address, frame.address, false, value, frame.is_static),
+            OpcodeId::DELEGATECALL => (
+                frame.caller,
+                frame.address,
+                false,
+                frame.value,
+                frame.is_static,
+            ),
+            _ => (frame.address, address, false, Word::zero(), true),
+        };
+        let outcome = self
+            .call(
+                caller,
+                context,
+                address,
+                transfer,
+                call_value,
+                input,
+                callee_gas,
+                is_static,
+                frame.depth + 1,
+            )
+            .map_err(|Unsupported(reason)| Interrupt::Unsupported(reason))?;
+
+        frame.gas += outcome.gas_left;
+        let size = (output_size.low_u64() as usize).min(outcome.output.len());
+        frame.memory[output_offset..output_offset + size].copy_from_slice(&outcome.output[..size]);
+        frame.return_data = outcome.output;
+        frame.push(bool_word(outcome.success));
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod reexec_tests {
+    use super::*;
+    use eth_types::{bytecode, geth_types::Account as GethAccount, U64};
+
+    const CONTRACT: Address = Address::repeat_byte(0xcc);
+    const CALLEE: Address = Address::repeat_byte(0xdd);
+    const SENDER: Address = Address::repeat_byte(0xaa);
+
+    fn trace_config(code: Vec<u8>, callee_code: Vec<u8>) -> TraceConfig {
+        let accounts = [
+            (SENDER, Vec::new(), Word::from(10).pow(18.into())),
+            (CONTRACT, code, Word::zero()),
+            (CALLEE, callee_code, Word::zero()),
+        ];
+        TraceConfig {
+            chain_id: Word::one(),
+            accounts: accounts
+                .into_iter()
+                .map(|(address, code, balance)| {
+                    (
+                        address,
+                        GethAccount {
+                            address,
+                            balance,
+                            code: code.into(),
+                            ..Default::default()
+                        },
+                    )
+                })
+                .collect(),
+            transactions: vec![Transaction {
+                from: SENDER,
+                to: Some(CONTRACT),
+                gas_limit: U64::from(1_000_000),
+                gas_price: Word::one(),
+                ..Default::default()
+            }],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn storage_accesses() {
+        let code = bytecode! {
+            PUSH1(1)
+            PUSH1(0)
+            SSTORE
+            PUSH1(0)
+            SLOAD
+            STOP
+        };
+        let rws = reexecute(&trace_config(code.code(), Vec::new()), MainnetFork::Merge).unwrap();
+        assert_eq!(
+            rws[0].storage,
+            vec![
+                StorageAccess {
+                    step: 3,
+                    is_write: true,
+                    address: CONTRACT,
+                    key: Word::zero(),
+                    value: Word::one(),
+                    value_prev: Word::zero(),
+                    committed_value: Word::zero(),
+                },
+                StorageAccess {
+                    step: 5,
+                    is_write: false,
+                    address: CONTRACT,
+                    key: Word::zero(),
+                    value: Word::one(),
+                    value_prev: Word::one(),
+                    committed_value: Word::zero(),
+                },
+            ]
+        );
+        assert_eq!(
+            rws[0].warm_slots,
+            BTreeMap::from([((CONTRACT, Word::zero()), false)])
+        );
+    }
+
+    #[test]
+    fn reverted_call_restores_storage() {
+        // the callee writes its slot 0 twice and reverts
+        let callee_code = bytecode! {
+            PUSH1(1)
+            PUSH1(0)
+            SSTORE
+            PUSH1(2)
+            PUSH1(0)
+            SSTORE
+            PUSH1(0)
+            PUSH1(0)
+            REVERT
+        };
+        let code = bytecode! {
+            PUSH1(0)
+            PUSH1(0)
+            PUSH1(0)
+            PUSH1(0)
+            PUSH1(0)
+            PUSH20(CALLEE.to_word())
+            PUSH3(100_000)
+            CALL
+            STOP
+        };
+        let accesses = reexecute(
+            &trace_config(code.code(), callee_code.code()),
+            MainnetFork::Merge,
+        )
+        .unwrap()
+        .remove(0)
+        .storage;
+        let writes: Vec<_> = accesses
+            .iter()
+            .map(|access| (access.step, access.value_prev, access.value))
+            .collect();
+        // the call is step 8, the writes of the callee are steps 11 and 14
+        assert_eq!(
+            writes,
+            vec![
+                (11, Word::zero(), Word::one()),
+                (14, Word::one(), Word::from(2)),
+                (14, Word::from(2), Word::one()),
+                (11, Word::one(), Word::zero()),
+            ]
+        );
+        assert!(accesses.iter().all(|access| access.address == CALLEE));
+    }
+
+    #[test]
+    fn account_changes() {
+        let code = bytecode! {
+            PUSH1(0)
+            PUSH1(0)
+            PUSH1(0)
+            PUSH1(0)
+            PUSH1(7)
+            PUSH20(CALLEE.to_word())
+            PUSH3(100_000)
+            CALL
+            STOP
+        };
+        let mut config = trace_config(code.code(), Vec::new());
+        config.accounts.get_mut(&CONTRACT).unwrap().balance = Word::from(10);
+        let rws = reexecute(&config, MainnetFork::Merge).unwrap().remove(0);
+
+        assert_eq!(
+            rws.nonces,
+            BTreeMap::from([(SENDER, (Word::zero(), Word::one()))])
+        );
+        assert_eq!(
+            rws.balances.get(&CONTRACT),
+            Some(&(Word::from(10), Word::from(3)))
+        );
+        assert_eq!(
+            rws.balances.get(&CALLEE),
+            Some(&(Word::zero(), Word::from(7)))
+        );
+        assert_eq!(
+            rws.warm_accounts,
+            BTreeMap::from([(SENDER, false), (CONTRACT, false), (CALLEE, false)])
+        );
+    }
+
+    #[test]
+    fn creations_are_unsupported() {
+        let mut config = trace_config(Vec::new(), Vec::new());
+        config.transactions[0].to = None;
+        assert!(reexecute(&config, MainnetFork::Merge).is_err());
+    }
+}
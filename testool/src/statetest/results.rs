@@ -4,7 +4,7 @@ use prettytable::{Row, Table};
 use serde::{Deserialize, Serialize};
 use serde_json::json;
 use std::{
-    collections::{HashMap, HashSet},
+    collections::{BTreeMap, HashMap, HashSet},
     io::{Read, Write},
     path::PathBuf,
     str::FromStr,
@@ -32,6 +32,34 @@ pub struct ResultInfo {
     pub level: ResultLevel,
     pub details: String,
     pub path: String,
+    /// execution time, not kept in the csv cache
+    #[serde(default)]
+    pub duration_ms: Option<u64>,
+    /// rows needed by the largest sub-circuit, not kept in the csv cache
+    #[serde(default)]
+    pub rows: Option<usize>,
+    /// gas used by the transactions, not kept in the csv cache
+    #[serde(default)]
+    pub gas_used: Option<u64>,
+    /// rows needed by each sub-circuit, not kept in the csv cache
+    #[serde(default)]
+    pub circuit_rows: Option<BTreeMap<String, usize>>,
+}
+
+impl ResultInfo {
+    /// returns whether both results have the same outcome, regardless of the
+    /// execution stats
+    pub fn same_outcome(&self, other: &Self) -> bool {
+        self.level == other.level && self.details == other.details && self.path == other.path
+    }
+
+    /// gas used per row of the largest sub-circuit
+    pub fn gas_per_row(&self) -> Option<f64> {
+        match (self.gas_used, self.rows) {
+            (Some(gas_used), Some(rows)) if rows > 0 => Some(gas_used as f64 / rows as f64),
+            _ => None,
+        }
+    }
 }
 
 impl ResultLevel {
@@ -119,6 +147,7 @@ pub struct Report {
     diffs: Diffs,
     by_folder: Table,
     by_result: Table,
+    by_circuit: Table,
 }
 
 impl Report {
@@ -134,6 +163,7 @@ impl Report {
             }
         }
         by_result_short.print_tty(false)?;
+        self.by_circuit.print_tty(false)?;
         let (_, files_diff) = self.diffs.gen_info();
         files_diff.print_tty(false)?;
         for (test_id, info) in &self.tests {
@@ -148,10 +178,12 @@ impl Report {
         let reg = Handlebars::new();
         let mut by_folder = Vec::new();
         let mut by_result = Vec::new();
+        let mut by_circuit = Vec::new();
         let mut diffs = Vec::new();
 
         self.by_folder.print_html(&mut by_folder)?;
         self.by_result.print_html(&mut by_result)?;
+        self.by_circuit.print_html(&mut by_circuit)?;
         self.diffs.gen_info().1.print_html(&mut diffs)?;
 
         // strip_prefix `tests/` for rendering purpose. It helps to generate hyperlink
@@ -169,6 +201,7 @@ impl Report {
         let data = &json!({
                 "by_folder": String::from_utf8(by_folder)?,
                 "by_result" : String::from_utf8(by_result)? ,
+                "by_circuit" : String::from_utf8(by_circuit)?,
                 "diffs" : String::from_utf8(diffs)?,
                 "all_results" : tests_for_render,
                 "githash": githash,
@@ -213,6 +246,10 @@ impl Results {
                     level,
                     details,
                     path,
+                    duration_ms: None,
+                    rows: None,
+                    gas_used: None,
+                    circuit_rows: None,
                 },
             );
         }
@@ -268,7 +305,7 @@ impl Results {
 
             if let Some(prev_results) = &prev_results {
                 if let Some(prev_info) = prev_results.tests.get(id) {
-                    if info != prev_info {
+                    if !info.same_outcome(prev_info) {
                         diffs.tests.push(DiffEntry {
                             id: id.to_string(),
                             prev: Some(prev_info.clone()),
@@ -344,14 +381,87 @@ impl Results {
             by_result.add_row(row![format!("{}", entry.0), entry.1]);
         }
 
+        let by_circuit = self.gas_per_row_table();
+
         Report {
             tests: self.tests,
             by_folder,
             by_result,
+            by_circuit,
             diffs,
         }
     }
 
+    /// gas per row stats of each sub-circuit over the successful tests.  The
+    /// minimum gas per row bounds the gas that fits in a circuit of a given
+    /// number of rows.
+    fn gas_per_row_table(&self) -> Table {
+        let mut ratios: BTreeMap<&str, Vec<(f64, usize)>> = BTreeMap::new();
+        for info in self.tests.values() {
+            if let (ResultLevel::Success, Some(ratio)) = (info.level, info.gas_per_row()) {
+                ratios
+                    .entry("largest")
+                    .or_default()
+                    .push((ratio, info.rows.unwrap_or_default()));
+            }
+
+            let (gas_used, circuit_rows) = match (info.gas_used, &info.circuit_rows) {
+                (Some(gas_used), Some(circuit_rows))
+                    if info.level == ResultLevel::Success && gas_used > 0 =>
+                {
+                    (gas_used, circuit_rows)
+                }
+                _ => continue,
+            };
+            for (circuit, rows) in circuit_rows.iter().filter(|(_, rows)| **rows > 0) {
+                ratios
+                    .entry(circuit.as_str())
+                    .or_default()
+                    .push((gas_used as f64 / *rows as f64, *rows));
+            }
+        }
+
+        let mut table = Table::new();
+        table.add_row(row![
+            "By sub-circuit",
+            "Tests",
+            "Max rows",
+            "Min gas/row",
+            "Avg gas/row",
+            "Max gas/row"
+        ]);
+        for (circuit, ratios) in ratios {
+            let max_rows = ratios
+                .iter()
+                .map(|(_, rows)| *rows)
+                .max()
+                .unwrap_or_default();
+            let min = ratios
+                .iter()
+                .map(|(ratio, _)| *ratio)
+                .fold(f64::MAX, f64::min);
+            let max = ratios.iter().map(|(ratio, _)| *ratio).fold(0f64, f64::max);
+            let avg = ratios.iter().map(|(ratio, _)| ratio).sum::<f64>() / ratios.len() as f64;
+            table.add_row(row![
+                circuit,
+                ratios.len(),
+                max_rows,
+                format!("{:.2}", min),
+                format!("{:.2}", avg),
+                format!("{:.2}", max)
+            ]);
+        }
+        table
+    }
+
+    /// writes the results as a json array sorted by test id
+    pub fn write_json(&self, path: PathBuf) -> Result<()> {
+        let mut tests: Vec<_> = self.tests.values().collect();
+        tests.sort_by(|a, b| (&a.test_id, &a.path).cmp(&(&b.test_id, &b.path)));
+        std::fs::write(path, serde_json::to_string_pretty(&tests)?)?;
+        Ok(())
+    }
+
     pub fn success(&self) -> bool {
         !self
             .tests
@@ -1,5 +1,6 @@
+use crate::utils::{MainnetFork, TEST_FORK};
 use anyhow::{anyhow, bail, Context};
-use eth_types::{geth_types::Account, Address, Bytes, Word, H256, U256, U64};
+use eth_types::{evm_types::OpcodeId, geth_types::Account, Address, Bytes, Word, H256, U256, U64};
 use ethers_core::{k256::ecdsa::SigningKey, utils::secret_key_to_address};
 use std::{collections::HashMap, str::FromStr};
 
@@ -53,6 +54,9 @@ pub struct StateTest {
     pub pre: HashMap<Address, Account>,
     pub result: StateTestResult,
     pub exception: bool,
+    pub fork: MainnetFork,
+    /// expected post state root, only known for the filled tests
+    pub state_root: Option<H256>,
 }
 
 impl std::fmt::Display for StateTest {
@@ -106,6 +110,7 @@ impl std::fmt::Display for StateTest {
         table.add_row(row!["value", format!("{}", self.value)]);
         table.add_row(row!["data", format(&hex::encode(&self.data), "")]);
         table.add_row(row!["exception", self.exception]);
+        table.add_row(row!["fork", self.fork]);
 
         let mut addrs: Vec<_> = self.pre.keys().collect();
         addrs.extend(self.result.keys());
@@ -158,9 +163,58 @@ impl std::fmt::Display for StateTest {
 
         Ok(())
     }
+
+    #[test]
+    fn builder() -> anyhow::Result<()> {
+        let callee = Address::from_low_u64_be(0xdead);
+        let test = StateTest::builder()
+            .code(callee, eth_types::bytecode! { PUSH1(1) PUSH1(0) SSTORE })
+            .storage(callee, U256::one(), U256::one())
+            .call(callee)
+            .expect_storage(callee, U256::zero(), U256::one())
+            .build();
+
+        assert_eq!(
+            test,
+            StateTest {
+                result: test.result.clone(),
+                ..StateTest::parse_oneline_spec("call dead;6001600055;0;1:1")?
+            }
+        );
+        assert_eq!(test.result[&callee].storage[&U256::zero()], U256::one());
+        Ok(())
+    }
 }
 
 impl StateTest {
+    /// Returns whether `opcode` appears in the code of the pre-state accounts
+    /// or in the init code of a creation transaction.  Code deployed during
+    /// the execution is not taken into account.
+    pub fn uses_opcode(&self, opcode: OpcodeId) -> bool {
+        fn code_uses_opcode(code: &[u8], opcode: OpcodeId) -> bool {
+            let mut pc = 0;
+            while pc < code.len() {
+                let op = OpcodeId::from(code[pc]);
+                if op == opcode {
+                    return true;
+                }
+                pc += 1 + op.data_len();
+            }
+            false
+        }
+
+        self.pre
+            .values()
+            .any(|account| code_uses_opcode(&account.code, opcode))
+            || (self.to.is_none() && code_uses_opcode(&self.data, opcode))
+    }
+
+    /// Returns a builder of a custom test, with the same defaults as the
+    /// oneliner spec
+    pub fn builder() -> StateTestBuilder {
+        StateTestBuilder::new()
+    }
+
     pub fn parse_oneline_spec(tx: &str) -> anyhow::Result<StateTest> {
         // call;calldata;value;gas addr;code;balance;slot1:val1;slot2:val2
         // create;calldata;value;gas addr;code;balance;slot1:val1;slot2:val2
@@ -192,23 +246,10 @@ impl StateTest {
                 _ => bail!("no call or create specified"),
             }
         };
-        let data = hex::decode(tx.next().unwrap_or(""))?;
-        let value = parse_u256(tx.next().unwrap_or("0"))?;
-        let gas_limit = u64::from_str(tx.next().unwrap_or("10000000"))?;
-        let secret_key = Bytes::from(&[1u8; 32]);
-        let from = secret_key_to_address(&SigningKey::from_slice(&secret_key)?);
-
-        let mut pre = HashMap::<Address, Account>::new();
-
-        // setup tx.origin (from) account
-        pre.insert(
-            from,
-            Account {
-                address: from,
-                balance: U256::from(10).pow(18.into()),
-                ..Default::default()
-            },
-        );
+        let mut builder = Self::builder()
+            .data(hex::decode(tx.next().unwrap_or(""))?)
+            .value(parse_u256(tx.next().unwrap_or("0"))?)
+            .gas_limit(u64::from_str(tx.next().unwrap_or("10000000"))?);
 
         // parse rest accounts
         let mut to = None;
@@ -227,27 +268,66 @@ impl StateTest {
             }
             let code = crate::utils::bytecode_of(account.next().unwrap_or(""))?;
             let balance = Word::from_str(account.next().unwrap_or("0"))?;
-            let mut storage = HashMap::<U256, U256>::new();
+            builder = builder.account(address, balance).code(address, code.code());
 
             // parse storage (if any)
             for key_value in account {
                 let (key, value) = key_value
                     .split_once(':')
                     .ok_or_else(|| anyhow!("Invalid storage spec"))?;
-                storage.insert(parse_u256(key)?, parse_u256(value)?);
+                builder = builder.storage(address, parse_u256(key)?, parse_u256(value)?);
             }
-            pre.insert(
-                address,
-                Account {
-                    address,
-                    nonce: U64::one(),
-                    code: Bytes::from(code.code()),
-                    balance,
-                    storage,
-                },
-            );
         }
 
+        Ok(match to {
+            Some(to) => builder.call(to),
+            None => builder,
+        }
+        .build())
+    }
+}
+
+/// Builder of custom tests, to write targeted regression tests in rust:
+///
+/// ```ignore
+/// let test = StateTest::builder()
+///     .code(callee, bytecode! { PUSH1(1) PUSH1(0) SSTORE })
+///     .call(callee)
+///     .expect_storage(callee, 0.into(), 1.into())
+///     .build();
+/// ```
+///
+/// The sender is funded with 1 ether and the contract accounts are created
+/// with nonce 1 and no balance, unless set with `account`.  Without `call`,
+/// the transaction creates a contract with the calldata as init code.
+pub struct StateTestBuilder {
+    test: StateTest,
+}
+
+impl Default for StateTestBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// not all the setters are needed by the oneliner spec, the rest are used to
+// write tests
+#[allow(dead_code)]
+impl StateTestBuilder {
+    pub fn new() -> Self {
+        let secret_key = Bytes::from(&[1u8; 32]);
+        let from = secret_key_to_address(&SigningKey::from_slice(&secret_key).unwrap());
+
+        let mut pre = HashMap::<Address, Account>::new();
+        // setup tx.origin (from) account
+        pre.insert(
+            from,
+            Account {
+                address: from,
+                balance: U256::from(10).pow(18.into()),
+                ..Default::default()
+            },
+        );
         pre.insert(
             *mock::MOCK_COINBASE,
             Account {
@@ -257,30 +337,159 @@ impl StateTest {
             },
         );
 
-        let state_test = StateTest {
-            path: String::default(),
-            id: String::default(),
-            env: Env {
-                current_coinbase: *mock::MOCK_COINBASE,
-                current_difficulty: U256::default(),
-                current_gas_limit: 16000000,
-                current_number: 1,
-                current_timestamp: 1,
-                previous_hash: H256::default(),
+        Self {
+            test: StateTest {
+                path: String::default(),
+                id: String::default(),
+                env: Env {
+                    current_coinbase: *mock::MOCK_COINBASE,
+                    current_difficulty: U256::default(),
+                    current_gas_limit: 16000000,
+                    current_number: 1,
+                    current_timestamp: 1,
+                    previous_hash: H256::default(),
+                },
+                secret_key,
+                from,
+                to: None,
+                gas_limit: 10000000,
+                gas_price: U256::one(),
+                nonce: 0,
+                value: U256::zero(),
+                data: Bytes::default(),
+                pre,
+                result: HashMap::new(),
+                exception: false,
+                fork: TEST_FORK,
+                state_root: None,
             },
-            secret_key,
-            from,
-            to,
-            gas_limit,
-            gas_price: U256::one(),
-            nonce: 0,
-            value,
-            data: data.into(),
-            pre,
-            result: HashMap::new(),
-            exception: false,
-        };
+        }
+    }
+
+    /// pre-state account of a contract, created with nonce 1
+    fn contract(&mut self, address: Address) -> &mut Account {
+        self.test.pre.entry(address).or_insert_with(|| Account {
+            address,
+            nonce: U64::one(),
+            ..Default::default()
+        })
+    }
+
+    /// expected post-state of an account
+    fn expected(&mut self, address: Address) -> &mut AccountMatch {
+        self.test
+            .result
+            .entry(address)
+            .or_insert_with(|| AccountMatch {
+                address,
+                ..Default::default()
+            })
+    }
+
+    pub fn id(mut self, id: &str) -> Self {
+        self.test.id = id.to_string();
+        self
+    }
+
+    pub fn fork(mut self, fork: MainnetFork) -> Self {
+        self.test.fork = fork;
+        self
+    }
+
+    /// sets the balance of a pre-state account
+    pub fn account(mut self, address: Address, balance: U256) -> Self {
+        self.contract(address).balance = balance;
+        self
+    }
+
+    /// sets the code of a pre-state account
+    pub fn code(mut self, address: Address, code: impl Into<Bytes>) -> Self {
+        self.contract(address).code = code.into();
+        self
+    }
+
+    /// sets a storage slot of a pre-state account
+    pub fn storage(mut self, address: Address, slot: U256, value: U256) -> Self {
+        self.contract(address).storage.insert(slot, value);
+        self
+    }
+
+    /// makes the transaction a call to `to`
+    pub fn call(mut self, to: Address) -> Self {
+        self.test.to = Some(to);
+        self
+    }
+
+    pub fn data(mut self, data: impl Into<Bytes>) -> Self {
+        self.test.data = data.into();
+        self
+    }
+
+    pub fn value(mut self, value: U256) -> Self {
+        self.test.value = value;
+        self
+    }
+
+    pub fn gas_limit(mut self, gas_limit: u64) -> Self {
+        self.test.gas_limit = gas_limit;
+        self
+    }
+
+    /// expects the transaction to be invalid
+    pub fn exception(mut self) -> Self {
+        self.test.exception = true;
+        self
+    }
+
+    /// expects a post-state balance
+    pub fn expect_balance(mut self, address: Address, balance: U256) -> Self {
+        self.expected(address).balance = Some(balance);
+        self
+    }
+
+    /// expects a post-state storage slot value
+    pub fn expect_storage(mut self, address: Address, slot: U256, value: U256) -> Self {
+        self.expected(address).storage.insert(slot, value);
+        self
+    }
+
+    pub fn build(self) -> StateTest {
+        self.test
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn uses_opcode() -> anyhow::Result<()> {
+        // PUSH1 0x1d (SAR as push data), PUSH1 0x01, SAR
+        let test = StateTest::parse_oneline_spec("call dead;601d60011d")?;
+        assert!(test.uses_opcode(OpcodeId::SAR));
+        let test = StateTest::parse_oneline_spec("call dead;601d6001")?;
+        assert!(!test.uses_opcode(OpcodeId::SAR));
+        Ok(())
+    }
+
+    #[test]
+    fn builder() -> anyhow::Result<()> {
+        let callee = Address::from_low_u64_be(0xdead);
+        let test = StateTest::builder()
+            .code(callee, eth_types::bytecode! { PUSH1(1) PUSH1(0) SSTORE })
+            .storage(callee, U256::one(), U256::one())
+            .call(callee)
+            .expect_storage(callee, U256::zero(), U256::one())
+            .build();
 
-        Ok(state_test)
+        assert_eq!(
+            test,
+            StateTest {
+                result: test.result.clone(),
+                ..StateTest::parse_oneline_spec("call dead;6001600055;0;1:1")?
+            }
+        );
+        assert_eq!(test.result[&callee].storage[&U256::zero()], U256::one());
+        Ok(())
     }
 }
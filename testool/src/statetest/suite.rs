@@ -1,4 +1,8 @@
-use super::{executor::run_test, CircuitsConfig, JsonStateTestBuilder, Results, StateTest};
+use super::{
+    executor::{run_blockchain_test, run_test},
+    BlockchainTest, BlockchainTestBuilder, CircuitsConfig, GeneralStateTestBuilder,
+    JsonStateTestBuilder, Results, StateTest, StateTestError, TestStats,
+};
 use crate::{
     compiler::Compiler,
     config::{Config, TestSuite},
@@ -6,33 +10,125 @@ use crate::{
         results::{ResultInfo, ResultLevel},
         YamlStateTestBuilder,
     },
+    utils::MainnetFork,
 };
 use anyhow::{Context, Result};
 use rayon::prelude::*;
 use std::{
-    panic::AssertUnwindSafe,
-    sync::{Arc, RwLock},
+    panic::{self, AssertUnwindSafe},
+    path::PathBuf,
+    sync::{
+        mpsc::{self, RecvTimeoutError},
+        Arc, RwLock,
+    },
+    thread,
+    time::{Duration, Instant},
 };
 
-pub fn load_statetests_suite(
-    path: &str,
-    config: Config,
-    mut compiler: Compiler,
-) -> Result<Vec<StateTest>> {
+/// A test that can be run by `run_statetests_suite`
+pub trait SuiteTest: Clone + Send + Sync + 'static {
+    fn id(&self) -> &str;
+    fn path(&self) -> &str;
+    fn run(
+        &self,
+        suite: TestSuite,
+        circuits_config: CircuitsConfig,
+    ) -> Result<TestStats, StateTestError>;
+}
+
+impl SuiteTest for StateTest {
+    fn id(&self) -> &str {
+        &self.id
+    }
+    fn path(&self) -> &str {
+        &self.path
+    }
+    fn run(
+        &self,
+        suite: TestSuite,
+        circuits_config: CircuitsConfig,
+    ) -> Result<TestStats, StateTestError> {
+        run_test(self.clone(), suite, circuits_config)
+    }
+}
+
+impl SuiteTest for BlockchainTest {
+    fn id(&self) -> &str {
+        &self.id
+    }
+    fn path(&self) -> &str {
+        &self.path
+    }
+    fn run(
+        &self,
+        suite: TestSuite,
+        circuits_config: CircuitsConfig,
+    ) -> Result<TestStats, StateTestError> {
+        run_blockchain_test(self.clone(), suite, circuits_config)
+    }
+}
+
+/// Runs `tc`, catching its panics.  With a suite timeout, the test runs on a
+/// watched thread of its own, and is reported as timed out if it's still
+/// running at the deadline.  A thread cannot be killed, so the late test is
+/// left running detached and its result is dropped; its `TestGuard` stops it
+/// at the end of its current phase.
+fn run_with_deadline<T: SuiteTest>(
+    tc: &T,
+    suite: &TestSuite,
+    circuits_config: &CircuitsConfig,
+) -> thread::Result<Result<TestStats, StateTestError>> {
+    let timeout = match suite.timeout {
+        Some(timeout) => timeout,
+        None => {
+            return panic::catch_unwind(AssertUnwindSafe(|| {
+                tc.run(suite.clone(), circuits_config.clone())
+            }))
+        }
+    };
+
+    let (sender, receiver) = mpsc::channel();
+    let (tc, suite, circuits_config) = (tc.clone(), suite.clone(), circuits_config.clone());
+    thread::spawn(move || {
+        let result = panic::catch_unwind(AssertUnwindSafe(|| tc.run(suite, circuits_config)));
+        // the receiver is gone if the test timed out
+        let _ = sender.send(result);
+    });
+    match receiver.recv_timeout(Duration::from_secs(timeout)) {
+        Ok(result) => result,
+        Err(RecvTimeoutError::Timeout) => Ok(Err(StateTestError::SkipTestTimeout(timeout))),
+        Err(RecvTimeoutError::Disconnected) => Err(Box::new("the test thread stopped")),
+    }
+}
+
+/// Files of the suite matching the `path` glob, except the skipped ones
+fn suite_files(path: &str, config: &Config) -> Result<Vec<PathBuf>> {
     let skip_paths: Vec<&String> = config.skip_paths.iter().flat_map(|t| &t.paths).collect();
-    let skip_tests: Vec<&String> = config.skip_tests.iter().flat_map(|t| &t.tests).collect();
 
-    let files = glob::glob(path)
+    Ok(glob::glob(path)
         .context("failed to read glob")?
         .filter_map(|v| v.ok())
         .filter(|f| {
             !skip_paths
                 .iter()
                 .any(|e| f.as_path().to_string_lossy().contains(*e))
-        });
+        })
+        .collect())
+}
+
+/// Loads the tests of the suite for each of the `forks`.  When more than one
+/// fork is selected, the fork name is appended to the test ids so that each
+/// fork has its own results.
+pub fn load_statetests_suite(
+    path: &str,
+    config: Config,
+    mut compiler: Compiler,
+    forks: &[MainnetFork],
+) -> Result<Vec<StateTest>> {
+    let skip_tests: Vec<&String> = config.skip_tests.iter().flat_map(|t| &t.tests).collect();
 
     let mut tests = Vec::new();
-    for file in files {
+    for file in suite_files(path, &config)? {
         if let Some(ext) = file.extension() {
             let ext = &*ext.to_string_lossy();
             if !["yml", "json"].contains(&ext) {
@@ -41,30 +137,85 @@ pub fn load_statetests_suite(
             let path = file.as_path().to_string_lossy();
             let src = std::fs::read_to_string(&file)?;
             log::debug!(target: "testool", "Reading file {:?}", file);
-            let mut tcs = match ext {
-                "yml" => YamlStateTestBuilder::new(&mut compiler).load_yaml(&path, &src)?,
-                "json" => JsonStateTestBuilder::new(&mut compiler).load_json(&path, &src)?,
-                _ => unreachable!(),
-            };
+            for fork in forks {
+                let mut tcs = match ext {
+                    "yml" => YamlStateTestBuilder::new(&mut compiler)
+                        .with_fork(*fork)
+                        .load_yaml(&path, &src)?,
+                    "json" if GeneralStateTestBuilder::is_general_state_test(&src) => {
+                        GeneralStateTestBuilder::default()
+                            .with_fork(*fork)
+                            .load_json(&path, &src)?
+                    }
+                    "json" => JsonStateTestBuilder::new(&mut compiler)
+                        .with_fork(*fork)
+                        .load_json(&path, &src)?,
+                    _ => unreachable!(),
+                };
+
+                tcs.retain(|v| !skip_tests.contains(&&v.id));
+                if forks.len() > 1 {
+                    tcs.iter_mut()
+                        .for_each(|tc| tc.id = format!("{}_{}", tc.id, tc.fork));
+                }
+                tests.append(&mut tcs);
+            }
+        }
+    }
+    Ok(tests)
+}
+
+/// Loads the `BlockchainTests` of the suite for each of the `forks`, with
+/// the same test ids as `load_statetests_suite`.
+pub fn load_blockchain_tests_suite(
+    path: &str,
+    config: Config,
+    forks: &[MainnetFork],
+) -> Result<Vec<BlockchainTest>> {
+    let skip_tests: Vec<&String> = config.skip_tests.iter().flat_map(|t| &t.tests).collect();
+
+    let mut tests = Vec::new();
+    for file in suite_files(path, &config)? {
+        if file.extension().map_or(true, |ext| ext != "json") {
+            continue;
+        }
+        let path = file.as_path().to_string_lossy();
+        let src = std::fs::read_to_string(&file)?;
+        if !BlockchainTestBuilder::is_blockchain_test(&src) {
+            log::debug!(target: "testool", "Skipping {:?}, not a blockchain test", file);
+            continue;
+        }
+        log::debug!(target: "testool", "Reading file {:?}", file);
+        for fork in forks {
+            let mut tcs = BlockchainTestBuilder::default()
+                .with_fork(*fork)
+                .load_json(&path, &src)?;
 
             tcs.retain(|v| !skip_tests.contains(&&v.id));
+            if forks.len() > 1 {
+                tcs.iter_mut()
+                    .for_each(|tc| tc.id = format!("{}_{}", tc.id, tc.fork));
+            }
             tests.append(&mut tcs);
         }
     }
     Ok(tests)
 }
 
-pub fn run_statetests_suite(
-    tcs: Vec<StateTest>,
+/// Runs the tests in a pool of `jobs` threads (one per CPU by default),
+/// recording a result for each of them, including the ones that panic.
+pub fn run_statetests_suite<T: SuiteTest>(
+    tcs: Vec<T>,
     circuits_config: &CircuitsConfig,
     suite: &TestSuite,
     results: &mut Results,
+    jobs: Option<usize>,
 ) -> Result<()> {
     // Filter already cached entries
     let all_test_count = tcs.len();
-    let tcs: Vec<StateTest> = tcs
+    let tcs: Vec<T> = tcs
         .into_iter()
-        .filter(|t| !results.contains(&format!("{}#{}", t.id, t.path)))
+        .filter(|t| !results.contains(&format!("{}#{}", t.id(), t.path())))
         .collect();
 
     log::info!(
@@ -75,101 +226,166 @@ pub fn run_statetests_suite(
 
     let results = Arc::new(RwLock::from(results));
 
-    // for each test
-    let test_count = tcs.len();
-    tcs.into_par_iter().for_each(|ref tc| {
-        let (test_id, path) = (tc.id.clone(), tc.path.clone());
-        if !suite.allowed(&test_id) {
-            results
-                .write()
-                .unwrap()
-                .insert(ResultInfo {
-                    test_id,
-                    level: ResultLevel::Ignored,
-                    details: "Ignored in config file".to_string(),
-                    path,
-                })
-                .unwrap();
-            return;
-        }
+    let mut pool = rayon::ThreadPoolBuilder::new();
+    if let Some(jobs) = jobs {
+        pool = pool.num_threads(jobs);
+    }
+    let pool = pool.build().context("failed to build thread pool")?;
 
-        std::panic::set_hook(Box::new(|_info| {}));
-
-        log::debug!(
-            target : "testool",
-            "🐕 running test (done {}/{}) {}#{}...",
-            1 + results.read().unwrap().tests.len(),
-            test_count,
-            test_id,
-            path,
-        );
-        let result = std::panic::catch_unwind(AssertUnwindSafe(|| {
-            run_test(tc.clone(), suite.clone(), circuits_config.clone())
-        }));
-
-        // handle panic
-        let result = match result {
-            Ok(res) => res,
-            Err(err) => {
-                let panic_err = if let Some(s) = err.downcast_ref::<String>() {
-                    s.to_string()
-                } else if let Some(s) = err.downcast_ref::<&str>() {
-                    s.to_string()
-                } else {
-                    "unable to get panic info".into()
-                };
+    // panics are reported as test results
+    let default_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(|_info| {}));
 
-                let level = if panic_err.contains("circuit was not satisfied") {
-                    ResultLevel::Fail
-                } else if panic_err.contains("evm_unimplemented") {
-                    ResultLevel::Ignored
-                } else {
-                    ResultLevel::Panic
-                };
+    // for each test
+    let test_count = tcs.len();
+    pool.install(|| {
+        tcs.into_par_iter().for_each(|ref tc| {
+            let (test_id, path) = (tc.id().to_string(), tc.path().to_string());
+            if !suite.allowed(&test_id) {
                 results
                     .write()
                     .unwrap()
                     .insert(ResultInfo {
                         test_id,
-                        level,
-                        details: panic_err,
+                        level: ResultLevel::Ignored,
+                        details: "Ignored in config file".to_string(),
                         path,
+                        duration_ms: None,
+                        rows: None,
+                        gas_used: None,
+                        circuit_rows: None,
                     })
                     .unwrap();
                 return;
             }
-        };
 
-        // handle known error
-        if let Err(err) = result {
+            log::debug!(
+                target : "testool",
+                "🐕 running test (done {}/{}) {}#{}...",
+                1 + results.read().unwrap().tests.len(),
+                test_count,
+                test_id,
+                path,
+            );
+            let start = Instant::now();
+            let result = run_with_deadline(tc, suite, circuits_config);
+            let duration_ms = Some(start.elapsed().as_millis() as u64);
+
+            // handle panic
+            let (level, details, stats) = match result {
+                Ok(Ok(stats)) => (ResultLevel::Success, String::default(), Some(stats)),
+                // handle known error
+                Ok(Err(err)) => {
+                    let level = if err.is_skip() {
+                        ResultLevel::Ignored
+                    } else {
+                        ResultLevel::Fail
+                    };
+                    (level, err.to_string(), None)
+                }
+                Err(err) => {
+                    let panic_err = if let Some(s) = err.downcast_ref::<String>() {
+                        s.to_string()
+                    } else if let Some(s) = err.downcast_ref::<&str>() {
+                        s.to_string()
+                    } else {
+                        "unable to get panic info".into()
+                    };
+
+                    let level = if panic_err.contains("circuit was not satisfied") {
+                        ResultLevel::Fail
+                    } else if panic_err.contains("evm_unimplemented") {
+                        ResultLevel::Ignored
+                    } else {
+                        ResultLevel::Panic
+                    };
+                    (level, panic_err, None)
+                }
+            };
+
+            // known failures are tracked without failing the suite
+            let (level, details) = match (suite.known_failure(&test_id), level) {
+                (Some(reason), ResultLevel::Fail | ResultLevel::Panic) => (
+                    ResultLevel::Ignored,
+                    format!("known failure ({}): {}", reason, details),
+                ),
+                (Some(reason), ResultLevel::Success) => {
+                    log::warn!("{} is listed as a known failure but passes", test_id);
+                    (level, format!("known failure ({}) passes", reason))
+                }
+                _ => (level, details),
+            };
+
             results
                 .write()
                 .unwrap()
                 .insert(ResultInfo {
                     test_id,
-                    level: if err.is_skip() {
-                        ResultLevel::Ignored
-                    } else {
-                        ResultLevel::Fail
-                    },
-                    details: err.to_string(),
+                    level,
+                    details,
                     path,
+                    duration_ms,
+                    rows: stats.as_ref().map(|stats| stats.rows.max_rows()),
+                    gas_used: stats.as_ref().map(|stats| stats.gas_used),
+                    circuit_rows: stats.map(|stats| {
+                        stats
+                            .rows
+                            .entries()
+                            .into_iter()
+                            .map(|(name, rows)| (name.to_string(), rows))
+                            .collect()
+                    }),
                 })
                 .unwrap();
-            return;
-        }
-
-        results
-            .write()
-            .unwrap()
-            .insert(ResultInfo {
-                test_id,
-                level: ResultLevel::Success,
-                details: String::default(),
-                path,
-            })
-            .unwrap();
+        })
     });
 
+    std::panic::set_hook(default_hook);
+
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Clone)]
+    struct SleepingTest(Duration);
+
+    impl SuiteTest for SleepingTest {
+        fn id(&self) -> &str {
+            "sleeping"
+        }
+        fn path(&self) -> &str {
+            "sleeping.json"
+        }
+        fn run(
+            &self,
+            _suite: TestSuite,
+            _circuits_config: CircuitsConfig,
+        ) -> Result<TestStats, StateTestError> {
+            thread::sleep(self.0);
+            Ok(TestStats::default())
+        }
+    }
+
+    #[test]
+    fn deadline_stops_waiting_for_a_running_test() {
+        let suite = TestSuite {
+            timeout: Some(1),
+            ..TestSuite::default()
+        };
+        let config = CircuitsConfig::default();
+
+        let start = Instant::now();
+        let result = run_with_deadline(&SleepingTest(Duration::from_secs(60)), &suite, &config);
+        assert!(matches!(
+            result,
+            Ok(Err(StateTestError::SkipTestTimeout(1)))
+        ));
+        assert!(start.elapsed() < Duration::from_secs(30));
+
+        let result = run_with_deadline(&SleepingTest(Duration::ZERO), &suite, &config);
+        assert!(matches!(result, Ok(Ok(_))));
+    }
+}
@@ -0,0 +1,195 @@
+use eth_types::{keccak256, Address, Word, H256};
+use ethers_core::utils::rlp::RlpStream;
+use std::collections::HashMap;
+
+/// An account as committed to by the state trie
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TrieAccount {
+    pub nonce: u64,
+    pub balance: Word,
+    /// keccak hash of the code
+    pub code_hash: H256,
+    pub storage: HashMap<Word, Word>,
+}
+
+impl TrieAccount {
+    fn rlp(&self) -> Vec<u8> {
+        let mut stream = RlpStream::new_list(4);
+        stream.append(&self.nonce);
+        stream.append(&self.balance);
+        stream.append(&storage_root(&self.storage));
+        stream.append(&self.code_hash);
+        stream.out().to_vec()
+    }
+}
+
+/// Root of the secure trie of the storage of an account, without the zero
+/// slots
+pub fn storage_root(storage: &HashMap<Word, Word>) -> H256 {
+    secure_trie_root(
+        storage
+            .iter()
+            .filter(|(_, value)| !value.is_zero())
+            .map(|(slot, value)| {
+                let mut stream = RlpStream::new();
+                stream.append(value);
+                (H256::from_uint(slot).0.to_vec(), stream.out().to_vec())
+            })
+            .collect(),
+    )
+}
+
+/// Root of the state trie of `accounts`, which must not contain empty
+/// accounts
+pub fn state_root(accounts: impl IntoIterator<Item = (Address, TrieAccount)>) -> H256 {
+    secure_trie_root(
+        accounts
+            .into_iter()
+            .map(|(address, account)| (address.0.to_vec(), account.rlp()))
+            .collect(),
+    )
+}
+
+/// Root of a trie whose keys are hashed
+fn secure_trie_root(entries: Vec<(Vec<u8>, Vec<u8>)>) -> H256 {
+    trie_root(
+        entries
+            .into_iter()
+            .map(|(key, value)| (keccak256(&key).to_vec(), value))
+            .collect(),
+    )
+}
+
+/// Root of the Merkle Patricia Trie of the `(key, value)` entries
+pub fn trie_root(entries: Vec<(Vec<u8>, Vec<u8>)>) -> H256 {
+    let entries: Vec<(Vec<u8>, Vec<u8>)> = entries
+        .into_iter()
+        .map(|(key, value)| (nibbles(&key), value))
+        .collect();
+    H256(keccak256(&encode_node(&entries, 0)))
+}
+
+fn nibbles(key: &[u8]) -> Vec<u8> {
+    key.iter()
+        .flat_map(|byte| [byte >> 4, byte & 0xf])
+        .collect()
+}
+
+/// Hex-prefix encoding of a path of nibbles
+fn hex_prefix(path: &[u8], is_leaf: bool) -> Vec<u8> {
+    let flag = if is_leaf { 2 } else { 0 } + (path.len() % 2) as u8;
+    let (first, rest) = if path.len() % 2 == 1 {
+        (flag << 4 | path[0], &path[1..])
+    } else {
+        (flag << 4, path)
+    };
+    std::iter::once(first)
+        .chain(rest.chunks(2).map(|pair| pair[0] << 4 | pair[1]))
+        .collect()
+}
+
+/// Appends the reference to a node: the node itself if its encoding is
+/// shorter than a hash, else its hash
+fn append_reference(stream: &mut RlpStream, node: &[u8]) {
+    if node.len() < 32 {
+        stream.append_raw(node, 1);
+    } else {
+        stream.append(&H256(keccak256(node)));
+    }
+}
+
+/// RLP encoding of the node holding the entries, whose keys share their
+/// first `depth` nibbles
+fn encode_node(entries: &[(Vec<u8>, Vec<u8>)], depth: usize) -> Vec<u8> {
+    match entries {
+        [] => vec![0x80],
+        [(key, value)] => {
+            let mut stream = RlpStream::new_list(2);
+            stream.append(&hex_prefix(&key[depth..], true));
+            stream.append(value);
+            stream.out().to_vec()
+        }
+        _ => {
+            let (first, _) = &entries[0];
+            let prefix_len = (depth..first.len())
+                .take_while(|&i| entries.iter().all(|(key, _)| key.get(i) == first.get(i)))
+                .count();
+            if prefix_len > 0 {
+                let mut stream = RlpStream::new_list(2);
+                stream.append(&hex_prefix(&first[depth..depth + prefix_len], false));
+                append_reference(&mut stream, &encode_node(entries, depth + prefix_len));
+                return stream.out().to_vec();
+            }
+
+            let mut stream = RlpStream::new_list(17);
+            for nibble in 0..16 {
+                let children: Vec<_> = entries
+                    .iter()
+                    .filter(|(key, _)| key.get(depth) == Some(&nibble))
+                    .cloned()
+                    .collect();
+                if children.is_empty() {
+                    stream.append_empty_data();
+                } else {
+                    append_reference(&mut stream, &encode_node(&children, depth + 1));
+                }
+            }
+            match entries.iter().find(|(key, _)| key.len() == depth) {
+                Some((_, value)) => stream.append(value),
+                None => stream.append_empty_data(),
+            };
+            stream.out().to_vec()
+        }
+    }
+}
+
+#[cfg(test)]
+mod trie_tests {
+    use super::*;
+    use std::str::FromStr;
+
+    fn entries(pairs: &[(&str, &str)]) -> Vec<(Vec<u8>, Vec<u8>)> {
+        pairs
+            .iter()
+            .map(|(key, value)| (key.as_bytes().to_vec(), value.as_bytes().to_vec()))
+            .collect()
+    }
+
+    #[test]
+    fn trie_roots() {
+        // vectors of ethereum/tests TrieTests
+        assert_eq!(
+            trie_root(vec![]),
+            H256::from_str("0x56e81f171bcc55a6ff8345e692c0f86e5b48e01b996cadc001622fb5e363b421")
+                .unwrap()
+        );
+        assert_eq!(
+            trie_root(entries(&[(
+                "A",
+                "aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa"
+            )])),
+            H256::from_str("0xd23786fb4a010da3ce639d66d5e904a11dbc02746d1ce25029e53290cabf28ab")
+                .unwrap()
+        );
+        assert_eq!(
+            trie_root(entries(&[
+                ("do", "verb"),
+                ("horse", "stallion"),
+                ("doge", "coin"),
+                ("dog", "puppy"),
+            ])),
+            H256::from_str("0x5991bb8c6514148a29db676a14ac506cd2cd5775ace63c30a4fe457715e9ac84")
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn zero_slots_are_not_committed() {
+        let mut storage = HashMap::new();
+        assert_eq!(storage_root(&storage), trie_root(vec![]));
+        storage.insert(Word::one(), Word::zero());
+        assert_eq!(storage_root(&storage), trie_root(vec![]));
+        storage.insert(Word::from(2), Word::one());
+        assert_ne!(storage_root(&storage), trie_root(vec![]));
+    }
+}
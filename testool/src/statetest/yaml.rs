@@ -2,7 +2,10 @@ use super::{
     parse,
     spec::{AccountMatch, Env, StateTest},
 };
-use crate::{utils::MainnetFork, Compiler};
+use crate::{
+    utils::{MainnetFork, TEST_FORK},
+    Compiler,
+};
 use anyhow::{bail, Context, Result};
 use eth_types::{geth_types::Account, Address, Bytes, H256, U256};
 use ethers_core::{k256::ecdsa::SigningKey, utils::secret_key_to_address};
@@ -39,11 +42,21 @@ impl Refs {
 
 pub struct YamlStateTestBuilder<'a> {
     compiler: &'a mut Compiler,
+    fork: MainnetFork,
 }
 
 impl<'a> YamlStateTestBuilder<'a> {
     pub fn new(compiler: &'a mut Compiler) -> Self {
-        Self { compiler }
+        Self {
+            compiler,
+            fork: TEST_FORK,
+        }
+    }
+
+    /// selects the fork whose expectations are loaded
+    pub fn with_fork(mut self, fork: MainnetFork) -> Self {
+        self.fork = fork;
+        self
     }
 
     /// generates `StateTest` vectors from a ethereum yaml test specification
@@ -128,7 +141,7 @@ impl<'a> YamlStateTestBuilder<'a> {
                 if let Some(exceptions) = expect["expectException"].as_hash() {
                     for (network, _error_type) in exceptions {
                         let network = network.as_str().unwrap().to_string();
-                        if MainnetFork::in_network_range(&[network])? {
+                        if MainnetFork::in_network_range(&[network], self.fork)? {
                             exception = true;
                         }
                     }
@@ -139,7 +152,7 @@ impl<'a> YamlStateTestBuilder<'a> {
                 let value_refs = Self::parse_refs(&expect["indexes"]["value"])?;
                 let result = self.parse_accounts(&expect["result"])?;
 
-                if MainnetFork::in_network_range(&networks)? {
+                if MainnetFork::in_network_range(&networks, self.fork)? {
                     expects.push((exception, data_refs, gparse_refs, value_refs, result));
                 }
             }
@@ -189,6 +202,8 @@ impl<'a> YamlStateTestBuilder<'a> {
                                 value: *value,
                                 data: data.0.clone(),
                                 exception: *exception,
+                                fork: self.fork,
+                                state_root: None,
                             });
                             break;
                         }
@@ -649,6 +664,8 @@ arith:
                 },
             )]),
             exception: false,
+            fork: TEST_FORK,
+            state_root: None,
         };
 
         assert_eq!(current, expected);
@@ -1,13 +1,15 @@
 use std::str::FromStr;
 
 use anyhow::{bail, Result};
+use bus_mapping::chain_config::{ChainConfig, Hardfork};
 use eth_types::{bytecode::OpcodeWithData, Bytecode, GethExecTrace, U256};
 use log::{error, info};
 use prettytable::Table;
 use std::process::{Command, Stdio};
 
-#[derive(Debug, Eq, PartialEq, PartialOrd)]
+#[derive(Debug, Clone, Copy, Eq, PartialEq, PartialOrd, Hash)]
 pub enum MainnetFork {
+    Shanghai = 15,
     Merge = 14,
     GrayGlacier = 13,
     ArrowGlacier = 12,
@@ -31,7 +33,8 @@ impl FromStr for MainnetFork {
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         Ok(match s {
-            "Merge" => Self::Merge,
+            "Shanghai" => Self::Shanghai,
+            "Merge" | "Paris" => Self::Merge,
             "Gray Glacier" => Self::GrayGlacier,
             "Arrow Glacier" => Self::ArrowGlacier,
             "Altair" => Self::Altair,
@@ -50,18 +53,52 @@ impl FromStr for MainnetFork {
     }
 }
 
+impl std::fmt::Display for MainnetFork {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
 impl MainnetFork {
-    pub fn in_network_range(expect: &[String]) -> Result<bool, anyhow::Error> {
+    /// Returns the rules the circuit input builder applies for this fork, or
+    /// None if the fork predates the ones supported by the circuits.
+    pub fn chain_config(&self, chain_id: u64) -> Option<ChainConfig> {
+        let hardfork = match self {
+            Self::Shanghai => Hardfork::Shanghai,
+            Self::Merge => Hardfork::Merge,
+            Self::GrayGlacier | Self::ArrowGlacier | Self::Altair | Self::London => {
+                Hardfork::London
+            }
+            Self::Berlin => Hardfork::Berlin,
+            Self::MuirGlacier | Self::Istanbul => Hardfork::Istanbul,
+            _ => return None,
+        };
+        Some(ChainConfig::new(chain_id, hardfork))
+    }
+
+    /// Returns the name of the fork whose rules are applied by the geth
+    /// tracer, which only distinguishes the forks changing the EVM.
+    pub fn tracer_fork(&self) -> &'static str {
+        match self {
+            Self::Shanghai => "Shanghai",
+            Self::Merge => "Merge",
+            Self::Berlin => "Berlin",
+            Self::MuirGlacier | Self::Istanbul => "Istanbul",
+            _ => "London",
+        }
+    }
+
+    pub fn in_network_range(expect: &[String], fork: MainnetFork) -> Result<bool, anyhow::Error> {
         let in_network = if expect.is_empty() {
             true
         } else {
             let mut in_network = false;
             for network in expect {
                 if let Some(network) = network.strip_prefix(">=") {
-                    if crate::utils::TEST_FORK >= crate::utils::MainnetFork::from_str(network)? {
+                    if fork >= crate::utils::MainnetFork::from_str(network)? {
                         in_network = true;
                     }
-                } else if crate::utils::TEST_FORK == crate::utils::MainnetFork::from_str(network)? {
+                } else if fork == crate::utils::MainnetFork::from_str(network)? {
                     in_network = true;
                 }
             }
@@ -200,7 +237,26 @@ mod test {
     use super::*;
     #[test]
     fn networks() {
-        assert!(MainnetFork::in_network_range(&[String::from(">=Istanbul")])
-            .expect("can parse network"));
+        assert!(
+            MainnetFork::in_network_range(&[String::from(">=Istanbul")], TEST_FORK)
+                .expect("can parse network")
+        );
+        assert!(
+            !MainnetFork::in_network_range(&[String::from(">=Shanghai")], MainnetFork::London)
+                .expect("can parse network")
+        );
+        assert_eq!(
+            MainnetFork::Merge.chain_config(1),
+            Some(ChainConfig::new(1, Hardfork::Merge))
+        );
+        assert_eq!(
+            MainnetFork::GrayGlacier.chain_config(1),
+            Some(ChainConfig::new(1, Hardfork::London))
+        );
+        assert_eq!(
+            MainnetFork::MuirGlacier.chain_config(1),
+            Some(ChainConfig::new(1, Hardfork::Istanbul))
+        );
+        assert_eq!(MainnetFork::Constantinople.chain_config(1), None);
     }
 }
@@ -1,21 +1,31 @@
-use cli_table::{print_stdout, Cell, Style, Table};
+use cli_table::{print_stdout, Cell, CellStruct, Style, Table};
 use eth_types::{bytecode, evm_types::OpcodeId, ToWord};
 use halo2_proofs::{
     halo2curves::bn256::Fr,
     plonk::{Circuit, ConstraintSystem},
 };
 mod helpers;
+#[cfg(feature = "mem-stats")]
+mod memory;
 use helpers::{bytecode_prefix_op_big_rws, print_circuit_stats_by_states};
 use itertools::Itertools;
 use mock::MOCK_ACCOUNTS;
 use std::env;
-use zkevm_circuits::evm_circuit::{
-    param::{
-        LOOKUP_CONFIG, N_COPY_COLUMNS, N_PHASE1_COLUMNS, N_PHASE2_COLUMNS, N_U16_LOOKUPS,
-        N_U8_LOOKUPS,
+use zkevm_circuits::{
+    bytecode_circuit::TestBytecodeCircuit,
+    copy_circuit::TestCopyCircuit,
+    evm_circuit::{
+        param::{
+            LOOKUP_CONFIG, N_COPY_COLUMNS, N_PHASE1_COLUMNS, N_PHASE2_COLUMNS, N_U16_LOOKUPS,
+            N_U8_LOOKUPS,
+        },
+        step::ExecutionState,
+        EvmCircuit,
     },
-    step::ExecutionState,
-    EvmCircuit,
+    exp_circuit::TestExpCircuit,
+    keccak_circuit::TestKeccakCircuit,
+    state_circuit::TestStateCircuit,
+    tx_circuit::TestTxCircuit,
 };
 fn main() {
     let args: Vec<String> = env::args().collect();
@@ -25,6 +35,10 @@ fn main() {
         "state" => state_states_stats(),
         "copy" => copy_states_stats(),
         "exec" => get_exec_steps_occupancy(),
+        "summary" => evm_states_summary(),
+        "circuits" => sub_circuits_summary(),
+        #[cfg(feature = "mem-stats")]
+        "memory" => memory::prover_memory_stats(),
         &_ => unreachable!("Unsupported arg"),
     }
 }
@@ -210,3 +224,93 @@ fn get_exec_steps_occupancy() {
         LOOKUP_CONFIG[7].1
     );
 }
+
+/// This function prints to stdout a table with, for each EVM ExecutionState,
+/// its height, the number of cells it uses, the number of lookups into each
+/// table and the max degree of its constraints.
+fn evm_states_summary() {
+    let mut meta = ConstraintSystem::<Fr>::default();
+    let circuit = EvmCircuit::configure(&mut meta);
+
+    let report = circuit.0.execution.instrument().clone().analyze();
+    let rows = report
+        .iter()
+        .map(|exec| {
+            let cells = exec.storage_1.used_cells
+                + exec.storage_2.used_cells
+                + exec.storage_perm.used_cells;
+            let lookups = [
+                ("u8", &exec.u8_lookup),
+                ("u16", &exec.u16_lookup),
+                ("fixed", &exec.fixed_table),
+                ("tx", &exec.tx_table),
+                ("rw", &exec.rw_table),
+                ("bytecode", &exec.bytecode_table),
+                ("block", &exec.block_table),
+                ("copy", &exec.copy_table),
+                ("keccak", &exec.keccak_table),
+                ("exp", &exec.exp_table),
+            ]
+            .into_iter()
+            .filter(|(_, row)| row.used_cells > 0)
+            .map(|(table, row)| format!("{}: {}", table, row.used_cells))
+            .join(", ");
+            vec![
+                format!("{:?}", exec.state).cell(),
+                exec.state.get_step_height_option().unwrap_or(0).cell(),
+                cells.cell(),
+                lookups.cell(),
+                exec.max_degree.cell(),
+            ]
+        })
+        .collect::<Vec<_>>();
+    let table = rows.table().title(vec![
+        "Execution State".cell().bold(true),
+        "Height".cell().bold(true),
+        "Cells".cell().bold(true),
+        "Lookups".cell().bold(true),
+        "Max Degree".cell().bold(true),
+    ]);
+    print_stdout(table).unwrap();
+}
+
+/// Returns the columns, gates and lookups of a configured circuit.
+fn circuit_summary<C: Circuit<Fr, Params = ()>>(name: &str) -> Vec<CellStruct> {
+    let mut meta = ConstraintSystem::<Fr>::default();
+    C::configure(&mut meta);
+    vec![
+        name.cell(),
+        meta.num_advice_columns().cell(),
+        meta.num_fixed_columns().cell(),
+        meta.num_selectors().cell(),
+        meta.num_instance_columns().cell(),
+        meta.gates().len().cell(),
+        meta.lookups().len().cell(),
+        meta.degree().cell(),
+    ]
+}
+
+/// This function prints to stdout a table with the totals of each sub-circuit
+/// configured on its own, with the tables it needs.
+fn sub_circuits_summary() {
+    let rows = vec![
+        circuit_summary::<EvmCircuit<Fr>>("EVM"),
+        circuit_summary::<TestStateCircuit<Fr>>("State"),
+        circuit_summary::<TestTxCircuit<Fr>>("Tx"),
+        circuit_summary::<TestBytecodeCircuit<Fr>>("Bytecode"),
+        circuit_summary::<TestCopyCircuit<Fr>>("Copy"),
+        circuit_summary::<TestKeccakCircuit<Fr>>("Keccak"),
+        circuit_summary::<TestExpCircuit<Fr>>("Exp"),
+    ];
+    let table = rows.table().title(vec![
+        "Circuit".cell().bold(true),
+        "Advice".cell().bold(true),
+        "Fixed".cell().bold(true),
+        "Selectors".cell().bold(true),
+        "Instance".cell().bold(true),
+        "Gates".cell().bold(true),
+        "Lookups".cell().bold(true),
+        "Degree".cell().bold(true),
+    ]);
+    print_stdout(table).unwrap();
+}
@@ -0,0 +1,215 @@
+//! Memory profiling of the phases of proving, enabled with the `mem-stats`
+//! feature.
+
+use bus_mapping::{circuit_input_builder::FixedCParams, mock::BlockData};
+use cli_table::{print_stdout, Cell, Style, Table};
+use eth_types::{bytecode, geth_types::GethData};
+use halo2_proofs::{
+    halo2curves::bn256::{Bn256, Fr, G1Affine},
+    plonk::{create_proof, keygen_pk, keygen_vk, verify_proof},
+    poly::kzg::{
+        commitment::{KZGCommitmentScheme, ParamsKZG},
+        multiopen::{ProverSHPLONK, VerifierSHPLONK},
+        strategy::SingleStrategy,
+    },
+    transcript::{
+        Blake2bRead, Blake2bWrite, Challenge255, TranscriptReadBuffer, TranscriptWriterBuffer,
+    },
+};
+use mock::test_ctx::TestContext;
+use rand::SeedableRng;
+use rand_xorshift::XorShiftRng;
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    fs,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+use zkevm_circuits::evm_circuit::{witness::block_convert, EvmCircuit};
+
+/// Allocator of the system that keeps track of the bytes allocated.
+struct CountingAllocator;
+
+/// Bytes currently allocated
+static ALLOCATED: AtomicUsize = AtomicUsize::new(0);
+/// Peak of the bytes allocated since the start of the current phase
+static PEAK: AtomicUsize = AtomicUsize::new(0);
+/// Total of the bytes allocated since the start of the current phase
+static TOTAL: AtomicUsize = AtomicUsize::new(0);
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+impl CountingAllocator {
+    fn record_alloc(size: usize) {
+        let allocated = ALLOCATED.fetch_add(size, Ordering::Relaxed) + size;
+        PEAK.fetch_max(allocated, Ordering::Relaxed);
+        TOTAL.fetch_add(size, Ordering::Relaxed);
+    }
+
+    fn record_dealloc(size: usize) {
+        ALLOCATED.fetch_sub(size, Ordering::Relaxed);
+    }
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc(layout);
+        if !ptr.is_null() {
+            Self::record_alloc(layout.size());
+        }
+        ptr
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc_zeroed(layout);
+        if !ptr.is_null() {
+            Self::record_alloc(layout.size());
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout);
+        Self::record_dealloc(layout.size());
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let new_ptr = System.realloc(ptr, layout, new_size);
+        if !new_ptr.is_null() {
+            Self::record_dealloc(layout.size());
+            Self::record_alloc(new_size);
+        }
+        new_ptr
+    }
+}
+
+/// Memory used by a phase of proving
+struct PhaseMemory {
+    name: &'static str,
+    /// Total of the bytes allocated in the phase
+    allocated: usize,
+    /// Peak of the bytes allocated in the phase
+    peak: usize,
+    /// Bytes still allocated at the end of the phase
+    retained: usize,
+    /// Peak resident set size of the process at the end of the phase, in KiB
+    peak_rss_kib: Option<u64>,
+}
+
+/// Peak resident set size of the process in KiB, only available on Linux.
+fn peak_rss_kib() -> Option<u64> {
+    let status = fs::read_to_string("/proc/self/status").ok()?;
+    status
+        .lines()
+        .find_map(|line| line.strip_prefix("VmHWM:"))
+        .and_then(|value| value.trim().trim_end_matches("kB").trim().parse().ok())
+}
+
+/// Run `phase` recording its memory usage in `phases`.
+fn measure<T>(phases: &mut Vec<PhaseMemory>, name: &'static str, phase: impl FnOnce() -> T) -> T {
+    let start = ALLOCATED.load(Ordering::Relaxed);
+    PEAK.store(start, Ordering::Relaxed);
+    TOTAL.store(0, Ordering::Relaxed);
+    let result = phase();
+    phases.push(PhaseMemory {
+        name,
+        allocated: TOTAL.load(Ordering::Relaxed),
+        peak: PEAK.load(Ordering::Relaxed),
+        retained: ALLOCATED.load(Ordering::Relaxed),
+        peak_rss_kib: peak_rss_kib(),
+    });
+    result
+}
+
+fn mib(bytes: usize) -> String {
+    format!("{:.1}", bytes as f64 / (1 << 20) as f64)
+}
+
+/// Prints the memory used by each phase of proving the EVM circuit for a
+/// block with a single transaction.  The proving phase includes the FFTs,
+/// MSMs and commitments done by halo2, which can't be told apart from here.
+pub(crate) fn prover_memory_stats() {
+    let mut phases = Vec::new();
+
+    let block = measure(&mut phases, "Witness generation", || {
+        let bytecode = bytecode! {
+            PUSH1(0x20)
+            PUSH1(0x00)
+            SHA3
+            POP
+            STOP
+        };
+        let geth_data: GethData = TestContext::<2, 1>::simple_ctx_with_bytecode(bytecode)
+            .unwrap()
+            .into();
+        let mut builder =
+            BlockData::new_from_geth_data_with_params(geth_data.clone(), FixedCParams::default())
+                .new_circuit_input_builder();
+        builder
+            .handle_block(&geth_data.eth_block, &geth_data.geth_traces)
+            .unwrap();
+        block_convert::<Fr>(&builder).unwrap()
+    });
+    let degree = block.get_test_degree();
+    let circuit = EvmCircuit::<Fr>::new(block);
+    let mut rng = XorShiftRng::seed_from_u64(2);
+
+    let params = measure(&mut phases, "Setup", || {
+        ParamsKZG::<Bn256>::setup(degree, &mut rng)
+    });
+    let pk = measure(&mut phases, "Key generation", || {
+        let vk = keygen_vk(&params, &circuit).expect("keygen_vk should not fail");
+        keygen_pk(&params, vk, &circuit).expect("keygen_pk should not fail")
+    });
+    let proof = measure(&mut phases, "Proving", || {
+        let mut transcript = Blake2bWrite::<_, G1Affine, Challenge255<_>>::init(vec![]);
+        create_proof::<KZGCommitmentScheme<Bn256>, ProverSHPLONK<'_, Bn256>, _, _, _, _>(
+            &params,
+            &pk,
+            &[circuit],
+            &[&[]],
+            rng,
+            &mut transcript,
+        )
+        .expect("proof generation should not fail");
+        transcript.finalize()
+    });
+    measure(&mut phases, "Verification", || {
+        let mut transcript = Blake2bRead::<_, G1Affine, Challenge255<_>>::init(&proof[..]);
+        verify_proof::<KZGCommitmentScheme<Bn256>, VerifierSHPLONK<'_, Bn256>, _, _, _>(
+            &params,
+            pk.get_vk(),
+            SingleStrategy::new(&params),
+            &[&[]],
+            &mut transcript,
+        )
+        .expect("failed to verify circuit");
+    });
+
+    let rows = phases
+        .iter()
+        .map(|phase| {
+            vec![
+                phase.name.cell(),
+                mib(phase.allocated).cell(),
+                mib(phase.peak).cell(),
+                mib(phase.retained).cell(),
+                phase
+                    .peak_rss_kib
+                    .map(|kib| format!("{:.1}", kib as f64 / 1024.0))
+                    .unwrap_or_else(|| "-".to_string())
+                    .cell(),
+            ]
+        })
+        .collect::<Vec<_>>();
+    let table = rows.table().title(vec![
+        format!("Phase (EVM circuit, k = {})", degree)
+            .cell()
+            .bold(true),
+        "Allocated MiB".cell().bold(true),
+        "Peak heap MiB".cell().bold(true),
+        "Retained MiB".cell().bold(true),
+        "Peak RSS MiB".cell().bold(true),
+    ]);
+    print_stdout(table).unwrap();
+}
@@ -1,3 +1,5 @@
+#[cfg(feature = "poseidon-codehash")]
+use crate::table::PoseidonTable;
 use crate::{
     evm_circuit::util::{
         and,
@@ -12,7 +14,7 @@ use crate::{
     },
     witness::{self},
 };
-use bus_mapping::state_db::{CodeDB, EMPTY_CODE_HASH_LE};
+use bus_mapping::state_db::{CodeDB, HashScheme, EMPTY_CODE_HASH_LE};
 use eth_types::{Bytecode, Field};
 use gadgets::is_zero::{IsZeroChip, IsZeroInstruction};
 use halo2_proofs::{
@@ -80,7 +82,9 @@ impl<F: Field> From<Vec<Bytecode>> for BytecodeCircuitAssignment<F> {
     fn from(codes: Vec<Bytecode>) -> Self {
         let mut rows = vec![];
         for bytecode in codes.iter() {
-            let code_hash = util::word::Word::from(bytecode.hash()).into_value();
+            let code_hash =
+                util::word::Word::from(HashScheme::default().hash_code(&bytecode.code()))
+                    .into_value();
             let code_size = bytecode.codesize();
             let head = BytecodeCircuitRow {
                 code_hash,
@@ -125,7 +129,7 @@ impl<F: Field> From<Vec<Bytecode>> for BytecodeCircuitAssignment<F> {
 
 impl<F: Field> From<CodeDB> for BytecodeCircuitAssignment<F> {
     fn from(code_db: CodeDB) -> Self {
-        // CodeDB use hash maps, so the bytecodes will be reordered.
+        // CodeDB iterates the bytecodes ordered by code hash.
         code_db.into_iter().collect_vec().into()
     }
 }
@@ -166,6 +170,8 @@ pub struct BytecodeCircuitConfig<F> {
     push_table: [Column<Fixed>; PUSH_TABLE_WIDTH],
     // External tables
     pub(crate) keccak_table: KeccakTable,
+    #[cfg(feature = "poseidon-codehash")]
+    pub(crate) poseidon_table: PoseidonTable,
 }
 
 /// Circuit configuration arguments
@@ -174,6 +180,9 @@ pub struct BytecodeCircuitConfigArgs<F: Field> {
     pub bytecode_table: BytecodeTable,
     /// KeccakTable
     pub keccak_table: KeccakTable,
+    /// PoseidonTable, looked up for the code hashes instead of the KeccakTable
+    #[cfg(feature = "poseidon-codehash")]
+    pub poseidon_table: PoseidonTable,
     /// Challenges
     pub challenges: Challenges<Expression<F>>,
 }
@@ -187,6 +196,8 @@ impl<F: Field> SubCircuitConfig<F> for BytecodeCircuitConfig<F> {
         Self::ConfigArgs {
             bytecode_table,
             keccak_table,
+            #[cfg(feature = "poseidon-codehash")]
+            poseidon_table,
             challenges,
         }: Self::ConfigArgs,
     ) -> Self {
@@ -205,6 +216,8 @@ impl<F: Field> SubCircuitConfig<F> for BytecodeCircuitConfig<F> {
         // annotate columns
         bytecode_table.annotate_columns(meta);
         keccak_table.annotate_columns(meta);
+        #[cfg(feature = "poseidon-codehash")]
+        poseidon_table.annotate_columns(meta);
         push_table.iter().enumerate().for_each(|(idx, &col)| {
             meta.annotate_lookup_any_column(col, || format!("push_table_{}", idx))
         });
@@ -498,7 +511,7 @@ impl<F: Field> SubCircuitConfig<F> for BytecodeCircuitConfig<F> {
 
         // When is_byte_to_header ->
         // assert cur.index + 1 == cur.length
-        // assert keccak256_table_lookup(cur.hash, cur.length, cur.value_rlc)
+        // assert code_hash_table_lookup(cur.hash, cur.length, cur.value_rlc)
         meta.create_gate("Byte to Header row", |meta| {
             let mut cb = BaseConstraintBuilder::default();
 
@@ -514,32 +527,41 @@ impl<F: Field> SubCircuitConfig<F> for BytecodeCircuitConfig<F> {
                 is_byte_to_header(meta),
             ]))
         });
-        meta.lookup_any(
+        // The code hashes are looked up in the poseidon table instead of the
+        // keccak table with the poseidon-codehash feature.
+        #[cfg(not(feature = "poseidon-codehash"))]
+        let (code_hash_name, code_hash_is_enabled, code_hash_columns) = (
             "keccak256_table_lookup(cur.value_rlc, cur.length, cur.hash_word)",
-            |meta| {
-                let enable = and::expr(vec![
-                    meta.query_fixed(q_enable, Rotation::cur()),
-                    not::expr(meta.query_fixed(q_last, Rotation::cur())),
-                    is_byte_to_header(meta),
-                ]);
-
-                let mut constraints = vec![(
-                    enable.clone(),
-                    meta.query_advice(keccak_table.is_enabled, Rotation::cur()),
-                )];
-
-                for (circuit_column, table_column) in
-                    keccak_table.match_columns(value_rlc, length, bytecode_table.code_hash)
-                {
-                    constraints.push((
-                        enable.clone() * meta.query_advice(circuit_column, Rotation::cur()),
-                        meta.query_advice(table_column, Rotation::cur()),
-                    ))
-                }
-
-                constraints
-            },
+            keccak_table.is_enabled,
+            keccak_table.match_columns(value_rlc, length, bytecode_table.code_hash),
+        );
+        #[cfg(feature = "poseidon-codehash")]
+        let (code_hash_name, code_hash_is_enabled, code_hash_columns) = (
+            "poseidon_table_lookup(cur.value_rlc, cur.length, cur.hash_word)",
+            poseidon_table.is_enabled,
+            poseidon_table.match_columns(value_rlc, length, bytecode_table.code_hash),
         );
+        meta.lookup_any(code_hash_name, |meta| {
+            let enable = and::expr(vec![
+                meta.query_fixed(q_enable, Rotation::cur()),
+                not::expr(meta.query_fixed(q_last, Rotation::cur())),
+                is_byte_to_header(meta),
+            ]);
+
+            let mut constraints = vec![(
+                enable.clone(),
+                meta.query_advice(code_hash_is_enabled, Rotation::cur()),
+            )];
+
+            for &(circuit_column, table_column) in code_hash_columns.iter() {
+                constraints.push((
+                    enable.clone() * meta.query_advice(circuit_column, Rotation::cur()),
+                    meta.query_advice(table_column, Rotation::cur()),
+                ))
+            }
+
+            constraints
+        });
 
         let push_data_left_is_zero = IsZeroChip::construct(push_data_left_is_zero);
         let index_length_diff_is_zero = IsZeroChip::construct(index_length_diff_is_zero);
@@ -558,6 +580,8 @@ impl<F: Field> SubCircuitConfig<F> for BytecodeCircuitConfig<F> {
             index_length_diff_is_zero,
             push_table,
             keccak_table,
+            #[cfg(feature = "poseidon-codehash")]
+            poseidon_table,
         }
     }
 }
@@ -717,6 +741,8 @@ impl<F: Field> BytecodeCircuitConfig<F> {
     fn annotate_circuit(&self, region: &mut Region<F>) {
         self.bytecode_table.annotate_columns_in_region(region);
         self.keccak_table.annotate_columns_in_region(region);
+        #[cfg(feature = "poseidon-codehash")]
+        self.poseidon_table.annotate_columns_in_region(region);
 
         self.push_data_left_is_zero
             .annotate_columns_in_region(region, "BYTECODE");
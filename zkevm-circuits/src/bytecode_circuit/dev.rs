@@ -1,5 +1,7 @@
 pub use super::circuit::BytecodeCircuit;
 
+#[cfg(feature = "poseidon-codehash")]
+use crate::table::PoseidonTable;
 use crate::{
     bytecode_circuit::circuit::{BytecodeCircuitConfig, BytecodeCircuitConfigArgs},
     table::{BytecodeTable, KeccakTable},
@@ -24,6 +26,8 @@ impl<F: Field> Circuit<F> for BytecodeCircuit<F> {
     fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
         let bytecode_table = BytecodeTable::construct(meta);
         let keccak_table = KeccakTable::construct(meta);
+        #[cfg(feature = "poseidon-codehash")]
+        let poseidon_table = PoseidonTable::construct(meta);
         let challenges = Challenges::construct(meta);
 
         let config = {
@@ -33,6 +37,8 @@ impl<F: Field> Circuit<F> for BytecodeCircuit<F> {
                 BytecodeCircuitConfigArgs {
                     bytecode_table,
                     keccak_table,
+                    #[cfg(feature = "poseidon-codehash")]
+                    poseidon_table,
                     challenges,
                 },
             )
@@ -48,16 +54,19 @@ impl<F: Field> Circuit<F> for BytecodeCircuit<F> {
     ) -> Result<(), Error> {
         let challenges = challenges.values(&mut layouter);
 
-        config.keccak_table.dev_load(
-            &mut layouter,
-            &self
-                .bytecodes
-                .clone()
-                .into_iter()
-                .map(|b| b.code())
-                .collect_vec(),
-            &challenges,
-        )?;
+        let codes = self
+            .bytecodes
+            .clone()
+            .into_iter()
+            .map(|b| b.code())
+            .collect_vec();
+        config
+            .keccak_table
+            .dev_load(&mut layouter, &codes, &challenges)?;
+        #[cfg(feature = "poseidon-codehash")]
+        config
+            .poseidon_table
+            .dev_load(&mut layouter, &codes, &challenges)?;
         self.synthesize_sub(&config, &challenges, &mut layouter)?;
         Ok(())
     }
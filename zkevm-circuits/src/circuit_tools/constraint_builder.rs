@@ -11,7 +11,7 @@ use crate::{
     util::{word::Word, Expr},
 };
 use eth_types::Field;
-use gadgets::util::{and, sum, Scalar};
+use gadgets::util::{and, degree, sum, Scalar};
 use halo2_proofs::{
     plonk::{ConstraintSystem, Expression},
     poly::Rotation,
@@ -267,13 +267,7 @@ impl<F: Field, C: CellType> ConstraintBuilder<F, C> {
 
     pub(crate) fn validate_degree(&self, degree: usize, name: &'static str) {
         if self.max_global_degree > 0 && self.region_id != 0 {
-            debug_assert!(
-                degree <= self.max_degree,
-                "Expression {} degree too high: {} > {}",
-                name,
-                degree,
-                self.max_degree,
-            );
+            degree::validate(name, degree, self.max_degree);
         }
     }
 
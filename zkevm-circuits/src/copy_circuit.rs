@@ -12,10 +12,7 @@ pub use dev::CopyCircuit as TestCopyCircuit;
 
 use crate::{
     evm_circuit::util::constraint_builder::{BaseConstraintBuilder, ConstrainBuilderCommon},
-    table::{
-        BytecodeFieldTag, BytecodeTable, CopyTable, LookupTable, RwTable, TxContextFieldTag,
-        TxTable,
-    },
+    table::{BytecodeTable, CopyTable, LookupTable, RwTable, TxContextFieldTag, TxTable, UXTable},
     util::{Challenges, SubCircuit, SubCircuitConfig},
     witness,
     witness::{RwMap, Transaction},
@@ -29,6 +26,7 @@ use eth_types::Field;
 use gadgets::{
     binary_number::BinaryNumberChip,
     less_than::{LtChip, LtConfig, LtInstruction},
+    range_check_chip::RangeCheckConfig,
     util::{and, not, or, Expr},
 };
 use halo2_proofs::{
@@ -72,6 +70,8 @@ pub struct CopyCircuitConfig<F> {
     pub rw_table: RwTable,
     /// BytecodeTable
     pub bytecode_table: BytecodeTable,
+    /// u8 table, shared with the other circuits
+    pub u8_table: UXTable<8>,
 }
 
 /// Circuit configuration arguments
@@ -84,6 +84,8 @@ pub struct CopyCircuitConfigArgs<F: Field> {
     pub bytecode_table: BytecodeTable,
     /// CopyTable
     pub copy_table: CopyTable,
+    /// u8 table
+    pub u8_table: UXTable<8>,
     /// q_enable
     pub q_enable: Column<Fixed>,
     /// Challenges
@@ -102,6 +104,7 @@ impl<F: Field> SubCircuitConfig<F> for CopyCircuitConfig<F> {
             rw_table,
             bytecode_table,
             copy_table,
+            u8_table,
             q_enable,
             challenges,
         }: Self::ConfigArgs,
@@ -128,11 +131,12 @@ impl<F: Field> SubCircuitConfig<F> for CopyCircuitConfig<F> {
         bytecode_table.annotate_columns(meta);
         copy_table.annotate_columns(meta);
 
-        let addr_lt_addr_end = LtChip::configure(
+        let addr_lt_addr_end = LtChip::configure_with_u8_table(
             meta,
             |meta| meta.query_selector(q_step),
             |meta| meta.query_advice(addr, Rotation::cur()),
             |meta| meta.query_advice(src_addr_end, Rotation::cur()),
+            RangeCheckConfig::from(u8_table),
         );
 
         meta.create_gate("verify row", |meta| {
@@ -227,6 +231,34 @@ impl<F: Field> SubCircuitConfig<F> for CopyCircuitConfig<F> {
                     rw_diff,
                 );
             });
+            // Padding rows don't belong to any copy event, so a copy event running into them
+            // needs to have used up all of its rw counter increase.
+            cb.condition(
+                tag.value_equals(CopyDataType::Padding, Rotation::cur())(meta),
+                |cb| {
+                    cb.require_zero(
+                        "rwc_inc_left == 0 for padding rows",
+                        meta.query_advice(rwc_inc_left, Rotation::cur()),
+                    );
+                },
+            );
+            // The rows after the last enabled row are unconstrained, so the last copy event
+            // needs to be closed on the last enabled row.
+            cb.condition(
+                and::expr([
+                    not::expr(meta.query_fixed(q_enable, Rotation::next())),
+                    not::expr(tag.value_equals(CopyDataType::Padding, Rotation::cur())(
+                        meta,
+                    )),
+                ]),
+                |cb| {
+                    cb.require_equal(
+                        "is_last == 1 for the last enabled row",
+                        meta.query_advice(is_last, Rotation::cur()),
+                        1.expr(),
+                    );
+                },
+            );
 
             cb.gate(meta.query_fixed(q_enable, Rotation::cur()))
         });
@@ -387,14 +419,12 @@ impl<F: Field> SubCircuitConfig<F> for CopyCircuitConfig<F> {
             let cond = meta.query_fixed(q_enable, Rotation::cur())
                 * tag.value_equals(CopyDataType::Bytecode, Rotation::cur())(meta)
                 * not::expr(meta.query_advice(is_pad, Rotation::cur()));
-            vec![
-                meta.query_advice(id.lo(), Rotation::cur()),
-                meta.query_advice(id.hi(), Rotation::cur()),
-                BytecodeFieldTag::Byte.expr(),
+            BytecodeTable::lookup_code(
+                id.map(|limb| meta.query_advice(limb, Rotation::cur())),
                 meta.query_advice(addr, Rotation::cur()),
-                meta.query_advice(is_code, Rotation::cur()),
                 meta.query_advice(value, Rotation::cur()),
-            ]
+                meta.query_advice(is_code, Rotation::cur()),
+            )
             .into_iter()
             .zip_eq(bytecode_table.table_exprs(meta).into_iter())
             .map(|(arg, table)| (cond.clone() * arg, table))
@@ -464,6 +494,7 @@ impl<F: Field> SubCircuitConfig<F> for CopyCircuitConfig<F> {
             tx_table,
             rw_table,
             bytecode_table,
+            u8_table,
         }
     }
 }
@@ -575,8 +606,6 @@ impl<F: Field> CopyCircuitConfig<F> {
         let tag_chip = BinaryNumberChip::construct(self.copy_table.tag);
         let lt_chip = LtChip::construct(self.addr_lt_addr_end);
 
-        lt_chip.load(layouter)?;
-
         layouter.assign_region(
             || "assign copy table",
             |mut region| {
@@ -748,6 +777,8 @@ pub struct ExternalData {
     pub max_calldata: usize,
     /// TxCircuit -> txs
     pub txs: Vec<Transaction>,
+    /// TxCircuit -> chain_id
+    pub chain_id: u64,
     /// StateCircuit -> max_rws
     pub max_rws: usize,
     /// StateCircuit -> rws
@@ -822,6 +853,7 @@ impl<F: Field> SubCircuit<F> for CopyCircuit<F> {
                 max_txs: block.circuits_params.max_txs,
                 max_calldata: block.circuits_params.max_calldata,
                 txs: block.txs.clone(),
+                chain_id: block.context.chain_id.as_u64(),
                 max_rws: block.circuits_params.max_rws,
                 rws: block.rws.clone(),
                 bytecodes: block.bytecodes.clone(),
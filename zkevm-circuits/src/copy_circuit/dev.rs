@@ -2,7 +2,7 @@ pub use super::CopyCircuit;
 
 use crate::{
     copy_circuit::{CopyCircuitConfig, CopyCircuitConfigArgs},
-    table::{BytecodeTable, CopyTable, RwTable, TxTable},
+    table::{BytecodeTable, CopyTable, RwTable, TxTable, UXTable},
     util::{Challenges, SubCircuit, SubCircuitConfig},
 };
 use eth_types::Field;
@@ -26,6 +26,7 @@ impl<F: Field> Circuit<F> for CopyCircuit<F> {
         let bytecode_table = BytecodeTable::construct(meta);
         let q_enable = meta.fixed_column();
         let copy_table = CopyTable::construct(meta, q_enable);
+        let u8_table = UXTable::construct(meta);
         let challenges = Challenges::construct(meta);
         let challenge_exprs = challenges.exprs(meta);
 
@@ -37,6 +38,7 @@ impl<F: Field> Circuit<F> for CopyCircuit<F> {
                     rw_table,
                     bytecode_table,
                     copy_table,
+                    u8_table,
                     q_enable,
                     challenges: challenge_exprs,
                 },
@@ -57,6 +59,7 @@ impl<F: Field> Circuit<F> for CopyCircuit<F> {
             &self.external_data.txs,
             self.external_data.max_txs,
             self.external_data.max_calldata,
+            self.external_data.chain_id,
         )?;
 
         config.0.rw_table.load(
@@ -69,6 +72,7 @@ impl<F: Field> Circuit<F> for CopyCircuit<F> {
             .0
             .bytecode_table
             .load(&mut layouter, self.external_data.bytecodes.clone())?;
+        config.0.u8_table.load(&mut layouter)?;
         self.synthesize_sub(&config.0, &challenge_values, &mut layouter)
     }
 }
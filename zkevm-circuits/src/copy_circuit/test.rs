@@ -52,6 +52,7 @@ pub fn test_copy_circuit_from_block<F: Field>(
             max_txs: block.circuits_params.max_txs,
             max_calldata: block.circuits_params.max_calldata,
             txs: block.txs,
+            chain_id: block.context.chain_id.as_u64(),
             max_rws: block.circuits_params.max_rws,
             rws: block.rws,
             bytecodes: block.bytecodes,
@@ -207,6 +208,36 @@ fn copy_circuit_valid_tx_log() {
     assert_eq!(test_copy_circuit_from_block(10, block), Ok(()));
 }
 
+#[test]
+fn copy_circuit_valid_without_padding_rows() {
+    let builder = gen_tx_log_data();
+    let block = block_convert::<Fr>(&builder).unwrap();
+    // Only the two disabled rows at the end, no padding rows
+    let max_copy_rows = block
+        .copy_events
+        .iter()
+        .map(|c| c.bytes.len() * 2)
+        .sum::<usize>()
+        + 2;
+    assert_eq!(
+        test_copy_circuit::<Fr>(
+            10,
+            block.copy_events,
+            max_copy_rows,
+            ExternalData {
+                max_txs: block.circuits_params.max_txs,
+                max_calldata: block.circuits_params.max_calldata,
+                txs: block.txs,
+                chain_id: block.context.chain_id.as_u64(),
+                max_rws: block.circuits_params.max_rws,
+                rws: block.rws,
+                bytecodes: block.bytecodes,
+            },
+        ),
+        Ok(())
+    );
+}
+
 #[test]
 fn copy_circuit_invalid_calldatacopy() {
     let mut builder = gen_calldatacopy_data();
@@ -26,17 +26,74 @@ use crate::{
     },
     util::{Challenges, SubCircuit, SubCircuitConfig},
 };
-use bus_mapping::evm::OpcodeId;
+use bus_mapping::{
+    chain_config::{ChainConfig, Eip},
+    circuit_input_builder::L1DataFeePolicy,
+    evm::OpcodeId,
+    operation::MemoryOpMode,
+};
 use eth_types::Field;
 use execution::ExecutionConfig;
 use itertools::Itertools;
+use std::collections::BTreeSet;
 use strum::IntoEnumIterator;
-use table::FixedTableTag;
+use table::{FixedTableProvider, FixedTableTag};
 use witness::Block;
 
+/// Features of the EVM circuit selected at configuration time.  They change
+/// the constraints, and so the verifying key, and must match the ones the
+/// witness block was generated with.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FeatureConfig {
+    /// L1 data fee charged by the fee policy of the block.  When set, BeginTx
+    /// and EndTx look up the balance writes of the sender and of the fee
+    /// vault.
+    pub l1_data_fee: Option<L1DataFeePolicy>,
+    /// EIPs of the chain config, which select the hardfork rules constrained
+    /// by the gadgets (warm coinbase, init code gas, refund quotient) and the
+    /// opcodes they accept.
+    pub eips: BTreeSet<Eip>,
+    /// Granularity of the memory rws of `MLOAD`, `MSTORE` and `MSTORE8`,
+    /// which the MemoryGadget looks up per byte or per 32-byte aligned word.
+    pub memory_op_mode: MemoryOpMode,
+}
+
+impl Default for FeatureConfig {
+    fn default() -> Self {
+        Self {
+            l1_data_fee: None,
+            eips: ChainConfig::default().eips,
+            memory_op_mode: MemoryOpMode::default(),
+        }
+    }
+}
+
+impl FeatureConfig {
+    /// Returns the features `block` was generated with.
+    pub fn from_block<F>(block: &Block<F>) -> Self {
+        Self {
+            l1_data_fee: block.l1_data_fee.clone(),
+            eips: block.chain_config.eips.clone(),
+            memory_op_mode: block.memory_op_mode,
+        }
+    }
+
+    /// Return whether `eip` is enabled.
+    pub fn is_enabled(&self, eip: Eip) -> bool {
+        self.eips.contains(&eip)
+    }
+
+    /// Return whether `opcode` is enabled, see
+    /// [`ChainConfig::is_opcode_enabled`].
+    pub fn is_opcode_enabled(&self, opcode: OpcodeId) -> bool {
+        Eip::introducing(opcode).map_or(true, |eip| self.is_enabled(eip))
+    }
+}
+
 /// EvmCircuitConfig implements verification of execution trace of a block.
 #[derive(Clone, Debug)]
 pub struct EvmCircuitConfig<F> {
+    feature_config: FeatureConfig,
     fixed_table: [Column<Fixed>; 4],
     u8_table: UXTable<8>,
     u16_table: UXTable<16>,
@@ -74,6 +131,8 @@ pub struct EvmCircuitConfigArgs<F: Field> {
     pub u8_table: UXTable<8>,
     /// U16Table
     pub u16_table: UXTable<16>,
+    /// Features of the circuit
+    pub feature_config: FeatureConfig,
 }
 
 impl<F: Field> SubCircuitConfig<F> for EvmCircuitConfig<F> {
@@ -93,12 +152,14 @@ impl<F: Field> SubCircuitConfig<F> for EvmCircuitConfig<F> {
             exp_table,
             u8_table,
             u16_table,
+            feature_config,
         }: Self::ConfigArgs,
     ) -> Self {
         let fixed_table = [(); 4].map(|_| meta.fixed_column());
         let execution = Box::new(ExecutionConfig::configure(
             meta,
             challenges,
+            &feature_config,
             &fixed_table,
             &u8_table,
             &u16_table,
@@ -127,6 +188,7 @@ impl<F: Field> SubCircuitConfig<F> for EvmCircuitConfig<F> {
         u16_table.annotate_columns(meta);
 
         Self {
+            feature_config,
             fixed_table,
             u8_table,
             u16_table,
@@ -143,21 +205,29 @@ impl<F: Field> SubCircuitConfig<F> for EvmCircuitConfig<F> {
 }
 
 impl<F: Field> EvmCircuitConfig<F> {
-    /// Load fixed table
+    /// Load fixed table, skipping the tags which no execution state looks up.
+    /// The rows are taken from the shared [`FixedTableProvider`].
     pub fn load_fixed_table(
         &self,
         layouter: &mut impl Layouter<F>,
         fixed_table_tags: Vec<FixedTableTag>,
     ) -> Result<(), Error> {
+        let used_tags = self.execution.used_fixed_table_tags();
+        let provider = FixedTableProvider::<F>::shared();
+        let tables: Vec<_> = fixed_table_tags
+            .into_iter()
+            .filter(|tag| used_tags.contains(tag))
+            .map(|tag| provider.rows(tag))
+            .collect();
         layouter.assign_region(
             || "fixed table",
             |mut region| {
-                for (offset, row) in std::iter::once([F::ZERO; 4])
-                    .chain(fixed_table_tags.iter().flat_map(|tag| tag.build()))
+                for (offset, row) in std::iter::once(&[F::ZERO; 4])
+                    .chain(tables.iter().flat_map(|rows| rows.iter()))
                     .enumerate()
                 {
                     for (column, value) in self.fixed_table.iter().zip_eq(row) {
-                        region.assign_fixed(|| "", *column, offset, || Value::known(value))?;
+                        region.assign_fixed(|| "", *column, offset, || Value::known(*value))?;
                     }
                 }
 
@@ -173,12 +243,14 @@ pub struct EvmCircuit<F: Field> {
     /// Block
     pub block: Option<Block<F>>,
     fixed_table_tags: Vec<FixedTableTag>,
+    feature_config: FeatureConfig,
 }
 
 impl<F: Field> EvmCircuit<F> {
     /// Return a new EvmCircuit
     pub fn new(block: Block<F>) -> Self {
         Self {
+            feature_config: FeatureConfig::from_block(&block),
             block: Some(block),
             fixed_table_tags: FixedTableTag::iter().collect(),
         }
@@ -189,6 +261,7 @@ impl<F: Field> EvmCircuit<F> {
     pub(crate) fn get_test_circuit_from_block(block: Block<F>) -> Self {
         let fixed_table_tags = detect_fixed_table_tags(&block);
         Self {
+            feature_config: FeatureConfig::from_block(&block),
             block: Some(block),
             fixed_table_tags,
         }
@@ -244,10 +317,8 @@ impl<F: Field> SubCircuit<F> for EvmCircuit<F> {
     /// Return the minimum number of rows required to prove the block
     fn min_num_rows_block(block: &witness::Block<F>) -> (usize, usize) {
         let num_rows_required_for_execution_steps: usize = Self::get_num_rows_required(block);
-        let num_rows_required_for_fixed_table: usize = detect_fixed_table_tags(block)
-            .iter()
-            .map(|tag| tag.build::<F>().count())
-            .sum();
+        let num_rows_required_for_fixed_table: usize =
+            FixedTableProvider::<F>::shared().num_rows(&detect_fixed_table_tags(block));
         (
             std::cmp::max(
                 num_rows_required_for_execution_steps,
@@ -265,6 +336,14 @@ impl<F: Field> SubCircuit<F> for EvmCircuit<F> {
         layouter: &mut impl Layouter<F>,
     ) -> Result<(), Error> {
         let block = self.block.as_ref().unwrap();
+        if FeatureConfig::from_block(block) != config.feature_config {
+            log::error!(
+                "EvmCircuit configured with {:?}, but the block requires {:?}",
+                config.feature_config,
+                FeatureConfig::from_block(block)
+            );
+            return Err(Error::Synthesis);
+        }
 
         config.load_fixed_table(layouter, self.fixed_table_tags.clone())?;
         config.execution.assign_block(layouter, block, challenges)
@@ -320,17 +399,34 @@ pub(crate) mod cached {
     /// results of the configuration which are cached in the public variable
     /// `CACHE`.  This wrapper is useful for testing because it allows running
     /// many unit tests while reusing the configuration step of the circuit.
+    /// Only the circuit with the default [`FeatureConfig`] is cached, the
+    /// other ones are configured as usual.
     pub struct EvmCircuitCached(EvmCircuit<Fr>);
 
     impl Circuit<Fr> for EvmCircuitCached {
         type Config = (EvmCircuitConfig<Fr>, Challenges);
         type FloorPlanner = SimpleFloorPlanner;
-        type Params = ();
+        type Params = FeatureConfig;
 
         fn without_witnesses(&self) -> Self {
             Self(self.0.without_witnesses())
         }
 
+        fn params(&self) -> Self::Params {
+            self.0.params()
+        }
+
+        fn configure_with_params(
+            meta: &mut ConstraintSystem<Fr>,
+            params: Self::Params,
+        ) -> Self::Config {
+            if params == FeatureConfig::default() {
+                Self::configure(meta)
+            } else {
+                EvmCircuit::configure_with_params(meta, params)
+            }
+        }
+
         fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
             *meta = CACHE.cs.clone();
             CACHE.config.clone()
@@ -356,13 +452,24 @@ pub(crate) mod cached {
 impl<F: Field> Circuit<F> for EvmCircuit<F> {
     type Config = (EvmCircuitConfig<F>, Challenges);
     type FloorPlanner = SimpleFloorPlanner;
-    type Params = ();
+    type Params = FeatureConfig;
 
     fn without_witnesses(&self) -> Self {
-        Self::default()
+        Self {
+            feature_config: self.feature_config.clone(),
+            ..Self::default()
+        }
+    }
+
+    fn params(&self) -> Self::Params {
+        self.feature_config.clone()
     }
 
     fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        Self::configure_with_params(meta, FeatureConfig::default())
+    }
+
+    fn configure_with_params(meta: &mut ConstraintSystem<F>, params: Self::Params) -> Self::Config {
         let tx_table = TxTable::construct(meta);
         let rw_table = RwTable::construct(meta);
         let bytecode_table = BytecodeTable::construct(meta);
@@ -390,6 +497,7 @@ impl<F: Field> Circuit<F> for EvmCircuit<F> {
                     exp_table,
                     u8_table,
                     u16_table,
+                    feature_config: params,
                 },
             ),
             challenges,
@@ -411,6 +519,7 @@ impl<F: Field> Circuit<F> for EvmCircuit<F> {
             &block.txs,
             block.circuits_params.max_txs,
             block.circuits_params.max_calldata,
+            block.context.chain_id.as_u64(),
         )?;
         block.rws.check_rw_counter_sanity();
         config.rw_table.load(
@@ -438,7 +547,11 @@ impl<F: Field> Circuit<F> for EvmCircuit<F> {
 #[cfg(test)]
 mod evm_circuit_stats {
     use crate::{
-        evm_circuit::EvmCircuit,
+        evm_circuit::{
+            step::ExecutionState,
+            table::{FixedTableProvider, FixedTableTag},
+            EvmCircuit, FeatureConfig,
+        },
         test_util::CircuitTestBuilder,
         util::{unusable_rows, SubCircuit},
         witness::block_convert,
@@ -446,7 +559,13 @@ mod evm_circuit_stats {
     use bus_mapping::{circuit_input_builder::FixedCParams, mock::BlockData};
 
     use eth_types::{bytecode, geth_types::GethData};
-    use halo2_proofs::{self, dev::MockProver, halo2curves::bn256::Fr};
+    use halo2_proofs::{
+        self,
+        dev::MockProver,
+        halo2curves::bn256::Fr,
+        plonk::{Circuit, ConstraintSystem},
+    };
+    use std::sync::Arc;
 
     use mock::test_ctx::{
         helpers::{account_0_code_account_1_no_code, tx_from_1_to_0},
@@ -457,7 +576,7 @@ mod evm_circuit_stats {
     fn evm_circuit_unusable_rows() {
         assert_eq!(
             EvmCircuit::<Fr>::unusable_rows(),
-            unusable_rows::<Fr, EvmCircuit::<Fr>>(()),
+            unusable_rows::<Fr, EvmCircuit::<Fr>>(FeatureConfig::default()),
         )
     }
 
@@ -525,4 +644,91 @@ mod evm_circuit_stats {
         assert_eq!(prover1.fixed(), prover2.fixed());
         assert_eq!(prover1.permutation(), prover2.permutation());
     }
+    #[test]
+    fn fixed_table_tags_of_the_gadgets() {
+        let mut meta = ConstraintSystem::<Fr>::default();
+        let (config, _) = EvmCircuit::<Fr>::configure(&mut meta);
+        let used_tags = config.execution.used_fixed_table_tags();
+        for tag in [
+            FixedTableTag::BitwiseAnd,
+            FixedTableTag::BitwiseOr,
+            FixedTableTag::BitwiseXor,
+            FixedTableTag::ResponsibleOpcode,
+            FixedTableTag::Pow2,
+            FixedTableTag::ExecutionStateTransition,
+        ] {
+            assert!(used_tags.contains(&tag), "{:?} not used", tag);
+        }
+
+        // The rows are built once and shared
+        let rows = FixedTableProvider::<Fr>::shared().rows(FixedTableTag::BitwiseAnd);
+        assert_eq!(rows.len(), 256 * 256);
+        assert!(Arc::ptr_eq(
+            &rows,
+            &FixedTableProvider::<Fr>::shared().rows(FixedTableTag::BitwiseAnd)
+        ));
+    }
+
+    #[test]
+    fn execution_state_transition_table() {
+        let rows = FixedTableTag::ExecutionStateTransition
+            .build::<Fr>()
+            .collect::<Vec<_>>();
+        let has_transition = |from: ExecutionState, to: ExecutionState| {
+            rows.iter()
+                .any(|row| row[1] == Fr::from(from.as_u64()) && row[2] == Fr::from(to.as_u64()))
+        };
+
+        assert!(has_transition(
+            ExecutionState::BeginTx,
+            ExecutionState::ADD_SUB
+        ));
+        assert!(has_transition(
+            ExecutionState::BeginTx,
+            ExecutionState::EndTx
+        ));
+        assert!(has_transition(ExecutionState::STOP, ExecutionState::EndTx));
+        assert!(has_transition(
+            ExecutionState::ErrorInvalidJump,
+            ExecutionState::EndTx
+        ));
+        assert!(has_transition(
+            ExecutionState::EndTx,
+            ExecutionState::BeginTx
+        ));
+        assert!(has_transition(
+            ExecutionState::EndTx,
+            ExecutionState::EndBlock
+        ));
+        assert!(has_transition(
+            ExecutionState::EndBlock,
+            ExecutionState::EndBlock
+        ));
+
+        // A transaction can't end without halting, nor skip its EndTx
+        assert!(!has_transition(
+            ExecutionState::ADD_SUB,
+            ExecutionState::EndTx
+        ));
+        assert!(!has_transition(
+            ExecutionState::STOP,
+            ExecutionState::BeginTx
+        ));
+        assert!(!has_transition(
+            ExecutionState::ADD_SUB,
+            ExecutionState::EndBlock
+        ));
+        assert!(!has_transition(
+            ExecutionState::BeginTx,
+            ExecutionState::BeginTx
+        ));
+        assert!(!has_transition(
+            ExecutionState::EndTx,
+            ExecutionState::ADD_SUB
+        ));
+        assert!(!has_transition(
+            ExecutionState::EndBlock,
+            ExecutionState::BeginTx
+        ));
+    }
 }
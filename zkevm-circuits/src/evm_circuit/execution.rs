@@ -5,13 +5,16 @@ use super::{
         N_U8_LOOKUPS, RW_TABLE_LOOKUPS, TX_TABLE_LOOKUPS,
     },
     step::HasExecutionState,
-    util::{instrumentation::Instrument, CachedRegion, StoredExpression},
+    util::{
+        instrumentation::Instrument, replay_assignments, AdviceAssignment, CachedRegion,
+        StoredExpression,
+    },
 };
 use crate::{
     evm_circuit::{
         param::{EVM_LOOKUP_COLS, MAX_STEP_HEIGHT, N_PHASE2_COLUMNS, STEP_WIDTH},
         step::{ExecutionState, Step},
-        table::Table,
+        table::{FixedTableTag, Lookup, Table},
         util::{
             constraint_builder::{
                 BaseConstraintBuilder, ConstrainBuilderCommon, EVMConstraintBuilder,
@@ -19,6 +22,7 @@ use crate::{
             evaluate_expression, rlc,
         },
         witness::{Block, Call, ExecStep, Transaction},
+        FeatureConfig,
     },
     table::LookupTable,
     util::{
@@ -30,13 +34,17 @@ use bus_mapping::operation::Target;
 use eth_types::{evm_unimplemented, Field};
 use gadgets::util::not;
 use halo2_proofs::{
-    circuit::{Layouter, Region, Value},
+    circuit::{
+        layouter::{RegionLayouter, RegionShape},
+        Layouter, Region, RegionIndex, Value,
+    },
     plonk::{
         Advice, Column, ConstraintSystem, Error, Expression, FirstPhase, Fixed, SecondPhase,
         Selector, ThirdPhase, VirtualCells,
     },
     poly::Rotation,
 };
+use rayon::prelude::*;
 use std::{
     collections::{BTreeSet, HashMap},
     iter,
@@ -192,6 +200,10 @@ use sstore::SstoreGadget;
 use stop::StopGadget;
 use swap::SwapGadget;
 
+/// Number of steps whose witness is computed in parallel before being
+/// assigned, which bounds the memory holding the computed assignments.
+const STEP_WITNESS_CHUNK_SIZE: usize = 1 << 12;
+
 pub(crate) trait ExecutionGadget<F: Field> {
     const NAME: &'static str;
 
@@ -232,6 +244,7 @@ pub struct ExecutionConfig<F> {
     pub(crate) height_map: HashMap<ExecutionState, usize>,
     stored_expressions_map: HashMap<ExecutionState, Vec<StoredExpression<F>>>,
     debug_expressions_map: HashMap<ExecutionState, Vec<(String, Expression<F>)>>,
+    fixed_table_tags_map: HashMap<ExecutionState, Vec<FixedTableTag>>,
     instrument: Instrument,
     // internal state gadgets
     begin_tx_gadget: Box<BeginTxGadget<F>>,
@@ -331,6 +344,7 @@ impl<F: Field> ExecutionConfig<F> {
     pub(crate) fn configure(
         meta: &mut ConstraintSystem<F>,
         challenges: Challenges<Expression<F>>,
+        feature_config: &FeatureConfig,
         fixed_table: &dyn LookupTable<F>,
         u8_table: &dyn LookupTable<F>,
         u16_table: &dyn LookupTable<F>,
@@ -462,6 +476,7 @@ impl<F: Field> ExecutionConfig<F> {
 
         let mut stored_expressions_map = HashMap::new();
         let mut debug_expressions_map = HashMap::new();
+        let mut fixed_table_tags_map = HashMap::new();
 
         macro_rules! configure_gadget {
             () => {
@@ -480,10 +495,12 @@ impl<F: Field> ExecutionConfig<F> {
                         q_step_first,
                         q_step_last,
                         &challenges,
+                        feature_config,
                         &step_curr,
                         &mut height_map,
                         &mut stored_expressions_map,
                         &mut debug_expressions_map,
+                        &mut fixed_table_tags_map,
                         &mut instrument,
                     ))
                 })()
@@ -590,6 +607,7 @@ impl<F: Field> ExecutionConfig<F> {
             height_map,
             stored_expressions_map,
             debug_expressions_map,
+            fixed_table_tags_map,
             instrument,
         };
 
@@ -615,6 +633,18 @@ impl<F: Field> ExecutionConfig<F> {
         &self.instrument
     }
 
+    /// Returns the tags of the fixed table looked up by at least one
+    /// execution state, the other ones don't need to be assigned.
+    pub(crate) fn used_fixed_table_tags(&self) -> Vec<FixedTableTag> {
+        FixedTableTag::iter()
+            .filter(|tag| {
+                self.fixed_table_tags_map
+                    .values()
+                    .any(|tags| tags.contains(tag))
+            })
+            .collect()
+    }
+
     #[allow(clippy::too_many_arguments)]
     fn configure_gadget<G: ExecutionGadget<F>>(
         meta: &mut ConstraintSystem<F>,
@@ -625,10 +655,12 @@ impl<F: Field> ExecutionConfig<F> {
         q_step_first: Selector,
         q_step_last: Selector,
         challenges: &Challenges<Expression<F>>,
+        feature_config: &FeatureConfig,
         step_curr: &Step<F>,
         height_map: &mut HashMap<ExecutionState, usize>,
         stored_expressions_map: &mut HashMap<ExecutionState, Vec<StoredExpression<F>>>,
         debug_expressions_map: &mut HashMap<ExecutionState, Vec<(String, Expression<F>)>>,
+        fixed_table_tags_map: &mut HashMap<ExecutionState, Vec<FixedTableTag>>,
         instrument: &mut Instrument,
     ) -> G {
         // Configure the gadget with the max height first so we can find out the actual
@@ -640,9 +672,11 @@ impl<F: Field> ExecutionConfig<F> {
                 step_curr.clone(),
                 dummy_step_next,
                 challenges,
+                feature_config,
                 G::EXECUTION_STATE,
             );
             G::configure(&mut cb);
+            Self::configure_transition_lookup(&mut cb);
             let (_, _, height, _) = cb.build();
             height
         };
@@ -654,10 +688,12 @@ impl<F: Field> ExecutionConfig<F> {
             step_curr.clone(),
             step_next.clone(),
             challenges,
+            feature_config,
             G::EXECUTION_STATE,
         );
 
         let gadget = G::configure(&mut cb);
+        Self::configure_transition_lookup(&mut cb);
 
         Self::configure_gadget_impl(
             q_usable,
@@ -670,6 +706,7 @@ impl<F: Field> ExecutionConfig<F> {
             height_map,
             stored_expressions_map,
             debug_expressions_map,
+            fixed_table_tags_map,
             instrument,
             G::NAME,
             G::EXECUTION_STATE,
@@ -680,6 +717,26 @@ impl<F: Field> ExecutionConfig<F> {
         gadget
     }
 
+    /// Look up the execution states of the step and the next one in the legal
+    /// transitions.  EndBlock is constrained by a gate instead, as the last
+    /// step has no next step to look up.
+    fn configure_transition_lookup(cb: &mut EVMConstraintBuilder<F>) {
+        let execution_state = cb.execution_state();
+        if execution_state != ExecutionState::EndBlock {
+            cb.add_lookup(
+                "ExecutionState transition",
+                Lookup::Fixed {
+                    tag: FixedTableTag::ExecutionStateTransition.expr(),
+                    values: [
+                        execution_state.as_u64().expr(),
+                        cb.next.execution_state_value(),
+                        0.expr(),
+                    ],
+                },
+            );
+        }
+    }
+
     #[allow(clippy::too_many_arguments)]
     fn configure_gadget_impl(
         q_usable: Selector,
@@ -692,6 +749,7 @@ impl<F: Field> ExecutionConfig<F> {
         height_map: &mut HashMap<ExecutionState, usize>,
         stored_expressions_map: &mut HashMap<ExecutionState, Vec<StoredExpression<F>>>,
         debug_expressions_map: &mut HashMap<ExecutionState, Vec<(String, Expression<F>)>>,
+        fixed_table_tags_map: &mut HashMap<ExecutionState, Vec<FixedTableTag>>,
         instrument: &mut Instrument,
         name: &'static str,
         execution_state: ExecutionState,
@@ -707,9 +765,19 @@ impl<F: Field> ExecutionConfig<F> {
             (height - 1).expr(),
         );
 
+        // Every opcode step needs to fetch the executed opcode from the bytecode table, so the
+        // opcode always matches the committed bytecode.
+        assert!(
+            !execution_state.executes_opcode() || cb.has_opcode_lookup(),
+            "{} ({:?}) does not look up the opcode in the bytecode table",
+            name,
+            execution_state
+        );
+
         instrument.on_gadget_built(execution_state, &cb);
 
         let debug_expressions = cb.debug_expressions.clone();
+        let fixed_table_tags = cb.fixed_table_tags();
         let (constraints, stored_expressions, _, meta) = cb.build();
         debug_assert!(
             !height_map.contains_key(&execution_state),
@@ -723,6 +791,7 @@ impl<F: Field> ExecutionConfig<F> {
         );
         stored_expressions_map.insert(execution_state, stored_expressions);
         debug_expressions_map.insert(execution_state, debug_expressions);
+        fixed_table_tags_map.insert(execution_state, fixed_table_tags);
 
         // Enforce the logic for this opcode
         let sel_step: &dyn Fn(&mut VirtualCells<F>) -> Expression<F> =
@@ -752,65 +821,21 @@ impl<F: Field> ExecutionConfig<F> {
             }
         }
 
-        // Enforce the state transitions for this opcode
-        meta.create_gate("Constrain state machine transitions", |meta| {
-            let q_usable = meta.query_selector(q_usable);
-            let q_step = meta.query_advice(q_step, Rotation::cur());
-            let q_step_last = meta.query_selector(q_step_last);
-
-            // ExecutionState transition should be correct.
-            iter::empty()
-                .chain(
-                    IntoIterator::into_iter([
-                        (
-                            "EndTx can only transit to BeginTx or EndBlock",
-                            ExecutionState::EndTx,
-                            vec![ExecutionState::BeginTx, ExecutionState::EndBlock],
-                        ),
-                        (
-                            "EndBlock can only transit to EndBlock",
-                            ExecutionState::EndBlock,
-                            vec![ExecutionState::EndBlock],
-                        ),
-                    ])
-                    .filter(move |(_, from, _)| *from == execution_state)
-                    .map(|(_, _, to)| 1.expr() - step_next.execution_state_selector(to)),
-                )
-                .chain(
-                    IntoIterator::into_iter([
-                        (
-                            "Only EndTx can transit to BeginTx",
-                            ExecutionState::BeginTx,
-                            vec![ExecutionState::EndTx],
-                        ),
-                        (
-                            "Only ExecutionState which halts or BeginTx can transit to EndTx",
-                            ExecutionState::EndTx,
-                            ExecutionState::iter()
-                                .filter(ExecutionState::halts)
-                                .chain(iter::once(ExecutionState::BeginTx))
-                                .collect(),
-                        ),
-                        (
-                            "Only EndTx or EndBlock can transit to EndBlock",
-                            ExecutionState::EndBlock,
-                            vec![ExecutionState::EndTx, ExecutionState::EndBlock],
-                        ),
-                    ])
-                    .filter(move |(_, _, from)| !from.contains(&execution_state))
-                    .map(|(_, to, _)| step_next.execution_state_selector([to])),
-                )
-                // Accumulate all state transition checks.
-                // This can be done because all summed values are enforced to be boolean.
-                .reduce(|accum, poly| accum + poly)
-                .map(move |poly| {
-                    q_usable.clone()
-                        * q_step.clone()
-                        * (1.expr() - q_step_last.clone())
-                        * step_curr.execution_state_selector([execution_state])
-                        * poly
-                })
-        });
+        // Enforce the state transitions of EndBlock
+        if execution_state == ExecutionState::EndBlock {
+            meta.create_gate("Constrain state machine transitions", |meta| {
+                let q_usable = meta.query_selector(q_usable);
+                let q_step = meta.query_advice(q_step, Rotation::cur());
+                let q_step_last = meta.query_selector(q_step_last);
+
+                // EndBlock can only transit to EndBlock
+                [q_usable
+                    * q_step
+                    * (1.expr() - q_step_last)
+                    * step_curr.execution_state_selector([ExecutionState::EndBlock])
+                    * (1.expr() - step_next.execution_state_selector([ExecutionState::EndBlock]))]
+            });
+        }
     }
 
     #[allow(clippy::too_many_arguments)]
@@ -915,57 +940,60 @@ impl<F: Field> ExecutionConfig<F> {
 
                 self.q_step_first.enable(&mut region, offset)?;
 
-                let dummy_tx = Transaction::default();
-                let last_call = block
-                    .txs
-                    .last()
-                    .map(|tx| tx.calls()[0].clone())
-                    .unwrap_or_else(Call::default);
+                let end_block_context = block.end_block_context();
+                let (dummy_tx, last_call) = (&end_block_context.0, &end_block_context.1);
                 let end_block_not_last = &block.end_block_not_last;
                 let end_block_last = &block.end_block_last;
                 // Collect all steps
-                let mut steps = block
-                    .txs
-                    .iter()
-                    .flat_map(|tx| {
-                        tx.steps()
-                            .iter()
-                            .map(move |step| (tx, &tx.calls()[step.call_index], step))
-                    })
-                    .chain(std::iter::once((&dummy_tx, &last_call, end_block_not_last)))
-                    .peekable();
+                let steps: Vec<_> = block.steps(&end_block_context).collect();
+                if steps.is_empty() {
+                    log::error!("evm circuit block without steps");
+                    return Err(Error::Synthesis);
+                }
 
                 let evm_rows = block.circuits_params.max_evm_rows;
                 let no_padding = evm_rows == 0;
 
-                // part1: assign real steps
-                loop {
-                    let (transaction, call, step) = steps.next().expect("should not be empty");
-                    let next = steps.peek();
-                    if next.is_none() {
-                        break;
+                // part1: assign real steps, all of them but the last (non-last EndBlock) one.
+                // Their witness is computed in parallel, then assigned in order.
+                let mut real_steps = Vec::with_capacity(steps.len() - 1);
+                for (index, &(transaction, call, step)) in
+                    steps.iter().enumerate().take(steps.len() - 1)
+                {
+                    real_steps.push((offset, transaction, call, step, steps[index + 1]));
+                    offset += step.execution_state().get_step_height();
+                }
+                for chunk in real_steps.chunks(STEP_WITNESS_CHUNK_SIZE) {
+                    let witnesses = chunk
+                        .par_iter()
+                        .map(|&(offset, transaction, call, step, next)| {
+                            self.compute_step_witness(
+                                offset,
+                                block,
+                                transaction,
+                                call,
+                                step,
+                                next,
+                                challenges,
+                                assign_pass,
+                            )
+                        })
+                        .collect::<Result<Vec<_>, Error>>()?;
+                    for (&(offset, _, _, step, _), witness) in chunk.iter().zip(witnesses) {
+                        // Assign the step witness
+                        replay_assignments(&mut region, &witness)?;
+
+                        // q_step logic
+                        let height = step.execution_state().get_step_height();
+                        self.assign_q_step(&mut region, offset, height)?;
                     }
-                    let height = step.execution_state().get_step_height();
-
-                    // Assign the step witness
-                    self.assign_exec_step(
-                        &mut region,
-                        offset,
-                        block,
-                        transaction,
-                        call,
-                        step,
-                        height,
-                        next.copied(),
-                        challenges,
-                        assign_pass,
-                    )?;
-
-                    // q_step logic
-                    self.assign_q_step(&mut region, offset, height)?;
-
-                    offset += height;
                 }
+                tracing::debug!(
+                    steps = real_steps.len(),
+                    rows = offset,
+                    assign_pass,
+                    "execution steps assigned"
+                );
 
                 // part2: assign non-last EndBlock steps when padding needed
                 if !no_padding {
@@ -990,8 +1018,8 @@ impl<F: Field> ExecutionConfig<F> {
                         offset,
                         last_row,
                         block,
-                        &dummy_tx,
-                        &last_call,
+                        dummy_tx,
+                        last_call,
                         end_block_not_last,
                         height,
                         challenges,
@@ -1012,8 +1040,8 @@ impl<F: Field> ExecutionConfig<F> {
                     &mut region,
                     offset,
                     block,
-                    &dummy_tx,
-                    &last_call,
+                    dummy_tx,
+                    last_call,
                     end_block_last,
                     height,
                     None,
@@ -1129,6 +1157,50 @@ impl<F: Field> ExecutionConfig<F> {
         Ok(())
     }
 
+    /// Computes the witness of a step followed by `next`, in a region which
+    /// only measures its shape, and returns the assignments to replay in the
+    /// region of the circuit.  As it doesn't touch the region of the circuit,
+    /// it can be called for several steps in parallel.  The step must not
+    /// have copy constraints, which can't be replayed, so it must not be the
+    /// last EndBlock step.
+    #[allow(clippy::too_many_arguments)]
+    fn compute_step_witness(
+        &self,
+        offset: usize,
+        block: &Block<F>,
+        transaction: &Transaction,
+        call: &Call,
+        step: &ExecStep,
+        next: (&Transaction, &Call, &ExecStep),
+        challenges: &Challenges<Value<F>>,
+        assign_pass: usize,
+    ) -> Result<Vec<AdviceAssignment<F>>, Error> {
+        debug_assert!(!matches!(step.execution_state(), ExecutionState::EndBlock));
+        let mut shape = RegionShape::new(RegionIndex::from(0));
+        let mut region: Region<'_, F> = (&mut shape as &mut dyn RegionLayouter<F>).into();
+        let height = step.execution_state().get_step_height();
+        let mut region = CachedRegion::<'_, '_, F>::new(
+            &mut region,
+            challenges,
+            self.advices.to_vec(),
+            MAX_STEP_HEIGHT * 3,
+            offset,
+        )
+        .with_assignment_log();
+        self.assign_exec_step_cached(
+            &mut region,
+            offset,
+            block,
+            transaction,
+            call,
+            step,
+            height,
+            Some(next),
+            assign_pass,
+        )?;
+        Ok(region.take_assignment_log())
+    }
+
     #[allow(clippy::too_many_arguments)]
     fn assign_exec_step(
         &self,
@@ -1143,15 +1215,6 @@ impl<F: Field> ExecutionConfig<F> {
         challenges: &Challenges<Value<F>>,
         assign_pass: usize,
     ) -> Result<(), Error> {
-        if !matches!(step.execution_state(), ExecutionState::EndBlock) {
-            log::trace!(
-                "assign_exec_step offset: {} state {:?} step: {:?} call: {:?}",
-                offset,
-                step.execution_state(),
-                step,
-                call
-            );
-        }
         // Make the region large enough for the current step and the next step.
         // The next step's next step may also be accessed, so make the region large
         // enough for 3 steps.
@@ -1162,7 +1225,41 @@ impl<F: Field> ExecutionConfig<F> {
             MAX_STEP_HEIGHT * 3,
             offset,
         );
+        self.assign_exec_step_cached(
+            region,
+            offset,
+            block,
+            transaction,
+            call,
+            step,
+            height,
+            next,
+            assign_pass,
+        )
+    }
 
+    #[allow(clippy::too_many_arguments)]
+    fn assign_exec_step_cached(
+        &self,
+        region: &mut CachedRegion<'_, '_, F>,
+        offset: usize,
+        block: &Block<F>,
+        transaction: &Transaction,
+        call: &Call,
+        step: &ExecStep,
+        height: usize,
+        next: Option<(&Transaction, &Call, &ExecStep)>,
+        assign_pass: usize,
+    ) -> Result<(), Error> {
+        if !matches!(step.execution_state(), ExecutionState::EndBlock) {
+            log::trace!(
+                "assign_exec_step offset: {} state {:?} step: {:?} call: {:?}",
+                offset,
+                step.execution_state(),
+                step,
+                call
+            );
+        }
         // Also set the witness of the next step.
         // These may be used in stored expressions and
         // so their witness values need to be known to be able
@@ -3,13 +3,13 @@ use crate::{
         execution::ExecutionGadget,
         step::ExecutionState,
         util::{
-            common_gadget::SameContextGadget,
+            common_gadget::{account_access_gas_cost, SameContextGadget},
             constraint_builder::{
                 ConstrainBuilderCommon, EVMConstraintBuilder, ReversionInfo, StepStateTransition,
                 Transition::Delta,
             },
             math_gadget::IsZeroWordGadget,
-            not, select, AccountAddress, CachedRegion, Cell,
+            not, AccountAddress, CachedRegion, Cell,
         },
         witness::{Block, Call, ExecStep, Transaction},
     },
@@ -19,7 +19,7 @@ use crate::{
         Expr,
     },
 };
-use eth_types::{evm_types::GasCost, Field, ToAddress, ToWord};
+use eth_types::{Field, ToAddress, ToWord};
 use halo2_proofs::{circuit::Value, plonk::Error};
 
 #[derive(Clone, Debug)]
@@ -76,11 +76,7 @@ impl<F: Field> ExecutionGadget<F> for BalanceGadget<F> {
 
         cb.stack_push(balance.to_word());
 
-        let gas_cost = select::expr(
-            is_warm.expr(),
-            GasCost::WARM_ACCESS.expr(),
-            GasCost::COLD_ACCOUNT_ACCESS.expr(),
-        );
+        let gas_cost = account_access_gas_cost(cb, is_warm.expr());
 
         let step_state_transition = StepStateTransition {
             rw_counter: Delta(7.expr() + exists.expr()),
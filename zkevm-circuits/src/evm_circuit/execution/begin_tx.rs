@@ -5,7 +5,7 @@ use crate::{
         step::ExecutionState,
         util::{
             and,
-            common_gadget::TransferWithGasFeeGadget,
+            common_gadget::{L1DataFeeGadget, TransferWithGasFeeGadget, UpdateBalanceGadget},
             constraint_builder::{
                 ConstrainBuilderCommon, EVMConstraintBuilder, ReversionInfo, StepStateTransition,
                 Transition::{Delta, To},
@@ -27,7 +27,7 @@ use crate::{
         Expr,
     },
 };
-use bus_mapping::state_db::CodeDB;
+use bus_mapping::{chain_config::Eip, state_db::CodeDB};
 use eth_types::{evm_types::GasCost, keccak256, Field, ToWord, U256};
 use halo2_proofs::{
     circuit::Value,
@@ -53,18 +53,20 @@ pub(crate) struct BeginTxGadget<F> {
     reversion_info: ReversionInfo<F>,
     sufficient_gas_left: RangeCheckGadget<F, N_BYTES_GAS>,
     transfer_with_gas_fee: TransferWithGasFeeGadget<F>,
+    // L1 data fee deducted from the caller, if the circuit is configured with
+    // an L1 data fee policy.
+    l1_data_fee: Option<(L1DataFeeGadget<F>, UpdateBalanceGadget<F, 2, false>)>,
     code_hash: WordCell<F>,
     is_empty_code_hash: IsEqualWordGadget<F, Word<Expression<F>>, Word<Expression<F>>>,
     caller_nonce_hash_bytes: Word32Cell<F>,
     create: ContractCreateGadget<F, false>,
     callee_not_exists: IsZeroWordGadget<F, WordCell<F>>,
     is_caller_callee_equal: Cell<F>,
-    // EIP-3651 (Warm COINBASE)
-    coinbase: WordCell<F>,
-    // Caller, callee and a list addresses are added to the access list before
-    // coinbase, and may be duplicate.
+    // EIP-3651 (Warm COINBASE), if enabled: the coinbase and whether it's
+    // already warm.  Caller, callee and a list addresses are added to the
+    // access list before coinbase, and may be duplicate.
     // <https://github.com/ethereum/go-ethereum/blob/604e215d1bb070dff98fb76aa965064c74e3633f/core/state/statedb.go#LL1119C9-L1119C9>
-    is_coinbase_warm: Cell<F>,
+    coinbase_warm: Option<(WordCell<F>, Cell<F>)>,
 }
 
 impl<F: Field> ExecutionGadget<F> for BeginTxGadget<F> {
@@ -130,8 +132,10 @@ impl<F: Field> ExecutionGadget<F> for BeginTxGadget<F> {
             cb.require_equal("tx_id is initialized to be 1", tx_id.expr(), 1.expr());
         });
 
-        // Increase caller's nonce.
+        // Increase caller's nonce by exactly 1.
         // (tx caller's nonce always increases even tx ends with error)
+        // The state circuit checks that the previous value of the write is the
+        // caller's nonce, so the tx nonce must match it.
         cb.account_write(
             tx_caller_address.to_word(),
             AccountFieldTag::Nonce,
@@ -149,13 +153,17 @@ impl<F: Field> ExecutionGadget<F> for BeginTxGadget<F> {
         let tx_call_data_word_length =
             ConstantDivisionGadget::construct(cb, tx_call_data_length.expr() + 31.expr(), 32);
 
-        // Calculate gas cost of init code for EIP-3860.
-        let init_code_gas_cost = select::expr(
-            tx_is_create.expr(),
-            tx_call_data_word_length.quotient().expr()
-                * eth_types::evm_types::INIT_CODE_WORD_GAS.expr(),
-            0.expr(),
-        );
+        // Calculate gas cost of init code for EIP-3860, if enabled.
+        let init_code_gas_cost = if cb.feature_config().is_enabled(Eip::Eip3860) {
+            select::expr(
+                tx_is_create.expr(),
+                tx_call_data_word_length.quotient().expr()
+                    * eth_types::evm_types::INIT_CODE_WORD_GAS.expr(),
+                0.expr(),
+            )
+        } else {
+            0.expr()
+        };
 
         // TODO: Take gas cost of access list (EIP 2930) into consideration.
         // Use intrinsic gas
@@ -189,21 +197,25 @@ impl<F: Field> ExecutionGadget<F> for BeginTxGadget<F> {
             None,
         ); // rwc_delta += 1
 
-        // Query coinbase address.
-        let coinbase = cb.query_word_unchecked();
-        let is_coinbase_warm = cb.query_bool();
-        cb.block_lookup(
-            BlockContextFieldTag::Coinbase.expr(),
-            None,
-            coinbase.to_word(),
-        );
-        cb.account_access_list_write_unchecked(
-            tx_id.expr(),
-            coinbase.to_word(),
-            1.expr(),
-            is_coinbase_warm.expr(),
-            None,
-        ); // rwc_delta += 1
+        // Query coinbase address and prepare its access list for EIP-3651.
+        let coinbase_warm = cb.feature_config().is_enabled(Eip::Eip3651).then(|| {
+            let coinbase = cb.query_word_unchecked();
+            let is_coinbase_warm = cb.query_bool();
+            cb.block_lookup(
+                BlockContextFieldTag::Coinbase.expr(),
+                None,
+                coinbase.to_word(),
+            );
+            cb.account_access_list_write_unchecked(
+                tx_id.expr(),
+                coinbase.to_word(),
+                1.expr(),
+                is_coinbase_warm.expr(),
+                None,
+            ); // rwc_delta += 1
+            (coinbase, is_coinbase_warm)
+        });
+        let coinbase_rw_delta = coinbase_warm.is_some().expr();
 
         // Read code_hash of callee
         let code_hash = cb.query_word_unchecked();
@@ -225,6 +237,10 @@ impl<F: Field> ExecutionGadget<F> for BeginTxGadget<F> {
         });
 
         // Transfer value from caller to callee, creating account if necessary.
+        // The caller's balance decreases by gas * gas_price (not reversible,
+        // the unused gas is refunded in EndTx) and by the value (reversible),
+        // in two balance writes whose additions are checked not to overflow:
+        // the caller can afford both.
         let transfer_with_gas_fee = TransferWithGasFeeGadget::construct(
             cb,
             tx_caller_address.to_word(),
@@ -236,6 +252,19 @@ impl<F: Field> ExecutionGadget<F> for BeginTxGadget<F> {
             &mut reversion_info,
         );
 
+        // Deduct the L1 data fee from the caller (not reversible).
+        let l1_data_fee = cb.feature_config().l1_data_fee.as_ref().map(|policy| {
+            let l1_data_fee = L1DataFeeGadget::construct(cb, policy, tx_call_data_gas_cost.expr());
+            let caller_sub_l1_fee = UpdateBalanceGadget::construct(
+                cb,
+                tx_caller_address.to_word(),
+                vec![l1_data_fee.l1_fee()],
+                None,
+            );
+            (l1_data_fee, caller_sub_l1_fee)
+        });
+        let l1_data_fee_rw_delta = l1_data_fee.is_some().expr();
+
         let caller_nonce_hash_bytes = cb.query_word32();
         let create = ContractCreateGadget::construct(cb);
         cb.require_equal_word(
@@ -322,8 +351,9 @@ impl<F: Field> ExecutionGadget<F> for BeginTxGadget<F> {
                 //   - Write Account (Caller) Nonce
                 //   - Write TxAccessListAccount (Caller)
                 //   - Write TxAccessListAccount (Callee)
-                //   - Write TxAccessListAccount (Coinbase) for EIP-3651
+                //   - Write TxAccessListAccount (Coinbase) for EIP-3651, if enabled
                 //   - a TransferWithGasFeeGadget
+                //   - Write Account (Caller) Balance for the L1 data fee, if enabled
                 //   - Write Account (Callee) Nonce (Reversible)
                 //   - Write CallContext Depth
                 //   - Write CallContext CallerAddress
@@ -338,7 +368,12 @@ impl<F: Field> ExecutionGadget<F> for BeginTxGadget<F> {
                 //   - Write CallContext IsRoot
                 //   - Write CallContext IsCreate
                 //   - Write CallContext CodeHash
-                rw_counter: Delta(22.expr() + transfer_with_gas_fee.rw_delta()),
+                rw_counter: Delta(
+                    21.expr()
+                        + coinbase_rw_delta.clone()
+                        + transfer_with_gas_fee.rw_delta()
+                        + l1_data_fee_rw_delta.clone(),
+                ),
                 call_id: To(call_id.expr()),
                 is_root: To(true.expr()),
                 is_create: To(tx_is_create.expr()),
@@ -379,10 +414,16 @@ impl<F: Field> ExecutionGadget<F> for BeginTxGadget<F> {
                     //   - Write Account Nonce
                     //   - Write TxAccessListAccount (Caller)
                     //   - Write TxAccessListAccount (Callee)
-                    //   - Write TxAccessListAccount (Coinbase) for EIP-3651
+                    //   - Write TxAccessListAccount (Coinbase) for EIP-3651, if enabled
                     //   - Read Account CodeHash
                     //   - a TransferWithGasFeeGadget
-                    rw_counter: Delta(9.expr() + transfer_with_gas_fee.rw_delta()),
+                    //   - Write Account (Caller) Balance for the L1 data fee, if enabled
+                    rw_counter: Delta(
+                        8.expr()
+                            + coinbase_rw_delta.clone()
+                            + transfer_with_gas_fee.rw_delta()
+                            + l1_data_fee_rw_delta.clone(),
+                    ),
                     call_id: To(call_id.expr()),
                     ..StepStateTransition::any()
                 });
@@ -439,9 +480,10 @@ impl<F: Field> ExecutionGadget<F> for BeginTxGadget<F> {
                     //   - Write Account Nonce
                     //   - Write TxAccessListAccount (Caller)
                     //   - Write TxAccessListAccount (Callee)
-                    //   - Write TxAccessListAccount (Coinbase) for EIP-3651
+                    //   - Write TxAccessListAccount (Coinbase) for EIP-3651, if enabled
                     //   - Read Account CodeHash
                     //   - a TransferWithGasFeeGadget
+                    //   - Write Account (Caller) Balance for the L1 data fee, if enabled
                     //   - Write CallContext Depth
                     //   - Write CallContext CallerAddress
                     //   - Write CallContext CalleeAddress
@@ -455,7 +497,12 @@ impl<F: Field> ExecutionGadget<F> for BeginTxGadget<F> {
                     //   - Write CallContext IsRoot
                     //   - Write CallContext IsCreate
                     //   - Write CallContext CodeHash
-                    rw_counter: Delta(22.expr() + transfer_with_gas_fee.rw_delta()),
+                    rw_counter: Delta(
+                        21.expr()
+                            + coinbase_rw_delta
+                            + transfer_with_gas_fee.rw_delta()
+                            + l1_data_fee_rw_delta,
+                    ),
                     call_id: To(call_id.expr()),
                     is_root: To(true.expr()),
                     is_create: To(tx_is_create.expr()),
@@ -485,14 +532,14 @@ impl<F: Field> ExecutionGadget<F> for BeginTxGadget<F> {
             reversion_info,
             sufficient_gas_left,
             transfer_with_gas_fee,
+            l1_data_fee,
             code_hash,
             is_empty_code_hash,
             caller_nonce_hash_bytes,
             create,
             callee_not_exists,
             is_caller_callee_equal,
-            coinbase,
-            is_coinbase_warm,
+            coinbase_warm,
         }
     }
 
@@ -511,7 +558,10 @@ impl<F: Field> ExecutionGadget<F> for BeginTxGadget<F> {
         let mut rws = StepRws::new(block, step);
         rws.offset_add(7);
 
-        let is_coinbase_warm = rws.next().tx_access_list_value_pair().1;
+        let is_coinbase_warm = self
+            .coinbase_warm
+            .as_ref()
+            .map(|_| rws.next().tx_access_list_value_pair().1);
         let mut callee_code_hash = zero;
         if !is_precompiled(&tx.to_or_contract_addr()) && !tx.is_create() {
             callee_code_hash = rws.next().account_value_pair().1;
@@ -529,6 +579,10 @@ impl<F: Field> ExecutionGadget<F> for BeginTxGadget<F> {
             caller_balance_sub_value_pair = rws.next().account_value_pair();
             callee_balance_pair = rws.next().account_value_pair();
         };
+        let caller_balance_sub_l1_fee_pair = self
+            .l1_data_fee
+            .as_ref()
+            .map(|_| rws.next().account_value_pair());
 
         self.tx_id
             .assign(region, offset, Value::known(F::from(tx.id)))?;
@@ -587,6 +641,20 @@ impl<F: Field> ExecutionGadget<F> for BeginTxGadget<F> {
             tx.value,
             gas_fee,
         )?;
+        if let (Some((l1_data_fee, caller_sub_l1_fee)), Some(policy), Some(balance_pair)) = (
+            &self.l1_data_fee,
+            &block.l1_data_fee,
+            caller_balance_sub_l1_fee_pair,
+        ) {
+            let l1_fee = l1_data_fee.assign(region, offset, policy, tx.call_data_gas_cost())?;
+            caller_sub_l1_fee.assign(
+                region,
+                offset,
+                balance_pair.1,
+                vec![l1_fee],
+                balance_pair.0,
+            )?;
+        }
         self.code_hash
             .assign_u256(region, offset, callee_code_hash)?;
         self.is_empty_code_hash.assign_u256(
@@ -620,13 +688,16 @@ impl<F: Field> ExecutionGadget<F> for BeginTxGadget<F> {
             None,
         )?;
 
-        self.coinbase
-            .assign_h160(region, offset, block.context.coinbase)?;
-        self.is_coinbase_warm.assign(
-            region,
-            offset,
-            Value::known(F::from(is_coinbase_warm as u64)),
-        )?;
+        if let (Some((coinbase, is_coinbase_warm_cell)), Some(is_coinbase_warm)) =
+            (&self.coinbase_warm, is_coinbase_warm)
+        {
+            coinbase.assign_h160(region, offset, block.context.coinbase)?;
+            is_coinbase_warm_cell.assign(
+                region,
+                offset,
+                Value::known(F::from(is_coinbase_warm as u64)),
+            )?;
+        }
 
         Ok(())
     }
@@ -785,6 +856,33 @@ mod test {
         }
     }
 
+    #[test]
+    fn begin_tx_exact_balance() {
+        // The sender can exactly afford the gas fee and the value
+        let gas_price = gwei(2);
+        let value = eth(1);
+        let balance = value + gas_price * GasCost::TX;
+        let ctx = TestContext::<2, 1>::new(
+            None,
+            |accs| {
+                accs[0].address(MOCK_ACCOUNTS[0]).balance(balance);
+                accs[1].address(MOCK_ACCOUNTS[1]).balance(eth(10));
+            },
+            |mut txs, _accs| {
+                txs[0]
+                    .from(MOCK_ACCOUNTS[0])
+                    .to(MOCK_ACCOUNTS[1])
+                    .gas_price(gas_price)
+                    .gas(Word::from(GasCost::TX))
+                    .value(value);
+            },
+            |block, _tx| block.number(0xcafeu64),
+        )
+        .unwrap();
+
+        CircuitTestBuilder::new_from_test_ctx(ctx).run();
+    }
+
     #[test]
     fn begin_tx_no_code() {
         let ctx = TestContext::<2, 1>::new(
@@ -47,6 +47,11 @@ impl<F: Field> ExecutionGadget<F> for BitwiseGadget<F> {
         // OpcodeId::AND as the delta to FixedTableTag::BitwiseAnd.
         let tag =
             FixedTableTag::BitwiseAnd.expr() + (opcode.expr() - OpcodeId::AND.as_u64().expr());
+        cb.register_fixed_table_tags(&[
+            FixedTableTag::BitwiseAnd,
+            FixedTableTag::BitwiseOr,
+            FixedTableTag::BitwiseXor,
+        ]);
         for idx in 0..32 {
             cb.add_lookup(
                 "Bitwise lookup",
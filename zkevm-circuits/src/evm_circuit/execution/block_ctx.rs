@@ -83,8 +83,11 @@ impl<F: Field> ExecutionGadget<F> for BlockCtxGadget<F> {
 #[cfg(test)]
 mod test {
     use crate::test_util::CircuitTestBuilder;
-    use eth_types::bytecode;
-    use mock::TestContext;
+    use eth_types::{bytecode, H256};
+    use mock::{
+        test_ctx::helpers::{account_0_code_account_1_no_code, tx_from_1_to_0},
+        TestContext,
+    };
 
     fn test_ok(bytecode: bytecode::Bytecode) {
         CircuitTestBuilder::new_from_test_ctx(
@@ -124,4 +127,21 @@ mod test {
         };
         test_ok(bytecode);
     }
+
+    #[test]
+    fn blockcxt_prevrandao_gadget_test() {
+        // After the merge DIFFICULTY returns the mix hash of the block
+        let bytecode = bytecode! {
+            DIFFICULTY
+            STOP
+        };
+        let ctx = TestContext::<2, 1>::new(
+            None,
+            account_0_code_account_1_no_code(bytecode),
+            tx_from_1_to_0,
+            |block, _txs| block.mix_hash(H256::from_low_u64_be(0xcafe)),
+        )
+        .unwrap();
+        CircuitTestBuilder::new_from_test_ctx(ctx).run()
+    }
 }
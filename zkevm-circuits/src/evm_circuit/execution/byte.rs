@@ -5,8 +5,8 @@ use crate::{
         util::{
             common_gadget::SameContextGadget,
             constraint_builder::{EVMConstraintBuilder, StepStateTransition, Transition::Delta},
-            math_gadget::{IsEqualGadget, IsZeroGadget},
-            sum, CachedRegion,
+            math_gadget::{IsEqualGadget, Pow2Gadget},
+            CachedRegion,
         },
         witness::{Block, Call, ExecStep, Transaction},
     },
@@ -23,9 +23,10 @@ use halo2_proofs::plonk::Error;
 #[derive(Clone, Debug)]
 pub(crate) struct ByteGadget<F> {
     same_context: SameContextGadget<F>,
-    index: Word32Cell<F>,
+    /// Index of the selected byte; no byte is selected when it's not less
+    /// than 32
+    index: Pow2Gadget<F>,
     value: Word32Cell<F>,
-    is_msb_sum_zero: IsZeroGadget<F>,
     is_byte_selected: [IsEqualGadget<F>; 32],
 }
 
@@ -35,14 +36,11 @@ impl<F: Field> ExecutionGadget<F> for ByteGadget<F> {
     const EXECUTION_STATE: ExecutionState = ExecutionState::BYTE;
 
     fn configure(cb: &mut EVMConstraintBuilder<F>) -> Self {
-        let index = cb.query_word32();
-        let value = cb.query_word32();
-
         // If any of the non-LSB bytes of the index word are non-zero we never
-        // need to copy any bytes. So just sum all the non-LSB byte
-        // values here and then check if it's non-zero so we can use
-        // that as an additional condition when to copy the byte value.
-        let is_msb_sum_zero = IsZeroGadget::construct(cb, sum::expr(&index.limbs[1..32]));
+        // need to copy any bytes, so we use `index < 256` as an additional
+        // condition when to copy the byte value.
+        let index = Pow2Gadget::construct(cb);
+        let value = cb.query_word32();
 
         // Now we just need to check that `result[0]` is the sum of all copied
         // bytes. We go byte by byte and check if `idx == index[0]`.
@@ -53,22 +51,23 @@ impl<F: Field> ExecutionGadget<F> for ByteGadget<F> {
         let is_byte_selected = array_init(|idx| {
             // Check if this byte is selected looking only at the LSB of the
             // index word
-            IsEqualGadget::construct(cb, index.limbs[0].expr(), (31 - idx).expr())
+            IsEqualGadget::construct(cb, index.lsb(), (31 - idx).expr())
         });
 
         // Sum all possible selected bytes
-        let selected_byte = value.limbs.iter().zip(is_byte_selected.iter()).fold(
-            0.expr(),
-            |acc, (cell, is_selected)| {
-                acc + is_selected.expr() * is_msb_sum_zero.expr() * cell.expr()
-            },
-        );
+        let selected_byte = value
+            .limbs
+            .iter()
+            .zip(is_byte_selected.iter())
+            .fold(0.expr(), |acc, (cell, is_selected)| {
+                acc + is_selected.expr() * index.lt_256() * cell.expr()
+            });
 
         // Pop the byte index and the value from the stack,
         // push the selected byte on the stack
         // We can push the selected byte here directly because
         // it only uses the LSB of a word.
-        cb.stack_pop(index.to_word());
+        cb.stack_pop(index.amount().to_word());
         cb.stack_pop(value.to_word());
         cb.stack_push(Word::from_lo_unchecked(selected_byte));
 
@@ -87,7 +86,6 @@ impl<F: Field> ExecutionGadget<F> for ByteGadget<F> {
             same_context,
             index,
             value,
-            is_msb_sum_zero,
             is_byte_selected,
         }
     }
@@ -107,13 +105,9 @@ impl<F: Field> ExecutionGadget<F> for ByteGadget<F> {
         let index = block.get_rws(step, 0).stack_value();
         let value = block.get_rws(step, 1).stack_value();
         let index_bytes = index.to_le_bytes();
-        self.index.assign_u256(region, offset, index)?;
+        self.index.assign(region, offset, index)?;
         self.value.assign_u256(region, offset, value)?;
 
-        // Set `is_msb_sum_zero`
-        self.is_msb_sum_zero
-            .assign(region, offset, sum::value(&index_bytes[1..32]))?;
-
         // Set `is_byte_selected`
         for i in 0..32 {
             self.is_byte_selected[i].assign(
@@ -154,6 +148,10 @@ mod test {
         test_ok(29.into(), 0x030201.into());
         // Select byte 256
         test_ok(256.into(), 0x030201.into());
+        // Select byte 256 + 29, whose least significant byte is a valid index
+        test_ok((256 + 29).into(), 0x030201.into());
+        // Select byte 32
+        test_ok(32.into(), 0x030201.into());
     }
 
     #[test]
@@ -274,7 +274,10 @@ impl<F: Field> ExecutionGadget<F> for CallDataLoadGadget<F> {
 mod test {
     use crate::{evm_circuit::test::rand_bytes, test_util::CircuitTestBuilder};
     use eth_types::{bytecode, Word};
-    use mock::{generate_mock_call_bytecode, MockCallBytecodeParams, TestContext};
+    use mock::{
+        generate_mock_call_bytecode, test_ctx::helpers::account_0_code_account_1_no_code,
+        MockCallBytecodeParams, TestContext,
+    };
 
     fn test_bytecode(offset: Word) -> eth_types::Bytecode {
         bytecode! {
@@ -293,6 +296,26 @@ mod test {
         .run();
     }
 
+    fn test_root_calldata_ok(call_data_length: usize, offset: Word) {
+        let bytecode = test_bytecode(offset);
+        let call_data = rand_bytes(call_data_length);
+
+        let ctx = TestContext::<2, 1>::new(
+            None,
+            account_0_code_account_1_no_code(bytecode),
+            |mut txs, accs| {
+                txs[0]
+                    .to(accs[0].address)
+                    .from(accs[1].address)
+                    .input(call_data.into());
+            },
+            |block, _tx| block,
+        )
+        .unwrap();
+
+        CircuitTestBuilder::new_from_test_ctx(ctx).run();
+    }
+
     fn test_internal_ok(call_data_length: usize, call_data_offset: usize, offset: Word) {
         let (addr_a, addr_b) = (mock::MOCK_ACCOUNTS[0], mock::MOCK_ACCOUNTS[1]);
 
@@ -333,6 +356,16 @@ mod test {
         test_root_ok(0x2010.into());
     }
 
+    #[test]
+    fn calldataload_gadget_root_calldata() {
+        test_root_calldata_ok(0x40, 0x00.into());
+        test_root_calldata_ok(0x40, 0x10.into());
+        // Zero-padded after the end of the call data
+        test_root_calldata_ok(0x40, 0x30.into());
+        test_root_calldata_ok(0x40, 0x40.into());
+        test_root_calldata_ok(0x40, 0x1000.into());
+    }
+
     #[test]
     fn calldataload_gadget_internal() {
         test_internal_ok(0x20, 0x00, 0x00.into());
@@ -341,6 +374,15 @@ mod test {
         test_internal_ok(0x1010, 0xff, 0x10.into());
     }
 
+    #[test]
+    fn calldataload_gadget_internal_out_of_range() {
+        // Zero-padded after the end of the call data
+        test_internal_ok(0x20, 0x10, 0x18.into());
+        test_internal_ok(0x20, 0x10, 0x20.into());
+        test_internal_ok(0x20, 0x10, 0x1000.into());
+        test_internal_ok(0x00, 0x00, 0x00.into());
+    }
+
     #[test]
     fn calldataload_gadget_offset_overflow() {
         test_root_ok(Word::MAX);
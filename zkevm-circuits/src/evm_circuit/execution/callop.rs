@@ -1,18 +1,16 @@
 use crate::{
     evm_circuit::{
         execution::ExecutionGadget,
-        param::{N_BYTES_GAS, N_BYTES_U64},
+        param::N_BYTES_U64,
         step::ExecutionState,
         util::{
             and,
-            common_gadget::{CommonCallGadget, TransferGadget},
+            common_gadget::{CalleeGasGadget, CommonCallGadget, TransferGadget},
             constraint_builder::{
                 ConstrainBuilderCommon, EVMConstraintBuilder, ReversionInfo, StepStateTransition,
                 Transition::{Delta, To},
             },
-            math_gadget::{
-                ConstantDivisionGadget, IsZeroGadget, LtGadget, LtWordGadget, MinMaxGadget,
-            },
+            math_gadget::{IsZeroGadget, LtGadget, LtWordGadget},
             memory_gadget::CommonMemoryAddressGadget,
             not, or, select, CachedRegion, Cell,
         },
@@ -26,7 +24,7 @@ use crate::{
     util::Expr,
 };
 use bus_mapping::evm::OpcodeId;
-use eth_types::{evm_types::GAS_STIPEND_CALL_WITH_VALUE, Field, ToAddress, U256};
+use eth_types::{Field, ToAddress, U256};
 use halo2_proofs::{circuit::Value, plonk::Error};
 
 /// Gadget for call related opcodes. It supports `OpcodeId::CALL`,
@@ -57,8 +55,7 @@ pub(crate) struct CallOpGadget<F> {
     // check if insufficient balance case
     is_insufficient_balance: LtWordGadget<F>,
     is_depth_ok: LtGadget<F, N_BYTES_U64>,
-    one_64th_gas: ConstantDivisionGadget<F, N_BYTES_GAS>,
-    capped_callee_gas_left: MinMaxGadget<F, N_BYTES_GAS>,
+    callee_gas: CalleeGasGadget<F>,
 }
 
 impl<F: Field> ExecutionGadget<F> for CallOpGadget<F> {
@@ -213,17 +210,15 @@ impl<F: Field> ExecutionGadget<F> for CallOpGadget<F> {
 
         // Sum up and verify gas cost.
         // Only CALL opcode could invoke transfer to make empty account into non-empty.
-        let gas_cost = call_gadget.gas_cost_expr(is_warm_prev.expr(), is_call.expr());
+        let gas_cost = call_gadget.gas_cost_expr(cb, is_warm_prev.expr(), is_call.expr());
         // Apply EIP 150
         let gas_available = cb.curr.state.gas_left.expr() - gas_cost.clone();
-        let one_64th_gas = ConstantDivisionGadget::construct(cb, gas_available.clone(), 64);
-        let all_but_one_64th_gas = gas_available - one_64th_gas.quotient();
-        let capped_callee_gas_left =
-            MinMaxGadget::construct(cb, call_gadget.gas_expr(), all_but_one_64th_gas.clone());
-        let callee_gas_left = select::expr(
+        let callee_gas = CalleeGasGadget::construct_call(
+            cb,
+            gas_available,
+            call_gadget.gas_expr(),
             call_gadget.gas_is_u64.expr(),
-            capped_callee_gas_left.min(),
-            all_but_one_64th_gas,
+            call_gadget.has_value.clone(),
         );
 
         // TODO: Handle precompiled
@@ -264,10 +259,7 @@ impl<F: Field> ExecutionGadget<F> for CallOpGadget<F> {
                     rw_counter: Delta(rw_counter_delta),
                     program_counter: Delta(1.expr()),
                     stack_pointer: Delta(stack_pointer_delta.expr()),
-                    gas_left: Delta(
-                        call_gadget.has_value.clone() * GAS_STIPEND_CALL_WITH_VALUE.expr()
-                            - gas_cost.clone(),
-                    ),
+                    gas_left: Delta(callee_gas.stipend() - gas_cost.clone()),
                     memory_word_size: To(memory_expansion.next_memory_word_size()),
                     // For CALL opcode, `transfer` invocation has two account write if value is not
                     // zero.
@@ -292,10 +284,7 @@ impl<F: Field> ExecutionGadget<F> for CallOpGadget<F> {
                 rw_counter: Delta(22.expr()),
                 program_counter: Delta(1.expr()),
                 stack_pointer: Delta(stack_pointer_delta.expr()),
-                gas_left: Delta(
-                    call_gadget.has_value.clone() * GAS_STIPEND_CALL_WITH_VALUE.expr()
-                        - gas_cost.clone(),
-                ),
+                gas_left: Delta(callee_gas.stipend() - gas_cost.clone()),
                 memory_word_size: To(memory_expansion.next_memory_word_size()),
                 reversible_write_counter: Delta(1.expr()),
                 ..StepStateTransition::default()
@@ -317,7 +306,7 @@ impl<F: Field> ExecutionGadget<F> for CallOpGadget<F> {
                     ),
                     (
                         CallContextFieldTag::GasLeft,
-                        cb.curr.state.gas_left.expr() - gas_cost - callee_gas_left.clone(),
+                        cb.curr.state.gas_left.expr() - gas_cost - callee_gas.callee_gas_left(),
                     ),
                     (
                         CallContextFieldTag::MemorySize,
@@ -400,10 +389,6 @@ impl<F: Field> ExecutionGadget<F> for CallOpGadget<F> {
                     cb.call_context_lookup_write(Some(callee_call_id.expr()), field_tag, value);
                 }
 
-                // Give gas stipend if value is not zero
-                let callee_gas_left = callee_gas_left
-                    + call_gadget.has_value.clone() * GAS_STIPEND_CALL_WITH_VALUE.expr();
-
                 // For CALL opcode, it has an extra stack pop `value` (+1) and if the value is
                 // not zero, two account write for `transfer` call (+2).
                 //
@@ -427,7 +412,8 @@ impl<F: Field> ExecutionGadget<F> for CallOpGadget<F> {
                     is_root: To(false.expr()),
                     is_create: To(false.expr()),
                     code_hash: To(call_gadget.callee_code_hash.to_word()),
-                    gas_left: To(callee_gas_left),
+                    // Give gas stipend if value is not zero
+                    gas_left: To(callee_gas.callee_gas_left_with_stipend()),
                     // For CALL opcode, `transfer` invocation has two account write if value is not
                     // zero.
                     reversible_write_counter: To(transfer_rwc_delta),
@@ -457,8 +443,7 @@ impl<F: Field> ExecutionGadget<F> for CallOpGadget<F> {
             caller_balance,
             is_insufficient_balance,
             is_depth_ok,
-            one_64th_gas,
-            capped_callee_gas_left,
+            callee_gas,
         }
     }
 
@@ -613,6 +598,7 @@ impl<F: Field> ExecutionGadget<F> for CallOpGadget<F> {
 
         let has_value = !value.is_zero() && !is_delegatecall;
         let gas_cost = self.call.cal_gas_cost_for_assignment(
+            &block.chain_config,
             memory_expansion_gas_cost,
             is_warm_prev,
             is_call,
@@ -621,14 +607,8 @@ impl<F: Field> ExecutionGadget<F> for CallOpGadget<F> {
         )?;
         let gas_available: u64 = step.gas_left - gas_cost;
 
-        self.one_64th_gas
-            .assign(region, offset, gas_available.into())?;
-        self.capped_callee_gas_left.assign(
-            region,
-            offset,
-            F::from(gas.low_u64()),
-            F::from(gas_available - gas_available / 64),
-        )?;
+        self.callee_gas
+            .assign(region, offset, gas_available, Some(gas))?;
 
         Ok(())
     }
@@ -203,10 +203,12 @@ impl<F: Field> ExecutionGadget<F> for CodeCopyGadget<F> {
 #[cfg(test)]
 mod tests {
     use crate::test_util::CircuitTestBuilder;
-    use eth_types::{bytecode, Word};
-    use mock::TestContext;
+    use eth_types::{bytecode, Bytecode, Word};
+    use mock::{
+        eth, generate_mock_call_bytecode, MockCallBytecodeParams, TestContext, MOCK_ACCOUNTS,
+    };
 
-    fn test_ok(code_offset: Word, memory_offset: Word, size: usize, large: bool) {
+    fn test_bytecode(code_offset: Word, memory_offset: Word, size: usize, large: bool) -> Bytecode {
         let mut code = bytecode! {};
         if large {
             for _ in 0..size {
@@ -221,6 +223,11 @@ mod tests {
             STOP
         };
         code.append(&tail);
+        code
+    }
+
+    fn test_ok(code_offset: Word, memory_offset: Word, size: usize, large: bool) {
+        let code = test_bytecode(code_offset, memory_offset, size, large);
 
         CircuitTestBuilder::new_from_test_ctx(
             TestContext::<2, 1>::simple_ctx_with_bytecode(code).unwrap(),
@@ -228,6 +235,52 @@ mod tests {
         .run();
     }
 
+    fn test_internal_ok(code_offset: Word, memory_offset: Word, size: usize) {
+        let (addr_a, addr_b) = (MOCK_ACCOUNTS[0], MOCK_ACCOUNTS[1]);
+
+        // code B gets called by code A, so the call is an internal call.
+        let code_b = test_bytecode(code_offset, memory_offset, size, false);
+        let code_a = generate_mock_call_bytecode(MockCallBytecodeParams {
+            address: addr_b,
+            ..MockCallBytecodeParams::default()
+        });
+
+        let ctx = TestContext::<3, 1>::new(
+            None,
+            |accs| {
+                accs[0].address(addr_b).code(code_b);
+                accs[1].address(addr_a).code(code_a);
+                accs[2].address(MOCK_ACCOUNTS[2]).balance(eth(10));
+            },
+            |mut txs, accs| {
+                txs[0].to(accs[1].address).from(accs[2].address);
+            },
+            |block, _tx| block,
+        )
+        .unwrap();
+
+        CircuitTestBuilder::new_from_test_ctx(ctx).run();
+    }
+
+    fn test_root_create_ok(code_offset: Word, memory_offset: Word, size: usize) {
+        // The init code copies itself, so the source is the creation bytecode.
+        let code = test_bytecode(code_offset, memory_offset, size, false);
+
+        let ctx = TestContext::<1, 1>::new(
+            None,
+            |accs| {
+                accs[0].address(MOCK_ACCOUNTS[0]).balance(eth(10));
+            },
+            |mut txs, accs| {
+                txs[0].from(accs[0].address).input(code.code().into());
+            },
+            |block, _| block,
+        )
+        .unwrap();
+
+        CircuitTestBuilder::new_from_test_ctx(ctx).run();
+    }
+
     #[test]
     fn codecopy_gadget_simple() {
         test_ok(0x00.into(), 0x00.into(), 0x20, false);
@@ -249,4 +302,25 @@ mod tests {
     fn codecopy_gadget_overflow_memory_offset_and_zero_size() {
         test_ok(0x102.into(), Word::MAX, 0, false);
     }
+
+    #[test]
+    fn codecopy_gadget_beyond_code_size() {
+        // The copy starts within the code and is padded with zeros past its end.
+        test_ok(0x10.into(), 0x00.into(), 0x40, false);
+        // The copy starts past the end of the code, so only zeros are copied.
+        test_ok(0x80.into(), 0x20.into(), 0x20, false);
+    }
+
+    #[test]
+    fn codecopy_gadget_internal() {
+        test_internal_ok(0x00.into(), 0x00.into(), 0x20);
+        test_internal_ok(0x10.into(), 0x40.into(), 0x40);
+        test_internal_ok(Word::MAX, 0x00.into(), 0x20);
+    }
+
+    #[test]
+    fn codecopy_gadget_root_create() {
+        test_root_create_ok(0x00.into(), 0x00.into(), 0x20);
+        test_root_create_ok(0x10.into(), 0x20.into(), 0x40);
+    }
 }
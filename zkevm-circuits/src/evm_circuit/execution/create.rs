@@ -2,12 +2,12 @@ use crate::{
     evm_circuit::{
         execution::ExecutionGadget,
         param::{
-            N_BYTES_ACCOUNT_ADDRESS, N_BYTES_GAS, N_BYTES_MEMORY_ADDRESS, N_BYTES_MEMORY_WORD_SIZE,
-            N_BYTES_U64, N_BYTES_WORD,
+            N_BYTES_ACCOUNT_ADDRESS, N_BYTES_MEMORY_ADDRESS, N_BYTES_MEMORY_WORD_SIZE, N_BYTES_U64,
+            N_BYTES_WORD,
         },
         step::ExecutionState,
         util::{
-            common_gadget::TransferGadget,
+            common_gadget::{CalleeGasGadget, TransferGadget},
             constraint_builder::{
                 ConstrainBuilderCommon, EVMConstraintBuilder, ReversionInfo, StepStateTransition,
                 Transition::{Delta, To},
@@ -74,7 +74,7 @@ pub(crate) struct CreateGadget<F, const IS_CREATE2: bool, const S: ExecutionStat
     not_address_collision: IsZeroWordGadget<F, Word<Expression<F>>>,
 
     memory_expansion: MemoryExpansionGadget<F, 1, N_BYTES_MEMORY_WORD_SIZE>,
-    gas_left: ConstantDivisionGadget<F, N_BYTES_GAS>,
+    callee_gas: CalleeGasGadget<F>,
 }
 
 impl<F: Field, const IS_CREATE2: bool, const S: ExecutionState> ExecutionGadget<F>
@@ -166,8 +166,7 @@ impl<F: Field, const IS_CREATE2: bool, const S: ExecutionState> ExecutionGadget<
             );
         let gas_cost = GasCost::CREATE.expr() + memory_expansion.gas_cost() + keccak_gas_cost;
         let gas_remaining = cb.curr.state.gas_left.expr() - gas_cost.clone();
-        let gas_left = ConstantDivisionGadget::construct(cb, gas_remaining.clone(), 64);
-        let callee_gas_left = gas_remaining - gas_left.quotient();
+        let callee_gas = CalleeGasGadget::construct_create(cb, gas_remaining);
 
         let was_warm = cb.query_bool();
         let init_code_rlc = cb.query_cell_phase2();
@@ -228,7 +227,7 @@ impl<F: Field, const IS_CREATE2: bool, const S: ExecutionState> ExecutionGadget<
             ),
             (
                 CallContextFieldTag::GasLeft,
-                Word::from_lo_unchecked(gas_left.quotient()),
+                Word::from_lo_unchecked(callee_gas.one_64th_gas()),
             ),
             (
                 CallContextFieldTag::MemorySize,
@@ -351,7 +350,7 @@ impl<F: Field, const IS_CREATE2: bool, const S: ExecutionState> ExecutionGadget<
                         is_root: To(false.expr()),
                         is_create: To(true.expr()),
                         code_hash: To(create.code_hash()),
-                        gas_left: To(callee_gas_left),
+                        gas_left: To(callee_gas.callee_gas_left()),
                         reversible_write_counter: To(
                             1.expr() + transfer.reversible_w_delta().expr()
                         ),
@@ -435,7 +434,7 @@ impl<F: Field, const IS_CREATE2: bool, const S: ExecutionState> ExecutionGadget<
             init_code,
             init_code_rlc,
             memory_expansion,
-            gas_left,
+            callee_gas,
             init_code_word_size,
             create,
             caller_balance,
@@ -569,7 +568,7 @@ impl<F: Field, const IS_CREATE2: bool, const S: ExecutionState> ExecutionGadget<
                 } else {
                     INIT_CODE_WORD_GAS
                 };
-        self.gas_left.assign(region, offset, gas_left.into())?;
+        self.callee_gas.assign(region, offset, gas_left, None)?;
         self.callee_reversion_info.assign(
             region,
             offset,
@@ -4,16 +4,20 @@ use crate::{
     evm_circuit::{
         execution::ExecutionGadget,
         step::ExecutionState,
-        util::{constraint_builder::EVMConstraintBuilder, CachedRegion},
+        util::{constraint_builder::EVMConstraintBuilder, CachedRegion, Cell},
         witness::{Block, Call, ExecStep, Transaction},
     },
-    util::word::{WordCell, WordExpr},
+    util::{
+        word::{WordCell, WordExpr},
+        Expr,
+    },
 };
 use eth_types::Field;
-use halo2_proofs::plonk::Error;
+use halo2_proofs::{circuit::Value, plonk::Error};
 
 #[derive(Clone, Debug)]
 pub(crate) struct DummyGadget<F, const N_POP: usize, const N_PUSH: usize, const S: ExecutionState> {
+    opcode: Cell<F>,
     pops: [WordCell<F>; N_POP],
     pushes: [WordCell<F>; N_PUSH],
     _marker: PhantomData<F>,
@@ -27,6 +31,9 @@ impl<F: Field, const N_POP: usize, const N_PUSH: usize, const S: ExecutionState>
     const EXECUTION_STATE: ExecutionState = S;
 
     fn configure(cb: &mut EVMConstraintBuilder<F>) -> Self {
+        let opcode = cb.query_cell();
+        cb.opcode_lookup(opcode.expr(), 1.expr());
+
         let pops: [WordCell<F>; N_POP] = [(); N_POP].map(|_| cb.query_word_unchecked());
         let pushes: [WordCell<F>; N_PUSH] = [(); N_PUSH].map(|_| cb.query_word_unchecked());
         for pop in pops.iter() {
@@ -36,6 +43,7 @@ impl<F: Field, const N_POP: usize, const N_PUSH: usize, const S: ExecutionState>
             cb.stack_push(push.to_word());
         }
         Self {
+            opcode,
             pops,
             pushes,
             _marker: PhantomData,
@@ -59,6 +67,10 @@ impl<F: Field, const N_POP: usize, const N_PUSH: usize, const S: ExecutionState>
             log::warn!("DummyGadget: wrong number of rw indices for {:?}", step);
         }
 
+        let opcode = step.opcode().unwrap();
+        self.opcode
+            .assign(region, offset, Value::known(F::from(opcode.as_u64())))?;
+
         for i in 0..N_POP {
             let value = block.get_rws(step, i).stack_value();
             self.pops[i].assign_u256(region, offset, value)?;
@@ -17,6 +17,9 @@ use crate::{
 use eth_types::{evm_types::OpcodeId, Field};
 use halo2_proofs::plonk::Error;
 
+/// Gadget for DUP1, ..., DUP16.  The stack slot to copy is derived from the
+/// opcode, which the same context gadget checks against the execution state,
+/// so all the variants share one configuration.
 #[derive(Clone, Debug)]
 pub(crate) struct DupGadget<F> {
     same_context: SameContextGadget<F>,
@@ -4,7 +4,7 @@ use crate::{
         param::N_BYTES_GAS,
         step::ExecutionState,
         util::{
-            common_gadget::UpdateBalanceGadget,
+            common_gadget::{L1DataFeeGadget, UpdateBalanceGadget},
             constraint_builder::{
                 ConstrainBuilderCommon, EVMConstraintBuilder, StepStateTransition,
                 Transition::{Delta, Same},
@@ -23,9 +23,15 @@ use crate::{
         Expr,
     },
 };
-use bus_mapping::operation::Target;
-use eth_types::{evm_types::MAX_REFUND_QUOTIENT_OF_GAS_USED, Field};
-use halo2_proofs::{circuit::Value, plonk::Error};
+use bus_mapping::{chain_config::Eip, operation::Target};
+use eth_types::{
+    evm_types::{MAX_REFUND_QUOTIENT_OF_GAS_USED, MAX_REFUND_QUOTIENT_OF_GAS_USED_PRE_EIP3529},
+    Field,
+};
+use halo2_proofs::{
+    circuit::Value,
+    plonk::{Error, Expression},
+};
 use strum::EnumCount;
 
 #[derive(Clone, Debug)]
@@ -42,6 +48,9 @@ pub(crate) struct EndTxGadget<F> {
     mul_effective_tip_by_gas_used: MulWordByU64Gadget<F>,
     coinbase: WordCell<F>,
     coinbase_reward: UpdateBalanceGadget<F, 2, true>,
+    // L1 data fee credited to the fee vault, if the circuit is configured with
+    // an L1 data fee policy.
+    l1_data_fee: Option<(Cell<F>, L1DataFeeGadget<F>, UpdateBalanceGadget<F, 2, true>)>,
     current_cumulative_gas_used: Cell<F>,
     is_first_tx: IsEqualGadget<F>,
     is_persistent: Cell<F>,
@@ -61,13 +70,16 @@ impl<F: Field> ExecutionGadget<F> for EndTxGadget<F> {
             cb.tx_context_as_word(tx_id.expr(), TxContextFieldTag::CallerAddress, None);
         let tx_gas_price = cb.tx_context_as_word32(tx_id.expr(), TxContextFieldTag::GasPrice, None);
 
-        // Calculate effective gas to refund
+        // Calculate effective gas to refund, capped by the quotient of EIP-3529
+        // if enabled.
         let gas_used = tx_gas.expr() - cb.curr.state.gas_left.expr();
-        let max_refund = ConstantDivisionGadget::construct(
-            cb,
-            gas_used.clone(),
-            MAX_REFUND_QUOTIENT_OF_GAS_USED as u64,
-        );
+        let max_refund_quotient = if cb.feature_config().is_enabled(Eip::Eip3529) {
+            MAX_REFUND_QUOTIENT_OF_GAS_USED
+        } else {
+            MAX_REFUND_QUOTIENT_OF_GAS_USED_PRE_EIP3529
+        };
+        let max_refund =
+            ConstantDivisionGadget::construct(cb, gas_used.clone(), max_refund_quotient as u64);
         let refund = cb.query_cell();
         cb.tx_refund_read(tx_id.expr(), Word::from_lo_unchecked(refund.expr()));
         let effective_refund = MinMaxGadget::construct(cb, max_refund.quotient(), refund.expr());
@@ -85,7 +97,10 @@ impl<F: Field> ExecutionGadget<F> for EndTxGadget<F> {
             None,
         );
 
-        // Add gas_used * effective_tip to coinbase's balance
+        // Add gas_used * effective_tip to coinbase's balance.  The base fee part of the gas
+        // price paid by the caller is credited to no one, which burns it as in EIP-1559.  For
+        // the blocks before London the base fee of the block table is 0, so the coinbase is
+        // credited with the whole gas price.
         let coinbase = cb.query_word_unchecked();
         let base_fee = cb.query_word32();
         // lookup && range check
@@ -107,6 +122,22 @@ impl<F: Field> ExecutionGadget<F> for EndTxGadget<F> {
             None,
         );
 
+        // Add the L1 data fee deducted from the caller in BeginTx to the fee vault's
+        // balance.
+        let l1_data_fee = cb.feature_config().l1_data_fee.as_ref().map(|policy| {
+            let tx_call_data_gas_cost =
+                cb.tx_context(tx_id.expr(), TxContextFieldTag::CallDataGasCost, None);
+            let l1_data_fee = L1DataFeeGadget::construct(cb, policy, tx_call_data_gas_cost.expr());
+            let fee_vault_reward = UpdateBalanceGadget::construct(
+                cb,
+                Word::<F>::from(policy.fee_vault).map(Expression::Constant),
+                vec![l1_data_fee.l1_fee()],
+                None,
+            );
+            (tx_call_data_gas_cost, l1_data_fee, fee_vault_reward)
+        });
+        let l1_data_fee_rw_delta = l1_data_fee.is_some().expr();
+
         // constrain tx receipt fields
         cb.tx_receipt_lookup(
             1.expr(),
@@ -158,7 +189,9 @@ impl<F: Field> ExecutionGadget<F> for EndTxGadget<F> {
                 );
 
                 cb.require_step_state_transition(StepStateTransition {
-                    rw_counter: Delta(10.expr() - is_first_tx.expr()),
+                    rw_counter: Delta(
+                        10.expr() - is_first_tx.expr() + l1_data_fee_rw_delta.clone(),
+                    ),
                     ..StepStateTransition::any()
                 });
             },
@@ -168,7 +201,7 @@ impl<F: Field> ExecutionGadget<F> for EndTxGadget<F> {
             cb.next.execution_state_selector([ExecutionState::EndBlock]),
             |cb| {
                 cb.require_step_state_transition(StepStateTransition {
-                    rw_counter: Delta(9.expr() - is_first_tx.expr()),
+                    rw_counter: Delta(9.expr() - is_first_tx.expr() + l1_data_fee_rw_delta),
                     // We propagate call_id so that EndBlock can get the last tx_id
                     // in order to count processed txs.
                     call_id: Same,
@@ -190,6 +223,7 @@ impl<F: Field> ExecutionGadget<F> for EndTxGadget<F> {
             mul_effective_tip_by_gas_used,
             coinbase,
             coinbase_reward,
+            l1_data_fee,
             current_cumulative_gas_used,
             is_first_tx,
             is_persistent,
@@ -264,6 +298,25 @@ impl<F: Field> ExecutionGadget<F> for EndTxGadget<F> {
             vec![effective_tip * gas_used],
             coinbase_balance,
         )?;
+        if let (Some((tx_call_data_gas_cost, l1_data_fee, fee_vault_reward)), Some(policy)) =
+            (&self.l1_data_fee, &block.l1_data_fee)
+        {
+            let (fee_vault_balance, fee_vault_balance_prev) =
+                block.get_rws(step, 5).account_value_pair();
+            tx_call_data_gas_cost.assign(
+                region,
+                offset,
+                Value::known(F::from(tx.call_data_gas_cost())),
+            )?;
+            let l1_fee = l1_data_fee.assign(region, offset, policy, tx.call_data_gas_cost())?;
+            fee_vault_reward.assign(
+                region,
+                offset,
+                fee_vault_balance_prev,
+                vec![l1_fee],
+                fee_vault_balance,
+            )?;
+        }
 
         let current_cumulative_gas_used: u64 = if tx.id == 1 {
             0
@@ -297,11 +350,16 @@ impl<F: Field> ExecutionGadget<F> for EndTxGadget<F> {
 #[cfg(test)]
 mod test {
     use crate::test_util::CircuitTestBuilder;
-    use bus_mapping::circuit_input_builder::FixedCParams;
+    use bus_mapping::{
+        chain_config::{ChainConfig, Hardfork},
+        circuit_input_builder::{FixedCParams, L1DataFeePolicy, L1_FEE_PRECISION},
+    };
     use eth_types::{self, bytecode, Word};
     use mock::{
         eth, gwei, test_ctx::helpers::account_0_code_account_1_no_code, TestContext, MOCK_ACCOUNTS,
+        MOCK_CHAIN_ID,
     };
+    use std::sync::Arc;
 
     fn test_ok<const NACC: usize, const NTX: usize>(ctx: TestContext<NACC, NTX>) {
         CircuitTestBuilder::new_from_test_ctx(ctx)
@@ -476,4 +534,101 @@ mod test {
             .unwrap(),
         );
     }
+
+    #[test]
+    fn end_tx_gadget_base_fee() {
+        // The coinbase is credited with gas_used * (gas_price - base_fee), and the base fee is
+        // burned.  With a gas price equal to the base fee the coinbase is credited with nothing.
+        for gas_price in [gwei(2), gwei(1)] {
+            test_ok(
+                TestContext::<2, 1>::new(
+                    None,
+                    account_0_code_account_1_no_code(bytecode! { STOP }),
+                    |mut txs, accs| {
+                        txs[0]
+                            .to(accs[0].address)
+                            .from(accs[1].address)
+                            .gas_price(gas_price);
+                    },
+                    |block, _tx| block.base_fee_per_gas(Some(gwei(1))),
+                )
+                .unwrap(),
+            );
+        }
+    }
+
+    #[test]
+    fn end_tx_gadget_l1_data_fee() {
+        // The L1 data fee is deducted from the caller in BeginTx and credited to the fee vault
+        // in EndTx, for the transactions with and without calldata.
+        let ctx = TestContext::<3, 2>::new(
+            None,
+            |accs| {
+                accs[0].address(MOCK_ACCOUNTS[0]).code(bytecode! { STOP });
+                accs[1].address(MOCK_ACCOUNTS[1]).balance(eth(10));
+                accs[2].address(MOCK_ACCOUNTS[2]);
+            },
+            |mut txs, accs| {
+                txs[0]
+                    .to(accs[0].address)
+                    .from(accs[1].address)
+                    .input(vec![0, 1, 2, 3].into());
+                txs[1].to(accs[0].address).from(accs[1].address);
+            },
+            |block, _tx| block.number(0xcafeu64),
+        )
+        .unwrap();
+
+        CircuitTestBuilder::new_from_test_ctx(ctx)
+            .params(FixedCParams {
+                max_txs: 5,
+                ..Default::default()
+            })
+            .input_builder_modifier(Box::new(|builder| {
+                builder.block.fee_policy = Arc::new(L1DataFeePolicy {
+                    fee_vault: MOCK_ACCOUNTS[2],
+                    l1_base_fee: gwei(30),
+                    overhead: 2100,
+                    scalar: L1_FEE_PRECISION / 2,
+                });
+            }))
+            .run();
+    }
+
+    #[test]
+    fn end_tx_gadget_berlin() {
+        // Before London the refund is capped by half of the gas used, the coinbase isn't warm
+        // at the beginning of the transaction and BASEFEE is an invalid opcode.
+        let ctx = TestContext::<3, 2>::new_with_hardfork(
+            None,
+            |accs| {
+                accs[0]
+                    .address(MOCK_ACCOUNTS[0])
+                    .code(bytecode! { COINBASE BALANCE STOP });
+                accs[1].address(MOCK_ACCOUNTS[1]).balance(eth(10));
+                accs[2]
+                    .address(MOCK_ACCOUNTS[2])
+                    .code(bytecode! { BASEFEE STOP });
+            },
+            |mut txs, accs| {
+                txs[0].to(accs[0].address).from(accs[1].address);
+                txs[1].to(accs[2].address).from(accs[1].address);
+            },
+            |block, _tx| block.number(0xcafeu64).base_fee_per_gas(Some(Word::zero())),
+            "Berlin",
+        )
+        .unwrap();
+
+        CircuitTestBuilder::new_from_test_ctx(ctx)
+            .params(FixedCParams {
+                max_txs: 5,
+                ..Default::default()
+            })
+            .input_builder_modifier(Box::new(|builder| {
+                builder
+                    .block
+                    .set_chain_config(ChainConfig::new(MOCK_CHAIN_ID.as_u64(), Hardfork::Berlin));
+            }))
+            .run();
+    }
 }
@@ -4,20 +4,17 @@ use crate::{
         param::N_BYTES_GAS,
         step::ExecutionState,
         util::{
-            common_gadget::CommonErrorGadget,
+            common_gadget::{account_access_gas_cost, CommonErrorGadget},
             constraint_builder::{ConstrainBuilderCommon, EVMConstraintBuilder},
             math_gadget::LtGadget,
-            select, AccountAddress, CachedRegion, Cell,
+            AccountAddress, CachedRegion, Cell,
         },
         witness::{Block, Call, ExecStep, Transaction},
     },
     table::CallContextFieldTag,
     util::{word::WordExpr, Expr},
 };
-use eth_types::{
-    evm_types::{GasCost, OpcodeId},
-    Field, ToAddress,
-};
+use eth_types::{evm_types::OpcodeId, Field, ToAddress};
 use halo2_proofs::{circuit::Value, plonk::Error};
 
 /// Gadget to implement the corresponding out of gas errors for
@@ -56,11 +53,7 @@ impl<F: Field> ExecutionGadget<F> for ErrorOOGAccountAccessGadget<F> {
         // read is_warm
         cb.account_access_list_read(tx_id.expr(), address.to_word(), is_warm.expr());
 
-        let gas_cost = select::expr(
-            is_warm.expr(),
-            GasCost::WARM_ACCESS.expr(),
-            GasCost::COLD_ACCOUNT_ACCESS.expr(),
-        );
+        let gas_cost = account_access_gas_cost(cb, is_warm.expr());
 
         let insufficient_gas_cost =
             LtGadget::construct(cb, cb.curr.state.gas_left.expr(), gas_cost);
@@ -107,11 +100,7 @@ impl<F: Field> ExecutionGadget<F> for ErrorOOGAccountAccessGadget<F> {
             .assign(region, offset, Value::known(F::from(is_warm as u64)))?;
 
         // BALANCE EXTCODESIZE EXTCODEHASH shares same gas cost model
-        let gas_cost = if is_warm {
-            GasCost::WARM_ACCESS
-        } else {
-            GasCost::COLD_ACCOUNT_ACCESS
-        };
+        let gas_cost = block.chain_config.account_access_gas(is_warm);
 
         self.insufficient_gas_cost.assign_value(
             region,
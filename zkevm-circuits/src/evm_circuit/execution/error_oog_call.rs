@@ -74,7 +74,7 @@ impl<F: Field> ExecutionGadget<F> for ErrorOOGCallGadget<F> {
         });
 
         // Verify gas cost
-        let gas_cost = call_gadget.gas_cost_expr(is_warm.expr(), is_call.expr());
+        let gas_cost = call_gadget.gas_cost_expr(cb, is_warm.expr(), is_call.expr());
 
         // Check if the amount of gas available is less than the amount of gas required
         let insufficient_gas = LtGadget::construct(cb, cb.curr.state.gas_left.expr(), gas_cost);
@@ -190,6 +190,7 @@ impl<F: Field> ExecutionGadget<F> for ErrorOOGCallGadget<F> {
 
         let has_value = !value.is_zero();
         let gas_cost = self.call.cal_gas_cost_for_assignment(
+            &block.chain_config,
             memory_expansion_gas_cost,
             is_warm_prev,
             true,
@@ -35,7 +35,7 @@ impl<F: Field> ExecutionGadget<F> for ErrorOOGConstantGadget<F> {
 
         let gas_required = cb.query_cell();
 
-        cb.constant_gas_lookup(opcode.expr(), gas_required.expr());
+        cb.constant_gas_lookup(opcode.expr(), gas_required.expr(), 0.expr());
         // Check if the amount of gas available is less than the amount of gas
         // required
         let insufficient_gas =
@@ -114,6 +114,10 @@ mod test {
     }
 
     fn test_oog_constant(tx: eth_types::Transaction, is_success: bool) {
+        CircuitTestBuilder::new_from_test_ctx(oog_constant_ctx(tx, is_success)).run();
+    }
+
+    fn oog_constant_ctx(tx: eth_types::Transaction, is_success: bool) -> TestContext<2, 1> {
         let code = if is_success {
             bytecode! {
                 PUSH1(0)
@@ -128,7 +132,7 @@ mod test {
         };
 
         // Get the execution steps from the external tracer
-        let ctx = TestContext::<2, 1>::new(
+        TestContext::<2, 1>::new(
             None,
             account_0_code_account_1_no_code(code),
             |mut txs, _accs| {
@@ -142,9 +146,7 @@ mod test {
             },
             |block, _tx| block.number(0xcafeu64),
         )
-        .unwrap();
-
-        CircuitTestBuilder::new_from_test_ctx(ctx).run();
+        .unwrap()
     }
 
     fn mock_tx(value: Word, gas_price: Word, calldata: Vec<u8>) -> eth_types::Transaction {
@@ -168,6 +170,17 @@ mod test {
         test_oog_constant(mock_tx(eth(1), gwei(2), vec![]), false);
     }
 
+    #[test]
+    fn test_oog_constant_root_missing_step() {
+        // The tracer may not log the last opcode, at which the root call runs out of gas.
+        let mut ctx = oog_constant_ctx(mock_tx(eth(1), gwei(2), vec![]), false);
+        let oog_step = ctx.geth_traces[0].struct_logs.pop().unwrap();
+        assert_eq!(oog_step.op, OpcodeId::PUSH1);
+        assert!(oog_step.error.is_some());
+
+        CircuitTestBuilder::new_from_test_ctx(ctx).run();
+    }
+
     #[derive(Clone, Copy, Debug, Default)]
     struct Stack {
         gas: u64,
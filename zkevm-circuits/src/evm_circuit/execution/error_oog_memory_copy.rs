@@ -4,7 +4,7 @@ use crate::{
         param::{N_BYTES_GAS, N_BYTES_MEMORY_WORD_SIZE},
         step::ExecutionState,
         util::{
-            common_gadget::CommonErrorGadget,
+            common_gadget::{account_access_gas_cost, CommonErrorGadget},
             constraint_builder::{ConstrainBuilderCommon, EVMConstraintBuilder},
             math_gadget::{IsZeroGadget, LtGadget},
             memory_gadget::{
@@ -107,11 +107,7 @@ impl<F: Field> ExecutionGadget<F> for ErrorOOGMemoryCopyGadget<F> {
             is_extcodecopy.expr(),
             // According to EIP-2929, EXTCODECOPY constant gas cost is different for cold and warm
             // accounts.
-            select::expr(
-                is_warm.expr(),
-                GasCost::WARM_ACCESS.expr(),
-                GasCost::COLD_ACCOUNT_ACCESS.expr(),
-            ),
+            account_access_gas_cost(cb, is_warm.expr()),
             // Constant gas cost is same for CALLDATACOPY, CODECOPY and RETURNDATACOPY.
             OpcodeId::CALLDATACOPY.constant_gas_cost().expr(),
         );
@@ -205,11 +201,7 @@ impl<F: Field> ExecutionGadget<F> for ErrorOOGMemoryCopyGadget<F> {
             memory_expansion_cost,
         )?;
         let constant_gas_cost = if is_extcodecopy {
-            if is_warm {
-                GasCost::WARM_ACCESS
-            } else {
-                GasCost::COLD_ACCOUNT_ACCESS
-            }
+            block.chain_config.account_access_gas(is_warm)
         } else {
             GasCost::FASTEST
         };
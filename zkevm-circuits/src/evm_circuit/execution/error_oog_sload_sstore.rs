@@ -6,8 +6,8 @@ use crate::{
         util::{
             and,
             common_gadget::{
-                cal_sload_gas_cost_for_assignment, cal_sstore_gas_cost_for_assignment,
-                CommonErrorGadget, SloadGasGadget, SstoreGasGadget,
+                cal_sstore_gas_cost_for_assignment, CommonErrorGadget, SloadGasGadget,
+                SstoreGasGadget,
             },
             constraint_builder::{ConstrainBuilderCommon, EVMConstraintBuilder},
             math_gadget::{LtGadget, PairSelectGadget},
@@ -170,11 +170,16 @@ impl<F: Field> ExecutionGadget<F> for ErrorOOGSloadSstoreGadget<F> {
         let (value, value_prev, original_value, gas_cost) = if is_sstore {
             let value = block.get_rws(step, 5).stack_value();
             let (_, value_prev, _, original_value) = block.get_rws(step, 6).storage_value_aux();
-            let gas_cost =
-                cal_sstore_gas_cost_for_assignment(value, value_prev, original_value, is_warm);
+            let gas_cost = cal_sstore_gas_cost_for_assignment(
+                &block.chain_config,
+                value,
+                value_prev,
+                original_value,
+                is_warm,
+            );
             (value, value_prev, original_value, gas_cost)
         } else {
-            let gas_cost = cal_sload_gas_cost_for_assignment(is_warm);
+            let gas_cost = block.chain_config.sload_gas(is_warm);
             (U256::zero(), U256::zero(), U256::zero(), gas_cost)
         };
 
@@ -245,6 +250,7 @@ mod test {
         evm_circuit::{test::rand_bytes, util::common_gadget::cal_sstore_gas_cost_for_assignment},
         test_util::CircuitTestBuilder,
     };
+    use bus_mapping::chain_config::ChainConfig;
     use eth_types::{
         bytecode,
         evm_types::{GasCost, OpcodeId},
@@ -379,14 +385,14 @@ mod test {
                 SLOAD
             };
             let mut gas_cost =
-                OpcodeId::PUSH32.constant_gas_cost() + cal_sload_gas_cost_for_assignment(false);
+                OpcodeId::PUSH32.constant_gas_cost() + ChainConfig::default().sload_gas(false);
             if is_warm {
                 bytecode.append(&bytecode! {
                     PUSH32(key)
                     SLOAD
                 });
                 gas_cost +=
-                    OpcodeId::PUSH32.constant_gas_cost() + cal_sload_gas_cost_for_assignment(true);
+                    OpcodeId::PUSH32.constant_gas_cost() + ChainConfig::default().sload_gas(true);
             }
 
             Self {
@@ -409,6 +415,7 @@ mod test {
                 SSTORE
             };
             let sstore_gas_cost = cal_sstore_gas_cost_for_assignment(
+                &ChainConfig::default(),
                 value_prev,
                 original_value,
                 original_value,
@@ -426,6 +433,7 @@ mod test {
                     SSTORE
                 });
                 let sstore_gas_cost = cal_sstore_gas_cost_for_assignment(
+                    &ChainConfig::default(),
                     value_prev,
                     original_value,
                     original_value,
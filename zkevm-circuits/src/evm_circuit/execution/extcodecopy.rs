@@ -3,7 +3,7 @@ use crate::{
         param::{N_BYTES_ACCOUNT_ADDRESS, N_BYTES_MEMORY_WORD_SIZE, N_BYTES_U64},
         step::ExecutionState,
         util::{
-            common_gadget::{SameContextGadget, WordByteCapGadget},
+            common_gadget::{account_access_gas_cost, SameContextGadget, WordByteCapGadget},
             constraint_builder::{
                 ConstrainBuilderCommon, EVMConstraintBuilder, ReversionInfo, StepStateTransition,
                 Transition,
@@ -107,12 +107,7 @@ impl<F: Field> ExecutionGadget<F> for ExtcodecopyGadget<F> {
             memory_address.length(),
             memory_expansion.gas_cost(),
         );
-        let gas_cost = memory_copier_gas.gas_cost()
-            + select::expr(
-                is_warm.expr(),
-                GasCost::WARM_ACCESS.expr(),
-                GasCost::COLD_ACCOUNT_ACCESS.expr(),
-            );
+        let gas_cost = memory_copier_gas.gas_cost() + account_access_gas_cost(cb, is_warm.expr());
 
         let copy_rwc_inc = cb.query_cell();
         cb.condition(memory_address.has_length(), |cb| {
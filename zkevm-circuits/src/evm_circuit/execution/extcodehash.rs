@@ -4,11 +4,11 @@ use crate::{
         param::N_BYTES_ACCOUNT_ADDRESS,
         step::ExecutionState,
         util::{
-            common_gadget::SameContextGadget,
+            common_gadget::{account_access_gas_cost, SameContextGadget},
             constraint_builder::{
                 EVMConstraintBuilder, ReversionInfo, StepStateTransition, Transition::Delta,
             },
-            select, AccountAddress, CachedRegion, Cell,
+            AccountAddress, CachedRegion, Cell,
         },
         witness::{Block, Call, ExecStep, Transaction},
     },
@@ -18,7 +18,7 @@ use crate::{
         Expr,
     },
 };
-use eth_types::{evm_types::GasCost, Field};
+use eth_types::Field;
 use halo2_proofs::{circuit::Value, plonk::Error};
 
 #[derive(Clone, Debug)]
@@ -68,11 +68,7 @@ impl<F: Field> ExecutionGadget<F> for ExtcodehashGadget<F> {
         );
         cb.stack_push(code_hash.to_word());
 
-        let gas_cost = select::expr(
-            is_warm.expr(),
-            GasCost::WARM_ACCESS.expr(),
-            GasCost::COLD_ACCOUNT_ACCESS.expr(),
-        );
+        let gas_cost = account_access_gas_cost(cb, is_warm.expr());
         let step_state_transition = StepStateTransition {
             rw_counter: Delta(cb.rw_counter_offset()),
             program_counter: Delta(1.expr()),
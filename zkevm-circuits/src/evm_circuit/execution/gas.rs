@@ -109,6 +109,27 @@ mod test {
         test_ok();
     }
 
+    #[test]
+    fn gas_gadget_branch_on_gas_left() {
+        // Jump to the JUMPDEST at offset 10 only if more than 0x10 gas is left
+        let bytecode = bytecode! {
+            PUSH3(0x10)
+            GAS
+            GT
+            PUSH1(10)
+            JUMPI
+            STOP
+            JUMPDEST
+            GAS
+            STOP
+        };
+
+        CircuitTestBuilder::new_from_test_ctx(
+            TestContext::<2, 1>::simple_ctx_with_bytecode(bytecode).unwrap(),
+        )
+        .run();
+    }
+
     #[test]
     fn gas_gadget_incorrect_deduction() {
         let bytecode = bytecode! {
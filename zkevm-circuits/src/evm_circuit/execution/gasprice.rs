@@ -90,9 +90,60 @@ impl<F: Field> ExecutionGadget<F> for GasPriceGadget<F> {
 
 #[cfg(test)]
 mod test {
-    use crate::test_util::CircuitTestBuilder;
-    use eth_types::{bytecode, Word};
-    use mock::test_ctx::{helpers::*, TestContext};
+    use crate::{
+        test_util::CircuitTestBuilder,
+        witness::{Block, Rw},
+    };
+    use bus_mapping::{circuit_input_builder::ExecState, evm::OpcodeId, exec_trace::OperationRef};
+    use eth_types::{bytecode, Bytecode, Word};
+    use halo2_proofs::halo2curves::bn256::Fr;
+    use mock::{
+        eth,
+        test_ctx::{helpers::*, TestContext},
+        MOCK_ACCOUNTS,
+    };
+
+    /// Context in which the code is run by an internal call.
+    fn internal_call_ctx(code: Bytecode) -> TestContext<3, 1> {
+        let (addr_a, addr_b) = (MOCK_ACCOUNTS[0], MOCK_ACCOUNTS[1]);
+        let code_a = bytecode! {
+            .op_call(0xc350u64, addr_b, 0u64, 0u64, 0u64, 0u64, 0u64)
+            STOP
+        };
+
+        TestContext::<3, 1>::new(
+            None,
+            |accs| {
+                accs[0].address(addr_b).code(code);
+                accs[1].address(addr_a).code(code_a).balance(eth(1));
+                accs[2].address(MOCK_ACCOUNTS[2]).balance(eth(10));
+            },
+            |mut txs, accs| {
+                txs[0]
+                    .to(accs[1].address)
+                    .from(accs[2].address)
+                    .gas_price(Word::from(2_000_000_000u64));
+            },
+            |block, _tx| block,
+        )
+        .unwrap()
+    }
+
+    /// Push a wrong value to the stack in the GASPRICE step, which the lookup
+    /// of the value must reject.
+    fn push_wrong_value(block: &mut Block<Fr>) {
+        let step = block.txs[0]
+            .steps()
+            .iter()
+            .find(|step| step.exec_state == ExecState::Op(OpcodeId::GASPRICE))
+            .unwrap()
+            .clone();
+        let OperationRef(target, index) = step.rw_index(1);
+        match &mut block.rws.0.get_mut(&target).unwrap()[index] {
+            Rw::Stack { value, .. } => *value += Word::one(),
+            _ => unreachable!(),
+        }
+    }
 
     #[test]
     fn gasprice_gadget_test() {
@@ -120,4 +171,29 @@ mod test {
 
         CircuitTestBuilder::new_from_test_ctx(ctx).run();
     }
+
+    #[test]
+    fn gasprice_gadget_internal() {
+        let bytecode = bytecode! {
+            GASPRICE
+            STOP
+        };
+
+        CircuitTestBuilder::new_from_test_ctx(internal_call_ctx(bytecode)).run();
+    }
+
+    #[test]
+    fn gasprice_gadget_wrong_value() {
+        CircuitTestBuilder::new_from_test_ctx(internal_call_ctx(bytecode! {
+            GASPRICE
+            STOP
+        }))
+        .block_modifier(Box::new(push_wrong_value))
+        .evm_checks(Box::new(|prover, gate_rows, lookup_rows| {
+            assert!(prover
+                .verify_at_rows_par(gate_rows.iter().cloned(), lookup_rows.iter().cloned())
+                .is_err())
+        }))
+        .run();
+    }
 }
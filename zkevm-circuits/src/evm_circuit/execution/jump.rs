@@ -34,8 +34,8 @@ impl<F: Field> ExecutionGadget<F> for JumpGadget<F> {
         // Pop the value from the stack
         cb.stack_pop(destination.to_word());
 
-        // Lookup opcode at destination
-        cb.opcode_lookup_at(destination.expr(), OpcodeId::JUMPDEST.expr(), 1.expr());
+        // Lookup the JUMPDEST opcode at destination
+        cb.jumpdest_lookup(destination.expr());
 
         // State transition
         let opcode = cb.query_cell();
@@ -76,7 +76,7 @@ impl<F: Field> ExecutionGadget<F> for JumpGadget<F> {
 mod test {
     use crate::{evm_circuit::test::rand_range, test_util::CircuitTestBuilder};
     use eth_types::bytecode;
-    use mock::TestContext;
+    use mock::{eth, TestContext, MOCK_ACCOUNTS};
 
     fn test_ok(destination: usize) {
         assert!((34..(1 << 24) - 1).contains(&destination));
@@ -137,6 +137,32 @@ mod test {
         test_ok(rand_range(34..1 << 11));
     }
 
+    #[test]
+    fn jump_gadget_root_create() {
+        let mut code = bytecode! {
+            PUSH1(4)
+            JUMP
+        };
+        code.write(0, false);
+        code.append(&bytecode! {
+            JUMPDEST
+            STOP
+        });
+
+        let ctx = TestContext::<1, 1>::new(
+            None,
+            |accs| {
+                accs[0].address(MOCK_ACCOUNTS[0]).balance(eth(10));
+            },
+            |mut txs, accs| {
+                txs[0].from(accs[0].address).input(code.code().into());
+            },
+            |block, _| block,
+        )
+        .unwrap();
+        CircuitTestBuilder::new_from_test_ctx(ctx).run();
+    }
+
     #[test]
     fn invalid_jump_err() {
         test_invalid_jump(34);
@@ -55,7 +55,7 @@ impl<F: Field> ExecutionGadget<F> for JumpiGadget<F> {
                 1.expr(),
             );
 
-            cb.opcode_lookup_at(dest.valid_value(), OpcodeId::JUMPDEST.expr(), 1.expr());
+            cb.jumpdest_lookup(dest.valid_value());
         });
 
         // Transit program_counter to destination when should_jump, otherwise by
@@ -114,7 +114,7 @@ mod test {
         test_util::CircuitTestBuilder,
     };
     use eth_types::{bytecode, Word};
-    use mock::TestContext;
+    use mock::{eth, TestContext, MOCK_ACCOUNTS};
 
     fn test_ok(destination: usize, condition: Word) {
         assert!((68..(1 << 24) - 1).contains(&destination));
@@ -169,6 +169,33 @@ mod test {
         test_ok(rand_range(1 << 11..0x5fff), rand_word());
     }
 
+    #[test]
+    fn jumpi_gadget_root_create() {
+        for condition in [0u64, 1] {
+            let code = bytecode! {
+                PUSH1(condition)
+                PUSH1(6)
+                JUMPI
+                STOP
+                JUMPDEST
+                STOP
+            };
+
+            let ctx = TestContext::<1, 1>::new(
+                None,
+                |accs| {
+                    accs[0].address(MOCK_ACCOUNTS[0]).balance(eth(10));
+                },
+                |mut txs, accs| {
+                    txs[0].from(accs[0].address).input(code.code().into());
+                },
+                |block, _| block,
+            )
+            .unwrap();
+            CircuitTestBuilder::new_from_test_ctx(ctx).run();
+        }
+    }
+
     #[test]
     fn jumpi_gadget_with_zero_cond_and_overflow_dest() {
         let bytecode = bytecode! {
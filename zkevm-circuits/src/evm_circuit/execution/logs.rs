@@ -126,6 +126,24 @@ impl<F: Field> ExecutionGadget<F> for LogGadget<F> {
         // check memory copy
         let memory_address = MemoryAddressGadget::construct(cb, mstart, msize);
 
+        // check topic and data lengths used for the RLP encoding of the tx receipt
+        cb.condition(is_persistent.expr(), |cb| {
+            cb.tx_log_lookup(
+                tx_id.expr(),
+                cb.curr.state.log_id.expr() + 1.expr(),
+                TxLogFieldTag::TopicLength,
+                0.expr(),
+                Word::from_lo_unchecked(topic_count.clone()),
+            );
+            cb.tx_log_lookup(
+                tx_id.expr(),
+                cb.curr.state.log_id.expr() + 1.expr(),
+                TxLogFieldTag::DataLength,
+                0.expr(),
+                memory_address.length_word(),
+            );
+        });
+
         // Calculate the next memory size and the gas cost for this memory
         // access
         let memory_expansion = MemoryExpansionGadget::construct(cb, [memory_address.address()]);
@@ -1,27 +1,31 @@
 use crate::{
     evm_circuit::{
         execution::ExecutionGadget,
-        param::N_BYTES_MEMORY_WORD_SIZE,
+        param::{N_BYTES_MEMORY_WORD_SIZE, N_BYTES_WORD},
         step::ExecutionState,
         util::{
             common_gadget::SameContextGadget,
             constraint_builder::{
-                EVMConstraintBuilder, StepStateTransition,
+                ConstrainBuilderCommon, EVMConstraintBuilder, StepStateTransition,
                 Transition::{Delta, To},
             },
             math_gadget::IsEqualGadget,
             memory_gadget::MemoryExpansionGadget,
-            not, CachedRegion, MemoryAddress,
+            not, select, sum, CachedRegion, Cell, MemoryAddress,
         },
         witness::{Block, Call, ExecStep, Transaction},
     },
     util::{
-        word::{Word32Cell, WordExpr},
+        word::{Word, Word32Cell, WordExpr},
         Expr,
     },
 };
-use eth_types::{evm_types::OpcodeId, Field};
-use halo2_proofs::plonk::Error;
+use bus_mapping::operation::MemoryOpMode;
+use eth_types::{evm_types::OpcodeId, Field, U256};
+use halo2_proofs::{
+    circuit::Value,
+    plonk::{Error, Expression},
+};
 
 #[derive(Clone, Debug)]
 pub(crate) struct MemoryGadget<F> {
@@ -31,6 +35,9 @@ pub(crate) struct MemoryGadget<F> {
     memory_expansion: MemoryExpansionGadget<F, 1, N_BYTES_MEMORY_WORD_SIZE>,
     is_mload: IsEqualGadget<F>,
     is_mstore8: IsEqualGadget<F>,
+    /// Lookups of the accessed words in [`MemoryOpMode::Word`], which replace
+    /// the lookups of the accessed bytes.
+    words: Option<MemoryWordsGadget<F>>,
 }
 
 impl<F: Field> ExecutionGadget<F> for MemoryGadget<F> {
@@ -72,30 +79,42 @@ impl<F: Field> ExecutionGadget<F> for MemoryGadget<F> {
             value.to_word(),
         );
 
-        cb.condition(is_mstore8.expr(), |cb| {
-            cb.memory_lookup(1.expr(), address.expr(), value.limbs[0].expr(), None);
-        });
+        let words = match cb.feature_config().memory_op_mode {
+            MemoryOpMode::Byte => {
+                cb.condition(is_mstore8.expr(), |cb| {
+                    cb.memory_lookup(1.expr(), address.expr(), value.limbs[0].expr(), None);
+                });
 
-        cb.condition(is_not_mstore8, |cb| {
-            for idx in 0..32 {
-                cb.memory_lookup(
-                    is_store.clone(),
-                    address.expr() + idx.expr(),
-                    value.limbs[31 - idx].expr(),
-                    None,
-                );
+                cb.condition(is_not_mstore8, |cb| {
+                    for idx in 0..32 {
+                        cb.memory_lookup(
+                            is_store.clone(),
+                            address.expr() + idx.expr(),
+                            value.limbs[31 - idx].expr(),
+                            None,
+                        );
+                    }
+                });
+                None
             }
-        });
+            MemoryOpMode::Word => Some(MemoryWordsGadget::construct(
+                cb,
+                address.expr(),
+                &value,
+                is_store.clone(),
+                is_mstore8.expr(),
+            )),
+        };
 
         // State transition
-        // - `rw_counter` needs to be increased by 34 when is_not_mstore8, otherwise to be increased
-        //   by 31
+        // - `rw_counter` needs to be increased by the number of stack and memory lookups: 34 when
+        //   is_not_mstore8 and 3 otherwise in byte mode, 3 or 4 in word mode
         // - `program_counter` needs to be increased by 1
         // - `stack_pointer` needs to be increased by 2 when is_store, otherwise to be same
         // - `memory_size` needs to be set to `next_memory_size`
         let gas_cost = OpcodeId::MLOAD.constant_gas_cost().expr() + memory_expansion.gas_cost();
         let step_state_transition = StepStateTransition {
-            rw_counter: Delta(34.expr() - is_mstore8.expr() * 31.expr()),
+            rw_counter: Delta(cb.rw_counter_offset()),
             program_counter: Delta(1.expr()),
             stack_pointer: Delta(is_store * 2.expr()),
             gas_left: Delta(-gas_cost),
@@ -111,6 +130,7 @@ impl<F: Field> ExecutionGadget<F> for MemoryGadget<F> {
             memory_expansion,
             is_mload,
             is_mstore8,
+            words,
         }
     }
 
@@ -155,6 +175,193 @@ impl<F: Field> ExecutionGadget<F> for MemoryGadget<F> {
             [address.as_u64() + if is_mstore8 == F::ONE { 1 } else { 32 }],
         )?;
 
+        if let Some(words) = &self.words {
+            let words_rws = (2..step.rw_indices_len())
+                .map(|index| {
+                    let rw = block.get_rws(step, index);
+                    (
+                        rw.value_assignment(),
+                        rw.value_prev_assignment().unwrap_or_default(),
+                    )
+                })
+                .collect::<Vec<_>>();
+            words.assign(region, offset, address.as_u64(), &words_rws)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Relates the value of `MLOAD`, `MSTORE` and `MSTORE8` to the 32-byte aligned
+/// words of the memory in [`MemoryOpMode::Word`].  The address is decomposed
+/// into `32 * word_index + shift`, and the one or two words starting at
+/// `32 * word_index` are looked up: the accessed bytes of the words are the
+/// bytes of the value, and the other ones are left unchanged by the stores.
+#[derive(Clone, Debug)]
+struct MemoryWordsGadget<F> {
+    word_index: MemoryAddress<F>,
+    /// `shift_selectors[s]` is 1 iff `shift == s`
+    shift_selectors: [Cell<F>; N_BYTES_WORD],
+    /// Whether the access spans the word following the first one
+    is_crossing: Cell<F>,
+    words: [Word32Cell<F>; 2],
+    words_prev: [Word32Cell<F>; 2],
+}
+
+impl<F: Field> MemoryWordsGadget<F> {
+    fn construct(
+        cb: &mut EVMConstraintBuilder<F>,
+        address: Expression<F>,
+        value: &Word32Cell<F>,
+        is_store: Expression<F>,
+        is_mstore8: Expression<F>,
+    ) -> Self {
+        // The word index fits into 5 bytes like the address, so that the aligned
+        // address can't wrap around the field.
+        let word_index = cb.query_memory_address();
+        let shift_selectors = [(); N_BYTES_WORD].map(|_| cb.query_bool());
+        let is_crossing = cb.query_bool();
+        let words = [(); 2].map(|_| cb.query_word32());
+        let words_prev = [(); 2].map(|_| cb.query_word32());
+
+        cb.require_equal(
+            "exactly one shift selector is enabled",
+            sum::expr(&shift_selectors),
+            1.expr(),
+        );
+        let shift = sum::expr(
+            shift_selectors
+                .iter()
+                .enumerate()
+                .map(|(shift, selector)| shift.expr() * selector.expr()),
+        );
+        cb.require_equal(
+            "address == 32 * word_index + shift",
+            address,
+            word_index.expr() * N_BYTES_WORD.expr() + shift,
+        );
+        // An unaligned MLOAD/MSTORE spans two words, a MSTORE8 always one.
+        cb.require_equal(
+            "is_crossing == !is_mstore8 && shift != 0",
+            is_crossing.expr(),
+            not::expr(is_mstore8.clone()) * not::expr(shift_selectors[0].expr()),
+        );
+
+        // Bytes of the two words in memory order
+        let to_bytes = |words: &[Word32Cell<F>; 2]| {
+            words
+                .iter()
+                .flat_map(|word| word.limbs.iter().rev().map(|byte| byte.expr()))
+                .collect::<Vec<_>>()
+        };
+        let (bytes, bytes_prev) = (to_bytes(&words), to_bytes(&words_prev));
+        // Byte at `address + idx` of the words
+        let byte_at = |idx: usize| {
+            sum::expr(
+                shift_selectors
+                    .iter()
+                    .enumerate()
+                    .map(|(shift, selector)| selector.expr() * bytes[shift + idx].clone()),
+            )
+        };
+
+        cb.condition(not::expr(is_mstore8.clone()), |cb| {
+            for (idx, byte) in value.limbs.iter().rev().enumerate() {
+                cb.require_equal(
+                    "MLOAD/MSTORE value byte is the word byte at address + idx",
+                    byte.expr(),
+                    byte_at(idx),
+                );
+            }
+        });
+        cb.condition(is_mstore8.clone(), |cb| {
+            cb.require_equal(
+                "MSTORE8 value byte is the word byte at address",
+                value.limbs[0].expr(),
+                byte_at(0),
+            );
+        });
+
+        for (position, (byte, byte_prev)) in bytes.iter().zip(bytes_prev.iter()).enumerate() {
+            // The byte at `position` is accessed by the shifts in
+            // `position - 31..=position` for MLOAD/MSTORE, and by `position` for
+            // MSTORE8.
+            let first_shift = position.saturating_sub(N_BYTES_WORD - 1);
+            let last_shift = position.min(N_BYTES_WORD - 1);
+            let is_accessed = select::expr(
+                is_mstore8.clone(),
+                shift_selectors
+                    .get(position)
+                    .map_or(0.expr(), |selector| selector.expr()),
+                sum::expr(&shift_selectors[first_shift..=last_shift]),
+            );
+            cb.require_zero(
+                "bytes not accessed are unchanged",
+                not::expr(is_accessed) * (byte.clone() - byte_prev.clone()),
+            );
+        }
+
+        // MLOAD reads the words, which are unchanged.
+        let value_prev = |idx: usize| {
+            Word::select(
+                is_store.clone(),
+                words_prev[idx].to_word(),
+                words[idx].to_word(),
+            )
+        };
+        let aligned_address = word_index.expr() * N_BYTES_WORD.expr();
+        cb.memory_word_lookup(
+            is_store.clone(),
+            aligned_address.clone(),
+            words[0].to_word(),
+            value_prev(0),
+        );
+        cb.condition(is_crossing.expr(), |cb| {
+            cb.memory_word_lookup(
+                is_store.clone(),
+                aligned_address + N_BYTES_WORD.expr(),
+                words[1].to_word(),
+                value_prev(1),
+            );
+        });
+
+        Self {
+            word_index,
+            shift_selectors,
+            is_crossing,
+            words,
+            words_prev,
+        }
+    }
+
+    /// Assign the decomposition of `address` and the (value, value_prev) of
+    /// the looked up words.
+    fn assign(
+        &self,
+        region: &mut CachedRegion<'_, '_, F>,
+        offset: usize,
+        address: u64,
+        words: &[(U256, U256)],
+    ) -> Result<(), Error> {
+        let shift = (address % N_BYTES_WORD as u64) as usize;
+        self.word_index
+            .assign_u256(region, offset, U256::from(address / N_BYTES_WORD as u64))?;
+        for (idx, selector) in self.shift_selectors.iter().enumerate() {
+            selector.assign(region, offset, Value::known(F::from((idx == shift) as u64)))?;
+        }
+        self.is_crossing.assign(
+            region,
+            offset,
+            Value::known(F::from((words.len() == 2) as u64)),
+        )?;
+        // The second word is not looked up when the access doesn't cross
+        // the word boundary, it is left zero.
+        for (idx, (word, word_prev)) in self.words.iter().zip(self.words_prev.iter()).enumerate() {
+            let (value, value_prev) = words.get(idx).copied().unwrap_or_default();
+            word.assign_u256(region, offset, value)?;
+            word_prev.assign_u256(region, offset, value_prev)?;
+        }
+
         Ok(())
     }
 }
@@ -162,6 +369,7 @@ impl<F: Field> ExecutionGadget<F> for MemoryGadget<F> {
 #[cfg(test)]
 mod test {
     use crate::{evm_circuit::test::rand_word, test_util::CircuitTestBuilder};
+    use bus_mapping::operation::MemoryOpMode;
     use eth_types::{
         bytecode,
         evm_types::{GasCost, OpcodeId},
@@ -255,6 +463,46 @@ mod test {
         }
     }
 
+    #[test]
+    fn memory_gadget_word_mode() {
+        let value = Word::from_big_endian(&(1..33).collect::<Vec<_>>());
+        let bytecode = bytecode! {
+            // unaligned store spanning the words at 0x00 and 0x20
+            PUSH32(value)
+            PUSH1(0x10)
+            MSTORE
+            // aligned store overwriting the word at 0x20
+            PUSH32(value)
+            PUSH1(0x20)
+            MSTORE
+            PUSH32(value)
+            PUSH1(0x25)
+            MSTORE8
+            PUSH1(0x08)
+            MLOAD
+            PUSH1(0x20)
+            MLOAD
+            // unaligned load past the end of the memory
+            PUSH1(0x30)
+            MLOAD
+            STOP
+        };
+
+        let ctx = TestContext::<2, 1>::new(
+            None,
+            account_0_code_account_1_no_code(bytecode),
+            tx_from_1_to_0,
+            |block, _tx| block.number(0xcafeu64),
+        )
+        .unwrap();
+
+        CircuitTestBuilder::new_from_test_ctx(ctx)
+            .input_builder_modifier(Box::new(|builder| {
+                builder.block.memory_op_mode = MemoryOpMode::Word;
+            }))
+            .run();
+    }
+
     #[test]
     fn oog_static_memory_case() {
         test_ok(
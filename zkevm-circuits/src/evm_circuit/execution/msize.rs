@@ -83,25 +83,46 @@ impl<F: Field> ExecutionGadget<F> for MsizeGadget<F> {
 
 #[cfg(test)]
 mod test {
-    use crate::test_util::CircuitTestBuilder;
-    use eth_types::{bytecode, Word};
+    use crate::{evm_circuit::param::N_BYTES_WORD, test_util::CircuitTestBuilder};
+    use eth_types::{bytecode, Bytecode, Word};
     use mock::TestContext;
 
-    #[test]
-    fn msize_gadget() {
+    fn msize_bytecode() -> Bytecode {
         let address = Word::from(0x10);
         let value = Word::from_big_endian(&(1..33).collect::<Vec<_>>());
-        let bytecode = bytecode! {
+        bytecode! {
             PUSH32(value)
             PUSH32(address)
             MSTORE
             MSIZE
             STOP
-        };
+        }
+    }
 
+    #[test]
+    fn msize_gadget() {
+        CircuitTestBuilder::new_from_test_ctx(
+            TestContext::<2, 1>::simple_ctx_with_bytecode(msize_bytecode()).unwrap(),
+        )
+        .run();
+    }
+
+    #[test]
+    fn msize_gadget_understated_memory_size() {
         CircuitTestBuilder::new_from_test_ctx(
-            TestContext::<2, 1>::simple_ctx_with_bytecode(bytecode).unwrap(),
+            TestContext::<2, 1>::simple_ctx_with_bytecode(msize_bytecode()).unwrap(),
         )
+        .block_modifier(Box::new(|block| {
+            // The steps are BeginTx, PUSH32, PUSH32, MSTORE, MSIZE, STOP and
+            // EndTx.  Understate the memory expanded by MSTORE in the MSIZE step.
+            assert_eq!(block.txs[0].steps().len(), 7);
+            block.txs[0].steps_mut()[4].memory_size -= N_BYTES_WORD;
+        }))
+        .evm_checks(Box::new(|prover, gate_rows, lookup_rows| {
+            assert!(prover
+                .verify_at_rows_par(gate_rows.iter().cloned(), lookup_rows.iter().cloned())
+                .is_err())
+        }))
         .run();
     }
 }
@@ -8,7 +8,7 @@ use crate::{
                 ConstrainBuilderCommon, EVMConstraintBuilder, StepStateTransition,
                 Transition::Delta,
             },
-            math_gadget::{IsZeroWordGadget, LtWordGadget, MulAddWordsGadget},
+            math_gadget::DivModWordsGadget,
             CachedRegion,
         },
         witness::{Block, Call, ExecStep, Transaction},
@@ -32,12 +32,9 @@ pub(crate) struct MulDivModGadget<F> {
     same_context: SameContextGadget<F>,
     /// Words a, b, c, d
     pub words: [Word32Cell<F>; 4],
-    /// Gadget that verifies a * b + c = d
-    mul_add_words: MulAddWordsGadget<F>,
-    /// Check if divisor is zero for DIV and MOD
-    divisor_is_zero: IsZeroWordGadget<F, Word32Cell<F>>,
-    /// Check if residue < divisor when divisor != 0 for DIV and MOD
-    lt_word: LtWordGadget<F>,
+    /// Gadget that verifies a * b + c = d, and for DIV and MOD that c < b
+    /// when b != 0
+    div_mod_words: DivModWordsGadget<F>,
 }
 
 impl<F: Field> ExecutionGadget<F> for MulDivModGadget<F> {
@@ -62,9 +59,10 @@ impl<F: Field> ExecutionGadget<F> for MulDivModGadget<F> {
         let c = cb.query_word32();
         let d = cb.query_word32();
 
-        let mul_add_words = MulAddWordsGadget::construct(cb, [&a, &b, &c, &d]);
-        let divisor_is_zero = IsZeroWordGadget::construct(cb, &b);
-        let lt_word = LtWordGadget::construct(cb, &c.to_word(), &b.to_word());
+        // The division constraints are disabled for MUL
+        let div_mod_words =
+            DivModWordsGadget::construct(cb, [&a, &b, &c, &d], 1.expr() - is_mul.clone());
+        let divisor_is_zero = div_mod_words.divisor_is_zero();
 
         // Pop a and b from the stack, push result on the stack
         // The first pop is multiplier for MUL and dividend for DIV/MOD
@@ -78,11 +76,11 @@ impl<F: Field> ExecutionGadget<F> for MulDivModGadget<F> {
                 .mul_selector(is_mul.clone())
                 .add_unchecked(
                     a.to_word()
-                        .mul_selector(is_div * (1.expr() - divisor_is_zero.expr())),
+                        .mul_selector(is_div * (1.expr() - divisor_is_zero.clone())),
                 )
                 .add_unchecked(
                     c.to_word()
-                        .mul_selector(is_mod * (1.expr() - divisor_is_zero.expr())),
+                        .mul_selector(is_mod * (1.expr() - divisor_is_zero)),
                 ),
         );
 
@@ -92,15 +90,6 @@ impl<F: Field> ExecutionGadget<F> for MulDivModGadget<F> {
             c.to_word().mul_selector(is_mul.clone()),
         );
 
-        // Constraints for DIV and MOD cases
-        cb.condition(1.expr() - is_mul, |cb| {
-            cb.add_constraint(
-                "residue < divisor when divisor != 0 for opcode DIV/MOD",
-                (1.expr() - lt_word.expr()) * (1.expr() - divisor_is_zero.expr()),
-            );
-            cb.require_zero("overflow == 0 for opcode DIV/MOD", mul_add_words.overflow());
-        });
-
         // State transition
         let step_state_transition = StepStateTransition {
             rw_counter: Delta(3.expr()),
@@ -114,9 +103,7 @@ impl<F: Field> ExecutionGadget<F> for MulDivModGadget<F> {
         Self {
             words: [a, b, c, d],
             same_context,
-            mul_add_words,
-            divisor_is_zero,
-            lt_word,
+            div_mod_words,
         }
     }
 
@@ -150,9 +137,7 @@ impl<F: Field> ExecutionGadget<F> for MulDivModGadget<F> {
         self.words[1].assign_u256(region, offset, b)?;
         self.words[2].assign_u256(region, offset, c)?;
         self.words[3].assign_u256(region, offset, d)?;
-        self.mul_add_words.assign(region, offset, [a, b, c, d])?;
-        self.lt_word.assign(region, offset, c, b)?;
-        self.divisor_is_zero.assign(region, offset, Word::from(b))?;
+        self.div_mod_words.assign(region, offset, [a, b, c, d])?;
         Ok(())
     }
 }
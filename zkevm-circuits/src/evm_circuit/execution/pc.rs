@@ -79,9 +79,20 @@ impl<F: Field> ExecutionGadget<F> for PcGadget<F> {
 
 #[cfg(test)]
 mod test {
-    use crate::test_util::CircuitTestBuilder;
-    use eth_types::bytecode;
+    use crate::{
+        evm_circuit::test::run_steps,
+        test_util::CircuitTestBuilder,
+        witness::{ExecStep, Rw, RwMap},
+    };
+    use bus_mapping::{
+        circuit_input_builder::ExecState,
+        evm::OpcodeId,
+        exec_trace::OperationRef,
+        operation::{RWCounter, Target},
+    };
+    use eth_types::{bytecode, Word};
     use mock::TestContext;
+    use std::collections::HashMap;
 
     fn test_ok() {
         let bytecode = bytecode! {
@@ -100,4 +111,51 @@ mod test {
     fn pc_gadget_simple() {
         test_ok();
     }
+
+    /// Run a PC step at program counter 1 pushing `value`, without a trace.
+    fn run_pc_step(value: Word) -> bool {
+        let bytecode = bytecode! {
+            JUMPDEST
+            PC
+            PC
+        };
+        let gas_cost = OpcodeId::PC.constant_gas_cost();
+        let steps = vec![
+            ExecStep {
+                exec_state: ExecState::Op(OpcodeId::PC),
+                pc: 1,
+                gas_left: 100,
+                gas_cost,
+                rwc: RWCounter(1),
+                bus_mapping_instance: vec![OperationRef(Target::Stack, 0)],
+                ..Default::default()
+            },
+            ExecStep {
+                exec_state: ExecState::Op(OpcodeId::PC),
+                pc: 2,
+                stack_size: 1,
+                gas_left: 100 - gas_cost,
+                gas_cost,
+                rwc: RWCounter(2),
+                ..Default::default()
+            },
+        ];
+        let rws = RwMap(HashMap::from([(
+            Target::Stack,
+            vec![Rw::Stack {
+                rw_counter: 1,
+                is_write: true,
+                call_id: 1,
+                stack_pointer: 1023,
+                value,
+            }],
+        )]));
+        run_steps(steps, rws, bytecode).is_ok()
+    }
+
+    #[test]
+    fn pc_gadget_hand_crafted_steps() {
+        assert!(run_pc_step(Word::one()));
+        assert!(!run_pc_step(Word::zero()));
+    }
 }
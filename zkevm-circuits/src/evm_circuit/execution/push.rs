@@ -217,8 +217,14 @@ impl<F: Field> ExecutionGadget<F> for PushGadget<F> {
 
 #[cfg(test)]
 mod test {
-    use crate::{evm_circuit::test::rand_bytes, test_util::CircuitTestBuilder};
-    use eth_types::{bytecode, evm_types::OpcodeId};
+    use crate::{
+        evm_circuit::test::rand_bytes,
+        test_util::CircuitTestBuilder,
+        witness::{Block, Rw},
+    };
+    use bus_mapping::{circuit_input_builder::ExecState, exec_trace::OperationRef};
+    use eth_types::{bytecode, evm_types::OpcodeId, Word};
+    use halo2_proofs::halo2curves::bn256::Fr;
     use mock::TestContext;
 
     fn test_ok(opcode: OpcodeId, bytes: &[u8]) {
@@ -262,6 +268,40 @@ mod test {
         test_ok(OpcodeId::PUSH16, &[1, 2, 3, 4, 5, 6, 7, 8, 9, 10]);
     }
 
+    /// Push a value different from the immediate bytes in the PUSH2 step,
+    /// which the bytecode lookups of the bytes must reject.
+    fn push_wrong_value(block: &mut Block<Fr>) {
+        let step = block.txs[0]
+            .steps()
+            .iter()
+            .find(|step| step.exec_state == ExecState::Op(OpcodeId::PUSH2))
+            .unwrap()
+            .clone();
+        let OperationRef(target, index) = step.rw_index(0);
+        match &mut block.rws.0.get_mut(&target).unwrap()[index] {
+            Rw::Stack { value, .. } => *value += Word::one(),
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn push_gadget_wrong_value() {
+        CircuitTestBuilder::new_from_test_ctx(
+            TestContext::<2, 1>::simple_ctx_with_bytecode(bytecode! {
+                PUSH2(0x0102)
+                STOP
+            })
+            .unwrap(),
+        )
+        .block_modifier(Box::new(push_wrong_value))
+        .evm_checks(Box::new(|prover, gate_rows, lookup_rows| {
+            assert!(prover
+                .verify_at_rows_par(gate_rows.iter().cloned(), lookup_rows.iter().cloned())
+                .is_err())
+        }))
+        .run();
+    }
+
     #[test]
     #[ignore]
     fn push_gadget_rand() {
@@ -249,13 +249,16 @@ impl<F: Field> ExecutionGadget<F> for ReturnRevertGadget<F> {
         );
 
         // Without this, copy_rw_increase would be unconstrained for non-create root
-        // calls.
-        cb.condition(not::expr(is_create) * is_root, |cb| {
-            cb.require_zero(
-                "rw counter is 0 if there is no copy event",
-                copy_rw_increase.expr(),
-            );
-        });
+        // calls and for failed creations, which copy nothing.
+        cb.condition(
+            not::expr(is_create.clone()) * is_root + is_create * not::expr(is_success.expr()),
+            |cb| {
+                cb.require_zero(
+                    "rw counter is 0 if there is no copy event",
+                    copy_rw_increase.expr(),
+                );
+            },
+        );
 
         Self {
             opcode,
@@ -337,7 +340,7 @@ impl<F: Field> ExecutionGadget<F> for ReturnRevertGadget<F> {
 
         let copy_rw_increase = if call.is_create() && call.is_success {
             length.as_u64()
-        } else if !call.is_root {
+        } else if !call.is_root && !call.is_create() {
             2 * std::cmp::min(call.return_data_length, length.as_u64())
         } else {
             0
@@ -472,6 +475,10 @@ mod test {
             ((0, 10), (0, 20)),
             ((0, 20), (0, 10)),
             ((64, 1), (0, 10)), // Expands memory in RETURN/REVERT opcode
+            // Partial copies into the middle of the caller's memory
+            ((10, 20), (100, 5)),
+            ((10, 5), (100, 20)),
+            ((33, 40), (7, 40)),
             ((0, 10), (1000, 0)),
             ((1000, 0), (0, 10)),
             ((1000, 0), (1000, 0)),
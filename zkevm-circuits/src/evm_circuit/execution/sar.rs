@@ -9,8 +9,8 @@ use crate::{
                 ConstrainBuilderCommon, EVMConstraintBuilder, StepStateTransition,
                 Transition::Delta,
             },
-            math_gadget::{IsEqualGadget, IsZeroGadget, LtGadget},
-            select, sum, CachedRegion, Cell,
+            math_gadget::{IsEqualGadget, IsZeroGadget, LtGadget, Pow2Gadget},
+            select, CachedRegion, Cell,
         },
         witness::{Block, Call, ExecStep, Transaction},
     },
@@ -33,7 +33,8 @@ use halo2_proofs::{
 #[derive(Clone, Debug)]
 pub(crate) struct SarGadget<F> {
     same_context: SameContextGadget<F>,
-    shift: Word32Cell<F>,
+    // Shift word, where only `shift[0]` matters when `shift < 256`.
+    shift: Pow2Gadget<F>,
     a: Word32Cell<F>,
     b: Word32Cell<F>,
     // Each of the four `a64s` limbs is split into two parts (`a64s_lo` and `a64s_hi`) at position
@@ -57,8 +58,6 @@ pub(crate) struct SarGadget<F> {
     shf_div64_lt_4: LtGadget<F, 1>,
     // Verify `shf_mod64 < 64`.
     shf_mod64_lt_64: LtGadget<F, 1>,
-    // Identify if `shift` is less than 256 or not.
-    shf_lt256: IsZeroGadget<F>,
     // shf_div64 == 0
     shf_lo_div64_eq0: IsZeroGadget<F>,
     // shf_div64 == 1
@@ -81,11 +80,11 @@ impl<F: Field> ExecutionGadget<F> for SarGadget<F> {
     fn configure(cb: &mut EVMConstraintBuilder<F>) -> Self {
         let opcode = cb.query_cell();
 
-        let shift = cb.query_word32();
+        let shift = Pow2Gadget::construct(cb);
         let a = cb.query_word32();
         let b = cb.query_word32();
 
-        cb.stack_pop(shift.to_word());
+        cb.stack_pop(shift.amount().to_word());
         cb.stack_pop(a.to_word());
         cb.stack_push(b.to_word());
 
@@ -100,7 +99,6 @@ impl<F: Field> ExecutionGadget<F> for SarGadget<F> {
         let p_hi = cb.query_cell();
         let p_top = cb.query_cell();
         let is_neg = LtGadget::construct(cb, 127.expr(), a.limbs[31].expr());
-        let shf_lt256 = IsZeroGadget::construct(cb, sum::expr(&shift.limbs[1..32]));
 
         for idx in 0..4 {
             cb.require_equal(
@@ -129,10 +127,10 @@ impl<F: Field> ExecutionGadget<F> for SarGadget<F> {
         let shf_lo_div64_eq1 = IsEqualGadget::construct(cb, shf_div64.expr(), 1.expr());
         let shf_lo_div64_eq2 = IsEqualGadget::construct(cb, shf_div64.expr(), 2.expr());
         let shf_lo_div64_eq3 = IsEqualGadget::construct(cb, shf_div64.expr(), 3.expr());
-        let shf_div64_eq0 = shf_lt256.expr() * shf_lo_div64_eq0.expr();
-        let shf_div64_eq1 = shf_lt256.expr() * shf_lo_div64_eq1.expr();
-        let shf_div64_eq2 = shf_lt256.expr() * shf_lo_div64_eq2.expr();
-        let shf_div64_eq3 = shf_lt256.expr() * shf_lo_div64_eq3.expr();
+        let shf_div64_eq0 = shift.lt_256() * shf_lo_div64_eq0.expr();
+        let shf_div64_eq1 = shift.lt_256() * shf_lo_div64_eq1.expr();
+        let shf_div64_eq2 = shift.lt_256() * shf_lo_div64_eq2.expr();
+        let shf_div64_eq3 = shift.lt_256() * shf_lo_div64_eq3.expr();
 
         cb.require_equal(
             "Constrain merged b64s[0] value",
@@ -185,7 +183,7 @@ impl<F: Field> ExecutionGadget<F> for SarGadget<F> {
         cb.require_equal("shf_mod64 < 64", shf_mod64_lt_64.expr(), 1.expr());
         cb.require_equal(
             "shift[0] == shf_mod64 + shf_div64 * 64",
-            shift.limbs[0].expr(),
+            shift.lsb(),
             shf_mod64.expr() + shf_div64.expr() * 64.expr(),
         );
 
@@ -253,7 +251,6 @@ impl<F: Field> ExecutionGadget<F> for SarGadget<F> {
             is_neg,
             shf_div64_lt_4,
             shf_mod64_lt_64,
-            shf_lt256,
             shf_lo_div64_eq0,
             shf_lo_div64_eq1,
             shf_lo_div64_eq2,
@@ -275,7 +272,7 @@ impl<F: Field> ExecutionGadget<F> for SarGadget<F> {
         self.same_context.assign_exec_step(region, offset, step)?;
         let [shift, a, b] = [0, 1, 2].map(|idx| block.get_rws(step, idx).stack_value());
 
-        self.shift.assign_u256(region, offset, shift)?;
+        self.shift.assign(region, offset, shift)?;
         self.a.assign_u256(region, offset, a)?;
         self.b.assign_u256(region, offset, b)?;
 
@@ -344,8 +341,6 @@ impl<F: Field> ExecutionGadget<F> for SarGadget<F> {
             .assign(region, offset, F::from_u128(shf_div64), 4.into())?;
         self.shf_mod64_lt_64
             .assign(region, offset, F::from_u128(shf_mod64), 64.into())?;
-        self.shf_lt256
-            .assign(region, offset, F::from_u128(shf_lt256))?;
         self.shf_lo_div64_eq0
             .assign(region, offset, F::from_u128(shf_div64))?;
         self.shf_lo_div64_eq1
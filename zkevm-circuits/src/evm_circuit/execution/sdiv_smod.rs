@@ -8,9 +8,7 @@ use crate::{
                 ConstrainBuilderCommon, EVMConstraintBuilder, StepStateTransition,
                 Transition::Delta,
             },
-            math_gadget::{
-                AbsWordGadget, IsZeroWordGadget, LtGadget, LtWordGadget, MulAddWordsGadget,
-            },
+            math_gadget::{AbsWordGadget, DivModWordsGadget, IsZeroWordGadget, LtGadget},
             CachedRegion,
         },
         witness::{Block, Call, ExecStep, Transaction},
@@ -31,11 +29,9 @@ pub(crate) struct SignedDivModGadget<F> {
     divisor_abs: AbsWordGadget<F>,
     remainder_abs: AbsWordGadget<F>,
     dividend_abs: AbsWordGadget<F>,
-    mul_add_words: MulAddWordsGadget<F>,
-    remainder_abs_lt_divisor_abs: LtWordGadget<F>,
+    div_mod_words: DivModWordsGadget<F>,
     dividend_is_signed_overflow: LtGadget<F, 1>,
     quotient_is_zero: IsZeroWordGadget<F, Word32Cell<F>>,
-    divisor_is_zero: IsZeroWordGadget<F, Word32Cell<F>>,
     remainder_is_zero: IsZeroWordGadget<F, Word32Cell<F>>,
 }
 
@@ -53,9 +49,22 @@ impl<F: Field> ExecutionGadget<F> for SignedDivModGadget<F> {
         let remainder_abs = AbsWordGadget::construct(cb);
         let dividend_abs = AbsWordGadget::construct(cb);
         let quotient_is_zero = IsZeroWordGadget::construct(cb, quotient_abs.x());
-        let divisor_is_zero = IsZeroWordGadget::construct(cb, divisor_abs.x());
         let remainder_is_zero = IsZeroWordGadget::construct(cb, remainder_abs.x());
 
+        // Constrain `|quotient| * |divisor| + |remainder| = |dividend|` and
+        // `|remainder| < |divisor|` when divisor != 0.
+        let div_mod_words = DivModWordsGadget::construct(
+            cb,
+            [
+                quotient_abs.x_abs(),
+                divisor_abs.x_abs(),
+                remainder_abs.x_abs(),
+                dividend_abs.x_abs(),
+            ],
+            1.expr(),
+        );
+        let divisor_is_zero = div_mod_words.divisor_is_zero();
+
         cb.stack_pop(dividend_abs.x().to_word());
         cb.stack_pop(divisor_abs.x().to_word());
         cb.stack_push(Word::select(
@@ -63,38 +72,16 @@ impl<F: Field> ExecutionGadget<F> for SignedDivModGadget<F> {
             quotient_abs
                 .x()
                 .to_word()
-                .mul_selector(1.expr() - divisor_is_zero.expr()),
+                .mul_selector(1.expr() - divisor_is_zero.clone()),
             remainder_abs
                 .x()
                 .to_word()
-                .mul_selector(1.expr() - divisor_is_zero.expr()),
+                .mul_selector(1.expr() - divisor_is_zero.clone()),
         ));
 
-        // Constrain `|quotient| * |divisor| + |remainder| = |dividend|`.
-        let mul_add_words = MulAddWordsGadget::construct(
-            cb,
-            [
-                quotient_abs.x_abs(),
-                divisor_abs.x_abs(),
-                remainder_abs.x_abs(),
-                dividend_abs.x_abs(),
-            ],
-        );
-        cb.add_constraint("overflow == 0", mul_add_words.overflow());
-
-        let remainder_abs_lt_divisor_abs = LtWordGadget::construct(
-            cb,
-            &remainder_abs.x_abs().to_word(),
-            &divisor_abs.x_abs().to_word(),
-        );
-        cb.add_constraint(
-            "abs(remainder) < abs(divisor) when divisor != 0",
-            (1.expr() - remainder_abs_lt_divisor_abs.expr()) * (1.expr() - divisor_is_zero.expr()),
-        );
-
         cb.condition(
             (1.expr() - quotient_is_zero.expr())
-            * (1.expr() - divisor_is_zero.expr())
+            * (1.expr() - divisor_is_zero.clone())
             * (1.expr() - remainder_is_zero.expr()),
             |cb| cb.add_constraint(
                 "sign(dividend) == sign(remainder) when quotient, divisor and remainder are all non-zero",
@@ -115,7 +102,7 @@ impl<F: Field> ExecutionGadget<F> for SignedDivModGadget<F> {
         // quotient and divisor are non-zero and dividend is not signed overflow.
         cb.condition(
             (1.expr() - quotient_is_zero.expr())
-                * (1.expr() - divisor_is_zero.expr())
+                * (1.expr() - divisor_is_zero)
                 * (1.expr() - dividend_is_signed_overflow.expr()),
             |cb| {
                 cb.add_constraint(
@@ -142,11 +129,9 @@ impl<F: Field> ExecutionGadget<F> for SignedDivModGadget<F> {
             divisor_abs,
             remainder_abs,
             dividend_abs,
-            mul_add_words,
-            remainder_abs_lt_divisor_abs,
+            div_mod_words,
             dividend_is_signed_overflow,
             quotient_is_zero,
-            divisor_is_zero,
             remainder_is_zero,
         }
     }
@@ -204,13 +189,11 @@ impl<F: Field> ExecutionGadget<F> for SignedDivModGadget<F> {
             .assign(region, offset, remainder, remainder_abs)?;
         self.dividend_abs
             .assign(region, offset, dividend, dividend_abs)?;
-        self.mul_add_words.assign(
+        self.div_mod_words.assign(
             region,
             offset,
             [quotient_abs, divisor_abs, remainder_abs, dividend_abs],
         )?;
-        self.remainder_abs_lt_divisor_abs
-            .assign(region, offset, remainder_abs, divisor_abs)?;
         self.dividend_is_signed_overflow.assign(
             region,
             offset,
@@ -219,8 +202,6 @@ impl<F: Field> ExecutionGadget<F> for SignedDivModGadget<F> {
         )?;
         self.quotient_is_zero
             .assign(region, offset, Word::from(quotient))?;
-        self.divisor_is_zero
-            .assign(region, offset, Word::from(divisor))?;
         self.remainder_is_zero
             .assign(region, offset, Word::from(remainder))?;
         Ok(())
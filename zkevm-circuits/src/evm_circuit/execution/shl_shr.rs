@@ -2,15 +2,14 @@ use crate::{
     evm_circuit::{
         execution::ExecutionGadget,
         step::ExecutionState,
-        table::{FixedTableTag, Lookup},
         util::{
             common_gadget::SameContextGadget,
             constraint_builder::{
                 ConstrainBuilderCommon, EVMConstraintBuilder, StepStateTransition,
                 Transition::Delta,
             },
-            math_gadget::{IsZeroGadget, IsZeroWordGadget, LtWordGadget, MulAddWordsGadget},
-            sum, CachedRegion, Cell,
+            math_gadget::{IsZeroWordGadget, LtWordGadget, MulAddWordsGadget, Pow2Gadget},
+            CachedRegion,
         },
         witness::{Block, Call, ExecStep, Transaction},
     },
@@ -20,8 +19,8 @@ use crate::{
     },
 };
 use bus_mapping::evm::OpcodeId;
-use eth_types::{Field, ToLittleEndian, U256};
-use halo2_proofs::{circuit::Value, plonk::Error};
+use eth_types::{Field, U256};
+use halo2_proofs::plonk::Error;
 
 /// ShlShrGadget verifies opcode SHL and SHR.
 /// For SHL, verify pop1 * (2^pop2) % 2^256 == push;
@@ -34,19 +33,13 @@ pub(crate) struct ShlShrGadget<F> {
     divisor: Word32Cell<F>,
     remainder: Word32Cell<F>,
     dividend: Word32Cell<F>,
-    /// Shift word
-    shift: Word32Cell<F>,
-    /// First byte of shift word
-    shf0: Cell<F>,
+    /// Shift word, with `divisor == 2^shift` (`0` when `shift >= 256`)
+    shift: Pow2Gadget<F>,
     /// Gadget that verifies quotient * divisor + remainder = dividend
     mul_add_words: MulAddWordsGadget<F>,
-    /// Identify if `shift` is less than 256 or not
-    shf_lt256: IsZeroGadget<F>,
-    /// Check if divisor is zero
-    divisor_is_zero: IsZeroWordGadget<F, Word32Cell<F>>,
     /// Check if remainder is zero
     remainder_is_zero: IsZeroWordGadget<F, Word32Cell<F>>,
-    /// Check if remainder < divisor when divisor != 0
+    /// Check if remainder < divisor when shift < 256
     remainder_lt_divisor: LtWordGadget<F>,
 }
 
@@ -64,13 +57,10 @@ impl<F: Field> ExecutionGadget<F> for ShlShrGadget<F> {
         let divisor = cb.query_word32();
         let remainder = cb.query_word32();
         let dividend = cb.query_word32();
-        let shift = cb.query_word32();
-        let shf0 = cb.query_cell();
+        let shift = Pow2Gadget::construct(cb);
 
         let mul_add_words =
             MulAddWordsGadget::construct(cb, [&quotient, &divisor, &remainder, &dividend]);
-        let shf_lt256 = IsZeroGadget::construct(cb, sum::expr(&shift.limbs[1..32]));
-        let divisor_is_zero = IsZeroWordGadget::construct(cb, &divisor);
         let remainder_is_zero = IsZeroWordGadget::construct(cb, &remainder);
         let remainder_lt_divisor =
             LtWordGadget::construct(cb, &remainder.to_word(), &divisor.to_word());
@@ -78,7 +68,8 @@ impl<F: Field> ExecutionGadget<F> for ShlShrGadget<F> {
         // Constrain stack pops and pushes as:
         // - for SHL, two pops are shift and quotient, and push is dividend.
         // - for SHR, two pops are shift and dividend, and push is quotient.
-        cb.stack_pop(shift.to_word());
+        // Every bit is shifted out when shift >= 256, so push is 0.
+        cb.stack_pop(shift.amount().to_word());
         cb.stack_pop(
             quotient
                 .to_word()
@@ -90,30 +81,16 @@ impl<F: Field> ExecutionGadget<F> for ShlShrGadget<F> {
                 .to_word()
                 .mul_selector(is_shl.expr())
                 .add_unchecked(quotient.to_word().mul_selector(is_shr.expr())))
-            .mul_selector(1.expr() - divisor_is_zero.expr()),
+            .mul_selector(shift.lt_256()),
         );
 
-        cb.require_zero(
-            "shf0 == shift.cells[0]",
-            shf0.expr() - shift.limbs[0].expr(),
-        );
-
-        cb.require_zero_word(
-            "shift == shift.cells[0] when divisor != 0",
-            shift
-                .to_word()
-                .sub_unchecked(Word::from_lo_unchecked(shift.limbs[0].expr()))
-                .mul_selector(1.expr() - divisor_is_zero.expr()),
-        );
+        // Constrain divisor == 2^shift when shift < 256, and divisor == 0
+        // otherwise.
+        shift.constrain_pow(cb, &divisor);
 
         cb.require_zero(
-            "shift < 256 when divisor != 0 or shift >= 256 when divisor == 0",
-            1.expr() - divisor_is_zero.expr() - shf_lt256.expr(),
-        );
-
-        cb.require_zero(
-            "remainder < divisor when divisor != 0",
-            (1.expr() - divisor_is_zero.expr()) * (1.expr() - remainder_lt_divisor.expr()),
+            "remainder < divisor when shift < 256",
+            shift.lt_256() * (1.expr() - remainder_lt_divisor.expr()),
         );
 
         cb.require_zero(
@@ -126,19 +103,6 @@ impl<F: Field> ExecutionGadget<F> for ShlShrGadget<F> {
             is_shr * mul_add_words.overflow(),
         );
 
-        // Constrain divisor_lo == 2^shf0 when shf0 < 128, and
-        // divisor_hi == 2^(128 - shf0) otherwise.
-        let (divisor_lo, divisor_hi) = divisor.to_word().to_lo_hi();
-        cb.condition(1.expr() - divisor_is_zero.expr(), |cb| {
-            cb.add_lookup(
-                "Pow2 lookup of shf0, divisor_lo and divisor_hi",
-                Lookup::Fixed {
-                    tag: FixedTableTag::Pow2.expr(),
-                    values: [shf0.expr(), divisor_lo.expr(), divisor_hi.expr()],
-                },
-            );
-        });
-
         let step_state_transition = StepStateTransition {
             rw_counter: Delta(3.expr()),
             program_counter: Delta(1.expr()),
@@ -156,10 +120,7 @@ impl<F: Field> ExecutionGadget<F> for ShlShrGadget<F> {
             remainder,
             dividend,
             shift,
-            shf0,
             mul_add_words,
-            shf_lt256,
-            divisor_is_zero,
             remainder_is_zero,
             remainder_lt_divisor,
         }
@@ -176,20 +137,7 @@ impl<F: Field> ExecutionGadget<F> for ShlShrGadget<F> {
     ) -> Result<(), Error> {
         self.same_context.assign_exec_step(region, offset, step)?;
         let [pop1, pop2, push] = [0, 1, 2].map(|idx| block.get_rws(step, idx).stack_value());
-        let shf0 = u64::from(pop1.to_le_bytes()[0]);
-        let shf_lt256 = pop1
-            .to_le_bytes()
-            .iter()
-            .fold(Some(0_u64), |acc, val| {
-                acc.and_then(|acc| acc.checked_add(u64::from(*val)))
-            })
-            .unwrap()
-            - shf0;
-        let divisor = if shf_lt256 == 0 {
-            U256::from(1) << shf0
-        } else {
-            U256::from(0)
-        };
+        let divisor = self.shift.assign(region, offset, pop1)?;
 
         let (quotient, remainder, dividend) = match step.opcode().unwrap() {
             OpcodeId::SHL => (pop2, U256::from(0), push),
@@ -200,14 +148,8 @@ impl<F: Field> ExecutionGadget<F> for ShlShrGadget<F> {
         self.divisor.assign_u256(region, offset, divisor)?;
         self.remainder.assign_u256(region, offset, remainder)?;
         self.dividend.assign_u256(region, offset, dividend)?;
-        self.shift.assign_u256(region, offset, pop1)?;
-        self.shf0
-            .assign(region, offset, Value::known(F::from(shf0)))?;
         self.mul_add_words
             .assign(region, offset, [quotient, divisor, remainder, dividend])?;
-        self.shf_lt256.assign(region, offset, F::from(shf_lt256))?;
-        self.divisor_is_zero
-            .assign(region, offset, Word::from(divisor))?;
         self.remainder_is_zero
             .assign(region, offset, Word::from(remainder))?;
         self.remainder_lt_divisor
@@ -135,8 +135,9 @@ impl<F: Field> ExecutionGadget<F> for SloadGadget<F> {
 mod test {
 
     use crate::{evm_circuit::test::rand_word, test_util::CircuitTestBuilder};
+    use bus_mapping::chain_config::{ChainConfig, Hardfork};
     use eth_types::{bytecode, Word};
-    use mock::{test_ctx::helpers::tx_from_1_to_0, TestContext, MOCK_ACCOUNTS};
+    use mock::{test_ctx::helpers::tx_from_1_to_0, TestContext, MOCK_ACCOUNTS, MOCK_CHAIN_ID};
 
     fn test_ok(key: Word, value: Word) {
         // Here we use two bytecodes to test both is_persistent(STOP) or not(REVERT)
@@ -193,4 +194,41 @@ mod test {
         let value = rand_word();
         test_ok(key, value);
     }
+
+    #[test]
+    fn sload_gadget_istanbul() {
+        // Before Berlin an SLOAD costs 800 whether the slot is warm or not.
+        let key = Word::from(0x030201);
+        let ctx = TestContext::<2, 1>::new_with_hardfork(
+            None,
+            |accs| {
+                accs[0]
+                    .address(MOCK_ACCOUNTS[0])
+                    .balance(Word::from(10u64.pow(19)))
+                    .code(bytecode! {
+                        PUSH32(key)
+                        SLOAD
+                        PUSH32(key)
+                        SLOAD
+                        STOP
+                    })
+                    .storage(vec![(key, Word::from(0x060504))].into_iter());
+                accs[1]
+                    .address(MOCK_ACCOUNTS[1])
+                    .balance(Word::from(10u64.pow(19)));
+            },
+            tx_from_1_to_0,
+            |block, _txs| block.base_fee_per_gas(Some(Word::zero())),
+            "Istanbul",
+        )
+        .unwrap();
+
+        CircuitTestBuilder::new_from_test_ctx(ctx)
+            .input_builder_modifier(Box::new(|builder| {
+                builder
+                    .block
+                    .set_chain_config(ChainConfig::new(MOCK_CHAIN_ID.as_u64(), Hardfork::Istanbul));
+            }))
+            .run();
+    }
 }
@@ -21,6 +21,7 @@ use crate::{
     },
 };
 
+use bus_mapping::chain_config::{ChainConfig, Eip};
 use eth_types::{evm_types::GasCost, Field};
 use halo2_proofs::{
     circuit::Value,
@@ -211,6 +212,7 @@ impl<F: Field> ExecutionGadget<F> for SstoreGadget<F> {
         self.tx_refund.assign(
             region,
             offset,
+            &block.chain_config,
             tx_refund,
             tx_refund_prev,
             value,
@@ -278,11 +280,22 @@ impl<F: Field> SstoreTxRefundGadget<F> {
         let recreate_slot =
             not::expr(prev_eq_value) * not::expr(original_eq_prev) * (value_prev_is_zero);
 
+        // See `calc_expected_tx_refund`
+        let (warm_read_gas, reset_gas) = if cb.feature_config().is_enabled(Eip::Eip2929) {
+            (GasCost::WARM_ACCESS, GasCost::SSTORE_RESET)
+        } else {
+            (GasCost::ISTANBUL_SLOAD, GasCost::ISTANBUL_SSTORE_RESET)
+        };
+        let clears_schedule = if cb.feature_config().is_enabled(Eip::Eip3529) {
+            GasCost::SSTORE_CLEARS_SCHEDULE
+        } else {
+            GasCost::SSTORE_CLEARS_SCHEDULE_PRE_EIP3529
+        };
         let tx_refund_new = tx_refund_old.expr()
-            + delete_slot * GasCost::SSTORE_CLEARS_SCHEDULE.expr()
-            + reset_existing * (GasCost::SSTORE_RESET.expr() - GasCost::WARM_ACCESS.expr())
-            + reset_inexistent * (GasCost::SSTORE_SET.expr() - GasCost::WARM_ACCESS.expr())
-            - recreate_slot * (GasCost::SSTORE_CLEARS_SCHEDULE.expr());
+            + delete_slot * clears_schedule.expr()
+            + reset_existing * (reset_gas - warm_read_gas).expr()
+            + reset_inexistent * (GasCost::SSTORE_SET - warm_read_gas).expr()
+            - recreate_slot * clears_schedule.expr();
 
         Self {
             tx_refund_old,
@@ -306,6 +319,7 @@ impl<F: Field> SstoreTxRefundGadget<F> {
         &self,
         region: &mut CachedRegion<'_, '_, F>,
         offset: usize,
+        chain_config: &ChainConfig,
         tx_refund: u64,
         tx_refund_old: u64,
         value: eth_types::Word,
@@ -339,7 +353,13 @@ impl<F: Field> SstoreTxRefundGadget<F> {
             Word::from(value_prev),
         )?;
         debug_assert_eq!(
-            calc_expected_tx_refund(tx_refund_old, value, value_prev, original_value),
+            calc_expected_tx_refund(
+                chain_config,
+                tx_refund_old,
+                value,
+                value_prev,
+                original_value
+            ),
             tx_refund
         );
         Ok(())
@@ -347,6 +367,7 @@ impl<F: Field> SstoreTxRefundGadget<F> {
 }
 
 fn calc_expected_tx_refund(
+    chain_config: &ChainConfig,
     tx_refund_old: u64,
     value: eth_types::Word,
     value_prev: eth_types::Word,
@@ -357,6 +378,8 @@ fn calc_expected_tx_refund(
     // understanding and comparison.
 
     let mut tx_refund_new = tx_refund_old;
+    let clears_schedule = chain_config.sstore_clears_schedule();
+    let warm_read_gas = chain_config.sload_gas(true);
 
     // The "clearing slot refund" and "resetting value refund" are ADDED together,
     // they are NOT MUTUALLY EXCLUSIVE.
@@ -367,6 +390,8 @@ fn calc_expected_tx_refund(
     // 3. SSTORE_SET - WARM_ACCESS
     // 4. SSTORE_RESET - WARM_ACCESS
     // 5. -SSTORE_CLEARS_SCHEDULE + SSTORE_RESET - WARM_ACCESS
+    // where the costs are the ones of the chain config, e.g. WARM_ACCESS is
+    // the SLOAD cost of 800 and SSTORE_RESET is 5000 before Berlin.
     // The last case can happen if (original_value, prev_value, value) be (v,0,v)
     // where v != 0,
     // then both "clearing slot refund" and "resetting value refund" are non zero.
@@ -377,11 +402,11 @@ fn calc_expected_tx_refund(
         if !original_value.is_zero() {
             if value_prev.is_zero() {
                 // recreate slot (2.2.1.1)
-                tx_refund_new -= GasCost::SSTORE_CLEARS_SCHEDULE
+                tx_refund_new -= clears_schedule
             }
             if value.is_zero() {
                 // delete slot (2.2.1.2)
-                tx_refund_new += GasCost::SSTORE_CLEARS_SCHEDULE
+                tx_refund_new += clears_schedule
             }
         }
 
@@ -389,10 +414,10 @@ fn calc_expected_tx_refund(
         if original_value == value {
             if original_value.is_zero() {
                 // reset to original inexistent slot (2.2.2.1)
-                tx_refund_new += GasCost::SSTORE_SET - GasCost::WARM_ACCESS;
+                tx_refund_new += GasCost::SSTORE_SET - warm_read_gas;
             } else {
                 // reset to original existing slot (2.2.2.2)
-                tx_refund_new += GasCost::SSTORE_RESET - GasCost::WARM_ACCESS;
+                tx_refund_new += chain_config.sstore_reset_gas() - warm_read_gas;
             }
         }
     }
@@ -404,8 +429,9 @@ fn calc_expected_tx_refund(
 mod test {
 
     use crate::test_util::CircuitTestBuilder;
+    use bus_mapping::chain_config::{ChainConfig, Hardfork};
     use eth_types::{bytecode, Word};
-    use mock::{test_ctx::helpers::tx_from_1_to_0, TestContext, MOCK_ACCOUNTS};
+    use mock::{test_ctx::helpers::tx_from_1_to_0, TestContext, MOCK_ACCOUNTS, MOCK_CHAIN_ID};
 
     #[test]
     fn sstore_gadget_no_refund() {
@@ -468,6 +494,49 @@ mod test {
         );
     }
 
+    #[test]
+    fn sstore_gadget_istanbul() {
+        // Before Berlin the first SSTORE resets the slot for 5000 and clears it,
+        // which refunds 15000 before London.  The second one costs the SLOAD
+        // cost of 800 and resets the slot to its original value, which refunds
+        // 5000 - 800 but takes back the 15000 of the clear.
+        let key = Word::from(0x030201);
+        let original_value = Word::from(0x060504);
+        let ctx = TestContext::<2, 1>::new_with_hardfork(
+            None,
+            |accs| {
+                accs[0]
+                    .address(MOCK_ACCOUNTS[0])
+                    .balance(Word::from(10u64.pow(19)))
+                    .code(bytecode! {
+                        PUSH32(0)
+                        PUSH32(key)
+                        SSTORE
+                        PUSH32(original_value)
+                        PUSH32(key)
+                        SSTORE
+                        STOP
+                    })
+                    .storage(vec![(key, original_value)].into_iter());
+                accs[1]
+                    .address(MOCK_ACCOUNTS[1])
+                    .balance(Word::from(10u64.pow(19)));
+            },
+            tx_from_1_to_0,
+            |block, _txs| block.base_fee_per_gas(Some(Word::zero())),
+            "Istanbul",
+        )
+        .unwrap();
+
+        CircuitTestBuilder::new_from_test_ctx(ctx)
+            .input_builder_modifier(Box::new(|builder| {
+                builder
+                    .block
+                    .set_chain_config(ChainConfig::new(MOCK_CHAIN_ID.as_u64(), Hardfork::Istanbul));
+            }))
+            .run();
+    }
+
     fn test_ok(key: Word, value: Word, value_prev: Word, original_value: Word) {
         // Here we use two bytecodes to test both is_persistent(STOP) or not(REVERT)
         // Besides, in bytecode we use two SSTOREs,
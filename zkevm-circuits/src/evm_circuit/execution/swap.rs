@@ -17,6 +17,9 @@ use crate::{
 use eth_types::{evm_types::OpcodeId, Field};
 use halo2_proofs::plonk::Error;
 
+/// Gadget for SWAP1, ..., SWAP16.  The stack slot to swap with the top is
+/// derived from the opcode, which the same context gadget checks against the
+/// execution state, so all the variants share one configuration.
 #[derive(Clone, Debug)]
 pub(crate) struct SwapGadget<F> {
     same_context: SameContextGadget<F>,
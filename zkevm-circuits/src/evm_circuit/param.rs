@@ -112,6 +112,14 @@ pub(crate) const N_BYTES_PROGRAM_COUNTER: usize = N_BYTES_U64;
 // Number of bytes that will be used for a tx's gas field.
 pub(crate) const N_BYTES_GAS: usize = N_BYTES_U64;
 
+// Number of bytes that will be used for the L1 data fee of a tx, which is
+// below 2^(65 + 128) / L1_FEE_PRECISION.
+pub(crate) const N_BYTES_L1_FEE: usize = 21;
+
+// Number of bytes that will be used for the remainder of the division of the L1
+// data fee by L1_FEE_PRECISION.
+pub(crate) const N_BYTES_L1_FEE_REMAINDER: usize = 4;
+
 // Number of bytes that will be used for call data's size.
 pub(crate) const N_BYTES_CALLDATASIZE: usize = N_BYTES_U64;
 
@@ -149,6 +157,8 @@ pub(crate) const N_BYTES_TX_VALUE: usize = N_BYTES_WORD;
 pub(crate) const N_BYTES_TX_CALLDATA_LEN: usize = N_BYTES_CALLDATASIZE;
 pub(crate) const N_BYTES_TX_CALLDATA_GASCOST: usize = N_BYTES_U64;
 pub(crate) const N_BYTES_TX_TXSIGNHASH: usize = N_BYTES_WORD;
+pub(crate) const N_BYTES_TX_CALLDATA_OFFSET: usize = N_BYTES_U64;
+pub(crate) const N_BYTES_TX_TXHASH: usize = N_BYTES_WORD;
 pub(crate) const N_BYTES_TX: usize = N_BYTES_TX_NONCE
     + N_BYTES_TX_GAS_LIMIT
     + N_BYTES_TX_GASPRICE
@@ -158,7 +168,9 @@ pub(crate) const N_BYTES_TX: usize = N_BYTES_TX_NONCE
     + N_BYTES_TX_VALUE
     + N_BYTES_TX_CALLDATA_LEN
     + N_BYTES_TX_CALLDATA_GASCOST
-    + N_BYTES_TX_TXSIGNHASH;
+    + N_BYTES_TX_TXSIGNHASH
+    + N_BYTES_TX_CALLDATA_OFFSET
+    + N_BYTES_TX_TXHASH;
 
 lazy_static::lazy_static! {
     // Step slot height in evm circuit
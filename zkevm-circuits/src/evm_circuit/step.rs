@@ -356,6 +356,28 @@ impl ExecutionState {
             || self.halts_in_exception()
     }
 
+    /// Whether the state executes an opcode, in contrast to the internal states
+    pub(crate) fn executes_opcode(&self) -> bool {
+        !matches!(self, Self::BeginTx | Self::EndTx | Self::EndBlock)
+    }
+
+    /// Whether a step in this state can be followed by a step in `next`, as
+    /// listed by [`FixedTableTag::ExecutionStateTransition`].
+    ///
+    /// [`FixedTableTag::ExecutionStateTransition`]: crate::evm_circuit::table::FixedTableTag::ExecutionStateTransition
+    pub(crate) fn can_transit_to(&self, next: &Self) -> bool {
+        match (self, next) {
+            (Self::EndTx, next) => matches!(next, Self::BeginTx | Self::EndBlock),
+            (Self::EndBlock, next) => matches!(next, Self::EndBlock),
+            // Only EndTx starts the next transaction or the padding
+            (_, Self::BeginTx | Self::EndBlock) => false,
+            // A transaction ends when its root call halts, or right away when
+            // there is no code to execute
+            (from, Self::EndTx) => from.halts() || matches!(from, Self::BeginTx),
+            _ => true,
+        }
+    }
+
     /// Get the opocdes that are related to the execution state
     pub fn responsible_opcodes(&self) -> Vec<ResponsibleOp> {
         if matches!(self, Self::ErrorStack) {
@@ -513,7 +535,11 @@ impl ExecutionState {
             Self::RETURN_REVERT => vec![OpcodeId::RETURN, OpcodeId::REVERT],
             Self::CREATE2 => vec![OpcodeId::CREATE2],
             Self::SELFDESTRUCT => vec![OpcodeId::SELFDESTRUCT],
-            Self::ErrorInvalidOpcode => OpcodeId::invalid_opcodes(),
+            // BASEFEE is invalid before London, see `OpcodeGatingGadget`.
+            Self::ErrorInvalidOpcode => OpcodeId::invalid_opcodes()
+                .into_iter()
+                .chain([OpcodeId::BASEFEE])
+                .collect(),
             _ => vec![],
         }
         .into_iter()
@@ -633,6 +659,16 @@ impl<F: Field> DynamicSelectorHalf<F> {
             .expect("Select some Targets")
     }
 
+    /// Returns the selected target: `2 * pair_index + odd`.
+    pub(crate) fn value(&self) -> Expression<F> {
+        self.target_pairs
+            .iter()
+            .enumerate()
+            .fold(self.target_odd.expr(), |acc, (pair_index, cell)| {
+                acc + (2 * pair_index).expr() * cell.expr()
+            })
+    }
+
     pub(crate) fn assign(
         &self,
         region: &mut CachedRegion<'_, '_, F>,
@@ -747,6 +783,12 @@ impl<F: Field> Step<F> {
             .selector(execution_states.into_iter().map(|s| s as usize))
     }
 
+    /// Returns the execution state of the step as a number, see
+    /// [`ExecutionState::as_u64`].
+    pub(crate) fn execution_state_value(&self) -> Expression<F> {
+        self.state.execution_state.value()
+    }
+
     pub(crate) fn assign_exec_step(
         &self,
         region: &mut CachedRegion<'_, '_, F>,
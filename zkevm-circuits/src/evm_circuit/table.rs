@@ -3,16 +3,23 @@
 use crate::{
     evm_circuit::step::{ExecutionState, ResponsibleOp},
     impl_expr,
+    table::BytecodeTable,
     util::word::Word,
 };
 use bus_mapping::evm::OpcodeId;
 use eth_types::Field;
 use gadgets::util::Expr;
 use halo2_proofs::plonk::Expression;
+use lazy_static::lazy_static;
+use std::{
+    any::{Any, TypeId},
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
 use strum::IntoEnumIterator;
 use strum_macros::EnumIter;
 
-#[derive(Clone, Copy, Debug, EnumIter)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, EnumIter)]
 /// Tags for different fixed tables
 pub enum FixedTableTag {
     /// x == 0
@@ -47,10 +54,23 @@ pub enum FixedTableTag {
     Pow2,
     /// Lookup constant gas cost for opcodes
     ConstantGasCost,
+    /// Lookup program counter delta for opcodes
+    ProgramCounterDelta,
+    /// Legal transitions between the execution states of consecutive steps
+    ExecutionStateTransition,
 }
 impl_expr!(FixedTableTag);
 
 impl FixedTableTag {
+    /// Returns the tag of a constant tag expression, or `None` if the
+    /// expression is not constant.
+    pub(crate) fn from_expr<F: Field>(expr: &Expression<F>) -> Option<Self> {
+        match expr {
+            Expression::Constant(value) => Self::iter().find(|tag| F::from(*tag as u64) == *value),
+            _ => None,
+        }
+    }
+
     /// build up the fixed table row values
     pub(crate) fn build<F: Field>(&self) -> Box<dyn Iterator<Item = [F; 4]>> {
         let tag = F::from(*self as u64);
@@ -125,22 +145,93 @@ impl FixedTableTag {
                 };
                 [tag, F::from(value), pow_lo, pow_hi]
             })),
-            Self::ConstantGasCost => Box::new(
-                OpcodeId::iter()
-                    .filter(move |opcode| opcode.constant_gas_cost() > 0)
-                    .map(move |opcode| {
-                        [
-                            tag,
-                            F::from(opcode.as_u64()),
-                            F::from(opcode.constant_gas_cost()),
-                            F::ZERO,
-                        ]
-                    }),
-            ),
+            Self::ConstantGasCost => {
+                Box::new(OpcodeId::valid_opcodes().into_iter().map(move |opcode| {
+                    [
+                        tag,
+                        F::from(opcode.as_u64()),
+                        F::from(opcode.constant_gas_cost()),
+                        F::from(opcode.has_dynamic_gas_cost() as u64),
+                    ]
+                }))
+            }
+            Self::ProgramCounterDelta => {
+                Box::new(OpcodeId::valid_opcodes().into_iter().map(move |opcode| {
+                    let delta = program_counter_delta(opcode);
+                    [
+                        tag,
+                        F::from(opcode.as_u64()),
+                        F::from(delta.unwrap_or_default()),
+                        F::from(delta.is_none() as u64),
+                    ]
+                }))
+            }
+            Self::ExecutionStateTransition => {
+                Box::new(ExecutionState::iter().flat_map(move |from| {
+                    ExecutionState::iter()
+                        .filter(move |to| from.can_transit_to(to))
+                        .map(move |to| [tag, F::from(from.as_u64()), F::from(to.as_u64()), F::ZERO])
+                }))
+            }
         }
     }
 }
 
+/// Returns the amount by which `opcode` increases the program counter, or
+/// `None` for the jump family, whose destination is witnessed by the execution
+/// gadget.
+pub(crate) fn program_counter_delta(opcode: OpcodeId) -> Option<u64> {
+    match opcode {
+        OpcodeId::JUMP | OpcodeId::JUMPI => None,
+        _ => Some(1 + opcode.data_len() as u64),
+    }
+}
+
+lazy_static! {
+    static ref SHARED_FIXED_TABLE_PROVIDERS: Mutex<HashMap<TypeId, Box<dyn Any + Send + Sync>>> =
+        Mutex::new(HashMap::new());
+}
+
+/// Rows of the fixed table, computed lazily once per tag.
+///
+/// The provider returned by [`FixedTableProvider::shared`] is shared by all
+/// the circuits of the process, so that building several circuits (or the
+/// circuits of several blocks) doesn't rebuild the rows of the large tables,
+/// like the bitwise ones.
+#[derive(Clone, Debug, Default)]
+pub struct FixedTableProvider<F> {
+    rows: Arc<Mutex<HashMap<FixedTableTag, Arc<Vec<[F; 4]>>>>>,
+}
+
+impl<F: Field> FixedTableProvider<F> {
+    /// Returns the provider shared by the circuits over the field `F`.
+    pub fn shared() -> Self {
+        SHARED_FIXED_TABLE_PROVIDERS
+            .lock()
+            .unwrap()
+            .entry(TypeId::of::<F>())
+            .or_insert_with(|| Box::<Self>::default())
+            .downcast_ref::<Self>()
+            .expect("provider of another field")
+            .clone()
+    }
+
+    /// Returns the rows of `tag`, building them on the first call.
+    pub fn rows(&self, tag: FixedTableTag) -> Arc<Vec<[F; 4]>> {
+        self.rows
+            .lock()
+            .unwrap()
+            .entry(tag)
+            .or_insert_with(|| Arc::new(tag.build().collect()))
+            .clone()
+    }
+
+    /// Returns the number of rows of the tables of `tags`.
+    pub fn num_rows(&self, tags: &[FixedTableTag]) -> usize {
+        tags.iter().map(|tag| self.rows(*tag).len()).sum()
+    }
+}
+
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, EnumIter)]
 /// Each item represents the lookup table to query
 pub enum Table {
@@ -397,14 +488,13 @@ impl<F: Field> Lookup<F> {
                 index,
                 is_code,
                 value,
-            } => vec![
-                hash.lo(),
-                hash.hi(),
+            } => BytecodeTable::lookup_exprs(
+                hash.clone(),
                 tag.clone(),
                 index.clone(),
                 is_code.clone(),
                 value.clone(),
-            ],
+            ),
             Self::Block {
                 field_tag,
                 number,
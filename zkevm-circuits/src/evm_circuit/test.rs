@@ -1,6 +1,19 @@
 pub use super::EvmCircuit;
 
-use eth_types::Word;
+use super::{
+    cached::EvmCircuitCached,
+    step::{ExecutionState, HasExecutionState},
+};
+use crate::witness::{Block, Call, ExecStep, RwMap, Transaction};
+use bus_mapping::{
+    circuit_input_builder::{ExecState, FixedCParams},
+    state_db::CodeDB,
+};
+use eth_types::{Bytecode, Word};
+use halo2_proofs::{
+    dev::{MockProver, VerifyFailure},
+    halo2curves::bn256::Fr,
+};
 use rand::{
     distributions::uniform::{SampleRange, SampleUniform},
     random, thread_rng, Rng,
@@ -25,3 +38,65 @@ pub(crate) fn rand_bytes_array<const N: usize>() -> [u8; N] {
 pub(crate) fn rand_word() -> Word {
     Word::from_big_endian(&rand_bytes_array::<32>())
 }
+
+/// Run the EVM circuit on a minimal block built around the hand-crafted
+/// `steps` of a root call with id 1 executing `bytecode`, with `rws` holding
+/// the rw operations referenced by the steps.  The last step is only assigned
+/// as the successor of the previous one: the constraints are verified at the
+/// rows of all the other steps.  This allows testing an execution gadget
+/// without a trace from bus-mapping.
+pub(crate) fn run_steps(
+    steps: Vec<ExecStep>,
+    rws: RwMap,
+    bytecode: Bytecode,
+) -> Result<(), Vec<VerifyFailure>> {
+    assert!(steps.len() > 1, "the last step is only used as successor");
+    let mut bytecodes = CodeDB::default();
+    let code_hash = bytecodes.insert(bytecode.code());
+    let end_block = ExecStep {
+        exec_state: ExecState::EndBlock,
+        rwc: steps[steps.len() - 1].rwc,
+        ..Default::default()
+    };
+
+    // The first step of the EVM circuit must be a BeginTx or EndBlock, so the
+    // steps are preceded by an EndBlock step which is not verified.
+    let first_row = ExecutionState::EndBlock.get_step_height();
+    let height: usize = steps[..steps.len() - 1]
+        .iter()
+        .map(|step| step.execution_state().get_step_height())
+        .sum();
+    let rows = first_row..first_row + height;
+
+    let mut tx = Transaction::padding_tx(1);
+    tx.calls_mut().push(Call {
+        call_id: 1,
+        is_root: true,
+        is_persistent: true,
+        is_success: true,
+        code_hash,
+        depth: 1,
+        ..Default::default()
+    });
+    *tx.steps_mut() = std::iter::once(ExecStep {
+        rwc: steps[0].rwc,
+        ..end_block.clone()
+    })
+    .chain(steps)
+    .collect();
+
+    let block = Block::<Fr> {
+        randomness: Fr::from(0xcafeu64),
+        txs: vec![tx],
+        end_block_not_last: end_block.clone(),
+        end_block_last: end_block,
+        rws,
+        bytecodes,
+        circuits_params: FixedCParams::default(),
+        ..Default::default()
+    };
+    let k = block.get_test_degree();
+    let circuit = EvmCircuitCached::get_test_circuit_from_block(block);
+    let prover = MockProver::<Fr>::run(k, &circuit, vec![]).unwrap();
+    prover.verify_at_rows(rows.clone(), rows)
+}
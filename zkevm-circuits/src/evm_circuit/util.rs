@@ -35,6 +35,10 @@ use super::param::{N_BYTES_ACCOUNT_ADDRESS, N_BYTES_U64, N_U16_LOOKUPS};
 #[deprecated(note = "Removing this would require to edit almost all gadget")]
 pub(crate) use crate::util::cell_manager::{Cell, CellType};
 
+/// Advice assignment recorded by a `CachedRegion`, to be replayed in the
+/// region of the circuit.
+pub(crate) type AdviceAssignment<F> = (Column<Advice>, usize, Value<F>);
+
 pub struct CachedRegion<'r, 'b, F: Field> {
     region: &'r mut Region<'b, F>,
     advice: Vec<Vec<F>>,
@@ -42,6 +46,7 @@ pub struct CachedRegion<'r, 'b, F: Field> {
     advice_columns: Vec<Column<Advice>>,
     width_start: usize,
     height_start: usize,
+    assignment_log: Option<Vec<AdviceAssignment<F>>>,
 }
 
 impl<'r, 'b, F: Field> CachedRegion<'r, 'b, F> {
@@ -60,9 +65,26 @@ impl<'r, 'b, F: Field> CachedRegion<'r, 'b, F> {
             width_start: advice_columns[0].index(),
             height_start,
             advice_columns,
+            assignment_log: None,
         }
     }
 
+    /// Records the advice assignments, so that they can be computed in a
+    /// region which only measures its shape, and replayed later with
+    /// [`replay_assignments`].
+    pub(crate) fn with_assignment_log(mut self) -> Self {
+        self.assignment_log = Some(Vec::new());
+        self
+    }
+
+    /// Returns the advice assignments recorded so far, in order.
+    pub(crate) fn take_assignment_log(&mut self) -> Vec<AdviceAssignment<F>> {
+        self.assignment_log
+            .as_mut()
+            .map(std::mem::take)
+            .unwrap_or_default()
+    }
+
     /// This method replicates the assignment of 1 row at height_start (which
     /// must be already assigned via the CachedRegion) into a range of rows
     /// indicated by offset_begin, offset_end. It can be used as a "quick"
@@ -117,10 +139,13 @@ impl<'r, 'b, F: Field> CachedRegion<'r, 'b, F> {
         // the column has different phase than current one, so we call to `to`
         // again here to cache the value.
         if res.is_ok() {
-            to().map(|f| {
-                self.advice[column.index() - self.width_start][offset - self.height_start] =
-                    Assigned::from(&f).evaluate();
+            let value = to().map(|f| Assigned::from(&f).evaluate());
+            value.map(|f| {
+                self.advice[column.index() - self.width_start][offset - self.height_start] = f;
             });
+            if let Some(log) = self.assignment_log.as_mut() {
+                log.push((column, offset, value));
+            }
         }
         res
     }
@@ -164,6 +189,19 @@ impl<'r, 'b, F: Field> CachedRegion<'r, 'b, F> {
     }
 }
 
+/// Assigns in `region` the advice assignments recorded by a `CachedRegion`,
+/// in the order they were recorded, so that the latest assignment of a cell
+/// is the one kept.
+pub(crate) fn replay_assignments<F: Field>(
+    region: &mut Region<'_, F>,
+    assignments: &[AdviceAssignment<F>],
+) -> Result<(), Error> {
+    for &(column, offset, value) in assignments {
+        region.assign_advice(|| "step witness", column, offset, || value)?;
+    }
+    Ok(())
+}
+
 #[derive(Debug, Clone)]
 pub struct StoredExpression<F> {
     pub(crate) name: String,
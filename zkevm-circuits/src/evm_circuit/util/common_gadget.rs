@@ -1,15 +1,18 @@
 use super::{
     constraint_builder::ConstrainBuilderCommon,
     from_bytes,
-    math_gadget::{IsEqualWordGadget, IsZeroGadget, IsZeroWordGadget, LtGadget},
+    math_gadget::{
+        ConstantDivisionGadget, IsEqualGadget, IsEqualWordGadget, IsZeroBatchGadget, IsZeroGadget,
+        IsZeroWordGadget, LtGadget, MinMaxGadget,
+    },
     memory_gadget::{CommonMemoryAddressGadget, MemoryAddressGadget, MemoryExpansionGadget},
     AccountAddress, CachedRegion,
 };
 use crate::{
     evm_circuit::{
-        param::{N_BYTES_GAS, N_BYTES_MEMORY_WORD_SIZE},
+        param::{N_BYTES_GAS, N_BYTES_L1_FEE, N_BYTES_L1_FEE_REMAINDER, N_BYTES_MEMORY_WORD_SIZE},
         step::ExecutionState,
-        table::{FixedTableTag, Lookup},
+        table::{program_counter_delta, FixedTableTag, Lookup},
         util::{
             constraint_builder::{
                 EVMConstraintBuilder, ReversionInfo, StepStateTransition,
@@ -26,21 +29,41 @@ use crate::{
     },
     witness::{Block, Call, ExecStep},
 };
-use bus_mapping::state_db::CodeDB;
-use eth_types::{evm_types::GasCost, Field, ToAddress, ToLittleEndian, ToScalar, ToWord, U256};
+use bus_mapping::{
+    chain_config::{ChainConfig, Eip},
+    circuit_input_builder::{L1DataFeePolicy, L1_FEE_PRECISION},
+    state_db::CodeDB,
+};
+use eth_types::{
+    evm_types::{GasCost, OpcodeId, GAS_STIPEND_CALL_WITH_VALUE},
+    Field, ToAddress, ToLittleEndian, ToScalar, ToWord, U256,
+};
 use gadgets::util::{select, sum};
 use halo2_proofs::{
     circuit::Value,
     plonk::{Error, Expression},
 };
+use itertools::Itertools;
 
 /// Construction of execution state that stays in the same call context, which
 /// lookups the opcode and verifies the execution state is responsible for it,
 /// then calculates the gas_cost and constrain the state transition.
+///
+/// Whatever the gas cost the execution state constrains, the gas left must
+/// decrease by the constant gas cost of the opcode, plus a dynamic gas cost
+/// only for the opcodes which have one. Likewise, the program counter must
+/// increase by the size of the opcode and its immediate data, except for the
+/// jump family.
 #[derive(Clone, Debug)]
 pub(crate) struct SameContextGadget<F> {
     opcode: Cell<F>,
     sufficient_gas_left: RangeCheckGadget<F, N_BYTES_GAS>,
+    constant_gas: Cell<F>,
+    has_dynamic_gas: Cell<F>,
+    dynamic_gas: RangeCheckGadget<F, N_BYTES_GAS>,
+    program_counter_delta: Cell<F>,
+    is_jump: Cell<F>,
+    opcode_gating: OpcodeGatingGadget<F>,
 }
 
 impl<F: Field> SameContextGadget<F> {
@@ -61,16 +84,53 @@ impl<F: Field> SameContextGadget<F> {
                 ],
             },
         );
+        let opcode_gating = OpcodeGatingGadget::construct(cb, opcode.expr());
 
         // Check gas_left is sufficient
         let sufficient_gas_left = RangeCheckGadget::construct(cb, cb.next.state.gas_left.expr());
 
+        // Check gas_left decreases by the constant gas cost, plus a non-negative
+        // dynamic gas cost when the opcode has one
+        let constant_gas = cb.query_cell();
+        let has_dynamic_gas = cb.query_cell();
+        cb.constant_gas_lookup(opcode.expr(), constant_gas.expr(), has_dynamic_gas.expr());
+        let dynamic_gas_expr =
+            cb.curr.state.gas_left.expr() - cb.next.state.gas_left.expr() - constant_gas.expr();
+        cb.require_zero(
+            "dynamic gas cost is 0 when the opcode has none",
+            (1.expr() - has_dynamic_gas.expr()) * dynamic_gas_expr.clone(),
+        );
+        let dynamic_gas = RangeCheckGadget::construct(cb, dynamic_gas_expr);
+
+        // Check program_counter increases by the size of the opcode and its
+        // immediate data, unless it jumps
+        let program_counter_delta = cb.query_cell();
+        let is_jump = cb.query_cell();
+        cb.program_counter_delta_lookup(
+            opcode.expr(),
+            program_counter_delta.expr(),
+            is_jump.expr(),
+        );
+        cb.require_zero(
+            "program counter delta when the opcode doesn't jump",
+            (1.expr() - is_jump.expr())
+                * (cb.next.state.program_counter.expr()
+                    - cb.curr.state.program_counter.expr()
+                    - program_counter_delta.expr()),
+        );
+
         // Do step state transition
         cb.require_step_state_transition(step_state_transition);
 
         Self {
             opcode,
             sufficient_gas_left,
+            constant_gas,
+            has_dynamic_gas,
+            dynamic_gas,
+            program_counter_delta,
+            is_jump,
+            opcode_gating,
         }
     }
 
@@ -83,10 +143,39 @@ impl<F: Field> SameContextGadget<F> {
         let opcode = step.opcode().unwrap();
         self.opcode
             .assign(region, offset, Value::known(F::from(opcode.as_u64())))?;
+        self.opcode_gating.assign(region, offset, opcode)?;
 
         self.sufficient_gas_left
             .assign(region, offset, F::from(step.gas_left - step.gas_cost))?;
 
+        self.constant_gas.assign(
+            region,
+            offset,
+            Value::known(F::from(opcode.constant_gas_cost())),
+        )?;
+        self.has_dynamic_gas.assign(
+            region,
+            offset,
+            Value::known(F::from(opcode.has_dynamic_gas_cost() as u64)),
+        )?;
+        self.dynamic_gas.assign(
+            region,
+            offset,
+            F::from(step.gas_cost - opcode.constant_gas_cost()),
+        )?;
+
+        let program_counter_delta = program_counter_delta(opcode);
+        self.program_counter_delta.assign(
+            region,
+            offset,
+            Value::known(F::from(program_counter_delta.unwrap_or_default())),
+        )?;
+        self.is_jump.assign(
+            region,
+            offset,
+            Value::known(F::from(program_counter_delta.is_none() as u64)),
+        )?;
+
         Ok(())
     }
 }
@@ -475,6 +564,89 @@ impl<F: Field> TransferWithGasFeeGadget<F> {
     }
 }
 
+/// The L1DataFeeGadget computes the L1 data fee charged by the
+/// [`L1DataFeePolicy`] the circuit is configured with, which is
+/// `(call_data_gas_cost + overhead) * l1_base_fee * scalar / L1_FEE_PRECISION`
+/// rounded down.  This gadget is used in BeginTx and EndTx.
+#[derive(Clone, Debug)]
+pub(crate) struct L1DataFeeGadget<F> {
+    l1_fee: Word32Cell<F>,
+    remainder: Cell<F>,
+    remainder_range: RangeCheckGadget<F, N_BYTES_L1_FEE_REMAINDER>,
+    remainder_lt_precision: RangeCheckGadget<F, N_BYTES_L1_FEE_REMAINDER>,
+}
+
+impl<F: Field> L1DataFeeGadget<F> {
+    pub(crate) fn construct(
+        cb: &mut EVMConstraintBuilder<F>,
+        policy: &L1DataFeePolicy,
+        call_data_gas_cost: Expression<F>,
+    ) -> Self {
+        let scaled_l1_base_fee = policy.scaled_l1_base_fee();
+        // The product with the L1 gas, which fits in 65 bits, must not overflow
+        // the field.
+        assert!(
+            scaled_l1_base_fee.bits() <= 128,
+            "l1_base_fee * scalar of the L1 data fee policy must fit in 128 bits"
+        );
+
+        let l1_fee = cb.query_word32();
+        // The fee is below 2^(65 + 128) / L1_FEE_PRECISION, so only the low
+        // N_BYTES_L1_FEE bytes can be non-zero.
+        for limb in l1_fee.limbs[N_BYTES_L1_FEE..].iter() {
+            cb.require_zero("L1 data fee high bytes are zero", limb.expr());
+        }
+        let remainder = cb.query_cell();
+        let remainder_range = RangeCheckGadget::construct(cb, remainder.expr());
+        let remainder_lt_precision =
+            RangeCheckGadget::construct(cb, (L1_FEE_PRECISION - 1).expr() - remainder.expr());
+
+        cb.require_equal(
+            "l1_fee * L1_FEE_PRECISION + remainder == l1_gas * l1_base_fee * scalar",
+            from_bytes::expr(&l1_fee.limbs[..N_BYTES_L1_FEE]) * L1_FEE_PRECISION.expr()
+                + remainder.expr(),
+            (call_data_gas_cost + policy.overhead.expr())
+                * Expression::Constant(F::from_u128(scaled_l1_base_fee.as_u128())),
+        );
+
+        Self {
+            l1_fee,
+            remainder,
+            remainder_range,
+            remainder_lt_precision,
+        }
+    }
+
+    pub(crate) fn l1_fee(&self) -> Word32Cell<F> {
+        self.l1_fee.clone()
+    }
+
+    pub(crate) fn assign(
+        &self,
+        region: &mut CachedRegion<'_, '_, F>,
+        offset: usize,
+        policy: &L1DataFeePolicy,
+        call_data_gas_cost: u64,
+    ) -> Result<U256, Error> {
+        let l1_gas = U256::from(call_data_gas_cost) + U256::from(policy.overhead);
+        let (l1_fee, remainder) =
+            (l1_gas * policy.scaled_l1_base_fee()).div_mod(U256::from(L1_FEE_PRECISION));
+        let remainder = remainder.as_u64();
+
+        self.l1_fee.assign_u256(region, offset, l1_fee)?;
+        self.remainder
+            .assign(region, offset, Value::known(F::from(remainder)))?;
+        self.remainder_range
+            .assign(region, offset, F::from(remainder))?;
+        self.remainder_lt_precision.assign(
+            region,
+            offset,
+            F::from(L1_FEE_PRECISION - 1 - remainder),
+        )?;
+        Ok(l1_fee)
+    }
+}
+
 /// The TransferGadget handles a transfer of value from sender to receiver.  The
 /// transfer is only performed if the value is not zero.  If the transfer is
 /// performed and the receiver account doesn't exist, it will be created by
@@ -485,7 +657,7 @@ pub(crate) struct TransferGadget<F> {
     receiver: UpdateBalanceGadget<F, 2, true>,
     must_create: Expression<F>,
     receiver_exists: Expression<F>,
-    pub(crate) value_is_zero: IsZeroWordGadget<F, Word32Cell<F>>,
+    pub(crate) value_is_zero: IsZeroBatchGadget<F, 2>,
 }
 
 impl<F: Field> TransferGadget<F> {
@@ -498,7 +670,7 @@ impl<F: Field> TransferGadget<F> {
         value: Word32Cell<F>,
         reversion_info: &mut ReversionInfo<F>,
     ) -> Self {
-        let value_is_zero = IsZeroWordGadget::construct(cb, &value);
+        let value_is_zero = IsZeroBatchGadget::construct_word(cb, &value);
         // If receiver doesn't exist, create it
         cb.condition(
             or::expr([
@@ -574,8 +746,7 @@ impl<F: Field> TransferGadget<F> {
             vec![value],
             receiver_balance,
         )?;
-        self.value_is_zero
-            .assign_value(region, offset, Value::known(Word::from(value)))?;
+        self.value_is_zero.assign_word(region, offset, value)?;
         Ok(())
     }
 }
@@ -592,12 +763,12 @@ pub(crate) struct CommonCallGadget<F, const IS_SUCCESS_CALL: bool> {
     pub rd_address: MemoryAddressGadget<F>,
     pub memory_expansion: MemoryExpansionGadget<F, 2, N_BYTES_MEMORY_WORD_SIZE>,
 
-    value_is_zero: IsZeroWordGadget<F, Word32Cell<F>>,
+    value_is_zero: IsZeroBatchGadget<F, 2>,
     pub has_value: Expression<F>,
     pub callee_code_hash: WordCell<F>,
-    pub is_empty_code_hash: IsEqualWordGadget<F, WordCell<F>, Word<Expression<F>>>,
+    pub is_empty_code_hash: IsZeroBatchGadget<F, 2>,
 
-    pub callee_not_exists: IsZeroWordGadget<F, WordCell<F>>,
+    pub callee_not_exists: IsZeroBatchGadget<F, 2>,
 }
 
 impl<F: Field, const IS_SUCCESS_CALL: bool> CommonCallGadget<F, IS_SUCCESS_CALL> {
@@ -655,7 +826,7 @@ impl<F: Field, const IS_SUCCESS_CALL: bool> CommonCallGadget<F, IS_SUCCESS_CALL>
             MemoryExpansionGadget::construct(cb, [cd_address.address(), rd_address.address()]);
 
         // construct common gadget
-        let value_is_zero = IsZeroWordGadget::construct(cb, &value);
+        let value_is_zero = IsZeroBatchGadget::construct_word(cb, &value);
         let has_value = select::expr(
             is_delegatecall.expr() + is_staticcall.expr(),
             0.expr(),
@@ -669,8 +840,8 @@ impl<F: Field, const IS_SUCCESS_CALL: bool> CommonCallGadget<F, IS_SUCCESS_CALL>
             callee_code_hash.to_word(),
         );
         let is_empty_code_hash =
-            IsEqualWordGadget::construct(cb, &callee_code_hash, &cb.empty_code_hash());
-        let callee_not_exists = IsZeroWordGadget::construct(cb, &callee_code_hash);
+            IsZeroBatchGadget::construct_equal_word(cb, &callee_code_hash, &cb.empty_code_hash());
+        let callee_not_exists = IsZeroBatchGadget::construct_word(cb, &callee_code_hash);
 
         Self {
             is_success,
@@ -699,17 +870,15 @@ impl<F: Field, const IS_SUCCESS_CALL: bool> CommonCallGadget<F, IS_SUCCESS_CALL>
 
     pub fn gas_cost_expr(
         &self,
+        cb: &EVMConstraintBuilder<F>,
         is_warm_prev: Expression<F>,
         is_call: Expression<F>,
     ) -> Expression<F> {
-        select::expr(
-            is_warm_prev,
-            GasCost::WARM_ACCESS.expr(),
-            GasCost::COLD_ACCOUNT_ACCESS.expr(),
-        ) + self.has_value.clone()
-            * (GasCost::CALL_WITH_VALUE.expr()
-                // Only CALL opcode could invoke transfer to make empty account into non-empty.
-                + is_call * self.callee_not_exists.expr() * GasCost::NEW_ACCOUNT.expr())
+        account_access_gas_cost(cb, is_warm_prev)
+            + self.has_value.clone()
+                * (GasCost::CALL_WITH_VALUE.expr()
+                    // Only CALL opcode could invoke transfer to make empty account into non-empty.
+                    + is_call * self.callee_not_exists.expr() * GasCost::NEW_ACCOUNT.expr())
             + self.memory_expansion.gas_cost()
     }
 
@@ -755,64 +924,83 @@ impl<F: Field, const IS_SUCCESS_CALL: bool> CommonCallGadget<F, IS_SUCCESS_CALL>
             [cd_address, rd_address],
         )?;
 
-        self.value_is_zero
-            .assign(region, offset, Word::from(value))?;
+        self.value_is_zero.assign_word(region, offset, value)?;
         self.callee_code_hash
             .assign_u256(region, offset, callee_code_hash)?;
-        self.is_empty_code_hash.assign_value(
-            region,
-            offset,
-            Value::known(Word::from(callee_code_hash)),
-            Value::known(Word::from(CodeDB::empty_code_hash().to_word())),
-        )?;
-        self.callee_not_exists.assign_value(
+        self.is_empty_code_hash.assign_equal_word(
             region,
             offset,
-            Value::known(Word::from(callee_code_hash)),
+            callee_code_hash,
+            CodeDB::empty_code_hash().to_word(),
         )?;
+        self.callee_not_exists
+            .assign_word(region, offset, callee_code_hash)?;
         Ok(memory_expansion_gas_cost)
     }
 
     pub(crate) fn cal_gas_cost_for_assignment(
         &self,
+        chain_config: &ChainConfig,
         memory_expansion_gas_cost: u64,
         is_warm_prev: bool,
         is_call: bool,
         has_value: bool,
         is_empty_account: bool,
     ) -> Result<u64, Error> {
-        let gas_cost = if is_warm_prev {
-            GasCost::WARM_ACCESS
-        } else {
-            GasCost::COLD_ACCOUNT_ACCESS
-        } + if has_value {
-            GasCost::CALL_WITH_VALUE
-                // Only CALL opcode could invoke transfer to make empty account into non-empty.
-                + if is_call && is_empty_account {
-                    GasCost::NEW_ACCOUNT
-                } else {
-                    0
-                }
-        } else {
-            0
-        } + memory_expansion_gas_cost;
+        let gas_cost = chain_config.account_access_gas(is_warm_prev)
+            + if has_value {
+                GasCost::CALL_WITH_VALUE
+                    // Only CALL opcode could invoke transfer to make empty account into non-empty.
+                    + if is_call && is_empty_account {
+                        GasCost::NEW_ACCOUNT
+                    } else {
+                        0
+                    }
+            } else {
+                0
+            }
+            + memory_expansion_gas_cost;
 
         Ok(gas_cost)
     }
 }
 
+/// Gas cost of the access to an account which is warm or not, by BALANCE,
+/// EXTCODESIZE, EXTCODECOPY, EXTCODEHASH and the CALL family.  The flat cost of
+/// Istanbul applies unless EIP-2929 is enabled, see
+/// [`ChainConfig::account_access_gas`].
+pub(crate) fn account_access_gas_cost<F: Field>(
+    cb: &EVMConstraintBuilder<F>,
+    is_warm: Expression<F>,
+) -> Expression<F> {
+    if cb.feature_config().is_enabled(Eip::Eip2929) {
+        select::expr(
+            is_warm,
+            GasCost::WARM_ACCESS.expr(),
+            GasCost::COLD_ACCOUNT_ACCESS.expr(),
+        )
+    } else {
+        GasCost::ISTANBUL_ACCOUNT_ACCESS.expr()
+    }
+}
+
 #[derive(Clone, Debug)]
 pub(crate) struct SloadGasGadget<F> {
     gas_cost: Expression<F>,
 }
 
 impl<F: Field> SloadGasGadget<F> {
-    pub(crate) fn construct(_cb: &mut EVMConstraintBuilder<F>, is_warm: Expression<F>) -> Self {
-        let gas_cost = select::expr(
-            is_warm.expr(),
-            GasCost::WARM_ACCESS.expr(),
-            GasCost::COLD_SLOAD.expr(),
-        );
+    pub(crate) fn construct(cb: &mut EVMConstraintBuilder<F>, is_warm: Expression<F>) -> Self {
+        // See `ChainConfig::sload_gas`
+        let gas_cost = if cb.feature_config().is_enabled(Eip::Eip2929) {
+            select::expr(
+                is_warm.expr(),
+                GasCost::WARM_ACCESS.expr(),
+                GasCost::COLD_SLOAD.expr(),
+            )
+        } else {
+            GasCost::ISTANBUL_SLOAD.expr()
+        };
 
         Self { gas_cost }
     }
@@ -843,24 +1031,35 @@ impl<F: Field, T: WordExpr<F> + Clone> SstoreGasGadget<F, T> {
         let value_eq_prev = IsEqualWordGadget::construct(cb, &value, &value_prev);
         let original_eq_prev = IsEqualWordGadget::construct(cb, &original_value, &value_prev);
         let original_is_zero = IsZeroWordGadget::construct(cb, &original_value);
+        // See `cal_sstore_gas_cost_for_assignment`
+        let is_access_list_gas = cb.feature_config().is_enabled(Eip::Eip2929);
+        let (warm_read_gas, reset_gas) = if is_access_list_gas {
+            (GasCost::WARM_ACCESS, GasCost::SSTORE_RESET)
+        } else {
+            (GasCost::ISTANBUL_SLOAD, GasCost::ISTANBUL_SSTORE_RESET)
+        };
         let warm_case_gas = select::expr(
             value_eq_prev.expr(),
-            GasCost::WARM_ACCESS.expr(),
+            warm_read_gas.expr(),
             select::expr(
                 original_eq_prev.expr(),
                 select::expr(
                     original_is_zero.expr(),
                     GasCost::SSTORE_SET.expr(),
-                    GasCost::SSTORE_RESET.expr(),
+                    reset_gas.expr(),
                 ),
-                GasCost::WARM_ACCESS.expr(),
+                warm_read_gas.expr(),
             ),
         );
-        let gas_cost = select::expr(
-            is_warm.expr(),
-            warm_case_gas.expr(),
-            warm_case_gas + GasCost::COLD_SLOAD.expr(),
-        );
+        let gas_cost = if is_access_list_gas {
+            select::expr(
+                is_warm.expr(),
+                warm_case_gas.expr(),
+                warm_case_gas + GasCost::COLD_SLOAD.expr(),
+            )
+        } else {
+            warm_case_gas
+        };
 
         Self {
             is_warm,
@@ -907,42 +1106,202 @@ impl<F: Field, T: WordExpr<F> + Clone> SstoreGasGadget<F, T> {
     }
 }
 
-pub(crate) fn cal_sload_gas_cost_for_assignment(is_warm: bool) -> u64 {
-    if is_warm {
-        GasCost::WARM_ACCESS
-    } else {
-        GasCost::COLD_SLOAD
-    }
-}
-
+/// Gas cost of an SSTORE following EIP-2200, plus the surcharge of a cold slot
+/// with EIP-2929.
 pub(crate) fn cal_sstore_gas_cost_for_assignment(
+    chain_config: &ChainConfig,
     value: U256,
     value_prev: U256,
     original_value: U256,
     is_warm: bool,
 ) -> u64 {
     let warm_case_gas = if value_prev == value {
-        GasCost::WARM_ACCESS
+        chain_config.sload_gas(true)
     } else if original_value == value_prev {
         if original_value.is_zero() {
             GasCost::SSTORE_SET
         } else {
-            GasCost::SSTORE_RESET
+            chain_config.sstore_reset_gas()
         }
     } else {
-        GasCost::WARM_ACCESS
+        chain_config.sload_gas(true)
     };
-    if is_warm {
+    if is_warm || !chain_config.is_access_list_gas() {
         warm_case_gas
     } else {
         warm_case_gas + GasCost::COLD_SLOAD
     }
 }
 
+/// Gas forwarded to the callee of a call or a creation (EIP-150): all but one
+/// 64th of the gas available after paying the cost of the opcode, capped by the
+/// gas requested by the caller if any, plus the stipend of calls transferring
+/// value.
+#[derive(Clone, Debug)]
+pub(crate) struct CalleeGasGadget<F> {
+    one_64th_gas: ConstantDivisionGadget<F, N_BYTES_GAS>,
+    capped_callee_gas_left: Option<MinMaxGadget<F, N_BYTES_GAS>>,
+    callee_gas_left: Expression<F>,
+    stipend: Expression<F>,
+}
+
+impl<F: Field> CalleeGasGadget<F> {
+    /// Construct the gadget for CALL, CALLCODE, DELEGATECALL and STATICCALL,
+    /// where `gas` is the gas requested by the caller, which only caps the
+    /// forwarded gas when it fits in a u64.
+    pub(crate) fn construct_call(
+        cb: &mut EVMConstraintBuilder<F>,
+        gas_available: Expression<F>,
+        gas: Expression<F>,
+        gas_is_u64: Expression<F>,
+        has_value: Expression<F>,
+    ) -> Self {
+        let one_64th_gas = ConstantDivisionGadget::construct(cb, gas_available.clone(), 64);
+        let all_but_one_64th_gas = gas_available - one_64th_gas.quotient();
+        let capped_callee_gas_left = MinMaxGadget::construct(cb, gas, all_but_one_64th_gas.clone());
+        let callee_gas_left = select::expr(
+            gas_is_u64,
+            capped_callee_gas_left.min(),
+            all_but_one_64th_gas,
+        );
+
+        Self {
+            one_64th_gas,
+            capped_callee_gas_left: Some(capped_callee_gas_left),
+            callee_gas_left,
+            stipend: has_value * GAS_STIPEND_CALL_WITH_VALUE.expr(),
+        }
+    }
+
+    /// Construct the gadget for CREATE and CREATE2, which forward all but one
+    /// 64th of the gas available.
+    pub(crate) fn construct_create(
+        cb: &mut EVMConstraintBuilder<F>,
+        gas_available: Expression<F>,
+    ) -> Self {
+        let one_64th_gas = ConstantDivisionGadget::construct(cb, gas_available.clone(), 64);
+        let callee_gas_left = gas_available - one_64th_gas.quotient();
+
+        Self {
+            one_64th_gas,
+            capped_callee_gas_left: None,
+            callee_gas_left,
+            stipend: 0.expr(),
+        }
+    }
+
+    /// Gas taken from the caller and forwarded to the callee, without the
+    /// stipend
+    pub(crate) fn callee_gas_left(&self) -> Expression<F> {
+        self.callee_gas_left.clone()
+    }
+
+    /// Gas stipend given to the callee on top of the forwarded gas
+    pub(crate) fn stipend(&self) -> Expression<F> {
+        self.stipend.clone()
+    }
+
+    /// Gas the callee starts with: the forwarded gas plus the stipend
+    pub(crate) fn callee_gas_left_with_stipend(&self) -> Expression<F> {
+        self.callee_gas_left() + self.stipend()
+    }
+
+    /// One 64th of the gas available, which the caller keeps in a creation
+    pub(crate) fn one_64th_gas(&self) -> Expression<F> {
+        self.one_64th_gas.quotient()
+    }
+
+    /// Assign the gadget, where `gas` is the gas requested by the caller of a
+    /// call (None for creations), and return the gas forwarded to the callee
+    /// without the stipend.
+    pub(crate) fn assign(
+        &self,
+        region: &mut CachedRegion<'_, '_, F>,
+        offset: usize,
+        gas_available: u64,
+        gas: Option<U256>,
+    ) -> Result<u64, Error> {
+        self.one_64th_gas
+            .assign(region, offset, gas_available.into())?;
+        let all_but_one_64th_gas = gas_available - gas_available / 64;
+        Ok(match (&self.capped_callee_gas_left, gas) {
+            (Some(capped_callee_gas_left), Some(gas)) => {
+                capped_callee_gas_left.assign(
+                    region,
+                    offset,
+                    F::from(gas.low_u64()),
+                    F::from(all_but_one_64th_gas),
+                )?;
+                if gas.bits() <= 64 {
+                    gas.low_u64().min(all_but_one_64th_gas)
+                } else {
+                    all_but_one_64th_gas
+                }
+            }
+            (None, None) => all_but_one_64th_gas,
+            _ => unreachable!("gas is requested by calls only"),
+        })
+    }
+}
+
+/// Requires the opcode of the step to be allowed by the EIPs of the feature
+/// config: the opcodes responsible for the execution state which the config
+/// disables (like BASEFEE before London) can't be executed.  It's the other
+/// way around for ErrorInvalidOpcode, where the opcodes the config enables
+/// can't be invalid.
+#[derive(Clone, Debug)]
+pub(crate) struct OpcodeGatingGadget<F> {
+    excluded_opcodes: Vec<(OpcodeId, IsEqualGadget<F>)>,
+}
+
+impl<F: Field> OpcodeGatingGadget<F> {
+    pub(crate) fn construct(cb: &mut EVMConstraintBuilder<F>, opcode: Expression<F>) -> Self {
+        let feature_config = cb.feature_config();
+        let execution_state = cb.execution_state();
+        let is_invalid_opcode = execution_state == ExecutionState::ErrorInvalidOpcode;
+        let excluded_opcodes = execution_state
+            .responsible_opcodes()
+            .into_iter()
+            .map(|responsible_op| responsible_op.opcode())
+            .dedup()
+            .filter(|&responsible_opcode| {
+                Eip::introducing(responsible_opcode).is_some()
+                    && feature_config.is_opcode_enabled(responsible_opcode) == is_invalid_opcode
+            })
+            .map(|excluded_opcode| {
+                let is_excluded =
+                    IsEqualGadget::construct(cb, opcode.expr(), excluded_opcode.expr());
+                cb.require_zero("opcode is allowed by the chain config", is_excluded.expr());
+                (excluded_opcode, is_excluded)
+            })
+            .collect();
+
+        Self { excluded_opcodes }
+    }
+
+    pub(crate) fn assign(
+        &self,
+        region: &mut CachedRegion<'_, '_, F>,
+        offset: usize,
+        opcode: OpcodeId,
+    ) -> Result<(), Error> {
+        for (excluded_opcode, is_excluded) in self.excluded_opcodes.iter() {
+            is_excluded.assign(
+                region,
+                offset,
+                F::from(opcode.as_u64()),
+                F::from(excluded_opcode.as_u64()),
+            )?;
+        }
+        Ok(())
+    }
+}
+
 #[derive(Clone, Debug)]
 pub(crate) struct CommonErrorGadget<F> {
     rw_counter_end_of_reversion: WordCell<F>,
     restore_context: RestoreContextGadget<F>,
+    opcode_gating: OpcodeGatingGadget<F>,
 }
 
 impl<F: Field> CommonErrorGadget<F> {
@@ -962,6 +1321,7 @@ impl<F: Field> CommonErrorGadget<F> {
         return_data_length: Expression<F>,
     ) -> Self {
         cb.opcode_lookup(opcode.expr(), 1.expr());
+        let opcode_gating = OpcodeGatingGadget::construct(cb, opcode.expr());
 
         let rw_counter_end_of_reversion = cb.query_word_unchecked(); // rw_counter_end_of_reversion just used for read lookup, therefore skip range check
 
@@ -1018,6 +1378,7 @@ impl<F: Field> CommonErrorGadget<F> {
         Self {
             rw_counter_end_of_reversion,
             restore_context,
+            opcode_gating,
         }
     }
 
@@ -1038,6 +1399,8 @@ impl<F: Field> CommonErrorGadget<F> {
         )?;
         self.restore_context
             .assign(region, offset, block, call, step, rw_offset)?;
+        self.opcode_gating
+            .assign(region, offset, step.opcode().unwrap())?;
 
         // NOTE: return value not use for now.
         Ok(1u64)
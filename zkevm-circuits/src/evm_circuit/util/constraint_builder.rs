@@ -4,6 +4,7 @@ use crate::{
         step::{ExecutionState, Step},
         table::{FixedTableTag, Lookup, RwValues, Table},
         util::{Cell, RandomLinearCombination},
+        FeatureConfig,
     },
     table::{
         AccountFieldTag, BytecodeFieldTag, CallContextFieldTag, TxContextFieldTag, TxLogFieldTag,
@@ -16,8 +17,8 @@ use crate::{
     },
 };
 use bus_mapping::{operation::Target, state_db::EMPTY_CODE_HASH_LE};
-use eth_types::Field;
-use gadgets::util::not;
+use eth_types::{evm_types::OpcodeId, Field};
+use gadgets::util::{degree, not};
 use halo2_proofs::{
     circuit::Value,
     plonk::{
@@ -26,6 +27,7 @@ use halo2_proofs::{
         VirtualCells,
     },
 };
+use strum::IntoEnumIterator;
 
 use super::{
     address_word_to_expr, rlc, AccountAddress, CachedRegion, CellType, MemoryAddress,
@@ -248,13 +250,7 @@ impl<F: Field> BaseConstraintBuilder<F> {
 
     pub(crate) fn validate_degree(&self, degree: usize, name: &'static str) {
         if self.max_degree > 0 {
-            debug_assert!(
-                degree <= self.max_degree,
-                "Expression {} degree too high: {} > {}",
-                name,
-                degree,
-                self.max_degree,
-            );
+            degree::validate(name, degree, self.max_degree);
         }
     }
 
@@ -295,8 +291,9 @@ pub(crate) struct EVMConstraintBuilder<'a, F: Field> {
     pub(crate) curr: Step<F>,
     pub(crate) next: Step<F>,
     challenges: &'a Challenges<Expression<F>>,
+    feature_config: &'a FeatureConfig,
     execution_state: ExecutionState,
-    constraints: Constraints<F>,
+    pub(crate) constraints: Constraints<F>,
     rw_counter_offset: Expression<F>,
     program_counter_offset: usize,
     stack_pointer_offset: Expression<F>,
@@ -305,6 +302,14 @@ pub(crate) struct EVMConstraintBuilder<'a, F: Field> {
     constraints_location: ConstraintLocation,
     stored_expressions: Vec<StoredExpression<F>>,
     pub(crate) debug_expressions: Vec<(String, Expression<F>)>,
+    // Tags of the fixed table looked up, to skip the unused ones.
+    fixed_table_tags: Vec<FixedTableTag>,
+    // Whether a fixed table lookup has a non-constant tag, which is not among the registered
+    // `fixed_table_tags` unless `dynamic_fixed_table_tags_registered`.
+    has_dynamic_fixed_table_tag: bool,
+    dynamic_fixed_table_tags_registered: bool,
+    // Whether the opcode at the program counter is looked up in the bytecode table.
+    has_opcode_lookup: bool,
     meta: &'a mut ConstraintSystem<F>,
 }
 
@@ -327,12 +332,14 @@ impl<'a, F: Field> EVMConstraintBuilder<'a, F> {
         curr: Step<F>,
         next: Step<F>,
         challenges: &'a Challenges<Expression<F>>,
+        feature_config: &'a FeatureConfig,
         execution_state: ExecutionState,
     ) -> Self {
         Self {
             curr,
             next,
             challenges,
+            feature_config,
             execution_state,
             constraints: Constraints {
                 step: Vec::new(),
@@ -349,6 +356,10 @@ impl<'a, F: Field> EVMConstraintBuilder<'a, F> {
             stored_expressions: Vec::new(),
             meta,
             debug_expressions: Vec::new(),
+            fixed_table_tags: Vec::new(),
+            has_dynamic_fixed_table_tag: false,
+            dynamic_fixed_table_tags_registered: false,
+            has_opcode_lookup: false,
         }
     }
 
@@ -399,6 +410,10 @@ impl<'a, F: Field> EVMConstraintBuilder<'a, F> {
         self.challenges
     }
 
+    pub(crate) fn feature_config(&self) -> &'a FeatureConfig {
+        self.feature_config
+    }
+
     pub(crate) fn execution_state(&self) -> ExecutionState {
         self.execution_state
     }
@@ -592,12 +607,33 @@ impl<'a, F: Field> EVMConstraintBuilder<'a, F> {
     }
 
     // constant gas
-    pub(crate) fn constant_gas_lookup(&mut self, opcode: Expression<F>, gas: Expression<F>) {
+    pub(crate) fn constant_gas_lookup(
+        &mut self,
+        opcode: Expression<F>,
+        gas: Expression<F>,
+        has_dynamic_gas: Expression<F>,
+    ) {
         self.add_lookup(
             "constant gas",
             Lookup::Fixed {
                 tag: FixedTableTag::ConstantGasCost.expr(),
-                values: [opcode, gas, 0.expr()],
+                values: [opcode, gas, has_dynamic_gas],
+            },
+        );
+    }
+
+    // program counter delta
+    pub(crate) fn program_counter_delta_lookup(
+        &mut self,
+        opcode: Expression<F>,
+        delta: Expression<F>,
+        is_jump: Expression<F>,
+    ) {
+        self.add_lookup(
+            "program counter delta",
+            Lookup::Fixed {
+                tag: FixedTableTag::ProgramCounterDelta.expr(),
+                values: [opcode, delta, is_jump],
             },
         );
     }
@@ -605,6 +641,9 @@ impl<'a, F: Field> EVMConstraintBuilder<'a, F> {
     // Opcode
 
     pub(crate) fn opcode_lookup(&mut self, opcode: Expression<F>, is_code: Expression<F>) {
+        if self.program_counter_offset == 0 {
+            self.has_opcode_lookup = true;
+        }
         self.opcode_lookup_at(
             self.curr.state.program_counter.expr() + self.program_counter_offset.expr(),
             opcode,
@@ -633,6 +672,19 @@ impl<'a, F: Field> EVMConstraintBuilder<'a, F> {
         );
     }
 
+    /// Lookup a JUMPDEST opcode at `destination` in the code of the current
+    /// call.  Unlike [`Self::opcode_lookup_at`], it also applies in root
+    /// creations, whose init code is in the bytecode table too, so that the
+    /// destinations of JUMP and JUMPI are always constrained.
+    pub(crate) fn jumpdest_lookup(&mut self, destination: Expression<F>) {
+        self.bytecode_lookup(
+            self.curr.state.code_hash.to_word(),
+            destination,
+            1.expr(),
+            OpcodeId::JUMPDEST.expr(),
+        );
+    }
+
     pub(crate) fn bytecode_lookup(
         &mut self,
         code_hash: Word<Expression<F>>,
@@ -1241,6 +1293,29 @@ impl<'a, F: Field> EVMConstraintBuilder<'a, F> {
         );
     }
 
+    pub(crate) fn memory_word_lookup(
+        &mut self,
+        is_write: Expression<F>,
+        memory_address: Expression<F>,
+        value: Word<Expression<F>>,
+        value_prev: Word<Expression<F>>,
+    ) {
+        self.rw_lookup(
+            "MemoryWord lookup",
+            is_write,
+            Target::MemoryWord,
+            RwValues::new(
+                self.curr.state.call_id.expr(),
+                memory_address,
+                0.expr(),
+                Word::zero(),
+                value,
+                value_prev,
+                Word::zero(),
+            ),
+        );
+    }
+
     pub(crate) fn tx_log_lookup(
         &mut self,
         tx_id: Expression<F>,
@@ -1392,13 +1467,7 @@ impl<'a, F: Field> EVMConstraintBuilder<'a, F> {
         // We need to subtract IMPLICIT_DEGREE from MAX_DEGREE because all expressions
         // will be multiplied by state selector and q_step/q_step_first
         // selector.
-        debug_assert!(
-            degree <= MAX_DEGREE - IMPLICIT_DEGREE,
-            "Expression {} degree too high: {} > {}",
-            name,
-            degree,
-            MAX_DEGREE - IMPLICIT_DEGREE,
-        );
+        degree::validate(name, degree, MAX_DEGREE - IMPLICIT_DEGREE);
     }
 
     // General
@@ -1451,7 +1520,46 @@ impl<'a, F: Field> EVMConstraintBuilder<'a, F> {
         }
     }
 
+    /// Registers the tags a fixed table lookup with a non-constant tag can
+    /// take.  Without it, all the tags are kept in the fixed table.
+    pub(crate) fn register_fixed_table_tags(&mut self, tags: &[FixedTableTag]) {
+        self.dynamic_fixed_table_tags_registered = true;
+        for tag in tags {
+            self.add_fixed_table_tag(*tag);
+        }
+    }
+
+    fn add_fixed_table_tag(&mut self, tag: FixedTableTag) {
+        if !self.fixed_table_tags.contains(&tag) {
+            self.fixed_table_tags.push(tag);
+        }
+    }
+
+    /// Returns whether the gadget looks up the opcode at the program counter.
+    pub(crate) fn has_opcode_lookup(&self) -> bool {
+        self.has_opcode_lookup
+    }
+
+    /// Returns the tags of the fixed table looked up by the gadget.
+    pub(crate) fn fixed_table_tags(&self) -> Vec<FixedTableTag> {
+        if self.has_dynamic_fixed_table_tag && !self.dynamic_fixed_table_tags_registered {
+            FixedTableTag::iter().collect()
+        } else {
+            self.fixed_table_tags.clone()
+        }
+    }
+
+    /// Add a lookup under the current condition. The condition is applied to
+    /// the input expressions, which are then compressed by RLC and split
+    /// into stored cells until they fit the maximum degree, so gadgets can
+    /// look up under conditions of any degree.
     pub(crate) fn add_lookup(&mut self, name: &str, lookup: Lookup<F>) {
+        if let Lookup::Fixed { tag, .. } = &lookup {
+            match FixedTableTag::from_expr(tag) {
+                Some(tag) => self.add_fixed_table_tag(tag),
+                None => self.has_dynamic_fixed_table_tag = true,
+            }
+        }
         let lookup = match self.condition_expr_opt() {
             Some(condition) => lookup.conditional(condition),
             None => lookup,
@@ -1519,6 +1627,8 @@ impl<'a, F: Field> EVMConstraintBuilder<'a, F> {
             .find(|&e| e.cell_type == cell_type && e.expr_id == expr_id)
     }
 
+    /// Reduce the degree of `expr` to `max_degree` by storing the factors of
+    /// its highest degree products in cells, higher degree factor first.
     fn split_expression(
         &mut self,
         name: &'static str,
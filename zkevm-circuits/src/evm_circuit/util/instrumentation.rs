@@ -5,6 +5,7 @@ use crate::{
     util::cell_manager::CellType,
 };
 use eth_types::Field;
+use gadgets::util::degree;
 use itertools::Itertools;
 
 type StepSize = Vec<(CellType, ColumnSize)>;
@@ -16,6 +17,8 @@ type ColumnSize = (usize, usize, usize);
 pub struct Instrument {
     // States -> Cell Types -> (width, height, num_cells)
     states: Vec<(ExecutionState, StepSize)>,
+    // States -> max degree of the constraints, before the step selectors
+    degrees: Vec<(ExecutionState, usize)>,
 }
 
 impl Instrument {
@@ -35,15 +38,28 @@ impl Instrument {
             .collect::<Vec<_>>();
 
         self.states.push((execution_state, sizes));
+
+        let constraints = &cb.constraints;
+        let max_degree = degree::max(
+            constraints
+                .step
+                .iter()
+                .chain(&constraints.step_first)
+                .chain(&constraints.step_last)
+                .chain(&constraints.not_step_last)
+                .map(|(_, constraint)| constraint),
+        );
+        self.degrees.push((execution_state, max_degree));
     }
 
     /// Dissasembles the instrumentation data and returns a collection of
     /// `ExecStateReport`s. One for each EVM `ExecutionState`.
     pub fn analyze(&self) -> Vec<ExecStateReport> {
         let mut report_collection = vec![];
-        for (state, sizes) in &self.states {
+        for ((state, sizes), (_, max_degree)) in self.states.iter().zip(&self.degrees) {
             // Create a state report
             let mut report = ExecStateReport::from(state);
+            report.max_degree = *max_degree;
             // Compute max_height required for any kind of CellType for the current
             // `ExecutionState`.
             let top_height: usize = sizes.iter().map(|(_, (_, h, _))| *h).max().unwrap();
@@ -131,6 +147,9 @@ pub struct ExecStateReport {
     pub copy_table: StateReportRow,
     pub keccak_table: StateReportRow,
     pub exp_table: StateReportRow,
+    // The highest degree of the constraints of the state, before they are
+    // multiplied by the step selectors.
+    pub max_degree: usize,
 }
 
 impl From<ExecutionState> for ExecStateReport {
@@ -8,9 +8,11 @@ mod byte_size;
 mod cmp_words;
 mod comparison;
 mod constant_division;
+mod div_mod_words;
 mod is_equal;
 mod is_equal_word;
 mod is_zero;
+mod is_zero_batch;
 mod is_zero_word;
 mod lt;
 mod lt_word;
@@ -20,6 +22,7 @@ mod mul_add_words;
 mod mul_add_words512;
 mod mul_word_u64;
 mod pair_select;
+mod pow2;
 mod range_check;
 mod rlp;
 #[cfg(test)]
@@ -31,9 +34,11 @@ pub(crate) use byte_size::ByteSizeGadget;
 pub(crate) use cmp_words::CmpWordsGadget;
 pub(crate) use comparison::ComparisonGadget;
 pub(crate) use constant_division::ConstantDivisionGadget;
+pub(crate) use div_mod_words::DivModWordsGadget;
 pub(crate) use is_equal::IsEqualGadget;
 pub(crate) use is_equal_word::IsEqualWordGadget;
 pub(crate) use is_zero::IsZeroGadget;
+pub(crate) use is_zero_batch::IsZeroBatchGadget;
 pub(crate) use is_zero_word::IsZeroWordGadget;
 pub(crate) use lt::LtGadget;
 pub(crate) use lt_word::LtWordGadget;
@@ -43,6 +48,7 @@ pub(crate) use mul_add_words::MulAddWordsGadget;
 pub(crate) use mul_add_words512::MulAddWords512Gadget;
 pub(crate) use mul_word_u64::MulWordByU64Gadget;
 pub(crate) use pair_select::PairSelectGadget;
+pub(crate) use pow2::Pow2Gadget;
 pub(crate) use range_check::RangeCheckGadget;
 pub(crate) use rlp::ContractCreateGadget;
 
@@ -0,0 +1,196 @@
+use crate::{
+    evm_circuit::util::{
+        constraint_builder::{ConstrainBuilderCommon, EVMConstraintBuilder},
+        math_gadget::{IsZeroWordGadget, LtWordGadget, MulAddWordsGadget},
+        CachedRegion,
+    },
+    util::{
+        word::{Word, Word32Cell, WordExpr},
+        Expr,
+    },
+};
+use eth_types::{Field, Word as U256Word};
+use halo2_proofs::plonk::{Error, Expression};
+
+/// Constraints for the words quotient, divisor, remainder and dividend, when
+/// `enable` is 1:
+/// quotient * divisor + remainder = dividend (without overflow), and
+/// remainder < divisor, if divisor != 0
+///
+/// When the divisor is 0 the remainder is free, so the callers (DIV/MOD and
+/// SDIV/SMOD) are expected to select 0 as the result with `divisor_is_zero`.
+/// The MulAddWordsGadget is always constrained, so that the MulDivModGadget
+/// can share it with MUL by disabling only the division constraints.
+#[derive(Clone, Debug)]
+pub(crate) struct DivModWordsGadget<F> {
+    mul_add_words: MulAddWordsGadget<F>,
+    divisor_is_zero: IsZeroWordGadget<F, Word32Cell<F>>,
+    remainder_lt_divisor: LtWordGadget<F>,
+}
+
+impl<F: Field> DivModWordsGadget<F> {
+    pub(crate) fn construct(
+        cb: &mut EVMConstraintBuilder<F>,
+        words: [&Word32Cell<F>; 4],
+        enable: Expression<F>,
+    ) -> Self {
+        let (quotient, divisor, remainder, dividend) = (words[0], words[1], words[2], words[3]);
+        let mul_add_words =
+            MulAddWordsGadget::construct(cb, [quotient, divisor, remainder, dividend]);
+        let divisor_is_zero = IsZeroWordGadget::construct(cb, divisor);
+        let remainder_lt_divisor =
+            LtWordGadget::construct(cb, &remainder.to_word(), &divisor.to_word());
+
+        cb.condition(enable, |cb| {
+            cb.add_constraint(
+                "remainder < divisor when divisor != 0",
+                (1.expr() - remainder_lt_divisor.expr()) * (1.expr() - divisor_is_zero.expr()),
+            );
+            cb.require_zero(
+                "overflow == 0 for quotient * divisor + remainder",
+                mul_add_words.overflow(),
+            );
+        });
+
+        Self {
+            mul_add_words,
+            divisor_is_zero,
+            remainder_lt_divisor,
+        }
+    }
+
+    pub(crate) fn divisor_is_zero(&self) -> Expression<F> {
+        self.divisor_is_zero.expr()
+    }
+
+    pub(crate) fn assign(
+        &self,
+        region: &mut CachedRegion<'_, '_, F>,
+        offset: usize,
+        words: [U256Word; 4],
+    ) -> Result<(), Error> {
+        let (divisor, remainder) = (words[1], words[2]);
+        self.mul_add_words.assign(region, offset, words)?;
+        self.divisor_is_zero
+            .assign(region, offset, Word::from(divisor))?;
+        self.remainder_lt_divisor
+            .assign(region, offset, remainder, divisor)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{super::test_util::*, *};
+    use eth_types::{Word, U256, U512};
+    use halo2_proofs::{halo2curves::bn256::Fr, plonk::Error};
+
+    #[derive(Clone)]
+    /// DivModWordsGadgetTestContainer: require(q * d + r == n && (r < d || d == 0))
+    struct DivModWordsGadgetTestContainer<F> {
+        div_mod_gadget: DivModWordsGadget<F>,
+        words: [Word32Cell<F>; 4],
+    }
+
+    impl<F: Field> MathGadgetContainer<F> for DivModWordsGadgetTestContainer<F> {
+        fn configure_gadget_container(cb: &mut EVMConstraintBuilder<F>) -> Self {
+            let words = [(); 4].map(|_| cb.query_word32());
+            let div_mod_gadget = DivModWordsGadget::<F>::construct(
+                cb,
+                [&words[0], &words[1], &words[2], &words[3]],
+                1.expr(),
+            );
+            DivModWordsGadgetTestContainer {
+                div_mod_gadget,
+                words,
+            }
+        }
+
+        fn assign_gadget_container(
+            &self,
+            witnesses: &[Word],
+            region: &mut CachedRegion<'_, '_, F>,
+        ) -> Result<(), Error> {
+            let offset = 0;
+            let words: [Word; 4] = witnesses[..4].try_into().unwrap();
+            for (cell, word) in self.words.iter().zip(words) {
+                cell.assign_u256(region, offset, word)?;
+            }
+            self.div_mod_gadget.assign(region, offset, words)
+        }
+    }
+
+    #[test]
+    fn test_div_mod_expected() {
+        // 10 * 50 + 48 == 548
+        try_test!(
+            DivModWordsGadgetTestContainer<Fr>,
+            vec![
+                Word::from(10),
+                Word::from(50),
+                Word::from(48),
+                Word::from(548)
+            ],
+            true,
+        );
+        // 0 * 1024 + 1023 == 1023
+        try_test!(
+            DivModWordsGadgetTestContainer<Fr>,
+            vec![
+                Word::from(0),
+                Word::from(1024),
+                Word::from(1023),
+                Word::from(1023)
+            ],
+            true,
+        );
+        // the remainder is free when dividing by 0
+        try_test!(
+            DivModWordsGadgetTestContainer<Fr>,
+            vec![Word::from(0), Word::from(0), WORD_CELL_MAX, WORD_CELL_MAX],
+            true,
+        );
+        try_test!(
+            DivModWordsGadgetTestContainer<Fr>,
+            vec![WORD_LOW_MAX, Word::from(1), Word::from(0), WORD_LOW_MAX],
+            true,
+        );
+    }
+
+    #[test]
+    fn test_div_mod_unexpected() {
+        // remainder == divisor
+        try_test!(
+            DivModWordsGadgetTestContainer<Fr>,
+            vec![
+                Word::from(10),
+                Word::from(50),
+                Word::from(50),
+                Word::from(550)
+            ],
+            false,
+        );
+        // 9 * 50 + 98 == 548, but 98 > 50
+        try_test!(
+            DivModWordsGadgetTestContainer<Fr>,
+            vec![
+                Word::from(9),
+                Word::from(50),
+                Word::from(98),
+                Word::from(548)
+            ],
+            false,
+        );
+        // 2 == ((2^256 + 2) / 3) * 3 + 0 (mod 2^256) overflows
+        try_test!(
+            DivModWordsGadgetTestContainer<Fr>,
+            vec![
+                U256::try_from(U512([2, 0, 0, 0, 1, 0, 0, 0]) / U512::from(3)).unwrap(),
+                Word::from(3),
+                Word::from(0),
+                Word::from(2)
+            ],
+            false,
+        );
+    }
+}
@@ -0,0 +1,188 @@
+use crate::{
+    evm_circuit::util::{
+        constraint_builder::{ConstrainBuilderCommon, EVMConstraintBuilder},
+        CachedRegion, Cell, CellType,
+    },
+    util::{
+        word::{Word, WordExpr},
+        Expr,
+    },
+};
+use eth_types::Field;
+use halo2_proofs::plonk::{Error, Expression};
+
+/// Returns `1` when all the N `values` are `0`, and returns `0` otherwise,
+/// with a single inverse cell instead of one per value: the values are
+/// combined with the powers of the `zero_check` challenge, and their random
+/// linear combination is checked like in the IsZeroGadget.  The values must be
+/// in the first phase, so that they are fixed before the challenge is known.
+///
+/// The words are checked with `construct_word` and `construct_equal_word`,
+/// which give the result of each word with one inverse cell instead of the two
+/// of the IsZeroWordGadget and the IsEqualWordGadget.
+#[derive(Clone, Debug)]
+pub struct IsZeroBatchGadget<F, const N: usize> {
+    inverse: Cell<F>,
+    is_zero: Expression<F>,
+}
+
+impl<F: Field, const N: usize> IsZeroBatchGadget<F, N> {
+    pub(crate) fn construct(cb: &mut EVMConstraintBuilder<F>, values: [Expression<F>; N]) -> Self {
+        assert!(
+            values.iter().all(|value| CellType::expr_phase(value) == 0),
+            "values of IsZeroBatchGadget must be in the first phase"
+        );
+        let challenge = cb.challenges().zero_check();
+        let rlc = values.iter().rev().fold(0.expr(), |acc, value| {
+            acc * challenge.clone() + value.clone()
+        });
+        let inverse = cb.query_cell_with_type(CellType::storage_for_expr(&rlc));
+
+        let is_zero = 1.expr() - (rlc.clone() * inverse.expr());
+        // when `rlc != 0` check `inverse = rlc.invert()`: rlc * (1 - rlc *
+        // inverse)
+        cb.add_constraint("rlc ⋅ (1 - rlc ⋅ rlc_inv)", rlc * is_zero.clone());
+        // when `rlc == 0` check `inverse = 0`: `inverse ⋅ (1 - rlc * inverse)`
+        cb.add_constraint(
+            "rlc_inv ⋅ (1 - rlc ⋅ rlc_inv)",
+            inverse.expr() * is_zero.clone(),
+        );
+
+        Self { inverse, is_zero }
+    }
+
+    pub(crate) fn expr(&self) -> Expression<F> {
+        self.is_zero.clone()
+    }
+
+    pub(crate) fn assign(
+        &self,
+        region: &mut CachedRegion<'_, '_, F>,
+        offset: usize,
+        values: [F; N],
+    ) -> Result<F, Error> {
+        let rlc = region.challenges().zero_check().map(|challenge| {
+            values
+                .iter()
+                .rev()
+                .fold(F::ZERO, |acc, value| acc * challenge + value)
+        });
+        self.inverse.assign(
+            region,
+            offset,
+            rlc.map(|rlc| rlc.invert().unwrap_or(F::ZERO)),
+        )?;
+        Ok(if values.iter().all(|value| value.is_zero().into()) {
+            F::ONE
+        } else {
+            F::ZERO
+        })
+    }
+}
+
+impl<F: Field> IsZeroBatchGadget<F, 2> {
+    /// Returns `1` when `word == 0`
+    pub(crate) fn construct_word<T: WordExpr<F>>(
+        cb: &mut EVMConstraintBuilder<F>,
+        word: &T,
+    ) -> Self {
+        let (lo, hi) = word.to_word().to_lo_hi();
+        Self::construct(cb, [lo, hi])
+    }
+
+    /// Returns `1` when `lhs == rhs`
+    pub(crate) fn construct_equal_word<T1: WordExpr<F>, T2: WordExpr<F>>(
+        cb: &mut EVMConstraintBuilder<F>,
+        lhs: &T1,
+        rhs: &T2,
+    ) -> Self {
+        let (lhs_lo, lhs_hi) = lhs.to_word().to_lo_hi();
+        let (rhs_lo, rhs_hi) = rhs.to_word().to_lo_hi();
+        Self::construct(cb, [lhs_lo - rhs_lo, lhs_hi - rhs_hi])
+    }
+
+    pub(crate) fn assign_word(
+        &self,
+        region: &mut CachedRegion<'_, '_, F>,
+        offset: usize,
+        word: eth_types::Word,
+    ) -> Result<F, Error> {
+        let (lo, hi) = Word::<F>::from(word).to_lo_hi();
+        self.assign(region, offset, [lo, hi])
+    }
+
+    pub(crate) fn assign_equal_word(
+        &self,
+        region: &mut CachedRegion<'_, '_, F>,
+        offset: usize,
+        lhs: eth_types::Word,
+        rhs: eth_types::Word,
+    ) -> Result<F, Error> {
+        let (lhs_lo, lhs_hi) = Word::<F>::from(lhs).to_lo_hi();
+        let (rhs_lo, rhs_hi) = Word::<F>::from(rhs).to_lo_hi();
+        self.assign(region, offset, [lhs_lo - rhs_lo, lhs_hi - rhs_hi])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{super::test_util::*, *};
+    use eth_types::{ToScalar, Word};
+    use halo2_proofs::{circuit::Value, halo2curves::bn256::Fr, plonk::Error};
+
+    #[derive(Clone)]
+    /// IsZeroBatchGadgetTestContainer: require(a == 0 && b == 0 && c == 0)
+    struct IsZeroBatchGadgetTestContainer<F> {
+        z_gadget: IsZeroBatchGadget<F, 3>,
+        values: [Cell<F>; 3],
+    }
+
+    impl<F: Field> MathGadgetContainer<F> for IsZeroBatchGadgetTestContainer<F> {
+        fn configure_gadget_container(cb: &mut EVMConstraintBuilder<F>) -> Self {
+            let values = [(); 3].map(|_| cb.query_cell());
+            let z_gadget =
+                IsZeroBatchGadget::<F, 3>::construct(cb, values.clone().map(|value| value.expr()));
+            cb.require_equal("Inputs are zero", z_gadget.expr(), 1.expr());
+            IsZeroBatchGadgetTestContainer { z_gadget, values }
+        }
+
+        fn assign_gadget_container(
+            &self,
+            witnesses: &[Word],
+            region: &mut CachedRegion<'_, '_, F>,
+        ) -> Result<(), Error> {
+            let values = [0, 1, 2].map(|i| witnesses[i].to_scalar().unwrap());
+            let offset = 0;
+
+            for (cell, value) in self.values.iter().zip(values) {
+                cell.assign(region, offset, Value::known(value))?;
+            }
+            self.z_gadget.assign(region, offset, values)?;
+
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_all_zero() {
+        try_test!(
+            IsZeroBatchGadgetTestContainer<Fr>,
+            vec![Word::from(0), Word::from(0), Word::from(0)],
+            true,
+        );
+    }
+
+    #[test]
+    fn test_one_not_zero() {
+        try_test!(
+            IsZeroBatchGadgetTestContainer<Fr>,
+            vec![Word::from(0), Word::from(0), Word::from(1)],
+            false,
+        );
+        try_test!(
+            IsZeroBatchGadgetTestContainer<Fr>,
+            vec![Word::from(10000), Word::from(0), Word::from(0)],
+            false,
+        );
+    }
+}
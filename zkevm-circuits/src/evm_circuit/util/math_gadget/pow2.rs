@@ -0,0 +1,172 @@
+use crate::{
+    evm_circuit::{
+        table::{FixedTableTag, Lookup},
+        util::{
+            constraint_builder::{ConstrainBuilderCommon, EVMConstraintBuilder},
+            math_gadget::IsZeroGadget,
+            sum, CachedRegion,
+        },
+    },
+    util::{
+        word::{Word32Cell, WordExpr},
+        Expr,
+    },
+};
+use eth_types::{Field, ToLittleEndian, Word};
+use halo2_proofs::plonk::{Error, Expression};
+
+/// Decomposes a 256-bit shift amount (or byte index) into its least
+/// significant byte and whether it's less than 256, and optionally constrains
+/// a word to be `2^amount`.
+///
+/// Only the least significant byte of the amount matters when it's less than
+/// 256; otherwise every bit is shifted out, and `2^amount` is taken as `0`
+/// (`pow % 2^256`).
+#[derive(Clone, Debug)]
+pub(crate) struct Pow2Gadget<F> {
+    amount: Word32Cell<F>,
+    /// Check if the bytes of `amount` other than the least significant one are
+    /// all zero
+    lt_256: IsZeroGadget<F>,
+}
+
+impl<F: Field> Pow2Gadget<F> {
+    pub(crate) fn construct(cb: &mut EVMConstraintBuilder<F>) -> Self {
+        let amount = cb.query_word32();
+        let lt_256 = IsZeroGadget::construct(cb, sum::expr(&amount.limbs[1..32]));
+
+        Self { amount, lt_256 }
+    }
+
+    /// Constrain `pow == 2^amount` when `amount < 256`, and `pow == 0`
+    /// otherwise.
+    pub(crate) fn constrain_pow(&self, cb: &mut EVMConstraintBuilder<F>, pow: &Word32Cell<F>) {
+        let (pow_lo, pow_hi) = pow.to_word().to_lo_hi();
+        cb.condition(self.lt_256(), |cb| {
+            cb.add_lookup(
+                "Pow2 lookup of amount, pow_lo and pow_hi",
+                Lookup::Fixed {
+                    tag: FixedTableTag::Pow2.expr(),
+                    values: [self.lsb(), pow_lo.expr(), pow_hi.expr()],
+                },
+            );
+        });
+        cb.require_zero_word(
+            "pow == 0 when amount >= 256",
+            pow.to_word().mul_selector(1.expr() - self.lt_256()),
+        );
+    }
+
+    /// The amount, as popped from the stack
+    pub(crate) fn amount(&self) -> &Word32Cell<F> {
+        &self.amount
+    }
+
+    /// The least significant byte of the amount
+    pub(crate) fn lsb(&self) -> Expression<F> {
+        self.amount.limbs[0].expr()
+    }
+
+    /// `1` when `amount < 256`, `0` otherwise
+    pub(crate) fn lt_256(&self) -> Expression<F> {
+        self.lt_256.expr()
+    }
+
+    /// Assign the amount, and return `2^amount % 2^256`.
+    pub(crate) fn assign(
+        &self,
+        region: &mut CachedRegion<'_, '_, F>,
+        offset: usize,
+        amount: Word,
+    ) -> Result<Word, Error> {
+        self.amount.assign_u256(region, offset, amount)?;
+        self.lt_256
+            .assign(region, offset, sum::value(&amount.to_le_bytes()[1..32]))?;
+
+        Ok(if amount < Word::from(256) {
+            Word::one() << amount.low_u64()
+        } else {
+            Word::zero()
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{super::test_util::*, *};
+    use halo2_proofs::halo2curves::bn256::Fr;
+
+    #[derive(Clone)]
+    /// Pow2GadgetTestContainer: require(pow == 2^amount % 2^256)
+    struct Pow2GadgetTestContainer<F> {
+        pow2_gadget: Pow2Gadget<F>,
+        pow: Word32Cell<F>,
+    }
+
+    impl<F: Field> MathGadgetContainer<F> for Pow2GadgetTestContainer<F> {
+        fn configure_gadget_container(cb: &mut EVMConstraintBuilder<F>) -> Self {
+            let pow2_gadget = Pow2Gadget::construct(cb);
+            let pow = cb.query_word32();
+            pow2_gadget.constrain_pow(cb, &pow);
+            cb.require_boolean("lt_256 is boolean", pow2_gadget.lt_256());
+
+            Pow2GadgetTestContainer { pow2_gadget, pow }
+        }
+
+        fn assign_gadget_container(
+            &self,
+            witnesses: &[Word],
+            region: &mut CachedRegion<'_, '_, F>,
+        ) -> Result<(), Error> {
+            let offset = 0;
+            self.pow2_gadget.assign(region, offset, witnesses[0])?;
+            self.pow.assign_u256(region, offset, witnesses[1])?;
+
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_pow2_lt_256() {
+        for amount in [0u64, 1, 8, 127, 128, 129, 255] {
+            try_test!(
+                Pow2GadgetTestContainer<Fr>,
+                vec![Word::from(amount), Word::one() << amount],
+                true,
+            );
+        }
+    }
+
+    #[test]
+    fn test_pow2_ge_256() {
+        try_test!(
+            Pow2GadgetTestContainer<Fr>,
+            vec![Word::from(256), Word::zero()],
+            true,
+        );
+        try_test!(
+            Pow2GadgetTestContainer<Fr>,
+            vec![Word::MAX, Word::zero()],
+            true,
+        );
+        try_test!(
+            Pow2GadgetTestContainer<Fr>,
+            vec![Word::from(256 + 8), Word::from(256)],
+            false,
+        );
+    }
+
+    #[test]
+    fn test_pow2_wrong_pow() {
+        try_test!(
+            Pow2GadgetTestContainer<Fr>,
+            vec![Word::from(8), Word::from(128)],
+            false,
+        );
+        try_test!(
+            Pow2GadgetTestContainer<Fr>,
+            vec![Word::from(8), Word::zero()],
+            false,
+        );
+    }
+}
@@ -11,7 +11,7 @@ use halo2_proofs::{
 
 use crate::{
     evm_circuit::{
-        param::{N_BYTES_U64, N_BYTES_WORD},
+        param::N_BYTES_U64,
         util::{
             constraint_builder::{ConstrainBuilderCommon, EVMConstraintBuilder},
             AccountAddress, CachedRegion, Cell, RandomLinearCombination,
@@ -276,15 +276,7 @@ impl<F: Field, const IS_CREATE2: bool> ContractCreateGadget<F, IS_CREATE2> {
 
     /// Code hash keccak RLC.
     pub(crate) fn code_hash_keccak_rlc(&self, cb: &EVMConstraintBuilder<F>) -> Expression<F> {
-        cb.keccak_rlc::<N_BYTES_WORD>(
-            self.code_hash
-                .limbs
-                .iter()
-                .map(Expr::expr)
-                .collect::<Vec<_>>()
-                .try_into()
-                .unwrap(),
-        )
+        self.code_hash.rlc(cb.challenges().keccak_input())
     }
 
     pub(crate) fn salt(&self) -> word::Word<Expression<F>> {
@@ -293,15 +285,7 @@ impl<F: Field, const IS_CREATE2: bool> ContractCreateGadget<F, IS_CREATE2> {
 
     /// Salt keccak RLC.
     pub(crate) fn salt_keccak_rlc(&self, cb: &EVMConstraintBuilder<F>) -> Expression<F> {
-        cb.keccak_rlc::<N_BYTES_WORD>(
-            self.salt
-                .limbs
-                .iter()
-                .map(Expr::expr)
-                .collect::<Vec<_>>()
-                .try_into()
-                .unwrap(),
-        )
+        self.salt.rlc(cb.challenges().keccak_input())
     }
 
     /// Caller address' RLC value.
@@ -11,7 +11,7 @@ use crate::{
             constraint_builder::EVMConstraintBuilder, rlc, CachedRegion, StoredExpression,
             LOOKUP_CONFIG,
         },
-        Advice, Column, Fixed,
+        Advice, Column, FeatureConfig, Fixed,
     },
     table::LookupTable,
     util::{cell_manager::CellType, Challenges},
@@ -116,11 +116,13 @@ impl<F: Field, G: MathGadgetContainer<F>> Circuit<F> for UnitTestMathGadgetBaseC
 
         let step_curr = Step::new(meta, advices, 0);
         let step_next = Step::new(meta, advices, MAX_STEP_HEIGHT);
+        let feature_config = FeatureConfig::default();
         let mut cb = EVMConstraintBuilder::new(
             meta,
             step_curr.clone(),
             step_next,
             &challenges_exprs,
+            &feature_config,
             ExecutionState::STOP,
         );
         let math_gadget_container = G::configure_gadget_container(&mut cb);
@@ -201,8 +203,8 @@ impl<F: Field, G: MathGadgetContainer<F>> Circuit<F> for UnitTestMathGadgetBaseC
             },
         )?;
 
-        // assign fixed range tables only as they are the only tables referred by a
-        // specfic math gadget -- ConstantDivisionGadget.
+        // assign fixed range and pow2 tables only as they are the only tables
+        // referred by specfic math gadgets -- ConstantDivisionGadget and Pow2Gadget.
         layouter.assign_region(
             || "fixed table",
             |mut region| {
@@ -220,6 +222,7 @@ impl<F: Field, G: MathGadgetContainer<F>> Circuit<F> for UnitTestMathGadgetBaseC
                                         | FixedTableTag::Range256
                                         | FixedTableTag::Range512
                                         | FixedTableTag::Range1024
+                                        | FixedTableTag::Pow2
                                 )
                             })
                             .flat_map(|tag| tag.build()),
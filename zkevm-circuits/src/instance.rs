@@ -1,6 +1,6 @@
 //! The instance definition.
 
-use eth_types::{geth_types::BlockConstants, BigEndianHash, Field, Keccak};
+use eth_types::{geth_types::BlockConstants, keccak256, BigEndianHash, Field, Keccak};
 use std::{iter, ops::Deref};
 
 use eth_types::{geth_types::Transaction, Address, ToBigEndian, Word, H256};
@@ -55,6 +55,22 @@ pub struct TxValues {
     pub call_data_gas_cost: u64,
     /// tx_sign_hash
     pub tx_sign_hash: [u8; 32],
+    /// call_data_offset
+    pub call_data_offset: u64,
+    /// tx_hash (little endian)
+    pub tx_hash: [u8; 32],
+}
+
+impl TxValues {
+    /// Values of a padding tx, whose tx hash is the hash of the empty input
+    pub fn padding() -> Self {
+        let mut tx_hash = keccak256(&[]);
+        tx_hash.reverse();
+        Self {
+            tx_hash,
+            ..Default::default()
+        }
+    }
 }
 
 /// Extra values (not contained in block or tx tables)
@@ -68,6 +84,11 @@ pub struct ExtraValues {
     pub prev_state_root: H256,
 }
 
+/// Version of the layout of the public inputs, the first byte of the PI hash
+/// preimage.  It must be bumped with any change of the preimage or of the
+/// circuits, so that a proof can't be checked by a verifier of another version.
+pub const PI_VERSION: u8 = 1;
+
 /// PublicData contains all the values that the PiCircuit recieves as input
 #[derive(Debug, Clone)]
 pub struct PublicData {
@@ -132,10 +153,13 @@ impl PublicData {
             .try_into()
             .expect("Error converting chain_id to u64");
         let mut tx_vals = vec![];
+        let mut call_data_offset = 0;
         for tx in &self.transactions {
             let sign_data_res = tx.sign_data(chain_id);
             let msg_hash_le =
                 sign_data_res.map_or_else(|_| [0u8; 32], |sign_data| sign_data.msg_hash.to_bytes());
+            let mut tx_hash_le = tx.hash(chain_id).to_fixed_bytes();
+            tx_hash_le.reverse();
             tx_vals.push(TxValues {
                 nonce: tx.nonce.low_u64(),
                 gas_price: tx.gas_price,
@@ -153,7 +177,10 @@ impl PublicData {
                     }
                 }),
                 tx_sign_hash: msg_hash_le,
+                call_data_offset,
+                tx_hash: tx_hash_le,
             });
+            call_data_offset += tx.call_data.0.len() as u64;
         }
         tx_vals
     }
@@ -172,7 +199,8 @@ impl PublicData {
         // Assign block table
         let block_values = self.get_block_table_values();
         let result = iter::empty()
-            .chain(0u8.to_be_bytes()) // zero byte
+            .chain(PI_VERSION.to_be_bytes()) // version
+            .chain(block_values.chain_id.to_be_bytes()) // chain_id domain separator
             .chain(block_values.coinbase.to_fixed_bytes()) // coinbase
             .chain(block_values.gas_limit.to_be_bytes()) // gas_limit
             .chain(block_values.number.to_be_bytes()) // number
@@ -213,6 +241,8 @@ impl PublicData {
                 tx.call_data_len.to_be_bytes().to_vec(),             // call_data_len
                 tx.call_data_gas_cost.to_be_bytes().to_vec(),        // call_data_gas_cost
                 tx.tx_sign_hash.iter().rev().copied().collect_vec(), // tx sign hash
+                tx.call_data_offset.to_be_bytes().to_vec(),          // call_data_offset
+                tx.tx_hash.iter().rev().copied().collect_vec(),      // tx hash
             ]
             .iter()
             .flat_map(move |value_bytes| tx_field_byte_fn(tx_id, index, value_bytes))
@@ -220,7 +250,7 @@ impl PublicData {
         };
 
         let txs_values = self.get_tx_table_values();
-        let tx_values_default = TxValues::default();
+        let tx_values_default = TxValues::padding();
 
         // all tx bytes including tx padding
         let all_tx_bytes = iter::empty()
@@ -280,6 +310,7 @@ pub fn public_data_convert<F: Field>(block: &Block<F>) -> PublicData {
             difficulty: block.context.difficulty,
             gas_limit: block.context.gas_limit.into(),
             base_fee: block.context.base_fee,
+            mix_hash: block.eth_block.mix_hash.unwrap_or_default(),
         },
     }
 }
@@ -17,7 +17,7 @@ use std::marker::PhantomData;
 pub use KeccakCircuitConfig as KeccakConfig;
 
 use self::{
-    keccak_packed_multi::{keccak_unusable_rows, multi_keccak, KeccakRow},
+    keccak_packed_multi::{empty_hash_offset, keccak_unusable_rows, multi_keccak, KeccakRow},
     param::*,
     table::*,
     util::*,
@@ -37,7 +37,7 @@ use crate::{
     },
     witness,
 };
-use eth_types::Field;
+use eth_types::{keccak256, Field};
 use gadgets::util::{and, not, select, sum, Expr};
 use halo2_proofs::{
     circuit::{Layouter, Region, Value},
@@ -58,6 +58,7 @@ pub struct KeccakCircuitConfig<F> {
     q_round_last: Column<Fixed>,
     q_padding: Column<Fixed>,
     q_padding_last: Column<Fixed>,
+    q_empty_hash: Column<Fixed>,
     /// The columns for other circuits to lookup Keccak hash results
     pub keccak_table: KeccakTable,
     cell_manager: CellManager<CMFixedHeightStrategy>,
@@ -100,6 +101,7 @@ impl<F: Field> SubCircuitConfig<F> for KeccakCircuitConfig<F> {
         let q_round_last = meta.fixed_column();
         let q_padding = meta.fixed_column();
         let q_padding_last = meta.fixed_column();
+        let q_empty_hash = meta.fixed_column();
         let round_cst = meta.fixed_column();
 
         let is_final = keccak_table.is_enabled;
@@ -607,6 +609,35 @@ impl<F: Field> SubCircuitConfig<F> for KeccakCircuitConfig<F> {
             cb.gate(meta.query_fixed(q_first, Rotation::cur()))
         });
 
+        // The empty input hash is always at the same position
+        meta.create_gate("empty input hash", |meta| {
+            let mut cb = BaseConstraintBuilder::new(MAX_DEGREE);
+            cb.require_equal(
+                "is_final needs to be enabled for the empty input hash",
+                meta.query_advice(is_final, Rotation::cur()),
+                1.expr(),
+            );
+            cb.require_zero(
+                "length == 0 for the empty input hash",
+                meta.query_advice(length, Rotation::cur()),
+            );
+            cb.require_zero(
+                "data_rlc == 0 for the empty input hash",
+                meta.query_advice(data_rlc, Rotation::cur()),
+            );
+            let empty_hash_le: [u8; 32] = {
+                let mut bytes = keccak256(&[]);
+                bytes.reverse();
+                bytes
+            };
+            cb.require_equal_word(
+                "output == keccak([]) for the empty input hash",
+                word::Word32::new(empty_hash_le.map(|byte| byte.expr())).to_word(),
+                hash_word.map(|col| meta.query_advice(col, Rotation::cur())),
+            );
+            cb.gate(meta.query_fixed(q_empty_hash, Rotation::cur()))
+        });
+
         // Enforce logic for when this block is the last block for a hash
         let last_is_padding_in_block = is_paddings.last().unwrap().at_offset(
             meta,
@@ -864,6 +895,7 @@ impl<F: Field> SubCircuitConfig<F> for KeccakCircuitConfig<F> {
             q_round_last,
             q_padding,
             q_padding_last,
+            q_empty_hash,
             keccak_table,
             cell_manager,
             round_cst,
@@ -919,6 +951,11 @@ impl<F: Field> KeccakCircuitConfig<F> {
                 self.q_padding_last,
                 F::from(row.q_padding_last as u64),
             ),
+            (
+                "q_empty_hash",
+                self.q_empty_hash,
+                F::from((offset == empty_hash_offset()) as u64),
+            ),
         ] {
             region.assign_fixed(
                 || format!("assign {} {}", name, offset),
@@ -987,6 +1024,7 @@ impl<F: Field> KeccakCircuitConfig<F> {
         region.name_column(|| "KECCAK_q_absorb", self.q_absorb);
         region.name_column(|| "KECCAK_q_round_last", self.q_round_last);
         region.name_column(|| "KECCAK_q_padding_last", self.q_padding_last);
+        region.name_column(|| "KECCAK_q_empty_hash", self.q_empty_hash);
     }
 }
 
@@ -1019,11 +1057,13 @@ impl<F: Field> SubCircuit<F> for KeccakCircuit<F> {
     fn min_num_rows_block(block: &witness::Block<F>) -> (usize, usize) {
         let rows_per_chunk = (NUM_ROUNDS + 1) * get_num_rows_per_round();
         (
-            block
-                .keccak_inputs
-                .iter()
-                .map(|bytes| (bytes.len() as f64 / 136.0).ceil() as usize * rows_per_chunk)
-                .sum(),
+            // One extra chunk for the empty input hash
+            rows_per_chunk
+                + block
+                    .keccak_inputs
+                    .iter()
+                    .map(|bytes| (bytes.len() as f64 / 136.0).ceil() as usize * rows_per_chunk)
+                    .sum::<usize>(),
             block.circuits_params.max_keccak_rows,
         )
     }
@@ -1051,7 +1091,8 @@ impl<F: Field> KeccakCircuit<F> {
         }
     }
 
-    /// The number of keccak_f's that can be done in this circuit
+    /// The number of keccak_f's that can be done in this circuit, including
+    /// the one of the empty input hash
     pub fn capacity(&self) -> Option<usize> {
         if self.num_rows > 0 {
             // Subtract two for unusable rows
@@ -27,6 +27,12 @@ pub(crate) fn keccak_unusable_rows() -> usize {
     UNUSABLE_ROWS_BY_KECCAK_ROWS[get_num_rows_per_round() - NUM_BYTES_PER_WORD - 1]
 }
 
+/// The offset of the `is_final` row of the empty input hash, which always
+/// comes first, right after the dummy rows.
+pub(crate) fn empty_hash_offset() -> usize {
+    (1 + NUM_ROUNDS) * get_num_rows_per_round()
+}
+
 pub(crate) fn get_num_bits_per_absorb_lookup() -> usize {
     get_num_bits_per_lookup(ABSORB_LOOKUP_RANGE)
 }
@@ -892,6 +898,9 @@ pub(crate) fn multi_keccak<F: Field>(
             cell_values: Vec::new(),
         });
     }
+    // The empty input hash is always present, so that it can be looked up even
+    // when no one requested it.
+    keccak(&mut rows, &[], challenges);
     // Actual keccaks
     for bytes in bytes {
         keccak(&mut rows, bytes, challenges);
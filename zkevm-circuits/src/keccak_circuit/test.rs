@@ -1,6 +1,6 @@
 use super::*;
 use crate::util::unusable_rows;
-use eth_types::Field;
+use eth_types::{Field, U256};
 use halo2_proofs::{dev::MockProver, halo2curves::bn256::Fr};
 use log::error;
 use std::iter::zip;
@@ -50,6 +50,24 @@ fn packed_multi_keccak_simple() {
     verify::<Fr>(k, inputs, true);
 }
 
+#[test]
+fn packed_multi_keccak_empty_input() {
+    let k = 14;
+    let inputs = vec![(0u8..10).collect::<Vec<_>>()];
+    // The empty input hash is always provided, even when it's not requested
+    verify::<Fr>(k, inputs.clone(), true);
+
+    let circuit = KeccakCircuit::<Fr>::new(2usize.pow(k), inputs);
+    let challenge = Value::known(Fr::from(0x100));
+    let rows = circuit.generate_witness(Challenges::mock(challenge, challenge, challenge));
+    let row = &rows[empty_hash_offset()];
+    assert!(row.is_final);
+    assert_eq!(row.length, 0);
+    let empty_hash = word::Word::<Fr>::from(U256::from_big_endian(&keccak256(&[])));
+    row.hash.lo().assert_if_known(|lo| *lo == empty_hash.lo());
+    row.hash.hi().assert_if_known(|hi| *hi == empty_hash.hi());
+}
+
 #[test]
 fn variadic_size_check() {
     let k = 14;
@@ -10,6 +10,7 @@ use halo2_proofs::{
     poly::Rotation,
 };
 
+use log::error;
 use std::{convert::TryInto, env::var, marker::PhantomData};
 
 mod account_leaf;
@@ -29,11 +30,7 @@ use self::{
     helpers::{key_memory, RLPItemView},
     param::RLP_UNIT_NUM_BYTES,
     rlp_gadgets::decode_rlp,
-    witness_row::{
-        AccountRowType, ExtensionBranchRowType, Node, StartRowType, StorageRowType,
-        NODE_RLP_TYPES_ACCOUNT, NODE_RLP_TYPES_BRANCH, NODE_RLP_TYPES_START,
-        NODE_RLP_TYPES_STORAGE,
-    },
+    witness_row::{AccountRowType, ExtensionBranchRowType, Node, StartRowType, StorageRowType},
 };
 use crate::{
     assign, assignf, circuit,
@@ -54,7 +51,7 @@ use crate::{
     util::Challenges,
 };
 use extension_branch::ExtensionBranchConfig;
-use param::HASH_WIDTH;
+use param::{HASH_WIDTH, MAX_MULT_LENGTH};
 
 #[derive(Debug, Eq, PartialEq)]
 pub(crate) enum MPTRegion {
@@ -141,7 +138,7 @@ pub struct MPTContext<F> {
 /// RLP item type
 #[derive(Copy, Clone, Debug, PartialEq)]
 pub enum RlpItemType {
-    /// Node (string with len == 0 or 32, OR list with len <= 31)
+    /// Node (string with len == 0 or 32, OR list with len <= 30)
     Node,
     /// Value (string with len <= 32)
     Value,
@@ -202,6 +199,10 @@ pub enum FixedTableTag {
 }
 impl_expr!(FixedTableTag);
 
+/// Witness state shared between the nodes of a proof.
+/// All values handed from one node to the next go through the memory banks
+/// using the typed `KeyData`, `ParentData` and `MainData` store/load helpers,
+/// so no untyped accumulators can go stale between nodes.
 #[derive(Default)]
 pub(crate) struct MPTState<F> {
     pub(crate) memory: Memory<F, MptCellType>,
@@ -223,6 +224,11 @@ impl<F: Field> MPTConfig<F> {
         keccak_table: KeccakTable,
         params: MPTCircuitParams,
     ) -> Self {
+        assert!(
+            params.max_mult_length <= MAX_MULT_LENGTH,
+            "the RLP items are at most {} bytes long after their RLP byte",
+            MAX_MULT_LENGTH
+        );
         let q_enable = meta.fixed_column();
         let q_first = meta.fixed_column();
         let q_last = meta.fixed_column();
@@ -353,6 +359,9 @@ impl<F: Field> MPTConfig<F> {
                             state_machine.step_constraints(meta, &mut cb, AccountRowType::Count as usize);
                             cb.base.push_region(MPTRegion::Account as usize);
                             state_machine.account_config = AccountLeafConfig::configure(meta, &mut cb, ctx.clone());
+                            // Only storage proofs continue with the storage trie, all other proofs
+                            // skip the storage proof rows and directly start the next proof.
+                            require!(a!(state_machine.is_start, AccountRowType::Count) => not!(state_machine.account_config.is_storage_proof()));
                             ctx.memory.build_constraints(&mut cb.base, f!(q_first));
                             cb.base.pop_region();
                         },
@@ -471,17 +480,10 @@ impl<F: Field> MPTConfig<F> {
                         keccak_r,
                     );
 
-                    let item_types = if node.start.is_some() {
-                        NODE_RLP_TYPES_START.to_vec()
-                    } else if node.extension_branch.is_some() {
-                        NODE_RLP_TYPES_BRANCH.to_vec()
-                    } else if node.account.is_some() {
-                        NODE_RLP_TYPES_ACCOUNT.to_vec()
-                    } else if node.storage.is_some() {
-                        NODE_RLP_TYPES_STORAGE.to_vec()
-                    } else {
-                        unreachable!()
-                    };
+                    let item_types = node.rlp_item_types().map_err(|e| {
+                        error!("invalid MPT witness node at offset {}: {}", offset, e);
+                        Error::Synthesis
+                    })?;
 
                     // Assign bytes
                     let mut rlp_values = Vec::new();
@@ -561,8 +563,9 @@ impl<F: Field> MPTConfig<F> {
                 }
                 height = offset;
 
-                // Make sure the circuit is high enough for the mult table
-                while height < (2 * HASH_WIDTH + 1) {
+                // Make sure the circuit is high enough for the mult table, which has a row
+                // for every length up to max_mult_length
+                while height < (2 * HASH_WIDTH).max(self.params.max_mult_length) + 1 {
                     height += 1;
                 }
 
@@ -682,6 +685,14 @@ impl<F: Field> MPTConfig<F> {
         challenges: &Challenges<Value<F>>,
         height: usize,
     ) -> Result<(), Error> {
+        if height < self.params.max_mult_length {
+            error!(
+                "mult table of {} rows too short for lengths up to {}",
+                height + 1,
+                self.params.max_mult_length
+            );
+            return Err(Error::Synthesis);
+        }
         layouter.assign_region(
             || "mult table",
             |mut region| {
@@ -713,15 +724,42 @@ pub struct MPTCircuit<F: Field> {
     /// Can be used to test artificially created tests with keys without known their known
     /// preimage. ONLY ENABLE FOR TESTS!
     pub disable_preimage_check: bool,
+    /// Maximum length of the RLP items after their RLP byte, i.e. of the
+    /// multipliers in the mult table.  At most `MAX_MULT_LENGTH`.
+    pub max_mult_length: usize,
     /// Marker
     pub _marker: PhantomData<F>,
 }
 
+impl<F: Field> Default for MPTCircuit<F> {
+    fn default() -> Self {
+        Self {
+            nodes: Vec::new(),
+            keccak_data: Vec::new(),
+            degree: 0,
+            disable_preimage_check: false,
+            max_mult_length: MAX_MULT_LENGTH,
+            _marker: PhantomData,
+        }
+    }
+}
+
 /// MPT Circuit configuration parameters
-#[derive(Copy, Clone, Debug, Default)]
+#[derive(Copy, Clone, Debug)]
 pub struct MPTCircuitParams {
     degree: usize,
     disable_preimage_check: bool,
+    max_mult_length: usize,
+}
+
+impl Default for MPTCircuitParams {
+    fn default() -> Self {
+        Self {
+            degree: 0,
+            disable_preimage_check: false,
+            max_mult_length: MAX_MULT_LENGTH,
+        }
+    }
 }
 
 impl MPTCircuitParams {
@@ -747,6 +785,7 @@ impl<F: Field> Circuit<F> for MPTCircuit<F> {
         MPTCircuitParams {
             degree: self.degree,
             disable_preimage_check: self.disable_preimage_check,
+            max_mult_length: self.max_mult_length,
         }
     }
 
@@ -782,6 +821,7 @@ impl<F: Field> Circuit<F> for MPTCircuit<F> {
 }
 
 /// Loads an MPT proof from disk
+#[cfg(not(target_arch = "wasm32"))]
 pub fn load_proof(path: &str) -> Vec<Node> {
     let file = std::fs::File::open(path);
     let reader = std::io::BufReader::new(file.unwrap());
@@ -805,10 +845,87 @@ pub fn load_proof(path: &str) -> Vec<Node> {
 
 #[cfg(test)]
 mod tests {
-    use super::*;
-    use halo2_proofs::{dev::MockProver, halo2curves::bn256::Fr};
+    use super::{rlp_gadgets::RLPListWitness, witness_row::WitnessError, *};
+    use eth_types::keccak256;
+    use halo2_proofs::{
+        dev::{MockProver, VerifyFailure},
+        halo2curves::bn256::Fr,
+    };
     use std::fs;
 
+    fn verify(nodes: Vec<Node>) -> Result<(), Vec<VerifyFailure>> {
+        let num_rows: usize = nodes.iter().map(|node| node.values.len()).sum();
+
+        let mut keccak_data = vec![];
+        for node in nodes.iter() {
+            for k in node.keccak_data.iter() {
+                keccak_data.push(k.clone());
+            }
+        }
+
+        let disable_preimage_check = nodes[0].start.clone().unwrap().disable_preimage_check;
+        let degree = 14;
+        let circuit = MPTCircuit::<Fr> {
+            nodes,
+            keccak_data,
+            degree,
+            disable_preimage_check,
+            max_mult_length: MAX_MULT_LENGTH,
+            _marker: PhantomData,
+        };
+
+        let prover = MockProver::<Fr>::run(degree as u32, &circuit, vec![]).unwrap();
+        prover.verify_at_rows(0..num_rows, 0..num_rows)
+    }
+
+    /// Number of bytes of the RLP item, a short string or list, at the start of
+    /// `bytes`
+    fn rlp_item_num_bytes(bytes: &[u8]) -> usize {
+        match bytes[0] {
+            0..=0x7f => 1,
+            0x80..=0xb7 => 1 + (bytes[0] - 0x80) as usize,
+            0xc0..=0xf7 => 1 + (bytes[0] - 0xc0) as usize,
+            _ => unreachable!("RLP item with length bytes"),
+        }
+    }
+
+    /// Replaces the child `index` of the branch at the root of the trie, in
+    /// both the S and C branches, and updates the RLP and the hashes of the
+    /// branches, and the roots of the start node, to match.
+    fn replace_root_branch_child(nodes: &mut [Node], index: usize, child: &[u8]) {
+        let Node {
+            extension_branch,
+            values,
+            keccak_data,
+            ..
+        } = &mut nodes[1];
+        let branch = &mut extension_branch.as_mut().unwrap().branch;
+        assert_ne!(branch.modified_index, index);
+
+        let row = ExtensionBranchRowType::Child0 as usize + index;
+        let old_num_bytes = rlp_item_num_bytes(&values[row]);
+        values[row] = child.to_vec();
+        values[row].resize(RLP_UNIT_NUM_BYTES, 0);
+
+        let mut roots = vec![];
+        for (idx, preimage) in keccak_data.iter_mut().enumerate() {
+            // Skip the RLP bytes of the branch and the children before `index`
+            let mut pos = branch.list_rlp_bytes[idx].len();
+            for _ in 0..index {
+                pos += rlp_item_num_bytes(&preimage[pos..]);
+            }
+            preimage.splice(pos..pos + old_num_bytes, child.iter().cloned());
+            let len = preimage.len() - 3;
+            branch.list_rlp_bytes[idx] = vec![249, (len >> 8) as u8, len as u8];
+            preimage[..3].copy_from_slice(&branch.list_rlp_bytes[idx]);
+
+            let mut root = [vec![160], keccak256(&preimage[..]).to_vec()].concat();
+            root.resize(RLP_UNIT_NUM_BYTES, 0);
+            roots.push(root);
+        }
+        nodes[0].values[..2].clone_from_slice(&roots);
+    }
+
     #[test]
     fn test_mpt() {
         let path = "src/mpt_circuit/tests";
@@ -829,30 +946,115 @@ mod tests {
                 parts.next();
 
                 let nodes = load_proof(path.to_str().unwrap());
-                let num_rows: usize = nodes.iter().map(|node| node.values.len()).sum();
-
-                let mut keccak_data = vec![];
-                for node in nodes.iter() {
-                    for k in node.keccak_data.iter() {
-                        keccak_data.push(k.clone());
-                    }
-                }
-
-                let disable_preimage_check = nodes[0].start.clone().unwrap().disable_preimage_check;
-                let degree = 14;
-                let circuit = MPTCircuit::<Fr> {
-                    nodes,
-                    keccak_data,
-                    degree,
-                    disable_preimage_check,
-                    _marker: PhantomData,
-                };
 
                 println!("{} {:?}", idx, path);
-                let prover = MockProver::<Fr>::run(degree as u32, &circuit, vec![]).unwrap();
-                assert_eq!(prover.verify_at_rows(0..num_rows, 0..num_rows,), Ok(()));
-                // assert_eq!(prover.verify_par(), Ok(()));
-                // prover.assert_satisfied();
+                assert_eq!(verify(nodes), Ok(()));
             });
     }
+
+    #[test]
+    fn test_mpt_malformed_nodes() {
+        let nodes = load_proof("src/mpt_circuit/tests/StorageInFirstLevelNonExisting.json");
+        let circuit = |nodes: Vec<Node>| MPTCircuit::<Fr> {
+            nodes,
+            keccak_data: vec![],
+            degree: 14,
+            disable_preimage_check: false,
+            max_mult_length: MAX_MULT_LENGTH,
+            _marker: PhantomData,
+        };
+
+        // A node without a type
+        let mut untyped = nodes.clone();
+        let node = untyped.last_mut().unwrap();
+        node.start = None;
+        node.extension_branch = None;
+        node.account = None;
+        node.storage = None;
+        assert_eq!(
+            untyped.last().unwrap().rlp_item_types(),
+            Err(WitnessError::MissingNodeType)
+        );
+        assert!(MockProver::<Fr>::run(14, &circuit(untyped), vec![]).is_err());
+
+        // A node with a missing value
+        let mut truncated = nodes;
+        truncated.last_mut().unwrap().values.pop();
+        assert!(matches!(
+            truncated.last().unwrap().rlp_item_types(),
+            Err(WitnessError::NotEnoughValues { .. })
+        ));
+        assert!(MockProver::<Fr>::run(14, &circuit(truncated), vec![]).is_err());
+    }
+
+    #[test]
+    fn test_mpt_embedded_branch_child() {
+        let nodes = load_proof("src/mpt_circuit/tests/AccountAfterFirstLevel.json");
+
+        // A node encoded in less than 32 bytes is embedded in its parent branch
+        let mut embedded = nodes.clone();
+        replace_root_branch_child(&mut embedded, 0, &[vec![0xde], vec![0x80; 30]].concat());
+        assert_eq!(verify(embedded), Ok(()));
+
+        // A node of 32 bytes is hashed, embedding it is not the canonical encoding
+        let mut not_hashed = nodes;
+        replace_root_branch_child(&mut not_hashed, 0, &[vec![0xdf], vec![0x80; 31]].concat());
+        assert!(verify(not_hashed).is_err());
+    }
+
+    #[test]
+    fn test_mpt_account_leaf_with_long_list() {
+        // A list with the length in two bytes
+        let (_, is_short, is_long, is_very_long) = decode_rlp(0xf9);
+        let list = RLPListWitness {
+            is_short,
+            is_long,
+            is_very_long,
+            is_string: false,
+            bytes: vec![0xf9, 0x01, 0x02],
+        };
+        assert_eq!((list.len(), list.num_bytes()), (258, 261));
+
+        // The witness of an account leaf with such a list as its nonce can be
+        // generated, and the circuit rejects it as the nonce isn't a string
+        let mut nodes = load_proof("src/mpt_circuit/tests/AccountInFirstLevel.json");
+        let account = &mut nodes[1];
+        assert!(account.account.is_some());
+        let nonce = &mut account.values[AccountRowType::NonceS as usize];
+        *nonce = vec![0xf9, 0x00, 0x01, 0x80];
+        nonce.resize(RLP_UNIT_NUM_BYTES, 0);
+        assert!(verify(nodes).is_err());
+    }
+
+    #[test]
+    fn test_mpt_max_mult_length() {
+        // The hashes of the nodes are longer than the lengths supported by the mult table
+        let nodes = load_proof("src/mpt_circuit/tests/StorageInFirstLevelNonExisting.json");
+        let circuit = MPTCircuit::<Fr> {
+            nodes,
+            keccak_data: vec![],
+            degree: 14,
+            disable_preimage_check: false,
+            max_mult_length: 16,
+            _marker: PhantomData,
+        };
+        assert!(MockProver::<Fr>::run(14, &circuit, vec![]).is_err());
+    }
+
+    #[test]
+    fn test_mpt_storage_proof_without_storage_rows() {
+        // Storage proofs need to continue with the storage trie below the account
+        let mut nodes = load_proof("src/mpt_circuit/tests/StorageInFirstAccountInFirstLevel.json");
+        nodes.retain(|node| node.storage.is_none());
+        assert!(verify(nodes).is_err());
+    }
+
+    #[test]
+    fn test_mpt_account_proof_with_storage_rows() {
+        // Account updates without a storage change can't continue below the account
+        let mut nodes = load_proof("src/mpt_circuit/tests/StorageInFirstLevelNonExisting.json");
+        let start = nodes[0].start.as_mut().unwrap();
+        start.proof_type = MPTProofType::BalanceChanged;
+        assert!(verify(nodes).is_err());
+    }
 }
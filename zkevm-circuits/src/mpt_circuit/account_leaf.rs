@@ -50,6 +50,7 @@ pub(crate) struct AccountLeafConfig<F> {
     is_balance_mod: IsEqualGadget<F>,
     is_storage_mod: IsEqualGadget<F>,
     is_codehash_mod: IsEqualGadget<F>,
+    is_non_existing_storage_proof: IsEqualGadget<F>,
 }
 
 impl<F: Field> AccountLeafConfig<F> {
@@ -270,6 +271,7 @@ impl<F: Field> AccountLeafConfig<F> {
                     true.expr(),
                     false.expr(),
                     storage_items[is_s.idx()].word(),
+                    0.expr(),
                 );
             }
             // Proof types
@@ -303,6 +305,11 @@ impl<F: Field> AccountLeafConfig<F> {
                 config.main_data.proof_type.expr(),
                 MPTProofType::CodeHashChanged.expr(),
             );
+            config.is_non_existing_storage_proof = IsEqualGadget::construct(
+                &mut cb.base,
+                config.main_data.proof_type.expr(),
+                MPTProofType::StorageDoesNotExist.expr(),
+            );
 
             // Drifted leaf handling
             config.drifted = DriftedGadget::construct(
@@ -330,9 +337,8 @@ impl<F: Field> AccountLeafConfig<F> {
                 &cb.key_r.expr(),
             );
 
-            // Anything following this node is below the account
-            // TODO(Brecht): For non-existing accounts it should be impossible to prove
-            // storage leaves unless it's also a non-existing proof?
+            // Anything following this node is below the account. Only storage proofs
+            // continue below the account, see `is_storage_proof`.
             MainData::store(
                 cb,
                 &ctx.memory[main_memory()],
@@ -430,6 +436,13 @@ impl<F: Field> AccountLeafConfig<F> {
         config
     }
 
+    /// Whether the proof continues with the storage trie below the account.
+    /// Account updates without a storage change end at the account leaf, so
+    /// they don't have any storage proof rows.
+    pub(crate) fn is_storage_proof(&self) -> Expression<F> {
+        self.is_storage_mod.expr() + self.is_non_existing_storage_proof.expr()
+    }
+
     #[allow(clippy::too_many_arguments)]
     pub fn assign(
         &self,
@@ -555,6 +568,7 @@ impl<F: Field> AccountLeafConfig<F> {
                 true,
                 false,
                 storage_items[is_s.idx()].word(),
+                F::ZERO,
             )?;
         }
 
@@ -595,6 +609,12 @@ impl<F: Field> AccountLeafConfig<F> {
             main_data.proof_type.scalar(),
             MPTProofType::CodeHashChanged.scalar(),
         )? == true.scalar();
+        self.is_non_existing_storage_proof.assign(
+            region,
+            offset,
+            main_data.proof_type.scalar(),
+            MPTProofType::StorageDoesNotExist.scalar(),
+        )?;
         // Drifted leaf handling
         self.drifted.assign(
             region,
@@ -168,7 +168,8 @@ impl<F: Field> ExtensionBranchConfig<F> {
                         branch.mod_rlc[is_s.idx()].expr(),
                         false.expr(),
                         false.expr(),
-                        Word::<Expression<F>>::new([0.expr(), 0.expr()])
+                        Word::<Expression<F>>::new([0.expr(), 0.expr()]),
+                        0.expr(),
                     );
                  } elsex {
                     KeyData::store(
@@ -191,6 +192,7 @@ impl<F: Field> ExtensionBranchConfig<F> {
                         config.parent_data[is_s.idx()].is_root.expr(),
                         true.expr(),
                         branch.mod_word[is_s.idx()].clone(),
+                        branch.mod_rlc[is_s.idx()].expr(),
                     );
                 }}
             }
@@ -298,6 +300,7 @@ impl<F: Field> ExtensionBranchConfig<F> {
                     false,
                     false,
                     Word::<F>::new([0.scalar(), 0.scalar()]),
+                    F::ZERO,
                 )?;
             } else {
                 KeyData::witness_store(
@@ -320,6 +323,7 @@ impl<F: Field> ExtensionBranchConfig<F> {
                     parent_data[is_s.idx()].is_root,
                     true,
                     mod_node_hash_word[is_s.idx()],
+                    mod_node_hash_rlc[is_s.idx()],
                 )?;
             }
         }
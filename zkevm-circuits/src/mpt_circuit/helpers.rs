@@ -547,6 +547,7 @@ pub(crate) struct ParentData<F> {
     pub(crate) is_root: Cell<F>,
     pub(crate) is_placeholder: Cell<F>,
     pub(crate) drifted_parent_hash: WordCell<F>,
+    pub(crate) drifted_parent_rlc: Cell<F>,
 }
 
 #[derive(Clone, Debug, Default)]
@@ -556,6 +557,7 @@ pub(crate) struct ParentDataWitness<F> {
     pub(crate) is_root: bool,
     pub(crate) is_placeholder: bool,
     pub(crate) drifted_parent_hash: word::Word<F>,
+    pub(crate) drifted_parent_rlc: F,
 }
 
 impl<F: Field> ParentData<F> {
@@ -571,6 +573,7 @@ impl<F: Field> ParentData<F> {
             is_root: cb.query_cell(),
             is_placeholder: cb.query_cell(),
             drifted_parent_hash: cb.query_word_unchecked(),
+            drifted_parent_rlc: cb.query_cell_with_type(MptCellType::StoragePhase2),
         };
         circuit!([meta, cb.base], {
             memory.load(
@@ -585,6 +588,7 @@ impl<F: Field> ParentData<F> {
                     parent_data.is_placeholder.expr(),
                     parent_data.drifted_parent_hash.lo().expr(),
                     parent_data.drifted_parent_hash.hi().expr(),
+                    parent_data.drifted_parent_rlc.expr(),
                 ],
             );
         });
@@ -599,6 +603,7 @@ impl<F: Field> ParentData<F> {
         is_root: Expression<F>,
         is_placeholder: Expression<F>,
         drifted_parent_hash: word::Word<Expression<F>>,
+        drifted_parent_rlc: Expression<F>,
     ) {
         memory.store(
             &mut cb.base,
@@ -610,6 +615,7 @@ impl<F: Field> ParentData<F> {
                 is_placeholder,
                 drifted_parent_hash.lo(),
                 drifted_parent_hash.hi(),
+                drifted_parent_rlc,
             ],
         );
     }
@@ -624,6 +630,7 @@ impl<F: Field> ParentData<F> {
         force_hashed: bool,
         is_placeholder: bool,
         drifted_parent_hash: word::Word<F>,
+        drifted_parent_rlc: F,
     ) -> Result<(), Error> {
         memory.witness_store(
             offset,
@@ -635,6 +642,7 @@ impl<F: Field> ParentData<F> {
                 is_placeholder.scalar(),
                 drifted_parent_hash.lo(),
                 drifted_parent_hash.hi(),
+                drifted_parent_rlc,
             ],
         );
         Ok(())
@@ -660,6 +668,7 @@ impl<F: Field> ParentData<F> {
         self.drifted_parent_hash
             .hi()
             .assign(region, offset, values[6])?;
+        self.drifted_parent_rlc.assign(region, offset, values[7])?;
 
         Ok(ParentDataWitness {
             hash: word::Word::new([values[0], values[1]]),
@@ -667,6 +676,7 @@ impl<F: Field> ParentData<F> {
             is_root: values[3] == 1.scalar(),
             is_placeholder: values[4] == 1.scalar(),
             drifted_parent_hash: word::Word::new([values[5], values[6]]),
+            drifted_parent_rlc: values[7],
         })
     }
 }
@@ -1110,6 +1120,7 @@ impl<F: Field> IsPlaceholderLeafGadget<F> {
 #[derive(Clone, Debug, Default)]
 pub struct DriftedGadget<F> {
     drifted_rlp_key: ListKeyGadget<F>,
+    is_not_hashed: LtGadget<F, 1>,
 }
 
 impl<F: Field> DriftedGadget<F> {
@@ -1129,6 +1140,12 @@ impl<F: Field> DriftedGadget<F> {
         circuit!([meta, cb], {
             ifx! {parent_data[true.idx()].is_placeholder.expr() + parent_data[false.idx()].is_placeholder.expr() => {
                 config.drifted_rlp_key = ListKeyGadget::construct(cb, drifted_item);
+                // Leaves shorter than 32 bytes are embedded in the branch instead of hashed
+                config.is_not_hashed = LtGadget::construct(
+                    &mut cb.base,
+                    config.drifted_rlp_key.rlp_list.num_bytes(),
+                    HASH_WIDTH.expr(),
+                );
                 for is_s in [true, false] {
                     ifx! {parent_data[is_s.idx()].is_placeholder.expr() => {
                         // Check that the drifted leaf is unchanged and is stored at `drifted_index`.
@@ -1166,8 +1183,14 @@ impl<F: Field> DriftedGadget<F> {
                         //let leaf_rlc = (config.drifted_rlp_key.rlc(be_r), mult.expr()).rlc_chain(leaf_no_key_rlc[is_s.idx()].expr());
                         let leaf_rlc = config.drifted_rlp_key.rlc2(&cb.keccak_r).rlc_chain_rev((leaf_no_key_rlc[is_s.idx()].expr(), leaf_no_key_rlc_mult[is_s.idx()].expr()));
                         // The drifted leaf needs to be stored in the branch at `drifted_index`.
-                        let hash = parent_data[is_s.idx()].drifted_parent_hash.expr();
-                        require!(vec![1.expr(), leaf_rlc.expr(), config.drifted_rlp_key.rlp_list.num_bytes(), hash.lo(), hash.hi()] => @KECCAK);
+                        ifx! {config.is_not_hashed => {
+                            // Non-hashed leaf embedded in the branch
+                            require!(leaf_rlc => parent_data[is_s.idx()].drifted_parent_rlc.expr());
+                        } elsex {
+                            // Hashed leaf hash in the branch
+                            let hash = parent_data[is_s.idx()].drifted_parent_hash.expr();
+                            require!(vec![1.expr(), leaf_rlc.expr(), config.drifted_rlp_key.rlp_list.num_bytes(), hash.lo(), hash.hi()] => @KECCAK);
+                        }}
                     }
                 }}
             }}
@@ -1185,8 +1208,15 @@ impl<F: Field> DriftedGadget<F> {
         _r: F,
     ) -> Result<(), Error> {
         if parent_data[true.idx()].is_placeholder || parent_data[false.idx()].is_placeholder {
-            self.drifted_rlp_key
-                .assign(region, offset, drifted_list_bytes, drifted_item)?;
+            let drifted_key_witness =
+                self.drifted_rlp_key
+                    .assign(region, offset, drifted_list_bytes, drifted_item)?;
+            self.is_not_hashed.assign(
+                region,
+                offset,
+                drifted_key_witness.rlp_list.num_bytes().scalar(),
+                HASH_WIDTH.scalar(),
+            )?;
         }
         Ok(())
     }
@@ -1302,6 +1332,7 @@ pub struct MainRLPGadget<F> {
     is_big_endian: Cell<F>,
     is_hash: Cell<F>,
     keccak_r: Option<Expression<F>>,
+    max_mult_length: usize,
 }
 
 impl<F: Field> MainRLPGadget<F> {
@@ -1325,6 +1356,7 @@ impl<F: Field> MainRLPGadget<F> {
                 is_big_endian: cb.query_cell(),
                 is_hash: cb.query_cell(),
                 keccak_r: Some(cb.keccak_r.expr()),
+                max_mult_length: params.max_mult_length,
             };
             let all_bytes = vec![vec![config.rlp_byte.clone()], config.bytes.clone()].concat();
 
@@ -1423,6 +1455,16 @@ impl<F: Field> MainRLPGadget<F> {
 
         // Decode the RLP item
         let rlp = self.rlp.assign(region, offset, &bytes)?;
+        // The multiplier of the item is looked up in the mult table, which only goes up to
+        // the max_mult_length of the circuit params
+        if rlp.num_bytes() > self.max_mult_length + 1 {
+            log::error!(
+                "RLP item of {} bytes longer than the {} bytes supported",
+                rlp.num_bytes(),
+                self.max_mult_length + 1
+            );
+            return Err(Error::Synthesis);
+        }
 
         // Depending on the RLP item type, we store the data in little endian or big endian.
         // Little endian makes it much easer to decode the lo/hi split representation.
@@ -1453,7 +1495,7 @@ impl<F: Field> MainRLPGadget<F> {
             if rlp.is_string() {
                 self.max_length(item_type)
             } else {
-                HASH_WIDTH - 1
+                HASH_WIDTH - 2
             }
         } else {
             self.max_length(item_type)
@@ -1525,12 +1567,17 @@ impl<F: Field> MainRLPGadget<F> {
                 require!(len => HASH_WIDTH);
             }
             if item_type == RlpItemType::Node {
-                // Nodes always have length 0 or 32 when a string, or are < 32 when a list
+                // Nodes always have length 0 or 32 when a string, or are encoded in
+                // less than 32 bytes when a list (otherwise the node is hashed).
                 ifx! {is_string => {
                     require!(max_len => self.max_length(item_type).expr());
                     require!(len => [0, HASH_WIDTH]);
+                    // Only allow the canonical encoding, the length is stored in the RLP byte.
+                    // So an empty node is always `128`, and a hash is always `160` followed
+                    // by the hash bytes.
+                    require!(self.rlp.value.is_long_at(meta, rot) => true);
                 } elsex {
-                    require!(max_len => HASH_WIDTH - 1);
+                    require!(max_len => HASH_WIDTH - 2);
                 }}
             } else {
                 require!(max_len => self.max_length(item_type).expr());
@@ -30,3 +30,7 @@ pub const EMPTY_TRIE_HASH: [u8; 32] = [
 // Number of bytes required to decode an RLP item
 pub const RLP_UNIT_NUM_BYTES: usize = 34;
 pub const RLP_UNIT_NUM_VALUE_BYTES: usize = RLP_UNIT_NUM_BYTES - 1;
+// Upper bound of the lengths whose multiplier is looked up in the mult table:
+// the bytes of an RLP item after its RLP byte, which bound the keys and the
+// account and storage values.  The circuit params can lower it.
+pub const MAX_MULT_LENGTH: usize = RLP_UNIT_NUM_VALUE_BYTES;
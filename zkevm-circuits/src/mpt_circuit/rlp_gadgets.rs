@@ -149,7 +149,7 @@ impl<F: Field> RLPListGadget<F> {
         self.is_long.rot(meta, rot)
     }
 
-    // RLP byte followed by the length in 1 byte, followed by the length
+    // RLP byte followed by the length in 2 bytes, followed by the length
     pub(crate) fn is_very_long(&self) -> Expression<F> {
         self.is_very_long.expr()
     }
@@ -256,6 +256,7 @@ impl RLPListWitness {
         matchw! {
             self.is_short() => get_len_list_short::value(self.bytes[0]),
             self.is_long() => self.bytes[1] as usize,
+            self.is_very_long() => (self.bytes[1] as usize) * 256 + (self.bytes[2] as usize),
         }
     }
 
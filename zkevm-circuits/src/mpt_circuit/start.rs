@@ -73,6 +73,7 @@ impl<F: Field> StartConfig<F> {
                     true.expr(),
                     false.expr(),
                     root[is_s.idx()].clone(),
+                    0.expr(),
                 );
                 KeyData::store_defaults(cb, &ctx.memory[key_memory(is_s)]);
             }
@@ -127,6 +128,7 @@ impl<F: Field> StartConfig<F> {
                 true,
                 false,
                 root[is_s.idx()],
+                F::ZERO,
             )?;
             KeyData::witness_store(
                 region,
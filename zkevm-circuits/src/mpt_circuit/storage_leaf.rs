@@ -12,7 +12,7 @@ use crate::{
         cached_region::CachedRegion,
         cell_manager::Cell,
         constraint_builder::{RLCChainableRev, RLCable},
-        gadgets::{IsEqualGadget, LtGadget},
+        gadgets::{IsEqualGadget, IsZeroGadget, LtGadget},
     },
     mpt_circuit::{
         helpers::{
@@ -45,6 +45,7 @@ pub(crate) struct StorageLeafConfig<F> {
     is_wrong_leaf: Cell<F>,
     is_not_hashed: [LtGadget<F, 1>; 2],
     is_placeholder_leaf: [IsPlaceholderLeafGadget<F>; 2],
+    is_zero_value: [IsZeroGadget<F>; 2],
     drifted: DriftedGadget<F>,
     wrong: WrongGadget<F>,
     is_storage_mod_proof: IsEqualGadget<F>,
@@ -102,6 +103,18 @@ impl<F: Field> StorageLeafConfig<F> {
             // Storage leaves always need to be below accounts
             require!(config.main_data.is_below_account => true);
 
+            // Proof types
+            config.is_storage_mod_proof = IsEqualGadget::construct(
+                &mut cb.base,
+                config.main_data.proof_type.expr(),
+                MPTProofType::StorageChanged.expr(),
+            );
+            config.is_non_existing_storage_proof = IsEqualGadget::construct(
+                &mut cb.base,
+                config.main_data.proof_type.expr(),
+                MPTProofType::StorageDoesNotExist.expr(),
+            );
+
             let mut key_rlc = vec![0.expr(); 2];
             let mut value_word = vec![Word::<Expression<F>>::new([0.expr(), 0.expr()]); 2];
             let mut value_rlp_rlc = vec![0.expr(); 2];
@@ -178,6 +191,17 @@ impl<F: Field> StorageLeafConfig<F> {
                 ifx! {is_placeholder_leaf => {
                     require!(value_word[is_s.idx()] => [0.expr(), 0.expr()]);
                 }}
+                // Existing leaves never store `0`, setting a slot to `0` deletes its leaf.
+                // So in a storage change a `0` value always means the leaf doesn't exist,
+                // while a slot that doesn't exist before nor after the change needs a
+                // non-existing proof.
+                config.is_zero_value[is_s.idx()] = IsZeroGadget::construct(
+                    &mut cb.base,
+                    value_word[is_s.idx()].lo() + value_word[is_s.idx()].hi(),
+                );
+                ifx! {and::expr(&[config.is_storage_mod_proof.expr(), not!(is_placeholder_leaf)]) => {
+                    require!(config.is_zero_value[is_s.idx()] => false);
+                }}
 
                 // Make sure the RLP encoding is correct.
                 // storage = [key, "value"]
@@ -208,21 +232,10 @@ impl<F: Field> StorageLeafConfig<F> {
                     true.expr(),
                     false.expr(),
                     word::Word::<Expression<F>>::new([0.expr(), 0.expr()]),
+                    0.expr(),
                 );
             }
 
-            // Proof types
-            config.is_storage_mod_proof = IsEqualGadget::construct(
-                &mut cb.base,
-                config.main_data.proof_type.expr(),
-                MPTProofType::StorageChanged.expr(),
-            );
-            config.is_non_existing_storage_proof = IsEqualGadget::construct(
-                &mut cb.base,
-                config.main_data.proof_type.expr(),
-                MPTProofType::StorageDoesNotExist.expr(),
-            );
-
             // Drifted leaf handling
             config.drifted = DriftedGadget::construct(
                 cb,
@@ -281,6 +294,18 @@ impl<F: Field> StorageLeafConfig<F> {
                 config.is_non_existing_storage_proof => MPTProofType::StorageDoesNotExist.expr(),
                 _ => MPTProofType::Disabled.expr(),
             };
+            // A slot that doesn't exist has value `0`. The leaf in a non-existing proof
+            // can be another leaf (the wrong leaf), so its value is not the slot value.
+            let (new_value_lo, new_value_hi, old_value_lo, old_value_hi) = ifx! {config.is_non_existing_storage_proof => {
+                (0.expr(), 0.expr(), 0.expr(), 0.expr())
+            } elsex {
+                (
+                    value_word[false.idx()].lo(),
+                    value_word[false.idx()].hi(),
+                    value_word[true.idx()].lo(),
+                    value_word[true.idx()].hi(),
+                )
+            }};
             ifx! {not!(config.is_non_existing_storage_proof) => {
                 let key_rlc = ifx!{not!(config.parent_data[true.idx()].is_placeholder) => {
                     key_rlc[true.idx()].expr()
@@ -303,8 +328,8 @@ impl<F: Field> StorageLeafConfig<F> {
                 address_item.word(),
                 config.main_data.new_root.expr(),
                 config.main_data.old_root.expr(),
-                value_word[false.idx()].clone(),
-                value_word[true.idx()].clone(),
+                Word::<Expression<F>>::new([new_value_lo, new_value_hi]),
+                Word::<Expression<F>>::new([old_value_lo, old_value_hi]),
             );
         });
 
@@ -409,6 +434,11 @@ impl<F: Field> StorageLeafConfig<F> {
             } else {
                 value_item[is_s.idx()].word()
             };
+            self.is_zero_value[is_s.idx()].assign(
+                region,
+                offset,
+                value_word[is_s.idx()].lo() + value_word[is_s.idx()].hi(),
+            )?;
 
             ParentData::witness_store(
                 region,
@@ -419,6 +449,7 @@ impl<F: Field> StorageLeafConfig<F> {
                 true,
                 false,
                 word::Word::<F>::new([F::ZERO, F::ZERO]),
+                F::ZERO,
             )?;
 
             self.is_placeholder_leaf[is_s.idx()].assign(
@@ -484,6 +515,11 @@ impl<F: Field> StorageLeafConfig<F> {
         } else {
             MPTProofType::Disabled
         };
+        let (new_value, old_value) = if is_non_existing_proof {
+            (Word::new([F::ZERO, F::ZERO]), Word::new([F::ZERO, F::ZERO]))
+        } else {
+            (value_word[false.idx()], value_word[true.idx()])
+        };
         mpt_config.mpt_table.assign_cached(
             region,
             offset,
@@ -493,8 +529,8 @@ impl<F: Field> StorageLeafConfig<F> {
                 proof_type: Value::known(proof_type.scalar()),
                 new_root: main_data.new_root.into_value(),
                 old_root: main_data.old_root.into_value(),
-                new_value: value_word[false.idx()].into_value(),
-                old_value: value_word[true.idx()].into_value(),
+                new_value: new_value.into_value(),
+                old_value: old_value.into_value(),
             },
         )?;
 
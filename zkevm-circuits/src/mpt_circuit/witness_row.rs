@@ -1,6 +1,7 @@
 use crate::table::MPTProofType;
 
 use serde::{Deserialize, Serialize};
+use std::fmt;
 
 use super::RlpItemType;
 
@@ -135,6 +136,58 @@ pub struct Node {
     pub keccak_data: Vec<Vec<u8>>,
 }
 
+impl Node {
+    /// RLP item types of the rows of the node, checking that the node has a
+    /// type and a value for each of its rows.
+    pub fn rlp_item_types(&self) -> Result<&'static [RlpItemType], WitnessError> {
+        let item_types: &'static [RlpItemType] = if self.start.is_some() {
+            &NODE_RLP_TYPES_START
+        } else if self.extension_branch.is_some() {
+            &NODE_RLP_TYPES_BRANCH
+        } else if self.account.is_some() {
+            &NODE_RLP_TYPES_ACCOUNT
+        } else if self.storage.is_some() {
+            &NODE_RLP_TYPES_STORAGE
+        } else {
+            return Err(WitnessError::MissingNodeType);
+        };
+        if self.values.len() < item_types.len() {
+            return Err(WitnessError::NotEnoughValues {
+                expected: item_types.len(),
+                found: self.values.len(),
+            });
+        }
+        Ok(item_types)
+    }
+}
+
+/// Error of a malformed MPT witness node.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WitnessError {
+    /// The node is neither a start, branch, account or storage node
+    MissingNodeType,
+    /// The node has fewer values than rows
+    NotEnoughValues {
+        /// Rows of the node
+        expected: usize,
+        /// Values of the node
+        found: usize,
+    },
+}
+
+impl fmt::Display for WitnessError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MissingNodeType => write!(f, "node without a type"),
+            Self::NotEnoughValues { expected, found } => {
+                write!(f, "node with {} values, expected {}", found, expected)
+            }
+        }
+    }
+}
+
+impl std::error::Error for WitnessError {}
+
 /// RLP types start
 pub const NODE_RLP_TYPES_START: [RlpItemType; StartRowType::Count as usize] =
     [RlpItemType::Hash, RlpItemType::Hash];
@@ -28,7 +28,7 @@ use crate::{
     },
     instance::{
         public_data_convert, BlockValues, ExtraValues, PublicData, TxValues, NONZERO_BYTE_GAS_COST,
-        ZERO_BYTE_GAS_COST,
+        PI_VERSION, ZERO_BYTE_GAS_COST,
     },
     table::{BlockTable, KeccakTable, LookupTable, TxFieldTag, TxTable},
     tx_circuit::TX_LEN,
@@ -190,6 +190,10 @@ impl<F: Field> SubCircuitConfig<F> for PiCircuitConfig<F> {
 
         meta.enable_equality(pi_instance);
 
+        // the version byte is copy constrained to a constant
+        let constants = meta.fixed_column();
+        meta.enable_constant(constants);
+
         // gate 1 and gate 2 are compensation branch
         // 1: rpi_bytes_keccakrlc[last] = rpi_bytes[last]
         meta.create_gate("rpi_bytes_keccakrlc[last] = rpi_bytes[last]", |meta| {
@@ -520,7 +524,7 @@ impl<F: Field> PiCircuitConfig<F> {
     /// Return the number of rows for txs and calldata
     #[inline]
     fn circuit_len_by_txs_calldata(txs: usize, calldata: usize) -> usize {
-        N_BYTES_ONE
+        N_BYTES_DOMAIN
             + N_BYTES_BLOCK
             + N_BYTES_EXTRA_VALUE
             + Self::circuit_len_tx_id(txs)
@@ -985,7 +989,8 @@ impl<F: Field> PiCircuitConfig<F> {
 
     /// Assigns the values for block table in the block_table column
     /// and rpi_bytes columns. Copy constraints will be enable
-    /// to assure block_table value cell equal with respective rpi_byte_rlc cell
+    /// to assure block_table value cell equal with respective rpi_byte_rlc cell.
+    /// Returns the chain_id cell of the block table.
     #[allow(clippy::too_many_arguments)]
     fn assign_block_table(
         &self,
@@ -997,7 +1002,7 @@ impl<F: Field> PiCircuitConfig<F> {
         current_offset: &mut usize,
         rpi_bytes: &mut [u8],
         zero_cell: AssignedCell<F, F>,
-    ) -> Result<(), Error> {
+    ) -> Result<Word<AssignedCell<F, F>>, Error> {
         let mut block_copy_cells = vec![];
 
         // coinbase
@@ -1148,6 +1153,7 @@ impl<F: Field> PiCircuitConfig<F> {
             challenges,
             zero_cell.clone(),
         )?;
+        let chain_id_cell = block_value.clone();
         block_copy_cells.push((block_value, word));
         *block_table_offset += 1;
 
@@ -1182,7 +1188,7 @@ impl<F: Field> PiCircuitConfig<F> {
             Ok::<(), Error>(())
         })?;
 
-        Ok(())
+        Ok(chain_id_cell)
     }
 
     /// Assigns the extra fields (not in block or tx tables):
@@ -1415,9 +1421,22 @@ impl<F: Field> SubCircuit<F> for PiCircuit<F> {
                     block_table_offset,
                 )?;
                 let zero_cell = zero_word.hi();
-                let (_, _) = config.assign_raw_bytes(
+
+                // domain separation: the version byte, constrained to PI_VERSION, and the
+                // chain_id, copy constrained to the chain_id of the block table below.
+                let (_, version_word) = config.assign_raw_bytes(
                     &mut region,
-                    &0u8.to_le_bytes(),
+                    &PI_VERSION.to_le_bytes(),
+                    &mut rpi_bytes_keccakrlc,
+                    &mut rpi_bytes,
+                    &mut current_offset,
+                    challenges,
+                    zero_cell.clone(),
+                )?;
+                region.constrain_constant(version_word.lo().cell(), F::from(PI_VERSION as u64))?;
+                let (_, domain_chain_id_word) = config.assign_raw_bytes(
+                    &mut region,
+                    &block_values.chain_id.to_le_bytes(),
                     &mut rpi_bytes_keccakrlc,
                     &mut rpi_bytes,
                     &mut current_offset,
@@ -1425,7 +1444,7 @@ impl<F: Field> SubCircuit<F> for PiCircuit<F> {
                     zero_cell.clone(),
                 )?;
                 block_table_offset += 1;
-                config.assign_block_table(
+                let chain_id_word = config.assign_block_table(
                     &mut region,
                     &mut block_table_offset,
                     block_values,
@@ -1435,7 +1454,14 @@ impl<F: Field> SubCircuit<F> for PiCircuit<F> {
                     &mut rpi_bytes,
                     zero_cell.clone(),
                 )?;
-                assert_eq!(start_offset - current_offset, N_BYTES_ONE + N_BYTES_BLOCK);
+                region
+                    .constrain_equal(domain_chain_id_word.lo().cell(), chain_id_word.lo().cell())?;
+                region
+                    .constrain_equal(domain_chain_id_word.hi().cell(), chain_id_word.hi().cell())?;
+                assert_eq!(
+                    start_offset - current_offset,
+                    N_BYTES_DOMAIN + N_BYTES_BLOCK
+                );
 
                 // Assign extra fields
                 let extra_vals = self.public_data.get_extra_values();
@@ -1450,14 +1476,14 @@ impl<F: Field> SubCircuit<F> for PiCircuit<F> {
                 )?;
                 assert_eq!(
                     start_offset - current_offset,
-                    N_BYTES_ONE + N_BYTES_BLOCK + N_BYTES_EXTRA_VALUE
+                    N_BYTES_DOMAIN + N_BYTES_BLOCK + N_BYTES_EXTRA_VALUE
                 );
 
                 let mut tx_table_offset = 0;
                 // Assign Tx table
                 let txs = self.public_data.get_tx_table_values();
                 assert!(txs.len() <= config.max_txs);
-                let tx_default = TxValues::default();
+                let tx_default = TxValues::padding();
 
                 // Add empty row
                 // assign first tx_value empty row, and to obtain zero cell via hi() part.
@@ -1520,6 +1546,11 @@ impl<F: Field> SubCircuit<F> for PiCircuit<F> {
                             ),
                             // TODO witness tx.tx_sign_hash
                             (TxFieldTag::TxSignHash, tx.tx_sign_hash.to_vec()),
+                            (
+                                TxFieldTag::CallDataOffset,
+                                tx.call_data_offset.to_le_bytes().to_vec(),
+                            ),
+                            (TxFieldTag::TxHash, tx.tx_hash.to_vec()),
                         ] {
                             let i: u64 = i.try_into().unwrap();
                             // assign tx field
@@ -1542,7 +1573,7 @@ impl<F: Field> SubCircuit<F> for PiCircuit<F> {
                     })?;
                 assert_eq!(
                     start_offset - current_offset,
-                    N_BYTES_ONE
+                    N_BYTES_DOMAIN
                         + N_BYTES_BLOCK
                         + N_BYTES_EXTRA_VALUE
                         + Self::Config::circuit_len_tx_id(config.max_txs)
@@ -1,10 +1,12 @@
 use halo2_proofs::circuit::AssignedCell;
 
-use crate::util::word::Word;
+use crate::{evm_circuit::param::N_BYTES_U64, util::word::Word};
 
 /// Fixed by the spec
 pub(super) const BYTE_POW_BASE: u64 = 256;
 pub(super) const EMPTY_TX_ROW_COUNT: usize = 1;
 pub(super) const N_BYTES_ONE: usize = 1;
+/// Domain separation prefix of the preimage: version byte and chain id
+pub(super) const N_BYTES_DOMAIN: usize = N_BYTES_ONE + N_BYTES_U64;
 
 pub(super) type AssignedByteCells<F> = (AssignedCell<F, F>, Word<AssignedCell<F, F>>);
@@ -4,7 +4,7 @@ use crate::{pi_circuit::dev::PiCircuitParams, util::unusable_rows, witness::bloc
 
 use super::*;
 use bus_mapping::{circuit_input_builder::FixedCParams, mock::BlockData};
-use eth_types::{bytecode, geth_types::GethData, Word, H160};
+use eth_types::{bytecode, geth_types::GethData, keccak256, Word, H160};
 use ethers_signers::{LocalWallet, Signer};
 use halo2_proofs::{
     dev::{MockProver, VerifyFailure},
@@ -74,6 +74,42 @@ fn test_simple_pi() {
     assert_eq!(run::<Fr>(k, max_txs, max_calldata, public_data), Ok(()));
 }
 
+#[test]
+fn test_call_data_offsets() {
+    let mut public_data = PublicData::default();
+    public_data.chain_id = *MOCK_CHAIN_ID;
+    for tx in CORRECT_MOCK_TXS.iter() {
+        public_data.transactions.push(tx.clone().into());
+    }
+
+    // Each tx starts where the call data of the previous one ends
+    let tx_values = public_data.get_tx_table_values();
+    assert_eq!(tx_values[0].call_data_offset, 0);
+    for (i, values) in tx_values.iter().enumerate().skip(1) {
+        assert_eq!(
+            values.call_data_offset,
+            tx_values[i - 1].call_data_offset + tx_values[i - 1].call_data_len
+        );
+    }
+}
+
+#[test]
+fn test_tx_hashes() {
+    let mut public_data = PublicData::default();
+    public_data.chain_id = *MOCK_CHAIN_ID;
+    for tx in CORRECT_MOCK_TXS.iter() {
+        public_data.transactions.push(tx.clone().into());
+    }
+
+    // The tx hash is the hash of the signed RLP encoding, stored in little endian
+    let tx_values = public_data.get_tx_table_values();
+    for (tx, values) in public_data.transactions.iter().zip(tx_values.iter()) {
+        let mut tx_hash = keccak256(&tx.rlp_signed(MOCK_CHAIN_ID.as_u64()));
+        tx_hash.reverse();
+        assert_eq!(values.tx_hash, tx_hash);
+    }
+}
+
 #[test]
 fn test_1tx_1maxtx() {
     const MAX_TXS: usize = 1;
@@ -184,3 +220,56 @@ fn variadic_size_check() {
 
     run_size_check::<Fr>(max_txs, max_calldata, [pub_dat_1, pub_dat_2]);
 }
+
+#[test]
+fn pi_bytes_domain_separation() {
+    let max_txs = 2;
+    let max_calldata = 8;
+    let public_data = PublicData {
+        chain_id: *MOCK_CHAIN_ID,
+        ..Default::default()
+    };
+
+    let pi_bytes = public_data.get_pi_bytes(max_txs, max_calldata);
+    assert_eq!(pi_bytes[0], PI_VERSION);
+    assert_eq!(
+        pi_bytes[N_BYTES_ONE..N_BYTES_DOMAIN],
+        MOCK_CHAIN_ID.as_u64().to_be_bytes()
+    );
+    assert_eq!(
+        pi_bytes.len(),
+        PiCircuitConfig::<Fr>::circuit_len_by_txs_calldata(max_txs, max_calldata)
+    );
+
+    let other_chain = PublicData {
+        chain_id: *MOCK_CHAIN_ID + 1,
+        ..Default::default()
+    };
+    assert_ne!(
+        public_data.get_rpi_digest_word::<Fr>(max_txs, max_calldata),
+        other_chain.get_rpi_digest_word::<Fr>(max_txs, max_calldata)
+    );
+}
+
+#[test]
+fn test_pi_wrong_chain_id() {
+    let max_txs = 2;
+    let max_calldata = 8;
+    let public_data = PublicData {
+        chain_id: *MOCK_CHAIN_ID,
+        ..Default::default()
+    };
+    let circuit = PiCircuit::<Fr>::new(max_txs, max_calldata, public_data);
+
+    // the verifier of another chain expects another digest
+    let other_chain = PiCircuit::<Fr>::new(
+        max_txs,
+        max_calldata,
+        PublicData {
+            chain_id: *MOCK_CHAIN_ID + 1,
+            ..Default::default()
+        },
+    );
+    let prover = MockProver::run(17, &circuit, other_chain.instance()).unwrap();
+    assert!(prover.verify().is_err());
+}
@@ -9,7 +9,7 @@ mod param;
 mod dev;
 #[cfg(test)]
 mod test;
-use bus_mapping::operation::Target;
+use bus_mapping::operation::{MemoryOpMode, Target};
 #[cfg(feature = "test-circuits")]
 pub use dev::StateCircuit as TestStateCircuit;
 
@@ -425,6 +425,9 @@ pub struct StateCircuit<F> {
     pub rows: Vec<Rw>,
     updates: MptUpdates,
     pub(crate) n_rows: usize,
+    /// Granularity of the memory rows.  `MemoryWord` rows are only accepted
+    /// in [`MemoryOpMode::Word`].
+    pub(crate) memory_op_mode: MemoryOpMode,
     #[cfg(test)]
     overrides: HashMap<(dev::AdviceColumn, isize), F>,
     _marker: PhantomData<F>,
@@ -439,6 +442,7 @@ impl<F: Field> StateCircuit<F> {
             rows,
             updates,
             n_rows,
+            memory_op_mode: MemoryOpMode::default(),
             #[cfg(test)]
             overrides: HashMap::new(),
             _marker: PhantomData::default(),
@@ -450,7 +454,9 @@ impl<F: Field> SubCircuit<F> for StateCircuit<F> {
     type Config = StateCircuitConfig<F>;
 
     fn new_from_block(block: &witness::Block<F>) -> Self {
-        Self::new(block.rws.clone(), block.circuits_params.max_rws)
+        let mut circuit = Self::new(block.rws.clone(), block.circuits_params.max_rws);
+        circuit.memory_op_mode = block.memory_op_mode;
+        circuit
     }
 
     fn unusable_rows() -> usize {
@@ -474,6 +480,15 @@ impl<F: Field> SubCircuit<F> for StateCircuit<F> {
         _challenges: &Challenges<Value<F>>,
         layouter: &mut impl Layouter<F>,
     ) -> Result<(), Error> {
+        if self.memory_op_mode == MemoryOpMode::Byte
+            && self
+                .rows
+                .iter()
+                .any(|row| matches!(row, Rw::MemoryWord { .. }))
+        {
+            log::error!("MemoryWord rows are not allowed in byte memory mode");
+            return Err(Error::Synthesis);
+        }
         config.load_aux_tables(layouter)?;
 
         // Assigning to same columns in different regions should be avoided.
@@ -553,6 +568,7 @@ fn queries<F: Field>(meta: &mut VirtualCells<'_, F>, c: &StateCircuitConfig<F>)
             value: meta_query_word(meta, c.rw_table.value, Rotation::cur()),
             value_prev: meta_query_word(meta, c.rw_table.value, Rotation::prev()),
             value_prev_column: meta_query_word(meta, c.rw_table.value_prev, Rotation::cur()),
+            init_val: meta_query_word(meta, c.rw_table.init_val, Rotation::cur()),
         },
         // TODO: clean this up
         mpt_update_table: MptUpdateTableQueries {
@@ -27,6 +27,7 @@ pub struct RwTableQueries<F: Field> {
     pub value: word::Word<Expression<F>>,
     pub value_prev: word::Word<Expression<F>>, // meta.query(value, Rotation::prev())
     pub value_prev_column: word::Word<Expression<F>>, // meta.query(prev_value, Rotation::cur())
+    pub init_val: word::Word<Expression<F>>,
 }
 
 #[derive(Clone)]
@@ -145,6 +146,9 @@ impl<F: Field> ConstraintBuilder<F> {
         self.condition(q.tag_matches(Target::TxLog), |cb| {
             cb.build_tx_log_constraints(q)
         });
+        self.condition(q.tag_matches(Target::MemoryWord), |cb| {
+            cb.build_memory_word_constraints(q)
+        });
     }
 
     fn build_general_constraints(&mut self, q: &Queries<F>) {
@@ -199,6 +203,14 @@ impl<F: Field> ConstraintBuilder<F> {
                 q.initial_value.lo() - q.initial_value_prev().lo(),
             );
         });
+
+        // The committed value is only defined for AccountStorage
+        self.condition(not::expr(q.tag_matches(Target::Storage)), |cb| {
+            cb.require_word_zero(
+                "init_val is 0 for other tags than AccountStorage",
+                q.rw_table.init_val.clone(),
+            );
+        });
     }
 
     fn build_start_constraints(&mut self, q: &Queries<F>) {
@@ -268,6 +280,40 @@ impl<F: Field> ConstraintBuilder<F> {
         );
     }
 
+    fn build_memory_word_constraints(&mut self, q: &Queries<F>) {
+        // Same as Memory, except that the value is a whole word and that the
+        // value_prev column holds the word before the access.  The address
+        // alignment is enforced by the EVM circuit MemoryGadget, which looks
+        // up the words at `32 * word_index` for a range checked `word_index`.
+        self.require_zero("field_tag is 0 for MemoryWord", q.field_tag());
+        self.require_word_zero(
+            "storage_key is 0 for MemoryWord",
+            q.rw_table.storage_key.clone(),
+        );
+        self.condition(q.first_access() * q.is_read(), |cb| {
+            cb.require_word_zero(
+                "first access for a set of all keys are 0 if READ (MemoryWord)",
+                q.value(),
+            );
+        });
+        for limb in &q.address.limbs[2..] {
+            self.require_zero("memory word address fits into 2 limbs", limb.clone());
+        }
+        self.require_word_zero("initial MemoryWord value is 0", q.initial_value());
+        self.require_word_equal(
+            "state_root is unchanged for MemoryWord",
+            q.state_root(),
+            q.state_root_prev(),
+        );
+        self.condition(q.not_first_access.clone(), |cb| {
+            cb.require_word_equal(
+                "value column at Rotation::prev() equals value_prev at Rotation::cur()",
+                q.rw_table.value_prev.clone(),
+                q.value_prev_column(),
+            );
+        });
+    }
+
     fn build_stack_constraints(&mut self, q: &Queries<F>) {
         // 3.0. Unused keys are 0
         self.require_zero("field_tag is 0 for Stack", q.field_tag());
@@ -319,6 +365,16 @@ impl<F: Field> ConstraintBuilder<F> {
                 + (1.expr() - is_non_exist) * MPTProofType::StorageChanged.expr(),
         );
 
+        // The committed value looked up by SLOAD/SSTORE is the value at the
+        // start of the tx: the initial value of the (tx_id, address,
+        // storage_key) group, which is constant in the group and is the old
+        // value of its MPT update.
+        self.require_word_equal(
+            "init_val is the committed value for AccountStorage",
+            q.rw_table.init_val.clone(),
+            q.initial_value(),
+        );
+
         // ref. spec 4.1. MPT lookup for last access to (address, storage_key)
         self.condition(q.last_access(), |cb| {
             cb.add_lookup(
@@ -78,6 +78,8 @@ pub(crate) enum AdviceColumn {
     ValueHi,
     ValuePrevLo,
     ValuePrevHi,
+    InitValLo,
+    _InitValHi,
     RwCounter,
     RwCounterLimb0,
     _RwCounterLimb1,
@@ -115,6 +117,8 @@ impl AdviceColumn {
             Self::ValueHi => config.rw_table.value.hi(),
             Self::ValuePrevLo => config.rw_table.value_prev.lo(),
             Self::ValuePrevHi => config.rw_table.value_prev.hi(),
+            Self::InitValLo => config.rw_table.init_val.lo(),
+            Self::_InitValHi => config.rw_table.init_val.hi(),
             Self::RwCounter => config.rw_table.rw_counter,
             Self::RwCounterLimb0 => config.sort_keys.rw_counter.limbs[0],
             Self::_RwCounterLimb1 => config.sort_keys.rw_counter.limbs[1],
@@ -411,6 +411,54 @@ fn storage_key_mismatch() {
     assert_error_matches(result, "mpi value matches claimed limbs");
 }
 
+#[test]
+fn storage_committed_value_mismatch() {
+    let rows = vec![
+        Rw::AccountStorage {
+            rw_counter: 1,
+            is_write: true,
+            account_address: Address::default(),
+            storage_key: U256::from(6),
+            value: U256::from(5),
+            value_prev: U256::from(3),
+            tx_id: 4,
+            committed_value: U256::from(3),
+        },
+        Rw::AccountStorage {
+            rw_counter: 2,
+            is_write: true,
+            account_address: Address::default(),
+            storage_key: U256::from(6),
+            value: U256::from(7),
+            value_prev: U256::from(5),
+            tx_id: 4,
+            committed_value: U256::from(3),
+        },
+    ];
+    // The committed value stays the value at the start of the tx
+    let overrides = HashMap::from([((AdviceColumn::InitValLo, 1), Fr::from(5))]);
+
+    let result = verify_with_overrides(rows, overrides);
+
+    assert_error_matches(result, "init_val is the committed value for AccountStorage");
+}
+
+#[test]
+fn init_val_nonzero_for_call_context() {
+    let rows = vec![Rw::CallContext {
+        rw_counter: 1,
+        is_write: false,
+        call_id: 0,
+        field_tag: CallContextFieldTag::TxId,
+        value: U256::zero(),
+    }];
+    let overrides = HashMap::from([((AdviceColumn::InitValLo, 0), Fr::ONE)]);
+
+    let result = verify_with_overrides(rows, overrides);
+
+    assert_error_matches(result, "init_val is 0 for other tags than AccountStorage");
+}
+
 #[test]
 fn is_write_nonbinary() {
     let rows = vec![Rw::CallContext {
@@ -669,6 +717,107 @@ fn skipped_start_rw_counter() {
     assert_error_matches(result, "rw_counter increases by 1 for every non-first row");
 }
 
+#[test]
+fn memory_word_ok() {
+    let value = U256::from(0x1234u64) << 128;
+    let rows = vec![
+        Rw::MemoryWord {
+            rw_counter: 1,
+            is_write: false,
+            call_id: 1,
+            memory_address: 0x20,
+            value: U256::zero(),
+            value_prev: U256::zero(),
+            shift: 0x10,
+        },
+        Rw::MemoryWord {
+            rw_counter: 2,
+            is_write: true,
+            call_id: 1,
+            memory_address: 0x40,
+            value,
+            value_prev: U256::zero(),
+            shift: 0x10,
+        },
+        Rw::MemoryWord {
+            rw_counter: 3,
+            is_write: false,
+            call_id: 1,
+            memory_address: 0x40,
+            value,
+            value_prev: value,
+            shift: 0,
+        },
+    ];
+
+    assert_eq!(verify_word_mode(rows), Ok(()));
+}
+
+#[test]
+fn memory_word_in_byte_mode() {
+    let rows = vec![Rw::MemoryWord {
+        rw_counter: 1,
+        is_write: true,
+        call_id: 1,
+        memory_address: 0x20,
+        value: U256::one(),
+        value_prev: U256::zero(),
+        shift: 0,
+    }];
+    let circuit = circuit(rows, HashMap::new(), MemoryOpMode::Byte);
+    let instance = circuit.instance();
+
+    assert!(MockProver::<Fr>::run(17, &circuit, instance).is_err());
+}
+
+#[test]
+fn invalid_memory_word_value_prev() {
+    let rows = vec![
+        Rw::MemoryWord {
+            rw_counter: 1,
+            is_write: true,
+            call_id: 1,
+            memory_address: 0x20,
+            value: U256::one(),
+            value_prev: U256::zero(),
+            shift: 0,
+        },
+        Rw::MemoryWord {
+            rw_counter: 2,
+            is_write: true,
+            call_id: 1,
+            memory_address: 0x20,
+            value: U256::from(2u64),
+            // the word is 1 after the first write
+            value_prev: U256::zero(),
+            shift: 0,
+        },
+    ];
+
+    assert_error_matches(
+        verify_word_mode(rows),
+        "value column at Rotation::prev() equals value_prev at Rotation::cur()",
+    );
+}
+
+#[test]
+fn invalid_memory_word_address() {
+    let rows = vec![Rw::MemoryWord {
+        rw_counter: 1,
+        is_write: true,
+        call_id: 1,
+        memory_address: 1u64 << 32,
+        value: U256::from(1u64) << 200,
+        value_prev: U256::zero(),
+        shift: 0,
+    }];
+
+    assert_error_matches(
+        verify_word_mode(rows),
+        "memory word address fits into 2 limbs",
+    );
+}
+
 #[test]
 fn invalid_memory_address() {
     let rows = vec![Rw::Memory {
@@ -929,6 +1078,7 @@ fn variadic_size_check() {
         updates,
         overrides: HashMap::default(),
         n_rows: N_ROWS,
+        memory_op_mode: MemoryOpMode::default(),
         _marker: std::marker::PhantomData::default(),
     };
     let power_of_randomness = circuit.instance();
@@ -957,6 +1107,7 @@ fn variadic_size_check() {
         updates,
         overrides: HashMap::default(),
         n_rows: N_ROWS,
+        memory_op_mode: MemoryOpMode::default(),
         _marker: std::marker::PhantomData::default(),
     };
     let power_of_randomness = circuit.instance();
@@ -990,15 +1141,24 @@ fn bad_initial_tx_receipt_value() {
     );
 }
 
-fn prover(rows: Vec<Rw>, overrides: HashMap<(AdviceColumn, isize), Fr>) -> MockProver<Fr> {
+fn circuit(
+    rows: Vec<Rw>,
+    overrides: HashMap<(AdviceColumn, isize), Fr>,
+    memory_op_mode: MemoryOpMode,
+) -> StateCircuit<Fr> {
     let updates = MptUpdates::mock_from(&rows);
-    let circuit = StateCircuit::<Fr> {
+    StateCircuit::<Fr> {
         rows,
         updates,
         overrides,
         n_rows: N_ROWS,
+        memory_op_mode,
         _marker: std::marker::PhantomData::default(),
-    };
+    }
+}
+
+fn prover(rows: Vec<Rw>, overrides: HashMap<(AdviceColumn, isize), Fr>) -> MockProver<Fr> {
+    let circuit = circuit(rows, overrides, MemoryOpMode::Byte);
     let instance = circuit.instance();
 
     MockProver::<Fr>::run(17, &circuit, instance).unwrap()
@@ -1010,6 +1170,16 @@ fn verify(rows: Vec<Rw>) -> Result<(), Vec<VerifyFailure>> {
         .verify_at_rows(N_ROWS - used_rows..N_ROWS, N_ROWS - used_rows..N_ROWS)
 }
 
+fn verify_word_mode(rows: Vec<Rw>) -> Result<(), Vec<VerifyFailure>> {
+    let used_rows = rows.len();
+    let circuit = circuit(rows, HashMap::new(), MemoryOpMode::Word);
+    let instance = circuit.instance();
+
+    MockProver::<Fr>::run(17, &circuit, instance)
+        .unwrap()
+        .verify_at_rows(N_ROWS - used_rows..N_ROWS, N_ROWS - used_rows..N_ROWS)
+}
+
 fn verify_with_overrides(
     rows: Vec<Rw>,
     overrides: HashMap<(AdviceColumn, isize), Fr>,
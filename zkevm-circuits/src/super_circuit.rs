@@ -53,12 +53,16 @@
 #[cfg(test)]
 pub(crate) mod test;
 
+#[cfg(feature = "poseidon-codehash")]
+use crate::table::PoseidonTable;
+#[cfg(feature = "witness")]
+use crate::witness::{block_convert, choose_k};
 use crate::{
     bytecode_circuit::circuit::{
         BytecodeCircuit, BytecodeCircuitConfig, BytecodeCircuitConfigArgs,
     },
     copy_circuit::{CopyCircuit, CopyCircuitConfig, CopyCircuitConfigArgs},
-    evm_circuit::{EvmCircuit, EvmCircuitConfig, EvmCircuitConfigArgs},
+    evm_circuit::{EvmCircuit, EvmCircuitConfig, EvmCircuitConfigArgs, FeatureConfig},
     exp_circuit::{ExpCircuit, ExpCircuitConfig},
     keccak_circuit::{KeccakCircuit, KeccakCircuitConfig, KeccakCircuitConfigArgs},
     pi_circuit::{PiCircuit, PiCircuitConfig, PiCircuitConfigArgs},
@@ -68,14 +72,15 @@ use crate::{
         UXTable,
     },
     tx_circuit::{TxCircuit, TxCircuitConfig, TxCircuitConfigArgs},
-    util::{log2_ceil, Challenges, SubCircuit, SubCircuitConfig},
-    witness::{block_convert, Block, MptUpdates},
+    util::{Challenges, SubCircuit, SubCircuitConfig},
+    witness::{Block, MptUpdates},
 };
-use bus_mapping::{
-    circuit_input_builder::{CircuitInputBuilder, FixedCParams},
-    mock::BlockData,
-};
-use eth_types::{geth_types::GethData, Field};
+use bus_mapping::circuit_input_builder::FixedCParams;
+#[cfg(feature = "witness")]
+use bus_mapping::{circuit_input_builder::CircuitInputBuilder, mock::BlockData};
+#[cfg(feature = "witness")]
+use eth_types::geth_types::GethData;
+use eth_types::Field;
 use halo2_proofs::{
     circuit::{Layouter, SimpleFloorPlanner, Value},
     plonk::{Circuit, ConstraintSystem, Error, Expression},
@@ -88,6 +93,8 @@ use std::array;
 pub struct SuperCircuitConfig<F: Field> {
     block_table: BlockTable,
     mpt_table: MptTable,
+    #[cfg(feature = "poseidon-codehash")]
+    poseidon_table: PoseidonTable,
     u8_table: UXTable<8>,
     u10_table: UXTable<10>,
     u16_table: UXTable<16>,
@@ -109,6 +116,8 @@ pub struct SuperCircuitConfigArgs<F: Field> {
     pub max_calldata: usize,
     /// Mock randomness
     pub mock_randomness: F,
+    /// Features of the EVM circuit
+    pub feature_config: FeatureConfig,
 }
 
 impl<F: Field> SubCircuitConfig<F> for SuperCircuitConfig<F> {
@@ -121,6 +130,7 @@ impl<F: Field> SubCircuitConfig<F> for SuperCircuitConfig<F> {
             max_txs,
             max_calldata,
             mock_randomness,
+            feature_config,
         }: Self::ConfigArgs,
     ) -> Self {
         let tx_table = TxTable::construct(meta);
@@ -132,6 +142,8 @@ impl<F: Field> SubCircuitConfig<F> for SuperCircuitConfig<F> {
         let copy_table = CopyTable::construct(meta, q_copy_table);
         let exp_table = ExpTable::construct(meta);
         let keccak_table = KeccakTable::construct(meta);
+        #[cfg(feature = "poseidon-codehash")]
+        let poseidon_table = PoseidonTable::construct(meta);
         let u8_table = UXTable::construct(meta);
         let u10_table = UXTable::construct(meta);
         let u16_table = UXTable::construct(meta);
@@ -144,6 +156,7 @@ impl<F: Field> SubCircuitConfig<F> for SuperCircuitConfig<F> {
         let challenges = Challenges::mock(
             power_of_randomness[0].clone(),
             power_of_randomness[0].clone(),
+            power_of_randomness[0].clone(),
         );
 
         let keccak_circuit = KeccakCircuitConfig::new(
@@ -178,6 +191,8 @@ impl<F: Field> SubCircuitConfig<F> for SuperCircuitConfig<F> {
             BytecodeCircuitConfigArgs {
                 bytecode_table: bytecode_table.clone(),
                 keccak_table: keccak_table.clone(),
+                #[cfg(feature = "poseidon-codehash")]
+                poseidon_table: poseidon_table.clone(),
                 challenges: challenges.clone(),
             },
         );
@@ -188,6 +203,7 @@ impl<F: Field> SubCircuitConfig<F> for SuperCircuitConfig<F> {
                 rw_table,
                 bytecode_table: bytecode_table.clone(),
                 copy_table,
+                u8_table,
                 q_enable: q_copy_table,
                 challenges: challenges.clone(),
             },
@@ -217,12 +233,15 @@ impl<F: Field> SubCircuitConfig<F> for SuperCircuitConfig<F> {
                 exp_table,
                 u8_table,
                 u16_table,
+                feature_config,
             },
         );
 
         Self {
             block_table,
             mpt_table,
+            #[cfg(feature = "poseidon-codehash")]
+            poseidon_table,
             u8_table,
             u10_table,
             u16_table,
@@ -343,6 +362,18 @@ impl<F: Field> SubCircuit<F> for SuperCircuit<F> {
         let exp = ExpCircuit::min_num_rows_block(block);
         let pi = PiCircuit::min_num_rows_block(block);
 
+        for (circuit, (rows, rows_with_padding)) in [
+            ("evm", evm),
+            ("state", state),
+            ("bytecode", bytecode),
+            ("copy", copy),
+            ("keccak", keccak),
+            ("tx", tx),
+            ("exp", exp),
+            ("pi", pi),
+        ] {
+            tracing::debug!(circuit, rows, rows_with_padding, "rows of the block");
+        }
         let rows: Vec<(usize, usize)> = vec![evm, state, bytecode, copy, keccak, tx, exp, pi];
         let (rows_without_padding, rows_with_padding): (Vec<usize>, Vec<usize>) =
             rows.into_iter().unzip();
@@ -359,32 +390,85 @@ impl<F: Field> SubCircuit<F> for SuperCircuit<F> {
         challenges: &Challenges<Value<F>>,
         layouter: &mut impl Layouter<F>,
     ) -> Result<(), Error> {
-        self.keccak_circuit
-            .synthesize_sub(&config.keccak_circuit, challenges, layouter)?;
-        self.bytecode_circuit
-            .synthesize_sub(&config.bytecode_circuit, challenges, layouter)?;
-        self.tx_circuit
-            .synthesize_sub(&config.tx_circuit, challenges, layouter)?;
-        self.state_circuit
-            .synthesize_sub(&config.state_circuit, challenges, layouter)?;
-        self.copy_circuit
-            .synthesize_sub(&config.copy_circuit, challenges, layouter)?;
-        self.exp_circuit
-            .synthesize_sub(&config.exp_circuit, challenges, layouter)?;
-        self.evm_circuit
-            .synthesize_sub(&config.evm_circuit, challenges, layouter)?;
-        self.pi_circuit
-            .synthesize_sub(&config.pi_circuit, challenges, layouter)?;
-        Ok(())
+        synthesize_sub_traced(
+            "keccak",
+            &self.keccak_circuit,
+            &config.keccak_circuit,
+            challenges,
+            layouter,
+        )?;
+        synthesize_sub_traced(
+            "bytecode",
+            &self.bytecode_circuit,
+            &config.bytecode_circuit,
+            challenges,
+            layouter,
+        )?;
+        synthesize_sub_traced(
+            "tx",
+            &self.tx_circuit,
+            &config.tx_circuit,
+            challenges,
+            layouter,
+        )?;
+        synthesize_sub_traced(
+            "state",
+            &self.state_circuit,
+            &config.state_circuit,
+            challenges,
+            layouter,
+        )?;
+        synthesize_sub_traced(
+            "copy",
+            &self.copy_circuit,
+            &config.copy_circuit,
+            challenges,
+            layouter,
+        )?;
+        synthesize_sub_traced(
+            "exp",
+            &self.exp_circuit,
+            &config.exp_circuit,
+            challenges,
+            layouter,
+        )?;
+        synthesize_sub_traced(
+            "evm",
+            &self.evm_circuit,
+            &config.evm_circuit,
+            challenges,
+            layouter,
+        )?;
+        synthesize_sub_traced(
+            "pi",
+            &self.pi_circuit,
+            &config.pi_circuit,
+            challenges,
+            layouter,
+        )
     }
 }
 
+/// Synthesize the sub circuit `circuit` in a span named after it, so that the
+/// time spent in each sub circuit shows up in the logs.
+fn synthesize_sub_traced<F: Field, C: SubCircuit<F>>(
+    name: &'static str,
+    circuit: &C,
+    config: &C::Config,
+    challenges: &Challenges<Value<F>>,
+    layouter: &mut impl Layouter<F>,
+) -> Result<(), Error> {
+    let _span = tracing::info_span!("synthesize", circuit = name).entered();
+    circuit.synthesize_sub(config, challenges, layouter)
+}
+
 /// Super Circuit configuration parameters
 #[derive(Default)]
 pub struct SuperCircuitParams<F: Field> {
     max_txs: usize,
     max_calldata: usize,
     mock_randomness: F,
+    feature_config: FeatureConfig,
 }
 
 impl<F: Field> Circuit<F> for SuperCircuit<F> {
@@ -401,6 +485,7 @@ impl<F: Field> Circuit<F> for SuperCircuit<F> {
             max_txs: self.circuits_params.max_txs,
             max_calldata: self.circuits_params.max_calldata,
             mock_randomness: self.mock_randomness,
+            feature_config: self.evm_circuit.params(),
         }
     }
 
@@ -411,6 +496,7 @@ impl<F: Field> Circuit<F> for SuperCircuit<F> {
                 max_txs: params.max_txs,
                 max_calldata: params.max_calldata,
                 mock_randomness: params.mock_randomness,
+                feature_config: params.feature_config,
             },
         )
     }
@@ -428,6 +514,7 @@ impl<F: Field> Circuit<F> for SuperCircuit<F> {
         let challenges = Challenges::mock(
             Value::known(block.randomness),
             Value::known(block.randomness),
+            Value::known(block.randomness),
         );
         let rws = &self.state_circuit.rows;
 
@@ -437,6 +524,17 @@ impl<F: Field> Circuit<F> for SuperCircuit<F> {
             .mpt_table
             .load(&mut layouter, &MptUpdates::mock_from(rws))?;
 
+        #[cfg(feature = "poseidon-codehash")]
+        config.poseidon_table.dev_load(
+            &mut layouter,
+            &block
+                .bytecodes
+                .iter()
+                .map(|(_, code)| code.to_vec())
+                .collect::<Vec<_>>(),
+            &challenges,
+        )?;
+
         config.u8_table.load(&mut layouter)?;
         config.u10_table.load(&mut layouter)?;
         config.u16_table.load(&mut layouter)?;
@@ -445,6 +543,7 @@ impl<F: Field> Circuit<F> for SuperCircuit<F> {
     }
 }
 
+#[cfg(feature = "witness")]
 impl<F: Field> SuperCircuit<F> {
     /// From the witness data, generate a SuperCircuit instance with all of the
     /// sub-circuits filled with their corresponding witnesses.
@@ -481,9 +580,16 @@ impl<F: Field> SuperCircuit<F> {
         let mut block = block_convert(builder).unwrap();
         block.randomness = mock_randomness;
 
-        let (_, rows_needed) = Self::min_num_rows_block(&block);
-        let k = log2_ceil(Self::unusable_rows() + rows_needed);
+        // A block overflowing the capacity of the sub-circuits is still
+        // built, for the MockProver to report the failing constraints.
+        let k = choose_k(&block, &block.circuits_params).unwrap_or_else(|err| {
+            log::warn!("{}", err);
+            err.k
+        });
         log::debug!("super circuit uses k = {}", k);
+        for (name, rows) in block.estimate_rows().entries() {
+            log::debug!("super circuit {} rows: {}", name, rows);
+        }
 
         let circuit = SuperCircuit::new_from_block(&block);
 
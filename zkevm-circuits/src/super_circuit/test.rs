@@ -1,4 +1,5 @@
 pub use super::*;
+use crate::witness::{choose_k, RowEstimate};
 use ethers_signers::{LocalWallet, Signer};
 use halo2_proofs::{dev::MockProver, halo2curves::bn256::Fr};
 use log::error;
@@ -16,6 +17,7 @@ fn super_circuit_degree() {
         max_txs: 1,
         max_calldata: 32,
         mock_randomness: Fr::from(0x100),
+        feature_config: FeatureConfig::default(),
     };
     SuperCircuit::configure_with_params(&mut cs, params);
     log::info!("super circuit degree: {}", cs.degree());
@@ -137,9 +139,95 @@ fn serial_test_super_circuit_1tx_1max_tx() {
         max_bytecode: 512,
         max_evm_rows: 0,
         max_keccak_rows: 0,
+        anchor_tx: None,
     };
     test_super_circuit(block, circuits_params, Fr::from(TEST_MOCK_RANDOMNESS));
 }
+fn witness_block_1tx(circuits_params: FixedCParams) -> Block<Fr> {
+    let block = block_1tx();
+    let mut builder = BlockData::new_from_geth_data_with_params(block.clone(), circuits_params)
+        .new_circuit_input_builder();
+    builder
+        .handle_block(&block.eth_block, &block.geth_traces)
+        .unwrap();
+    block_convert::<Fr>(&builder).unwrap()
+}
+
+#[test]
+fn serial_test_super_circuit_1tx_row_estimate() {
+    let circuits_params = FixedCParams {
+        max_txs: 1,
+        max_calldata: 32,
+        max_rws: 256,
+        max_copy_rows: 256,
+        max_exp_steps: 256,
+        max_bytecode: 512,
+        max_evm_rows: 0,
+        max_keccak_rows: 0,
+        anchor_tx: None,
+    };
+    let block = witness_block_1tx(circuits_params);
+
+    let estimate = block.estimate_rows();
+    assert_eq!(estimate.rw_rows, block.rws.0.values().flatten().count() + 1);
+    assert!(estimate.bytecode_rows > 0);
+    assert!(estimate.mpt_rows > 0);
+    assert!(estimate
+        .entries()
+        .iter()
+        .all(|(_, rows)| *rows <= estimate.max_rows()));
+
+    let capacity = block.row_capacity();
+    assert_eq!(capacity.rw_rows, 256);
+    assert!(estimate.overflows(&capacity).is_empty());
+    let capacity = RowEstimate {
+        rw_rows: estimate.rw_rows - 1,
+        ..capacity
+    };
+    assert_eq!(
+        estimate.overflows(&capacity),
+        vec![("rw", estimate.rw_rows, estimate.rw_rows - 1)]
+    );
+}
+
+#[test]
+fn serial_test_super_circuit_1tx_choose_k() {
+    let circuits_params = FixedCParams {
+        max_txs: 1,
+        max_calldata: 32,
+        max_rws: 256,
+        max_copy_rows: 256,
+        max_exp_steps: 256,
+        max_bytecode: 512,
+        max_evm_rows: 0,
+        max_keccak_rows: 0,
+        anchor_tx: None,
+    };
+    let block = witness_block_1tx(circuits_params);
+
+    let k = choose_k(&block, &circuits_params).unwrap();
+    let (_, rows) = SuperCircuit::<Fr>::min_num_rows_block(&block);
+    assert!(rows + SuperCircuit::<Fr>::unusable_rows() <= 1 << k);
+    // The 16 bits range table doesn't fit in 2^16 rows with the unusable ones
+    assert!(k >= 17);
+
+    // More capacity requires a larger circuit
+    let params = FixedCParams {
+        max_rws: 1 << k,
+        ..circuits_params
+    };
+    assert_eq!(choose_k(&block, &params), Ok(k + 1));
+
+    // Too little capacity for the rws
+    let params = FixedCParams {
+        max_rws: 16,
+        ..circuits_params
+    };
+    let err = choose_k(&block, &params).unwrap_err();
+    assert_eq!(err.rows.overflows(&err.capacity)[0].0, "rw");
+    assert!(err.to_string().contains(", capacity 16 (overflow)"));
+}
+
 #[ignore]
 #[test]
 fn serial_test_super_circuit_1tx_2max_tx() {
@@ -153,6 +241,7 @@ fn serial_test_super_circuit_1tx_2max_tx() {
         max_bytecode: 512,
         max_evm_rows: 0,
         max_keccak_rows: 0,
+        anchor_tx: None,
     };
     test_super_circuit(block, circuits_params, Fr::from(TEST_MOCK_RANDOMNESS));
 }
@@ -169,6 +258,7 @@ fn serial_test_super_circuit_2tx_2max_tx() {
         max_bytecode: 512,
         max_evm_rows: 0,
         max_keccak_rows: 0,
+        anchor_tx: None,
     };
     test_super_circuit(block, circuits_params, Fr::from(TEST_MOCK_RANDOMNESS));
 }
@@ -24,6 +24,7 @@ use halo2_proofs::{
     poly::Rotation,
 };
 use itertools::Itertools;
+use serde::{Deserialize, Serialize};
 use std::array;
 use strum_macros::{EnumCount, EnumIter};
 
@@ -39,6 +40,9 @@ pub(crate) mod exp_table;
 pub(crate) mod keccak_table;
 /// mpt table
 pub(crate) mod mpt_table;
+/// poseidon table
+#[cfg(feature = "poseidon-codehash")]
+pub(crate) mod poseidon_table;
 /// rw table
 pub(crate) mod rw_table;
 /// tx table
@@ -51,6 +55,8 @@ pub(crate) use bytecode_table::{BytecodeFieldTag, BytecodeTable};
 pub(crate) use copy_table::CopyTable;
 pub(crate) use exp_table::ExpTable;
 pub(crate) use keccak_table::KeccakTable;
+#[cfg(feature = "poseidon-codehash")]
+pub(crate) use poseidon_table::PoseidonTable;
 pub(crate) use ux_table::UXTable;
 
 pub(crate) use mpt_table::{MPTProofType, MptTable};
@@ -120,7 +126,9 @@ impl<F: Field, C: Into<Column<Any>> + Copy, const W: usize> LookupTable<F> for [
 }
 
 /// Tag for an AccountField in RwTable
-#[derive(Clone, Copy, Debug, EnumIter, Hash, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(
+    Clone, Copy, Debug, EnumIter, Hash, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize,
+)]
 pub enum AccountFieldTag {
     /// Nonce field
     Nonce = 1,
@@ -134,7 +142,7 @@ pub enum AccountFieldTag {
 impl_expr!(AccountFieldTag);
 
 /// Tag for a CallContextField in RwTable
-#[derive(Clone, Copy, Debug, PartialEq, Eq, EnumIter)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, EnumIter, Serialize, Deserialize)]
 pub enum CallContextFieldTag {
     /// RwCounterEndOfReversion
     RwCounterEndOfReversion = 1,
@@ -190,3 +198,35 @@ pub enum CallContextFieldTag {
     ReversibleWriteCounter,
 }
 impl_expr!(CallContextFieldTag);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use halo2_proofs::halo2curves::bn256::Fr;
+
+    fn assert_annotated<T: LookupTable<Fr>>(name: &str, table: &T) {
+        assert_eq!(
+            table.columns().len(),
+            table.annotations().len(),
+            "{} columns and annotations differ",
+            name
+        );
+    }
+
+    #[test]
+    fn tables_annotate_all_columns() {
+        let mut meta = ConstraintSystem::<Fr>::default();
+        let q_enable = meta.fixed_column();
+        assert_annotated("block", &BlockTable::construct(&mut meta));
+        assert_annotated("bytecode", &BytecodeTable::construct(&mut meta));
+        assert_annotated("copy", &CopyTable::construct(&mut meta, q_enable));
+        assert_annotated("exp", &ExpTable::construct(&mut meta));
+        assert_annotated("keccak", &KeccakTable::construct(&mut meta));
+        assert_annotated("mpt", &MptTable::construct(&mut meta));
+        #[cfg(feature = "poseidon-codehash")]
+        assert_annotated("poseidon", &PoseidonTable::construct(&mut meta));
+        assert_annotated("rw", &RwTable::construct(&mut meta));
+        assert_annotated("tx", &TxTable::construct(&mut meta));
+        assert_annotated("u8", &UXTable::<8>::construct(&mut meta));
+    }
+}
@@ -1,6 +1,7 @@
 use super::*;
 use crate::util;
 use bus_mapping::state_db::CodeDB;
+use gadgets::util::Expr;
 
 /// Tag to identify the field in a Bytecode Table row
 #[derive(Clone, Copy, Debug)]
@@ -41,6 +42,35 @@ impl BytecodeTable {
         }
     }
 
+    /// Returns the lookup input expressions for a row of the table, following
+    /// the table column order.
+    pub fn lookup_exprs<F: Field>(
+        code_hash: word::Word<Expression<F>>,
+        tag: Expression<F>,
+        index: Expression<F>,
+        is_code: Expression<F>,
+        value: Expression<F>,
+    ) -> Vec<Expression<F>> {
+        vec![code_hash.lo(), code_hash.hi(), tag, index, is_code, value]
+    }
+
+    /// Returns the lookup input expressions for the byte at `index` of the
+    /// bytecode with `code_hash`, together with whether it is an opcode.
+    pub fn lookup_code<F: Field>(
+        code_hash: word::Word<Expression<F>>,
+        index: Expression<F>,
+        byte: Expression<F>,
+        is_code: Expression<F>,
+    ) -> Vec<Expression<F>> {
+        Self::lookup_exprs(
+            code_hash,
+            BytecodeFieldTag::Byte.expr(),
+            index,
+            is_code,
+            byte,
+        )
+    }
+
     /// Assign the `BytecodeTable` from a list of bytecodes, followig the same
     /// table layout that the Bytecode Circuit uses.
     pub fn load<F: Field>(
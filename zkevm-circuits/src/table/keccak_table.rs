@@ -105,7 +105,11 @@ impl KeccakTable {
                 offset += 1;
 
                 let keccak_table_columns = <KeccakTable as LookupTable<F>>::advice_columns(self);
-                for input in inputs.clone() {
+                // Like in the keccak circuit, the empty input hash always comes first
+                let empty_input: &[u8] = &[];
+                for input in std::iter::once(empty_input)
+                    .chain(inputs.clone().into_iter().map(Vec::as_slice))
+                {
                     for row in Self::assignments(input, challenges) {
                         // let mut column_index = 0;
                         for (&column, value) in keccak_table_columns.iter().zip_eq(row) {
@@ -22,9 +22,10 @@ pub enum MPTProofType {
     AccountDestructed,
     /// Account does not exist
     AccountDoesNotExist,
-    /// Storage updated
+    /// Storage updated, a `0` value means the slot doesn't exist (it's created
+    /// or deleted)
     StorageChanged,
-    /// Storage does not exist
+    /// Storage does not exist before nor after, both values are `0`
     StorageDoesNotExist,
 }
 impl_expr!(MPTProofType);
@@ -100,7 +101,7 @@ impl<F: Field> LookupTable<F> for MptTable {
 
 impl MptTable {
     /// Construct a new MptTable
-    pub(crate) fn construct<F: Field>(meta: &mut ConstraintSystem<F>) -> Self {
+    pub fn construct<F: Field>(meta: &mut ConstraintSystem<F>) -> Self {
         Self {
             address: meta.advice_column(),
             storage_key: word::Word::new([meta.advice_column(), meta.advice_column()]),
@@ -166,7 +167,9 @@ impl MptTable {
         Ok(())
     }
 
-    pub(crate) fn load<F: Field>(
+    /// Load the MPT table with the updates of the block, so that circuits
+    /// looking up into it can be tested without the MPT circuit.
+    pub fn load<F: Field>(
         &self,
         layouter: &mut impl Layouter<F>,
         updates: &MptUpdates,
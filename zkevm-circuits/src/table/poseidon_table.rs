@@ -0,0 +1,133 @@
+use super::*;
+use bus_mapping::state_db::poseidon_code_hash;
+
+/// Poseidon Table, used to verify the poseidon code hash from RLC'ed input.
+///
+/// It has the layout of the [`KeccakTable`], so that the bytecode circuit
+/// looks up either of them with the same columns.
+///
+/// No circuit constrains this table yet: its rows are only assigned by
+/// [`PoseidonTable::dev_load`], like the [`MptTable`] of the super circuit.
+/// The code hashes looked up with the `poseidon-codehash` feature are thus not
+/// proven to be poseidon hashes.
+#[derive(Clone, Debug)]
+pub struct PoseidonTable {
+    /// True when the row is enabled
+    pub is_enabled: Column<Advice>,
+    /// Byte array input as `RLC(reversed(input))`
+    pub input_rlc: Column<Advice>,
+    /// Byte array input length
+    pub input_len: Column<Advice>,
+    /// Output hash word
+    pub output: word::Word<Column<Advice>>,
+}
+
+impl<F: Field> LookupTable<F> for PoseidonTable {
+    fn columns(&self) -> Vec<Column<Any>> {
+        vec![
+            self.is_enabled.into(),
+            self.input_rlc.into(),
+            self.input_len.into(),
+            self.output.lo().into(),
+            self.output.hi().into(),
+        ]
+    }
+
+    fn annotations(&self) -> Vec<String> {
+        vec![
+            String::from("poseidon_is_enabled"),
+            String::from("poseidon_input_rlc"),
+            String::from("poseidon_input_len"),
+            String::from("poseidon_output_lo"),
+            String::from("poseidon_output_hi"),
+        ]
+    }
+}
+
+impl PoseidonTable {
+    /// Construct a new PoseidonTable
+    pub fn construct<F: Field>(meta: &mut ConstraintSystem<F>) -> Self {
+        Self {
+            is_enabled: meta.advice_column(),
+            input_rlc: meta.advice_column_in(SecondPhase),
+            input_len: meta.advice_column(),
+            output: word::Word::new([meta.advice_column(), meta.advice_column()]),
+        }
+    }
+
+    /// Generate the poseidon table assignments from a byte array input.
+    pub fn assignments<F: Field>(
+        input: &[u8],
+        challenges: &Challenges<Value<F>>,
+    ) -> Vec<[Value<F>; 5]> {
+        let input_rlc = challenges
+            .keccak_input()
+            .map(|challenge| rlc::value(input.iter().rev(), challenge));
+        let input_len = F::from(input.len() as u64);
+        let output = word::Word::from(poseidon_code_hash(input));
+
+        vec![[
+            Value::known(F::ONE),
+            input_rlc,
+            Value::known(input_len),
+            Value::known(output.lo()),
+            Value::known(output.hi()),
+        ]]
+    }
+
+    /// Provide this function for the case that we want to consume a poseidon
+    /// table but without running the full poseidon circuit
+    pub fn dev_load<'a, F: Field>(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        inputs: impl IntoIterator<Item = &'a Vec<u8>> + Clone,
+        challenges: &Challenges<Value<F>>,
+    ) -> Result<(), Error> {
+        layouter.assign_region(
+            || "poseidon table",
+            |mut region| {
+                let columns = <PoseidonTable as LookupTable<F>>::advice_columns(self);
+                for &column in columns.iter() {
+                    region.assign_advice(
+                        || "poseidon table all-zero row",
+                        column,
+                        0,
+                        || Value::known(F::ZERO),
+                    )?;
+                }
+
+                let rows = inputs
+                    .clone()
+                    .into_iter()
+                    .flat_map(|input| Self::assignments(input, challenges));
+                for (offset, row) in rows.enumerate() {
+                    for (&column, value) in columns.iter().zip_eq(row) {
+                        region.assign_advice(
+                            || format!("poseidon table row {}", offset + 1),
+                            column,
+                            offset + 1,
+                            || value,
+                        )?;
+                    }
+                }
+                Ok(())
+            },
+        )
+    }
+
+    /// returns matchings between the circuit columns passed as parameters and
+    /// the table collumns
+    pub fn match_columns(
+        &self,
+        value_rlc: Column<Advice>,
+        length: Column<Advice>,
+        code_hash: Word<Column<Advice>>,
+    ) -> Vec<(Column<Advice>, Column<Advice>)> {
+        vec![
+            (value_rlc, self.input_rlc),
+            (length, self.input_len),
+            (code_hash.lo(), self.output.lo()),
+            (code_hash.hi(), self.output.hi()),
+        ]
+    }
+}
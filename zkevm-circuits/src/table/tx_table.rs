@@ -29,11 +29,18 @@ pub enum TxFieldTag {
     TxSignHash,
     /// CallData
     CallData,
+    /// CallDataOffset: offset of the first call data byte of the tx in the
+    /// call data region of the table, where the call data of all the txs are
+    /// concatenated (0 for padding txs).
+    CallDataOffset,
+    /// TxHash: Hash of the signed transaction, keccak(rlp_signed) (keccak of
+    /// the empty input for padding txs).
+    TxHash,
 }
 impl_expr!(TxFieldTag);
 
 /// Tag for a TxLogField in RwTable
-#[derive(Clone, Copy, Debug, PartialEq, Eq, EnumIter)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, EnumIter, Serialize, Deserialize)]
 pub enum TxLogFieldTag {
     /// Address field
     Address = 1,
@@ -41,11 +48,15 @@ pub enum TxLogFieldTag {
     Topic,
     /// Data field
     Data,
+    /// Topic length field
+    TopicLength,
+    /// Data length field
+    DataLength,
 }
 impl_expr!(TxLogFieldTag);
 
 /// Tag for a TxReceiptField in RwTable
-#[derive(Clone, Copy, Debug, PartialEq, Eq, EnumIter, EnumCount)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, EnumIter, EnumCount, Serialize, Deserialize)]
 pub enum TxReceiptFieldTag {
     /// Tx result
     PostStateOrStatus = 1,
@@ -91,6 +102,7 @@ impl TxTable {
         txs: &[Transaction],
         max_txs: usize,
         max_calldata: usize,
+        chain_id: u64,
     ) -> Result<(), Error> {
         assert!(
             txs.len() <= max_txs,
@@ -152,6 +164,7 @@ impl TxTable {
                 // the tx calldata.  This is required to achieve a constant fixed column tag
                 // regardless of the number of input txs or the calldata size of each tx.
                 let mut calldata_assignments: Vec<[Value<F>; 5]> = Vec::new();
+                let mut calldata_offset = 0;
                 // Assign Tx data (all tx fields except for calldata)
                 let padding_txs: Vec<_> = (txs.len()..max_txs)
                     .map(|i| Transaction::padding_tx(i + 1))
@@ -183,6 +196,22 @@ impl TxTable {
                             TxContextFieldTag::CallDataGasCost,
                             word::Word::from(tx.call_data_gas_cost()),
                         ),
+                        (
+                            TxContextFieldTag::CallDataOffset,
+                            word::Word::from(if tx.id as usize <= txs.len() {
+                                calldata_offset as u64
+                            } else {
+                                0
+                            }),
+                        ),
+                        (
+                            TxContextFieldTag::TxHash,
+                            if tx.id as usize <= txs.len() {
+                                word::Word::from(tx.hash(chain_id))
+                            } else {
+                                word::Word::from(keccak(&[]))
+                            },
+                        ),
                     ]
                     .iter()
                     .map(|&(tag, word)| {
@@ -213,6 +242,7 @@ impl TxTable {
                         assign_row(&mut region, offset, &advice_columns, &self.tag, &row, "")?;
                         offset += 1;
                     }
+                    calldata_offset += tx_calldata.len();
                     calldata_assignments.extend(tx_calldata.iter());
                 }
                 // Assign Tx calldata
@@ -1,4 +1,5 @@
 use super::*;
+use gadgets::range_check_chip::RangeCheckConfig;
 
 /// Lookup table for max n bits range check
 #[derive(Clone, Copy, Debug)]
@@ -34,6 +35,12 @@ impl<const N_BITS: usize> UXTable<N_BITS> {
     }
 }
 
+impl<const N_BITS: usize> From<UXTable<N_BITS>> for RangeCheckConfig<N_BITS> {
+    fn from(table: UXTable<N_BITS>) -> Self {
+        RangeCheckConfig { table: table.col }
+    }
+}
+
 impl<F: Field, const N_BITS: usize> LookupTable<F> for UXTable<N_BITS> {
     fn columns(&self) -> Vec<Column<Any>> {
         vec![self.col.into()]
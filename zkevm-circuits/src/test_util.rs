@@ -6,14 +6,24 @@ use crate::{
     util::SubCircuit,
     witness::{Block, Rw},
 };
-use bus_mapping::{circuit_input_builder::FixedCParams, mock::BlockData};
+use bus_mapping::{
+    circuit_input_builder::{CircuitInputBuilder, DynamicCParams, FixedCParams},
+    mock::BlockData,
+};
 use eth_types::geth_types::GethData;
-use std::cmp;
+use std::{cmp, path::Path};
 
 use crate::util::log2_ceil;
-use halo2_proofs::{dev::MockProver, halo2curves::bn256::Fr};
+use halo2_proofs::{
+    dev::{MockProver, VerifyFailure},
+    halo2curves::bn256::Fr,
+    plonk::Circuit,
+};
 use mock::TestContext;
 
+pub mod failures;
+pub mod golden;
+
 #[cfg(test)]
 #[ctor::ctor]
 fn init_env_logger() {
@@ -81,6 +91,8 @@ pub struct CircuitTestBuilder<const NACC: usize, const NTX: usize> {
     evm_checks: Box<dyn Fn(MockProver<Fr>, &Vec<usize>, &Vec<usize>)>,
     state_checks: Box<dyn Fn(MockProver<Fr>, &Vec<usize>, &Vec<usize>)>,
     block_modifiers: Vec<Box<dyn Fn(&mut Block<Fr>)>>,
+    input_builder_modifiers: Vec<Box<dyn Fn(&mut CircuitInputBuilder<DynamicCParams>)>>,
+    rw_table_golden: Option<String>,
 }
 
 impl<const NACC: usize, const NTX: usize> CircuitTestBuilder<NACC, NTX> {
@@ -103,6 +115,8 @@ impl<const NACC: usize, const NTX: usize> CircuitTestBuilder<NACC, NTX> {
                 )
             }),
             block_modifiers: vec![],
+            input_builder_modifiers: vec![],
+            rw_table_golden: None,
         }
     }
 
@@ -174,6 +188,26 @@ impl<const NACC: usize, const NTX: usize> CircuitTestBuilder<NACC, NTX> {
         self.block_modifiers.push(modifier);
         self
     }
+
+    #[allow(clippy::type_complexity)]
+    /// Allows to provide modifier functions for the [`CircuitInputBuilder`]
+    /// that the block is generated with, such as a different fee policy,
+    /// applied before the [`TestContext`] is handled.
+    pub fn input_builder_modifier(
+        mut self,
+        modifier: Box<dyn Fn(&mut CircuitInputBuilder<DynamicCParams>)>,
+    ) -> Self {
+        self.input_builder_modifiers.push(modifier);
+        self
+    }
+
+    /// Compare the rw table of the block built from the [`TestContext`],
+    /// before the block modifiers, with the golden file `name` (see
+    /// [`golden`]).
+    pub fn rw_table_golden(mut self, name: &str) -> Self {
+        self.rw_table_golden = Some(name.to_string());
+        self
+    }
 }
 
 impl<const NACC: usize, const NTX: usize> CircuitTestBuilder<NACC, NTX> {
@@ -185,12 +219,19 @@ impl<const NACC: usize, const NTX: usize> CircuitTestBuilder<NACC, NTX> {
             self.block.unwrap()
         } else if self.test_ctx.is_some() {
             let block: GethData = self.test_ctx.unwrap().into();
-            let builder = BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+            let mut builder =
+                BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+            for modifier_fn in self.input_builder_modifiers {
+                modifier_fn.as_ref()(&mut builder);
+            }
             let builder = builder
                 .handle_block(&block.eth_block, &block.geth_traces)
                 .unwrap();
             // Build a witness block from trace result.
             let mut block = crate::witness::block_convert(&builder).unwrap();
+            if let Some(name) = &self.rw_table_golden {
+                golden::check_rw_table(name, &block);
+            }
 
             for modifier_fn in self.block_modifiers {
                 modifier_fn.as_ref()(&mut block);
@@ -219,7 +260,7 @@ impl<const NACC: usize, const NTX: usize> CircuitTestBuilder<NACC, NTX> {
         {
             let rows_needed = StateCircuit::<Fr>::min_num_rows_block(&block).1;
             let k = cmp::max(log2_ceil(rows_needed + NUM_BLINDING_ROWS), 18);
-            let state_circuit = StateCircuit::<Fr>::new(block.rws, params.max_rws);
+            let state_circuit = StateCircuit::<Fr>::new_from_block(&block);
             let instance = state_circuit.instance();
             let prover = MockProver::<Fr>::run(k, &state_circuit, instance).unwrap();
             // Skip verification of Start rows to accelerate testing
@@ -234,3 +275,22 @@ impl<const NACC: usize, const NTX: usize> CircuitTestBuilder<NACC, NTX> {
         }
     }
 }
+
+/// Run the circuit `C` with the [`MockProver`] on the block of the snapshot at
+/// `path` (see [`Block::save_snapshot`]), to reproduce a failure without the
+/// traces the block was built from.
+pub fn replay<C: SubCircuit<Fr> + Circuit<Fr>>(
+    path: impl AsRef<Path>,
+) -> Result<(), Vec<VerifyFailure>> {
+    let block = Block::<Fr>::load_snapshot(path).expect("cannot load the block snapshot");
+    let (_, rows) = C::min_num_rows_block(&block);
+    let k = cmp::max(
+        log2_ceil(rows + C::unusable_rows()),
+        block.get_test_degree(),
+    );
+    let circuit = C::new_from_block(&block);
+    let instance = circuit.instance();
+    MockProver::<Fr>::run(k, &circuit, instance)
+        .unwrap()
+        .verify_par()
+}
@@ -0,0 +1,215 @@
+//! Pretty-printing of the `MockProver::verify` failures: each failure is
+//! prefixed with the execution step (or MPT node) assigned at its row, instead
+//! of the raw region offset.
+
+use crate::{
+    evm_circuit::step::{ExecutionState, HasExecutionState},
+    mpt_circuit::witness_row::Node,
+    witness::Block,
+};
+use eth_types::{evm_types::OpcodeId, Field};
+use halo2_proofs::dev::{FailureLocation, VerifyFailure};
+use std::fmt;
+
+/// The step of the witness assigned at the row of a failure.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum FailureContext {
+    /// An execution step of the EVM circuit.
+    Step {
+        /// Id of the transaction, 0 for the steps after the last transaction
+        tx_id: u64,
+        /// Program counter
+        pc: u64,
+        /// Opcode of the step, if it executes one
+        opcode: Option<OpcodeId>,
+        /// Execution state of the step
+        execution_state: ExecutionState,
+        /// Row of the step inside its height
+        row: usize,
+    },
+    /// A node of the MPT circuit.
+    MptNode {
+        /// Index of the node in the witness
+        index: usize,
+        /// Type of the node: start, extension/branch, account or storage
+        node_type: &'static str,
+        /// Row of the node inside its height
+        row: usize,
+    },
+}
+
+impl fmt::Display for FailureContext {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Step {
+                tx_id,
+                pc,
+                opcode,
+                execution_state,
+                row,
+            } => {
+                write!(f, "tx {}, pc {:#x}, ", tx_id, pc)?;
+                if let Some(opcode) = opcode {
+                    write!(f, "{:?} ", opcode)?;
+                }
+                write!(f, "({:?}), row {}", execution_state, row)
+            }
+            Self::MptNode {
+                index,
+                node_type,
+                row,
+            } => write!(f, "node {} ({}), row {}", index, node_type, row),
+        }
+    }
+}
+
+/// Returns the offset of the failure inside its region.  Failures outside of
+/// a region can't be mapped to the witness, as the start of the region is not
+/// known.
+fn failure_offset(failure: &VerifyFailure) -> Option<usize> {
+    match failure {
+        VerifyFailure::CellNotAssigned { offset, .. } => usize::try_from(*offset).ok(),
+        VerifyFailure::ConstraintNotSatisfied { location, .. }
+        | VerifyFailure::Lookup { location, .. }
+        | VerifyFailure::Permutation { location, .. } => match location {
+            FailureLocation::InRegion { offset, .. } => Some(*offset),
+            FailureLocation::OutsideRegion { .. } => None,
+        },
+        _ => None,
+    }
+}
+
+/// Returns the execution step assigned at `offset` of the EVM circuit region.
+/// The offsets after the last step belong to the padding `EndBlock` steps.
+pub fn evm_failure_context<F: Field>(block: &Block<F>, offset: usize) -> FailureContext {
+    let end_block_context = block.end_block_context();
+    let mut step_offset = 0;
+    for (tx, _, step) in block.steps(&end_block_context) {
+        let height = step.execution_state().get_step_height();
+        if offset < step_offset + height {
+            return FailureContext::Step {
+                tx_id: tx.id,
+                pc: step.pc,
+                opcode: step.opcode(),
+                execution_state: step.execution_state(),
+                row: offset - step_offset,
+            };
+        }
+        step_offset += height;
+    }
+    FailureContext::Step {
+        tx_id: 0,
+        pc: 0,
+        opcode: None,
+        execution_state: ExecutionState::EndBlock,
+        row: offset - step_offset,
+    }
+}
+
+/// Returns the MPT node assigned at `offset` of the MPT circuit region, if
+/// any.
+pub fn mpt_failure_context(nodes: &[Node], offset: usize) -> Option<FailureContext> {
+    let mut node_offset = 0;
+    for (index, node) in nodes.iter().enumerate() {
+        let height = node.values.len();
+        if offset < node_offset + height {
+            let node_type = if node.start.is_some() {
+                "start"
+            } else if node.extension_branch.is_some() {
+                "extension/branch"
+            } else if node.account.is_some() {
+                "account"
+            } else if node.storage.is_some() {
+                "storage"
+            } else {
+                "unknown"
+            };
+            return Some(FailureContext::MptNode {
+                index,
+                node_type,
+                row: offset - node_offset,
+            });
+        }
+        node_offset += height;
+    }
+    None
+}
+
+fn print_failures(failures: &[VerifyFailure], context: impl Fn(usize) -> Option<FailureContext>) {
+    for failure in failures {
+        match failure_offset(failure).and_then(&context) {
+            Some(context) => println!("[{}] {}", context, failure),
+            None => println!("{}", failure),
+        }
+    }
+}
+
+/// Prints the failures of the EVM circuit, each with the execution step
+/// (transaction, pc, opcode) it happened in.
+pub fn print_evm_failures<F: Field>(failures: &[VerifyFailure], block: &Block<F>) {
+    print_failures(failures, |offset| Some(evm_failure_context(block, offset)));
+}
+
+/// Prints the failures of the MPT circuit, each with the type of the node it
+/// happened in.
+pub fn print_mpt_failures(failures: &[VerifyFailure], nodes: &[Node]) {
+    print_failures(failures, |offset| mpt_failure_context(nodes, offset));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bus_mapping::mock::BlockData;
+    use eth_types::{bytecode, geth_types::GethData};
+    use halo2_proofs::halo2curves::bn256::Fr;
+    use mock::TestContext;
+
+    #[test]
+    fn evm_failure_contexts() {
+        let bytecode = bytecode! {
+            PUSH1(0x01)
+            STOP
+        };
+        let block: GethData = TestContext::<2, 1>::simple_ctx_with_bytecode(bytecode)
+            .unwrap()
+            .into();
+        let builder = BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+        let builder = builder
+            .handle_block(&block.eth_block, &block.geth_traces)
+            .unwrap();
+        let block = crate::witness::block_convert::<Fr>(&builder).unwrap();
+
+        let begin_tx_height = ExecutionState::BeginTx.get_step_height();
+        match evm_failure_context(&block, begin_tx_height - 1) {
+            FailureContext::Step {
+                tx_id,
+                execution_state,
+                row,
+                ..
+            } => {
+                assert_eq!(tx_id, 1);
+                assert_eq!(execution_state, ExecutionState::BeginTx);
+                assert_eq!(row, begin_tx_height - 1);
+            }
+            context => panic!("unexpected context {}", context),
+        }
+        assert_eq!(
+            evm_failure_context(&block, begin_tx_height),
+            FailureContext::Step {
+                tx_id: 1,
+                pc: 0,
+                opcode: Some(OpcodeId::PUSH1),
+                execution_state: ExecutionState::PUSH,
+                row: 0,
+            }
+        );
+        // far after the last step
+        assert!(matches!(
+            evm_failure_context(&block, 1 << 20),
+            FailureContext::Step {
+                execution_state: ExecutionState::EndBlock,
+                ..
+            }
+        ));
+    }
+}
@@ -0,0 +1,143 @@
+//! Golden files of the rw table: the sorted rws of a test block are dumped to
+//! a file, and compared with the file on the next runs, so that a change of
+//! the rws generated by bus-mapping (their order, tags or values) shows up as
+//! an explicit diff instead of a failure of the state circuit.
+//!
+//! The golden files are in `src/test_util/golden`, and are written (or
+//! rewritten) by the tests when the `UPDATE_GOLDEN` environment variable is
+//! set.  Otherwise a missing golden file is an error.
+
+use crate::witness::Block;
+use eth_types::Field;
+use std::{env, fs, path::PathBuf};
+
+/// Environment variable to set to rewrite the golden files.
+pub const UPDATE_GOLDEN: &str = "UPDATE_GOLDEN";
+
+/// Maximum number of lines of each side of the diff shown on a mismatch.
+const MAX_DIFF_LINES: usize = 40;
+
+fn golden_path(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("src/test_util/golden")
+        .join(format!("{}.txt", name))
+}
+
+/// Dump of the rw table of `block`: the rws sorted as in the state circuit,
+/// one per line.
+pub fn rw_table_dump<F: Field>(block: &Block<F>) -> String {
+    block
+        .rws
+        .table_assignments()
+        .iter()
+        .map(|rw| format!("{:?}\n", rw))
+        .collect()
+}
+
+/// Compare the rw table of `block` with the golden file `name`, and panic
+/// with the differing lines if they don't match.
+pub fn check_rw_table<F: Field>(name: &str, block: &Block<F>) {
+    check_golden(name, &rw_table_dump(block))
+}
+
+/// Compare `actual` with the golden file `name`, and panic with the differing
+/// lines if they don't match.
+pub fn check_golden(name: &str, actual: &str) {
+    let path = golden_path(name);
+    if env::var_os(UPDATE_GOLDEN).is_some() {
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, actual)
+            .unwrap_or_else(|err| panic!("cannot write {}: {}", path.display(), err));
+        log::warn!("golden file {} written", path.display());
+        return;
+    }
+    let expected = fs::read_to_string(&path).unwrap_or_else(|err| {
+        panic!(
+            "cannot read the golden file {} (set {} to write it): {}",
+            path.display(),
+            UPDATE_GOLDEN,
+            err
+        )
+    });
+    if let Some(diff) = diff(&expected, actual) {
+        panic!(
+            "mismatch with the golden file {} (set {} to update it):\n{}",
+            path.display(),
+            UPDATE_GOLDEN,
+            diff
+        );
+    }
+}
+
+/// Diff of the lines of `expected` and `actual`: the lines between their
+/// common prefix and suffix.  Returns None if they are equal.
+fn diff(expected: &str, actual: &str) -> Option<String> {
+    let expected: Vec<_> = expected.lines().collect();
+    let actual: Vec<_> = actual.lines().collect();
+    if expected == actual {
+        return None;
+    }
+    let prefix = expected
+        .iter()
+        .zip(actual.iter())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let suffix = expected[prefix..]
+        .iter()
+        .rev()
+        .zip(actual[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+
+    let mut diff = format!("@@ line {} @@\n", prefix + 1);
+    for (sign, lines) in [
+        ('-', &expected[prefix..expected.len() - suffix]),
+        ('+', &actual[prefix..actual.len() - suffix]),
+    ] {
+        for line in lines.iter().take(MAX_DIFF_LINES) {
+            diff.push_str(&format!("{}{}\n", sign, line));
+        }
+        if lines.len() > MAX_DIFF_LINES {
+            diff.push_str(&format!(
+                "{}... {} more lines\n",
+                sign,
+                lines.len() - MAX_DIFF_LINES
+            ));
+        }
+    }
+    Some(diff)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::CircuitTestBuilder;
+    use eth_types::bytecode;
+    use mock::TestContext;
+
+    #[test]
+    fn diff_lines() {
+        assert_eq!(diff("a\nb\nc\n", "a\nb\nc\n"), None);
+        assert_eq!(
+            diff("a\nb\nc\nd\n", "a\nx\ny\nd\n").unwrap(),
+            "@@ line 2 @@\n-b\n-c\n+x\n+y\n"
+        );
+        assert_eq!(diff("a\nb\n", "a\nb\nc\n").unwrap(), "@@ line 3 @@\n+c\n");
+    }
+
+    #[test]
+    fn rw_table_golden_sstore() {
+        let code = bytecode! {
+            PUSH1(0x2a)
+            PUSH1(0x01)
+            SSTORE
+            PUSH1(0x01)
+            SLOAD
+            STOP
+        };
+        let ctx = TestContext::<2, 1>::simple_ctx_with_bytecode(code).unwrap();
+        CircuitTestBuilder::new_from_test_ctx(ctx)
+            .rw_table_golden("rw_table_sstore")
+            .run();
+    }
+}
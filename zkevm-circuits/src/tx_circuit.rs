@@ -18,10 +18,12 @@ use crate::{
     util::{word::Word, Challenges, SubCircuit, SubCircuitConfig},
     witness,
 };
+use bus_mapping::circuit_input_builder::AnchorTxParams;
 use eth_types::{geth_types::Transaction, sign_types::SignData, Field};
 use halo2_proofs::{
     circuit::{AssignedCell, Layouter, Region, Value},
-    plonk::{Advice, Column, ConstraintSystem, Error, Expression, Fixed},
+    plonk::{Advice, Column, ConstraintSystem, Error, Expression, Fixed, SecondPhase, Selector},
+    poly::Rotation,
 };
 use itertools::Itertools;
 use log::error;
@@ -30,10 +32,17 @@ use std::{marker::PhantomData, ops::Deref};
 
 /// Number of static fields per tx: [nonce, gas, gas_price,
 /// caller_address, callee_address, is_create, value, call_data_length,
-/// call_data_gas_cost, tx_sign_hash].
+/// call_data_gas_cost, tx_sign_hash, call_data_offset, tx_hash].
 /// Note that call data bytes are layed out in the TxTable after all the static
 /// fields arranged by txs.
-pub(crate) const TX_LEN: usize = 10;
+pub(crate) const TX_LEN: usize = 12;
+
+/// Assigned cells of the tx_id, the index and the value of a tx circuit row
+type TxRowCells<F> = (
+    AssignedCell<F, F>,
+    AssignedCell<F, F>,
+    Word<AssignedCell<F, F>>,
+);
 
 /// Config for TxCircuit
 #[derive(Clone, Debug)]
@@ -43,6 +52,12 @@ pub struct TxCircuitConfig<F: Field> {
     index: Column<Advice>,
     value: Word<Column<Advice>>,
     sign_verify: SignVerifyConfig,
+    // Enabled in the TxHash rows
+    q_tx_hash: Selector,
+    // RLC of the signed RLP encoding of the tx, in the TxHash rows
+    tx_rlp_rlc: Column<Advice>,
+    // Length of the signed RLP encoding of the tx, in the TxHash rows
+    tx_rlp_len: Column<Advice>,
     _marker: PhantomData<F>,
 }
 
@@ -74,8 +89,39 @@ impl<F: Field> SubCircuitConfig<F> for TxCircuitConfig<F> {
         let value = tx_table.value;
         meta.enable_equality(value.lo());
         meta.enable_equality(value.hi());
+        // The fields of the anchor tx are constrained to constants
+        meta.enable_equality(tx_id);
+        meta.enable_equality(index);
+        let constants = meta.fixed_column();
+        meta.enable_constant(constants);
+
+        let sign_verify = SignVerifyConfig::new(meta, keccak_table.clone(), challenges);
+
+        // Verify that the TxHash of every tx is keccak(rlp_signed) by keccak table lookup.
+        // Note that the RLP encoding is not yet constrained to match the tx fields.
+        let q_tx_hash = meta.complex_selector();
+        let tx_rlp_rlc = meta.advice_column_in(SecondPhase);
+        let tx_rlp_len = meta.advice_column();
+        meta.lookup_any("tx hash keccak", |meta| {
+            let q_tx_hash = meta.query_selector(q_tx_hash);
+            let input = [
+                q_tx_hash.clone(),
+                q_tx_hash.clone() * meta.query_advice(tx_rlp_rlc, Rotation::cur()),
+                q_tx_hash.clone() * meta.query_advice(tx_rlp_len, Rotation::cur()),
+                q_tx_hash.clone() * meta.query_advice(value.lo(), Rotation::cur()),
+                q_tx_hash * meta.query_advice(value.hi(), Rotation::cur()),
+            ];
+            let table = [
+                keccak_table.is_enabled,
+                keccak_table.input_rlc,
+                keccak_table.input_len,
+                keccak_table.output.lo(),
+                keccak_table.output.hi(),
+            ]
+            .map(|column| meta.query_advice(column, Rotation::cur()));
 
-        let sign_verify = SignVerifyConfig::new(meta, keccak_table, challenges);
+            input.into_iter().zip(table).collect()
+        });
 
         Self {
             tx_id,
@@ -83,6 +129,9 @@ impl<F: Field> SubCircuitConfig<F> for TxCircuitConfig<F> {
             index,
             value,
             sign_verify,
+            q_tx_hash,
+            tx_rlp_rlc,
+            tx_rlp_len,
             _marker: PhantomData,
         }
     }
@@ -94,8 +143,8 @@ impl<F: Field> TxCircuitConfig<F> {
         self.sign_verify.load_range(layouter)
     }
 
-    /// Assigns a tx circuit row and returns the assigned cell of the value in `word` in
-    /// the row.
+    /// Assigns a tx circuit row and returns the assigned cells of the tx_id, the
+    /// index and the value in `word` in the row.
     fn assign_row(
         &self,
         region: &mut Region<'_, F>,
@@ -104,8 +153,8 @@ impl<F: Field> TxCircuitConfig<F> {
         tag: TxFieldTag,
         index: usize,
         value: Word<Value<F>>,
-    ) -> Result<Word<AssignedCell<F, F>>, Error> {
-        region.assign_advice(
+    ) -> Result<TxRowCells<F>, Error> {
+        let tx_id = region.assign_advice(
             || "tx_id",
             self.tx_id,
             offset,
@@ -117,13 +166,14 @@ impl<F: Field> TxCircuitConfig<F> {
             offset,
             || Value::known(F::from(tag as u64)),
         )?;
-        region.assign_advice(
+        let index = region.assign_advice(
             || "index",
             self.index,
             offset,
             || Value::known(F::from(index as u64)),
         )?;
-        value.assign_advice(region, || "value", self.value, offset)
+        let value = value.assign_advice(region, || "value", self.value, offset)?;
+        Ok((tx_id, index, value))
     }
 
     /// Get number of rows required.
@@ -148,6 +198,9 @@ pub struct TxCircuit<F: Field> {
     pub txs: Vec<Transaction>,
     /// Chain ID
     pub chain_id: u64,
+    /// Expected fields of the first tx, when the block must start with an
+    /// anchor tx
+    pub anchor_tx: Option<AnchorTxParams>,
 }
 
 impl<F: Field> TxCircuit<F> {
@@ -159,9 +212,16 @@ impl<F: Field> TxCircuit<F> {
             sign_verify: SignVerifyChip::new(max_txs),
             txs,
             chain_id,
+            anchor_tx: None,
         }
     }
 
+    /// Require the first tx to be the anchor tx described by `anchor_tx`.
+    pub fn with_anchor_tx(mut self, anchor_tx: Option<AnchorTxParams>) -> Self {
+        self.anchor_tx = anchor_tx;
+        self
+    }
+
     /// Return the minimum number of rows required to prove an input of a
     /// particular size.
     pub fn min_num_rows(txs_len: usize, call_data_len: usize) -> usize {
@@ -169,11 +229,34 @@ impl<F: Field> TxCircuit<F> {
         std::cmp::max(tx_table_len, SignVerifyChip::<F>::min_num_rows(txs_len))
     }
 
+    /// Constrain the call data row at `position` in the call data section to be
+    /// the byte of the calldata prefix of the anchor tx, which comes first.
+    /// The padding rows are also constrained, so that an anchor tx with a
+    /// shorter call data is rejected.
+    fn constrain_anchor_calldata(
+        &self,
+        region: &mut Region<'_, F>,
+        position: usize,
+        (tx_id, index, value): TxRowCells<F>,
+    ) -> Result<(), Error> {
+        let prefix_byte = match &self.anchor_tx {
+            Some(anchor_tx) if position < anchor_tx.calldata_prefix.len() => {
+                anchor_tx.calldata_prefix[position]
+            }
+            _ => return Ok(()),
+        };
+        region.constrain_constant(tx_id.cell(), F::ONE)?;
+        region.constrain_constant(index.cell(), F::from(position as u64))?;
+        region.constrain_constant(value.lo().cell(), F::from(prefix_byte as u64))?;
+        region.constrain_constant(value.hi().cell(), F::ZERO)
+    }
+
     fn assign_tx_table(
         &self,
         config: &TxCircuitConfig<F>,
         layouter: &mut impl Layouter<F>,
         assigned_sig_verifs: Vec<AssignedSignatureVerify<F>>,
+        challenges: &Challenges<Value<F>>,
     ) -> Result<(), Error> {
         layouter.assign_region(
             || "tx table",
@@ -191,12 +274,21 @@ impl<F: Field> TxCircuit<F> {
                 offset += 1;
                 // Assign all Tx fields except for call data
                 let tx_default = Transaction::default();
+                let mut call_data_offset = 0;
                 for (i, assigned_sig_verif) in assigned_sig_verifs.iter().enumerate() {
                     let tx = if i < self.txs.len() {
                         &self.txs[i]
                     } else {
                         &tx_default
                     };
+                    // Padding txs hash the empty input
+                    let rlp_signed = if i < self.txs.len() {
+                        tx.rlp_signed(self.chain_id).to_vec()
+                    } else {
+                        vec![]
+                    };
+                    let [_, rlp_rlc, rlp_len, hash_lo, hash_hi] =
+                        KeccakTable::assignments(&rlp_signed, challenges)[0];
 
                     for (tag, value) in [
                         (
@@ -227,9 +319,34 @@ impl<F: Field> TxCircuit<F> {
                             TxFieldTag::TxSignHash,
                             assigned_sig_verif.msg_hash.map(|x| x.value().copied()),
                         ),
+                        (
+                            TxFieldTag::CallDataOffset,
+                            Word::from(if i < self.txs.len() {
+                                call_data_offset as u64
+                            } else {
+                                0
+                            })
+                            .into_value(),
+                        ),
+                        (TxFieldTag::TxHash, Word::new([hash_lo, hash_hi])),
                     ] {
-                        let assigned_cell =
+                        let (_, _, assigned_cell) =
                             config.assign_row(&mut region, offset, i + 1, tag, 0, value)?;
+                        if tag == TxFieldTag::TxHash {
+                            config.q_tx_hash.enable(&mut region, offset)?;
+                            region.assign_advice(
+                                || "tx_rlp_rlc",
+                                config.tx_rlp_rlc,
+                                offset,
+                                || rlp_rlc,
+                            )?;
+                            region.assign_advice(
+                                || "tx_rlp_len",
+                                config.tx_rlp_len,
+                                offset,
+                                || rlp_len,
+                            )?;
+                        }
                         offset += 1;
 
                         // Ref. spec 0. Copy constraints using fixed offsets between the tx rows and
@@ -257,7 +374,27 @@ impl<F: Field> TxCircuit<F> {
                             }
                             _ => (),
                         }
+
+                        // The caller and the callee of the anchor tx are fixed, and it's not a
+                        // creation.
+                        if let (0, Some(anchor_tx)) = (i, &self.anchor_tx) {
+                            let expected = match tag {
+                                TxFieldTag::CallerAddress => {
+                                    Some(Word::<F>::from(anchor_tx.caller))
+                                }
+                                TxFieldTag::CalleeAddress => Some(Word::from(anchor_tx.callee)),
+                                TxFieldTag::IsCreate => Some(Word::from(0u64)),
+                                _ => None,
+                            };
+                            if let Some(expected) = expected {
+                                region
+                                    .constrain_constant(assigned_cell.lo().cell(), expected.lo())?;
+                                region
+                                    .constrain_constant(assigned_cell.hi().cell(), expected.hi())?;
+                            }
+                        }
                     }
+                    call_data_offset += tx.call_data.0.len();
                 }
 
                 // Assign call data
@@ -265,7 +402,7 @@ impl<F: Field> TxCircuit<F> {
                 for (i, tx) in self.txs.iter().enumerate() {
                     for (index, byte) in tx.call_data.0.iter().enumerate() {
                         assert!(calldata_count < self.max_calldata);
-                        config.assign_row(
+                        let cells = config.assign_row(
                             &mut region,
                             offset,
                             i + 1, // tx_id
@@ -273,12 +410,13 @@ impl<F: Field> TxCircuit<F> {
                             index,
                             Word::from(*byte as u64).into_value(),
                         )?;
+                        self.constrain_anchor_calldata(&mut region, calldata_count, cells)?;
                         offset += 1;
                         calldata_count += 1;
                     }
                 }
-                for _ in calldata_count..self.max_calldata {
-                    config.assign_row(
+                for position in calldata_count..self.max_calldata {
+                    let cells = config.assign_row(
                         &mut region,
                         offset,
                         0, // tx_id
@@ -286,6 +424,7 @@ impl<F: Field> TxCircuit<F> {
                         0,
                         Word::default().into_value(),
                     )?;
+                    self.constrain_anchor_calldata(&mut region, position, cells)?;
                     offset += 1;
                 }
                 Ok(())
@@ -310,6 +449,7 @@ impl<F: Field> SubCircuit<F> for TxCircuit<F> {
             block.context.chain_id.as_u64(),
             block.txs.iter().map(|tx| tx.deref().clone()).collect_vec(),
         )
+        .with_anchor_tx(block.circuits_params.anchor_tx)
     }
 
     /// Return the minimum number of rows required to prove the block
@@ -334,6 +474,15 @@ impl<F: Field> SubCircuit<F> for TxCircuit<F> {
         layouter: &mut impl Layouter<F>,
     ) -> Result<(), Error> {
         assert!(self.txs.len() <= self.max_txs);
+        if let Some(anchor_tx) = &self.anchor_tx {
+            if self.max_txs == 0 || self.max_calldata < anchor_tx.calldata_prefix.len() {
+                error!(
+                    "no room for the anchor tx: max_txs={}, max_calldata={}",
+                    self.max_txs, self.max_calldata
+                );
+                return Err(Error::Synthesis);
+            }
+        }
         let sign_datas: Vec<SignData> = self
             .txs
             .iter()
@@ -349,7 +498,7 @@ impl<F: Field> SubCircuit<F> for TxCircuit<F> {
         let assigned_sig_verifs =
             self.sign_verify
                 .assign(&config.sign_verify, layouter, &sign_datas, challenges)?;
-        self.assign_tx_table(config, layouter, assigned_sig_verifs)?;
+        self.assign_tx_table(config, layouter, assigned_sig_verifs, challenges)?;
         Ok(())
     }
 
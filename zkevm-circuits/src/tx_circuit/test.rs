@@ -1,11 +1,11 @@
 use super::*;
 use crate::util::{log2_ceil, unusable_rows};
-use eth_types::address;
+use eth_types::{address, Bytes};
 use halo2_proofs::{
     dev::{MockProver, VerifyFailure},
     halo2curves::bn256::Fr,
 };
-use mock::AddrOrWallet;
+use mock::{AddrOrWallet, MockTransaction};
 
 #[test]
 fn tx_circuit_unusable_rows() {
@@ -20,12 +20,23 @@ fn run<F: Field>(
     chain_id: u64,
     max_txs: usize,
     max_calldata: usize,
+) -> Result<(), Vec<VerifyFailure>> {
+    run_with_anchor_tx::<F>(txs, chain_id, max_txs, max_calldata, None)
+}
+
+fn run_with_anchor_tx<F: Field>(
+    txs: Vec<Transaction>,
+    chain_id: u64,
+    max_txs: usize,
+    max_calldata: usize,
+    anchor_tx: Option<AnchorTxParams>,
 ) -> Result<(), Vec<VerifyFailure>> {
     let k = log2_ceil(
         TxCircuit::<Fr>::unusable_rows() + TxCircuit::<Fr>::min_num_rows(max_txs, max_calldata),
     );
     // SignVerifyChip -> ECDSAChip -> MainGate instance column
-    let circuit = TxCircuit::<F>::new(max_txs, max_calldata, chain_id, txs);
+    let circuit =
+        TxCircuit::<F>::new(max_txs, max_calldata, chain_id, txs).with_anchor_tx(anchor_tx);
 
     let prover = match MockProver::run(k, &circuit, vec![vec![]]) {
         Ok(prover) => prover,
@@ -113,3 +124,85 @@ fn variadic_size_check() {
     assert_eq!(prover1.fixed(), prover2.fixed());
     assert_eq!(prover1.permutation(), prover2.permutation());
 }
+
+fn anchor_tx_params(tx: &Transaction, calldata_prefix: &[u8; 4]) -> AnchorTxParams {
+    AnchorTxParams {
+        caller: tx.from,
+        callee: tx.to_or_zero(),
+        calldata_prefix: *calldata_prefix,
+    }
+}
+
+#[test]
+fn tx_circuit_anchor_tx() {
+    const MAX_TXS: usize = 2;
+    const MAX_CALLDATA: usize = 32;
+
+    let chain_id: u64 = mock::MOCK_CHAIN_ID.as_u64();
+    let txs: Vec<Transaction> = mock::CORRECT_MOCK_TXS[..2]
+        .iter()
+        .map(|tx| Transaction::from(tx.clone()))
+        .collect_vec();
+    let anchor_tx = anchor_tx_params(&txs[0], b"hell");
+
+    assert_eq!(
+        run_with_anchor_tx::<Fr>(txs, chain_id, MAX_TXS, MAX_CALLDATA, Some(anchor_tx)),
+        Ok(())
+    );
+}
+
+#[test]
+fn tx_circuit_anchor_tx_not_first() {
+    const MAX_TXS: usize = 2;
+    const MAX_CALLDATA: usize = 32;
+
+    let chain_id: u64 = mock::MOCK_CHAIN_ID.as_u64();
+    let tx1: Transaction = mock::CORRECT_MOCK_TXS[0].clone().into();
+    let tx2: Transaction = mock::CORRECT_MOCK_TXS[1].clone().into();
+    let anchor_tx = anchor_tx_params(&tx1, b"hell");
+
+    assert!(run_with_anchor_tx::<Fr>(
+        vec![tx2, tx1],
+        chain_id,
+        MAX_TXS,
+        MAX_CALLDATA,
+        Some(anchor_tx)
+    )
+    .is_err());
+}
+
+#[test]
+fn tx_circuit_anchor_tx_bad_calldata_prefix() {
+    const MAX_TXS: usize = 1;
+    const MAX_CALLDATA: usize = 32;
+
+    let chain_id: u64 = mock::MOCK_CHAIN_ID.as_u64();
+    let tx: Transaction = mock::CORRECT_MOCK_TXS[0].clone().into();
+    let anchor_tx = anchor_tx_params(&tx, b"help");
+
+    assert!(
+        run_with_anchor_tx::<Fr>(vec![tx], chain_id, MAX_TXS, MAX_CALLDATA, Some(anchor_tx))
+            .is_err()
+    );
+}
+
+#[test]
+fn tx_circuit_anchor_tx_short_calldata() {
+    const MAX_TXS: usize = 1;
+    const MAX_CALLDATA: usize = 32;
+
+    let chain_id: u64 = mock::MOCK_CHAIN_ID.as_u64();
+    let tx: Transaction = MockTransaction::default()
+        .from(AddrOrWallet::from(mock::MOCK_WALLETS[0].clone()))
+        .to(mock::MOCK_ACCOUNTS[0])
+        .input(Bytes::from(b"hel"))
+        .build()
+        .into();
+    // The padding row after the 3 bytes of call data can't match the prefix.
+    let anchor_tx = anchor_tx_params(&tx, b"hell");
+
+    assert!(
+        run_with_anchor_tx::<Fr>(vec![tx], chain_id, MAX_TXS, MAX_CALLDATA, Some(anchor_tx))
+            .is_err()
+    );
+}
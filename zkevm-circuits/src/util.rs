@@ -34,10 +34,20 @@ pub fn query_expression<F: Field, T>(
 }
 
 /// All challenges used in `SuperCircuit`.
+///
+/// Each challenge is used for a single purpose, so the soundness arguments relying on them stay
+/// independent: `lookup_input` only compresses the lookup expressions, and the values checked to
+/// be zero together are combined with their own `zero_check` challenge. EVM words don't need a
+/// challenge at all, they are encoded in two 128-bit limbs (see [`word::Word`]).
 #[derive(Default, Clone, Copy, Debug)]
 pub struct Challenges<T = Challenge> {
+    /// RLC of byte streams: keccak inputs, bytecode, copy data
     keccak_input: T,
+    /// Compression of the lookup input expressions
     lookup_input: T,
+    /// RLC of the first phase values checked to be zero together by
+    /// `IsZeroBatchGadget`
+    zero_check: T,
 }
 
 impl Challenges {
@@ -53,17 +63,20 @@ impl Challenges {
         Self {
             keccak_input: meta.challenge_usable_after(FirstPhase),
             lookup_input: meta.challenge_usable_after(SecondPhase),
+            zero_check: meta.challenge_usable_after(FirstPhase),
         }
     }
 
     /// Returns `Expression` of challenges from `ConstraintSystem`.
     pub fn exprs<F: Field>(&self, meta: &mut ConstraintSystem<F>) -> Challenges<Expression<F>> {
-        let [keccak_input, lookup_input] = query_expression(meta, |meta| {
-            [self.keccak_input, self.lookup_input].map(|challenge| meta.query_challenge(challenge))
+        let [keccak_input, lookup_input, zero_check] = query_expression(meta, |meta| {
+            [self.keccak_input, self.lookup_input, self.zero_check]
+                .map(|challenge| meta.query_challenge(challenge))
         });
         Challenges {
             keccak_input,
             lookup_input,
+            zero_check,
         }
     }
 
@@ -72,6 +85,7 @@ impl Challenges {
         Challenges {
             keccak_input: layouter.get_challenge(self.keccak_input),
             lookup_input: layouter.get_challenge(self.lookup_input),
+            zero_check: layouter.get_challenge(self.zero_check),
         }
     }
 }
@@ -87,15 +101,21 @@ impl<T: Clone> Challenges<T> {
         self.lookup_input.clone()
     }
 
+    /// Returns challenge of `zero_check`.
+    pub fn zero_check(&self) -> T {
+        self.zero_check.clone()
+    }
+
     /// Returns the challenges indexed by the challenge index
-    pub fn indexed(&self) -> [&T; 2] {
-        [&self.keccak_input, &self.lookup_input]
+    pub fn indexed(&self) -> [&T; 3] {
+        [&self.keccak_input, &self.lookup_input, &self.zero_check]
     }
 
-    pub(crate) fn mock(keccak_input: T, lookup_input: T) -> Self {
+    pub(crate) fn mock(keccak_input: T, lookup_input: T, zero_check: T) -> Self {
         Self {
             keccak_input,
             lookup_input,
+            zero_check,
         }
     }
 }
@@ -13,7 +13,7 @@ use halo2_proofs::{
 };
 use itertools::Itertools;
 
-use crate::evm_circuit::util::{from_bytes, CachedRegion, Cell};
+use crate::evm_circuit::util::{from_bytes, rlc, CachedRegion, Cell};
 
 /// evm word 32 bytes, half word 16 bytes
 const N_BYTES_HALF_WORD: usize = 16;
@@ -179,6 +179,11 @@ impl<F: Field, const N: usize> WordLimbs<Cell<F>, N> {
     pub fn to_word_n<const N2: usize>(&self) -> WordLimbs<Expression<F>, N2> {
         self.word_expr().to_word_n()
     }
+
+    /// RLC of the little-endian limbs, for the lookups that still work on RLC (e.g. keccak)
+    pub fn rlc(&self, randomness: Expression<F>) -> Expression<F> {
+        rlc::expr(&self.word_expr().limbs, randomness)
+    }
 }
 
 impl<F: Field, const N: usize> WordExpr<F> for WordLimbs<Cell<F>, N> {
@@ -1,11 +1,20 @@
 //! Witness for all circuits.
 //! The `Block<F>` is the witness struct post-processed from geth traces and
 //! used to generate witnesses for circuits.
+//!
+//! The conversion from the circuit input builder and the snapshots of the
+//! blocks are only compiled with the `witness` feature.
 
 mod block;
-pub use block::{block_convert, Block, BlockContext};
+#[cfg(feature = "witness")]
+pub use block::block_convert;
+pub use block::{choose_k, Block, BlockContext, CapacityError, RowEstimate, MAX_K};
 mod mpt;
 pub use mpt::{MptUpdate, MptUpdateRow, MptUpdates};
 mod rw;
 pub use bus_mapping::circuit_input_builder::{Call, ExecStep, Transaction};
 pub use rw::{Rw, RwMap, RwRow};
+#[cfg(feature = "witness")]
+mod snapshot;
+#[cfg(feature = "witness")]
+pub use snapshot::SnapshotFormat;
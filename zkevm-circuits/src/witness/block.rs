@@ -1,25 +1,42 @@
-use super::{ExecStep, Rw, RwMap, Transaction};
+use super::{Call, ExecStep, MptUpdates, Rw, RwMap, Transaction};
+#[cfg(feature = "witness")]
+use crate::instance::public_data_convert;
 use crate::{
-    evm_circuit::{detect_fixed_table_tags, EvmCircuit},
-    exp_circuit::param::OFFSET_INCREMENT,
-    instance::public_data_convert,
+    bytecode_circuit::circuit::BytecodeCircuit,
+    copy_circuit::CopyCircuit,
+    evm_circuit::{detect_fixed_table_tags, table::FixedTableProvider, EvmCircuit},
+    exp_circuit::{param::OFFSET_INCREMENT, ExpCircuit},
+    keccak_circuit::KeccakCircuit,
+    state_circuit::StateCircuit,
+    super_circuit::SuperCircuit,
     table::BlockContextFieldTag,
+    tx_circuit::TxCircuit,
     util::{log2_ceil, word, SubCircuit},
 };
 use bus_mapping::{
-    circuit_input_builder::{self, CopyEvent, ExpEvent, FixedCParams},
+    chain_config::ChainConfig,
+    circuit_input_builder::{CopyEvent, ExpEvent, FixedCParams, L1DataFeePolicy},
+    operation::MemoryOpMode,
     state_db::CodeDB,
+};
+#[cfg(feature = "witness")]
+use bus_mapping::{
+    circuit_input_builder::{self, ExecState},
     Error,
 };
 use eth_types::{Address, Field, ToScalar, Word};
 use halo2_proofs::circuit::Value;
+use serde::{Deserialize, Serialize};
+use std::fmt;
 
 // TODO: Remove fields that are duplicated in`eth_block`
 /// Block is the struct used by all circuits, which contains all the needed
 /// data for witness generation.
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(bound = "F: Field")]
 pub struct Block<F> {
     /// The randomness for random linear combination
+    #[serde(with = "field")]
     pub randomness: F,
     /// Transactions in the block
     pub txs: Vec<Transaction>,
@@ -34,6 +51,14 @@ pub struct Block<F> {
     pub bytecodes: CodeDB,
     /// The block context
     pub context: BlockContext,
+    /// Rules (hardfork and EIPs) of the chain the block belongs to
+    pub chain_config: ChainConfig,
+    /// Granularity of the memory rws generated by `MLOAD`, `MSTORE` and
+    /// `MSTORE8`
+    pub memory_op_mode: MemoryOpMode,
+    /// L1 data fee charged at the beginning and end of each transaction by
+    /// the fee policy of the block, if any
+    pub l1_data_fee: Option<L1DataFeePolicy>,
     /// Copy events for the copy circuit's table.
     pub copy_events: Vec<CopyEvent>,
     /// Exponentiation traces for the exponentiation circuit's table.
@@ -53,6 +78,40 @@ pub struct Block<F> {
 }
 
 impl<F: Field> Block<F> {
+    /// Transaction and call in which the virtual `EndBlock` steps are
+    /// executed: a padding transaction and the root call of the last
+    /// transaction.
+    pub(crate) fn end_block_context(&self) -> (Transaction, Call) {
+        let last_call = self
+            .txs
+            .last()
+            .map(|tx| tx.calls()[0].clone())
+            .unwrap_or_default();
+        (Transaction::default(), last_call)
+    }
+
+    /// Uniform stream of the steps assigned in the EVM circuit, each with the
+    /// transaction and call it belongs to: the `BeginTx`, opcode and `EndTx`
+    /// steps of every transaction followed by the non-last `EndBlock` step,
+    /// so that every step of a transaction has a successor.
+    pub(crate) fn steps<'a>(
+        &'a self,
+        end_block_context: &'a (Transaction, Call),
+    ) -> impl Iterator<Item = (&'a Transaction, &'a Call, &'a ExecStep)> {
+        self.txs
+            .iter()
+            .flat_map(|tx| {
+                tx.steps()
+                    .iter()
+                    .map(move |step| (tx, &tx.calls()[step.call_index], step))
+            })
+            .chain(std::iter::once((
+                &end_block_context.0,
+                &end_block_context.1,
+                &self.end_block_not_last,
+            )))
+    }
+
     /// For each tx, for each step, print the rwc at the beginning of the step,
     /// and all the rw operations of the step.
     #[allow(dead_code, reason = "useful debug function")]
@@ -80,17 +139,16 @@ impl<F: Field> Block<F> {
         let num_rows_required_for_execution_steps: usize =
             EvmCircuit::<F>::get_num_rows_required(self);
         let num_rows_required_for_rw_table: usize = self.circuits_params.max_rws;
-        let num_rows_required_for_fixed_table: usize = detect_fixed_table_tags(self)
-            .iter()
-            .map(|tag| tag.build::<F>().count())
-            .sum();
+        let num_rows_required_for_fixed_table: usize =
+            FixedTableProvider::<F>::shared().num_rows(&detect_fixed_table_tags(self));
         let num_rows_required_for_bytecode_table =
             self.bytecodes.num_rows_required_for_bytecode_table();
         let num_rows_required_for_copy_table: usize =
             self.copy_events.iter().map(|c| c.bytes.len() * 2).sum();
-        let num_rows_required_for_keccak_table: usize = self.keccak_inputs.len();
+        // One more row for the empty input hash
+        let num_rows_required_for_keccak_table: usize = 1 + self.keccak_inputs.len();
         let num_rows_required_for_tx_table: usize =
-            self.txs.iter().map(|tx| 9 + tx.call_data.len()).sum();
+            self.txs.iter().map(|tx| 11 + tx.call_data.len()).sum();
         let num_rows_required_for_exp_table: usize = self
             .exp_events
             .iter()
@@ -125,10 +183,182 @@ impl<F: Field> Block<F> {
         log::debug!("evm circuit uses k = {}, rows = {}", k, rows_needed);
         k
     }
+
+    /// Estimate the number of rows each sub-circuit requires to prove this
+    /// block, without padding.
+    pub fn estimate_rows(&self) -> RowEstimate {
+        let rows = self.rws.table_assignments();
+        RowEstimate {
+            evm_rows: EvmCircuit::<F>::min_num_rows_block(self).0,
+            rw_rows: StateCircuit::<F>::min_num_rows_block(self).0,
+            keccak_rows: KeccakCircuit::<F>::min_num_rows_block(self).0,
+            bytecode_rows: BytecodeCircuit::<F>::min_num_rows_block(self).0,
+            copy_rows: CopyCircuit::<F>::min_num_rows_block(self).0,
+            mpt_rows: MptUpdates::mock_from(&rows).len(),
+            tx_rows: TxCircuit::<F>::min_num_rows_block(self).0,
+            exp_rows: ExpCircuit::<F>::min_num_rows_block(self).0,
+        }
+    }
+
+    /// Number of rows each sub-circuit is padded to with the
+    /// `circuits_params` of this block.  A value of 0 means that the
+    /// sub-circuit has no fixed capacity and is sized from the witness.
+    pub fn row_capacity(&self) -> RowEstimate {
+        RowEstimate {
+            evm_rows: self.circuits_params.max_evm_rows,
+            rw_rows: StateCircuit::<F>::min_num_rows_block(self).1,
+            keccak_rows: KeccakCircuit::<F>::min_num_rows_block(self).1,
+            bytecode_rows: BytecodeCircuit::<F>::min_num_rows_block(self).1,
+            copy_rows: CopyCircuit::<F>::min_num_rows_block(self).1,
+            mpt_rows: 0,
+            tx_rows: TxCircuit::<F>::min_num_rows_block(self).1,
+            exp_rows: ExpCircuit::<F>::min_num_rows_block(self).1,
+        }
+    }
+}
+
+/// Number of rows required by each sub-circuit, as returned by
+/// [`Block::estimate_rows`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RowEstimate {
+    /// Rows of the EVM circuit (execution steps and fixed table)
+    pub evm_rows: usize,
+    /// Rows of the RwTable in the State circuit
+    pub rw_rows: usize,
+    /// Rows of the Keccak circuit
+    pub keccak_rows: usize,
+    /// Rows of the Bytecode circuit (one per bytecode byte plus one per
+    /// bytecode)
+    pub bytecode_rows: usize,
+    /// Rows of the Copy circuit
+    pub copy_rows: usize,
+    /// Rows of the MptTable (one per updated account field or storage slot)
+    pub mpt_rows: usize,
+    /// Rows of the Tx circuit
+    pub tx_rows: usize,
+    /// Rows of the Exponentiation circuit
+    pub exp_rows: usize,
+}
+
+impl RowEstimate {
+    /// Name and number of rows of every sub-circuit, to emit capacity
+    /// metrics.
+    pub fn entries(&self) -> [(&'static str, usize); 8] {
+        [
+            ("evm", self.evm_rows),
+            ("rw", self.rw_rows),
+            ("keccak", self.keccak_rows),
+            ("bytecode", self.bytecode_rows),
+            ("copy", self.copy_rows),
+            ("mpt", self.mpt_rows),
+            ("tx", self.tx_rows),
+            ("exp", self.exp_rows),
+        ]
+    }
+
+    /// Maximum number of rows required by a sub-circuit.
+    pub fn max_rows(&self) -> usize {
+        self.entries()
+            .into_iter()
+            .map(|(_, rows)| rows)
+            .max()
+            .unwrap_or_default()
+    }
+
+    /// Minimum degree of a circuit containing all the sub-circuits side by
+    /// side, given its number of unusable rows.
+    pub fn k(&self, unusable_rows: usize) -> u32 {
+        log2_ceil(self.max_rows() + unusable_rows)
+    }
+
+    /// Sub-circuits requiring more rows than their `capacity`, as `(name,
+    /// rows, capacity)`.  Sub-circuits with a capacity of 0 are sized from
+    /// the witness and never overflow.
+    pub fn overflows(&self, capacity: &RowEstimate) -> Vec<(&'static str, usize, usize)> {
+        self.entries()
+            .into_iter()
+            .zip(capacity.entries())
+            .filter(|((_, rows), (_, capacity))| *capacity != 0 && rows > capacity)
+            .map(|((name, rows), (_, capacity))| (name, rows, capacity))
+            .collect()
+    }
+}
+
+/// Largest degree picked by [`choose_k`].
+pub const MAX_K: u32 = 26;
+
+/// Rows of the range table of 16 bits shared by the EVM and State circuits.
+const U16_TABLE_ROWS: usize = 1 << 16;
+
+/// Error of [`choose_k`] when the block doesn't fit in the circuits.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CapacityError {
+    /// Rows required by each sub-circuit
+    pub rows: RowEstimate,
+    /// Rows each sub-circuit is padded to with the circuit parameters
+    pub capacity: RowEstimate,
+    /// Degree required by the sub-circuits with their padding
+    pub k: u32,
+}
+
+impl fmt::Display for CapacityError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "the block doesn't fit in the circuits, k = {} (max {})",
+            self.k, MAX_K
+        )?;
+        for ((name, rows), (_, capacity)) in
+            self.rows.entries().into_iter().zip(self.capacity.entries())
+        {
+            write!(f, "\n  {}: {} rows", name, rows)?;
+            if capacity != 0 {
+                write!(f, ", capacity {}", capacity)?;
+                if rows > capacity {
+                    write!(f, " (overflow)")?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for CapacityError {}
+
+/// Choose the smallest degree of the super circuit proving `block` with the
+/// circuit parameters `params`, from the rows required by each sub-circuit.
+/// Fails when a sub-circuit requires more rows than its capacity with
+/// `params`, or when the degree exceeds [`MAX_K`].
+pub fn choose_k<F: Field>(block: &Block<F>, params: &FixedCParams) -> Result<u32, CapacityError> {
+    let rows = block.estimate_rows();
+    let capacity = if *params == block.circuits_params {
+        block.row_capacity()
+    } else {
+        Block {
+            circuits_params: *params,
+            ..block.clone()
+        }
+        .row_capacity()
+    };
+    let padded_rows = rows
+        .entries()
+        .into_iter()
+        .zip(capacity.entries())
+        .map(|((_, rows), (_, capacity))| rows.max(capacity))
+        .chain([U16_TABLE_ROWS])
+        .max()
+        .unwrap_or_default();
+    let k = log2_ceil(padded_rows + SuperCircuit::<F>::unusable_rows());
+    log::debug!("choose_k: k = {} for {:?}", k, rows);
+
+    if k > MAX_K || !rows.overflows(&capacity).is_empty() {
+        return Err(CapacityError { rows, capacity, k });
+    }
+    Ok(k)
 }
 
 /// Block context for execution
-#[derive(Debug, Default, Clone)]
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
 pub struct BlockContext {
     /// The address of the miner for the block
     pub coinbase: Address,
@@ -138,7 +368,8 @@ pub struct BlockContext {
     pub number: Word,
     /// The timestamp of the block
     pub timestamp: Word,
-    /// The difficulty of the blcok
+    /// The value of the DIFFICULTY opcode: the difficulty of the block, or its
+    /// PREVRANDAO after the merge
     pub difficulty: Word,
     /// The base fee, the minimum amount of gas fee for a transaction
     pub base_fee: Word,
@@ -216,6 +447,7 @@ impl BlockContext {
     }
 }
 
+#[cfg(feature = "witness")]
 impl From<&circuit_input_builder::Block> for BlockContext {
     fn from(block: &circuit_input_builder::Block) -> Self {
         Self {
@@ -223,7 +455,7 @@ impl From<&circuit_input_builder::Block> for BlockContext {
             gas_limit: block.gas_limit,
             number: block.number,
             timestamp: block.timestamp,
-            difficulty: block.difficulty,
+            difficulty: block.difficulty_opcode_value(),
             base_fee: block.base_fee,
             history_hashes: block.history_hashes.clone(),
             chain_id: block.chain_id,
@@ -232,17 +464,34 @@ impl From<&circuit_input_builder::Block> for BlockContext {
 }
 
 /// Convert a block struct in bus-mapping to a witness block used in circuits
+#[cfg(feature = "witness")]
 pub fn block_convert<F: Field>(
     builder: &circuit_input_builder::CircuitInputBuilder<FixedCParams>,
 ) -> Result<Block<F>, Error> {
+    let _span = tracing::info_span!("block_convert", number = ?builder.block.number).entered();
     let block = &builder.block;
     let code_db = &builder.code_db;
+    // Every transaction is delimited by its virtual steps, which the EVM
+    // circuit relies on for the transitions between transactions.
+    for tx in block.txs() {
+        let steps = tx.steps();
+        if !matches!(steps.first(), Some(step) if step.exec_state == ExecState::BeginTx)
+            || !matches!(steps.last(), Some(step) if step.exec_state == ExecState::EndTx)
+        {
+            return Err(Error::InternalError(
+                "transaction steps must start with BeginTx and end with EndTx",
+            ));
+        }
+    }
     let rws = RwMap::from(&block.container);
     rws.check_value();
     let mut block = Block {
         // randomness: F::from(0x100), // Special value to reveal elements after RLC
         randomness: F::from(0xcafeu64),
         context: block.into(),
+        chain_config: block.chain_config.clone(),
+        memory_op_mode: block.memory_op_mode,
+        l1_data_fee: block.fee_policy.l1_data_fee().cloned(),
         rws,
         txs: block.txs().to_vec(),
         end_block_not_last: block.block_steps.end_block_not_last.clone(),
@@ -264,5 +513,34 @@ pub fn block_convert<F: Field>(
     );
     // PI Circuit
     block.keccak_inputs.extend_from_slice(&[rpi_bytes]);
+    tracing::debug!(
+        txs = block.txs.len(),
+        steps = block.txs.iter().map(|tx| tx.steps().len()).sum::<usize>(),
+        keccak_inputs = block.keccak_inputs.len(),
+        "block converted"
+    );
     Ok(block)
 }
+
+/// (De)serialization of a field element as its little endian representation,
+/// hex encoded.
+mod field {
+    use eth_types::{Field, U256};
+    use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+
+    pub(crate) fn serialize<F: Field, S: Serializer>(
+        value: &F,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        U256::from_little_endian(&value.to_repr()).serialize(serializer)
+    }
+
+    pub(crate) fn deserialize<'de, F: Field, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<F, D::Error> {
+        let mut repr = [0u8; 32];
+        U256::deserialize(deserializer)?.to_little_endian(&mut repr);
+        Option::from(F::from_repr(repr))
+            .ok_or_else(|| de::Error::custom("value is not a canonical field element"))
+    }
+}
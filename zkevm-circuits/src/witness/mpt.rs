@@ -58,6 +58,11 @@ impl MptUpdates {
         self.old_root
     }
 
+    /// Number of updated account fields and storage slots.
+    pub(crate) fn len(&self) -> usize {
+        self.updates.len()
+    }
+
     pub(crate) fn get(&self, row: &Rw) -> Option<MptUpdate> {
         key(row).map(|key| *self.updates.get(&key).expect("missing key in mpt updates"))
     }
@@ -8,6 +8,8 @@ use bus_mapping::{
 use eth_types::{Address, Field, ToAddress, ToScalar, Word, U256};
 use halo2_proofs::circuit::Value;
 use itertools::Itertools;
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
 
 use crate::{
     table::{AccountFieldTag, CallContextFieldTag, TxLogFieldTag, TxReceiptFieldTag},
@@ -17,7 +19,7 @@ use crate::{
 use super::MptUpdates;
 
 /// Rw constainer for a witness block
-#[derive(Debug, Default, Clone)]
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
 pub struct RwMap(pub HashMap<Target, Vec<Rw>>);
 
 impl std::ops::Index<(Target, usize)> for RwMap {
@@ -135,10 +137,11 @@ impl RwMap {
         let padding = (1..=padding_length).map(|rw_counter| Rw::Start { rw_counter });
         (padding.chain(rows.into_iter()).collect(), padding_length)
     }
-    /// Build Rws for assignment
+    /// Build Rws for assignment, sorted by tag, id, address, field tag,
+    /// storage key and rw counter.  The rows of each target are sorted in
+    /// parallel with their keys computed once, and then merged.
     pub fn table_assignments(&self) -> Vec<Rw> {
-        let mut rows: Vec<Rw> = self.0.values().flatten().cloned().collect();
-        rows.sort_by_key(|row| {
+        let key = |row: &Rw| {
             (
                 row.tag() as u64,
                 row.id().unwrap_or_default(),
@@ -147,8 +150,19 @@ impl RwMap {
                 row.storage_key().unwrap_or_default(),
                 row.rw_counter(),
             )
-        });
-        rows
+        };
+        self.0
+            .par_iter()
+            .map(|(_, rows)| {
+                let mut rows: Vec<_> = rows.iter().map(|row| (key(row), *row)).collect();
+                rows.sort_unstable_by(|a, b| a.0.cmp(&b.0));
+                rows
+            })
+            .collect::<Vec<_>>()
+            .into_iter()
+            .kmerge_by(|a, b| a.0 < b.0)
+            .map(|(_, row)| row)
+            .collect()
     }
 }
 
@@ -158,7 +172,7 @@ impl RwMap {
 )]
 /// Read-write records in execution. Rws are used for connecting evm circuit and
 /// state circuits.
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
 pub enum Rw {
     /// Start
     Start { rw_counter: usize },
@@ -233,6 +247,20 @@ pub enum Rw {
         memory_address: u64,
         byte: u8,
     },
+    /// MemoryWord
+    MemoryWord {
+        rw_counter: usize,
+        is_write: bool,
+        call_id: usize,
+        /// 32-byte aligned address of the word
+        memory_address: u64,
+        /// value of the whole word after the access
+        value: Word,
+        /// value of the whole word before the access
+        value_prev: Word,
+        /// offset of the accessed bytes within the word
+        shift: u8,
+    },
     /// TxLog
     TxLog {
         rw_counter: usize,
@@ -439,6 +467,7 @@ impl Rw {
         match self {
             Self::Start { rw_counter }
             | Self::Memory { rw_counter, .. }
+            | Self::MemoryWord { rw_counter, .. }
             | Self::Stack { rw_counter, .. }
             | Self::AccountStorage { rw_counter, .. }
             | Self::TxAccessListAccount { rw_counter, .. }
@@ -455,6 +484,7 @@ impl Rw {
         match self {
             Self::Start { .. } => false,
             Self::Memory { is_write, .. }
+            | Self::MemoryWord { is_write, .. }
             | Self::Stack { is_write, .. }
             | Self::AccountStorage { is_write, .. }
             | Self::TxAccessListAccount { is_write, .. }
@@ -471,6 +501,7 @@ impl Rw {
         match self {
             Self::Start { .. } => Target::Start,
             Self::Memory { .. } => Target::Memory,
+            Self::MemoryWord { .. } => Target::MemoryWord,
             Self::Stack { .. } => Target::Stack,
             Self::AccountStorage { .. } => Target::Storage,
             Self::TxAccessListAccount { .. } => Target::TxAccessListAccount,
@@ -493,7 +524,8 @@ impl Rw {
             | Self::TxReceipt { tx_id, .. } => Some(*tx_id),
             Self::CallContext { call_id, .. }
             | Self::Stack { call_id, .. }
-            | Self::Memory { call_id, .. } => Some(*call_id),
+            | Self::Memory { call_id, .. }
+            | Self::MemoryWord { call_id, .. } => Some(*call_id),
             Self::Start { .. } | Self::Account { .. } => None,
         }
     }
@@ -512,7 +544,9 @@ impl Rw {
             | Self::AccountStorage {
                 account_address, ..
             } => Some(*account_address),
-            Self::Memory { memory_address, .. } => Some(U256::from(*memory_address).to_address()),
+            Self::Memory { memory_address, .. } | Self::MemoryWord { memory_address, .. } => {
+                Some(U256::from(*memory_address).to_address())
+            }
             Self::Stack { stack_pointer, .. } => {
                 Some(U256::from(*stack_pointer as u64).to_address())
             }
@@ -539,6 +573,7 @@ impl Rw {
             Self::TxReceipt { field_tag, .. } => Some(*field_tag as u64),
             Self::Start { .. }
             | Self::Memory { .. }
+            | Self::MemoryWord { .. }
             | Self::Stack { .. }
             | Self::AccountStorage { .. }
             | Self::TxAccessListAccount { .. }
@@ -556,6 +591,7 @@ impl Rw {
             | Self::CallContext { .. }
             | Self::Stack { .. }
             | Self::Memory { .. }
+            | Self::MemoryWord { .. }
             | Self::TxRefund { .. }
             | Self::Account { .. }
             | Self::TxAccessListAccount { .. }
@@ -571,6 +607,7 @@ impl Rw {
             | Self::Account { value, .. }
             | Self::AccountStorage { value, .. }
             | Self::Stack { value, .. }
+            | Self::MemoryWord { value, .. }
             | Self::TxLog { value, .. } => *value,
             Self::TxAccessListAccount { is_warm, .. }
             | Self::TxAccessListAccountStorage { is_warm, .. } => U256::from(*is_warm as u64),
@@ -589,6 +626,7 @@ impl Rw {
                 Some(U256::from(*is_warm_prev as u64))
             }
             Self::TxRefund { value_prev, .. } => Some(U256::from(*value_prev)),
+            Self::MemoryWord { value_prev, .. } => Some(*value_prev),
             Self::Start { .. }
             | Self::Stack { .. }
             | Self::Memory { .. }
@@ -781,6 +819,22 @@ impl From<&operation::OperationContainer> for RwMap {
                 })
                 .collect(),
         );
+        rws.insert(
+            Target::MemoryWord,
+            container
+                .memory_word
+                .iter()
+                .map(|op| Rw::MemoryWord {
+                    rw_counter: op.rwc().into(),
+                    is_write: op.rw().is_write(),
+                    call_id: op.op().call_id(),
+                    memory_address: op.op().address().0 as u64,
+                    value: *op.op().value(),
+                    value_prev: *op.op().value_prev(),
+                    shift: op.op().shift(),
+                })
+                .collect(),
+        );
         rws.insert(
             Target::TxLog,
             container
@@ -795,6 +849,8 @@ impl From<&operation::OperationContainer> for RwMap {
                         TxLogField::Address => TxLogFieldTag::Address,
                         TxLogField::Topic => TxLogFieldTag::Topic,
                         TxLogField::Data => TxLogFieldTag::Data,
+                        TxLogField::TopicLength => TxLogFieldTag::TopicLength,
+                        TxLogField::DataLength => TxLogFieldTag::DataLength,
                     },
                     index: op.op().index,
                     value: op.op().value,
@@ -823,3 +879,42 @@ impl From<&operation::OperationContainer> for RwMap {
         Self(rws)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn table_assignments_sorted_across_targets() {
+        let stack = |rw_counter, call_id, stack_pointer| Rw::Stack {
+            rw_counter,
+            is_write: true,
+            call_id,
+            stack_pointer,
+            value: Word::zero(),
+        };
+        let rws = RwMap(HashMap::from([
+            (Target::Start, vec![Rw::Start { rw_counter: 1 }]),
+            (
+                Target::Stack,
+                vec![stack(2, 2, 1023), stack(3, 1, 1023), stack(4, 1, 1022)],
+            ),
+            (
+                Target::TxRefund,
+                vec![Rw::TxRefund {
+                    rw_counter: 5,
+                    is_write: false,
+                    tx_id: 1,
+                    value: 0,
+                    value_prev: 0,
+                }],
+            ),
+        ]));
+        let rw_counters: Vec<_> = rws
+            .table_assignments()
+            .iter()
+            .map(|row| row.rw_counter())
+            .collect();
+        assert_eq!(rw_counters, vec![1, 4, 3, 2, 5]);
+    }
+}
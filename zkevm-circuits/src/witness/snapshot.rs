@@ -0,0 +1,157 @@
+//! Snapshots of witness blocks, to reproduce the assignments of the circuits
+//! without the traces the block was built from.
+//!
+//! A snapshot contains everything the circuits are assigned from: the steps
+//! and calls of the transactions, the rws, the bytecodes, the copy and exp
+//! events, etc.  The MPT updates aren't stored as they're derived from the rws.
+
+use super::Block;
+use eth_types::Field;
+use flate2::{read::GzDecoder, write::GzEncoder, Compression};
+#[cfg(not(target_arch = "wasm32"))]
+use std::{
+    fs::File,
+    io::{BufReader, BufWriter},
+};
+use std::{
+    io::{self, Read, Write},
+    path::Path,
+};
+
+/// Encoding of a block snapshot
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SnapshotFormat {
+    /// Plain JSON, to inspect or edit the block by hand
+    Json,
+    /// Gzip compressed JSON
+    #[default]
+    Binary,
+}
+
+impl SnapshotFormat {
+    /// Format of the snapshot file at `path`: JSON for the `.json` extension,
+    /// binary otherwise.
+    pub fn from_path(path: &Path) -> Self {
+        match path.extension() {
+            Some(extension) if extension == "json" => Self::Json,
+            _ => Self::Binary,
+        }
+    }
+}
+
+impl<F: Field> Block<F> {
+    /// Write a snapshot of the block to `writer`.
+    pub fn write_snapshot<W: Write>(&self, writer: W, format: SnapshotFormat) -> io::Result<()> {
+        match format {
+            SnapshotFormat::Json => serde_json::to_writer(writer, self)?,
+            SnapshotFormat::Binary => {
+                let mut encoder = GzEncoder::new(writer, Compression::default());
+                serde_json::to_writer(&mut encoder, self)?;
+                encoder.finish()?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Read a block from a snapshot written by [`Block::write_snapshot`].
+    pub fn read_snapshot<R: Read>(reader: R, format: SnapshotFormat) -> io::Result<Self> {
+        Ok(match format {
+            SnapshotFormat::Json => serde_json::from_reader(reader)?,
+            SnapshotFormat::Binary => serde_json::from_reader(GzDecoder::new(reader))?,
+        })
+    }
+}
+
+/// Snapshot files, not available in wasm32 which has no filesystem.
+#[cfg(not(target_arch = "wasm32"))]
+impl<F: Field> Block<F> {
+    /// Save a snapshot of the block to the file at `path`, in the format
+    /// given by its extension (see [`SnapshotFormat::from_path`]).
+    pub fn save_snapshot(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let path = path.as_ref();
+        let mut writer = BufWriter::new(File::create(path)?);
+        self.write_snapshot(&mut writer, SnapshotFormat::from_path(path))?;
+        writer.flush()
+    }
+
+    /// Load a block from the snapshot file at `path`.
+    pub fn load_snapshot(path: impl AsRef<Path>) -> io::Result<Self> {
+        let path = path.as_ref();
+        Self::read_snapshot(
+            BufReader::new(File::open(path)?),
+            SnapshotFormat::from_path(path),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        bytecode_circuit::circuit::BytecodeCircuit, test_util::replay, witness::block_convert,
+    };
+    use bus_mapping::mock::BlockData;
+    use eth_types::{bytecode, geth_types::GethData};
+    use halo2_proofs::halo2curves::bn256::Fr;
+    use mock::TestContext;
+
+    fn block() -> Block<Fr> {
+        let bytecode = bytecode! {
+            PUSH1(0x20)
+            PUSH1(0x00)
+            MSTORE
+            PUSH1(0x20)
+            PUSH1(0x00)
+            RETURN
+        };
+        let block: GethData = TestContext::<2, 1>::simple_ctx_with_bytecode(bytecode)
+            .unwrap()
+            .into();
+        let builder = BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+        let builder = builder
+            .handle_block(&block.eth_block, &block.geth_traces)
+            .unwrap();
+        block_convert::<Fr>(&builder).unwrap()
+    }
+
+    fn assert_same_block(a: &Block<Fr>, b: &Block<Fr>) {
+        assert_eq!(a.randomness, b.randomness);
+        assert_eq!(format!("{:?}", a.txs), format!("{:?}", b.txs));
+        assert_eq!(
+            format!("{:?}", a.rws.table_assignments()),
+            format!("{:?}", b.rws.table_assignments())
+        );
+        assert_eq!(
+            a.bytecodes.iter().collect::<Vec<_>>(),
+            b.bytecodes.iter().collect::<Vec<_>>()
+        );
+        assert_eq!(
+            format!("{:?}", a.copy_events),
+            format!("{:?}", b.copy_events)
+        );
+        assert_eq!(format!("{:?}", a.context), format!("{:?}", b.context));
+        assert_eq!(a.chain_config, b.chain_config);
+        assert_eq!(a.l1_data_fee, b.l1_data_fee);
+        assert_eq!(a.keccak_inputs, b.keccak_inputs);
+        assert_eq!(a.eth_block, b.eth_block);
+    }
+
+    #[test]
+    fn snapshot_round_trip() {
+        let block = block();
+        for format in [SnapshotFormat::Json, SnapshotFormat::Binary] {
+            let mut bytes = Vec::new();
+            block.write_snapshot(&mut bytes, format).unwrap();
+            let read = Block::<Fr>::read_snapshot(&bytes[..], format).unwrap();
+            assert_same_block(&block, &read);
+        }
+    }
+
+    #[test]
+    fn snapshot_replay() {
+        let path = std::env::temp_dir().join("zkevm_circuits_snapshot_replay.json.gz");
+        block().save_snapshot(&path).unwrap();
+        assert_eq!(replay::<BytecodeCircuit<Fr>>(&path), Ok(()));
+        std::fs::remove_file(path).unwrap();
+    }
+}